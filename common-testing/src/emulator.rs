@@ -97,6 +97,12 @@ pub fn parse_output<T: DeserializeOwned>(
 
 /// Create a temporary directory with a new Cargo project that has nexus_rt as a local dependency.
 pub fn setup_guest_project(runtime_path: &PathBuf) -> TempDir {
+    setup_guest_project_with_features(runtime_path, &[])
+}
+
+/// Create a temporary directory with a new Cargo project that has nexus_rt as a local dependency, with the given
+/// features of nexus_rt enabled.
+pub fn setup_guest_project_with_features(runtime_path: &PathBuf, features: &[&str]) -> TempDir {
     // Create a temporary directory.
     let tmp_dir = tempdir().expect("Failed to create temporary directory");
     let tmp_dir_path = tmp_dir.path().join("integration");
@@ -113,14 +119,17 @@ pub fn setup_guest_project(runtime_path: &PathBuf) -> TempDir {
 
     // Add the nexus_rt dependency to the `Cargo.toml` file.
     let runtime_dir = std::env::current_dir().unwrap().join(runtime_path);
-    output = Command::new("cargo")
+    let mut command = Command::new("cargo");
+    command
         .current_dir(tmp_dir_str)
         .arg("add")
         .arg("nexus-rt")
         .arg("--path")
-        .arg(runtime_dir)
-        .output()
-        .expect("Failed to add nexus_rt dependency");
+        .arg(runtime_dir);
+    if !features.is_empty() {
+        command.arg("--features").arg(features.join(","));
+    }
+    output = command.output().expect("Failed to add nexus_rt dependency");
 
     if !output.status.success() {
         eprintln!("Error: {}", String::from_utf8_lossy(&output.stderr));
@@ -150,7 +159,7 @@ pub fn compile_guest_project(
     linker_path: &PathBuf,
     compile_flags: &str,
 ) -> Vec<u8> {
-    let target = "riscv32i-unknown-none-elf";
+    let target = "riscv32im-unknown-none-elf";
 
     let linker_script = std::env::current_dir().unwrap().join(linker_path);
 
@@ -188,10 +197,23 @@ pub fn compile_multi(
     test_name: &str,
     compile_flags: &[&str],
     home_path_relative: &str,
+) -> Vec<ElfFile> {
+    compile_multi_with_features(test_name, compile_flags, &[], home_path_relative)
+}
+
+/// Same as [`compile_multi`], with the given features of nexus_rt enabled.
+pub fn compile_multi_with_features(
+    test_name: &str,
+    compile_flags: &[&str],
+    features: &[&str],
+    home_path_relative: &str,
 ) -> Vec<ElfFile> {
     let mut elves = Vec::new();
     // Set up the temporary directories for intermediate project setup.
-    let tmp_dir = &setup_guest_project(&PathBuf::from(home_path_relative).join("runtime"));
+    let tmp_dir = &setup_guest_project_with_features(
+        &PathBuf::from(home_path_relative).join("runtime"),
+        features,
+    );
     let tmp_project_path = tmp_dir.path().join("integration");
 
     for flag_set in compile_flags {
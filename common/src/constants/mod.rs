@@ -12,4 +12,12 @@ pub const ELF_TEXT_START: u32 = PUBLIC_OUTPUT_ADDRESS_LOCATION + WORD_SIZE as u3
 pub const PRECOMPILE_SYMBOL_PREFIX: &str = "PRECOMPILE_";
 
 // TODO: handle built-in custom instructions.
+// The two lowest bits are `0b10`, the start of a compressed instruction, so these can't be used with RV32C.
 pub const KECCAKF_OPCODE: u8 = 0x5A;
+// Other built-in custom instructions share the opcode of keccakf and are told apart by fn3.
+pub const KECCAKF_FN3: u8 = 0b000;
+pub const SHA256_COMPRESS_FN3: u8 = 0b001;
+pub const MUL_ADD_MOD_FN3: u8 = 0b010;
+pub const POSEIDON2_FN3: u8 = 0b011;
+pub const MEMCPY_FN3: u8 = 0b100;
+pub const MEMSET_FN3: u8 = 0b101;
@@ -145,6 +145,7 @@ mod tests {
             op_a: 2.into(),
             op_b: 3.into(),
             op_c: 1,
+            ..Default::default()
         };
         let encoded_r = r_instruction.encode();
         assert_eq!(encoded_r, 0x118133);
@@ -156,6 +157,7 @@ mod tests {
             op_a: 2.into(),
             op_b: 3.into(),
             op_c: 10,
+            ..Default::default()
         };
         let encoded_i = i_instruction.encode();
         assert_eq!(encoded_i, 0xA18113);
@@ -167,6 +169,7 @@ mod tests {
             op_a: 2.into(),
             op_b: 3.into(),
             op_c: 10,
+            ..Default::default()
         };
         let encoded_s = s_instruction.encode();
         assert_eq!(encoded_s, 0x312523);
@@ -178,6 +181,7 @@ mod tests {
             op_a: 2.into(),
             op_b: 3.into(),
             op_c: 10,
+            ..Default::default()
         };
         let encoded_b = b_instruction.encode();
         assert_eq!(encoded_b, 0x310563);
@@ -189,6 +193,7 @@ mod tests {
             op_a: 2.into(),
             op_b: 0.into(),
             op_c: 10,
+            ..Default::default()
         };
         let encoded_u = u_instruction.encode();
         assert_eq!(encoded_u, 0xA137);
@@ -200,6 +205,7 @@ mod tests {
             op_a: 2.into(),
             op_b: 0.into(),
             op_c: 10,
+            ..Default::default()
         };
         let encoded_j = j_instruction.encode();
         assert_eq!(encoded_j, 0xA0016F);
@@ -211,6 +217,7 @@ mod tests {
             op_a: 2.into(),
             op_b: 3.into(),
             op_c: 10,
+            ..Default::default()
         };
         let encoded_i_shamt = i_shamt_instruction.encode();
         assert_eq!(encoded_i_shamt, 0x40A1D113);
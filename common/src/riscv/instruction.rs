@@ -32,6 +32,8 @@ pub struct Instruction {
     // Op_c can be either 12-bit immediate, 20-bit immediate, or a register index 5 bits wide.
     pub op_c: u32,
     pub ins_type: InstructionType,
+    /// Whether the instruction was decoded from a 16-bit RV32C encoding, the other fields then hold its expansion.
+    pub compressed: bool,
 }
 
 impl Instruction {
@@ -52,6 +54,7 @@ impl Instruction {
             op_b,
             op_c,
             ins_type,
+            compressed: false,
         }
     }
 
@@ -86,6 +89,15 @@ impl Instruction {
         )
     }
 
+    /// Returns the size of the encoding of the instruction in bytes, 2 for compressed instructions and 4 otherwise.
+    pub fn size(&self) -> u32 {
+        if self.compressed {
+            2
+        } else {
+            4
+        }
+    }
+
     /// Returns true if the instruction is a branch or jump instruction.
     pub fn is_branch_or_jump_instruction(&self) -> bool {
         if let Some(opcode) = self.opcode.builtin() {
@@ -7,18 +7,26 @@ pub mod nvm {
         elf::{ElfError, ElfFile},
         emulator::View,
         error::VMError,
-        trace::{bb_trace, k_trace, BBTrace, UniformTrace},
+        trace::{
+            bb_trace, k_trace, k_trace_streaming, k_trace_streaming_with_aux_input,
+            k_trace_with_aux_input, BBTrace, StreamingTrace, TraceSource, UniformTrace,
+        },
     };
     pub mod internals {
         pub use nexus_vm::emulator::{
-            convert_instruction, elf_into_program_info, io_entries_into_vec, map_into_io_entries,
-            slice_into_io_entries, LinearEmulator, LinearMemoryLayout, MemoryInitializationEntry,
-            ProgramInfo, PublicOutputEntry,
+            convert_instruction, convert_instructions, elf_into_program_info, io_entries_into_vec,
+            map_into_io_entries, slice_into_io_entries, InternalView, LinearEmulator,
+            LinearMemoryLayout, MemoryInitializationEntry, ProgramInfo, PublicOutputEntry,
         };
     }
 }
 
 /// Stwo proving
 pub mod stwo {
-    pub use nexus_vm_prover::{prove, verify, Proof, ProvingError, VerificationError};
+    pub use nexus_vm_prover::{
+        estimate, prove, prove_segments, prove_with_config, prove_with_extensions, verify,
+        verify_segments, verify_with_extensions, Blake2sMerkleChannel, BoundaryState, CostEstimate,
+        CostModel, Extension, InstructionFamily, MerkleChannel, Poseidon252MerkleChannel, Proof,
+        ProverConfig, ProvingError, SegmentProof, VerificationError, DEFAULT_MIN_SECURITY_BITS,
+    };
 }
@@ -0,0 +1,28 @@
+#![cfg_attr(target_arch = "riscv32", no_std, no_main)]
+
+use nexus_rt::{
+    bigint::{add_mod, mul_mod, U256},
+    println,
+};
+
+// secp256k1 base field modulus
+const P: U256 = [
+    0xfffffc2f, 0xfffffffe, 0xffffffff, 0xffffffff, 0xffffffff, 0xffffffff, 0xffffffff, 0xffffffff,
+];
+
+#[nexus_rt::main]
+fn main() {
+    // x^65537 + x mod p
+    let x: U256 = [
+        0x16f81798, 0x59f2815b, 0x2dce28d9, 0x029bfcdb, 0xce870b07, 0x55a06295, 0xf9dcbbac,
+        0x79be667e,
+    ];
+    let mut y = x;
+    for _ in 0..16 {
+        y = mul_mod(&y, &y, &P);
+    }
+    y = mul_mod(&y, &x, &P);
+    y = add_mod(&y, &x, &P);
+
+    println!("{:x?}", y);
+}
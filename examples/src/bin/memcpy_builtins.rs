@@ -0,0 +1,52 @@
+#![cfg_attr(target_arch = "riscv32", no_std, no_main)]
+
+// Relies on the `memcpy` and `memset` calls emitted by the compiler, build with the `memcpy-precompile` feature to
+// have them executed by the custom instructions.
+
+use core::hint::black_box;
+use nexus_rt::println;
+
+#[derive(Clone, Copy)]
+struct Record {
+    id: u32,
+    payload: [u32; 128],
+}
+
+#[nexus_rt::main]
+fn main() {
+    // Large zeroed arrays are initialized with `memset`.
+    let mut records = black_box(
+        [Record {
+            id: 0,
+            payload: [0; 128],
+        }; 4],
+    );
+    for (i, record) in records.iter_mut().enumerate() {
+        record.id = i as u32;
+        for (j, word) in record.payload.iter_mut().enumerate() {
+            *word = (i * j) as u32;
+        }
+    }
+
+    // Large structs are copied with `memcpy`.
+    records[3] = black_box(records[1]);
+    assert_eq!(records[3].id, 1);
+    for (j, &word) in records[3].payload.iter().enumerate() {
+        assert_eq!(word, j as u32);
+    }
+
+    // Regions of a length only known at run time always go through `memset` and `memcpy`.
+    let len = black_box(300);
+    let mut bytes = [0xffu8; 512];
+    bytes[..len].fill(0);
+    let mut copy = [0x11u8; 512];
+    copy[..len].copy_from_slice(&bytes[..len]);
+    assert!(copy[..len].iter().all(|&byte| byte == 0));
+    assert!(copy[len..].iter().all(|&byte| byte == 0x11));
+
+    let sum: u32 = records
+        .iter()
+        .flat_map(|record| record.payload.iter())
+        .sum();
+    println!("{}", sum);
+}
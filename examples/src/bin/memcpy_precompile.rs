@@ -0,0 +1,19 @@
+#![cfg_attr(target_arch = "riscv32", no_std, no_main)]
+
+use nexus_rt::{mem, println};
+
+#[nexus_rt::main]
+fn main() {
+    let mut src = [0u8; 1500];
+    mem::fill(&mut src, 7);
+    for (i, byte) in src.iter_mut().enumerate().step_by(3) {
+        *byte = i as u8;
+    }
+
+    let mut dst = [0u8; 1500];
+    mem::copy(&mut dst[5..], &src[..1495]);
+    assert_eq!(&dst[5..], &src[..1495]);
+
+    let sum: u32 = dst.iter().map(|&byte| u32::from(byte)).sum();
+    println!("{}", sum);
+}
@@ -0,0 +1,14 @@
+#![cfg_attr(target_arch = "riscv32", no_std, no_main)]
+
+use nexus_rt::{poseidon2, println};
+
+#[nexus_rt::main]
+fn main() {
+    // Merkle root of four leaves.
+    let leaves: [[u32; 8]; 4] = core::array::from_fn(|i| poseidon2::hash(&[i as u32]));
+    let left = poseidon2::compress(&leaves[0], &leaves[1]);
+    let right = poseidon2::compress(&leaves[2], &leaves[3]);
+    let root = poseidon2::compress(&left, &right);
+
+    println!("{:?}", root);
+}
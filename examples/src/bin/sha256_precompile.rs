@@ -0,0 +1,13 @@
+#![cfg_attr(target_arch = "riscv32", no_std, no_main)]
+
+use nexus_rt::{println, sha256::Sha256};
+
+#[nexus_rt::main]
+fn main() {
+    let mut sha256 = Sha256::new();
+    sha256.update(b"Hello, World!");
+
+    let output = sha256.finalize();
+
+    println!("{:?}", output);
+}
@@ -29,6 +29,9 @@ impl Display for PrecompileMetadata {
 }
 
 /// Generic placeholder trait for circuits used by the prover but provided by a precompile author.
+///
+/// The circuit itself is implemented against `nexus_vm_prover::extensions::ExternalExtension` in the host crate of
+/// the precompile, since this crate is a dependency of the VM and cannot depend on the prover.
 pub trait PrecompileCircuit {}
 
 /// A precompile's implementation
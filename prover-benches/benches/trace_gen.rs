@@ -8,7 +8,7 @@ use nexus_vm::{
 use nexus_vm_prover::{
     components::AllLookupElements,
     extensions::ExtensionsConfig,
-    machine::BaseComponent,
+    machine::{BaseComponent, Machine},
     trace::{
         program::iter_program_steps,
         program_trace::{ProgramTraceRef, ProgramTracesBuilder},
@@ -69,6 +69,7 @@ fn bench_trace_gen(c: &mut Criterion) {
             init_memory: view.get_initial_memory(),
             exit_code: view.get_exit_code(),
             public_output: view.get_public_output(),
+            segment: None,
         };
         let mut program_traces = ProgramTracesBuilder::new(log_size, program_trace_ref);
 
@@ -84,6 +85,26 @@ fn bench_trace_gen(c: &mut Criterion) {
             })
         });
 
+        // Rows are filled twice when split into chunks, once to find out the state each chunk starts with.
+        let num_threads = std::thread::available_parallelism().map_or(1, usize::from);
+        for (name, chunk_size) in [
+            ("MainTraceSingleChunk", 1 << log_size),
+            ("MainTraceInChunks", (1 << log_size) / num_threads),
+        ] {
+            group.bench_function(name, |b| {
+                b.iter(|| {
+                    let mut side_note = SideNote::new(&program_traces, &view);
+                    black_box(Machine::<BaseComponent>::fill_main_trace_in_chunks(
+                        black_box(&execution_trace),
+                        black_box(log_size),
+                        &mut side_note,
+                        &ext_config,
+                        black_box(chunk_size.max(1)),
+                    ))
+                })
+            });
+        }
+
         let mut prover_traces = TracesBuilder::new(log_size);
         fill_main_trace(
             &mut prover_traces,
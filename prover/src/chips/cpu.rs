@@ -19,7 +19,6 @@ use crate::{
     virtual_column::{self, VirtualColumn},
 };
 
-use nexus_common::constants::KECCAKF_OPCODE;
 use nexus_vm::{
     riscv::{
         BuiltinOpcode,
@@ -88,13 +87,15 @@ impl MachineChip for CpuChip {
         // Sanity check: preprocessed column `Clk` contains `row_idx + 1`
         assert!(step.timestamp as usize == row_idx + 1);
         traces.fill_columns(row_idx, pc, Pc);
+        let size = step.instruction.size();
+        traces.fill_columns(row_idx, step.instruction.compressed, IsCompressed);
         // Fill PcCarry
         // PcCarry isn't used in jump or branch instructions, but we fill it anyway.
-        let (_, pc_carry) = add_with_carries(pc.to_le_bytes(), 4u32.to_le_bytes());
+        let (_, pc_carry) = add_with_carries(pc.to_le_bytes(), size.to_le_bytes());
         // PcCarry only needs two flags for carries for 16-bit chunks because the constraints treat the addition 16 bits at a time.
         traces.fill_columns(row_idx, [pc_carry[1], pc_carry[3]], PcCarry);
-        traces.fill_columns(row_idx, pc.wrapping_add(WORD_SIZE as u32), PcNext); // default expectation of the next Pc; might be overwritten by Branch or Jump chips
-                                                                                 // Fill InstructionWord to the main trace for the program memory checking
+        traces.fill_columns(row_idx, pc.wrapping_add(size), PcNext); // default expectation of the next Pc; might be overwritten by Branch or Jump chips
+                                                                     // Fill InstructionWord to the main trace for the program memory checking
         traces.fill_columns(row_idx, step.raw_instruction, InstrVal);
 
         // Add opcode to the main trace
@@ -185,6 +186,30 @@ impl MachineChip for CpuChip {
             Some(BuiltinOpcode::SRA) | Some(BuiltinOpcode::SRAI) => {
                 traces.fill_columns(row_idx, true, IsSra);
             }
+            Some(BuiltinOpcode::MUL) => {
+                traces.fill_columns(row_idx, true, IsMul);
+            }
+            Some(BuiltinOpcode::MULH) => {
+                traces.fill_columns(row_idx, true, IsMulh);
+            }
+            Some(BuiltinOpcode::MULHSU) => {
+                traces.fill_columns(row_idx, true, IsMulhsu);
+            }
+            Some(BuiltinOpcode::MULHU) => {
+                traces.fill_columns(row_idx, true, IsMulhu);
+            }
+            Some(BuiltinOpcode::DIV) => {
+                traces.fill_columns(row_idx, true, IsDiv);
+            }
+            Some(BuiltinOpcode::DIVU) => {
+                traces.fill_columns(row_idx, true, IsDivu);
+            }
+            Some(BuiltinOpcode::REM) => {
+                traces.fill_columns(row_idx, true, IsRem);
+            }
+            Some(BuiltinOpcode::REMU) => {
+                traces.fill_columns(row_idx, true, IsRemu);
+            }
             Some(BuiltinOpcode::ECALL) => {
                 traces.fill_columns(row_idx, true, IsEcall);
             }
@@ -192,7 +217,8 @@ impl MachineChip for CpuChip {
                 traces.fill_columns(row_idx, true, IsEbreak);
             }
             _ => {
-                if step.instruction.opcode.raw != KECCAKF_OPCODE {
+                // Custom instructions are handled by `CustomInstructionChip`.
+                if step.instruction.opcode.is_builtin() {
                     panic!("Unsupported opcode: {:?}", step.instruction.opcode);
                 }
                 return;
@@ -259,7 +285,7 @@ impl MachineChip for CpuChip {
             RType => {
                 // Reg1Accessed has been replaced with virtual column OpBFlag
                 traces.fill_columns(row_idx, vm_step.step.instruction.op_b as u8, Reg1Address);
-                // Reg2Accessed has been replaced with virtual column IsTypeR
+                // Reg2Accessed is now a virtual column
                 traces.fill_columns(row_idx, vm_step.step.instruction.op_c as u8, Reg2Address);
                 // Reg3Accessed is now a virtual column
                 traces.fill_columns(row_idx, vm_step.step.instruction.op_a as u8, Reg3Address);
@@ -344,6 +370,14 @@ impl MachineChip for CpuChip {
         let [is_sll] = trace_eval!(trace_eval, IsSll);
         let [is_srl] = trace_eval!(trace_eval, IsSrl);
         let [is_sra] = trace_eval!(trace_eval, IsSra);
+        let [is_mul] = trace_eval!(trace_eval, IsMul);
+        let [is_mulh] = trace_eval!(trace_eval, IsMulh);
+        let [is_mulhsu] = trace_eval!(trace_eval, IsMulhsu);
+        let [is_mulhu] = trace_eval!(trace_eval, IsMulhu);
+        let [is_div] = trace_eval!(trace_eval, IsDiv);
+        let [is_divu] = trace_eval!(trace_eval, IsDivu);
+        let [is_rem] = trace_eval!(trace_eval, IsRem);
+        let [is_remu] = trace_eval!(trace_eval, IsRemu);
         let [is_padding] = trace_eval!(trace_eval, IsPadding);
         let [is_sb] = trace_eval!(trace_eval, IsSb);
         let [is_sh] = trace_eval!(trace_eval, IsSh);
@@ -356,6 +390,11 @@ impl MachineChip for CpuChip {
         let [is_ecall] = trace_eval!(trace_eval, IsEcall);
         let [is_ebreak] = trace_eval!(trace_eval, IsEbreak);
         let [is_keccak] = trace_eval!(trace_eval, IsCustomKeccak);
+        let [is_sha256] = trace_eval!(trace_eval, IsCustomSha256);
+        let [is_bigint] = trace_eval!(trace_eval, IsCustomBigInt);
+        let [is_poseidon2] = trace_eval!(trace_eval, IsCustomPoseidon2);
+        let [is_memcpy] = trace_eval!(trace_eval, IsCustomMemcpy);
+        let [is_external] = trace_eval!(trace_eval, IsCustomExternal);
         eval.add_constraint(
             is_add.clone()
                 + is_sub.clone()
@@ -385,10 +424,23 @@ impl MachineChip for CpuChip {
                 + is_sll.clone()
                 + is_srl.clone()
                 + is_sra.clone()
+                + is_mul
+                + is_mulh
+                + is_mulhsu
+                + is_mulhu
+                + is_div
+                + is_divu
+                + is_rem
+                + is_remu
                 + is_ecall.clone()
                 + is_ebreak.clone()
                 + is_padding
                 + is_keccak
+                + is_sha256
+                + is_bigint
+                + is_poseidon2
+                + is_memcpy
+                + is_external
                 - E::F::one(),
         );
 
@@ -488,9 +540,10 @@ impl MachineChip for CpuChip {
             );
         }
 
-        // Increment PC by four
-        // (is_pc_incremented)・(pc_next_1 + pc_next_2·2^8 + pc_carry_1·2^16 - (pc_1 + pc_2·2^8) - 4) = 0
+        // Increment PC by the instruction size, which is four unless the instruction is compressed
+        // (is_pc_incremented)・(pc_next_1 + pc_next_2·2^8 + pc_carry_1·2^16 - (pc_1 + pc_2·2^8) - instr_size) = 0
         let [is_pc_incremented] = virtual_column::IsPcIncremented::eval(trace_eval);
+        let [instr_size] = virtual_column::InstrSize::eval(trace_eval);
         let pc_carry = trace_eval!(trace_eval, Column::PcCarry);
         let pc = trace_eval!(trace_eval, Column::Pc);
         eval.add_constraint(
@@ -499,7 +552,7 @@ impl MachineChip for CpuChip {
                     + pc_next[1].clone() * BaseField::from(1 << 8)
                     + pc_carry[0].clone() * BaseField::from(1 << 16)
                     - (pc[0].clone() + pc[1].clone() * BaseField::from(1 << 8))
-                    - BaseField::from(4).into()),
+                    - instr_size),
         );
         // (is_pc_incremented)・(pc_next_3 + pc_next_4·2^8 + pc_carry_2·2^16 - (pc_3 + pc_4·2^8) - pc_carry_1) = 0
         eval.add_constraint(
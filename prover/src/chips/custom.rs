@@ -3,32 +3,107 @@
 //! Extensions handle memory checking, but the corresponding flags and decoding still must be constrained within
 //! the main component.
 
-use nexus_common::constants::KECCAKF_OPCODE;
+use nexus_common::constants::{
+    KECCAKF_OPCODE, MEMCPY_FN3, MEMSET_FN3, MUL_ADD_MOD_FN3, POSEIDON2_FN3, SHA256_COMPRESS_FN3,
+    WORD_SIZE_HALVED,
+};
 use nexus_vm::{
+    cpu::instructions::custom::{
+        bigint::{mul_add_mod, BIGINT_WORDS, MUL_ADD_MOD_OPERANDS},
+        poseidon2::{poseidon2_permute, M31_MODULUS, POSEIDON2_WIDTH},
+        sha256::sha256_compress,
+    },
     memory::{MemAccessSize, MemoryRecord},
+    riscv::InstructionType,
     WORD_SIZE,
 };
-use num_traits::One;
-use stwo_prover::core::channel::Channel;
+use num_traits::{One, Zero};
+use stwo_prover::{
+    constraint_framework::{logup::LogupTraceGenerator, Relation, RelationEntry},
+    core::{
+        backend::simd::m31::{PackedBaseField, LOG_N_LANES},
+        channel::Channel,
+        fields::{m31::BaseField, FieldExpOps},
+    },
+};
 
 use crate::{
-    column::Column,
+    chips::instructions::load_store::AccessMode,
+    column::{
+        Column::{
+            self, InstrVal, OpA, OpA0, OpA1_4, OpB, OpB0, OpB0_3, OpB1_4, OpB4, OpC, OpC0, OpC0_3,
+            OpC11, OpC1_4, OpC4, OpC5_7, OpC8_10, Reg1Address, Reg1ValPrev, Reg2Address,
+            Reg2ValPrev, Reg3Address, Reg3ValPrev, ValueA, ValueAEffective, ValueB, ValueC,
+        },
+        PreprocessedColumn,
+    },
     components::AllLookupElements,
-    extensions::ExtensionsConfig,
+    extensions::{Extension, ExtensionsConfig, ExternalMemoryAccess, ExternalStep},
     trace::{
-        eval::{trace_eval, TraceEval},
+        eval::{preprocessed_trace_eval, trace_eval, TraceEval},
+        program_trace::ProgramTraces,
         sidenote::SideNote,
-        ProgramStep, TracesBuilder,
+        FinalizedTraces, PreprocessedTraces, ProgramStep, TracesBuilder,
     },
     traits::MachineChip,
+    virtual_column::{IsCustomTypeS, VirtualColumn},
 };
 
 /// The custom instruction chip works as an (optional) bridge between main component and custom extensions.
 /// It **doesn't** constrain the result of execution of custom instructions.
-pub type CustomInstructionChip = (KeccakChip,);
+pub type CustomInstructionChip = (
+    KeccakChip,
+    Sha256Chip,
+    BigIntChip,
+    Poseidon2Chip,
+    MemcpyChip,
+    CustomTypeSChip,
+    ExternalChip,
+);
 
 pub struct KeccakChip;
 
+pub struct Sha256Chip;
+
+pub struct BigIntChip;
+
+pub struct Poseidon2Chip;
+
+pub struct MemcpyChip;
+
+/// Constrains decoding and register reads of built-in custom instructions flagged by [`IsCustomTypeS`].
+///
+/// Such instructions are encoded as type S with opcode [`KECCAKF_OPCODE`], their `funct3` field selects the
+/// instruction and is compared against its constant by the chip of the instruction. Both registers are only read,
+/// the first one through the third register access as for stores.
+pub struct CustomTypeSChip;
+
+/// Sends custom instructions that aren't handled by built-in extensions to [`crate::extensions::external`].
+///
+/// Such instructions are encoded as R-type instructions with opcode [`EXTERNAL_OPCODE`], their `funct3` and `funct7`
+/// fields select the extension. The chip constrains decoding and register accesses, the result is constrained by
+/// the extension that consumes the instruction.
+pub struct ExternalChip;
+
+/// Opcode of custom instructions proven by external extensions.
+pub const EXTERNAL_OPCODE: u8 = 0b0001011;
+
+/// Number of values sent to external extensions for each custom instruction: fn3, fn7, clk_low, clk_high, a, b, c.
+pub const EXTERNAL_LOOKUP_TUPLE_SIZE: usize = 4 + 3 * WORD_SIZE;
+stwo_prover::relation!(
+    ExternalInstructionLookupElements,
+    EXTERNAL_LOOKUP_TUPLE_SIZE
+);
+
+/// Number of values sent to the memcpy extension for each `memcpy` or `memset` call: halves of the destination address,
+/// halves of the source address (of the fill value for `memset`), the number of bytes and whether the call copies
+/// memory.
+pub const MEMCPY_LOOKUP_TUPLE_SIZE: usize = 2 * WORD_SIZE_HALVED + 2;
+stwo_prover::relation!(MemcpyCallLookupElements, MEMCPY_LOOKUP_TUPLE_SIZE);
+
+// `is_copy = MEMSET_FN3 - fn3` is boolean as long as the two instructions only differ in the lowest bit of fn3.
+const _: () = assert!(MEMSET_FN3 == MEMCPY_FN3 + 1);
+
 pub mod keccak_lookups {
     const BITWISE_TABLE_LOOKUP_SIZE: usize = 3;
     stwo_prover::relation!(XorLookupElements, BITWISE_TABLE_LOOKUP_SIZE);
@@ -73,6 +148,42 @@ pub mod keccak_lookups {
     }
 }
 
+pub mod sha256_lookups {
+    pub use state::StateLookupElements;
+    mod state {
+        // same as keccak, the state tuple is large enough to be boxed
+        use stwo_prover::constraint_framework::{Relation, RelationEFTraitBound};
+
+        // compression id, round index, eight working variables and sixteen message words split into 16-bit halves
+        const STATE_LOOKUP_SIZE: usize = 2 + 24 * 2;
+        stwo_prover::relation!(RawSha256StateLookupElements, STATE_LOOKUP_SIZE);
+
+        #[derive(Debug, Clone)]
+        pub struct StateLookupElements(Box<RawSha256StateLookupElements>);
+        impl StateLookupElements {
+            pub fn draw(channel: &mut impl stwo_prover::core::channel::Channel) -> Self {
+                Self(Box::new(RawSha256StateLookupElements::draw(channel)))
+            }
+            pub fn dummy() -> Self {
+                Self(Box::new(RawSha256StateLookupElements::dummy()))
+            }
+        }
+        impl<F: Clone, EF: RelationEFTraitBound<F>> Relation<F, EF> for StateLookupElements {
+            fn combine(&self, values: &[F]) -> EF {
+                <RawSha256StateLookupElements as Relation<F, EF>>::combine(&self.0, values)
+            }
+
+            fn get_name(&self) -> &str {
+                <RawSha256StateLookupElements as Relation<F, EF>>::get_name(&self.0)
+            }
+
+            fn get_size(&self) -> usize {
+                <RawSha256StateLookupElements as Relation<F, EF>>::get_size(&self.0)
+            }
+        }
+    }
+}
+
 impl KeccakChip {
     fn keccak_input_from_mem_records(addr: u32, step: &ProgramStep) -> [u64; 25] {
         let mut input = [0u32; 50];
@@ -107,7 +218,7 @@ impl KeccakChip {
         let mut timestamps = Vec::with_capacity(WORD_SIZE * 25);
         for (i, byte) in output.into_iter().flat_map(u64::to_le_bytes).enumerate() {
             let addr = addr + i as u32;
-            let (ts, prev_val) = side_note.rw_mem_check.last_access.entry(addr).or_default();
+            let (ts, prev_val) = side_note.rw_mem_check.precompile_access(addr, true);
             timestamps.push(*ts);
 
             *ts += 1;
@@ -145,7 +256,8 @@ impl MachineChip for KeccakChip {
         else {
             return;
         };
-        if step.step.instruction.opcode.raw != KECCAKF_OPCODE {
+        let opcode = &step.step.instruction.opcode;
+        if Extension::of_opcode(opcode.raw, opcode.fn3.value()) != Some(Extension::Keccak) {
             return;
         } else {
             assert!(
@@ -189,3 +301,1012 @@ impl MachineChip for KeccakChip {
         // TODO: constrain instruction decoding and register access.
     }
 }
+
+impl Sha256Chip {
+    fn sha256_input_from_mem_records(
+        state_addr: u32,
+        block_addr: u32,
+        step: &ProgramStep,
+    ) -> ([u32; 8], [u32; 16]) {
+        let mut state = [0u32; 8];
+        let mut block = [0u32; 16];
+        for record in &step.step.memory_records {
+            let MemoryRecord::LoadRecord(memory_read, _) = *record else {
+                continue;
+            };
+            let (size, address, value) = memory_read;
+            assert_eq!(size, MemAccessSize::Word);
+            // The state and the block may overlap, a single record then fills both.
+            for (addr, words) in [
+                (state_addr, state.as_mut_slice()),
+                (block_addr, block.as_mut_slice()),
+            ] {
+                let idx = address.wrapping_sub(addr) as usize / WORD_SIZE;
+                if address >= addr && idx < words.len() {
+                    words[idx] = value;
+                }
+            }
+        }
+        (state, block)
+    }
+
+    /// Modifies side-note timestamps for accessed memory and returns previous values.
+    ///
+    /// Bytes of the block are accessed before bytes of the state, so that overlapping accesses follow each other.
+    fn update_state_timestamps(
+        state_addr: u32,
+        block_addr: u32,
+        input: &([u32; 8], [u32; 16]),
+        side_note: &mut SideNote,
+    ) -> Vec<u32> {
+        let (mut output, block) = *input;
+        sha256_compress(&mut output, &block);
+
+        let mut timestamps = Vec::with_capacity(WORD_SIZE * (16 + 8));
+        for i in 0..16 * WORD_SIZE {
+            let addr = block_addr + i as u32;
+            let (ts, _) = side_note.rw_mem_check.precompile_access(addr, false);
+            timestamps.push(*ts);
+
+            *ts += 1;
+        }
+        for (i, byte) in output.into_iter().flat_map(u32::to_le_bytes).enumerate() {
+            let addr = state_addr + i as u32;
+            let (ts, prev_val) = side_note.rw_mem_check.precompile_access(addr, true);
+            timestamps.push(*ts);
+
+            *ts += 1;
+            *prev_val = byte;
+        }
+        timestamps
+    }
+}
+
+impl MachineChip for Sha256Chip {
+    fn draw_lookup_elements(
+        lookup_elements: &mut AllLookupElements,
+        channel: &mut impl Channel,
+        config: &ExtensionsConfig,
+    ) {
+        if !config.is_sha256_enabled() {
+            return;
+        }
+        lookup_elements.insert(sha256_lookups::StateLookupElements::draw(channel));
+    }
+
+    fn fill_main_trace(
+        traces: &mut TracesBuilder,
+        row_idx: usize,
+        vm_step: &Option<ProgramStep>,
+        side_note: &mut SideNote,
+        config: &ExtensionsConfig,
+    ) {
+        let Some(step) = vm_step
+            .as_ref()
+            .filter(|step| !step.step.instruction.opcode.is_builtin())
+        else {
+            return;
+        };
+        let opcode = &step.step.instruction.opcode;
+        if Extension::of_opcode(opcode.raw, opcode.fn3.value()) != Some(Extension::Sha256) {
+            return;
+        } else {
+            assert!(
+                config.is_sha256_enabled(),
+                "sha256_compress instruction is only supported with enabled extensions",
+            );
+        }
+
+        let reg = step.step.instruction.op_a;
+        let state_addr = step.regs[reg];
+        let block_addr = step.regs[step.step.instruction.op_b];
+
+        let input = Self::sha256_input_from_mem_records(state_addr, block_addr, step);
+        let timestamps = Self::update_state_timestamps(state_addr, block_addr, &input, side_note);
+
+        let sha256_side_note = &mut side_note.sha256;
+        sha256_side_note.inputs.push(input);
+        sha256_side_note.addresses.push((state_addr, block_addr));
+        sha256_side_note.timestamps.push(timestamps);
+
+        traces.fill_columns(row_idx, true, Column::IsCustomSha256);
+    }
+
+    fn add_constraints<E: stwo_prover::constraint_framework::EvalAtRow>(
+        eval: &mut E,
+        trace_eval: &TraceEval<E>,
+        _lookup_elements: &AllLookupElements,
+        config: &ExtensionsConfig,
+    ) {
+        let [is_custom_sha256] = trace_eval!(trace_eval, Column::IsCustomSha256);
+        if !config.is_sha256_enabled() {
+            eval.add_constraint(is_custom_sha256);
+            return;
+        }
+
+        eval.add_constraint(is_custom_sha256.clone() * (E::F::one() - is_custom_sha256.clone()));
+
+        // (is_custom_sha256)・ (fn3 - SHA256_COMPRESS_FN3) = 0
+        let fn3 = CustomTypeSChip::fn3(trace_eval);
+        eval.add_constraint(
+            is_custom_sha256 * (fn3 - E::F::from(BaseField::from(SHA256_COMPRESS_FN3 as u32))),
+        );
+    }
+}
+
+impl BigIntChip {
+    /// Returns operands read from memory and previous values of the result bytes.
+    fn mul_add_mod_input_from_mem_records(
+        result_addr: u32,
+        operands_addr: u32,
+        step: &ProgramStep,
+    ) -> (
+        [[u32; BIGINT_WORDS]; MUL_ADD_MOD_OPERANDS],
+        [u8; BIGINT_WORDS * WORD_SIZE],
+    ) {
+        let mut operands = [[0u32; BIGINT_WORDS]; MUL_ADD_MOD_OPERANDS];
+        let mut prev_result = [0u8; BIGINT_WORDS * WORD_SIZE];
+        for record in &step.step.memory_records {
+            match *record {
+                MemoryRecord::LoadRecord((size, address, value), _) => {
+                    assert_eq!(size, MemAccessSize::Word);
+                    let idx = (address - operands_addr) as usize / WORD_SIZE;
+                    operands[idx / BIGINT_WORDS][idx % BIGINT_WORDS] = value;
+                }
+                MemoryRecord::StoreRecord((size, address, _, prev_value), _) => {
+                    assert_eq!(size, MemAccessSize::Word);
+                    let offset = (address - result_addr) as usize;
+                    prev_result[offset..offset + WORD_SIZE]
+                        .copy_from_slice(&prev_value.to_le_bytes());
+                }
+            }
+        }
+        (operands, prev_result)
+    }
+
+    /// Modifies side-note timestamps for accessed memory and returns previous values.
+    ///
+    /// Bytes of the operands are accessed before bytes of the result, so that overlapping accesses follow each other.
+    fn update_state_timestamps(
+        result_addr: u32,
+        operands_addr: u32,
+        operands: &[[u32; BIGINT_WORDS]; MUL_ADD_MOD_OPERANDS],
+        side_note: &mut SideNote,
+    ) -> Vec<u32> {
+        let [a, b, c, m] = operands;
+        let result = mul_add_mod(a, b, c, m);
+
+        let mut timestamps =
+            Vec::with_capacity(WORD_SIZE * BIGINT_WORDS * (MUL_ADD_MOD_OPERANDS + 1));
+        for i in 0..MUL_ADD_MOD_OPERANDS * BIGINT_WORDS * WORD_SIZE {
+            let addr = operands_addr + i as u32;
+            let (ts, _) = side_note.rw_mem_check.precompile_access(addr, false);
+            timestamps.push(*ts);
+
+            *ts += 1;
+        }
+        for (i, byte) in result.into_iter().flat_map(u32::to_le_bytes).enumerate() {
+            let addr = result_addr + i as u32;
+            let (ts, prev_val) = side_note.rw_mem_check.precompile_access(addr, true);
+            timestamps.push(*ts);
+
+            *ts += 1;
+            *prev_val = byte;
+        }
+        timestamps
+    }
+}
+
+impl MachineChip for BigIntChip {
+    fn fill_main_trace(
+        traces: &mut TracesBuilder,
+        row_idx: usize,
+        vm_step: &Option<ProgramStep>,
+        side_note: &mut SideNote,
+        config: &ExtensionsConfig,
+    ) {
+        let Some(step) = vm_step
+            .as_ref()
+            .filter(|step| !step.step.instruction.opcode.is_builtin())
+        else {
+            return;
+        };
+        let opcode = &step.step.instruction.opcode;
+        if Extension::of_opcode(opcode.raw, opcode.fn3.value()) != Some(Extension::BigInt) {
+            return;
+        } else {
+            assert!(
+                config.is_bigint_enabled(),
+                "mul_add_mod instruction is only supported with enabled extensions",
+            );
+        }
+
+        let reg = step.step.instruction.op_a;
+        let result_addr = step.regs[reg];
+        let operands_addr = step.regs[step.step.instruction.op_b];
+
+        let (operands, prev_result) =
+            Self::mul_add_mod_input_from_mem_records(result_addr, operands_addr, step);
+        let timestamps =
+            Self::update_state_timestamps(result_addr, operands_addr, &operands, side_note);
+
+        let bigint_side_note = &mut side_note.bigint;
+        bigint_side_note.inputs.push(operands);
+        bigint_side_note.prev_results.push(prev_result);
+        bigint_side_note
+            .addresses
+            .push((result_addr, operands_addr));
+        bigint_side_note.timestamps.push(timestamps);
+
+        traces.fill_columns(row_idx, true, Column::IsCustomBigInt);
+    }
+
+    fn add_constraints<E: stwo_prover::constraint_framework::EvalAtRow>(
+        eval: &mut E,
+        trace_eval: &TraceEval<E>,
+        _lookup_elements: &AllLookupElements,
+        config: &ExtensionsConfig,
+    ) {
+        let [is_custom_bigint] = trace_eval!(trace_eval, Column::IsCustomBigInt);
+        if !config.is_bigint_enabled() {
+            eval.add_constraint(is_custom_bigint);
+            return;
+        }
+
+        eval.add_constraint(is_custom_bigint.clone() * (E::F::one() - is_custom_bigint.clone()));
+
+        // (is_custom_bigint)・ (fn3 - MUL_ADD_MOD_FN3) = 0
+        let fn3 = CustomTypeSChip::fn3(trace_eval);
+        eval.add_constraint(
+            is_custom_bigint * (fn3 - E::F::from(BaseField::from(MUL_ADD_MOD_FN3 as u32))),
+        );
+    }
+}
+
+impl Poseidon2Chip {
+    fn poseidon2_input_from_mem_records(addr: u32, step: &ProgramStep) -> [u32; POSEIDON2_WIDTH] {
+        let mut state = [0u32; POSEIDON2_WIDTH];
+        for record in &step.step.memory_records {
+            let MemoryRecord::LoadRecord((size, address, value), _) = *record else {
+                continue;
+            };
+            assert_eq!(size, MemAccessSize::Word);
+            state[(address - addr) as usize / WORD_SIZE] = value;
+        }
+        state
+    }
+
+    /// Modifies side-note timestamps for accessed memory and returns previous values.
+    fn update_state_timestamps(
+        addr: u32,
+        input: &[u32; POSEIDON2_WIDTH],
+        side_note: &mut SideNote,
+    ) -> Vec<u32> {
+        let mut output = input.map(|word| word % M31_MODULUS);
+        poseidon2_permute(&mut output);
+
+        let mut timestamps = Vec::with_capacity(WORD_SIZE * POSEIDON2_WIDTH);
+        for (i, byte) in output.into_iter().flat_map(u32::to_le_bytes).enumerate() {
+            let addr = addr + i as u32;
+            let (ts, prev_val) = side_note.rw_mem_check.precompile_access(addr, true);
+            timestamps.push(*ts);
+
+            *ts += 1;
+            *prev_val = byte;
+        }
+        timestamps
+    }
+}
+
+impl MachineChip for Poseidon2Chip {
+    fn fill_main_trace(
+        traces: &mut TracesBuilder,
+        row_idx: usize,
+        vm_step: &Option<ProgramStep>,
+        side_note: &mut SideNote,
+        config: &ExtensionsConfig,
+    ) {
+        let Some(step) = vm_step
+            .as_ref()
+            .filter(|step| !step.step.instruction.opcode.is_builtin())
+        else {
+            return;
+        };
+        let opcode = &step.step.instruction.opcode;
+        if Extension::of_opcode(opcode.raw, opcode.fn3.value()) != Some(Extension::Poseidon2) {
+            return;
+        } else {
+            assert!(
+                config.is_poseidon2_enabled(),
+                "poseidon2 instruction is only supported with enabled extensions",
+            );
+        }
+
+        let reg = step.step.instruction.op_a;
+        let addr = step.regs[reg];
+
+        let input = Self::poseidon2_input_from_mem_records(addr, step);
+        let timestamps = Self::update_state_timestamps(addr, &input, side_note);
+
+        let poseidon2_side_note = &mut side_note.poseidon2;
+        poseidon2_side_note.inputs.push(input);
+        poseidon2_side_note.addresses.push(addr);
+        poseidon2_side_note.timestamps.push(timestamps);
+
+        traces.fill_columns(row_idx, true, Column::IsCustomPoseidon2);
+    }
+
+    fn add_constraints<E: stwo_prover::constraint_framework::EvalAtRow>(
+        eval: &mut E,
+        trace_eval: &TraceEval<E>,
+        _lookup_elements: &AllLookupElements,
+        config: &ExtensionsConfig,
+    ) {
+        let [is_custom_poseidon2] = trace_eval!(trace_eval, Column::IsCustomPoseidon2);
+        if !config.is_poseidon2_enabled() {
+            eval.add_constraint(is_custom_poseidon2);
+            return;
+        }
+
+        eval.add_constraint(
+            is_custom_poseidon2.clone() * (E::F::one() - is_custom_poseidon2.clone()),
+        );
+
+        // (is_custom_poseidon2)・ (fn3 - POSEIDON2_FN3) = 0
+        let fn3 = CustomTypeSChip::fn3(trace_eval);
+        eval.add_constraint(
+            is_custom_poseidon2 * (fn3 - E::F::from(BaseField::from(POSEIDON2_FN3 as u32))),
+        );
+    }
+}
+
+impl MemcpyChip {
+    /// Returns bytes written by the call and previous values of the destination bytes.
+    ///
+    /// `memcpy` writes bytes read from the source, `memset` writes the low byte of the second register.
+    fn memcpy_input_from_mem_records(
+        dst: u32,
+        src: Option<u32>,
+        len: usize,
+        step: &ProgramStep,
+    ) -> (Vec<u8>, Vec<u8>) {
+        let fill_value = step.regs[step.step.instruction.op_b] as u8;
+        let mut values = vec![fill_value; len];
+        let mut prev_values = vec![0u8; len];
+        for record in &step.step.memory_records {
+            match *record {
+                MemoryRecord::LoadRecord((size, address, value), _) => {
+                    assert_eq!(size, MemAccessSize::Byte);
+                    let src = src.expect("memset doesn't read memory");
+                    values[(address - src) as usize] = value as u8;
+                }
+                MemoryRecord::StoreRecord((size, address, _, prev_value), _) => {
+                    assert_eq!(size, MemAccessSize::Byte);
+                    prev_values[(address - dst) as usize] = prev_value as u8;
+                }
+            }
+        }
+        (values, prev_values)
+    }
+
+    /// Modifies side-note timestamps for accessed memory and returns previous timestamps of the destination and
+    /// source bytes.
+    ///
+    /// Source bytes are read before destination bytes are written, so that overlapping accesses follow each other.
+    fn update_state_timestamps(
+        dst: u32,
+        src: Option<u32>,
+        values: &[u8],
+        side_note: &mut SideNote,
+    ) -> (Vec<u32>, Option<Vec<u32>>) {
+        let src_timestamps = src.map(|src| {
+            (0..values.len())
+                .map(|i| {
+                    let addr = src + i as u32;
+                    let (ts, _) = side_note.rw_mem_check.precompile_access(addr, false);
+                    let prev_ts = *ts;
+
+                    *ts += 1;
+                    prev_ts
+                })
+                .collect()
+        });
+        let mut dst_timestamps = Vec::with_capacity(values.len());
+        for (i, &byte) in values.iter().enumerate() {
+            let addr = dst + i as u32;
+            let (ts, prev_val) = side_note.rw_mem_check.precompile_access(addr, true);
+            dst_timestamps.push(*ts);
+
+            *ts += 1;
+            *prev_val = byte;
+        }
+        (dst_timestamps, src_timestamps)
+    }
+}
+
+impl MachineChip for MemcpyChip {
+    fn draw_lookup_elements(
+        lookup_elements: &mut AllLookupElements,
+        channel: &mut impl Channel,
+        config: &ExtensionsConfig,
+    ) {
+        if !config.is_memcpy_enabled() {
+            return;
+        }
+        lookup_elements.insert(MemcpyCallLookupElements::draw(channel));
+    }
+
+    fn fill_main_trace(
+        traces: &mut TracesBuilder,
+        row_idx: usize,
+        vm_step: &Option<ProgramStep>,
+        side_note: &mut SideNote,
+        config: &ExtensionsConfig,
+    ) {
+        let Some(step) = vm_step
+            .as_ref()
+            .filter(|step| !step.step.instruction.opcode.is_builtin())
+        else {
+            return;
+        };
+        let opcode = &step.step.instruction.opcode;
+        if Extension::of_opcode(opcode.raw, opcode.fn3.value()) != Some(Extension::Memcpy) {
+            return;
+        } else {
+            assert!(
+                config.is_memcpy_enabled(),
+                "memcpy and memset instructions are only supported with enabled extensions",
+            );
+        }
+
+        let dst = step.regs[step.step.instruction.op_a];
+        let src = (opcode.fn3.value() == MEMCPY_FN3).then(|| step.regs[step.step.instruction.op_b]);
+        // the number of bytes is encoded in the immediate
+        let len = step.step.instruction.op_c as usize;
+        assert!(
+            (1..1 << 11).contains(&len),
+            "memcpy and memset instructions must access between 1 and 2047 bytes, got {len}",
+        );
+
+        let (values, prev_values) = Self::memcpy_input_from_mem_records(dst, src, len, step);
+        let (dst_timestamps, src_timestamps) =
+            Self::update_state_timestamps(dst, src, &values, side_note);
+
+        let memcpy_side_note = &mut side_note.memcpy;
+        for (i, (&value, &prev_value)) in values.iter().zip(&prev_values).enumerate() {
+            let offset = i as u32;
+            memcpy_side_note.values.push(value);
+            memcpy_side_note.prev_values.push(prev_value);
+            memcpy_side_note
+                .addresses
+                .push((dst + offset, src.map(|src| src + offset)));
+            memcpy_side_note.timestamps.push((
+                dst_timestamps[i],
+                src_timestamps.as_ref().map(|timestamps| timestamps[i]),
+            ));
+            memcpy_side_note.remaining.push((len - i) as u32);
+        }
+
+        traces.fill_columns(row_idx, true, Column::IsCustomMemcpy);
+    }
+
+    fn fill_interaction_trace(
+        logup_trace_gen: &mut LogupTraceGenerator,
+        original_traces: &FinalizedTraces,
+        _preprocessed_traces: &PreprocessedTraces,
+        _program_traces: &ProgramTraces,
+        lookup_element: &AllLookupElements,
+    ) {
+        if !lookup_element.contains::<MemcpyCallLookupElements>() {
+            return;
+        }
+        let lookup_element: &MemcpyCallLookupElements = lookup_element.as_ref();
+        let [is_custom_memcpy] = original_traces.get_base_column(Column::IsCustomMemcpy);
+        let instr_val: [_; WORD_SIZE] = original_traces.get_base_column(InstrVal);
+        let [op_a0] = original_traces.get_base_column(OpA0);
+        let [op_c1_4] = original_traces.get_base_column(OpC1_4);
+        let [op_c] = original_traces.get_base_column(OpC);
+        let value_a: [_; WORD_SIZE] = original_traces.get_base_column(ValueAEffective);
+        let value_b: [_; WORD_SIZE] = original_traces.get_base_column(ValueB);
+
+        // Start the chain of bytes written by each call
+        let mut logup_col_gen = logup_trace_gen.new_col();
+        // vec_row is row_idx divided by 16. Because SIMD.
+        for vec_row in 0..(1 << (original_traces.log_size() - LOG_N_LANES)) {
+            let fn3 = (instr_val[1].data[vec_row]
+                - op_c1_4.data[vec_row]
+                - op_a0.data[vec_row] * PackedBaseField::broadcast((1 << 7).into()))
+                * PackedBaseField::broadcast(BaseField::from(1 << 4).inverse());
+            let is_copy = PackedBaseField::broadcast((MEMSET_FN3 as u32).into()) - fn3;
+            let shift = PackedBaseField::broadcast((1 << 8).into());
+
+            let tuple = [
+                value_a[0].data[vec_row] + value_a[1].data[vec_row] * shift,
+                value_a[2].data[vec_row] + value_a[3].data[vec_row] * shift,
+                value_b[0].data[vec_row] + is_copy * value_b[1].data[vec_row] * shift,
+                is_copy * (value_b[2].data[vec_row] + value_b[3].data[vec_row] * shift),
+                op_c.data[vec_row],
+                is_copy,
+            ];
+            logup_col_gen.write_frac(
+                vec_row,
+                is_custom_memcpy.data[vec_row].into(),
+                lookup_element.combine(&tuple),
+            );
+        }
+        logup_col_gen.finalize_col();
+    }
+
+    fn add_constraints<E: stwo_prover::constraint_framework::EvalAtRow>(
+        eval: &mut E,
+        trace_eval: &TraceEval<E>,
+        lookup_elements: &AllLookupElements,
+        config: &ExtensionsConfig,
+    ) {
+        let [is_custom_memcpy] = trace_eval!(trace_eval, Column::IsCustomMemcpy);
+        if !config.is_memcpy_enabled() {
+            eval.add_constraint(is_custom_memcpy);
+            return;
+        }
+
+        eval.add_constraint(is_custom_memcpy.clone() * (E::F::one() - is_custom_memcpy.clone()));
+
+        // (is_custom_memcpy)・ (fn3 - MEMCPY_FN3)・ (fn3 - MEMSET_FN3) = 0
+        let fn3 = CustomTypeSChip::fn3(trace_eval);
+        eval.add_constraint(
+            is_custom_memcpy.clone()
+                * (fn3.clone() - E::F::from(BaseField::from(MEMCPY_FN3 as u32)))
+                * (fn3.clone() - E::F::from(BaseField::from(MEMSET_FN3 as u32))),
+        );
+        let is_copy = E::F::from(BaseField::from(MEMSET_FN3 as u32)) - fn3;
+
+        // The extension writes one byte per row, chaining the rows of a call from the destination and source
+        // addresses and the number of bytes, which are therefore taken from the registers and the immediate.
+        // `memset` only uses the low byte of the second register as the fill value.
+        let value_a = trace_eval!(trace_eval, ValueAEffective);
+        let value_b = trace_eval!(trace_eval, ValueB);
+        let [op_c] = trace_eval!(trace_eval, OpC);
+        let tuple = [
+            value_a[0].clone() + value_a[1].clone() * BaseField::from(1 << 8),
+            value_a[2].clone() + value_a[3].clone() * BaseField::from(1 << 8),
+            value_b[0].clone() + is_copy.clone() * value_b[1].clone() * BaseField::from(1 << 8),
+            is_copy.clone() * (value_b[2].clone() + value_b[3].clone() * BaseField::from(1 << 8)),
+            op_c,
+            is_copy,
+        ];
+
+        let lookup_elements: &MemcpyCallLookupElements = lookup_elements.as_ref();
+        eval.add_to_relation(RelationEntry::new(
+            lookup_elements,
+            is_custom_memcpy.into(),
+            &tuple,
+        ));
+    }
+}
+
+impl CustomTypeSChip {
+    /// Returns the `funct3` field of a built-in custom instruction.
+    ///
+    /// instr_val_2 = op_c1_4 + fn3・2^4 + op_a0・2^7
+    fn fn3<E: stwo_prover::constraint_framework::EvalAtRow>(trace_eval: &TraceEval<E>) -> E::F {
+        let instr_val = trace_eval!(trace_eval, InstrVal);
+        let [op_c1_4] = trace_eval!(trace_eval, OpC1_4);
+        let [op_a0] = trace_eval!(trace_eval, OpA0);
+        (instr_val[1].clone() - op_c1_4 - op_a0 * BaseField::from(1 << 7))
+            * BaseField::from(1 << 4).inverse()
+    }
+}
+
+impl MachineChip for CustomTypeSChip {
+    fn fill_main_trace(
+        traces: &mut TracesBuilder,
+        row_idx: usize,
+        vm_step: &Option<ProgramStep>,
+        _side_note: &mut SideNote,
+        _config: &ExtensionsConfig,
+    ) {
+        let Some(step) = vm_step
+            .as_ref()
+            .filter(|step| !step.step.instruction.opcode.is_builtin())
+        else {
+            return;
+        };
+        // Flags are filled by the chips of the instructions.
+        let [is_custom_type_s] = IsCustomTypeS::read_from_traces_builder(traces, row_idx);
+        if is_custom_type_s.is_zero() {
+            return;
+        }
+
+        let instruction = &step.step.instruction;
+        let op_a = instruction.op_a as u8;
+        let op_b = instruction.op_b as u8;
+        // the immediate is zero-extended, as for type S instructions in `CpuChip`
+        let op_c = instruction.op_c & 0xFFF;
+        traces.fill_columns(row_idx, op_a, OpA);
+        traces.fill_columns(row_idx, op_b, OpB);
+        traces.fill_columns(row_idx, BaseField::from_u32_unchecked(op_c), OpC);
+        traces.fill_columns(row_idx, op_a & 0x1, OpA0);
+        traces.fill_columns(row_idx, (op_a >> 1) & 0xF, OpA1_4);
+        traces.fill_columns(row_idx, op_b & 0xF, OpB0_3);
+        traces.fill_columns(row_idx, (op_b >> 4) & 0x1, OpB4);
+        traces.fill_columns(row_idx, (op_c & 0x1) as u8, OpC0);
+        traces.fill_columns(row_idx, ((op_c >> 1) & 0xF) as u8, OpC1_4);
+        traces.fill_columns(row_idx, ((op_c >> 5) & 0x7) as u8, OpC5_7);
+        traces.fill_columns(row_idx, ((op_c >> 8) & 0x7) as u8, OpC8_10);
+        traces.fill_columns(row_idx, ((op_c >> 11) & 0x1) as u8, OpC11);
+
+        traces.fill_columns(row_idx, op_b, Reg1Address);
+        traces.fill_columns(row_idx, op_a, Reg3Address);
+        traces.fill_columns(row_idx, step.get_value_b(), ValueB);
+        traces.fill_columns(row_idx, step.get_value_a(), ValueA);
+    }
+
+    fn add_constraints<E: stwo_prover::constraint_framework::EvalAtRow>(
+        eval: &mut E,
+        trace_eval: &TraceEval<E>,
+        _lookup_elements: &AllLookupElements,
+        _config: &ExtensionsConfig,
+    ) {
+        let [is_custom_type_s] = IsCustomTypeS::eval(trace_eval);
+
+        // Operands are range-checked together with type S.
+        // (is_custom_type_s)・ (op_a0 + op_a1_4・2 – op_a) = 0
+        // (is_custom_type_s)・ (op_b0_3 + op_b4・2^4 – op_b) = 0
+        // (is_custom_type_s)・ (op_c0 + op_c1_4・2 + op_c5_7・2^5 + op_c8_10・2^8 + op_c11・2^11 – op_c) = 0
+        let [op_a] = trace_eval!(trace_eval, OpA);
+        let [op_a0] = trace_eval!(trace_eval, OpA0);
+        let [op_a1_4] = trace_eval!(trace_eval, OpA1_4);
+        let [op_b] = trace_eval!(trace_eval, OpB);
+        let [op_b0_3] = trace_eval!(trace_eval, OpB0_3);
+        let [op_b4] = trace_eval!(trace_eval, OpB4);
+        let [op_c] = trace_eval!(trace_eval, OpC);
+        let [op_c0] = trace_eval!(trace_eval, OpC0);
+        let [op_c1_4] = trace_eval!(trace_eval, OpC1_4);
+        let [op_c5_7] = trace_eval!(trace_eval, OpC5_7);
+        let [op_c8_10] = trace_eval!(trace_eval, OpC8_10);
+        let [op_c11] = trace_eval!(trace_eval, OpC11);
+        eval.add_constraint(
+            is_custom_type_s.clone()
+                * (op_a0 + op_a1_4.clone() * BaseField::from(1 << 1) - op_a.clone()),
+        );
+        eval.add_constraint(
+            is_custom_type_s.clone()
+                * (op_b0_3.clone() + op_b4.clone() * BaseField::from(1 << 4) - op_b.clone()),
+        );
+        eval.add_constraint(
+            is_custom_type_s.clone()
+                * (op_c0.clone()
+                    + op_c1_4 * BaseField::from(1 << 1)
+                    + op_c5_7.clone() * BaseField::from(1 << 5)
+                    + op_c8_10.clone() * BaseField::from(1 << 8)
+                    + op_c11.clone() * BaseField::from(1 << 11)
+                    - op_c),
+        );
+
+        // The second limb holds fn3, which is checked by the chip of the instruction.
+        // (is_custom_type_s)・ (b1011010 + op_c0・2^7 - instr_val_1) = 0
+        // (is_custom_type_s)・ (op_a1_4 + op_b0_3・2^4 - instr_val_3) = 0
+        // (is_custom_type_s)・ (op_b4 + op_c5_7・2 + op_c8_10・2^4 + op_c11・2^7 - instr_val_4) = 0
+        let instr_val = trace_eval!(trace_eval, InstrVal);
+        eval.add_constraint(
+            is_custom_type_s.clone()
+                * (E::F::from(BaseField::from(KECCAKF_OPCODE as u32))
+                    + op_c0 * BaseField::from(1 << 7)
+                    - instr_val[0].clone()),
+        );
+        eval.add_constraint(
+            is_custom_type_s.clone()
+                * (op_a1_4 + op_b0_3 * BaseField::from(1 << 4) - instr_val[2].clone()),
+        );
+        eval.add_constraint(
+            is_custom_type_s.clone()
+                * (op_b4
+                    + op_c5_7 * BaseField::from(1 << 1)
+                    + op_c8_10 * BaseField::from(1 << 4)
+                    + op_c11 * BaseField::from(1 << 7)
+                    - instr_val[3].clone()),
+        );
+
+        // Registers are accessed as for type S instructions
+        let [reg1_address] = trace_eval!(trace_eval, Reg1Address);
+        let [reg3_address] = trace_eval!(trace_eval, Reg3Address);
+        eval.add_constraint(is_custom_type_s.clone() * (op_b - reg1_address));
+        eval.add_constraint(is_custom_type_s.clone() * (op_a - reg3_address));
+
+        // Reading the registers doesn't change their values.
+        let reg1_val_prev = trace_eval!(trace_eval, Reg1ValPrev);
+        let reg3_val_prev = trace_eval!(trace_eval, Reg3ValPrev);
+        let value_b = trace_eval!(trace_eval, ValueB);
+        let value_a_effective = trace_eval!(trace_eval, ValueAEffective);
+        for limb_idx in 0..WORD_SIZE {
+            eval.add_constraint(
+                is_custom_type_s.clone()
+                    * (value_b[limb_idx].clone() - reg1_val_prev[limb_idx].clone()),
+            );
+            eval.add_constraint(
+                is_custom_type_s.clone()
+                    * (value_a_effective[limb_idx].clone() - reg3_val_prev[limb_idx].clone()),
+            );
+        }
+    }
+}
+
+impl ExternalChip {
+    /// Returns the step sent to external extensions, modifying side-note timestamps for accessed memory.
+    ///
+    /// Bytes are accessed in the order of their addresses, loads before stores, so that a byte both read and written by
+    /// the instruction is read first.
+    pub(crate) fn external_step(step: &ProgramStep, side_note: &mut SideNote) -> ExternalStep {
+        let opcode = &step.step.instruction.opcode;
+        let result = step.get_result().unwrap_or_else(|| step.get_value_a());
+
+        let mut records: Vec<&MemoryRecord> = step.step.memory_records.iter().collect();
+        records.sort_by_key(|record| {
+            (
+                matches!(record, MemoryRecord::StoreRecord(..)),
+                record.get_address(),
+            )
+        });
+        let mut memory_accesses = Vec::new();
+        for record in records {
+            let value = record.get_value().to_le_bytes();
+            let prev_value = record.get_prev_value().map_or(value, u32::to_le_bytes);
+            for i in 0..record.get_size() as usize {
+                let address = record
+                    .get_address()
+                    .checked_add(i as u32)
+                    .expect("memory access range overflowed back to address zero");
+                assert_eq!(
+                    side_note.rw_mem_check.access_mode(address),
+                    AccessMode::ReadWrite,
+                    "external extensions only access read-write memory, address 0x{:x}",
+                    address,
+                );
+                let (ts, prev_val) = side_note.rw_mem_check.precompile_access(address, true);
+                memory_accesses.push(ExternalMemoryAccess {
+                    address,
+                    prev_value: prev_value[i],
+                    value: value[i],
+                    prev_timestamp: *ts,
+                });
+
+                *ts += 1;
+                *prev_val = value[i];
+            }
+        }
+
+        ExternalStep {
+            clk: step.step.timestamp,
+            fn3: opcode.fn3.value(),
+            fn7: opcode.fn7.value(),
+            result: u32::from_le_bytes(result),
+            value_b: u32::from_le_bytes(step.get_value_b()),
+            value_c: u32::from_le_bytes(step.get_value_c().0),
+            memory_accesses,
+        }
+    }
+}
+
+impl MachineChip for ExternalChip {
+    fn draw_lookup_elements(
+        lookup_elements: &mut AllLookupElements,
+        channel: &mut impl Channel,
+        config: &ExtensionsConfig,
+    ) {
+        if !config.is_external_enabled() {
+            return;
+        }
+        lookup_elements.insert(ExternalInstructionLookupElements::draw(channel));
+    }
+
+    fn fill_main_trace(
+        traces: &mut TracesBuilder,
+        row_idx: usize,
+        vm_step: &Option<ProgramStep>,
+        side_note: &mut SideNote,
+        config: &ExtensionsConfig,
+    ) {
+        let Some(step) = vm_step.as_ref().filter(|step| {
+            !step.step.instruction.opcode.is_builtin()
+                && step.step.instruction.opcode.raw != KECCAKF_OPCODE
+        }) else {
+            return;
+        };
+        let instruction = &step.step.instruction;
+        assert!(
+            config.is_external_enabled(),
+            "custom instruction {} is only supported with external extensions",
+            instruction.opcode,
+        );
+        assert!(
+            instruction.opcode.raw == EXTERNAL_OPCODE
+                && instruction.ins_type == InstructionType::RType
+                && !instruction.compressed,
+            "custom instruction {} must be an R-type instruction with opcode 0b{:07b}",
+            instruction.opcode,
+            EXTERNAL_OPCODE,
+        );
+
+        let op_a = instruction.op_a as u8;
+        let op_b = instruction.op_b as u8;
+        let op_c = instruction.op_c as u8;
+        traces.fill_columns(row_idx, op_a, OpA);
+        traces.fill_columns(row_idx, op_b, OpB);
+        traces.fill_columns(row_idx, op_c, OpC);
+        traces.fill_columns(row_idx, op_a & 0x1, OpA0);
+        traces.fill_columns(row_idx, (op_a >> 1) & 0xF, OpA1_4);
+        traces.fill_columns(row_idx, op_b & 0x1, OpB0);
+        traces.fill_columns(row_idx, (op_b >> 1) & 0xF, OpB1_4);
+        traces.fill_columns(row_idx, op_c & 0xF, OpC0_3);
+        traces.fill_columns(row_idx, (op_c >> 4) & 0x1, OpC4);
+
+        // Both source registers are read and the result is written to the destination register.
+        traces.fill_columns(row_idx, op_b, Reg1Address);
+        traces.fill_columns(row_idx, op_c, Reg2Address);
+        traces.fill_columns(row_idx, op_a, Reg3Address);
+
+        let external_step = Self::external_step(step, side_note);
+        traces.fill_columns(row_idx, external_step.value_b, ValueB);
+        traces.fill_columns(row_idx, external_step.value_c, ValueC);
+        traces.fill_columns(row_idx, external_step.result, ValueA);
+        side_note.external_steps.push(external_step);
+
+        traces.fill_columns(row_idx, true, Column::IsCustomExternal);
+    }
+
+    fn fill_interaction_trace(
+        logup_trace_gen: &mut LogupTraceGenerator,
+        original_traces: &FinalizedTraces,
+        preprocessed_traces: &PreprocessedTraces,
+        _program_traces: &ProgramTraces,
+        lookup_element: &AllLookupElements,
+    ) {
+        if !lookup_element.contains::<ExternalInstructionLookupElements>() {
+            return;
+        }
+        let lookup_element: &ExternalInstructionLookupElements = lookup_element.as_ref();
+        let [is_custom_external] = original_traces.get_base_column(Column::IsCustomExternal);
+        let instr_val: [_; WORD_SIZE] = original_traces.get_base_column(InstrVal);
+        let [op_a1_4] = original_traces.get_base_column(OpA1_4);
+        let [op_b0] = original_traces.get_base_column(OpB0);
+        let [op_c4] = original_traces.get_base_column(OpC4);
+        let value_a: [_; WORD_SIZE] = original_traces.get_base_column(ValueA);
+        let value_b: [_; WORD_SIZE] = original_traces.get_base_column(ValueB);
+        let value_c: [_; WORD_SIZE] = original_traces.get_base_column(ValueC);
+        let clk =
+            preprocessed_traces.get_preprocessed_base_column::<WORD_SIZE>(PreprocessedColumn::Clk);
+
+        // Send custom instructions to external extensions
+        let mut logup_col_gen = logup_trace_gen.new_col();
+        // vec_row is row_idx divided by 16. Because SIMD.
+        for vec_row in 0..(1 << (original_traces.log_size() - LOG_N_LANES)) {
+            let fn3 = (instr_val[1].data[vec_row]
+                - op_a1_4.data[vec_row]
+                - op_b0.data[vec_row] * PackedBaseField::broadcast((1 << 7).into()))
+                * PackedBaseField::broadcast(BaseField::from(1 << 4).inverse());
+            let fn7 = (instr_val[3].data[vec_row] - op_c4.data[vec_row])
+                * PackedBaseField::broadcast(BaseField::from(1 << 1).inverse());
+            let clk_low = clk[0].data[vec_row]
+                + clk[1].data[vec_row] * PackedBaseField::broadcast((1 << 8).into());
+            let clk_high = clk[2].data[vec_row]
+                + clk[3].data[vec_row] * PackedBaseField::broadcast((1 << 8).into());
+
+            let mut tuple = vec![fn3, fn7, clk_low, clk_high];
+            for word in [&value_a, &value_b, &value_c] {
+                tuple.extend(word.iter().map(|limb| limb.data[vec_row]));
+            }
+            assert_eq!(tuple.len(), EXTERNAL_LOOKUP_TUPLE_SIZE);
+
+            logup_col_gen.write_frac(
+                vec_row,
+                is_custom_external.data[vec_row].into(),
+                lookup_element.combine(&tuple),
+            );
+        }
+        logup_col_gen.finalize_col();
+    }
+
+    fn add_constraints<E: stwo_prover::constraint_framework::EvalAtRow>(
+        eval: &mut E,
+        trace_eval: &TraceEval<E>,
+        lookup_elements: &AllLookupElements,
+        config: &ExtensionsConfig,
+    ) {
+        let [is_custom_external] = trace_eval!(trace_eval, Column::IsCustomExternal);
+        if !config.is_external_enabled() {
+            eval.add_constraint(is_custom_external);
+            return;
+        }
+
+        eval.add_constraint(
+            is_custom_external.clone() * (E::F::one() - is_custom_external.clone()),
+        );
+
+        // Decoding follows R-type instructions, operands are range-checked together with type R.
+        // (is_custom_external)・ (op_a0 + op_a1_4・2 – op_a) = 0
+        // (is_custom_external)・ (op_b0 + op_b1_4・2 – op_b) = 0
+        // (is_custom_external)・ (op_c0_3 + op_c4・2^4 – op_c) = 0
+        let [op_a] = trace_eval!(trace_eval, OpA);
+        let [op_a0] = trace_eval!(trace_eval, OpA0);
+        let [op_a1_4] = trace_eval!(trace_eval, OpA1_4);
+        let [op_b] = trace_eval!(trace_eval, OpB);
+        let [op_b0] = trace_eval!(trace_eval, OpB0);
+        let [op_b1_4] = trace_eval!(trace_eval, OpB1_4);
+        let [op_c] = trace_eval!(trace_eval, OpC);
+        let [op_c0_3] = trace_eval!(trace_eval, OpC0_3);
+        let [op_c4] = trace_eval!(trace_eval, OpC4);
+        eval.add_constraint(
+            is_custom_external.clone()
+                * (op_a0.clone() + op_a1_4.clone() * BaseField::from(1 << 1) - op_a.clone()),
+        );
+        eval.add_constraint(
+            is_custom_external.clone()
+                * (op_b0.clone() + op_b1_4.clone() * BaseField::from(1 << 1) - op_b.clone()),
+        );
+        eval.add_constraint(
+            is_custom_external.clone()
+                * (op_c0_3.clone() + op_c4.clone() * BaseField::from(1 << 4) - op_c.clone()),
+        );
+
+        // (is_custom_external)・ (b0001011 + op_a0・2^7 - instr_val_1) = 0
+        // (is_custom_external)・ (op_b1_4 + op_c0_3・2^4 - instr_val_3) = 0
+        let instr_val = trace_eval!(trace_eval, InstrVal);
+        eval.add_constraint(
+            is_custom_external.clone()
+                * (E::F::from(BaseField::from(EXTERNAL_OPCODE as u32))
+                    + op_a0 * BaseField::from(1 << 7)
+                    - instr_val[0].clone()),
+        );
+        eval.add_constraint(
+            is_custom_external.clone()
+                * (op_b1_4 + op_c0_3 * BaseField::from(1 << 4) - instr_val[2].clone()),
+        );
+
+        // Registers are accessed as for type R instructions
+        let [reg1_address] = trace_eval!(trace_eval, Reg1Address);
+        let [reg2_address] = trace_eval!(trace_eval, Reg2Address);
+        let [reg3_address] = trace_eval!(trace_eval, Reg3Address);
+        eval.add_constraint(is_custom_external.clone() * (op_b - reg1_address));
+        eval.add_constraint(is_custom_external.clone() * (op_c - reg2_address));
+        eval.add_constraint(is_custom_external.clone() * (op_a - reg3_address));
+
+        // Reading the second source register doesn't change its value, the first one is covered by OpBFlag.
+        let reg2_val_prev = trace_eval!(trace_eval, Reg2ValPrev);
+        let value_c = trace_eval!(trace_eval, ValueC);
+        for limb_idx in (0..WORD_SIZE).step_by(2) {
+            eval.add_constraint(
+                is_custom_external.clone()
+                    * (reg2_val_prev[limb_idx].clone()
+                        + reg2_val_prev[limb_idx + 1].clone() * BaseField::from(1 << 8)
+                        - (value_c[limb_idx].clone()
+                            + value_c[limb_idx + 1].clone() * BaseField::from(1 << 8))),
+            );
+        }
+
+        // fn3 and fn7 are the remaining bits of the instruction, an extension compares them against its constants.
+        let fn3 = (instr_val[1].clone() - op_a1_4 - op_b0 * BaseField::from(1 << 7))
+            * BaseField::from(1 << 4).inverse();
+        let fn7 = (instr_val[3].clone() - op_c4) * BaseField::from(1 << 1).inverse();
+        let clk = preprocessed_trace_eval!(trace_eval, PreprocessedColumn::Clk);
+        let clk_low = clk[0].clone() + clk[1].clone() * E::F::from((1 << 8).into());
+        let clk_high = clk[2].clone() + clk[3].clone() * E::F::from((1 << 8).into());
+
+        let mut tuple = vec![fn3, fn7, clk_low, clk_high];
+        for word in [
+            trace_eval!(trace_eval, ValueA),
+            trace_eval!(trace_eval, ValueB),
+            value_c,
+        ] {
+            tuple.extend(word);
+        }
+        assert_eq!(tuple.len(), EXTERNAL_LOOKUP_TUPLE_SIZE);
+
+        // Send custom instructions to external extensions
+        let lookup_elements: &ExternalInstructionLookupElements = lookup_elements.as_ref();
+        eval.add_to_relation(RelationEntry::new(
+            lookup_elements,
+            is_custom_external.into(),
+            &tuple,
+        ));
+    }
+}
@@ -19,6 +19,7 @@ use crate::{
         ProgramStep, TracesBuilder, Word,
     },
     traits::{ExecuteChip, MachineChip},
+    virtual_column::{self, VirtualColumn},
 };
 
 use super::add;
@@ -53,7 +54,7 @@ impl ExecuteChip for BeqChip {
         let (pc_next, carry_bits) = if value_a == value_b {
             add::add_with_carries(pc, imm)
         } else {
-            add::add_with_carries(pc, 4u32.to_le_bytes())
+            add::add_with_carries(pc, program_step.step.instruction.size().to_le_bytes())
         };
 
         let neq_flag = value_a != value_b;
@@ -165,6 +166,7 @@ impl MachineChip for BeqChip {
         _lookup_elements: &AllLookupElements,
         _config: &ExtensionsConfig,
     ) {
+        let [instr_size] = virtual_column::InstrSize::eval(trace_eval);
         let modulus = E::F::from(256u32.into());
         let neq_flag = trace_eval!(trace_eval, Column::Neq);
         let neq_12_flag = trace_eval!(trace_eval, Column::Neq12);
@@ -233,14 +235,14 @@ impl MachineChip for BeqChip {
 
         // Setting pc_next based on comparison result
         // pc_next=pc+c_val if neq_flag = 0
-        // pc_next=pc+4 	if neq_flag = 1
+        // pc_next=pc+instr_size 	if neq_flag = 1
         // carry_{1,2,3,4} used for carry handling
-        // is_beq・((1 - neq_flag)・(c_val_1 + c_val_2 * 256) + neq_flag・4 + pc_1 + pc_2 * 256 - carry_1·2^{16} - pc_next_1 - pc_next_2 * 256) = 0
+        // is_beq・((1 - neq_flag)・(c_val_1 + c_val_2 * 256) + neq_flag・instr_size + pc_1 + pc_2 * 256 - carry_1·2^{16} - pc_next_1 - pc_next_2 * 256) = 0
         eval.add_constraint(
             is_beq.clone()
                 * ((E::F::one() - neq_flag[0].clone())
                     * (value_c[0].clone() + value_c[1].clone() * modulus.clone())
-                    + neq_flag[0].clone() * E::F::from(4u32.into())
+                    + neq_flag[0].clone() * instr_size.clone()
                     + pc[0].clone()
                     + pc[1].clone() * modulus.clone()
                     - carry_bits[0].clone() * modulus.clone().pow(2)
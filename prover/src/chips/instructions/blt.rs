@@ -16,6 +16,7 @@ use crate::{
         ProgramStep, TracesBuilder, Word,
     },
     traits::{ExecuteChip, MachineChip},
+    virtual_column::{self, VirtualColumn},
 };
 
 use super::add::{self};
@@ -56,8 +57,8 @@ impl ExecuteChip for BltChip {
             // a < b is true: pc_next = pc + imm
             add::add_with_carries(pc, imm)
         } else {
-            // a >= b is true: pc_next = pc + 4
-            add::add_with_carries(pc, 4u32.to_le_bytes())
+            // a >= b is true: pc_next = pc + instr_size
+            add::add_with_carries(pc, program_step.step.instruction.size().to_le_bytes())
         };
         let mut h2 = value_a;
         let mut h3 = value_b;
@@ -129,6 +130,7 @@ impl MachineChip for BltChip {
         _lookup_elements: &AllLookupElements,
         _config: &ExtensionsConfig,
     ) {
+        let [instr_size] = virtual_column::InstrSize::eval(trace_eval);
         let modulus = E::F::from(256u32.into());
         let modulus_7_inv = E::F::from(BaseField::from(128u32).inverse());
         let value_a = trace_eval!(trace_eval, ValueA);
@@ -185,12 +187,12 @@ impl MachineChip for BltChip {
 
         // Setting pc_next based on comparison result
         // pc_next=pc+c_val if lt_flag = 1
-        // pc_next=pc+4 	if lt_flag = 0
-        // is_blt・(lt_flag・(c_val_1 + c_val_2 * 256) + (1-lt_flag)・4 + pc_1 + pc_2 * 256 - carry_1·2^{16} - pc_next_1 - pc_next_2 * 256) =0
+        // pc_next=pc+instr_size 	if lt_flag = 0
+        // is_blt・(lt_flag・(c_val_1 + c_val_2 * 256) + (1-lt_flag)・instr_size + pc_1 + pc_2 * 256 - carry_1·2^{16} - pc_next_1 - pc_next_2 * 256) =0
         eval.add_constraint(
             is_blt.clone()
                 * (lt_flag.clone() * (value_c[0].clone() + value_c[1].clone() * modulus.clone())
-                    + (E::F::one() - lt_flag.clone()) * E::F::from(4u32.into())
+                    + (E::F::one() - lt_flag.clone()) * instr_size.clone()
                     + pc[0].clone()
                     + pc[1].clone() * modulus.clone()
                     - carry_bits[0].clone() * modulus.clone().pow(2)
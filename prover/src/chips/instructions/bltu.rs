@@ -13,6 +13,7 @@ use crate::{
         ProgramStep, TracesBuilder, Word,
     },
     traits::{ExecuteChip, MachineChip},
+    virtual_column::{self, VirtualColumn},
 };
 
 use super::add::{self};
@@ -42,8 +43,8 @@ impl ExecuteChip for BltuChip {
             // a < b is true: pc_next = pc + imm
             add::add_with_carries(pc, imm)
         } else {
-            // a >= b is true: pc_next = pc + 4
-            add::add_with_carries(pc, 4u32.to_le_bytes())
+            // a >= b is true: pc_next = pc + instr_size
+            add::add_with_carries(pc, program_step.step.instruction.size().to_le_bytes())
         };
 
         let borrow_bits = [borrow_bits[1], borrow_bits[3]];
@@ -101,6 +102,7 @@ impl MachineChip for BltuChip {
         _lookup_elements: &AllLookupElements,
         _config: &ExtensionsConfig,
     ) {
+        let [instr_size] = virtual_column::InstrSize::eval(trace_eval);
         let modulus = E::F::from(256u32.into());
         let value_a = trace_eval!(trace_eval, ValueA);
         let value_b = trace_eval!(trace_eval, ValueB);
@@ -135,11 +137,11 @@ impl MachineChip for BltuChip {
                     - borrow_bits[0].clone()),
         );
 
-        // is_bltu・(ltu_flag・(c_val_1 + c_val_2 * 256) + (1-ltu_flag)・4 + pc_1 + pc_2 * 256 - carry_1·2^{16} - pc_next_1 - pc_next_2 * 256) =0
+        // is_bltu・(ltu_flag・(c_val_1 + c_val_2 * 256) + (1-ltu_flag)・instr_size + pc_1 + pc_2 * 256 - carry_1·2^{16} - pc_next_1 - pc_next_2 * 256) =0
         eval.add_constraint(
             is_bltu.clone()
                 * (ltu_flag.clone() * (value_c[0].clone() + value_c[1].clone() * modulus.clone())
-                    + (E::F::one() - ltu_flag.clone()) * E::F::from(4u32.into())
+                    + (E::F::one() - ltu_flag.clone()) * instr_size.clone()
                     + pc[0].clone()
                     + pc[1].clone() * modulus.clone()
                     - carry_bits[0].clone() * modulus.clone().pow(2)
@@ -19,6 +19,7 @@ use crate::{
         ProgramStep, TracesBuilder, Word,
     },
     traits::{ExecuteChip, MachineChip},
+    virtual_column::{self, VirtualColumn},
 };
 
 use super::add;
@@ -51,7 +52,7 @@ impl ExecuteChip for BneChip {
         let value_b_h = u16::from_le_bytes([value_b[2], value_b[3]]) as u32;
 
         let (pc_next, carry_bits) = if value_a == value_b {
-            add::add_with_carries(pc, 4u32.to_le_bytes())
+            add::add_with_carries(pc, program_step.step.instruction.size().to_le_bytes())
         } else {
             add::add_with_carries(pc, imm)
         };
@@ -164,6 +165,7 @@ impl MachineChip for BneChip {
         _lookup_elements: &AllLookupElements,
         _config: &ExtensionsConfig,
     ) {
+        let [instr_size] = virtual_column::InstrSize::eval(trace_eval);
         let modulus = E::F::from(256u32.into());
         let neq_flag = trace_eval!(trace_eval, Column::Neq);
         let neq_12_flag = trace_eval!(trace_eval, Column::Neq12);
@@ -232,14 +234,14 @@ impl MachineChip for BneChip {
 
         // Setting pc_next based on comparison result
         // pc_next=pc+c_val if neq_flag = 1
-        // pc_next=pc+4 	if neq_flag = 0
+        // pc_next=pc+instr_size 	if neq_flag = 0
         // carry_{2,4} used for carry handling
-        // is_bne・(neq_flag・(c_val_1 + c_val_2 * 256) + (1-neq_flag)・4 + pc_1 + pc_2 * 256 - carry_1·2^{16} - pc_next_1 - pc_next_2 * 256) = 0
+        // is_bne・(neq_flag・(c_val_1 + c_val_2 * 256) + (1-neq_flag)・instr_size + pc_1 + pc_2 * 256 - carry_1·2^{16} - pc_next_1 - pc_next_2 * 256) = 0
         eval.add_constraint(
             is_bne.clone()
                 * (neq_flag[0].clone()
                     * (value_c[0].clone() + value_c[1].clone() * modulus.clone())
-                    + (E::F::one() - neq_flag[0].clone()) * E::F::from(BaseField::from(4u32))
+                    + (E::F::one() - neq_flag[0].clone()) * instr_size.clone()
                     + pc[0].clone()
                     + pc[1].clone() * modulus.clone()
                     - carry_bits[0].clone() * modulus.clone().pow(2)
@@ -0,0 +1,503 @@
+use num_traits::One;
+use stwo_prover::constraint_framework::EvalAtRow;
+
+use nexus_vm::{riscv::BuiltinOpcode, WORD_SIZE};
+
+use crate::{
+    column::Column::*,
+    components::AllLookupElements,
+    extensions::ExtensionsConfig,
+    trace::{
+        eval::{trace_eval, TraceEval},
+        sidenote::SideNote,
+        ProgramStep, TracesBuilder, Word,
+    },
+    traits::{ExecuteChip, MachineChip},
+};
+
+use super::mul::{self, constrain_mul, mul_add_with_carries};
+
+pub struct ExecutionResult {
+    pub quotient: Word,
+    pub remainder: Word,
+    /// Sign of the quotient in quotient・divisor + remainder = dividend.
+    ///
+    /// On signed overflow the quotient 2^31 doesn't fit into i32, so it is treated as unsigned.
+    pub sgn_qt: bool,
+    pub sgn_b: bool,
+    pub sgn_c: bool,
+    pub sgn_rem: bool,
+    pub div_by_zero: bool,
+    pub abs_c: Word,
+    pub abs_rem: Word,
+    /// Carries at the 16-bit boundary when negating the divisor and the remainder.
+    pub abs_carry: [bool; 2],
+    /// |divisor| - |remainder| - 1, zero when dividing by zero.
+    pub rem_diff: Word,
+    pub diff_carry: bool,
+    pub mul: mul::ExecutionResult,
+}
+
+/// Returns the absolute value of a word and the carry at the 16-bit boundary of |x| + x.
+fn abs_with_carry(value: u32, sgn: bool) -> (Word, bool) {
+    if sgn {
+        (value.wrapping_neg().to_le_bytes(), value & 0xffff != 0)
+    } else {
+        (value.to_le_bytes(), false)
+    }
+}
+
+// Support DIV, DIVU, REM and REMU opcodes.
+pub struct DivRemChip;
+
+impl ExecuteChip for DivRemChip {
+    type ExecutionResult = ExecutionResult;
+
+    fn execute(program_step: &ProgramStep) -> Self::ExecutionResult {
+        let opcode = program_step
+            .step
+            .instruction
+            .opcode
+            .builtin()
+            .expect("DivRemChip only supports builtin opcodes");
+        let signed = matches!(opcode, BuiltinOpcode::DIV | BuiltinOpcode::REM);
+
+        let value_b = program_step.get_value_b();
+        let (value_c, _) = program_step.get_value_c();
+        let b = u32::from_le_bytes(value_b);
+        let c = u32::from_le_bytes(value_c);
+
+        let (quotient, remainder, sgn_qt) = if c == 0 {
+            // Division by zero: the quotient has all bits set and the remainder is the dividend.
+            (u32::MAX, b, signed)
+        } else if signed {
+            let (b, c) = (b as i32, c as i32);
+            if b == i32::MIN && c == -1 {
+                // Signed overflow: the quotient is the dividend and the remainder is zero.
+                (b as u32, 0, false)
+            } else {
+                ((b / c) as u32, (b % c) as u32, b / c < 0)
+            }
+        } else {
+            (b / c, b % c, false)
+        };
+
+        let sgn_b = signed && program_step.get_sgn_b();
+        let sgn_c = signed && program_step.get_sgn_c();
+        let sgn_rem = signed && (remainder >> 31) == 1;
+
+        let (abs_c, abs_carry_c) = abs_with_carry(c, sgn_c);
+        let (abs_rem, abs_carry_rem) = abs_with_carry(remainder, sgn_rem);
+
+        let div_by_zero = c == 0;
+        let (rem_diff, diff_carry) = if div_by_zero {
+            (0, false)
+        } else {
+            let abs_c = u32::from_le_bytes(abs_c);
+            let abs_rem = u32::from_le_bytes(abs_rem);
+            let rem_diff = abs_c - abs_rem - 1;
+            let diff_carry = (abs_rem & 0xffff) + 1 + (rem_diff & 0xffff) > 0xffff;
+            (rem_diff, diff_carry)
+        };
+
+        let quotient = quotient.to_le_bytes();
+        let remainder = remainder.to_le_bytes();
+        let mul = mul_add_with_carries(quotient, value_c, sgn_qt, sgn_c, remainder, sgn_rem);
+        debug_assert_eq!(
+            u64::from_le_bytes(mul.product),
+            if sgn_b { b as i32 as u64 } else { b as u64 },
+        );
+
+        ExecutionResult {
+            quotient,
+            remainder,
+            sgn_qt,
+            sgn_b,
+            sgn_c,
+            sgn_rem,
+            div_by_zero,
+            abs_c,
+            abs_rem,
+            abs_carry: [abs_carry_c, abs_carry_rem],
+            rem_diff: rem_diff.to_le_bytes(),
+            diff_carry,
+            mul,
+        }
+    }
+}
+
+impl MachineChip for DivRemChip {
+    fn fill_main_trace(
+        traces: &mut TracesBuilder,
+        row_idx: usize,
+        vm_step: &Option<ProgramStep>,
+        _side_note: &mut SideNote,
+        _config: &ExtensionsConfig,
+    ) {
+        let vm_step = match vm_step {
+            Some(vm_step) => vm_step,
+            None => return,
+        };
+        let opcode = match vm_step.step.instruction.opcode.builtin() {
+            Some(
+                opcode @ (BuiltinOpcode::DIV
+                | BuiltinOpcode::DIVU
+                | BuiltinOpcode::REM
+                | BuiltinOpcode::REMU),
+            ) => opcode,
+            _ => return,
+        };
+
+        let ExecutionResult {
+            quotient,
+            remainder,
+            sgn_qt,
+            sgn_b,
+            sgn_c,
+            sgn_rem,
+            div_by_zero,
+            abs_c,
+            abs_rem,
+            abs_carry,
+            rem_diff,
+            diff_carry,
+            mul,
+        } = Self::execute(vm_step);
+
+        let result = match opcode {
+            BuiltinOpcode::DIV | BuiltinOpcode::DIVU => quotient,
+            _ => remainder,
+        };
+        assert_eq!(result, vm_step.get_result().expect("DIV must have result"));
+
+        // Fill the operands without their sign bits for signed division
+        if matches!(opcode, BuiltinOpcode::DIV | BuiltinOpcode::REM) {
+            let mut helper_b = vm_step.get_value_b();
+            helper_b[WORD_SIZE - 1] &= 0x7f;
+            traces.fill_columns(row_idx, helper_b, Helper2);
+
+            let (mut helper_c, _) = vm_step.get_value_c();
+            helper_c[WORD_SIZE - 1] &= 0x7f;
+            traces.fill_columns(row_idx, helper_c, Helper3);
+
+            let mut helper_rem = remainder;
+            helper_rem[WORD_SIZE - 1] &= 0x7f;
+            traces.fill_columns(row_idx, helper_rem, Helper1);
+        }
+
+        traces.fill_columns(row_idx, sgn_b, SgnB);
+        traces.fill_columns(row_idx, sgn_c, SgnC);
+        traces.fill_columns(row_idx, sgn_qt, SgnQt);
+        traces.fill_columns(row_idx, sgn_rem, SgnRem);
+        traces.fill_columns(row_idx, div_by_zero, DivByZero);
+        traces.fill_columns(row_idx, quotient, Qt);
+        traces.fill_columns(row_idx, remainder, Rem);
+        traces.fill_columns(row_idx, abs_c, AbsValueC);
+        traces.fill_columns(row_idx, abs_rem, AbsRem);
+        traces.fill_columns(row_idx, abs_carry, AbsCarry);
+        traces.fill_columns(row_idx, rem_diff, RemDiff);
+        traces.fill_columns(row_idx, [diff_carry, false], CarryFlag);
+        traces.fill_columns(row_idx, mul.carry_low, MulCarryLow);
+        traces.fill_columns(row_idx, mul.carry_high, MulCarryHigh);
+        traces.fill_columns(row_idx, result, ValueA);
+    }
+
+    fn add_constraints<E: EvalAtRow>(
+        eval: &mut E,
+        trace_eval: &TraceEval<E>,
+        _lookup_elements: &AllLookupElements,
+        _config: &ExtensionsConfig,
+    ) {
+        let [is_div] = trace_eval!(trace_eval, IsDiv);
+        let [is_divu] = trace_eval!(trace_eval, IsDivu);
+        let [is_rem] = trace_eval!(trace_eval, IsRem);
+        let [is_remu] = trace_eval!(trace_eval, IsRemu);
+        let is_signed = is_div.clone() + is_rem.clone();
+        let is_unsigned = is_divu.clone() + is_remu.clone();
+        let is_div_rem = is_signed.clone() + is_unsigned.clone();
+
+        // modulus for 8-bit limbs
+        let modulus = E::F::from(256u32.into());
+        // modulus for 16-bit chunks
+        let modulus_16 = E::F::from((1u32 << 16).into());
+        // modulues for 7-bit
+        let modulus_7 = E::F::from(128u32.into());
+
+        let value_a = trace_eval!(trace_eval, ValueA);
+        let value_b = trace_eval!(trace_eval, ValueB);
+        let value_c = trace_eval!(trace_eval, ValueC);
+        let qt = trace_eval!(trace_eval, Qt);
+        let rem = trace_eval!(trace_eval, Rem);
+        let [sgn_b] = trace_eval!(trace_eval, SgnB);
+        let [sgn_c] = trace_eval!(trace_eval, SgnC);
+        let [sgn_qt] = trace_eval!(trace_eval, SgnQt);
+        let [sgn_rem] = trace_eval!(trace_eval, SgnRem);
+        let [div_by_zero] = trace_eval!(trace_eval, DivByZero);
+        let helper1_val = trace_eval!(trace_eval, Helper1);
+        let helper2_val = trace_eval!(trace_eval, Helper2);
+        let helper3_val = trace_eval!(trace_eval, Helper3);
+        let abs_c = trace_eval!(trace_eval, AbsValueC);
+        let abs_rem = trace_eval!(trace_eval, AbsRem);
+        let abs_carry = trace_eval!(trace_eval, AbsCarry);
+        let rem_diff = trace_eval!(trace_eval, RemDiff);
+        let [diff_carry, _] = trace_eval!(trace_eval, CarryFlag);
+        let carry_low = trace_eval!(trace_eval, MulCarryLow);
+        let carry_high = trace_eval!(trace_eval, MulCarryHigh);
+
+        // Sign bits of signed operands
+        // (is_div + is_rem) * (h2[3] + sgn_b * 2^7 - b_val[3]) = 0
+        // (is_div + is_rem) * (h3[3] + sgn_c * 2^7 - c_val[3]) = 0
+        // (is_div + is_rem) * (h1[3] + sgn_rem * 2^7 - rem[3]) = 0
+        eval.add_constraint(
+            is_signed.clone()
+                * (modulus_7.clone() * sgn_b.clone() + helper2_val[3].clone() - value_b[3].clone()),
+        );
+        eval.add_constraint(
+            is_signed.clone()
+                * (modulus_7.clone() * sgn_c.clone() + helper3_val[3].clone() - value_c[3].clone()),
+        );
+        eval.add_constraint(
+            is_signed.clone()
+                * (modulus_7.clone() * sgn_rem.clone() + helper1_val[3].clone() - rem[3].clone()),
+        );
+
+        // Unsigned operands are never sign-extended
+        // (is_divu + is_remu) * sgn_b = 0
+        // (is_divu + is_remu) * sgn_c = 0
+        // (is_divu + is_remu) * sgn_qt = 0
+        // (is_divu + is_remu) * sgn_rem = 0
+        for sgn in [&sgn_b, &sgn_c, &sgn_qt, &sgn_rem] {
+            eval.add_constraint(is_unsigned.clone() * sgn.clone());
+        }
+
+        // qt・c_val + rem = b_val, in 64 bits with sign-extended operands
+        let sign_extended_b: [E::F; 2 * WORD_SIZE] = std::array::from_fn(|i| {
+            if i < WORD_SIZE {
+                value_b[i].clone()
+            } else {
+                sgn_b.clone() * E::F::from(255u32.into())
+            }
+        });
+        constrain_mul(
+            eval,
+            is_div_rem.clone(),
+            &qt,
+            &value_c,
+            sgn_qt,
+            sgn_c.clone(),
+            Some((&rem, sgn_rem.clone())),
+            &sign_extended_b,
+            &carry_low,
+            &carry_high,
+        );
+
+        // Division by zero results in a quotient with all bits set
+        // (is_div + is_divu + is_rem + is_remu) * div_by_zero * c_val[i] = 0
+        // (is_div + is_divu + is_rem + is_remu) * div_by_zero * (qt[i] - 255) = 0
+        for i in 0..WORD_SIZE {
+            eval.add_constraint(is_div_rem.clone() * div_by_zero.clone() * value_c[i].clone());
+            eval.add_constraint(
+                is_div_rem.clone()
+                    * div_by_zero.clone()
+                    * (qt[i].clone() - E::F::from(255u32.into())),
+            );
+        }
+
+        // Absolute values of the divisor and the remainder
+        // (is_div + is_divu + is_rem + is_remu) * (1 - sgn) * (abs[i] - val[i]) = 0
+        // (is_div + is_divu + is_rem + is_remu) * sgn * (abs[0] + abs[1]・2^8 + val[0] + val[1]・2^8 - abs_carry・2^16) = 0
+        // (is_div + is_divu + is_rem + is_remu) * sgn * (abs[2] + abs[3]・2^8 + val[2] + val[3]・2^8 + abs_carry - 2^16) = 0
+        for (abs, val, sgn, carry) in [
+            (&abs_c, &value_c, &sgn_c, &abs_carry[0]),
+            (&abs_rem, &rem, &sgn_rem, &abs_carry[1]),
+        ] {
+            for i in 0..WORD_SIZE {
+                eval.add_constraint(
+                    is_div_rem.clone()
+                        * (E::F::one() - sgn.clone())
+                        * (abs[i].clone() - val[i].clone()),
+                );
+            }
+            eval.add_constraint(
+                is_div_rem.clone()
+                    * sgn.clone()
+                    * (abs[0].clone()
+                        + abs[1].clone() * modulus.clone()
+                        + val[0].clone()
+                        + val[1].clone() * modulus.clone()
+                        - carry.clone() * modulus_16.clone()),
+            );
+            eval.add_constraint(
+                is_div_rem.clone()
+                    * sgn.clone()
+                    * (abs[2].clone()
+                        + abs[3].clone() * modulus.clone()
+                        + val[2].clone()
+                        + val[3].clone() * modulus.clone()
+                        + carry.clone()
+                        - modulus_16.clone()),
+            );
+        }
+
+        // |rem| < |c_val| unless dividing by zero, i.e. |rem| + 1 + rem_diff = |c_val| without overflow
+        // (is_div + is_divu + is_rem + is_remu) * (1 - div_by_zero) *
+        //      (abs_rem[0] + abs_rem[1]・2^8 + 1 + rem_diff[0] + rem_diff[1]・2^8 - abs_c[0] - abs_c[1]・2^8 - diff_carry・2^16) = 0
+        // (is_div + is_divu + is_rem + is_remu) * (1 - div_by_zero) *
+        //      (abs_rem[2] + abs_rem[3]・2^8 + rem_diff[2] + rem_diff[3]・2^8 + diff_carry - abs_c[2] - abs_c[3]・2^8) = 0
+        eval.add_constraint(
+            is_div_rem.clone()
+                * (E::F::one() - div_by_zero.clone())
+                * (abs_rem[0].clone()
+                    + abs_rem[1].clone() * modulus.clone()
+                    + E::F::one()
+                    + rem_diff[0].clone()
+                    + rem_diff[1].clone() * modulus.clone()
+                    - abs_c[0].clone()
+                    - abs_c[1].clone() * modulus.clone()
+                    - diff_carry.clone() * modulus_16.clone()),
+        );
+        eval.add_constraint(
+            is_div_rem.clone()
+                * (E::F::one() - div_by_zero.clone())
+                * (abs_rem[2].clone()
+                    + abs_rem[3].clone() * modulus.clone()
+                    + rem_diff[2].clone()
+                    + rem_diff[3].clone() * modulus.clone()
+                    + diff_carry.clone()
+                    - abs_c[2].clone()
+                    - abs_c[3].clone() * modulus.clone()),
+        );
+
+        // The remainder is zero or has the sign of the dividend
+        // (is_div + is_rem) * sgn_rem * (1 - sgn_b) = 0
+        // (is_div + is_rem) * sgn_b * (1 - sgn_rem) * (rem[0] + rem[1] + rem[2] + rem[3]) = 0
+        eval.add_constraint(is_signed.clone() * sgn_rem.clone() * (E::F::one() - sgn_b.clone()));
+        eval.add_constraint(
+            is_signed.clone()
+                * sgn_b.clone()
+                * (E::F::one() - sgn_rem.clone())
+                * (rem[0].clone() + rem[1].clone() + rem[2].clone() + rem[3].clone()),
+        );
+
+        // (is_div + is_divu) * (a_val[i] - qt[i]) = 0
+        // (is_rem + is_remu) * (a_val[i] - rem[i]) = 0
+        for i in 0..WORD_SIZE {
+            eval.add_constraint(
+                (is_div.clone() + is_divu.clone()) * (value_a[i].clone() - qt[i].clone()),
+            );
+            eval.add_constraint(
+                (is_rem.clone() + is_remu.clone()) * (value_a[i].clone() - rem[i].clone()),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        chips::{
+            AddChip, CpuChip, DecodingCheckChip, ProgramMemCheckChip, RangeCheckChip,
+            RegisterMemCheckChip, SubChip,
+        },
+        test_utils::assert_chip,
+        trace::{
+            preprocessed::PreprocessedBuilder, program::iter_program_steps,
+            program_trace::ProgramTracesBuilder,
+        },
+    };
+
+    use super::*;
+    use nexus_vm::{
+        emulator::InternalView,
+        riscv::{BasicBlock, BuiltinOpcode, Instruction, Opcode},
+        trace::k_trace_direct,
+    };
+
+    const LOG_SIZE: u32 = PreprocessedBuilder::MIN_LOG_SIZE;
+
+    fn setup_basic_block_ir() -> Vec<BasicBlock> {
+        let mut instructions = vec![
+            // x1 = 2000
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::ADDI), 1, 0, 2000),
+            // x2 = 7
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::ADDI), 2, 0, 7),
+            // x3 = -2000
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::SUB), 3, 0, 1),
+            // x4 = -7
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::SUB), 4, 0, 2),
+            // x5 = -1
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::ADDI), 5, 0, 1),
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::SUB), 5, 0, 5),
+            // x6 = 0x80000000 (smallest negative 32-bit number)
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::ADDI), 6, 0, 1),
+        ];
+        instructions.extend(
+            (0..31).map(|_| Instruction::new_ir(Opcode::from(BuiltinOpcode::ADD), 6, 6, 6)),
+        );
+        for opcode in [
+            BuiltinOpcode::DIV,
+            BuiltinOpcode::DIVU,
+            BuiltinOpcode::REM,
+            BuiltinOpcode::REMU,
+        ] {
+            instructions.extend([
+                // Positive by positive
+                Instruction::new_ir(Opcode::from(opcode), 7, 1, 2),
+                // Negative by positive
+                Instruction::new_ir(Opcode::from(opcode), 8, 3, 2),
+                // Positive by negative
+                Instruction::new_ir(Opcode::from(opcode), 9, 1, 4),
+                // Negative by negative
+                Instruction::new_ir(Opcode::from(opcode), 10, 3, 4),
+                // Divisor larger than the dividend
+                Instruction::new_ir(Opcode::from(opcode), 11, 2, 1),
+                // Division by zero
+                Instruction::new_ir(Opcode::from(opcode), 12, 1, 0),
+                Instruction::new_ir(Opcode::from(opcode), 13, 3, 0),
+                // Signed overflow
+                Instruction::new_ir(Opcode::from(opcode), 14, 6, 5),
+                // Smallest negative number by itself
+                Instruction::new_ir(Opcode::from(opcode), 15, 6, 6),
+            ]);
+        }
+        vec![BasicBlock::new(instructions)]
+    }
+
+    #[test]
+    fn test_k_trace_constrained_div_rem_instructions() {
+        let basic_block = setup_basic_block_ir();
+        let k = 1;
+        type Chips = (
+            CpuChip,
+            DecodingCheckChip,
+            AddChip,
+            SubChip,
+            DivRemChip,
+            RegisterMemCheckChip,
+            ProgramMemCheckChip,
+            RangeCheckChip,
+        );
+
+        // Get traces from VM K-Trace interface
+        let (view, vm_traces) = k_trace_direct(&basic_block, k).expect("Failed to create trace");
+        let program_info = view.get_program_memory();
+
+        // Trace circuit
+        let mut traces = TracesBuilder::new(LOG_SIZE);
+        let program_steps = iter_program_steps(&vm_traces, traces.num_rows());
+        let program_traces = ProgramTracesBuilder::new_with_empty_memory(LOG_SIZE, program_info);
+        let mut side_note = SideNote::new(&program_traces, &view);
+
+        // We iterate each block in the trace for each instruction
+        for (row_idx, program_step) in program_steps.enumerate() {
+            Chips::fill_main_trace(
+                &mut traces,
+                row_idx,
+                &program_step,
+                &mut side_note,
+                &ExtensionsConfig::default(),
+            );
+        }
+        assert_chip::<Chips>(traces, Some(program_traces.finalize()));
+    }
+}
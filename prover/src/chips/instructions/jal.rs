@@ -12,6 +12,7 @@ use crate::{
         ProgramStep, TracesBuilder, Word,
     },
     traits::{ExecuteChip, MachineChip},
+    virtual_column::{self, VirtualColumn},
 };
 
 use super::add;
@@ -33,9 +34,10 @@ impl ExecuteChip for JalChip {
         let pc = program_step.step.pc.to_le_bytes();
 
         // 1. Compute pc_next = pc + imm
-        // 2. value_a = pc + 4
+        // 2. value_a = pc + instr_size
         let (pc_next, pc_carry_bits) = add::add_with_carries(pc, imm);
-        let (value_a, carry_bits) = add::add_with_carries(pc, 4u32.to_le_bytes());
+        let (value_a, carry_bits) =
+            add::add_with_carries(pc, program_step.step.instruction.size().to_le_bytes());
 
         let pc_carry_bits = [pc_carry_bits[1], pc_carry_bits[3]];
         let carry_bits = [carry_bits[1], carry_bits[3]];
@@ -90,6 +92,7 @@ impl MachineChip for JalChip {
         _lookup_elements: &AllLookupElements,
         _config: &ExtensionsConfig,
     ) {
+        let [instr_size] = virtual_column::InstrSize::eval(trace_eval);
         let modulus = E::F::from(256u32.into());
         let value_a = trace_eval!(trace_eval, ValueA);
         let value_c = trace_eval!(trace_eval, ValueC);
@@ -99,12 +102,12 @@ impl MachineChip for JalChip {
         let pc_next = trace_eval!(trace_eval, Column::PcNext);
         let [is_jal] = trace_eval!(trace_eval, Column::IsJal);
 
-        // a_val=pc+4
+        // a_val=pc+instr_size
         // carry1_{2,4} used for carry handling
-        // is_jal・(4 + pc_1 + pc_2 * 256 - carry1_1·2^{16} - a_val_1 - a_val_2 * 256) = 0
+        // is_jal・(instr_size + pc_1 + pc_2 * 256 - carry1_1·2^{16} - a_val_1 - a_val_2 * 256) = 0
         eval.add_constraint(
             is_jal.clone()
-                * (E::F::from(4.into()) + pc[0].clone() + pc[1].clone() * modulus.clone()
+                * (instr_size.clone() + pc[0].clone() + pc[1].clone() * modulus.clone()
                     - carry_bits[0].clone() * modulus.clone().pow(2)
                     - value_a[0].clone()
                     - value_a[1].clone() * modulus.clone()),
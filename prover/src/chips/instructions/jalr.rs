@@ -12,6 +12,7 @@ use crate::{
         ProgramStep, TracesBuilder, Word,
     },
     traits::{ExecuteChip, MachineChip},
+    virtual_column::{self, VirtualColumn},
 };
 
 use super::add;
@@ -38,7 +39,7 @@ impl ExecuteChip for JalrChip {
 
         // 1. Compute pc_next_aux = value_b + imm
         // 2. pc_next = qt_aux * 2 = pc_next_aux & 0xFFFF_FFFE
-        // 3. value_a = pc + 4
+        // 3. value_a = pc + instr_size
         let (pc_next_aux, pc_carry_bits) = add::add_with_carries(value_b, imm);
         let mut pc_next = pc_next_aux;
 
@@ -52,7 +53,8 @@ impl ExecuteChip for JalrChip {
         // To ensure 2*qt_aux = pc_next
         let qt_aux = pc_next[0] >> 1;
 
-        let (value_a, carry_bits) = add::add_with_carries(pc, 4u32.to_le_bytes());
+        let (value_a, carry_bits) =
+            add::add_with_carries(pc, program_step.step.instruction.size().to_le_bytes());
 
         let pc_carry_bits = [pc_carry_bits[1], pc_carry_bits[3]];
         let carry_bits = [carry_bits[1], carry_bits[3]];
@@ -119,6 +121,7 @@ impl MachineChip for JalrChip {
         _lookup_elements: &AllLookupElements,
         _config: &ExtensionsConfig,
     ) {
+        let [instr_size] = virtual_column::InstrSize::eval(trace_eval);
         let modulus = E::F::from(256u32.into());
         let value_a = trace_eval!(trace_eval, ValueA);
         let value_b = trace_eval!(trace_eval, ValueB);
@@ -132,14 +135,14 @@ impl MachineChip for JalrChip {
         let [qt_aux] = trace_eval!(trace_eval, Column::QtAux);
         let [is_jalr] = trace_eval!(trace_eval, Column::IsJalr);
 
-        // a_val=pc+4
+        // a_val=pc+instr_size
         // carry1_{1,2,3,4} used for carry handling
-        // is_jalr・(4 + pc_1 + pc_2 * 256 - carry1_1·2^{16} - a_val_1 - a_val_2 * 256) = 0
+        // is_jalr・(instr_size + pc_1 + pc_2 * 256 - carry1_1·2^{16} - a_val_1 - a_val_2 * 256) = 0
         // is_jalr・(pc_3 + pc_3 * 256 + carry1_1 - carry1_2·2^{16} - a_val_3 - a_val_4 * 256) = 0
 
         eval.add_constraint(
             is_jalr.clone()
-                * (E::F::from(4.into()) + pc[0].clone() + pc[1].clone() * modulus.clone()
+                * (instr_size.clone() + pc[0].clone() + pc[1].clone() * modulus.clone()
                     - carry_bits[0].clone() * modulus.clone().pow(2)
                     - value_a[0].clone()
                     - value_a[1].clone() * modulus.clone()),
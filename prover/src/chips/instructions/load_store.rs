@@ -67,6 +67,30 @@ pub struct LoadStoreChip;
 const LOOKUP_TUPLE_SIZE: usize = 2 * WORD_SIZE_HALVED + 1;
 stwo_prover::relation!(LoadStoreLookupElements, LOOKUP_TUPLE_SIZE);
 
+/// Final bytes of the public output as (addr_low, addr_high, value).
+///
+/// The RAM init/final component consumes an entry for every byte of the exit code and the public output, and the
+/// verifier adds the matching entries from the claimed values, so that they aren't a part of the preprocessed trace.
+const PUBLIC_OUTPUT_TUPLE_SIZE: usize = WORD_SIZE_HALVED + 1;
+stwo_prover::relation!(PublicOutputLookupElements, PUBLIC_OUTPUT_TUPLE_SIZE);
+
+/// Access mode of a byte under the RW memory checking.
+///
+/// The mode is encoded in the high half of the address in memory checking tuples, so that all accesses to a byte
+/// use the mode it was initialized with. Loads are only allowed on read-write and read-only bytes, and stores are
+/// only allowed on read-write and write-only bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum AccessMode {
+    ReadWrite = 0,
+    ReadOnly = 1,
+    WriteOnly = 2,
+}
+
+impl AccessMode {
+    /// The multiplier of the mode in the high half of the address, the address half is a 16-bit value.
+    pub(crate) const ADDR_HIGH_SHIFT: u32 = 1 << 16;
+}
+
 impl MachineChip for LoadStoreChip {
     fn draw_lookup_elements(
         all_elements: &mut AllLookupElements,
@@ -74,6 +98,7 @@ impl MachineChip for LoadStoreChip {
         _config: &ExtensionsConfig,
     ) {
         all_elements.insert(LoadStoreLookupElements::draw(channel));
+        all_elements.insert(PublicOutputLookupElements::draw(channel));
     }
 
     fn fill_main_trace(
@@ -121,6 +146,23 @@ impl MachineChip for LoadStoreChip {
             add_with_carries(value_a, offset)
         };
         traces.fill_columns(row_idx, ram_base_address, Column::RamBaseAddr);
+        let access_mode = side_note
+            .rw_mem_check
+            .access_mode(u32::from_le_bytes(ram_base_address));
+        if is_load {
+            assert_ne!(
+                access_mode,
+                AccessMode::WriteOnly,
+                "load from write-only memory"
+            );
+        } else {
+            assert_ne!(
+                access_mode,
+                AccessMode::ReadOnly,
+                "store to read-only memory"
+            );
+        }
+        traces.fill_columns(row_idx, access_mode as u8, Column::RamAccessMode);
         let carry_bits = [carry_bits[1], carry_bits[3]];
         traces.fill_columns(row_idx, carry_bits, Column::CarryFlag);
         let clk = row_idx as u32 + 1;
@@ -202,15 +244,19 @@ impl MachineChip for LoadStoreChip {
             .take(size)
             .enumerate()
             {
-                let prev_access = side_note.rw_mem_check.last_access.insert(
-                    byte_address
-                        .checked_add(i as u32)
-                        .expect("memory access range overflowed back to address zero"),
-                    (clk, cur_value[i]),
+                let address = byte_address
+                    .checked_add(i as u32)
+                    .expect("memory access range overflowed back to address zero");
+                assert_eq!(
+                    side_note.rw_mem_check.access_mode(address),
+                    access_mode,
+                    "memory access crosses segments with different access modes at address 0x{:x}",
+                    address,
                 );
+                let prev_access = side_note.rw_mem_check.access(address, clk, cur_value[i]);
                 let (prev_timestamp, prev_val) = prev_access.unwrap_or((0, 0));
                 // If it's LOAD, the vm and the prover need to agree on the previous value
-                if is_load {
+                if is_load && !side_note.rw_mem_check.speculative {
                     assert_eq!(
                         prev_val,
                         prev_value[i],
@@ -505,6 +551,21 @@ impl MachineChip for LoadStoreChip {
                     + carry_flag[1].clone() * BaseField::from(1 << 16)),
         );
 
+        // Loads are allowed on read-write and read-only memory, stores on read-write and write-only memory.
+        let [access_mode] = trace_eval!(trace_eval, Column::RamAccessMode);
+        // is_load * access_mode * (access_mode - 1) = 0
+        eval.add_constraint(
+            is_load.clone()
+                * access_mode.clone()
+                * (access_mode.clone() - E::F::from(BaseField::from(AccessMode::ReadOnly as u32))),
+        );
+        // is_store * access_mode * (access_mode - 2) = 0
+        eval.add_constraint(
+            is_store.clone()
+                * access_mode.clone()
+                * (access_mode - E::F::from(BaseField::from(AccessMode::WriteOnly as u32))),
+        );
+
         let [ram1_val_prev] = trace_eval!(trace_eval, Ram1ValPrev);
         let [ram2_val_prev] = trace_eval!(trace_eval, Ram2ValPrev);
         let [ram1_val_cur] = trace_eval!(trace_eval, Ram1ValCur);
@@ -726,6 +787,7 @@ impl LoadStoreChip {
         let [val_prev] = original_traces.get_base_column(val_prev);
         let ts_prev = original_traces.get_base_column::<WORD_SIZE>(ts_prev);
         let base_address = original_traces.get_base_column::<WORD_SIZE>(Column::RamBaseAddr);
+        let [access_mode] = original_traces.get_base_column(Column::RamAccessMode);
         // Subtract previous tuple
         let mut logup_col_gen = logup_trace_gen.new_col();
         for vec_row in 0..(1 << (original_traces.log_size() - LOG_N_LANES)) {
@@ -736,7 +798,9 @@ impl LoadStoreChip {
                 + PackedBaseField::broadcast(BaseField::from(address_offset as u32))
                 + base_address[1].data[vec_row] * PackedBaseField::broadcast((1 << 8).into());
             let addr_high = base_address[2].data[vec_row]
-                + base_address[3].data[vec_row] * PackedBaseField::broadcast((1 << 8).into());
+                + base_address[3].data[vec_row] * PackedBaseField::broadcast((1 << 8).into())
+                + access_mode.data[vec_row]
+                    * PackedBaseField::broadcast(AccessMode::ADDR_HIGH_SHIFT.into());
             tuple.push(addr_low);
             tuple.push(addr_high);
 
@@ -771,14 +835,16 @@ impl LoadStoreChip {
         let ts_prev = trace_eval.column_eval::<WORD_SIZE>(ts_prev);
         let [accessed] = Accessed::eval(trace_eval);
         let base_address = trace_eval!(trace_eval, Column::RamBaseAddr);
+        let [access_mode] = trace_eval!(trace_eval, Column::RamAccessMode);
         let mut tuple = vec![];
         // The least significant byte of the address is base_address[0] + address_offset
         // Adding an offset without carry is correct because of memory alignment.
         let addr_low = base_address[0].clone()
             + E::F::from(BaseField::from(address_offset as u32))
             + base_address[1].clone() * E::F::from((1 << 8).into());
-        let addr_high =
-            base_address[2].clone() + base_address[3].clone() * E::F::from((1 << 8).into());
+        let addr_high = base_address[2].clone()
+            + base_address[3].clone() * E::F::from((1 << 8).into())
+            + access_mode * E::F::from(AccessMode::ADDR_HIGH_SHIFT.into());
         tuple.push(addr_low);
         tuple.push(addr_high);
 
@@ -808,6 +874,7 @@ impl LoadStoreChip {
     ) {
         let [val_cur] = original_traces.get_base_column(val_cur);
         let base_address = original_traces.get_base_column::<WORD_SIZE>(Column::RamBaseAddr);
+        let [access_mode] = original_traces.get_base_column(Column::RamAccessMode);
         // Add current tuple
         let clk =
             preprocessed_traces.get_preprocessed_base_column::<WORD_SIZE>(PreprocessedColumn::Clk);
@@ -820,7 +887,9 @@ impl LoadStoreChip {
                 + PackedBaseField::broadcast(BaseField::from(address_offset as u32))
                 + base_address[1].data[vec_row] * PackedBaseField::broadcast((1 << 8).into());
             let addr_high = base_address[2].data[vec_row]
-                + base_address[3].data[vec_row] * PackedBaseField::broadcast((1 << 8).into());
+                + base_address[3].data[vec_row] * PackedBaseField::broadcast((1 << 8).into())
+                + access_mode.data[vec_row]
+                    * PackedBaseField::broadcast(AccessMode::ADDR_HIGH_SHIFT.into());
             tuple.push(addr_low);
             tuple.push(addr_high);
 
@@ -854,6 +923,7 @@ impl LoadStoreChip {
         let [val_cur] = trace_eval.column_eval(val_cur);
         let [accessed] = Accessed::eval(trace_eval);
         let base_address = trace_eval!(trace_eval, Column::RamBaseAddr);
+        let [access_mode] = trace_eval!(trace_eval, Column::RamAccessMode);
         let clk = preprocessed_trace_eval!(trace_eval, PreprocessedColumn::Clk);
         let mut tuple = vec![];
         // The least significant byte of the address is base_address[0] + address_offset
@@ -861,8 +931,9 @@ impl LoadStoreChip {
         let addr_low = base_address[0].clone()
             + E::F::from(BaseField::from(address_offset as u32))
             + base_address[1].clone() * E::F::from((1 << 8).into());
-        let addr_high =
-            base_address[2].clone() + base_address[3].clone() * E::F::from((1 << 8).into());
+        let addr_high = base_address[2].clone()
+            + base_address[3].clone() * E::F::from((1 << 8).into())
+            + access_mode * E::F::from(AccessMode::ADDR_HIGH_SHIFT.into());
         tuple.push(addr_low);
         tuple.push(addr_high);
 
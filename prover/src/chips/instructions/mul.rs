@@ -0,0 +1,328 @@
+use num_traits::Zero;
+use stwo_prover::constraint_framework::EvalAtRow;
+
+use nexus_vm::{riscv::BuiltinOpcode, WORD_SIZE};
+
+use crate::{
+    column::Column::*,
+    components::AllLookupElements,
+    extensions::ExtensionsConfig,
+    trace::{
+        eval::{trace_eval, TraceEval},
+        sidenote::SideNote,
+        ProgramStep, TracesBuilder, Word,
+    },
+    traits::{ExecuteChip, MachineChip},
+};
+
+pub struct ExecutionResult {
+    /// Little-endian bytes of the 64-bit product; the lower word is the first four bytes.
+    pub product: [u8; 2 * WORD_SIZE],
+    /// Lower eight bits of the carry at each 16-bit boundary of the product.
+    pub carry_low: Word,
+    /// Upper bits of the carry at each 16-bit boundary of the product.
+    pub carry_high: Word,
+}
+
+/// Multiplies two words limb by limb, treating each operand as signed when its flag is set.
+///
+/// The operands are sign-extended to eight bytes and the schoolbook convolution is truncated
+/// to the lower 64 bits, which equals the 64-bit product in two's complement. The sum of every
+/// pair of limbs is reduced with a carry at each 16-bit boundary. Carries never exceed 2039,
+/// so they fit into eleven bits.
+pub(crate) fn mul_with_carries(b: Word, c: Word, sgn_b: bool, sgn_c: bool) -> ExecutionResult {
+    mul_add_with_carries(b, c, sgn_b, sgn_c, [0; WORD_SIZE], false)
+}
+
+/// Computes b・c + d in 64 bits in the same way as [`mul_with_carries`], with d sign-extended when `sgn_d` is set.
+///
+/// Adding d increases each carry by at most one, so carries still fit into eleven bits.
+pub(crate) fn mul_add_with_carries(
+    b: Word,
+    c: Word,
+    sgn_b: bool,
+    sgn_c: bool,
+    d: Word,
+    sgn_d: bool,
+) -> ExecutionResult {
+    let extend = |word: Word, sgn: bool| -> [u32; 2 * WORD_SIZE] {
+        std::array::from_fn(|i| {
+            if i < WORD_SIZE {
+                word[i] as u32
+            } else if sgn {
+                0xff
+            } else {
+                0
+            }
+        })
+    };
+    let ext_b = extend(b, sgn_b);
+    let ext_c = extend(c, sgn_c);
+    let ext_d = extend(d, sgn_d);
+
+    // Convolution of the limbs, truncated to the lower eight bytes.
+    let conv: [u32; 2 * WORD_SIZE] =
+        std::array::from_fn(|k| (0..=k).map(|i| ext_b[i] * ext_c[k - i]).sum::<u32>() + ext_d[k]);
+
+    let mut product = [0u8; 2 * WORD_SIZE];
+    let mut carry_low = [0u8; WORD_SIZE];
+    let mut carry_high = [0u8; WORD_SIZE];
+    let mut carry = 0u32;
+    for k in 0..WORD_SIZE {
+        let sum = conv[2 * k] + (conv[2 * k + 1] << 8) + carry;
+        product[2 * k] = (sum & 0xff) as u8;
+        product[2 * k + 1] = ((sum >> 8) & 0xff) as u8;
+        carry = sum >> 16;
+        carry_low[k] = (carry & 0xff) as u8;
+        carry_high[k] = (carry >> 8) as u8;
+    }
+
+    ExecutionResult {
+        product,
+        carry_low,
+        carry_high,
+    }
+}
+
+/// Constrains `product` to hold the lowest `2 * num_chunks` bytes of the product of sign-extended `value_b` and `value_c`,
+/// plus the sign-extended `addend` when it is given.
+///
+/// For each 16-bit chunk k:
+/// selector・(p[2k] + p[2k+1]・2^8 + carry[k]・2^16 - (s[2k] + s[2k+1]・2^8 + carry[k-1])) = 0
+/// where s[k] = sum_i ext_b[i]・ext_c[k-i] + ext_d[k], ext_b[i] = sgn_b・255 for i >= 4, and carry[k] = carry_low[k] + carry_high[k]・2^8.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn constrain_mul<E: EvalAtRow>(
+    eval: &mut E,
+    selector: E::F,
+    value_b: &[E::F; WORD_SIZE],
+    value_c: &[E::F; WORD_SIZE],
+    sgn_b: E::F,
+    sgn_c: E::F,
+    addend: Option<(&[E::F; WORD_SIZE], E::F)>,
+    product: &[E::F],
+    carry_low: &[E::F; WORD_SIZE],
+    carry_high: &[E::F; WORD_SIZE],
+) {
+    let modulus = E::F::from(256u32.into());
+    let modulus_16 = E::F::from((1u32 << 16).into());
+
+    let extend = |word: &[E::F; WORD_SIZE], sgn: &E::F, i: usize| -> E::F {
+        if i < WORD_SIZE {
+            word[i].clone()
+        } else {
+            sgn.clone() * E::F::from(255u32.into())
+        }
+    };
+    let conv = |k: usize| -> E::F {
+        let init = match &addend {
+            Some((value_d, sgn_d)) => extend(value_d, sgn_d, k),
+            None => E::F::zero(),
+        };
+        (0..=k).fold(init, |acc, i| {
+            acc + extend(value_b, &sgn_b, i) * extend(value_c, &sgn_c, k - i)
+        })
+    };
+
+    let num_chunks = product.len() / 2;
+    let mut carry_prev = E::F::zero();
+    for k in 0..num_chunks {
+        let carry = carry_low[k].clone() + carry_high[k].clone() * modulus.clone();
+        eval.add_constraint(
+            selector.clone()
+                * (product[2 * k].clone()
+                    + product[2 * k + 1].clone() * modulus.clone()
+                    + carry.clone() * modulus_16.clone()
+                    - (conv(2 * k) + conv(2 * k + 1) * modulus.clone() + carry_prev)),
+        );
+        carry_prev = carry;
+    }
+}
+
+// Support MUL opcode.
+pub struct MulChip;
+
+impl ExecuteChip for MulChip {
+    type ExecutionResult = ExecutionResult;
+
+    fn execute(program_step: &ProgramStep) -> Self::ExecutionResult {
+        let value_b = program_step.get_value_b();
+        let (value_c, _) = program_step.get_value_c();
+
+        // The lower word of the product doesn't depend on signedness.
+        mul_with_carries(value_b, value_c, false, false)
+    }
+}
+
+impl MachineChip for MulChip {
+    fn fill_main_trace(
+        traces: &mut TracesBuilder,
+        row_idx: usize,
+        vm_step: &Option<ProgramStep>,
+        _side_note: &mut SideNote,
+        _config: &ExtensionsConfig,
+    ) {
+        let vm_step = match vm_step {
+            Some(vm_step) => vm_step,
+            None => return,
+        };
+        if !matches!(
+            vm_step.step.instruction.opcode.builtin(),
+            Some(BuiltinOpcode::MUL)
+        ) {
+            return;
+        }
+
+        let ExecutionResult {
+            product,
+            carry_low,
+            carry_high,
+        } = Self::execute(vm_step);
+        let result: Word = std::array::from_fn(|i| product[i]);
+
+        assert_eq!(result, vm_step.get_result().expect("MUL must have result"));
+
+        // Only the carries of the lower word are constrained.
+        traces.fill_columns(row_idx, [carry_low[0], carry_low[1], 0, 0], MulCarryLow);
+        traces.fill_columns(row_idx, [carry_high[0], carry_high[1], 0, 0], MulCarryHigh);
+        traces.fill_columns(row_idx, result, ValueA);
+    }
+
+    fn add_constraints<E: EvalAtRow>(
+        eval: &mut E,
+        trace_eval: &TraceEval<E>,
+        _lookup_elements: &AllLookupElements,
+        _config: &ExtensionsConfig,
+    ) {
+        let [is_mul] = trace_eval!(trace_eval, IsMul);
+        let value_a = trace_eval!(trace_eval, ValueA);
+        let value_b = trace_eval!(trace_eval, ValueB);
+        let value_c = trace_eval!(trace_eval, ValueC);
+        let carry_low = trace_eval!(trace_eval, MulCarryLow);
+        let carry_high = trace_eval!(trace_eval, MulCarryHigh);
+
+        // The lower word never reads the sign extension, so the sign flags are irrelevant.
+        constrain_mul(
+            eval,
+            is_mul,
+            &value_b,
+            &value_c,
+            E::F::zero(),
+            E::F::zero(),
+            None,
+            &value_a,
+            &carry_low,
+            &carry_high,
+        );
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        chips::{
+            AddChip, CpuChip, DecodingCheckChip, ProgramMemCheckChip, RangeCheckChip,
+            RegisterMemCheckChip, SubChip,
+        },
+        test_utils::assert_chip,
+        trace::{
+            preprocessed::PreprocessedBuilder, program::iter_program_steps,
+            program_trace::ProgramTracesBuilder,
+        },
+    };
+
+    use super::*;
+    use nexus_vm::{
+        emulator::InternalView,
+        riscv::{BasicBlock, BuiltinOpcode, Instruction, Opcode},
+        trace::k_trace_direct,
+    };
+
+    const LOG_SIZE: u32 = PreprocessedBuilder::MIN_LOG_SIZE;
+
+    #[test]
+    fn test_mul_with_carries() {
+        let cases: [(u32, u32); 6] = [
+            (0, 0),
+            (3, 7),
+            (0xffff_ffff, 0xffff_ffff),
+            (0x8000_0000, 0xffff_ffff),
+            (0x1234_5678, 0x9abc_def0),
+            (0x7fff_ffff, 0x8000_0000),
+        ];
+        for (b, c) in cases {
+            for (sgn_b, sgn_c) in [(false, false), (true, false), (true, true)] {
+                let ExecutionResult { product, .. } =
+                    mul_with_carries(b.to_le_bytes(), c.to_le_bytes(), sgn_b, sgn_c);
+                let ext_b = if sgn_b { b as i32 as i64 } else { b as i64 };
+                let ext_c = if sgn_c { c as i32 as i64 } else { c as i64 };
+                let expected = ext_b.wrapping_mul(ext_c) as u64;
+                assert_eq!(u64::from_le_bytes(product), expected, "{b:#x} * {c:#x}");
+            }
+        }
+    }
+
+    fn setup_basic_block_ir() -> Vec<BasicBlock> {
+        let basic_block = BasicBlock::new(vec![
+            // x1 = 2000
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::ADDI), 1, 0, 2000),
+            // x2 = 3
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::ADDI), 2, 0, 3),
+            // x3 = -2000
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::SUB), 3, 0, 1),
+            // x4 = -1
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::ADDI), 4, 0, 1),
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::SUB), 4, 0, 4),
+            // x5 = 6000
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::MUL), 5, 1, 2),
+            // x6 = -6000
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::MUL), 6, 3, 2),
+            // x7 = 4000000
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::MUL), 7, 3, 3),
+            // x8 = 1 because (-1) * (-1) = 1
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::MUL), 8, 4, 4),
+            // x9 = 0
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::MUL), 9, 1, 0),
+            // x10 = 16000000000 mod 2^32, overflowing the lower word
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::MUL), 10, 7, 7),
+        ]);
+        vec![basic_block]
+    }
+
+    #[test]
+    fn test_k_trace_constrained_mul_instructions() {
+        let basic_block = setup_basic_block_ir();
+        let k = 1;
+        type Chips = (
+            CpuChip,
+            DecodingCheckChip,
+            AddChip,
+            SubChip,
+            MulChip,
+            RegisterMemCheckChip,
+            ProgramMemCheckChip,
+            RangeCheckChip,
+        );
+
+        // Get traces from VM K-Trace interface
+        let (view, vm_traces) = k_trace_direct(&basic_block, k).expect("Failed to create trace");
+        let program_info = view.get_program_memory();
+
+        // Trace circuit
+        let mut traces = TracesBuilder::new(LOG_SIZE);
+        let program_steps = iter_program_steps(&vm_traces, traces.num_rows());
+        let program_traces = ProgramTracesBuilder::new_with_empty_memory(LOG_SIZE, program_info);
+        let mut side_note = SideNote::new(&program_traces, &view);
+
+        // We iterate each block in the trace for each instruction
+        for (row_idx, program_step) in program_steps.enumerate() {
+            Chips::fill_main_trace(
+                &mut traces,
+                row_idx,
+                &program_step,
+                &mut side_note,
+                &ExtensionsConfig::default(),
+            );
+        }
+        assert_chip::<Chips>(traces, Some(program_traces.finalize()));
+    }
+}
@@ -0,0 +1,268 @@
+use stwo_prover::constraint_framework::EvalAtRow;
+
+use nexus_vm::{riscv::BuiltinOpcode, WORD_SIZE};
+
+use crate::{
+    column::Column::*,
+    components::AllLookupElements,
+    extensions::ExtensionsConfig,
+    trace::{
+        eval::{trace_eval, TraceEval},
+        sidenote::SideNote,
+        ProgramStep, TracesBuilder, Word,
+    },
+    traits::{ExecuteChip, MachineChip},
+};
+
+use super::mul::{constrain_mul, mul_with_carries, ExecutionResult};
+
+// Support MULH, MULHSU and MULHU opcodes.
+pub struct MulhChip;
+
+impl MulhChip {
+    /// Returns whether rs1 and rs2 are interpreted as signed values.
+    fn signedness(opcode: BuiltinOpcode) -> (bool, bool) {
+        match opcode {
+            BuiltinOpcode::MULH => (true, true),
+            BuiltinOpcode::MULHSU => (true, false),
+            BuiltinOpcode::MULHU => (false, false),
+            _ => panic!("MulhChip doesn't support {opcode:?}"),
+        }
+    }
+}
+
+impl ExecuteChip for MulhChip {
+    type ExecutionResult = ExecutionResult;
+
+    fn execute(program_step: &ProgramStep) -> Self::ExecutionResult {
+        let opcode = program_step
+            .step
+            .instruction
+            .opcode
+            .builtin()
+            .expect("MulhChip only supports builtin opcodes");
+        let (signed_b, signed_c) = Self::signedness(opcode);
+
+        let value_b = program_step.get_value_b();
+        let (value_c, _) = program_step.get_value_c();
+        let sgn_b = signed_b && program_step.get_sgn_b();
+        let sgn_c = signed_c && program_step.get_sgn_c();
+
+        mul_with_carries(value_b, value_c, sgn_b, sgn_c)
+    }
+}
+
+impl MachineChip for MulhChip {
+    fn fill_main_trace(
+        traces: &mut TracesBuilder,
+        row_idx: usize,
+        vm_step: &Option<ProgramStep>,
+        _side_note: &mut SideNote,
+        _config: &ExtensionsConfig,
+    ) {
+        let vm_step = match vm_step {
+            Some(vm_step) => vm_step,
+            None => return,
+        };
+        let opcode = match vm_step.step.instruction.opcode.builtin() {
+            Some(opcode @ (BuiltinOpcode::MULH | BuiltinOpcode::MULHSU | BuiltinOpcode::MULHU)) => {
+                opcode
+            }
+            _ => return,
+        };
+        let (signed_b, signed_c) = Self::signedness(opcode);
+
+        let ExecutionResult {
+            product,
+            carry_low,
+            carry_high,
+        } = Self::execute(vm_step);
+        let low: Word = std::array::from_fn(|i| product[i]);
+        let high: Word = std::array::from_fn(|i| product[WORD_SIZE + i]);
+
+        assert_eq!(high, vm_step.get_result().expect("MULH must have result"));
+
+        // Fill the sign bits and the operands without them for signed operands
+        if signed_b {
+            let mut helper_b = vm_step.get_value_b();
+            helper_b[WORD_SIZE - 1] &= 0x7f;
+            traces.fill_columns(row_idx, helper_b, Helper2);
+            traces.fill_columns(row_idx, vm_step.get_sgn_b(), SgnB);
+        }
+        if signed_c {
+            let (mut helper_c, _) = vm_step.get_value_c();
+            helper_c[WORD_SIZE - 1] &= 0x7f;
+            traces.fill_columns(row_idx, helper_c, Helper3);
+            traces.fill_columns(row_idx, vm_step.get_sgn_c(), SgnC);
+        }
+
+        traces.fill_columns(row_idx, carry_low, MulCarryLow);
+        traces.fill_columns(row_idx, carry_high, MulCarryHigh);
+        traces.fill_columns(row_idx, low, Helper1);
+        traces.fill_columns(row_idx, high, ValueA);
+    }
+
+    fn add_constraints<E: EvalAtRow>(
+        eval: &mut E,
+        trace_eval: &TraceEval<E>,
+        _lookup_elements: &AllLookupElements,
+        _config: &ExtensionsConfig,
+    ) {
+        let [is_mulh] = trace_eval!(trace_eval, IsMulh);
+        let [is_mulhsu] = trace_eval!(trace_eval, IsMulhsu);
+        let [is_mulhu] = trace_eval!(trace_eval, IsMulhu);
+
+        // modulues for 7-bit
+        let modulus_7 = E::F::from(128u32.into());
+
+        let value_a = trace_eval!(trace_eval, ValueA);
+        let value_b = trace_eval!(trace_eval, ValueB);
+        let value_c = trace_eval!(trace_eval, ValueC);
+        let [sgn_b] = trace_eval!(trace_eval, SgnB);
+        let [sgn_c] = trace_eval!(trace_eval, SgnC);
+        let helper1_val = trace_eval!(trace_eval, Helper1);
+        let helper2_val = trace_eval!(trace_eval, Helper2);
+        let helper3_val = trace_eval!(trace_eval, Helper3);
+        let carry_low = trace_eval!(trace_eval, MulCarryLow);
+        let carry_high = trace_eval!(trace_eval, MulCarryHigh);
+
+        // (is_mulh + is_mulhsu) * (h2[3] + sgn_b * 2^7 - b_val[3]) = 0
+        eval.add_constraint(
+            (is_mulh.clone() + is_mulhsu.clone())
+                * (modulus_7.clone() * sgn_b.clone() + helper2_val[3].clone() - value_b[3].clone()),
+        );
+        // is_mulh * (h3[3] + sgn_c * 2^7 - c_val[3]) = 0
+        eval.add_constraint(
+            is_mulh.clone()
+                * (modulus_7.clone() * sgn_c.clone() + helper3_val[3].clone() - value_c[3].clone()),
+        );
+        // Unsigned operands are never sign-extended
+        // is_mulhu * sgn_b = 0
+        // (is_mulhsu + is_mulhu) * sgn_c = 0
+        eval.add_constraint(is_mulhu.clone() * sgn_b.clone());
+        eval.add_constraint((is_mulhsu.clone() + is_mulhu.clone()) * sgn_c.clone());
+
+        // The lower word of the product is kept in Helper1, the upper word is the result.
+        let product: [E::F; 2 * WORD_SIZE] = std::array::from_fn(|i| {
+            if i < WORD_SIZE {
+                helper1_val[i].clone()
+            } else {
+                value_a[i - WORD_SIZE].clone()
+            }
+        });
+        constrain_mul(
+            eval,
+            is_mulh + is_mulhsu + is_mulhu,
+            &value_b,
+            &value_c,
+            sgn_b,
+            sgn_c,
+            None,
+            &product,
+            &carry_low,
+            &carry_high,
+        );
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        chips::{
+            AddChip, CpuChip, DecodingCheckChip, ProgramMemCheckChip, RangeCheckChip,
+            RegisterMemCheckChip, SubChip,
+        },
+        test_utils::assert_chip,
+        trace::{
+            preprocessed::PreprocessedBuilder, program::iter_program_steps,
+            program_trace::ProgramTracesBuilder,
+        },
+    };
+
+    use super::*;
+    use nexus_vm::{
+        emulator::InternalView,
+        riscv::{BasicBlock, BuiltinOpcode, Instruction, Opcode},
+        trace::k_trace_direct,
+    };
+
+    const LOG_SIZE: u32 = PreprocessedBuilder::MIN_LOG_SIZE;
+
+    fn setup_basic_block_ir() -> Vec<BasicBlock> {
+        let mut instructions = vec![
+            // x1 = 2000
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::ADDI), 1, 0, 2000),
+            // x2 = -2000
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::SUB), 2, 0, 1),
+            // x3 = -1
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::ADDI), 3, 0, 1),
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::SUB), 3, 0, 3),
+            // x4 = 0x80000000 (smallest negative 32-bit number)
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::ADDI), 4, 0, 1),
+        ];
+        instructions.extend(
+            (0..31).map(|_| Instruction::new_ir(Opcode::from(BuiltinOpcode::ADD), 4, 4, 4)),
+        );
+        for opcode in [
+            BuiltinOpcode::MULH,
+            BuiltinOpcode::MULHSU,
+            BuiltinOpcode::MULHU,
+        ] {
+            instructions.extend([
+                // Positive times positive
+                Instruction::new_ir(Opcode::from(opcode), 5, 1, 1),
+                // Negative times positive
+                Instruction::new_ir(Opcode::from(opcode), 6, 2, 1),
+                // Positive times negative
+                Instruction::new_ir(Opcode::from(opcode), 7, 1, 2),
+                // Negative times negative
+                Instruction::new_ir(Opcode::from(opcode), 8, 3, 3),
+                // Zero operand
+                Instruction::new_ir(Opcode::from(opcode), 9, 0, 2),
+                // Edge cases around the smallest negative number
+                Instruction::new_ir(Opcode::from(opcode), 10, 4, 4),
+                Instruction::new_ir(Opcode::from(opcode), 11, 4, 3),
+                Instruction::new_ir(Opcode::from(opcode), 12, 3, 4),
+            ]);
+        }
+        vec![BasicBlock::new(instructions)]
+    }
+
+    #[test]
+    fn test_k_trace_constrained_mulh_instructions() {
+        let basic_block = setup_basic_block_ir();
+        let k = 1;
+        type Chips = (
+            CpuChip,
+            DecodingCheckChip,
+            AddChip,
+            SubChip,
+            MulhChip,
+            RegisterMemCheckChip,
+            ProgramMemCheckChip,
+            RangeCheckChip,
+        );
+
+        // Get traces from VM K-Trace interface
+        let (view, vm_traces) = k_trace_direct(&basic_block, k).expect("Failed to create trace");
+        let program_info = view.get_program_memory();
+
+        // Trace circuit
+        let mut traces = TracesBuilder::new(LOG_SIZE);
+        let program_steps = iter_program_steps(&vm_traces, traces.num_rows());
+        let program_traces = ProgramTracesBuilder::new_with_empty_memory(LOG_SIZE, program_info);
+        let mut side_note = SideNote::new(&program_traces, &view);
+
+        // We iterate each block in the trace for each instruction
+        for (row_idx, program_step) in program_steps.enumerate() {
+            Chips::fill_main_trace(
+                &mut traces,
+                row_idx,
+                &program_step,
+                &mut side_note,
+                &ExtensionsConfig::default(),
+            );
+        }
+        assert_chip::<Chips>(traces, Some(program_traces.finalize()));
+    }
+}
@@ -67,6 +67,10 @@ impl MachineChip for SyscallChip {
                 traces.fill_columns(row_idx, true, Column::IsSysHeapReset);
                 traces.fill_columns(row_idx, result, Column::ValueA);
             }
+            (0x404, Some(result)) => {
+                traces.fill_columns(row_idx, true, Column::IsSysAuxInput);
+                traces.fill_columns(row_idx, result, Column::ValueA);
+            }
             (0x405, None) => traces.fill_columns(row_idx, true, Column::IsSysMemoryAdvise),
             _ => {
                 panic!(
@@ -87,6 +91,7 @@ impl MachineChip for SyscallChip {
         let [is_sys_debug] = trace_eval!(trace_eval, Column::IsSysDebug);
         let [is_sys_halt] = trace_eval!(trace_eval, Column::IsSysHalt);
         let [is_sys_priv_input] = trace_eval!(trace_eval, Column::IsSysPrivInput);
+        let [is_sys_aux_input] = trace_eval!(trace_eval, Column::IsSysAuxInput);
         let [is_sys_cycle_count] = trace_eval!(trace_eval, Column::IsSysCycleCount);
         let [is_sys_stack_reset] = trace_eval!(trace_eval, Column::IsSysStackReset);
         let [is_sys_heap_reset] = trace_eval!(trace_eval, Column::IsSysHeapReset);
@@ -107,6 +112,8 @@ impl MachineChip for SyscallChip {
         // is_type_sys・is_sys_stack_reset・	(b_val_2 - 0x04) = 0  // b_val=0x402
         // is_type_sys・is_sys_heap_reset・	(b_val_1 - 0x03) = 0  // b_val=0x403
         // is_type_sys・is_sys_heap_reset・	(b_val_2 - 0x04) = 0  // b_val=0x403
+        // is_type_sys・is_sys_aux_input・	(b_val_1 - 0x04) = 0  // b_val=0x404
+        // is_type_sys・is_sys_aux_input・	(b_val_2 - 0x04) = 0  // b_val=0x404
 
         let syscall_table = [
             (SyscallCode::Write as u32, &is_sys_debug),
@@ -118,6 +125,10 @@ impl MachineChip for SyscallChip {
                 &is_sys_stack_reset,
             ),
             (SyscallCode::OverwriteHeapPointer as u32, &is_sys_heap_reset),
+            (
+                SyscallCode::ReadFromAuxiliaryInput as u32,
+                &is_sys_aux_input,
+            ),
             (SyscallCode::MemoryAdvise as u32, &is_sys_madvise),
         ];
 
@@ -138,12 +149,13 @@ impl MachineChip for SyscallChip {
         }
 
         // Enforce that one flag is set
-        // is_type_sys・(is_sys_debug + is_sys_halt + is_sys_priv_input + is_sys_cycle_count + is_sys_stack_reset + is_sys_heap_reset - 1) = 0
+        // is_type_sys・(is_sys_debug + is_sys_halt + is_sys_priv_input + is_sys_aux_input + is_sys_cycle_count + is_sys_stack_reset + is_sys_heap_reset - 1) = 0
         eval.add_constraint(
             is_type_sys.clone()
                 * (is_sys_debug.clone()
                     + is_sys_halt.clone()
                     + is_sys_priv_input.clone()
+                    + is_sys_aux_input.clone()
                     + is_sys_cycle_count.clone()
                     + is_sys_stack_reset.clone()
                     + is_sys_heap_reset.clone()
@@ -153,7 +165,7 @@ impl MachineChip for SyscallChip {
 
         // Enforcing values for op_a
         // is_type_sys・(is_sys_debug + is_sys_halt + is_sys_cycle_count + is_sys_madvise)・(op_a) = 0
-        // is_type_sys・(is_sys_priv_input + is_sys_heap_reset)・(10 - op_a) = 0
+        // is_type_sys・(is_sys_priv_input + is_sys_aux_input + is_sys_heap_reset)・(10 - op_a) = 0
         // is_type_sys・(is_sys_stack_reset)・(2 - op_a) = 0
         let [op_a] = trace_eval!(trace_eval, Column::OpA);
 
@@ -167,7 +179,9 @@ impl MachineChip for SyscallChip {
         );
         eval.add_constraint(
             is_type_sys.clone()
-                * (is_sys_priv_input.clone() + is_sys_heap_reset.clone())
+                * (is_sys_priv_input.clone()
+                    + is_sys_aux_input.clone()
+                    + is_sys_heap_reset.clone())
                 * (E::F::from(BaseField::from(10)) - op_a.clone()),
         );
         eval.add_constraint(
@@ -225,6 +239,9 @@ mod test {
             // Private input syscall (0x400)
             Instruction::new_ir(Opcode::from(BuiltinOpcode::ADDI), 17, 0, SyscallCode::ReadFromPrivateInput as u32),
             Instruction::new_ir(Opcode::from(BuiltinOpcode::ECALL), 0, 0, 0),
+            // Auxiliary input syscall (0x404)
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::ADDI), 17, 0, SyscallCode::ReadFromAuxiliaryInput as u32),
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::ECALL), 0, 0, 0),
             // Stack reset syscall (0x402)c
             Instruction::new_ir(Opcode::from(BuiltinOpcode::ADDI), 17, 0, SyscallCode::OverwriteStackPointer as u32),
             Instruction::new_ir(Opcode::from(BuiltinOpcode::ECALL), 0, 0, 0),
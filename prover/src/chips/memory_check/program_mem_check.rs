@@ -15,7 +15,9 @@ use crate::{
     components::AllLookupElements,
     extensions::ExtensionsConfig,
     trace::{
-        eval::{preprocessed_trace_eval, program_trace_eval, trace_eval, TraceEval},
+        eval::{
+            preprocessed_trace_eval, program_trace_eval, trace_eval, trace_eval_next_row, TraceEval,
+        },
         program_trace::ProgramTraces,
         sidenote::SideNote,
         utils::FromBaseFields,
@@ -105,17 +107,17 @@ impl MachineChip for ProgramMemCheckChip {
     ///
     /// For the initial content of the program memory:
     /// * 1 / lookup_element.combine(tuple) is added for each instruction
-    /// where tuples contain (the address, the whole word of the instruction, 0u32).
+    /// where tuples contain (the address, the whole word of the instruction, whether it is compressed, 0u32).
     ///
     /// On each program memory access:
     /// * 1 / lookup_element.combine(tuple_old) is subtracted
     /// * 1 / lookup_element.combine(tuple_new) is added
-    /// where tuples contain (the address, the whole word of the instruction, counter value).
+    /// where tuples contain (the address, the whole word of the instruction, whether it is compressed, counter value).
     /// The counter value is incremented by one on each access.
     ///
     /// For the final content of the program memory:
     /// * 1 / lookup_element.combine(tuple) is subtracted for each instruction
-    /// where tuples contain (the address, the whole word of the instruction, final counter value).
+    /// where tuples contain (the address, the whole word of the instruction, whether it is compressed, final counter value).
     fn fill_interaction_trace(
         logup_trace_gen: &mut LogupTraceGenerator,
         original_traces: &FinalizedTraces,
@@ -125,7 +127,7 @@ impl MachineChip for ProgramMemCheckChip {
     ) {
         let lookup_element: &ProgramCheckLookupElements = lookup_element.as_ref();
         // add initial digest
-        // For every used Pc, a tuple (address, instruction_as_word, 0u32) is added.
+        // For every used Pc, a tuple (address, instruction_as_word, is_compressed, 0u32) is added.
         Self::add_initial_digest(
             logup_trace_gen,
             original_traces,
@@ -134,7 +136,7 @@ impl MachineChip for ProgramMemCheckChip {
         );
 
         // subtract final digest
-        // For every used Pc, a tuple (address, instruction_as_word, final_counter) is subtracted.
+        // For every used Pc, a tuple (address, instruction_as_word, is_compressed, final_counter) is subtracted.
         Self::subtract_final_digest(
             logup_trace_gen,
             original_traces,
@@ -143,11 +145,11 @@ impl MachineChip for ProgramMemCheckChip {
         );
 
         // subtract program memory access, previous counter reads
-        // For each access, a tuple of the form (address, instruction_as_word, previous_counter) is subtracted.
+        // For each access, a tuple of the form (address, instruction_as_word, is_compressed, previous_counter) is subtracted.
         Self::subtract_access(logup_trace_gen, original_traces, lookup_element);
 
         // add program memory access, new counter write backs
-        // For each access, a tuple of the form (address, instruction_as_word, new_counter) is added.
+        // For each access, a tuple of the form (address, instruction_as_word, is_compressed, new_counter) is added.
         Self::add_access(logup_trace_gen, original_traces, lookup_element);
     }
 
@@ -168,8 +170,25 @@ impl MachineChip for ProgramMemCheckChip {
             );
         }
 
-        // Constrain PrgCurCtr = PrgPrevCtr + 1
+        // A continuation segment executes at least one instruction, and the last executed instruction
+        // jumps to the public final program counter.
         let [is_padding] = trace_eval.column_eval(Column::IsPadding);
+        let [next_is_padding] = trace_eval_next_row!(trace_eval, Column::IsPadding);
+        let [is_last] = preprocessed_trace_eval!(trace_eval, PreprocessedColumn::IsLast);
+        let [is_segment] = program_trace_eval!(trace_eval, ProgramColumn::PrgSegmentFlag);
+        let pc_next = trace_eval!(trace_eval, Column::PcNext);
+        let final_pc = program_trace_eval!(trace_eval, ProgramColumn::PrgFinalPc);
+        eval.add_constraint(is_segment.clone() * is_first * is_padding.clone());
+        for limb_idx in 0..WORD_SIZE {
+            eval.add_constraint(
+                is_segment.clone()
+                    * (E::F::one() - is_padding.clone())
+                    * (next_is_padding.clone() + is_last.clone())
+                    * (pc_next[limb_idx].clone() - final_pc[limb_idx].clone()),
+            );
+        }
+
+        // Constrain PrgCurCtr = PrgPrevCtr + 1
         let prg_prev_ctr = trace_eval.column_eval::<WORD_SIZE>(Column::ProgCtrPrev);
         let prg_cur_ctr = trace_eval.column_eval::<WORD_SIZE>(Column::ProgCtrCur);
         let prg_ctr_carry = trace_eval.column_eval::<WORD_SIZE_HALVED>(Column::ProgCtrCarry);
@@ -202,19 +221,19 @@ impl MachineChip for ProgramMemCheckChip {
         // Logup constraints
 
         // add initial digest
-        // For each used Pc, one tuple (address, instruction_as_word, 0u32) is added.
+        // For each used Pc, one tuple (address, instruction_as_word, is_compressed, 0u32) is added.
         Self::constrain_add_initial_digest(eval, trace_eval, lookup_elements);
 
         // subtract final digest
-        // For each used Pc, one tuple (address, instruction_as_word, final_counter) is subtracted.
+        // For each used Pc, one tuple (address, instruction_as_word, is_compressed, final_counter) is subtracted.
         Self::constrain_subtract_final_digest(eval, trace_eval, lookup_elements);
 
         // subtract program memory access, previous counter reads
-        // For each access, one tuple (address, instruction_as_word, previous_counter) is subtracted.
+        // For each access, one tuple (address, instruction_as_word, is_compressed, previous_counter) is subtracted.
         Self::constrain_subtract_access(eval, trace_eval, lookup_elements);
 
         // add program memory access, new counter write backs
-        // For each access, one tuple (address, instruction_as_word, new_counter) is added.
+        // For each access, one tuple (address, instruction_as_word, is_compressed, new_counter) is added.
         Self::constrain_add_access(eval, trace_eval, lookup_elements);
     }
 }
@@ -222,7 +241,7 @@ impl MachineChip for ProgramMemCheckChip {
 impl ProgramMemCheckChip {
     /// Fills the interaction trace columns for adding the initial content of the program memory:
     /// * 1 / lookup_element.combine(tuple) is added for each instruction
-    /// where tuples contain (the address, the whole word of the instruction, 0u32).
+    /// where tuples contain (the address, the whole word of the instruction, whether it is compressed, 0u32).
     /// The address and the instruction word are stored in two halfwords in little endian.
     ///
     /// The initial content of the memory is located on rows where PrgMemoryFlag is 1.
@@ -239,9 +258,11 @@ impl ProgramMemCheckChip {
         // Two limbs of 16 bits each
         let prg_memory_word =
             program_traces.get_base_column::<WORD_SIZE_HALVED>(ProgramColumn::PrgMemoryWord);
+        let [prg_memory_compressed] =
+            program_traces.get_base_column(ProgramColumn::PrgMemoryCompressed);
         // The counter is not used because initially the counters are zero.
         let mut logup_col_gen = logup_trace_gen.new_col();
-        // Add (Pc, prg_memory_word, 0u32)
+        // Add (Pc, prg_memory_word, prg_memory_compressed, 0u32)
         for vec_row in 0..(1 << (original_traces.log_size() - LOG_N_LANES)) {
             let mut tuple = vec![];
             for prg_memory_pc_halfword in prg_memory_pc.iter() {
@@ -251,9 +272,13 @@ impl ProgramMemCheckChip {
             for prg_memory_halfword in prg_memory_word.iter() {
                 tuple.push(prg_memory_halfword.data[vec_row]);
             }
+            tuple.push(prg_memory_compressed.data[vec_row]);
             // Initial counter is zero
             tuple.extend_from_slice(&[PackedBaseField::zero(); WORD_SIZE]);
-            assert_eq!(tuple.len(), WORD_SIZE_HALVED + WORD_SIZE_HALVED + WORD_SIZE);
+            assert_eq!(
+                tuple.len(),
+                WORD_SIZE_HALVED + WORD_SIZE_HALVED + 1 + WORD_SIZE
+            );
             let numerator = prg_memory_flag.data[vec_row];
             logup_col_gen.write_frac(
                 vec_row,
@@ -277,7 +302,9 @@ impl ProgramMemCheckChip {
         let prg_memory_pc = program_trace_eval!(trace_eval, ProgramColumn::PrgMemoryPc);
         // Two limbs of 16 bits each
         let prg_memory_word = program_trace_eval!(trace_eval, ProgramColumn::PrgMemoryWord);
-        // Add (Pc, prg_memory_word, 0u32)
+        let [prg_memory_compressed] =
+            program_trace_eval!(trace_eval, ProgramColumn::PrgMemoryCompressed);
+        // Add (Pc, prg_memory_word, prg_memory_compressed, 0u32)
         let mut tuple = vec![];
         for prg_memory_pc_halfword in prg_memory_pc.into_iter() {
             tuple.push(prg_memory_pc_halfword);
@@ -286,10 +313,14 @@ impl ProgramMemCheckChip {
         for prg_memory_halfword in prg_memory_word.into_iter() {
             tuple.push(prg_memory_halfword);
         }
+        tuple.push(prg_memory_compressed);
         for _ in 0..WORD_SIZE {
             tuple.extend_from_slice(&[E::F::zero()]);
         }
-        assert_eq!(tuple.len(), WORD_SIZE_HALVED + WORD_SIZE_HALVED + WORD_SIZE);
+        assert_eq!(
+            tuple.len(),
+            WORD_SIZE_HALVED + WORD_SIZE_HALVED + 1 + WORD_SIZE
+        );
         let numerator = prg_memory_flag;
 
         eval.add_to_relation(RelationEntry::new(
@@ -301,7 +332,7 @@ impl ProgramMemCheckChip {
 
     /// For the final content of the program memory, subtract in the interaction trace:
     /// * 1 / lookup_element.combine(tuple) for each instruction
-    /// where tuples contain (the address, the whole word of the instruction, final counter value).
+    /// where tuples contain (the address, the whole word of the instruction, whether it is compressed, final counter value).
     /// The address and the instruction word are stored in two halfwords in little endian.
     ///
     /// The information about the final content of the program memory is located on rows with PrgMemoryFlag set to 1.
@@ -320,10 +351,12 @@ impl ProgramMemCheckChip {
         // Two limbs of 16 bits each
         let prg_memory_word =
             program_traces.get_base_column::<WORD_SIZE_HALVED>(ProgramColumn::PrgMemoryWord);
+        let [prg_memory_compressed] =
+            program_traces.get_base_column(ProgramColumn::PrgMemoryCompressed);
         let prg_memory_ctr =
             original_traces.get_base_column::<WORD_SIZE>(Column::FinalPrgMemoryCtr);
         let mut logup_col_gen = logup_trace_gen.new_col();
-        // Subtract (Pc, prg_memory_word, 0u32)
+        // Subtract (Pc, prg_memory_word, prg_memory_compressed, 0u32)
         for vec_row in 0..(1 << (original_traces.log_size() - LOG_N_LANES)) {
             let mut tuple = vec![];
             for prg_memory_pc_halfword in prg_memory_pc.into_iter() {
@@ -334,10 +367,11 @@ impl ProgramMemCheckChip {
                 tuple.push(prg_memory_halfword.data[vec_row]);
             }
             assert_eq!(tuple.len(), 2 * WORD_SIZE_HALVED);
+            tuple.push(prg_memory_compressed.data[vec_row]);
             for prg_memory_ctr_byte in prg_memory_ctr.iter() {
                 tuple.push(prg_memory_ctr_byte.data[vec_row]);
             }
-            assert_eq!(tuple.len(), 2 * WORD_SIZE_HALVED + WORD_SIZE);
+            assert_eq!(tuple.len(), 2 * WORD_SIZE_HALVED + 1 + WORD_SIZE);
             let numerator = prg_memory_flag.data[vec_row];
             logup_col_gen.write_frac(
                 vec_row,
@@ -361,6 +395,8 @@ impl ProgramMemCheckChip {
         let prg_memory_pc = program_trace_eval!(trace_eval, ProgramColumn::PrgMemoryPc);
         // Two limbs of 16 bits each
         let prg_memory_word = program_trace_eval!(trace_eval, ProgramColumn::PrgMemoryWord);
+        let [prg_memory_compressed] =
+            program_trace_eval!(trace_eval, ProgramColumn::PrgMemoryCompressed);
         let prg_memory_ctr = trace_eval!(trace_eval, Column::FinalPrgMemoryCtr);
         let mut tuple = vec![];
         for prg_memory_pc_halfword in prg_memory_pc.into_iter() {
@@ -371,10 +407,11 @@ impl ProgramMemCheckChip {
             tuple.push(prg_memory_halfword)
         }
         assert_eq!(tuple.len(), 2 * WORD_SIZE_HALVED);
+        tuple.push(prg_memory_compressed);
         for prg_memory_ctr_byte in prg_memory_ctr.into_iter() {
             tuple.push(prg_memory_ctr_byte);
         }
-        assert_eq!(tuple.len(), 2 * WORD_SIZE_HALVED + WORD_SIZE);
+        assert_eq!(tuple.len(), 2 * WORD_SIZE_HALVED + 1 + WORD_SIZE);
         let numerator = prg_memory_flag;
         eval.add_to_relation(RelationEntry::new(
             lookup_elements,
@@ -385,7 +422,7 @@ impl ProgramMemCheckChip {
 
     /// On each program memory access:
     /// * 1 / lookup_element.combine(tuple_old) is subtracted
-    /// where tuples contain (the address, the whole word of the instruction, previous counter value).
+    /// where tuples contain (the address, the whole word of the instruction, whether it is compressed, previous counter value).
     /// The address and the instruction word are stored in two halfwords in little endian.
     ///
     /// The numerator is zero on the padding rows, so that the row doesn't contribute to the logup sum.
@@ -398,6 +435,7 @@ impl ProgramMemCheckChip {
         let prg_prev_ctr = original_traces.get_base_column::<WORD_SIZE>(Column::ProgCtrPrev);
         let pc = original_traces.get_base_column::<WORD_SIZE>(Column::Pc);
         let instruction_word = original_traces.get_base_column::<WORD_SIZE>(Column::InstrVal);
+        let [is_compressed] = original_traces.get_base_column(Column::IsCompressed);
         let mut logup_col_gen = logup_trace_gen.new_col();
         let modulo = PackedBaseField::from(BaseField::from(1u32 << 8));
         for vec_row in 0..(1 << (original_traces.log_size() - LOG_N_LANES)) {
@@ -412,10 +450,11 @@ impl ProgramMemCheckChip {
                 );
             }
             assert_eq!(tuple.len(), 2 * WORD_SIZE_HALVED);
+            tuple.push(is_compressed.data[vec_row]);
             for prg_prev_ctr_byte in prg_prev_ctr.iter() {
                 tuple.push(prg_prev_ctr_byte.data[vec_row]);
             }
-            assert_eq!(tuple.len(), 2 * WORD_SIZE_HALVED + WORD_SIZE);
+            assert_eq!(tuple.len(), 2 * WORD_SIZE_HALVED + 1 + WORD_SIZE);
             let numerator = PackedBaseField::one() - is_padding.data[vec_row];
             logup_col_gen.write_frac(
                 vec_row,
@@ -438,6 +477,7 @@ impl ProgramMemCheckChip {
         let prg_prev_ctr = trace_eval!(trace_eval, Column::ProgCtrPrev);
         let pc = trace_eval!(trace_eval, Column::Pc);
         let instruction_word = trace_eval!(trace_eval, Column::InstrVal);
+        let [is_compressed] = trace_eval!(trace_eval, Column::IsCompressed);
         let mut tuple = vec![];
         let modulo = E::F::from((1u32 << 8).into());
         for pc_byte in pc.chunks(2) {
@@ -448,10 +488,11 @@ impl ProgramMemCheckChip {
             tuple.push(instruction_byte[0].clone() + instruction_byte[1].clone() * modulo.clone());
         }
         assert_eq!(tuple.len(), 2 * WORD_SIZE_HALVED);
+        tuple.push(is_compressed.clone());
         for prg_prev_ctr_byte in prg_prev_ctr.into_iter() {
             tuple.push(prg_prev_ctr_byte);
         }
-        assert_eq!(tuple.len(), 2 * WORD_SIZE_HALVED + WORD_SIZE);
+        assert_eq!(tuple.len(), 2 * WORD_SIZE_HALVED + 1 + WORD_SIZE);
         let numerator = E::F::one() - is_padding;
 
         eval.add_to_relation(RelationEntry::new(
@@ -463,7 +504,7 @@ impl ProgramMemCheckChip {
 
     /// On each program memory access:
     /// * 1 / lookup_element.combine(tuple_new) is added
-    /// where tuples contain (the address, the whole word of the instruction, current counter value).
+    /// where tuples contain (the address, the whole word of the instruction, whether it is compressed, current counter value).
     /// The counter value is incremented by one on each access.
     ///
     /// The numerator is zero when the row is padding, so that the row doesn't contribute to the logup sum.
@@ -476,6 +517,7 @@ impl ProgramMemCheckChip {
         let prg_cur_ctr = original_traces.get_base_column::<WORD_SIZE>(Column::ProgCtrCur);
         let pc = original_traces.get_base_column::<WORD_SIZE>(Column::Pc);
         let instruction_word = original_traces.get_base_column::<WORD_SIZE>(Column::InstrVal);
+        let [is_compressed] = original_traces.get_base_column(Column::IsCompressed);
         let mut logup_col_gen = logup_trace_gen.new_col();
         let modulo = PackedBaseField::from(BaseField::from(1u32 << 8));
         for vec_row in 0..(1 << (original_traces.log_size() - LOG_N_LANES)) {
@@ -490,10 +532,11 @@ impl ProgramMemCheckChip {
                 );
             }
             assert_eq!(tuple.len(), 2 * WORD_SIZE_HALVED);
+            tuple.push(is_compressed.data[vec_row]);
             for prg_prev_ctr_byte in prg_cur_ctr.iter() {
                 tuple.push(prg_prev_ctr_byte.data[vec_row]);
             }
-            assert_eq!(tuple.len(), 2 * WORD_SIZE_HALVED + WORD_SIZE);
+            assert_eq!(tuple.len(), 2 * WORD_SIZE_HALVED + 1 + WORD_SIZE);
             let numerator = PackedBaseField::one() - is_padding.data[vec_row];
             logup_col_gen.write_frac(
                 vec_row,
@@ -516,6 +559,7 @@ impl ProgramMemCheckChip {
         let prg_cur_ctr = trace_eval!(trace_eval, Column::ProgCtrCur);
         let pc = trace_eval!(trace_eval, Column::Pc);
         let instruction_word = trace_eval!(trace_eval, Column::InstrVal);
+        let [is_compressed] = trace_eval!(trace_eval, Column::IsCompressed);
         let modulo = E::F::from((1u32 << 8).into());
         let mut tuple = vec![];
         for pc_byte in pc.chunks(2) {
@@ -526,10 +570,11 @@ impl ProgramMemCheckChip {
             tuple.push(instruction_byte[0].clone() + instruction_byte[1].clone() * modulo.clone());
         }
         assert_eq!(tuple.len(), 2 * WORD_SIZE_HALVED);
+        tuple.push(is_compressed.clone());
         for prg_prev_ctr_byte in prg_cur_ctr.into_iter() {
             tuple.push(prg_prev_ctr_byte);
         }
-        assert_eq!(tuple.len(), 2 * WORD_SIZE_HALVED + WORD_SIZE);
+        assert_eq!(tuple.len(), 2 * WORD_SIZE_HALVED + 1 + WORD_SIZE);
         let numerator = E::F::one() - is_padding;
         eval.add_to_relation(RelationEntry::new(
             lookup_elements,
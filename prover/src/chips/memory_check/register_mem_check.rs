@@ -25,7 +25,7 @@ use crate::{
         FinalizedTraces, PreprocessedTraces, ProgramStep, TracesBuilder,
     },
     traits::MachineChip,
-    virtual_column::{self, IsTypeR, OpBFlag, Reg3Accessed, VirtualColumn},
+    virtual_column::{self, OpBFlag, Reg2Accessed, Reg3Accessed, VirtualColumn},
 };
 
 /// A Chip for register memory checking
@@ -71,7 +71,7 @@ impl MachineChip for RegisterMemCheckChip {
 
         // Read inputs to the chip
         let reg1_accessed = virtual_column::OpBFlag::read_from_traces_builder(traces, row_idx);
-        let reg2_accessed = virtual_column::IsTypeR::read_from_traces_builder(traces, row_idx);
+        let reg2_accessed = virtual_column::Reg2Accessed::read_from_traces_builder(traces, row_idx);
         let reg3_accessed: [BaseField; 1] =
             virtual_column::Reg3Accessed::read_from_traces_builder(traces, row_idx);
         let reg1_address: [BaseField; 1] = traces.column(row_idx, Reg1Address);
@@ -148,7 +148,7 @@ impl MachineChip for RegisterMemCheckChip {
             Reg1TsPrev,
             Reg1ValPrev,
         );
-        let [reg2_accessed] = virtual_column::IsTypeR::eval(trace_eval);
+        let [reg2_accessed] = virtual_column::Reg2Accessed::eval(trace_eval);
         Self::constrain_subtract_prev_reg(
             eval,
             trace_eval,
@@ -208,6 +208,7 @@ impl MachineChip for RegisterMemCheckChip {
         let value_c = trace_eval!(trace_eval, Column::ValueC);
 
         // is_alu = is_add + is_sub + is_slt + is_sltu + is_xor + is_or + is_and + is_sll + is_srl + is_sra
+        //        + is_mul + is_mulh + is_mulhsu + is_mulhu + is_div + is_divu + is_rem + is_remu
         let [is_alu] = virtual_column::IsAlu::eval(trace_eval);
 
         for i in 0..WORD_SIZE {
@@ -246,7 +247,7 @@ impl MachineChip for RegisterMemCheckChip {
             Reg1TsPrev,
             Reg1ValPrev,
         );
-        Self::subtract_prev_reg::<IsTypeR>(
+        Self::subtract_prev_reg::<Reg2Accessed>(
             logup_trace_gen,
             original_traces,
             lookup_element,
@@ -273,7 +274,7 @@ impl MachineChip for RegisterMemCheckChip {
             PreprocessedColumn::Reg1TsCur,
             ValueB,
         );
-        Self::add_cur_reg::<IsTypeR>(
+        Self::add_cur_reg::<Reg2Accessed>(
             logup_trace_gen,
             original_traces,
             preprocessed_trace,
@@ -508,6 +509,7 @@ mod test {
             init_memory: Default::default(),
             exit_code: Default::default(),
             public_output: Default::default(),
+            segment: None,
         };
         let program_traces = ProgramTracesBuilder::new(LOG_SIZE, program_trace_ref);
         let mut side_note = super::SideNote::new(&program_traces, &view);
@@ -8,8 +8,8 @@ pub(crate) mod custom;
 
 pub use instructions::{
     add_with_carries, subtract_with_borrow, AddChip, AuipcChip, BeqChip, BgeChip, BgeuChip,
-    BitOpChip, BltChip, BltuChip, BneChip, JalChip, JalrChip, LoadStoreChip, LuiChip, SllChip,
-    SltChip, SltuChip, SraChip, SrlChip, SubChip, SyscallChip,
+    BitOpChip, BltChip, BltuChip, BneChip, DivRemChip, JalChip, JalrChip, LoadStoreChip, LuiChip,
+    MulChip, MulhChip, SllChip, SltChip, SltuChip, SraChip, SrlChip, SubChip, SyscallChip,
 };
 
 pub use cpu::CpuChip;
@@ -71,6 +71,17 @@ impl MachineChip for Range128Chip {
         fill_main_col(qt_aux, is_lh, side_note);
         let [is_lb] = traces.column(row_idx, Column::IsLb);
         fill_main_col(qt_aux, is_lb, side_note);
+        // Check the last limbs of signed operands of MULH, MULHSU, DIV and REM
+        let [is_mulh] = traces.column(row_idx, Column::IsMulh);
+        let [is_mulhsu] = traces.column(row_idx, Column::IsMulhsu);
+        let [is_div] = traces.column(row_idx, Column::IsDiv);
+        let [is_rem] = traces.column(row_idx, Column::IsRem);
+        let [_, _, _, h2_mul] = traces.column(row_idx, Helper2);
+        fill_main_col(h2_mul, is_mulh + is_mulhsu + is_div + is_rem, side_note);
+        let [_, _, _, h3_mul] = traces.column(row_idx, Helper3);
+        fill_main_col(h3_mul, is_mulh + is_div + is_rem, side_note);
+        let [_, _, _, h1_rem] = traces.column(row_idx, Column::Helper1);
+        fill_main_col(h1_rem, is_div + is_rem, side_note);
     }
     /// Fills the whole interaction trace in one-go using SIMD in the stwo-usual way
     ///
@@ -125,6 +136,34 @@ impl MachineChip for Range128Chip {
             logup_trace_gen,
             lookup_element,
         );
+        let [is_mulh] = original_traces.get_base_column(Column::IsMulh);
+        let [is_mulhsu] = original_traces.get_base_column(Column::IsMulhsu);
+        let [is_div] = original_traces.get_base_column(Column::IsDiv);
+        let [is_rem] = original_traces.get_base_column(Column::IsRem);
+        let [_, _, _, h2_mul] = original_traces.get_base_column(Helper2);
+        check_col(
+            h2_mul,
+            &[is_mulh, is_mulhsu, is_div, is_rem],
+            original_traces.log_size(),
+            logup_trace_gen,
+            lookup_element,
+        );
+        let [_, _, _, h3_mul] = original_traces.get_base_column(Helper3);
+        check_col(
+            h3_mul,
+            &[is_mulh, is_div, is_rem],
+            original_traces.log_size(),
+            logup_trace_gen,
+            lookup_element,
+        );
+        let [_, _, _, h1_rem] = original_traces.get_base_column(Column::Helper1);
+        check_col(
+            h1_rem,
+            &[is_div, is_rem],
+            original_traces.log_size(),
+            logup_trace_gen,
+            lookup_element,
+        );
     }
 
     fn add_constraints<E: stwo_prover::constraint_framework::EvalAtRow>(
@@ -180,6 +219,37 @@ impl MachineChip for Range128Chip {
             numerator.into(),
             &[qt_aux],
         ));
+
+        let [is_mulh] = trace_eval.column_eval(Column::IsMulh);
+        let [is_mulhsu] = trace_eval.column_eval(Column::IsMulhsu);
+        let [is_div] = trace_eval.column_eval(Column::IsDiv);
+        let [is_rem] = trace_eval.column_eval(Column::IsRem);
+        let [_, _, _, h2_mul] = trace_eval.column_eval::<WORD_SIZE>(Helper2);
+        let numerator = is_mulh.clone() + is_mulhsu.clone() + is_div.clone() + is_rem.clone();
+
+        eval.add_to_relation(RelationEntry::new(
+            lookup_elements,
+            numerator.into(),
+            &[h2_mul],
+        ));
+
+        let [_, _, _, h3_mul] = trace_eval.column_eval::<WORD_SIZE>(Helper3);
+        let numerator = is_mulh.clone() + is_div.clone() + is_rem.clone();
+
+        eval.add_to_relation(RelationEntry::new(
+            lookup_elements,
+            numerator.into(),
+            &[h3_mul],
+        ));
+
+        let [_, _, _, h1_rem] = trace_eval.column_eval::<WORD_SIZE>(Column::Helper1);
+        let numerator = is_div.clone() + is_rem.clone();
+
+        eval.add_to_relation(RelationEntry::new(
+            lookup_elements,
+            numerator.into(),
+            &[h1_rem],
+        ));
     }
 }
 
@@ -276,6 +346,7 @@ mod test {
             init_memory: Default::default(),
             exit_code: Default::default(),
             public_output: Default::default(),
+            segment: None,
         };
         let program_traces = ProgramTracesBuilder::new(LOG_SIZE, program_trace_ref);
         let mut side_note = SideNote::new(&program_traces, &HarvardEmulator::default().finalize());
@@ -19,7 +19,8 @@ use crate::{
     },
     traits::MachineChip,
     virtual_column::{
-        IsAluImmShift, IsTypeB, IsTypeINoShift, IsTypeJ, IsTypeR, IsTypeS, IsTypeU, VirtualColumn,
+        IsAluImmShift, IsCustomTypeR, IsCustomTypeS, IsTypeB, IsTypeINoShift, IsTypeJ, IsTypeR,
+        IsTypeS, IsTypeU, VirtualColumn,
     },
 };
 
@@ -56,7 +57,26 @@ impl MachineChip for Range16Chip {
         side_note: &mut SideNote,
         _config: &ExtensionsConfig,
     ) {
-        if !step.as_ref().is_some_and(ProgramStep::is_builtin) {
+        let Some(program_step) = step.as_ref() else {
+            return;
+        };
+        if !program_step.is_builtin() {
+            // Custom instructions proven by external extensions are encoded as type R.
+            let [is_custom_type_r] = IsCustomTypeR::read_from_traces_builder(traces, row_idx);
+            if !is_custom_type_r.is_zero() {
+                for col in TYPE_R_CHECKED.iter() {
+                    let [val] = traces.column(row_idx, *col);
+                    fill_main_elm(val, side_note);
+                }
+            }
+            // Built-in custom instructions are encoded as type S.
+            let [is_custom_type_s] = IsCustomTypeS::read_from_traces_builder(traces, row_idx);
+            if !is_custom_type_s.is_zero() {
+                for col in TYPE_S_CHECKED.iter() {
+                    let [val] = traces.column(row_idx, *col);
+                    fill_main_elm(val, side_note);
+                }
+            }
             return;
         }
         fill_main_for_type::<IsTypeR>(
@@ -171,6 +191,18 @@ impl MachineChip for Range16Chip {
             logup_trace_gen,
             &TYPE_S_CHECKED,
         );
+        fill_interaction_for_type::<IsCustomTypeR>(
+            original_traces,
+            lookup_element,
+            logup_trace_gen,
+            &TYPE_R_CHECKED,
+        );
+        fill_interaction_for_type::<IsCustomTypeS>(
+            original_traces,
+            lookup_element,
+            logup_trace_gen,
+            &TYPE_S_CHECKED,
+        );
     }
 
     fn add_constraints<E: stwo_prover::constraint_framework::EvalAtRow>(
@@ -199,6 +231,18 @@ impl MachineChip for Range16Chip {
         add_constraints_for_type::<E, IsTypeJ>(eval, trace_eval, lookup_elements, &TYPE_J_CHECKED);
         add_constraints_for_type::<E, IsTypeB>(eval, trace_eval, lookup_elements, &TYPE_B_CHECKED);
         add_constraints_for_type::<E, IsTypeS>(eval, trace_eval, lookup_elements, &TYPE_S_CHECKED);
+        add_constraints_for_type::<E, IsCustomTypeR>(
+            eval,
+            trace_eval,
+            lookup_elements,
+            &TYPE_R_CHECKED,
+        );
+        add_constraints_for_type::<E, IsCustomTypeS>(
+            eval,
+            trace_eval,
+            lookup_elements,
+            &TYPE_S_CHECKED,
+        );
     }
 }
 
@@ -386,6 +430,7 @@ mod test {
             init_memory: Default::default(),
             exit_code: Default::default(),
             public_output: Default::default(),
+            segment: None,
         };
         let program_traces = ProgramTracesBuilder::new(LOG_SIZE, program_trace_ref);
         let mut side_note = SideNote::new(&program_traces, &HarvardEmulator::default().finalize());
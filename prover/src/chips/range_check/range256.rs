@@ -11,12 +11,12 @@ use stwo_prover::core::{
 
 use crate::{
     column::Column::{
-        self, CReg1TsPrev, CReg2TsPrev, CReg3TsPrev, FinalPrgMemoryCtr, Helper1, InstrVal,
-        OpC16_23, OpC24_31, Pc, PcNextAux, PrevCtr, ProgCtrCur, ProgCtrPrev, Qt, Ram1TsPrev,
-        Ram1TsPrevAux, Ram1ValCur, Ram1ValPrev, Ram2TsPrev, Ram2TsPrevAux, Ram2ValCur, Ram2ValPrev,
-        Ram3TsPrev, Ram3TsPrevAux, Ram3ValCur, Ram3ValPrev, Ram4TsPrev, Ram4TsPrevAux, Ram4ValCur,
-        Ram4ValPrev, RamBaseAddr, Reg1TsPrev, Reg2TsPrev, Reg3TsPrev, Rem, RemDiff, ValueA, ValueB,
-        ValueC,
+        self, AbsRem, AbsValueC, CReg1TsPrev, CReg2TsPrev, CReg3TsPrev, FinalPrgMemoryCtr, Helper1,
+        InstrVal, MulCarryLow, OpC16_23, OpC24_31, Pc, PcNextAux, PrevCtr, ProgCtrCur, ProgCtrPrev,
+        Qt, Ram1TsPrev, Ram1TsPrevAux, Ram1ValCur, Ram1ValPrev, Ram2TsPrev, Ram2TsPrevAux,
+        Ram2ValCur, Ram2ValPrev, Ram3TsPrev, Ram3TsPrevAux, Ram3ValCur, Ram3ValPrev, Ram4TsPrev,
+        Ram4TsPrevAux, Ram4ValCur, Ram4ValPrev, RamBaseAddr, Reg1TsPrev, Reg2TsPrev, Reg3TsPrev,
+        Rem, RemDiff, ValueA, ValueB, ValueC,
     },
     components::AllLookupElements,
     extensions::ExtensionsConfig,
@@ -37,7 +37,7 @@ const LOOKUP_TUPLE_SIZE: usize = 1;
 stwo_prover::relation!(Range256LookupElements, LOOKUP_TUPLE_SIZE);
 
 impl Range256Chip {
-    const CHECKED_WORDS: [Column; 29] = [
+    const CHECKED_WORDS: [Column; 32] = [
         Pc,
         PcNextAux,
         InstrVal,
@@ -67,6 +67,9 @@ impl Range256Chip {
         Rem,
         Qt,
         RemDiff,
+        MulCarryLow,
+        AbsValueC,
+        AbsRem,
     ];
 
     const CHECKED_BYTES: [Column; 8] = [
@@ -300,6 +303,7 @@ mod test {
             init_memory: Default::default(),
             exit_code: Default::default(),
             public_output: Default::default(),
+            segment: None,
         };
         let program_traces = ProgramTracesBuilder::new(LOG_SIZE, program_trace_ref);
         let mut side_note = SideNote::new(&program_traces, &HarvardEmulator::default().finalize());
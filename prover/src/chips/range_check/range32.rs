@@ -158,6 +158,7 @@ mod test {
             init_memory: Default::default(),
             exit_code: Default::default(),
             public_output: Default::default(),
+            segment: None,
         };
         let program_traces = ProgramTracesBuilder::new(LOG_SIZE, program_trace_ref);
         let mut side_note = SideNote::new(&program_traces, &HarvardEmulator::default().finalize());
@@ -22,7 +22,8 @@ use crate::{
     },
     traits::MachineChip,
     virtual_column::{
-        IsTypeB, IsTypeINoShift, IsTypeJ, IsTypeS, VirtualColumn, VirtualColumnForSum,
+        IsCustomTypeS, IsTypeB, IsTypeINoShift, IsTypeJ, IsTypeS, VirtualColumn,
+        VirtualColumnForSum,
     },
 };
 
@@ -35,6 +36,24 @@ impl VirtualColumnForSum for Helper1MsbChecked {
     }
 }
 
+/// A flag for MulCarryHigh to be checked against 0..=7.
+struct MulCarryHighChecked;
+
+impl VirtualColumnForSum for MulCarryHighChecked {
+    fn columns() -> &'static [Column] {
+        &[
+            Column::IsMul,
+            Column::IsMulh,
+            Column::IsMulhsu,
+            Column::IsMulhu,
+            Column::IsDiv,
+            Column::IsDivu,
+            Column::IsRem,
+            Column::IsRemu,
+        ]
+    }
+}
+
 /// A Chip for range-checking values for 0..=7
 ///
 /// Range8Chip needs to be located at the end of the chip composition together with the other range check chips
@@ -65,10 +84,21 @@ impl MachineChip for Range8Chip {
         side_note: &mut SideNote,
         _config: &ExtensionsConfig,
     ) {
-        let step = match step.as_ref().filter(|s| s.is_builtin()) {
+        let step = match step.as_ref() {
             None => return, // Nothing to check in padding rows
             Some(step) => step,
         };
+        if !step.is_builtin() {
+            // Built-in custom instructions are encoded as type S.
+            let [is_custom_type_s] = IsCustomTypeS::read_from_traces_builder(traces, row_idx);
+            if !is_custom_type_s.is_zero() {
+                for col in TYPE_S_CHECKED.iter() {
+                    let [val] = traces.column(row_idx, *col);
+                    fill_main_elm(val, side_note);
+                }
+            }
+            return;
+        }
         // Add multiplicities for Helper1[0] in case of SLL, SLLI, SRL SRLI, SRA and SRAI
         if matches!(
             step.step.instruction.opcode.builtin(),
@@ -115,6 +145,24 @@ impl MachineChip for Range8Chip {
             &TYPE_S_CHECKED,
             side_note,
         );
+
+        // Add multiplicities for MulCarryHigh in case of multiplication and division
+        if matches!(
+            step.step.instruction.opcode.builtin(),
+            Some(BuiltinOpcode::MUL)
+                | Some(BuiltinOpcode::MULH)
+                | Some(BuiltinOpcode::MULHSU)
+                | Some(BuiltinOpcode::MULHU)
+                | Some(BuiltinOpcode::DIV)
+                | Some(BuiltinOpcode::DIVU)
+                | Some(BuiltinOpcode::REM)
+                | Some(BuiltinOpcode::REMU)
+        ) {
+            let carry_high: [_; WORD_SIZE] = traces.column(row_idx, Column::MulCarryHigh);
+            for limb in carry_high {
+                fill_main_elm(limb, side_note);
+            }
+        }
     }
 
     /// Fills the whole interaction trace in one-go using SIMD in the stwo-usual way
@@ -152,6 +200,12 @@ impl MachineChip for Range8Chip {
             logup_trace_gen,
             &TYPE_S_CHECKED,
         );
+        fill_interaction_for_type::<IsCustomTypeS>(
+            original_traces,
+            lookup_element,
+            logup_trace_gen,
+            &TYPE_S_CHECKED,
+        );
 
         // Fill the interaction trace for Helper1[0] in case of SLL, SRL and SRA
         let [value_basecolumn, _, _, _]: [&BaseColumn; WORD_SIZE] =
@@ -167,6 +221,21 @@ impl MachineChip for Range8Chip {
             logup_col_gen.write_frac(vec_row, is_type.into(), denom);
         }
         logup_col_gen.finalize_col();
+
+        // Fill the interaction trace for MulCarryHigh in case of multiplication and division
+        let carry_high: [&BaseColumn; WORD_SIZE] =
+            original_traces.get_base_column(Column::MulCarryHigh);
+        for limb_basecolumn in carry_high {
+            let mut logup_col_gen = logup_trace_gen.new_col();
+            for vec_row in 0..(1 << (log_size - LOG_N_LANES)) {
+                let checked_tuple = vec![limb_basecolumn.data[vec_row]];
+                let denom = lookup_element.combine(&checked_tuple);
+                let [is_mul] =
+                    MulCarryHighChecked::read_from_finalized_traces(original_traces, vec_row);
+                logup_col_gen.write_frac(vec_row, is_mul.into(), denom);
+            }
+            logup_col_gen.finalize_col();
+        }
     }
 
     fn add_constraints<E: stwo_prover::constraint_framework::EvalAtRow>(
@@ -186,6 +255,12 @@ impl MachineChip for Range8Chip {
         add_constraints_for_type::<E, IsTypeJ>(eval, trace_eval, lookup_elements, &TYPE_J_CHECKED);
         add_constraints_for_type::<E, IsTypeB>(eval, trace_eval, lookup_elements, &TYPE_B_CHECKED);
         add_constraints_for_type::<E, IsTypeS>(eval, trace_eval, lookup_elements, &TYPE_S_CHECKED);
+        add_constraints_for_type::<E, IsCustomTypeS>(
+            eval,
+            trace_eval,
+            lookup_elements,
+            &TYPE_S_CHECKED,
+        );
 
         // Add checked multiplicities for Helper1[0] in case of SLL, SRL and SRA
         let [numerator] = Helper1MsbChecked::eval(trace_eval);
@@ -196,6 +271,17 @@ impl MachineChip for Range8Chip {
             numerator.into(),
             &[value],
         ));
+
+        // Add checked multiplicities for MulCarryHigh in case of multiplication and division
+        let [numerator] = MulCarryHighChecked::eval(trace_eval);
+        let carry_high: [_; WORD_SIZE] = trace_eval.column_eval(Column::MulCarryHigh);
+        for limb in carry_high {
+            eval.add_to_relation(RelationEntry::new(
+                lookup_elements,
+                numerator.clone().into(),
+                &[limb],
+            ));
+        }
     }
 }
 
@@ -4,13 +4,14 @@ use num_traits::One;
 
 use crate::{
     column::Column::{
-        self, BorrowFlag, CH1Minus, CH2Minus, CH3Minus, CarryFlag, ImmC, IsAdd, IsAnd, IsAuipc,
-        IsBeq, IsBge, IsBgeu, IsBlt, IsBltu, IsBne, IsEbreak, IsEcall, IsJal, IsJalr, IsLb, IsLbu,
-        IsLh, IsLhu, IsLui, IsLw, IsOr, IsPadding, IsSb, IsSh, IsSll, IsSlt, IsSltu, IsSra, IsSrl,
-        IsSub, IsSw, IsSysCycleCount, IsSysDebug, IsSysHalt, IsSysHeapReset, IsSysPrivInput,
-        IsSysStackReset, IsXor, LtFlag, OpA0, OpB0, OpB4, OpC0, OpC11, OpC12, OpC20, OpC4, PcCarry,
-        ProgCtrCarry, RemAux, SgnA, SgnB, SgnC, ShiftBit1, ShiftBit2, ShiftBit3, ShiftBit4,
-        ShiftBit5, ValueAEffectiveFlag,
+        self, AbsCarry, BorrowFlag, CH1Minus, CH2Minus, CH3Minus, CarryFlag, DivByZero, ImmC,
+        IsAdd, IsAnd, IsAuipc, IsBeq, IsBge, IsBgeu, IsBlt, IsBltu, IsBne, IsCompressed, IsDiv,
+        IsDivu, IsEbreak, IsEcall, IsJal, IsJalr, IsLb, IsLbu, IsLh, IsLhu, IsLui, IsLw, IsMul,
+        IsMulh, IsMulhsu, IsMulhu, IsOr, IsPadding, IsRem, IsRemu, IsSb, IsSh, IsSll, IsSlt,
+        IsSltu, IsSra, IsSrl, IsSub, IsSw, IsSysAuxInput, IsSysCycleCount, IsSysDebug, IsSysHalt,
+        IsSysHeapReset, IsSysPrivInput, IsSysStackReset, IsXor, LtFlag, OpA0, OpB0, OpB4, OpC0,
+        OpC11, OpC12, OpC20, OpC4, PcCarry, ProgCtrCarry, RemAux, SgnA, SgnB, SgnC, SgnQt, SgnRem,
+        ShiftBit1, ShiftBit2, ShiftBit3, ShiftBit4, ShiftBit5, ValueAEffectiveFlag,
     },
     components::AllLookupElements,
     extensions::ExtensionsConfig,
@@ -24,9 +25,10 @@ use crate::{
 /// RangeBoolChip can be located anywhere in the chip composition.
 pub struct RangeBoolChip;
 
-const CHECKED_SINGLE: [Column; 49] = [
+const CHECKED_SINGLE: [Column; 62] = [
     ValueAEffectiveFlag,
     ImmC,
+    IsCompressed,
     IsAdd,
     IsOr,
     IsAnd,
@@ -55,6 +57,14 @@ const CHECKED_SINGLE: [Column; 49] = [
     IsSll,
     IsSrl,
     IsSra,
+    IsMul,
+    IsMulh,
+    IsMulhsu,
+    IsMulhu,
+    IsDiv,
+    IsDivu,
+    IsRem,
+    IsRemu,
     IsEcall,
     IsEbreak,
     IsSysCycleCount,
@@ -62,6 +72,7 @@ const CHECKED_SINGLE: [Column; 49] = [
     IsSysHalt,
     IsSysHeapReset,
     IsSysPrivInput,
+    IsSysAuxInput,
     IsSysStackReset,
     IsPadding,
     LtFlag,
@@ -74,8 +85,11 @@ const CHECKED_SINGLE: [Column; 49] = [
     ShiftBit3,
     ShiftBit4,
     ShiftBit5,
+    SgnQt,
+    SgnRem,
+    DivByZero,
 ];
-const CHECKED_HALF_WORD: [Column; 7] = [
+const CHECKED_HALF_WORD: [Column; 8] = [
     CarryFlag,
     PcCarry,
     CH1Minus,
@@ -83,6 +97,7 @@ const CHECKED_HALF_WORD: [Column; 7] = [
     CH3Minus,
     ProgCtrCarry,
     BorrowFlag,
+    AbsCarry,
 ];
 const TYPE_R_CHECKED_SINGLE: [Column; 3] = [OpC4, OpA0, OpB0];
 const TYPE_I_NO_SHIFT_SINGLE: [Column; 3] = [OpC11, OpA0, OpB0];
@@ -155,6 +170,20 @@ impl MachineChip for RangeBoolChip {
             let [col] = trace_eval.column_eval(col);
             eval.add_constraint(is_type_s.clone() * col.clone() * (col - E::F::one()));
         }
+
+        // Built-in custom instructions are encoded as type S.
+        let [is_custom_type_s] = virtual_column::IsCustomTypeS::eval(trace_eval);
+        for col in TYPE_S_CHECKED_SINGLE {
+            let [col] = trace_eval.column_eval(col);
+            eval.add_constraint(is_custom_type_s.clone() * col.clone() * (col - E::F::one()));
+        }
+
+        // Custom instructions proven by external extensions are encoded as type R.
+        let [is_custom_type_r] = virtual_column::IsCustomTypeR::eval(trace_eval);
+        for col in TYPE_R_CHECKED_SINGLE {
+            let [col] = trace_eval.column_eval(col);
+            eval.add_constraint(is_custom_type_r.clone() * col.clone() * (col - E::F::one()));
+        }
     }
 }
 
@@ -50,9 +50,12 @@ pub enum Column {
     /// Is operand op_c an immediate value?
     #[size = 1]
     ImmC,
-    /// The actual 32-bit of the instruction stored at pc.
+    /// The actual 32-bit of the instruction stored at pc, which is the expansion of compressed instructions.
     #[size = 4]
     InstrVal,
+    /// Boolean flag on whether the instruction stored at pc is a 16-bit compressed instruction.
+    #[size = 1]
+    IsCompressed,
     /// The previous counter for the instruction stored at pc.
     #[size = 4]
     PrevCtr,
@@ -154,6 +157,30 @@ pub enum Column {
     /// Boolean flag on whether the row is a SRA.
     #[size = 1]
     IsSra,
+    /// Boolean flag on whether the row is a MUL.
+    #[size = 1]
+    IsMul,
+    /// Boolean flag on whether the row is a MULH.
+    #[size = 1]
+    IsMulh,
+    /// Boolean flag on whether the row is a MULHSU.
+    #[size = 1]
+    IsMulhsu,
+    /// Boolean flag on whether the row is a MULHU.
+    #[size = 1]
+    IsMulhu,
+    /// Boolean flag on whether the row is a DIV.
+    #[size = 1]
+    IsDiv,
+    /// Boolean flag on whether the row is a DIVU.
+    #[size = 1]
+    IsDivu,
+    /// Boolean flag on whether the row is a REM.
+    #[size = 1]
+    IsRem,
+    /// Boolean flag on whether the row is a REMU.
+    #[size = 1]
+    IsRemu,
     /// Boolean flag on whether the row is an ECALL.
     #[size = 1]
     IsEcall,
@@ -172,6 +199,9 @@ pub enum Column {
     /// Boolean flag on whether the row is an ECALL_PRIVATE_INPUT (ReadFromPrivateInput).
     #[size = 1]
     IsSysPrivInput,
+    /// Boolean flag on whether the row is an ECALL_AUX_INPUT (ReadFromAuxiliaryInput).
+    #[size = 1]
+    IsSysAuxInput,
     /// Boolean flag on whether the row is an ECALL_CYCLECOUNT (CycleCount).
     #[size = 1]
     IsSysCycleCount,
@@ -184,6 +214,21 @@ pub enum Column {
     /// Boolean flag on whether the row is a custom keccakf instruction call.
     #[size = 1]
     IsCustomKeccak,
+    /// Boolean flag on whether the row is a custom sha256_compress instruction call.
+    #[size = 1]
+    IsCustomSha256,
+    /// Boolean flag on whether the row is a custom mul_add_mod instruction call.
+    #[size = 1]
+    IsCustomBigInt,
+    /// Boolean flag on whether the row is a custom poseidon2 instruction call.
+    #[size = 1]
+    IsCustomPoseidon2,
+    /// Boolean flag on whether the row is a custom memcpy or memset instruction call.
+    #[size = 1]
+    IsCustomMemcpy,
+    /// Boolean flag on whether the row is a custom instruction proven by an external extension.
+    #[size = 1]
+    IsCustomExternal,
     /// Boolean flag on whether the row is a padding.
     #[size = 1]
     IsPadding,
@@ -345,6 +390,9 @@ pub enum Column {
     /// The starting address of the read-write memory access
     #[size = 4]
     RamBaseAddr,
+    /// The access mode of the memory at RamBaseAddr, see [`AccessMode`](crate::chips::instructions::load_store::AccessMode)
+    #[size = 1]
+    RamAccessMode,
     /// The new value of the read-write memory at RamBaseAddr, if accessed
     #[size = 1]
     Ram1ValCur,
@@ -459,7 +507,7 @@ pub enum Column {
     #[size = 1]
     OpC24_31,
 
-    /// Auxiliary variable for incrementing program counter by four, assumes 16-bit limbs
+    /// Auxiliary variable for incrementing program counter by the instruction size, assumes 16-bit limbs
     #[size = 2]
     PcCarry,
 
@@ -472,6 +520,31 @@ pub enum Column {
     /// On bit-op rows, the more-significant four bits of each limb of ValueC. On those rows, ValueC4_7[i] contains ValueC[i] >> 4.
     #[size = 4]
     ValueC4_7,
+
+    /// On multiplication and division rows, the lower eight bits of the carries at each 16-bit boundary of the 64-bit product.
+    #[size = 4]
+    MulCarryLow,
+    /// On multiplication and division rows, the upper three bits of the carries at each 16-bit boundary of the 64-bit product.
+    #[size = 4]
+    MulCarryHigh,
+    /// On division rows, the sign of the quotient used in checking quotient・divisor + remainder = dividend.
+    #[size = 1]
+    SgnQt,
+    /// On division rows, the sign of the remainder.
+    #[size = 1]
+    SgnRem,
+    /// On division rows, whether the divisor is zero.
+    #[size = 1]
+    DivByZero,
+    /// On division rows, the absolute value of the divisor.
+    #[size = 4]
+    AbsValueC,
+    /// On division rows, the absolute value of the remainder.
+    #[size = 4]
+    AbsRem,
+    /// On division rows, the carries at the 16-bit boundary when negating the divisor and the remainder.
+    #[size = 2]
+    AbsCarry,
 }
 
 // proc macro derived:
@@ -495,9 +568,18 @@ pub enum ProgramColumn {
     /// Program memory content: 1 means the row contains real PrgMemory*. 0 otherwise.
     #[size = 1]
     PrgMemoryFlag,
+    /// Program memory content: 1 means the instruction at PrgMemoryPc is a 16-bit compressed instruction, whose expansion is in PrgMemoryWord.
+    #[size = 1]
+    PrgMemoryCompressed,
     /// The first program counter for finding the first executed instruction
     #[size = 4]
     PrgInitialPc,
+    /// The program counter following the last executed instruction, only used by continuation segments
+    #[size = 4]
+    PrgFinalPc,
+    /// 1 on every row if the trace is a continuation segment with public final program counter. 0 otherwise.
+    #[size = 1]
+    PrgSegmentFlag,
 }
 
 // proc macro derived:
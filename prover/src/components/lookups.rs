@@ -8,17 +8,29 @@
 //! Internally, [`AllLookupElements`] is a hashmap storing a set of generated alphas and z (=lookup elements) for each
 //! type. Since [`stwo_prover::constraint_framework::Relation`] is not object safe and cannot be boxed, the only way
 //! to store it is by using an enum.
+//!
+//! Relations of external extensions are only known at runtime and are stored separately, keyed by the name of
+//! the extension, see [`ExternalLookupElements`].
 
 use std::{any::TypeId, collections::HashMap};
 
+use crate::extensions::ExternalLookupElements;
+
 pub use crate::chips::{
-    custom::keccak_lookups::{
-        BitNotAndLookupElements as KeccakBitNotAndLookupElements,
-        BitRotateLookupElements as KeccakBitRotateLookupElements,
-        StateLookupElements as KeccakStateLookupElements,
-        XorLookupElements as KeccakXorLookupElements,
+    custom::{
+        keccak_lookups::{
+            BitNotAndLookupElements as KeccakBitNotAndLookupElements,
+            BitRotateLookupElements as KeccakBitRotateLookupElements,
+            StateLookupElements as KeccakStateLookupElements,
+            XorLookupElements as KeccakXorLookupElements,
+        },
+        sha256_lookups::StateLookupElements as Sha256StateLookupElements,
+        ExternalInstructionLookupElements, MemcpyCallLookupElements,
+    },
+    instructions::{
+        bit_op::BitOpLookupElements,
+        load_store::{LoadStoreLookupElements, PublicOutputLookupElements},
     },
-    instructions::{bit_op::BitOpLookupElements, load_store::LoadStoreLookupElements},
     memory_check::{
         program_mem_check::ProgramCheckLookupElements,
         register_mem_check::RegisterCheckLookupElements,
@@ -38,6 +50,7 @@ register_relation! {
     enum RelationVariant {
         BitOpLookupElements,
         LoadStoreLookupElements,
+        PublicOutputLookupElements,
         ProgramCheckLookupElements,
         RegisterCheckLookupElements,
         Range8LookupElements,
@@ -49,12 +62,18 @@ register_relation! {
         KeccakBitNotAndLookupElements,
         KeccakStateLookupElements,
         KeccakBitRotateLookupElements,
+        Sha256StateLookupElements,
+        MemcpyCallLookupElements,
+        ExternalInstructionLookupElements,
     };
     pub(crate) trait RegisteredLookupBound {}
 }
 
 #[derive(Default, Debug, Clone)]
-pub struct AllLookupElements(HashMap<TypeId, RelationVariant>);
+pub struct AllLookupElements(
+    HashMap<TypeId, RelationVariant>,
+    HashMap<String, ExternalLookupElements>,
+);
 
 impl AllLookupElements {
     pub fn insert<T: Into<RelationVariant> + 'static>(&mut self, relation: T) {
@@ -63,8 +82,43 @@ impl AllLookupElements {
         }
     }
 
+    pub(crate) fn insert_external(&mut self, relation: ExternalLookupElements) {
+        if self
+            .1
+            .insert(relation.name().to_owned(), relation)
+            .is_some()
+        {
+            panic!("attempt to insert duplicate relation")
+        }
+    }
+
+    /// Returns lookup elements drawn for the external extension with the given name.
+    pub fn external(&self, name: &str) -> &ExternalLookupElements {
+        self.1
+            .get(name)
+            .expect("lookup elements weren't initialized")
+    }
+
+    /// Returns lookup elements of custom instructions sent to external extensions.
+    pub fn external_instruction(&self) -> &ExternalInstructionLookupElements {
+        self.as_ref()
+    }
+
+    /// Returns lookup elements of the read-write memory checking.
+    pub fn memory(&self) -> &LoadStoreLookupElements {
+        self.as_ref()
+    }
+
+    /// Returns whether lookup elements of the relation have been drawn.
+    pub(crate) fn contains<T: 'static>(&self) -> bool {
+        self.0.contains_key(&TypeId::of::<T>())
+    }
+
     pub fn dummy() -> Self {
-        Self(HashMap::from_iter(RelationVariant::dummy_array()))
+        Self(
+            HashMap::from_iter(RelationVariant::dummy_array()),
+            HashMap::new(),
+        )
     }
 
     pub fn is_empty(&self) -> bool {
@@ -0,0 +1,134 @@
+//! Security parameters of the polynomial commitment scheme.
+
+use serde::{Deserialize, Serialize};
+use stwo_prover::core::{fri::FriConfig, pcs::PcsConfig};
+
+/// Number of bits of security the prover and the verifier require unless the caller sets another floor.
+pub const DEFAULT_MIN_SECURITY_BITS: u32 = 96;
+
+/// Bounds of the log2 of the blowup factor supported by the FRI protocol.
+pub const LOG_MIN_BLOWUP_FACTOR: u32 = 1;
+pub const LOG_MAX_BLOWUP_FACTOR: u32 = 16;
+
+/// Upper bound on the number of FRI queries, guards the verifier against unreasonably large proofs.
+pub const MAX_N_QUERIES: usize = 1024;
+
+/// Upper bound on the number of proof-of-work bits.
+pub const MAX_POW_BITS: u32 = 32;
+
+/// Prover configuration, trading the proof size and the verification time for the prover time.
+///
+/// The configuration is stored in the proof and the verifier rejects proofs below its security floor, by default
+/// [`DEFAULT_MIN_SECURITY_BITS`] bits, see [`ProverConfig::security_bits`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProverConfig {
+    /// Log2 of the ratio between the size of the evaluation domain and the trace size.
+    pub log_blowup_factor: u32,
+    /// Number of FRI queries.
+    pub n_queries: usize,
+    /// Number of proof-of-work bits required from the prover before drawing queries.
+    pub pow_bits: u32,
+}
+
+impl Default for ProverConfig {
+    /// Provides [`DEFAULT_MIN_SECURITY_BITS`] bits of security with the smallest blowup factor.
+    fn default() -> Self {
+        Self {
+            log_blowup_factor: 1,
+            n_queries: 86,
+            pow_bits: 10,
+        }
+    }
+}
+
+impl ProverConfig {
+    /// Returns the conjectured number of bits of security provided by the configuration.
+    pub fn security_bits(&self) -> u32 {
+        let queries_bits = (self.n_queries as u64).saturating_mul(self.log_blowup_factor as u64);
+        u32::try_from(queries_bits)
+            .unwrap_or(u32::MAX)
+            .saturating_add(self.pow_bits)
+    }
+
+    /// Checks that the parameters are supported and provide at least `min_security_bits` bits of security.
+    pub fn validate(&self, min_security_bits: u32) -> Result<(), String> {
+        let Self {
+            log_blowup_factor,
+            n_queries,
+            pow_bits,
+        } = *self;
+        if !(LOG_MIN_BLOWUP_FACTOR..=LOG_MAX_BLOWUP_FACTOR).contains(&log_blowup_factor) {
+            return Err(format!(
+                "log blowup factor {log_blowup_factor} is out of range {LOG_MIN_BLOWUP_FACTOR}..={LOG_MAX_BLOWUP_FACTOR}"
+            ));
+        }
+        if n_queries == 0 || n_queries > MAX_N_QUERIES {
+            return Err(format!(
+                "number of queries {n_queries} is out of range 1..={MAX_N_QUERIES}"
+            ));
+        }
+        if pow_bits > MAX_POW_BITS {
+            return Err(format!(
+                "proof-of-work bits {pow_bits} exceed {MAX_POW_BITS}"
+            ));
+        }
+        let security_bits = self.security_bits();
+        if security_bits < min_security_bits {
+            return Err(format!(
+                "{security_bits} bits of security is below the minimum of {min_security_bits}"
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl From<ProverConfig> for PcsConfig {
+    fn from(config: ProverConfig) -> Self {
+        Self {
+            pow_bits: config.pow_bits,
+            fri_config: FriConfig::new(0, config.log_blowup_factor, config.n_queries),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_config() {
+        let config = ProverConfig::default();
+        assert_eq!(config.security_bits(), DEFAULT_MIN_SECURITY_BITS);
+        assert!(config.validate(DEFAULT_MIN_SECURITY_BITS).is_ok());
+        assert!(config.validate(config.security_bits() + 1).is_err());
+
+        let config = ProverConfig {
+            log_blowup_factor: 2,
+            n_queries: 48,
+            pow_bits: 20,
+        };
+        assert_eq!(config.security_bits(), 116);
+        assert!(config.validate(100).is_ok());
+
+        for config in [
+            ProverConfig {
+                log_blowup_factor: 0,
+                ..config
+            },
+            ProverConfig {
+                log_blowup_factor: LOG_MAX_BLOWUP_FACTOR + 1,
+                ..config
+            },
+            ProverConfig {
+                n_queries: 0,
+                ..config
+            },
+            ProverConfig {
+                pow_bits: MAX_POW_BITS + 1,
+                ..config
+            },
+        ] {
+            assert!(config.validate(0).is_err());
+        }
+    }
+}
@@ -0,0 +1,583 @@
+//! Constraint debugging.
+//!
+//! [`Machine::debug_constraints`](crate::machine::Machine::debug_constraints) evaluates the constraints of every
+//! component on every row of the concrete trace, rather than checking their random linear combination as the prover
+//! does. A violated constraint is attributed to the chip that added it, along with the row, the instruction
+//! executed there and the columns it reads. A column is considered read by a constraint if changing its value at the
+//! row changes the value of the constraint.
+//!
+//! Logup constraints are evaluated on the interaction trace, each batch of entries of a component against its
+//! interaction column. Logup relations are also checked by matching entries across all components: entries with the
+//! same combined values must have multiplicities summing up to zero, including the entries of the exit code and the
+//! public output added by the verifier. Each entry of an unbalanced group is reported where it was added.
+
+use std::{collections::BTreeMap, fmt, ops::Mul};
+
+use num_traits::{One, Zero};
+use stwo_prover::{
+    constraint_framework::{
+        EvalAtRow, FrameworkEval, INTERACTION_TRACE_IDX, ORIGINAL_TRACE_IDX, PREPROCESSED_TRACE_IDX,
+    },
+    core::{
+        backend::simd::{column::BaseColumn, SimdBackend},
+        fields::{
+            m31::BaseField, qm31::SecureField, secure_column::SECURE_EXTENSION_DEGREE, FieldExpOps,
+        },
+        lookups::utils::Fraction,
+        poly::{circle::CircleEvaluation, BitReversedOrder},
+        utils::{bit_reverse_index, coset_index_to_circle_domain_index},
+        ColumnVec,
+    },
+};
+
+use crate::{
+    column::{Column, PreprocessedColumn, ProgramColumn},
+    components::AllLookupElements,
+    extensions::{ComponentTrace, ExtensionsConfig},
+    trace::{eval::TraceEval, ProgramStep},
+    traits::MachineChip,
+};
+
+/// Name of the base component in reports.
+pub const MAIN_COMPONENT: &str = "Main";
+
+/// Name of the exit code and the public output added by the verifier in reports.
+pub const PROVEN_OUTPUT: &str = "ProvenOutput";
+
+/// Differences added to a value read by a failing constraint, to find out whether the constraint depends on it.
+///
+/// Two of them rule out a polynomial constraint taking the same value by chance.
+const PERTURBATIONS: [u32; 2] = [1, 1 << 20];
+
+/// Reason of a [`ConstraintViolation`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ViolationKind {
+    /// Constraint with the given index doesn't evaluate to zero, indices start from the first constraint of the chip.
+    Constraint(usize),
+    /// Logup constraint of the interaction column with the given index doesn't evaluate to zero.
+    Logup(usize),
+    /// Logup entry with the given multiplicity isn't matched by other entries of the relation.
+    UnbalancedLookup(SecureField),
+}
+
+/// A failing constraint or an unmatched logup entry, located in the trace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConstraintViolation {
+    /// Name of the component, [`MAIN_COMPONENT`] for the base one.
+    pub component: String,
+    /// Name of the chip in the base component, extensions use the name of the component.
+    pub chip: String,
+    /// Row of the component trace.
+    pub row: usize,
+    /// Program counter of the instruction executed at the row of the base component.
+    pub pc: Option<u32>,
+    /// Instruction executed at the row of the base component.
+    pub instruction: Option<String>,
+    pub kind: ViolationKind,
+    /// Columns read by the failing constraint, suffixed by the row offset if it isn't zero. Columns of the base
+    /// component are named after their variants, the ones of extensions after their index in the trace.
+    ///
+    /// Empty for unmatched lookups.
+    pub columns: Vec<String>,
+}
+
+impl fmt::Display for ConstraintViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{} at row {}: ", self.component, self.chip, self.row)?;
+        match self.kind {
+            ViolationKind::Constraint(idx) => write!(f, "constraint #{idx} isn't satisfied")?,
+            ViolationKind::Logup(idx) => write!(f, "logup constraint #{idx} isn't satisfied")?,
+            ViolationKind::UnbalancedLookup(multiplicity) => {
+                write!(f, "unmatched lookup with multiplicity {multiplicity:?}")?
+            }
+        }
+        if !self.columns.is_empty() {
+            write!(f, " reading {}", self.columns.join(", "))?;
+        }
+        if let (Some(pc), Some(instruction)) = (self.pc, &self.instruction) {
+            write!(f, " (pc {pc:#x}: {instruction})")?;
+        }
+        Ok(())
+    }
+}
+
+/// Interaction trace of a component in coset order, along with its claimed logup sum.
+pub(crate) struct InteractionTrace {
+    columns: Vec<Vec<BaseField>>,
+    claimed_sum: SecureField,
+}
+
+impl InteractionTrace {
+    pub(crate) fn new(
+        columns: ColumnVec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>>,
+        claimed_sum: SecureField,
+    ) -> Self {
+        Self {
+            columns: columns.iter().map(|col| coset_order(&col.values)).collect(),
+            claimed_sum,
+        }
+    }
+}
+
+/// Constraint of a component, added by a chip or by finalizing logup.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum ConstraintId {
+    Chip(&'static str, usize),
+    Logup(usize),
+}
+
+/// Records the value of a constraint, while adding a difference to one of the values read by the evaluator.
+struct Probe {
+    constraint: ConstraintId,
+    /// Index of the value read and the difference added to it.
+    perturbation: Option<(usize, BaseField)>,
+    /// Trace, column and row offset of every value read.
+    reads: Vec<(usize, usize, isize)>,
+    value: Option<SecureField>,
+}
+
+/// Evaluator of constraints at a single row of the trace in coset order, used for debugging only.
+#[doc(hidden)]
+pub struct DebugEvaluator<'a> {
+    trace: [&'a [Vec<BaseField>]; 3],
+    col_index: [usize; 3],
+    row: usize,
+    /// Claimed logup sum of the component divided by the number of rows, subtracted from every row by the prover.
+    cumsum_shift: SecureField,
+    chip: &'static str,
+    constraint_index: usize,
+    violations: Vec<ConstraintId>,
+    fractions: Vec<(&'static str, Fraction<SecureField, SecureField>)>,
+    probe: Option<Probe>,
+}
+
+impl<'a> DebugEvaluator<'a> {
+    fn new(
+        trace: [&'a [Vec<BaseField>]; 3],
+        cumsum_shift: SecureField,
+        row: usize,
+        probe: Option<Probe>,
+    ) -> Self {
+        Self {
+            trace,
+            col_index: [0; 3],
+            row,
+            cumsum_shift,
+            chip: "",
+            constraint_index: 0,
+            violations: Vec::new(),
+            fractions: Vec::new(),
+            probe,
+        }
+    }
+
+    /// Attributes constraints and logup entries added after this call to the chip of type `name`.
+    pub(crate) fn enter_chip(&mut self, name: &'static str) {
+        let path = name.split('<').next().unwrap_or(name);
+        self.chip = path.rsplit("::").next().unwrap_or(path);
+        self.constraint_index = 0;
+    }
+
+    fn check(&mut self, constraint: ConstraintId, value: SecureField) {
+        if let Some(probe) = &mut self.probe {
+            if probe.constraint == constraint {
+                probe.value = Some(value);
+            }
+        }
+        if !value.is_zero() {
+            self.violations.push(constraint);
+        }
+    }
+
+    fn next_secure_interaction_mask<const N: usize>(
+        &mut self,
+        offsets: [isize; N],
+    ) -> [SecureField; N] {
+        let coordinates: [[BaseField; N]; SECURE_EXTENSION_DEGREE] =
+            std::array::from_fn(|_| self.next_interaction_mask(INTERACTION_TRACE_IDX, offsets));
+        std::array::from_fn(|i| SecureField::from_m31_array(coordinates.map(|values| values[i])))
+    }
+
+    /// Evaluates logup constraints of the fractions written at the row, batched by `batch_of` their index.
+    ///
+    /// Interaction columns hold sums of batches up to their own one, the last column is additionally summed up over
+    /// rows and shifted by the claimed sum.
+    fn finalize_batches(&mut self, batch_of: impl Fn(usize) -> usize) {
+        let Some(last) = self.fractions.len().checked_sub(1) else {
+            return;
+        };
+        let zero = Fraction::new(SecureField::zero(), SecureField::one());
+        let mut batches = vec![zero; batch_of(last) + 1];
+        for (idx, (_, fraction)) in self.fractions.iter().enumerate() {
+            let batch = &mut batches[batch_of(idx)];
+            *batch = Fraction::new(
+                batch.numerator * fraction.denominator + fraction.numerator * batch.denominator,
+                batch.denominator * fraction.denominator,
+            );
+        }
+        let num_batches = batches.len();
+        let mut prev_col_cumsum = SecureField::zero();
+        for (idx, batch) in batches.into_iter().enumerate() {
+            let diff = if idx + 1 < num_batches {
+                let [cur_cumsum] = self.next_secure_interaction_mask([0]);
+                let diff = cur_cumsum - prev_col_cumsum;
+                prev_col_cumsum = cur_cumsum;
+                diff
+            } else {
+                let [cur_cumsum, prev_row_cumsum] = self.next_secure_interaction_mask([0, -1]);
+                cur_cumsum - prev_row_cumsum - prev_col_cumsum + self.cumsum_shift
+            };
+            let value = diff * batch.denominator - batch.numerator;
+            self.check(ConstraintId::Logup(idx), value);
+        }
+    }
+}
+
+impl EvalAtRow for DebugEvaluator<'_> {
+    type F = BaseField;
+    type EF = SecureField;
+
+    fn next_interaction_mask<const N: usize>(
+        &mut self,
+        interaction: usize,
+        offsets: [isize; N],
+    ) -> [Self::F; N] {
+        assert!(
+            interaction == PREPROCESSED_TRACE_IDX
+                || interaction == ORIGINAL_TRACE_IDX
+                || interaction == INTERACTION_TRACE_IDX,
+            "unknown interaction {interaction}"
+        );
+        let col_index = self.col_index[interaction];
+        let col = &self.trace[interaction][col_index];
+        self.col_index[interaction] += 1;
+
+        let mut values = offsets.map(|offset| {
+            col[(self.row as isize + offset).rem_euclid(col.len() as isize) as usize]
+        });
+        if let Some(probe) = &mut self.probe {
+            for (value, offset) in values.iter_mut().zip(offsets) {
+                if let Some((idx, difference)) = probe.perturbation {
+                    if idx == probe.reads.len() {
+                        *value += difference;
+                    }
+                }
+                probe.reads.push((interaction, col_index, offset));
+            }
+        }
+        values
+    }
+
+    fn add_constraint<G>(&mut self, constraint: G)
+    where
+        Self::EF: Mul<G, Output = Self::EF> + From<G>,
+    {
+        let id = ConstraintId::Chip(self.chip, self.constraint_index);
+        self.check(id, SecureField::from(constraint));
+        self.constraint_index += 1;
+    }
+
+    fn combine_ef(values: [Self::F; SECURE_EXTENSION_DEGREE]) -> Self::EF {
+        SecureField::from_m31_array(values)
+    }
+
+    fn write_logup_frac(&mut self, fraction: Fraction<Self::EF, Self::EF>) {
+        self.fractions.push((self.chip, fraction));
+    }
+
+    // Entries are also matched across all components when the report is finished.
+    fn finalize_logup(&mut self) {
+        self.finalize_batches(|idx| idx);
+    }
+
+    fn finalize_logup_in_pairs(&mut self) {
+        self.finalize_batches(|idx| idx / 2);
+    }
+}
+
+/// Returns values of a bit-reversed column in coset order, the order rows are filled in.
+pub(crate) fn coset_order(col: &BaseColumn) -> Vec<BaseField> {
+    let values = col.as_slice();
+    let log_size = values.len().ilog2();
+    (0..values.len())
+        .map(|row| {
+            values[bit_reverse_index(coset_index_to_circle_domain_index(row, log_size), log_size)]
+        })
+        .collect()
+}
+
+struct LookupEntry {
+    component: usize,
+    chip: &'static str,
+    row: usize,
+    fraction: Fraction<SecureField, SecureField>,
+}
+
+/// Violations and logup entries collected from all components.
+#[derive(Default)]
+pub(crate) struct DebugReport {
+    components: Vec<String>,
+    /// Steps executed at rows of the base component.
+    steps: Vec<Option<ProgramStep>>,
+    violations: Vec<ConstraintViolation>,
+    entries: Vec<LookupEntry>,
+}
+
+impl DebugReport {
+    /// Checks constraints of the base component, `steps` are the ones filled into the main trace.
+    pub(crate) fn check_main<C: MachineChip>(
+        &mut self,
+        preprocessed: &[Vec<BaseField>],
+        original: &[Vec<BaseField>],
+        interaction_trace: &InteractionTrace,
+        steps: Vec<Option<ProgramStep>>,
+        lookup_elements: &AllLookupElements,
+        config: &ExtensionsConfig,
+    ) {
+        self.steps = steps;
+        let component = self.add_component(MAIN_COMPONENT);
+        let trace = [preprocessed, original, &interaction_trace.columns[..]];
+        let num_rows = original.first().map_or(0, Vec::len);
+        self.check_rows(
+            component,
+            trace,
+            num_rows,
+            interaction_trace.claimed_sum,
+            main_column_name,
+            |mut eval| {
+                let trace_eval = TraceEval::new(&mut eval);
+                C::debug_constraints(&mut eval, &trace_eval, lookup_elements, config);
+                if !lookup_elements.is_empty() {
+                    eval.finalize_logup();
+                }
+                eval
+            },
+        );
+    }
+
+    /// Checks constraints of an extension component.
+    pub(crate) fn check_component(
+        &mut self,
+        name: &str,
+        eval: &impl FrameworkEval,
+        component_trace: &ComponentTrace,
+        interaction_trace: &InteractionTrace,
+    ) {
+        let component = self.add_component(name);
+        let preprocessed: Vec<Vec<BaseField>> = component_trace
+            .preprocessed_trace
+            .iter()
+            .map(coset_order)
+            .collect();
+        let original: Vec<Vec<BaseField>> = component_trace
+            .original_trace
+            .iter()
+            .map(coset_order)
+            .collect();
+        let trace = [
+            &preprocessed[..],
+            &original[..],
+            &interaction_trace.columns[..],
+        ];
+        self.check_rows(
+            component,
+            trace,
+            1 << eval.log_size(),
+            interaction_trace.claimed_sum,
+            column_index_name,
+            |row_eval| eval.evaluate(row_eval),
+        );
+    }
+
+    /// Adds logup entries with multiplicity one provided by the verifier, the row is the index of the entry.
+    pub(crate) fn add_verifier_entries(&mut self, name: &str, denominators: Vec<SecureField>) {
+        let component = self.add_component(name);
+        self.entries.extend(
+            denominators
+                .into_iter()
+                .enumerate()
+                .map(|(row, denominator)| LookupEntry {
+                    component,
+                    chip: "",
+                    row,
+                    fraction: Fraction::new(SecureField::one(), denominator),
+                }),
+        );
+    }
+
+    /// Matches logup entries and returns all violations, ordered by component and row.
+    pub(crate) fn finish(mut self) -> Vec<ConstraintViolation> {
+        let mut balance: BTreeMap<[u32; 4], SecureField> = BTreeMap::new();
+        let key = |entry: &LookupEntry| entry.fraction.denominator.to_m31_array().map(|x| x.0);
+        for entry in &self.entries {
+            *balance.entry(key(entry)).or_insert_with(SecureField::zero) +=
+                entry.fraction.numerator;
+        }
+        let entries = std::mem::take(&mut self.entries);
+        for entry in entries {
+            if !entry.fraction.numerator.is_zero() && !balance[&key(&entry)].is_zero() {
+                let violation = self.violation(
+                    entry.component,
+                    entry.chip,
+                    entry.row,
+                    ViolationKind::UnbalancedLookup(entry.fraction.numerator),
+                    Vec::new(),
+                );
+                self.violations.push(violation);
+            }
+        }
+
+        let order: BTreeMap<&str, usize> = self
+            .components
+            .iter()
+            .enumerate()
+            .map(|(idx, name)| (name.as_str(), idx))
+            .collect();
+        let mut violations = std::mem::take(&mut self.violations);
+        violations.sort_by_key(|violation| (order[violation.component.as_str()], violation.row));
+        violations
+    }
+
+    fn add_component(&mut self, name: &str) -> usize {
+        self.components.push(name.to_owned());
+        self.components.len() - 1
+    }
+
+    /// Evaluates every row of a component, finding out columns read by each failing constraint at its first row.
+    fn check_rows<'a>(
+        &mut self,
+        component: usize,
+        trace: [&'a [Vec<BaseField>]; 3],
+        num_rows: usize,
+        claimed_sum: SecureField,
+        column_name: fn(usize, usize) -> String,
+        evaluate: impl Fn(DebugEvaluator<'a>) -> DebugEvaluator<'a>,
+    ) {
+        let cumsum_shift = claimed_sum * BaseField::from(num_rows as u32).inverse();
+        let mut columns: BTreeMap<ConstraintId, Vec<String>> = BTreeMap::new();
+        for row in 0..num_rows {
+            let eval = evaluate(DebugEvaluator::new(trace, cumsum_shift, row, None));
+            for constraint in eval.violations {
+                let columns = columns.entry(constraint).or_insert_with(|| {
+                    let probe = |perturbation| {
+                        let probe = Probe {
+                            constraint,
+                            perturbation,
+                            reads: Vec::new(),
+                            value: None,
+                        };
+                        let eval =
+                            evaluate(DebugEvaluator::new(trace, cumsum_shift, row, Some(probe)));
+                        eval.probe.expect("evaluation keeps the probe")
+                    };
+                    let Probe { reads, value, .. } = probe(None);
+                    let mut names: Vec<String> = Vec::new();
+                    for (idx, &(trace_idx, col, offset)) in reads.iter().enumerate() {
+                        let is_read = PERTURBATIONS.iter().any(|&difference| {
+                            probe(Some((idx, BaseField::from(difference)))).value != value
+                        });
+                        let mut name = column_name(trace_idx, col);
+                        if offset != 0 {
+                            name = format!("{name}@{offset:+}");
+                        }
+                        if is_read && !names.contains(&name) {
+                            names.push(name);
+                        }
+                    }
+                    names
+                });
+                let (chip, kind) = match constraint {
+                    ConstraintId::Chip(chip, idx) => (chip, ViolationKind::Constraint(idx)),
+                    ConstraintId::Logup(idx) => ("", ViolationKind::Logup(idx)),
+                };
+                let violation = self.violation(component, chip, row, kind, columns.clone());
+                self.violations.push(violation);
+            }
+            self.entries.extend(
+                eval.fractions
+                    .into_iter()
+                    .map(|(chip, fraction)| LookupEntry {
+                        component,
+                        chip,
+                        row,
+                        fraction,
+                    }),
+            );
+        }
+    }
+
+    fn violation(
+        &self,
+        component: usize,
+        chip: &'static str,
+        row: usize,
+        kind: ViolationKind,
+        columns: Vec<String>,
+    ) -> ConstraintViolation {
+        let name = &self.components[component];
+        let step = if component == 0 {
+            self.steps.get(row).and_then(Option::as_ref)
+        } else {
+            None
+        };
+        ConstraintViolation {
+            component: name.clone(),
+            chip: if chip.is_empty() {
+                name.clone()
+            } else {
+                chip.to_owned()
+            },
+            row,
+            pc: step.map(|step| step.step.pc),
+            instruction: step.map(|step| step.step.instruction.to_string()),
+            kind,
+            columns,
+        }
+    }
+}
+
+/// Returns the name of a column of the base component, given the trace and the index it is read at.
+fn main_column_name(trace: usize, col: usize) -> String {
+    fn variant_name<T: fmt::Debug + Copy>(
+        variants: &[T],
+        size: fn(T) -> usize,
+        mut col: usize,
+    ) -> Option<String> {
+        for &variant in variants {
+            let size = size(variant);
+            if col < size {
+                return Some(if size == 1 {
+                    format!("{variant:?}")
+                } else {
+                    format!("{variant:?}[{col}]")
+                });
+            }
+            col -= size;
+        }
+        None
+    }
+
+    let name = match trace {
+        PREPROCESSED_TRACE_IDX => variant_name(
+            PreprocessedColumn::ALL_VARIANTS,
+            PreprocessedColumn::size,
+            col,
+        )
+        .or_else(|| {
+            let col = col.checked_sub(PreprocessedColumn::COLUMNS_NUM)?;
+            variant_name(ProgramColumn::ALL_VARIANTS, ProgramColumn::size, col)
+        }),
+        ORIGINAL_TRACE_IDX => variant_name(Column::ALL_VARIANTS, Column::size, col),
+        _ => None,
+    };
+    name.unwrap_or_else(|| column_index_name(trace, col))
+}
+
+/// Returns the name of a column of an extension, given the trace and the index it is read at.
+fn column_index_name(trace: usize, col: usize) -> String {
+    match trace {
+        PREPROCESSED_TRACE_IDX => format!("Preprocessed[{col}]"),
+        ORIGINAL_TRACE_IDX => format!("Original[{col}]"),
+        _ => format!("Interaction[{col}]"),
+    }
+}
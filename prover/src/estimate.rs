@@ -0,0 +1,233 @@
+//! Dry-run estimation of the cost of proving an execution.
+//!
+//! [`Machine::estimate`](crate::machine::Machine::estimate) walks the execution trace once and only records what
+//! determines the sizes of components: the number of steps, the calls to built-in precompiles, the custom
+//! instructions proven by external extensions and the set of addresses under RAM memory checking.
+//! No column is generated and nothing is committed to, so that oversized executions can be rejected up front.
+//!
+//! Components of precompiles size themselves by the number of calls (or of bytes for `memcpy` and `memset`), so
+//! calls are recorded with placeholder inputs and the resulting sizes are the ones the prover picks for the same
+//! trace. The proving time and memory are derived from them with a linear [`CostModel`], whose coefficients depend
+//! on the hardware.
+
+use std::{collections::BTreeMap, fmt, time::Duration};
+
+use nexus_common::constants::KECCAKF_OPCODE;
+use nexus_vm::riscv::BuiltinOpcode;
+
+use crate::{
+    chips::custom::ExternalChip,
+    extensions::Extension,
+    trace::{sidenote::SideNote, ProgramStep},
+};
+
+/// Coarse group of instructions, as reported by [`CostEstimate::rows`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum InstructionFamily {
+    /// Arithmetic, comparisons, bitwise operations, `lui` and `auipc`.
+    Alu,
+    /// Logical and arithmetic shifts.
+    Shift,
+    /// RV32M multiplications, divisions and remainders.
+    MulDiv,
+    Load,
+    Store,
+    Branch,
+    /// `jal` and `jalr`.
+    Jump,
+    /// `ecall`, `ebreak` and `fence`.
+    System,
+    /// Custom instructions, proven by precompile extensions.
+    Custom,
+    /// Rows past the last step, padding the main trace to a power of two.
+    Padding,
+}
+
+impl InstructionFamily {
+    fn of(step: &ProgramStep) -> Self {
+        let Some(opcode) = step.step.instruction.opcode.builtin() else {
+            return Self::Custom;
+        };
+        match opcode {
+            BuiltinOpcode::SLL
+            | BuiltinOpcode::SLLI
+            | BuiltinOpcode::SRL
+            | BuiltinOpcode::SRLI
+            | BuiltinOpcode::SRA
+            | BuiltinOpcode::SRAI => Self::Shift,
+            BuiltinOpcode::MUL
+            | BuiltinOpcode::MULH
+            | BuiltinOpcode::MULHSU
+            | BuiltinOpcode::MULHU
+            | BuiltinOpcode::DIV
+            | BuiltinOpcode::DIVU
+            | BuiltinOpcode::REM
+            | BuiltinOpcode::REMU => Self::MulDiv,
+            BuiltinOpcode::LB
+            | BuiltinOpcode::LH
+            | BuiltinOpcode::LW
+            | BuiltinOpcode::LBU
+            | BuiltinOpcode::LHU => Self::Load,
+            BuiltinOpcode::SB | BuiltinOpcode::SH | BuiltinOpcode::SW => Self::Store,
+            BuiltinOpcode::BEQ
+            | BuiltinOpcode::BNE
+            | BuiltinOpcode::BLT
+            | BuiltinOpcode::BGE
+            | BuiltinOpcode::BLTU
+            | BuiltinOpcode::BGEU => Self::Branch,
+            BuiltinOpcode::JAL | BuiltinOpcode::JALR => Self::Jump,
+            BuiltinOpcode::ECALL | BuiltinOpcode::EBREAK | BuiltinOpcode::FENCE => Self::System,
+            _ => Self::Alu,
+        }
+    }
+}
+
+impl fmt::Display for InstructionFamily {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Alu => "alu",
+            Self::Shift => "shift",
+            Self::MulDiv => "mul/div",
+            Self::Load => "load",
+            Self::Store => "store",
+            Self::Branch => "branch",
+            Self::Jump => "jump",
+            Self::System => "system",
+            Self::Custom => "custom",
+            Self::Padding => "padding",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Linear model of the prover cost in the number of committed cells.
+///
+/// A cell is a base field element of a committed column, evaluated over the blown-up domain. The defaults are
+/// order-of-magnitude figures for a multi-core machine, they should be calibrated by proving a representative
+/// program and dividing the measured time and peak memory by [`CostEstimate::extended_cells`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CostModel {
+    /// Proving time per cell, in nanoseconds.
+    pub nanos_per_cell: f64,
+    /// Peak memory per cell, in bytes.
+    pub bytes_per_cell: f64,
+}
+
+impl Default for CostModel {
+    fn default() -> Self {
+        Self {
+            nanos_per_cell: 10.0,
+            bytes_per_cell: 16.0,
+        }
+    }
+}
+
+/// Sizes of the components the prover would use for an execution, along with the estimated cost of proving it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CostEstimate {
+    /// Number of executed steps.
+    pub num_steps: usize,
+    /// Log size of the main trace.
+    pub log_size: u32,
+    /// Names and log sizes of extension components, in the order they are proven.
+    pub extension_log_sizes: Vec<(String, u32)>,
+    /// Rows of the main trace per instruction family.
+    pub rows: BTreeMap<InstructionFamily, usize>,
+    /// Number of addresses under RAM memory checking.
+    pub tracked_ram_size: usize,
+    /// Number of committed columns over all components and interactions.
+    pub num_columns: usize,
+    /// Number of committed cells over the trace domains.
+    pub cells: u64,
+    /// Number of committed cells over the blown-up evaluation domains.
+    pub extended_cells: u64,
+    /// Estimated proving time.
+    pub proving_time: Duration,
+    /// Estimated peak memory of the prover, in bytes.
+    pub memory_bytes: u64,
+}
+
+impl CostEstimate {
+    /// Recomputes the proving time and memory with another cost model.
+    pub fn with_cost_model(mut self, model: &CostModel) -> Self {
+        let extended_cells = self.extended_cells as f64;
+        self.proving_time = Duration::from_nanos((extended_cells * model.nanos_per_cell) as u64);
+        self.memory_bytes = (extended_cells * model.bytes_per_cell) as u64;
+        self
+    }
+}
+
+impl fmt::Display for CostEstimate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "steps: {}", self.num_steps)?;
+        writeln!(f, "main trace log size: {}", self.log_size)?;
+        for (name, log_size) in &self.extension_log_sizes {
+            writeln!(f, "{name} log size: {log_size}")?;
+        }
+        for (family, rows) in &self.rows {
+            writeln!(f, "{family} rows: {rows}")?;
+        }
+        writeln!(f, "tracked RAM size: {}", self.tracked_ram_size)?;
+        writeln!(
+            f,
+            "committed columns: {}, cells: {}, extended cells: {}",
+            self.num_columns, self.cells, self.extended_cells
+        )?;
+        writeln!(
+            f,
+            "estimated proving time: {:.1}s",
+            self.proving_time.as_secs_f64()
+        )?;
+        write!(
+            f,
+            "estimated memory: {:.1} MiB",
+            self.memory_bytes as f64 / (1 << 20) as f64
+        )
+    }
+}
+
+/// Records the sizes a step contributes to components in the side note, along with its instruction family.
+///
+/// Built-in custom instructions are told apart with [`Extension::of_opcode`], as in their chips, and recorded with
+/// placeholder inputs since only their number matters for the sizes of components.
+pub(crate) fn record_step(
+    step: ProgramStep,
+    side_note: &mut SideNote,
+    rows: &mut BTreeMap<InstructionFamily, usize>,
+) {
+    for record in &step.step.memory_records {
+        let address = record.get_address();
+        for byte_address in address..address + record.get_size() as u32 {
+            side_note
+                .rw_mem_check
+                .last_access
+                .entry(byte_address)
+                .or_default();
+        }
+    }
+
+    let family = InstructionFamily::of(&step);
+    *rows.entry(family).or_default() += 1;
+
+    let opcode = &step.step.instruction.opcode;
+    match family {
+        InstructionFamily::Custom => match Extension::of_opcode(opcode.raw, opcode.fn3.value()) {
+            Some(Extension::Keccak) => side_note.keccak.inputs.push([0; 25]),
+            Some(Extension::Sha256) => side_note.sha256.inputs.push(([0; 8], [0; 16])),
+            Some(Extension::BigInt) => side_note.bigint.inputs.push([[0; 8]; 4]),
+            Some(Extension::Poseidon2) => side_note.poseidon2.inputs.push([0; 16]),
+            Some(Extension::Memcpy) => {
+                // the number of bytes is encoded in the immediate
+                let len = step.step.instruction.op_c as usize;
+                side_note.memcpy.values.extend(std::iter::repeat_n(0, len));
+            }
+            // no chip handles the opcode of built-in instructions with an unassigned fn3
+            None if opcode.raw == KECCAKF_OPCODE => {}
+            None => {
+                let external_step = ExternalChip::external_step(&step, side_note);
+                side_note.external_steps.push(external_step);
+            }
+        },
+        _ => {}
+    }
+}
@@ -0,0 +1,190 @@
+use nexus_common::constants::WORD_SIZE_HALVED;
+use num_traits::{One, Zero};
+use stwo_prover::{
+    constraint_framework::{EvalAtRow, RelationEntry},
+    core::fields::m31::BaseField,
+};
+
+use crate::components::lookups::LoadStoreLookupElements;
+
+pub struct MulAddModMemoryCheckEval<'a, E> {
+    pub(crate) eval: E,
+    pub(crate) memory_lookup_elements: &'a LoadStoreLookupElements,
+}
+
+impl<E: EvalAtRow> MulAddModMemoryCheckEval<'_, E> {
+    const RESULT_BYTES: usize = super::MulAddModMemoryCheckEval::RESULT_BYTES;
+    const OPERAND_BYTES: usize = super::MulAddModMemoryCheckEval::OPERAND_BYTES;
+    const NUM_BYTES: usize = super::MulAddModMemoryCheckEval::NUM_BYTES;
+    const PRODUCT_BYTES: usize = super::MulAddModMemoryCheckEval::PRODUCT_BYTES;
+    const CARRY_BITS: usize = super::MulAddModMemoryCheckEval::CARRY_BITS;
+
+    pub fn eval(mut self) -> E {
+        let operands = self.next_columns(Self::OPERAND_BYTES);
+        let prev_result = self.next_columns(Self::RESULT_BYTES);
+        let r_bits = self.next_columns(Self::RESULT_BYTES * 8);
+        let q_bits = self.next_columns(Self::RESULT_BYTES * 2 * 8);
+        let d_bits = self.next_columns(Self::RESULT_BYTES * 8);
+        let bound_carries = self.next_columns(Self::RESULT_BYTES);
+        let product_carry_bits = self.next_columns((Self::PRODUCT_BYTES - 1) * Self::CARRY_BITS);
+        let addrs = self.next_columns(Self::NUM_BYTES * WORD_SIZE_HALVED);
+        let prev_ts = self.next_columns(Self::NUM_BYTES * WORD_SIZE_HALVED);
+        let next_ts = self.next_columns(Self::NUM_BYTES * WORD_SIZE_HALVED);
+        let addr_carries = self.next_columns(Self::NUM_BYTES);
+        let ts_carries = self.next_columns(Self::NUM_BYTES);
+
+        let is_padding = self.eval.next_trace_mask();
+        self.eval
+            .add_constraint(is_padding.clone() * (E::F::one() - is_padding.clone()));
+        let is_real = E::F::one() - is_padding;
+
+        for bit in r_bits
+            .iter()
+            .chain(&q_bits)
+            .chain(&d_bits)
+            .chain(&bound_carries)
+            .chain(&product_carry_bits)
+            .chain(&addr_carries)
+            .chain(&ts_carries)
+        {
+            self.eval
+                .add_constraint(bit.clone() * (E::F::one() - bit.clone()));
+        }
+
+        let [a, b, c, m]: [&[E::F]; 4] = std::array::from_fn(|i| {
+            &operands[i * Self::RESULT_BYTES..(i + 1) * Self::RESULT_BYTES]
+        });
+        let r: Vec<E::F> = r_bits.chunks_exact(8).map(Self::from_bits).collect();
+        let q: Vec<E::F> = q_bits.chunks_exact(8).map(Self::from_bits).collect();
+        let d: Vec<E::F> = d_bits.chunks_exact(8).map(Self::from_bits).collect();
+        let byte_modulus = E::F::from(BaseField::from(1 << 8));
+
+        // r + d + 1 = m + 2^256 * wrap, the last carry wraps around only if the modulus is zero
+        for k in 0..Self::RESULT_BYTES {
+            let carry_in = if k == 0 {
+                E::F::one()
+            } else {
+                bound_carries[k - 1].clone()
+            };
+            self.eval.add_constraint(
+                r[k].clone() + d[k].clone() + carry_in
+                    - m[k].clone()
+                    - bound_carries[k].clone() * byte_modulus.clone(),
+            );
+        }
+        let wrap = bound_carries[Self::RESULT_BYTES - 1].clone();
+        for m_k in m {
+            self.eval.add_constraint(wrap.clone() * m_k.clone());
+        }
+
+        // a * b + c = q * m + q * 2^256 * wrap + r, position by position
+        let offset = E::F::from(BaseField::from(1 << (Self::CARRY_BITS - 1)));
+        let product_carries: Vec<E::F> = product_carry_bits
+            .chunks_exact(Self::CARRY_BITS)
+            .map(|bits| Self::from_bits(bits) - offset.clone())
+            .collect();
+        for k in 0..Self::PRODUCT_BYTES {
+            let mut sum = if k < Self::RESULT_BYTES {
+                c[k].clone() - r[k].clone()
+            } else {
+                E::F::zero() - wrap.clone() * q[k - Self::RESULT_BYTES].clone()
+            };
+            for (i, a_i) in a.iter().enumerate().take(k + 1) {
+                if let Some(b_j) = b.get(k - i) {
+                    sum = sum + a_i.clone() * b_j.clone();
+                }
+            }
+            for (j, m_j) in m.iter().enumerate().take(k + 1) {
+                if let Some(q_i) = q.get(k - j) {
+                    sum = sum - q_i.clone() * m_j.clone();
+                }
+            }
+            if k > 0 {
+                sum = sum + product_carries[k - 1].clone();
+            }
+            if k < Self::PRODUCT_BYTES - 1 {
+                sum = sum - product_carries[k].clone() * byte_modulus.clone();
+            }
+            self.eval.add_constraint(sum);
+        }
+
+        // addresses and timestamps of consecutive bytes
+        for (start, len) in [
+            (0, Self::OPERAND_BYTES),
+            (Self::OPERAND_BYTES, Self::RESULT_BYTES),
+        ] {
+            for i in start..start + len - 1 {
+                let j = i * WORD_SIZE_HALVED;
+                let addr = &addrs[j..j + WORD_SIZE_HALVED];
+                let next_addr = &addrs[j + WORD_SIZE_HALVED..j + WORD_SIZE_HALVED * 2];
+                self.constrain_increment(&is_real, addr, next_addr, &addr_carries[i]);
+            }
+        }
+        for (i, (prev_ts, next_ts)) in prev_ts
+            .chunks_exact(WORD_SIZE_HALVED)
+            .zip(next_ts.chunks_exact(WORD_SIZE_HALVED))
+            .enumerate()
+        {
+            self.constrain_increment(&is_real, prev_ts, next_ts, &ts_carries[i]);
+        }
+
+        // operands are only read, the result is overwritten
+        let prev_vals = operands.iter().chain(&prev_result);
+        let next_vals = operands.iter().chain(&r);
+        for (i, (prev_val, next_val)) in prev_vals.zip(next_vals).enumerate() {
+            let j = i * WORD_SIZE_HALVED;
+            // (addr, val, ts)
+            let sub_access = [
+                &addrs[j..j + WORD_SIZE_HALVED],
+                std::slice::from_ref(prev_val),
+                &prev_ts[j..j + WORD_SIZE_HALVED],
+            ]
+            .concat();
+            let add_access = [
+                &addrs[j..j + WORD_SIZE_HALVED],
+                std::slice::from_ref(next_val),
+                &next_ts[j..j + WORD_SIZE_HALVED],
+            ]
+            .concat();
+
+            self.eval.add_to_relation(RelationEntry::new(
+                self.memory_lookup_elements,
+                (-is_real.clone()).into(),
+                &sub_access,
+            ));
+            self.eval.add_to_relation(RelationEntry::new(
+                self.memory_lookup_elements,
+                is_real.clone().into(),
+                &add_access,
+            ));
+        }
+
+        self.eval.finalize_logup_in_pairs();
+
+        self.eval
+    }
+
+    fn next_columns(&mut self, size: usize) -> Vec<E::F> {
+        std::iter::repeat_with(|| self.eval.next_trace_mask())
+            .take(size)
+            .collect()
+    }
+
+    fn from_bits(bits: &[E::F]) -> E::F {
+        bits.iter().enumerate().fold(E::F::zero(), |acc, (i, bit)| {
+            acc + bit.clone() * BaseField::from(1 << i)
+        })
+    }
+
+    /// Constrains a 32-bit value split into halves to be incremented by one.
+    fn constrain_increment(&mut self, is_real: &E::F, prev: &[E::F], next: &[E::F], carry: &E::F) {
+        self.eval.add_constraint(
+            is_real.clone()
+                * (next[0].clone() + carry.clone() * E::F::from((1 << 16).into())
+                    - prev[0].clone()
+                    - E::F::one()),
+        );
+        self.eval
+            .add_constraint(is_real.clone() * (next[1].clone() - prev[1].clone() - carry.clone()));
+    }
+}
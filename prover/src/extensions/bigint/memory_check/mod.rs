@@ -0,0 +1,127 @@
+use stwo_prover::{
+    constraint_framework::{EvalAtRow, FrameworkEval},
+    core::{
+        backend::simd::{m31::LOG_N_LANES, SimdBackend},
+        fields::{m31::BaseField, qm31::SecureField},
+        poly::{circle::CircleEvaluation, BitReversedOrder},
+        ColumnVec,
+    },
+};
+
+use crate::{
+    components::{lookups::LoadStoreLookupElements, AllLookupElements},
+    extensions::{BuiltInExtension, ComponentTrace, FrameworkEvalExt},
+    trace::{program_trace::ProgramTraceRef, sidenote::SideNote},
+};
+
+mod constraints;
+mod trace;
+
+/// Checks memory accessed by `mul_add_mod` and the result of the call, one call per row.
+///
+/// Every row reads the operands `a`, `b`, `c` and `m` from memory and writes `r = (a * b + c) mod m` back, given
+/// a quotient `q` such that `a * b + c = q * m + r`. Byte-wise products are summed per position with signed carries,
+/// and `r < m` is enforced by decomposing `m - r - 1` into bits. A zero modulus stands for 2^256.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MulAddModMemoryCheck {
+    pub(crate) _private: (),
+}
+
+pub(crate) struct MulAddModMemoryCheckEval {
+    log_size: u32,
+    memory_lookup_elements: LoadStoreLookupElements,
+}
+
+impl MulAddModMemoryCheckEval {
+    const RESULT_BYTES: usize = 8 * 4;
+    const OPERAND_BYTES: usize = 4 * Self::RESULT_BYTES;
+    const NUM_BYTES: usize = Self::OPERAND_BYTES + Self::RESULT_BYTES;
+    /// Byte positions of `q * m`, with a 512-bit quotient and a 256-bit modulus.
+    const PRODUCT_BYTES: usize = 3 * Self::RESULT_BYTES;
+    /// Carries between byte positions are signed and stored with an offset of 2^15.
+    const CARRY_BITS: usize = 16;
+}
+
+impl FrameworkEval for MulAddModMemoryCheckEval {
+    fn log_size(&self) -> u32 {
+        self.log_size
+    }
+
+    fn max_constraint_log_degree_bound(&self) -> u32 {
+        self.log_size + 1
+    }
+
+    fn evaluate<E: EvalAtRow>(&self, eval: E) -> E {
+        constraints::MulAddModMemoryCheckEval {
+            eval,
+            memory_lookup_elements: &self.memory_lookup_elements,
+        }
+        .eval()
+    }
+}
+
+impl FrameworkEvalExt for MulAddModMemoryCheckEval {
+    fn new(log_size: u32, lookup_elements: &AllLookupElements) -> Self {
+        let memory_lookup_elements: &LoadStoreLookupElements = lookup_elements.as_ref();
+        Self {
+            log_size,
+            memory_lookup_elements: memory_lookup_elements.clone(),
+        }
+    }
+
+    fn dummy(log_size: u32) -> Self {
+        Self {
+            log_size,
+            memory_lookup_elements: LoadStoreLookupElements::dummy(),
+        }
+    }
+}
+
+impl BuiltInExtension for MulAddModMemoryCheck {
+    type Eval = MulAddModMemoryCheckEval;
+
+    fn generate_preprocessed_trace(
+        &self,
+        _log_size: u32,
+        _program_trace_ref: ProgramTraceRef,
+    ) -> ColumnVec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>> {
+        vec![]
+    }
+
+    fn generate_component_trace(
+        &self,
+        log_size: u32,
+        _program_trace_ref: ProgramTraceRef,
+        side_note: &mut SideNote,
+    ) -> ComponentTrace {
+        trace::generate_mul_add_mod_trace(log_size, side_note)
+    }
+
+    fn generate_interaction_trace(
+        &self,
+        component_trace: ComponentTrace,
+        side_note: &SideNote,
+        lookup_elements: &AllLookupElements,
+    ) -> (
+        ColumnVec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>>,
+        SecureField,
+    ) {
+        let memory_lookup_elements: &LoadStoreLookupElements = lookup_elements.as_ref();
+        trace::MemoryCheckLogUpGenerator {
+            component_trace: &component_trace,
+            inputs: &side_note.bigint.inputs,
+        }
+        .interaction_trace(memory_lookup_elements)
+    }
+
+    fn compute_log_size(&self, side_note: &SideNote) -> u32 {
+        let num_inputs = side_note.bigint.inputs.len();
+        let log_size = num_inputs.next_power_of_two().ilog2();
+
+        log_size.max(LOG_N_LANES)
+    }
+
+    fn preprocessed_trace_sizes(_log_size: u32) -> Vec<u32> {
+        vec![]
+    }
+}
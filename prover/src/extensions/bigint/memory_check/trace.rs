@@ -0,0 +1,264 @@
+use nexus_common::constants::WORD_SIZE_HALVED;
+use nexus_vm::cpu::instructions::custom::bigint::{mul_add_div_rem, mul_add_mod};
+use num_traits::{One, Zero};
+use stwo_prover::{
+    constraint_framework::{logup::LogupTraceGenerator, Relation},
+    core::{
+        backend::simd::{
+            column::BaseColumn,
+            m31::{PackedM31, LOG_N_LANES},
+            qm31::PackedSecureField,
+            SimdBackend,
+        },
+        fields::{m31::BaseField, qm31::SecureField},
+        poly::{circle::CircleEvaluation, BitReversedOrder},
+        ColumnVec,
+    },
+};
+
+use super::MulAddModMemoryCheckEval;
+use crate::{
+    components::lookups::LoadStoreLookupElements,
+    extensions::{keccak::round::trace::get_is_padding_base_column, ComponentTrace},
+    trace::sidenote::SideNote,
+};
+
+const MASK: u32 = (1 << 16) - 1;
+
+fn to_bytes(words: &[u32]) -> Vec<u32> {
+    words
+        .iter()
+        .flat_map(|word| word.to_le_bytes())
+        .map(u32::from)
+        .collect()
+}
+
+fn to_bits(words: &[u32]) -> Vec<u32> {
+    words
+        .iter()
+        .flat_map(|word| (0..32).map(move |i| (word >> i) & 1))
+        .collect()
+}
+
+/// Returns values of the columns checking the arithmetic of a single call, without memory addresses and timestamps.
+fn arithmetic_values(operands: &[[u32; 8]; 4], prev_result: &[u8; 32]) -> Vec<u32> {
+    let result_bytes = MulAddModMemoryCheckEval::RESULT_BYTES;
+    let product_bytes = MulAddModMemoryCheckEval::PRODUCT_BYTES;
+    let carry_bits = MulAddModMemoryCheckEval::CARRY_BITS;
+
+    let [a, b, c, m] = operands;
+    let (q, r) = mul_add_div_rem(a, b, c, m);
+    // d = m - r - 1 mod 2^256
+    let mut d = [0u32; 8];
+    let mut borrow = 1;
+    for (i, word) in d.iter_mut().enumerate() {
+        let v = i64::from(m[i]) - i64::from(r[i]) - borrow;
+        *word = v.rem_euclid(1 << 32) as u32;
+        borrow = i64::from(v < 0);
+    }
+
+    let mut values = Vec::new();
+    values.extend(operands.iter().flat_map(|operand| to_bytes(operand)));
+    values.extend(prev_result.iter().map(|&byte| u32::from(byte)));
+    values.extend(to_bits(&r));
+    values.extend(to_bits(&q));
+    values.extend(to_bits(&d));
+
+    let [a, b, c, m] = operands.each_ref().map(|operand| to_bytes(operand));
+    let (q, r, d) = (to_bytes(&q), to_bytes(&r), to_bytes(&d));
+
+    let mut carry = 1;
+    for k in 0..result_bytes {
+        let sum = r[k] + d[k] + carry;
+        assert_eq!(sum & 0xff, m[k]);
+        carry = sum >> 8;
+        values.push(carry);
+    }
+    let wrap = i64::from(carry);
+
+    let mut carry = 0i64;
+    for k in 0..product_bytes {
+        let mut sum = carry;
+        if k < result_bytes {
+            sum += i64::from(c[k]) - i64::from(r[k]);
+        } else {
+            sum -= wrap * i64::from(q[k - result_bytes]);
+        }
+        for (i, a_i) in a.iter().enumerate().take(k + 1) {
+            if let Some(b_j) = b.get(k - i) {
+                sum += i64::from(a_i * b_j);
+            }
+        }
+        for (j, m_j) in m.iter().enumerate().take(k + 1) {
+            if let Some(q_i) = q.get(k - j) {
+                sum -= i64::from(q_i * m_j);
+            }
+        }
+        assert_eq!(sum.rem_euclid(1 << 8), 0);
+        carry = sum >> 8;
+        if k < product_bytes - 1 {
+            let biased = (carry + (1 << (carry_bits - 1))) as u32;
+            values.extend((0..carry_bits).map(|i| (biased >> i) & 1));
+        }
+    }
+    assert_eq!(carry, 0);
+
+    values
+}
+
+pub fn generate_mul_add_mod_trace(log_size: u32, side_note: &SideNote) -> ComponentTrace {
+    let num_bytes = MulAddModMemoryCheckEval::NUM_BYTES;
+    let operand_bytes = MulAddModMemoryCheckEval::OPERAND_BYTES;
+    let result_bytes = MulAddModMemoryCheckEval::RESULT_BYTES;
+    let product_bytes = MulAddModMemoryCheckEval::PRODUCT_BYTES;
+    let carry_bits = MulAddModMemoryCheckEval::CARRY_BITS;
+    // [operands, prev_result, r_bits, q_bits, d_bits, bound_carries, product_carries,
+    //  addresses, prev_ts, next_ts, addr_carries, ts_carries]
+    let num_cols = num_bytes
+        + result_bytes * 8 * 4
+        + result_bytes
+        + (product_bytes - 1) * carry_bits
+        + num_bytes * WORD_SIZE_HALVED * 3
+        + num_bytes * 2;
+    let mut original_trace = vec![vec![BaseField::zero(); 1 << log_size]; num_cols];
+
+    let bigint_side_note = &side_note.bigint;
+    for (row, input) in bigint_side_note.inputs.iter().enumerate() {
+        let prev_result = &bigint_side_note.prev_results[row];
+        let (result_addr, operands_addr) = bigint_side_note.addresses[row];
+        let timestamps = &bigint_side_note.timestamps[row];
+
+        let mut values = arithmetic_values(input, prev_result);
+
+        let addrs: Vec<u32> = (0..operand_bytes)
+            .map(|i| operands_addr + i as u32)
+            .chain((0..result_bytes).map(|i| result_addr + i as u32))
+            .collect();
+        assert_eq!(timestamps.len(), addrs.len());
+        values.extend(addrs.iter().flat_map(|addr| [addr & MASK, addr >> 16]));
+        values.extend(timestamps.iter().flat_map(|ts| [ts & MASK, ts >> 16]));
+        values.extend(timestamps.iter().flat_map(|ts| {
+            let ts = ts + 1;
+            [ts & MASK, ts >> 16]
+        }));
+        values.extend(addrs.iter().map(|addr| u32::from(addr & MASK == MASK)));
+        values.extend(timestamps.iter().map(|ts| u32::from(ts & MASK == MASK)));
+
+        assert_eq!(values.len(), num_cols);
+        for (col, value) in original_trace.iter_mut().zip(values) {
+            col[row] = BaseField::from(value);
+        }
+    }
+    // Arithmetic constraints aren't disabled on padding, these rows prove 0 * 0 + 0 mod 2^256.
+    let real_rows = bigint_side_note.inputs.len();
+    let padding_values = arithmetic_values(&Default::default(), &Default::default());
+    for (col, value) in original_trace.iter_mut().zip(padding_values) {
+        col[real_rows..].fill(BaseField::from(value));
+    }
+
+    let is_padding = get_is_padding_base_column(log_size, real_rows);
+    let mut original_trace: Vec<BaseColumn> = original_trace
+        .into_iter()
+        .map(BaseColumn::from_iter)
+        .collect();
+    original_trace.push(is_padding);
+
+    ComponentTrace {
+        log_size,
+        preprocessed_trace: vec![],
+        original_trace,
+    }
+}
+
+pub(super) struct MemoryCheckLogUpGenerator<'a> {
+    pub(super) component_trace: &'a ComponentTrace,
+    pub(super) inputs: &'a [[[u32; 8]; 4]],
+}
+
+impl MemoryCheckLogUpGenerator<'_> {
+    pub fn interaction_trace(
+        &self,
+        memory_lookup_elements: &LoadStoreLookupElements,
+    ) -> (
+        ColumnVec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>>,
+        SecureField,
+    ) {
+        let num_bytes = MulAddModMemoryCheckEval::NUM_BYTES;
+        let operand_bytes = MulAddModMemoryCheckEval::OPERAND_BYTES;
+        let result_bytes = MulAddModMemoryCheckEval::RESULT_BYTES;
+        let product_bytes = MulAddModMemoryCheckEval::PRODUCT_BYTES;
+        let carry_bits = MulAddModMemoryCheckEval::CARRY_BITS;
+        let log_size = self.component_trace.log_size;
+        let mut logup_gen = LogupTraceGenerator::new(log_size);
+
+        let original_trace = self.component_trace.original_trace.as_slice();
+
+        let (operands_and_prev_result, rem) = original_trace.split_at(num_bytes);
+        // skip bits of the remainder, the quotient and the bound, and carries
+        let (_, rem) =
+            rem.split_at(result_bytes * 8 * 4 + result_bytes + (product_bytes - 1) * carry_bits);
+        let (addrs, rem) = rem.split_at(num_bytes * WORD_SIZE_HALVED);
+        let (prev_ts, rem) = rem.split_at(num_bytes * WORD_SIZE_HALVED);
+        let (next_ts, rem) = rem.split_at(num_bytes * WORD_SIZE_HALVED);
+
+        // skip carries
+        let (_, rem) = rem.split_at(num_bytes * 2);
+
+        assert_eq!(rem.len(), 1);
+        let is_padding = &rem[0];
+
+        // Result bytes are recomputed rather than collected from bits, padding rows compute zero.
+        let mut result = vec![vec![]; result_bytes];
+        for row in 0..1 << log_size {
+            let [a, b, c, m] = self.inputs.get(row).copied().unwrap_or_default();
+            let r = mul_add_mod(&a, &b, &c, &m);
+            for (col, byte) in result.iter_mut().zip(to_bytes(&r)) {
+                col.push(BaseField::from(byte));
+            }
+        }
+        let result: Vec<BaseColumn> = result.into_iter().map(BaseColumn::from_iter).collect();
+
+        // operands are only read, the result is overwritten
+        let prev_vals = operands_and_prev_result;
+        let next_vals: Vec<&BaseColumn> = operands_and_prev_result[..operand_bytes]
+            .iter()
+            .chain(&result)
+            .collect();
+        let one: PackedSecureField = SecureField::one().into();
+        for i in 0..num_bytes {
+            let j = i * WORD_SIZE_HALVED;
+            let mut logup_col_gen = logup_gen.new_col();
+            for vec_idx in 0..(1 << (log_size - LOG_N_LANES)) {
+                let addr = &addrs[j..j + WORD_SIZE_HALVED];
+
+                let p0: PackedSecureField = {
+                    let prev_ts = &prev_ts[j..j + WORD_SIZE_HALVED];
+                    let tuple: Vec<PackedM31> = addr
+                        .iter()
+                        .chain(std::iter::once(&prev_vals[i]))
+                        .chain(prev_ts)
+                        .map(|col| col.data[vec_idx])
+                        .collect();
+                    memory_lookup_elements.combine(&tuple)
+                };
+
+                let p1: PackedSecureField = {
+                    let next_ts = &next_ts[j..j + WORD_SIZE_HALVED];
+                    let tuple: Vec<PackedM31> = addr
+                        .iter()
+                        .chain(std::iter::once(next_vals[i]))
+                        .chain(next_ts)
+                        .map(|col| col.data[vec_idx])
+                        .collect();
+                    memory_lookup_elements.combine(&tuple)
+                };
+                let is_real = one - PackedSecureField::from(is_padding.data[vec_idx]);
+                let numerator = is_real * (p0 - p1);
+                logup_col_gen.write_frac(vec_idx, numerator, p0 * p1);
+            }
+
+            logup_col_gen.finalize_col();
+        }
+        logup_gen.finalize_last()
+    }
+}
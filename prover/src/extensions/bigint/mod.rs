@@ -0,0 +1,89 @@
+//! Components proving the `mul_add_mod` custom instruction.
+//!
+//! [`MulAddModMemoryCheck`] checks memory accesses of every call and the arithmetic relation between the operands
+//! and the result in the same row.
+
+pub(crate) mod memory_check;
+
+pub(crate) use memory_check::MulAddModMemoryCheck;
+
+use super::ExtensionComponent;
+
+pub const fn bigint_extensions() -> &'static [ExtensionComponent] {
+    // A constant rather than a promoted temporary: the enum has variants with destructors.
+    const EXTENSIONS: &[ExtensionComponent] = &[ExtensionComponent::MulAddModMemoryCheck(
+        MulAddModMemoryCheck { _private: () },
+    )];
+    EXTENSIONS
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        config::DEFAULT_MIN_SECURITY_BITS,
+        machine::{BaseComponent, Machine},
+    };
+    use nexus_vm::{
+        riscv::{BasicBlock, BuiltinOpcode, Instruction, Opcode},
+        trace::k_trace_direct,
+    };
+
+    use super::bigint_extensions;
+
+    #[test]
+    fn prove_execution_with_mul_add_mod() {
+        let mut instructions = vec![
+            // Create usable addresses for the result and the operands, x2 = 0x81008, x3 = x2 + 32
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::ADDI), 1, 0, 1),
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::SLLI), 1, 1, 19),
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::ADD), 2, 1, 2),
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::ADDI), 3, 2, 32),
+            // m = 0, a = b = c = 2^32 - 1
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::ADDI), 4, 0, 1),
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::SUB), 4, 0, 4),
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::SW), 3, 4, 0),
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::SW), 3, 4, 32),
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::SW), 3, 4, 64),
+        ];
+        let mul_add_mod_inst = Instruction::new_ir(
+            Opcode::new(0b1011010, Some(0b010), None, "mul_add_mod"),
+            2,
+            3,
+            0,
+        );
+        instructions.extend(vec![mul_add_mod_inst; 10]);
+        // result overwrites the first operand, m = 7
+        instructions.extend([
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::ADDI), 4, 0, 7),
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::SW), 3, 4, 96),
+            Instruction::new_ir(
+                Opcode::new(0b1011010, Some(0b010), None, "mul_add_mod"),
+                3,
+                3,
+                0,
+            ),
+        ]);
+
+        let basic_block = vec![BasicBlock::new(instructions)];
+        let (view, program_trace) =
+            k_trace_direct(&basic_block, 1).expect("error generating trace");
+
+        let proof = Machine::<BaseComponent>::prove_with_extensions(
+            bigint_extensions(),
+            &program_trace,
+            &view,
+        )
+        .unwrap();
+        Machine::<BaseComponent>::verify_with_extensions(
+            bigint_extensions(),
+            DEFAULT_MIN_SECURITY_BITS,
+            proof,
+            view.get_program_memory(),
+            &[],
+            view.get_initial_memory(),
+            view.get_exit_code(),
+            view.get_public_output(),
+        )
+        .unwrap();
+    }
+}
@@ -23,15 +23,41 @@ impl From<&[ExtensionComponent]> for ExtensionsConfig {
 
 impl ExtensionsConfig {
     pub fn is_keccak_enabled(&self) -> bool {
-        let keccak_extensions = ExtensionComponent::keccak_extensions();
-        let (memory_checker, rem) = keccak_extensions
+        self.is_group_enabled(ExtensionComponent::keccak_extensions(), "keccak")
+    }
+
+    pub fn is_sha256_enabled(&self) -> bool {
+        self.is_group_enabled(ExtensionComponent::sha256_extensions(), "sha256")
+    }
+
+    pub fn is_bigint_enabled(&self) -> bool {
+        self.is_group_enabled(ExtensionComponent::bigint_extensions(), "bigint")
+    }
+
+    pub fn is_poseidon2_enabled(&self) -> bool {
+        self.is_group_enabled(ExtensionComponent::poseidon2_extensions(), "poseidon2")
+    }
+
+    pub fn is_memcpy_enabled(&self) -> bool {
+        self.is_group_enabled(ExtensionComponent::memcpy_extensions(), "memcpy")
+    }
+
+    pub fn is_external_enabled(&self) -> bool {
+        self.0
+            .iter()
+            .any(|ext| matches!(ext, ExtensionComponent::External(_)))
+    }
+
+    /// Returns whether all components of a group are enabled, panics if only some of them are.
+    fn is_group_enabled(&self, extensions: &[ExtensionComponent], name: &str) -> bool {
+        let (first, rem) = extensions
             .split_first()
-            .expect("keccak extensions are not empty");
+            .expect("group extensions must not be empty");
 
-        let result = self.0.contains(memory_checker);
+        let result = self.0.contains(first);
         assert!(
             rem.iter().all(|ext| self.0.contains(ext) == result),
-            "keccak components cannot be enabled partially"
+            "{name} components cannot be enabled partially"
         );
 
         result
@@ -46,6 +72,23 @@ mod tests {
     fn test_config() {
         let config = ExtensionsConfig::from(ExtensionComponent::keccak_extensions());
         assert!(config.is_keccak_enabled());
+        assert!(!config.is_sha256_enabled());
+
+        let config = ExtensionsConfig::from(ExtensionComponent::sha256_extensions());
+        assert!(config.is_sha256_enabled());
+        assert!(!config.is_keccak_enabled());
+
+        let config = ExtensionsConfig::from(ExtensionComponent::bigint_extensions());
+        assert!(config.is_bigint_enabled());
+        assert!(!config.is_sha256_enabled());
+
+        let config = ExtensionsConfig::from(ExtensionComponent::poseidon2_extensions());
+        assert!(config.is_poseidon2_enabled());
+        assert!(!config.is_bigint_enabled());
+
+        let config = ExtensionsConfig::from(ExtensionComponent::memcpy_extensions());
+        assert!(config.is_memcpy_enabled());
+        assert!(!config.is_poseidon2_enabled());
     }
 
     #[test]
@@ -54,4 +97,11 @@ mod tests {
         let config = ExtensionsConfig::from(&ExtensionComponent::keccak_extensions()[1..]);
         let _ = config.is_keccak_enabled();
     }
+
+    #[test]
+    #[should_panic = "sha256 components cannot be enabled partially"]
+    fn invalid_sha256_config_panic() {
+        let config = ExtensionsConfig::from(&ExtensionComponent::sha256_extensions()[1..]);
+        let _ = config.is_sha256_enabled();
+    }
 }
@@ -0,0 +1,804 @@
+//! Extensions defined outside of the prover crate, e.g. circuits of precompiles.
+//!
+//! An external extension is a component with its own preprocessed, main and interaction traces constrained by
+//! a [`FrameworkEval`]. Unlike built-in extensions, it has no access to the prover's side note: its main trace is
+//! generated from [`ExternalStep`]s of custom instructions that aren't handled by built-in extensions, in the order
+//! of execution.
+//!
+//! Such instructions are R-type instructions with opcode `0b0001011`, their `funct3` and `funct7` fields select
+//! the extension. The base component constrains decoding, reads both source registers, writes `result` to
+//! the destination register and sends [`ExternalStep::lookup_values`] to [`ExternalInstructionLookupElements`] with
+//! multiplicity one. It **doesn't** constrain the result, an extension is expected to:
+//!
+//! 1. Consume the tuple of every step it proves with multiplicity minus one, using its own `funct3` and `funct7` as
+//!    constants, so that the logup sum only balances if each executed instruction is proven by one extension.
+//! 2. Constrain the result and the written memory.
+//! 3. For each of [`ExternalStep::memory_accesses`], remove [`ExternalMemoryAccess::prev_values`] from and add
+//!    [`ExternalMemoryAccess::next_values`] to [`MemoryLookupElements`], constraining the timestamp to be incremented
+//!    and the value to be unchanged by loads. Only read-write memory can be accessed.
+//!
+//! Extensions may also share relations drawn by [`ExternalLookupElements`]. An extension is registered by passing
+//! [`ExtensionComponent::external`] both to the prover and to the verifier.
+
+use std::{fmt, hash::Hash, iter, sync::Arc};
+
+use nexus_common::constants::WORD_SIZE_HALVED;
+use num_traits::One;
+use stwo_prover::{
+    constraint_framework::{
+        FrameworkComponent, FrameworkEval, InfoEvaluator, Relation, RelationEFTraitBound,
+        TraceLocationAllocator,
+    },
+    core::{
+        air::{Component, ComponentProver},
+        backend::simd::{column::BaseColumn, SimdBackend},
+        channel::Channel,
+        fields::{m31::BaseField, qm31::SecureField},
+        pcs::TreeVec,
+        poly::{
+            circle::{CanonicCoset, CircleEvaluation},
+            BitReversedOrder,
+        },
+        ColumnVec,
+    },
+};
+
+use super::{ComponentTrace, ExtensionComponent};
+use crate::{
+    components::AllLookupElements,
+    debug::{DebugReport, InteractionTrace},
+    trace::program_trace::ProgramTraceRef,
+};
+
+pub use crate::chips::custom::{
+    ExternalInstructionLookupElements, EXTERNAL_LOOKUP_TUPLE_SIZE, EXTERNAL_OPCODE,
+};
+/// Lookup elements of the read-write memory checking, see [`ExternalMemoryAccess`].
+pub use crate::components::lookups::LoadStoreLookupElements as MemoryLookupElements;
+
+/// Number of values combined by [`MemoryLookupElements`] for each access: the address, the value and the timestamp.
+pub const MEMORY_LOOKUP_TUPLE_SIZE: usize = 2 * WORD_SIZE_HALVED + 1;
+
+/// Execution of a custom instruction proven by an external extension.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ExternalStep {
+    /// Clock of the step, which is the index of its row in the main trace plus one.
+    pub clk: u32,
+    pub fn3: u8,
+    pub fn7: u8,
+    /// Value written to the destination register, the previous value of the register if the instruction
+    /// doesn't return one.
+    pub result: u32,
+    /// Value of the first source register.
+    pub value_b: u32,
+    /// Value of the second source register.
+    pub value_c: u32,
+    /// Byte accesses to the read-write memory, in the order of timestamps.
+    pub memory_accesses: Vec<ExternalMemoryAccess>,
+}
+
+impl ExternalStep {
+    /// Returns the tuple sent by the base component to [`ExternalInstructionLookupElements`]:
+    /// `fn3, fn7, clk_low, clk_high`, followed by bytes of `result`, `value_b` and `value_c`.
+    pub fn lookup_values(&self) -> [BaseField; EXTERNAL_LOOKUP_TUPLE_SIZE] {
+        let mut values = [BaseField::from(0); EXTERNAL_LOOKUP_TUPLE_SIZE];
+        values[0] = BaseField::from(self.fn3 as u32);
+        values[1] = BaseField::from(self.fn7 as u32);
+        values[2] = BaseField::from(self.clk & 0xFFFF);
+        values[3] = BaseField::from(self.clk >> 16);
+        for (value, byte) in values[4..].iter_mut().zip(
+            [self.result, self.value_b, self.value_c]
+                .into_iter()
+                .flat_map(u32::to_le_bytes),
+        ) {
+            *value = BaseField::from(byte as u32);
+        }
+        values
+    }
+}
+
+/// Access to a byte of the read-write memory made by an external custom instruction.
+///
+/// An access removes the byte with its previous value and timestamp from the memory checking, and adds it back with
+/// the current value and the timestamp incremented by one.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ExternalMemoryAccess {
+    pub address: u32,
+    pub prev_value: u8,
+    /// Equal to `prev_value` for loads.
+    pub value: u8,
+    pub prev_timestamp: u32,
+}
+
+impl ExternalMemoryAccess {
+    /// Returns the tuple removed from [`MemoryLookupElements`] by the access.
+    pub fn prev_values(&self) -> [BaseField; MEMORY_LOOKUP_TUPLE_SIZE] {
+        Self::lookup_values(self.address, self.prev_value, self.prev_timestamp)
+    }
+
+    /// Returns the tuple added to [`MemoryLookupElements`] by the access.
+    pub fn next_values(&self) -> [BaseField; MEMORY_LOOKUP_TUPLE_SIZE] {
+        Self::lookup_values(self.address, self.value, self.prev_timestamp + 1)
+    }
+
+    fn lookup_values(
+        address: u32,
+        value: u8,
+        timestamp: u32,
+    ) -> [BaseField; MEMORY_LOOKUP_TUPLE_SIZE] {
+        [
+            address & 0xFFFF,
+            address >> 16,
+            value as u32,
+            timestamp & 0xFFFF,
+            timestamp >> 16,
+        ]
+        .map(BaseField::from)
+    }
+}
+
+/// Prover extension implemented outside of the crate.
+pub trait ExternalExtension: fmt::Debug + Send + Sync + 'static {
+    /// Constraint evaluator of the component.
+    type Eval: FrameworkEval + Sync + 'static;
+
+    /// Unique name of the extension, two extensions with the same name are considered equal.
+    fn name(&self) -> &str;
+
+    /// Number of values combined by the extension's own relation, zero if it doesn't need random elements.
+    ///
+    /// The elements are drawn after the main trace is committed and are available with [`AllLookupElements::external`].
+    fn relation_size(&self) -> usize {
+        0
+    }
+
+    /// Creates the constraint evaluator of the component.
+    fn eval(&self, log_size: u32, lookup_elements: &AllLookupElements) -> Self::Eval;
+
+    /// Returns the log size of the component given the executed custom instructions.
+    fn compute_log_size(&self, steps: &[ExternalStep]) -> u32;
+
+    /// Generates preprocessed columns, they must only depend on the program and the initial memory as the verifier
+    /// recomputes them.
+    fn generate_preprocessed_trace(
+        &self,
+        log_size: u32,
+        program_trace_ref: ProgramTraceRef,
+    ) -> Vec<BaseColumn>;
+
+    /// Returns the log sizes of preprocessed columns.
+    fn preprocessed_trace_sizes(&self, log_size: u32) -> Vec<u32>;
+
+    /// Generates main trace columns from the executed custom instructions.
+    ///
+    /// Steps of all external extensions are passed, an extension only proves the ones matching its `funct3`
+    /// and `funct7`.
+    fn generate_main_trace(
+        &self,
+        log_size: u32,
+        program_trace_ref: ProgramTraceRef,
+        steps: &[ExternalStep],
+    ) -> Vec<BaseColumn>;
+
+    /// Generates the interaction trace and returns it along with the claimed logup sum.
+    fn generate_interaction_trace(
+        &self,
+        log_size: u32,
+        preprocessed_trace: &[BaseColumn],
+        main_trace: &[BaseColumn],
+        lookup_elements: &AllLookupElements,
+    ) -> (
+        ColumnVec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>>,
+        SecureField,
+    );
+}
+
+/// Dyn-compatible counterpart of [`ExternalExtension`], the associated evaluator type is erased by boxing components.
+trait DynExternalExtension: fmt::Debug + Send + Sync {
+    fn name(&self) -> &str;
+
+    fn relation_size(&self) -> usize;
+
+    fn compute_log_size(&self, steps: &[ExternalStep]) -> u32;
+
+    fn generate_preprocessed_trace(
+        &self,
+        log_size: u32,
+        program_trace_ref: ProgramTraceRef,
+    ) -> ColumnVec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>>;
+
+    fn preprocessed_trace_sizes(&self, log_size: u32) -> Vec<u32>;
+
+    fn generate_component_trace(
+        &self,
+        log_size: u32,
+        program_trace_ref: ProgramTraceRef,
+        steps: &[ExternalStep],
+    ) -> ComponentTrace;
+
+    fn generate_interaction_trace(
+        &self,
+        component_trace: ComponentTrace,
+        lookup_elements: &AllLookupElements,
+    ) -> (
+        ColumnVec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>>,
+        SecureField,
+    );
+
+    fn to_component_prover(
+        &self,
+        tree_span_provider: &mut TraceLocationAllocator,
+        lookup_elements: &AllLookupElements,
+        log_size: u32,
+        claimed_sum: SecureField,
+    ) -> Box<dyn ComponentProver<SimdBackend>>;
+
+    fn to_component(
+        &self,
+        tree_span_provider: &mut TraceLocationAllocator,
+        lookup_elements: &AllLookupElements,
+        log_size: u32,
+        claimed_sum: SecureField,
+    ) -> Box<dyn Component>;
+
+    fn trace_sizes(&self, log_size: u32) -> TreeVec<Vec<u32>>;
+
+    fn debug_constraints(
+        &self,
+        component_trace: &ComponentTrace,
+        interaction_trace: &InteractionTrace,
+        lookup_elements: &AllLookupElements,
+        report: &mut DebugReport,
+    );
+}
+
+impl<T: ExternalExtension> DynExternalExtension for T {
+    fn name(&self) -> &str {
+        ExternalExtension::name(self)
+    }
+
+    fn relation_size(&self) -> usize {
+        ExternalExtension::relation_size(self)
+    }
+
+    fn compute_log_size(&self, steps: &[ExternalStep]) -> u32 {
+        ExternalExtension::compute_log_size(self, steps)
+    }
+
+    fn generate_preprocessed_trace(
+        &self,
+        log_size: u32,
+        program_trace_ref: ProgramTraceRef,
+    ) -> ColumnVec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>> {
+        let domain = CanonicCoset::new(log_size).circle_domain();
+        ExternalExtension::generate_preprocessed_trace(self, log_size, program_trace_ref)
+            .into_iter()
+            .map(|col| CircleEvaluation::new(domain, col))
+            .collect()
+    }
+
+    fn preprocessed_trace_sizes(&self, log_size: u32) -> Vec<u32> {
+        ExternalExtension::preprocessed_trace_sizes(self, log_size)
+    }
+
+    fn generate_component_trace(
+        &self,
+        log_size: u32,
+        program_trace_ref: ProgramTraceRef,
+        steps: &[ExternalStep],
+    ) -> ComponentTrace {
+        ComponentTrace {
+            log_size,
+            preprocessed_trace: ExternalExtension::generate_preprocessed_trace(
+                self,
+                log_size,
+                program_trace_ref,
+            ),
+            original_trace: self.generate_main_trace(log_size, program_trace_ref, steps),
+        }
+    }
+
+    fn generate_interaction_trace(
+        &self,
+        component_trace: ComponentTrace,
+        lookup_elements: &AllLookupElements,
+    ) -> (
+        ColumnVec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>>,
+        SecureField,
+    ) {
+        ExternalExtension::generate_interaction_trace(
+            self,
+            component_trace.log_size,
+            &component_trace.preprocessed_trace,
+            &component_trace.original_trace,
+            lookup_elements,
+        )
+    }
+
+    fn to_component_prover(
+        &self,
+        tree_span_provider: &mut TraceLocationAllocator,
+        lookup_elements: &AllLookupElements,
+        log_size: u32,
+        claimed_sum: SecureField,
+    ) -> Box<dyn ComponentProver<SimdBackend>> {
+        Box::new(FrameworkComponent::new(
+            tree_span_provider,
+            self.eval(log_size, lookup_elements),
+            claimed_sum,
+        ))
+    }
+
+    fn to_component(
+        &self,
+        tree_span_provider: &mut TraceLocationAllocator,
+        lookup_elements: &AllLookupElements,
+        log_size: u32,
+        claimed_sum: SecureField,
+    ) -> Box<dyn Component> {
+        Box::new(FrameworkComponent::new(
+            tree_span_provider,
+            self.eval(log_size, lookup_elements),
+            claimed_sum,
+        ))
+    }
+
+    fn trace_sizes(&self, log_size: u32) -> TreeVec<Vec<u32>> {
+        let mut lookup_elements = AllLookupElements::dummy();
+        let relation_size = ExternalExtension::relation_size(self);
+        if relation_size > 0 {
+            lookup_elements.insert_external(ExternalLookupElements::dummy(
+                ExternalExtension::name(self),
+                relation_size,
+            ));
+        }
+        self.eval(log_size, &lookup_elements)
+            .evaluate(InfoEvaluator::empty())
+            .mask_offsets
+            .as_cols_ref()
+            .map_cols(|_| log_size)
+    }
+
+    fn debug_constraints(
+        &self,
+        component_trace: &ComponentTrace,
+        interaction_trace: &InteractionTrace,
+        lookup_elements: &AllLookupElements,
+        report: &mut DebugReport,
+    ) {
+        report.check_component(
+            ExternalExtension::name(self),
+            &self.eval(component_trace.log_size, lookup_elements),
+            component_trace,
+            interaction_trace,
+        );
+    }
+}
+
+/// Type-erased external extension, see [`ExtensionComponent::external`].
+#[derive(Clone)]
+pub struct ExternalComponent(Arc<dyn DynExternalExtension>);
+
+impl ExternalComponent {
+    pub(crate) fn name(&self) -> &str {
+        self.0.name()
+    }
+
+    pub(crate) fn draw_lookup_elements(
+        &self,
+        lookup_elements: &mut AllLookupElements,
+        channel: &mut impl Channel,
+    ) {
+        let relation_size = self.0.relation_size();
+        if relation_size > 0 {
+            lookup_elements.insert_external(ExternalLookupElements::draw(
+                channel,
+                self.0.name(),
+                relation_size,
+            ));
+        }
+    }
+
+    pub(crate) fn generate_preprocessed_trace(
+        &self,
+        log_size: u32,
+        program_trace_ref: ProgramTraceRef,
+    ) -> ColumnVec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>> {
+        self.0
+            .generate_preprocessed_trace(log_size, program_trace_ref)
+    }
+
+    pub(crate) fn generate_component_trace(
+        &self,
+        log_size: u32,
+        program_trace_ref: ProgramTraceRef,
+        steps: &[ExternalStep],
+    ) -> ComponentTrace {
+        self.0
+            .generate_component_trace(log_size, program_trace_ref, steps)
+    }
+
+    pub(crate) fn generate_interaction_trace(
+        &self,
+        component_trace: ComponentTrace,
+        lookup_elements: &AllLookupElements,
+    ) -> (
+        ColumnVec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>>,
+        SecureField,
+    ) {
+        self.0
+            .generate_interaction_trace(component_trace, lookup_elements)
+    }
+
+    pub(crate) fn to_component_prover(
+        &self,
+        tree_span_provider: &mut TraceLocationAllocator,
+        lookup_elements: &AllLookupElements,
+        log_size: u32,
+        claimed_sum: SecureField,
+    ) -> Box<dyn ComponentProver<SimdBackend>> {
+        self.0
+            .to_component_prover(tree_span_provider, lookup_elements, log_size, claimed_sum)
+    }
+
+    pub(crate) fn to_component(
+        &self,
+        tree_span_provider: &mut TraceLocationAllocator,
+        lookup_elements: &AllLookupElements,
+        log_size: u32,
+        claimed_sum: SecureField,
+    ) -> Box<dyn Component> {
+        self.0
+            .to_component(tree_span_provider, lookup_elements, log_size, claimed_sum)
+    }
+
+    pub(crate) fn compute_log_size(&self, steps: &[ExternalStep]) -> u32 {
+        self.0.compute_log_size(steps)
+    }
+
+    pub(crate) fn trace_sizes(&self, log_size: u32) -> TreeVec<Vec<u32>> {
+        self.0.trace_sizes(log_size)
+    }
+
+    pub(crate) fn preprocessed_trace_sizes(&self, log_size: u32) -> Vec<u32> {
+        self.0.preprocessed_trace_sizes(log_size)
+    }
+
+    pub(crate) fn debug_constraints(
+        &self,
+        component_trace: &ComponentTrace,
+        interaction_trace: &InteractionTrace,
+        lookup_elements: &AllLookupElements,
+        report: &mut DebugReport,
+    ) {
+        self.0
+            .debug_constraints(component_trace, interaction_trace, lookup_elements, report)
+    }
+}
+
+impl fmt::Debug for ExternalComponent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.0, f)
+    }
+}
+
+impl PartialEq for ExternalComponent {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.name() == other.0.name()
+    }
+}
+
+impl Eq for ExternalComponent {}
+
+impl Hash for ExternalComponent {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.0.name().hash(state);
+    }
+}
+
+impl ExtensionComponent {
+    /// Wraps an extension implemented outside of the crate.
+    pub fn external(extension: impl ExternalExtension) -> Self {
+        Self::External(ExternalComponent(Arc::new(extension)))
+    }
+}
+
+/// Random elements of a relation owned by an external extension.
+///
+/// Same as the ones generated by [`stwo_prover::relation`], except that the size is only known at runtime.
+#[derive(Debug, Clone)]
+pub struct ExternalLookupElements {
+    name: String,
+    z: SecureField,
+    alpha_powers: Vec<SecureField>,
+}
+
+impl ExternalLookupElements {
+    pub(crate) fn draw(channel: &mut impl Channel, name: &str, size: usize) -> Self {
+        let [z, alpha] = channel.draw_felts(2).try_into().unwrap();
+        Self::new(name, z, alpha, size)
+    }
+
+    pub fn dummy(name: &str, size: usize) -> Self {
+        Self::new(name, SecureField::one(), SecureField::one(), size)
+    }
+
+    fn new(name: &str, z: SecureField, alpha: SecureField, size: usize) -> Self {
+        Self {
+            name: name.to_owned(),
+            z,
+            alpha_powers: iter::successors(Some(SecureField::one()), |power| Some(*power * alpha))
+                .take(size)
+                .collect(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl<F: Clone, EF: RelationEFTraitBound<F>> Relation<F, EF> for ExternalLookupElements {
+    fn combine(&self, values: &[F]) -> EF {
+        assert!(
+            values.len() <= self.alpha_powers.len(),
+            "relation {} combines at most {} values",
+            self.name,
+            self.alpha_powers.len()
+        );
+        values
+            .iter()
+            .zip(&self.alpha_powers)
+            .fold(EF::zero(), |acc, (value, &power)| {
+                acc + EF::from(power) * value.clone()
+            })
+            - EF::from(self.z)
+    }
+
+    fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    fn get_size(&self) -> usize {
+        self.alpha_powers.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use stwo_prover::{
+        constraint_framework::{logup::LogupTraceGenerator, EvalAtRow, RelationEntry},
+        core::backend::simd::{
+            m31::{PackedBaseField, LOG_N_LANES},
+            qm31::PackedSecureField,
+        },
+    };
+
+    use crate::{
+        config::DEFAULT_MIN_SECURITY_BITS,
+        machine::{BaseComponent, Machine},
+    };
+    use nexus_vm::{
+        riscv::{BasicBlock, BuiltinOpcode, Instruction, Opcode},
+        trace::k_trace_direct,
+        WORD_SIZE,
+    };
+
+    /// Toy extension proving an instruction that copies the first source register to the destination register.
+    #[derive(Debug)]
+    struct Mv;
+
+    const MV_FN3: u8 = 0b000;
+    const MV_FN7: u8 = 0b0000001;
+
+    /// is_real, clk_low, clk_high, value_b and value_c
+    const NUM_COLUMNS: usize = 3 + 2 * WORD_SIZE;
+
+    impl Mv {
+        fn steps(steps: &[ExternalStep]) -> impl Iterator<Item = &ExternalStep> {
+            steps
+                .iter()
+                .filter(|step| step.fn3 == MV_FN3 && step.fn7 == MV_FN7)
+        }
+
+        /// Tuple consumed from the instruction relation, the result equals `value_b`.
+        fn tuple<F: Clone + From<BaseField>>(columns: &[F]) -> Vec<F> {
+            let (clk, values) = columns[1..].split_at(2);
+            let (value_b, value_c) = values.split_at(WORD_SIZE);
+            [
+                &[
+                    F::from(BaseField::from(MV_FN3 as u32)),
+                    F::from(BaseField::from(MV_FN7 as u32)),
+                ],
+                clk,
+                value_b,
+                value_b,
+                value_c,
+            ]
+            .concat()
+        }
+    }
+
+    struct MvEval {
+        log_size: u32,
+        lookup_elements: ExternalInstructionLookupElements,
+    }
+
+    impl FrameworkEval for MvEval {
+        fn log_size(&self) -> u32 {
+            self.log_size
+        }
+
+        fn max_constraint_log_degree_bound(&self) -> u32 {
+            self.log_size + 1
+        }
+
+        fn evaluate<E: EvalAtRow>(&self, mut eval: E) -> E {
+            let columns: Vec<E::F> = iter::repeat_with(|| eval.next_trace_mask())
+                .take(NUM_COLUMNS)
+                .collect();
+            let is_real = columns[0].clone();
+            eval.add_constraint(is_real.clone() * (E::F::one() - is_real.clone()));
+
+            eval.add_to_relation(RelationEntry::new(
+                &self.lookup_elements,
+                (-is_real).into(),
+                &Mv::tuple(&columns),
+            ));
+            eval.finalize_logup();
+            eval
+        }
+    }
+
+    impl ExternalExtension for Mv {
+        type Eval = MvEval;
+
+        fn name(&self) -> &str {
+            "mv"
+        }
+
+        fn eval(&self, log_size: u32, lookup_elements: &AllLookupElements) -> Self::Eval {
+            MvEval {
+                log_size,
+                lookup_elements: lookup_elements.external_instruction().clone(),
+            }
+        }
+
+        fn compute_log_size(&self, steps: &[ExternalStep]) -> u32 {
+            let num_steps = Self::steps(steps).count();
+            num_steps
+                .next_power_of_two()
+                .trailing_zeros()
+                .max(LOG_N_LANES)
+        }
+
+        fn generate_preprocessed_trace(
+            &self,
+            _log_size: u32,
+            _program_trace_ref: ProgramTraceRef,
+        ) -> Vec<BaseColumn> {
+            Vec::new()
+        }
+
+        fn preprocessed_trace_sizes(&self, _log_size: u32) -> Vec<u32> {
+            Vec::new()
+        }
+
+        fn generate_main_trace(
+            &self,
+            log_size: u32,
+            _program_trace_ref: ProgramTraceRef,
+            steps: &[ExternalStep],
+        ) -> Vec<BaseColumn> {
+            let mut columns = vec![vec![BaseField::from(0); 1 << log_size]; NUM_COLUMNS];
+            for (row_idx, step) in Self::steps(steps).enumerate() {
+                assert_eq!(step.result, step.value_b);
+                assert!(step.memory_accesses.is_empty());
+
+                // skip fn3, fn7 and the result
+                let values = step.lookup_values();
+                let (clk, values) = values[2..].split_at(2);
+                let row = iter::once(BaseField::one())
+                    .chain(clk.iter().copied())
+                    .chain(values[WORD_SIZE..].iter().copied());
+                for (column, value) in columns.iter_mut().zip(row) {
+                    column[row_idx] = value;
+                }
+            }
+            columns.into_iter().map(BaseColumn::from_iter).collect()
+        }
+
+        fn generate_interaction_trace(
+            &self,
+            log_size: u32,
+            _preprocessed_trace: &[BaseColumn],
+            main_trace: &[BaseColumn],
+            lookup_elements: &AllLookupElements,
+        ) -> (
+            ColumnVec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>>,
+            SecureField,
+        ) {
+            let lookup_elements = lookup_elements.external_instruction();
+            let mut logup_trace_gen = LogupTraceGenerator::new(log_size);
+
+            let mut logup_col_gen = logup_trace_gen.new_col();
+            for vec_row in 0..1 << (log_size - LOG_N_LANES) {
+                let columns: Vec<PackedBaseField> =
+                    main_trace.iter().map(|col| col.data[vec_row]).collect();
+                let denom: PackedSecureField = lookup_elements.combine(&Mv::tuple(&columns));
+                logup_col_gen.write_frac(vec_row, (-columns[0]).into(), denom);
+            }
+            logup_col_gen.finalize_col();
+            logup_trace_gen.finalize_last()
+        }
+    }
+
+    #[test]
+    fn prove_verify_external_extension() {
+        let basic_block = vec![BasicBlock::new(vec![
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::ADDI), 1, 0, 1),
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::ADDI), 2, 1, 1),
+        ])];
+        let (view, program_trace) =
+            k_trace_direct(&basic_block, 1).expect("error generating trace");
+        let extensions = [ExtensionComponent::external(Mv)];
+
+        let proof =
+            Machine::<BaseComponent>::prove_with_extensions(&extensions, &program_trace, &view)
+                .unwrap();
+        Machine::<BaseComponent>::verify_with_extensions(
+            &extensions,
+            DEFAULT_MIN_SECURITY_BITS,
+            proof.clone(),
+            view.get_program_memory(),
+            &[],
+            view.get_initial_memory(),
+            view.get_exit_code(),
+            view.get_public_output(),
+        )
+        .unwrap();
+
+        // The verifier must be aware of the extension.
+        assert!(Machine::<BaseComponent>::verify(
+            proof,
+            view.get_program_memory(),
+            &[],
+            view.get_initial_memory(),
+            view.get_exit_code(),
+            view.get_public_output(),
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn external_step_lookup_values() {
+        let step = ExternalStep {
+            clk: 0x0001_0002,
+            fn3: MV_FN3,
+            fn7: MV_FN7,
+            result: 0x0403_0201,
+            value_b: 0x0403_0201,
+            value_c: 0xFF,
+            memory_accesses: vec![],
+        };
+        let expected = [0u32, 1, 2, 1, 1, 2, 3, 4, 1, 2, 3, 4, 0xFF, 0, 0, 0].map(BaseField::from);
+        assert_eq!(step.lookup_values(), expected);
+
+        let access = ExternalMemoryAccess {
+            address: 0x0001_0002,
+            prev_value: 3,
+            value: 4,
+            prev_timestamp: 0xFFFF,
+        };
+        assert_eq!(
+            access.prev_values(),
+            [2u32, 1, 3, 0xFFFF, 0].map(BaseField::from)
+        );
+        assert_eq!(
+            access.next_values(),
+            [2u32, 1, 4, 0, 1].map(BaseField::from)
+        );
+    }
+}
@@ -82,12 +82,40 @@ impl FrameworkEval for FinalRegEval {
         // let _reg_idx = eval.next_trace_mask();
         let reg_idx = RegisterIdx::new(FinalRegEval::LOG_SIZE);
         let reg_idx = eval.get_preprocessed_column(reg_idx.id());
+        let initial_value: Vec<_> = (0..WORD_SIZE)
+            .map(|i| {
+                eval.get_preprocessed_column(PreProcessedColumnId {
+                    id: format!("preprocessed_final_reg_initial_value{i}"),
+                })
+            })
+            .collect();
+        let public_final_value: Vec<_> = (0..WORD_SIZE)
+            .map(|i| {
+                eval.get_preprocessed_column(PreProcessedColumnId {
+                    id: format!("preprocessed_final_reg_public_final_value{i}"),
+                })
+            })
+            .collect();
+        let public_final_flag = eval.get_preprocessed_column(PreProcessedColumnId {
+            id: "preprocessed_final_reg_public_final_flag".to_owned(),
+        });
         let final_timestamp: Vec<_> = (0..4).map(|_| eval.next_trace_mask()).collect();
         let final_value: Vec<_> = (0..4).map(|_| eval.next_trace_mask()).collect();
 
+        // Enforce: public_final_flag * (final_value - public_final_value) = 0
+        for i in 0..WORD_SIZE {
+            eval.add_constraint(
+                public_final_flag.clone()
+                    * (final_value[i].clone() - public_final_value[i].clone()),
+            );
+        }
+
         // Add initial register memory state
         let mut tuple: [E::F; Self::TUPLE_SIZE] = std::array::from_fn(|_| E::F::zero());
         tuple[0] = reg_idx.clone();
+        for (i, elm) in initial_value.into_iter().enumerate() {
+            tuple[1 + WORD_SIZE + i] = elm;
+        }
         let numerator = E::F::one();
 
         eval.add_to_relation(RelationEntry::new(
@@ -141,9 +169,9 @@ impl BuiltInExtension for FinalReg {
     fn generate_preprocessed_trace(
         &self,
         _log_size: u32,
-        _program_trace_ref: ProgramTraceRef,
+        program_trace_ref: ProgramTraceRef,
     ) -> ColumnVec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>> {
-        let base_cols = Self::preprocessed_base_columns();
+        let base_cols = Self::preprocessed_base_columns(program_trace_ref);
         let domain = CanonicCoset::new(FinalRegEval::LOG_SIZE).circle_domain();
         base_cols
             .into_iter()
@@ -157,10 +185,10 @@ impl BuiltInExtension for FinalReg {
     fn generate_component_trace(
         &self,
         log_size: u32,
-        _: ProgramTraceRef,
+        program_trace_ref: ProgramTraceRef,
         side_note: &mut SideNote,
     ) -> ComponentTrace {
-        let preprocessed_trace = Self::preprocessed_base_columns();
+        let preprocessed_trace = Self::preprocessed_base_columns(program_trace_ref);
         let original_trace = Self::base_columns(side_note);
 
         ComponentTrace {
@@ -171,7 +199,7 @@ impl BuiltInExtension for FinalReg {
     }
 
     fn preprocessed_trace_sizes(_log_size: u32) -> Vec<u32> {
-        vec![FinalRegEval::LOG_SIZE]
+        vec![FinalRegEval::LOG_SIZE; FinalReg::NUM_PREPROCESSED_TRACE_COLS]
     }
 
     fn generate_interaction_trace(
@@ -187,6 +215,7 @@ impl BuiltInExtension for FinalReg {
 
         let mut logup_trace_gen = LogupTraceGenerator::new(FinalRegEval::LOG_SIZE);
         let row_idx = &component_trace.preprocessed_trace[0];
+        let initial_value = &component_trace.preprocessed_trace[1..1 + WORD_SIZE];
         let base_cols = &component_trace.original_trace;
 
         // Adding the initial register memory state and subtracting the final register memory state
@@ -196,6 +225,9 @@ impl BuiltInExtension for FinalReg {
             let mut tuple: [PackedM31; FinalRegEval::TUPLE_SIZE] =
                 [BaseField::zero().into(); FinalRegEval::TUPLE_SIZE]; // reg_idx, cur_timestamp, cur_value
             tuple[0] = row_idx; // Use row_idx as register index
+            for (i, col) in initial_value.iter().enumerate() {
+                tuple[1 + WORD_SIZE + i] = col.data[vec_row];
+            }
             let denom_a: PackedSecureField = lookup_element.combine(tuple.as_slice());
             let numerator_a: PackedSecureField =
                 PackedBaseField::broadcast(BaseField::one()).into();
@@ -220,9 +252,33 @@ impl BuiltInExtension for FinalReg {
 }
 
 impl FinalReg {
-    fn preprocessed_base_columns() -> Vec<BaseColumn> {
+    const NUM_PREPROCESSED_TRACE_COLS: usize = 2 + 2 * WORD_SIZE;
+
+    /// Register indices, followed by four columns of initial values, four columns of public final values
+    /// and the flag enabling the latter.
+    ///
+    /// Initial values are zero and final values are private unless the trace is a continuation segment.
+    fn preprocessed_base_columns(program_trace_ref: ProgramTraceRef) -> Vec<BaseColumn> {
         let reg_idx = BaseColumn::from_iter((0..32).map(BaseField::from));
-        vec![reg_idx]
+        let mut base_cols = vec![reg_idx];
+
+        let (initial_values, final_values) = program_trace_ref.segment.map_or(
+            ([0; NUM_REGISTERS as usize], [0; NUM_REGISTERS as usize]),
+            |segment| (*segment.initial_regs, *segment.final_regs),
+        );
+        for values in [initial_values, final_values] {
+            let values = values.map(|val| val.into_base_fields());
+            for i in 0..WORD_SIZE {
+                base_cols.push(BaseColumn::from_iter(values.iter().map(|val| val[i])));
+            }
+        }
+        let public_final_flag = program_trace_ref.segment.is_some().into_base_fields()[0];
+        base_cols.push(BaseColumn::from_iter(std::iter::repeat_n(
+            public_final_flag,
+            NUM_REGISTERS as usize,
+        )));
+        assert_eq!(base_cols.len(), Self::NUM_PREPROCESSED_TRACE_COLS);
+        base_cols
     }
     fn base_columns(side_note: &SideNote) -> Vec<BaseColumn> {
         let mut base_cols: Vec<BaseColumn> = vec![];
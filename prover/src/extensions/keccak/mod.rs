@@ -10,7 +10,8 @@ pub(crate) use round::{KeccakRound, LANE_SIZE};
 use super::ExtensionComponent;
 
 pub const fn keccak_extensions() -> &'static [ExtensionComponent] {
-    &[
+    // A constant rather than a promoted temporary: the enum has variants with destructors.
+    const EXTENSIONS: &[ExtensionComponent] = &[
         ExtensionComponent::PermutationMemoryCheck(PermutationMemoryCheck { _private: () }),
         ExtensionComponent::KeccakRound(KeccakRound {
             index: 0,
@@ -29,7 +30,8 @@ pub const fn keccak_extensions() -> &'static [ExtensionComponent] {
             _phantom: std::marker::PhantomData,
         }),
         ExtensionComponent::BitRotateTable(bit_rotate::BitRotateTable { _private: () }),
-    ]
+    ];
+    EXTENSIONS
 }
 
 #[cfg(test)]
@@ -37,6 +39,7 @@ mod tests {
     use crate::{
         chips::{custom::KeccakChip, LoadStoreChip},
         components::AllLookupElements,
+        config::DEFAULT_MIN_SECURITY_BITS,
         extensions::{ComponentTrace, ExtensionsConfig},
         machine::{BaseComponent, Machine},
         trace::{
@@ -81,6 +84,7 @@ mod tests {
             init_memory: view.get_initial_memory(),
             exit_code: view.get_exit_code(),
             public_output: view.get_public_output(),
+            segment: None,
         };
 
         let program_traces =
@@ -213,6 +217,7 @@ mod tests {
         .unwrap();
         Machine::<BaseComponent>::verify_with_extensions(
             keccak_extensions(),
+            DEFAULT_MIN_SECURITY_BITS,
             proof,
             view.get_program_memory(),
             &[],
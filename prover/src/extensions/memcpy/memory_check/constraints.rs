@@ -0,0 +1,147 @@
+use nexus_common::constants::WORD_SIZE_HALVED;
+use num_traits::One;
+use stwo_prover::{
+    constraint_framework::{EvalAtRow, RelationEntry},
+    core::fields::m31::BaseField,
+};
+
+use crate::components::lookups::{LoadStoreLookupElements, MemcpyCallLookupElements};
+
+pub struct MemcpyMemoryCheckEval<'a, E> {
+    pub(crate) eval: E,
+    pub(crate) memory_lookup_elements: &'a LoadStoreLookupElements,
+    pub(crate) call_lookup_elements: &'a MemcpyCallLookupElements,
+}
+
+impl<E: EvalAtRow> MemcpyMemoryCheckEval<'_, E> {
+    pub fn eval(mut self) -> E {
+        let value = self.eval.next_trace_mask();
+        let prev_value = self.eval.next_trace_mask();
+        let dst_addr = self.next_columns(WORD_SIZE_HALVED);
+        let dst_prev_ts = self.next_columns(WORD_SIZE_HALVED);
+        let dst_next_ts = self.next_columns(WORD_SIZE_HALVED);
+        let src_addr = self.next_columns(WORD_SIZE_HALVED);
+        let src_prev_ts = self.next_columns(WORD_SIZE_HALVED);
+        let src_next_ts = self.next_columns(WORD_SIZE_HALVED);
+        let dst_ts_carry = self.eval.next_trace_mask();
+        let src_ts_carry = self.eval.next_trace_mask();
+        let is_copy = self.eval.next_trace_mask();
+        let remaining = self.eval.next_trace_mask();
+        let remaining_minus_one_inv = self.eval.next_trace_mask();
+        let is_last = self.eval.next_trace_mask();
+        let dst_addr_carry = self.eval.next_trace_mask();
+        let src_addr_carry = self.eval.next_trace_mask();
+
+        let is_padding = self.eval.next_trace_mask();
+        self.eval
+            .add_constraint(is_padding.clone() * (E::F::one() - is_padding.clone()));
+        let is_real = E::F::one() - is_padding.clone();
+
+        // only real rows can read the source
+        self.eval
+            .add_constraint(is_copy.clone() * (E::F::one() - is_copy.clone()));
+        self.eval.add_constraint(is_copy.clone() * is_padding);
+
+        for flag in [
+            &dst_ts_carry,
+            &src_ts_carry,
+            &is_last,
+            &dst_addr_carry,
+            &src_addr_carry,
+        ] {
+            self.eval
+                .add_constraint(flag.clone() * (E::F::one() - flag.clone()));
+        }
+        self.constrain_increment(&is_real, &dst_prev_ts, &dst_next_ts, &dst_ts_carry);
+        self.constrain_increment(&is_copy, &src_prev_ts, &src_next_ts, &src_ts_carry);
+
+        // the last byte of a call is the one with a single byte remaining, every other real row has more
+        // is_last・ (remaining - 1) = 0
+        // (remaining - 1)・ remaining_minus_one_inv - (is_real - is_last) = 0
+        let remaining_minus_one = remaining.clone() - E::F::one();
+        self.eval
+            .add_constraint(is_last.clone() * remaining_minus_one.clone());
+        self.eval.add_constraint(
+            remaining_minus_one.clone() * remaining_minus_one_inv
+                - (is_real.clone() - is_last.clone()),
+        );
+
+        // the destination address moves to the next byte, the source address of `memset` holds the fill value and
+        // stays in place
+        let modulus = E::F::from(BaseField::from(1 << 16));
+        self.eval.add_constraint(
+            dst_addr_carry.clone() * (dst_addr[0].clone() + E::F::one() - modulus.clone()),
+        );
+        self.eval.add_constraint(
+            src_addr_carry.clone() * (src_addr[0].clone() + is_copy.clone() - modulus.clone()),
+        );
+        self.eval.add_constraint(
+            (is_real.clone() - is_copy.clone()) * (value.clone() - src_addr[0].clone()),
+        );
+
+        // (addr, val, ts)
+        let accesses = [
+            (&is_real, &dst_addr, &prev_value, &dst_prev_ts, &dst_next_ts),
+            (&is_copy, &src_addr, &value, &src_prev_ts, &src_next_ts),
+        ];
+        for (multiplicity, addr, prev_val, prev_ts, next_ts) in accesses {
+            let sub_access = [&addr[..], std::slice::from_ref(prev_val), &prev_ts[..]].concat();
+            let add_access = [&addr[..], std::slice::from_ref(&value), &next_ts[..]].concat();
+
+            self.eval.add_to_relation(RelationEntry::new(
+                self.memory_lookup_elements,
+                (-multiplicity.clone()).into(),
+                &sub_access,
+            ));
+            self.eval.add_to_relation(RelationEntry::new(
+                self.memory_lookup_elements,
+                multiplicity.clone().into(),
+                &add_access,
+            ));
+        }
+
+        // Rows of a call form a chain started by the main component: every row consumes the cursor of its byte and,
+        // unless it is the last one, produces the cursor of the next byte.
+        let cursor = [&dst_addr[..], &src_addr[..], &[remaining, is_copy.clone()]].concat();
+        let next_cursor = [
+            dst_addr[0].clone() + E::F::one() - dst_addr_carry.clone() * modulus.clone(),
+            dst_addr[1].clone() + dst_addr_carry,
+            src_addr[0].clone() + is_copy.clone() - src_addr_carry.clone() * modulus,
+            src_addr[1].clone() + src_addr_carry,
+            remaining_minus_one,
+            is_copy,
+        ];
+        self.eval.add_to_relation(RelationEntry::new(
+            self.call_lookup_elements,
+            (-is_real.clone()).into(),
+            &cursor,
+        ));
+        self.eval.add_to_relation(RelationEntry::new(
+            self.call_lookup_elements,
+            (is_real - is_last).into(),
+            &next_cursor,
+        ));
+
+        self.eval.finalize_logup_in_pairs();
+
+        self.eval
+    }
+
+    fn next_columns(&mut self, size: usize) -> Vec<E::F> {
+        std::iter::repeat_with(|| self.eval.next_trace_mask())
+            .take(size)
+            .collect()
+    }
+
+    /// Constrains a 32-bit value split into halves to be incremented by one.
+    fn constrain_increment(&mut self, is_real: &E::F, prev: &[E::F], next: &[E::F], carry: &E::F) {
+        self.eval.add_constraint(
+            is_real.clone()
+                * (next[0].clone() + carry.clone() * E::F::from((1 << 16).into())
+                    - prev[0].clone()
+                    - E::F::one()),
+        );
+        self.eval
+            .add_constraint(is_real.clone() * (next[1].clone() - prev[1].clone() - carry.clone()));
+    }
+}
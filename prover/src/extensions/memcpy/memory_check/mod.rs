@@ -0,0 +1,126 @@
+use stwo_prover::{
+    constraint_framework::{EvalAtRow, FrameworkEval},
+    core::{
+        backend::simd::{m31::LOG_N_LANES, SimdBackend},
+        fields::{m31::BaseField, qm31::SecureField},
+        poly::{circle::CircleEvaluation, BitReversedOrder},
+        ColumnVec,
+    },
+};
+
+use crate::{
+    components::{
+        lookups::{LoadStoreLookupElements, MemcpyCallLookupElements},
+        AllLookupElements,
+    },
+    extensions::{BuiltInExtension, ComponentTrace, FrameworkEvalExt},
+    trace::{program_trace::ProgramTraceRef, sidenote::SideNote},
+};
+
+mod constraints;
+mod trace;
+
+/// Checks memory accessed by `memcpy` and `memset`, one byte per row.
+///
+/// Every row writes a byte to the destination, rows of `memcpy` also read the same byte from the source. Accesses
+/// are fed into the same logup sum as the initial and final memory of `RamInitFinal`. Rows of a call are chained
+/// through a cursor of (destination, source, remaining length) that starts at the instruction in the main component
+/// and ends at the row with the last byte.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MemcpyMemoryCheck {
+    pub(crate) _private: (),
+}
+
+pub(crate) struct MemcpyMemoryCheckEval {
+    log_size: u32,
+    memory_lookup_elements: LoadStoreLookupElements,
+    call_lookup_elements: MemcpyCallLookupElements,
+}
+
+impl FrameworkEval for MemcpyMemoryCheckEval {
+    fn log_size(&self) -> u32 {
+        self.log_size
+    }
+
+    fn max_constraint_log_degree_bound(&self) -> u32 {
+        self.log_size + 1
+    }
+
+    fn evaluate<E: EvalAtRow>(&self, eval: E) -> E {
+        constraints::MemcpyMemoryCheckEval {
+            eval,
+            memory_lookup_elements: &self.memory_lookup_elements,
+            call_lookup_elements: &self.call_lookup_elements,
+        }
+        .eval()
+    }
+}
+
+impl FrameworkEvalExt for MemcpyMemoryCheckEval {
+    fn new(log_size: u32, lookup_elements: &AllLookupElements) -> Self {
+        let memory_lookup_elements: &LoadStoreLookupElements = lookup_elements.as_ref();
+        let call_lookup_elements: &MemcpyCallLookupElements = lookup_elements.as_ref();
+        Self {
+            log_size,
+            memory_lookup_elements: memory_lookup_elements.clone(),
+            call_lookup_elements: call_lookup_elements.clone(),
+        }
+    }
+
+    fn dummy(log_size: u32) -> Self {
+        Self {
+            log_size,
+            memory_lookup_elements: LoadStoreLookupElements::dummy(),
+            call_lookup_elements: MemcpyCallLookupElements::dummy(),
+        }
+    }
+}
+
+impl BuiltInExtension for MemcpyMemoryCheck {
+    type Eval = MemcpyMemoryCheckEval;
+
+    fn generate_preprocessed_trace(
+        &self,
+        _log_size: u32,
+        _program_trace_ref: ProgramTraceRef,
+    ) -> ColumnVec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>> {
+        vec![]
+    }
+
+    fn generate_component_trace(
+        &self,
+        log_size: u32,
+        _program_trace_ref: ProgramTraceRef,
+        side_note: &mut SideNote,
+    ) -> ComponentTrace {
+        trace::generate_memcpy_trace(log_size, side_note)
+    }
+
+    fn generate_interaction_trace(
+        &self,
+        component_trace: ComponentTrace,
+        _side_note: &SideNote,
+        lookup_elements: &AllLookupElements,
+    ) -> (
+        ColumnVec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>>,
+        SecureField,
+    ) {
+        let memory_lookup_elements: &LoadStoreLookupElements = lookup_elements.as_ref();
+        let call_lookup_elements: &MemcpyCallLookupElements = lookup_elements.as_ref();
+        trace::MemoryCheckLogUpGenerator {
+            component_trace: &component_trace,
+        }
+        .interaction_trace(memory_lookup_elements, call_lookup_elements)
+    }
+
+    fn compute_log_size(&self, side_note: &SideNote) -> u32 {
+        let num_bytes = side_note.memcpy.values.len();
+        let log_size = num_bytes.next_power_of_two().ilog2();
+
+        log_size.max(LOG_N_LANES)
+    }
+
+    fn preprocessed_trace_sizes(_log_size: u32) -> Vec<u32> {
+        vec![]
+    }
+}
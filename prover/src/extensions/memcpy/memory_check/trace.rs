@@ -0,0 +1,208 @@
+use nexus_common::constants::WORD_SIZE_HALVED;
+use num_traits::{One, Zero};
+use stwo_prover::core::fields::FieldExpOps;
+use stwo_prover::{
+    constraint_framework::{logup::LogupTraceGenerator, Relation},
+    core::{
+        backend::simd::{
+            column::BaseColumn,
+            m31::{PackedM31, LOG_N_LANES},
+            qm31::PackedSecureField,
+            SimdBackend,
+        },
+        fields::{m31::BaseField, qm31::SecureField},
+        poly::{circle::CircleEvaluation, BitReversedOrder},
+        ColumnVec,
+    },
+};
+
+use crate::{
+    components::lookups::{LoadStoreLookupElements, MemcpyCallLookupElements},
+    extensions::{keccak::round::trace::get_is_padding_base_column, ComponentTrace},
+    trace::sidenote::SideNote,
+};
+
+const MASK: u32 = (1 << 16) - 1;
+
+pub fn generate_memcpy_trace(log_size: u32, side_note: &SideNote) -> ComponentTrace {
+    // [value, prev_value, dst_addr, dst_prev_ts, dst_next_ts, src_addr, src_prev_ts, src_next_ts, dst_ts_carry,
+    //  src_ts_carry, is_copy, remaining, remaining_minus_one_inv, is_last, dst_addr_carry, src_addr_carry]
+    let num_cols = 2 + WORD_SIZE_HALVED * 6 + 8;
+    let mut original_trace = vec![vec![BaseField::zero(); 1 << log_size]; num_cols];
+
+    let memcpy_side_note = &side_note.memcpy;
+    for (row, (&(dst, src), &(dst_ts, src_ts))) in memcpy_side_note
+        .addresses
+        .iter()
+        .zip(&memcpy_side_note.timestamps)
+        .enumerate()
+    {
+        let value = memcpy_side_note.values[row];
+        let prev_value = memcpy_side_note.prev_values[row];
+        // rows of `memset` leave the source timestamps empty and carry the fill value in place of the address
+        let is_copy = src.is_some();
+        let src = src.unwrap_or(u32::from(value));
+        let remaining = memcpy_side_note.remaining[row];
+        let is_last = remaining == 1;
+        let src_ts = src_ts.unwrap_or_default();
+        let src_next_ts = if is_copy { src_ts + 1 } else { 0 };
+
+        let mut values = vec![u32::from(value), u32::from(prev_value)];
+        for x in [dst, dst_ts, dst_ts + 1, src, src_ts, src_next_ts] {
+            values.extend([x & MASK, x >> 16]);
+        }
+        values.extend([
+            u32::from(dst_ts & MASK == MASK),
+            u32::from(is_copy && src_ts & MASK == MASK),
+            u32::from(is_copy),
+            remaining,
+        ]);
+        let mut values: Vec<BaseField> = values.into_iter().map(BaseField::from).collect();
+        values.push(if is_last {
+            BaseField::zero()
+        } else {
+            BaseField::from(remaining - 1).inverse()
+        });
+        values.extend(
+            [is_last, dst & MASK == MASK, is_copy && src & MASK == MASK]
+                .map(|flag| BaseField::from(u32::from(flag))),
+        );
+
+        assert_eq!(values.len(), num_cols);
+        for (col, value) in original_trace.iter_mut().zip(values) {
+            col[row] = value;
+        }
+    }
+
+    let is_padding = get_is_padding_base_column(log_size, memcpy_side_note.values.len());
+    let mut original_trace: Vec<BaseColumn> = original_trace
+        .into_iter()
+        .map(BaseColumn::from_iter)
+        .collect();
+    original_trace.push(is_padding);
+
+    ComponentTrace {
+        log_size,
+        preprocessed_trace: vec![],
+        original_trace,
+    }
+}
+
+pub(super) struct MemoryCheckLogUpGenerator<'a> {
+    pub(super) component_trace: &'a ComponentTrace,
+}
+
+impl MemoryCheckLogUpGenerator<'_> {
+    pub fn interaction_trace(
+        &self,
+        memory_lookup_elements: &LoadStoreLookupElements,
+        call_lookup_elements: &MemcpyCallLookupElements,
+    ) -> (
+        ColumnVec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>>,
+        SecureField,
+    ) {
+        let log_size = self.component_trace.log_size;
+        let mut logup_gen = LogupTraceGenerator::new(log_size);
+
+        let original_trace = self.component_trace.original_trace.as_slice();
+
+        let (values, rem) = original_trace.split_at(2);
+        let (value, prev_value) = (&values[0], &values[1]);
+        let (dst_addr, rem) = rem.split_at(WORD_SIZE_HALVED);
+        let (dst_prev_ts, rem) = rem.split_at(WORD_SIZE_HALVED);
+        let (dst_next_ts, rem) = rem.split_at(WORD_SIZE_HALVED);
+        let (src_addr, rem) = rem.split_at(WORD_SIZE_HALVED);
+        let (src_prev_ts, rem) = rem.split_at(WORD_SIZE_HALVED);
+        let (src_next_ts, rem) = rem.split_at(WORD_SIZE_HALVED);
+
+        // skip carries
+        let (_, rem) = rem.split_at(2);
+
+        assert_eq!(rem.len(), 7);
+        let is_copy = &rem[0];
+        let remaining = &rem[1];
+        let is_last = &rem[3];
+        let dst_addr_carry = &rem[4];
+        let src_addr_carry = &rem[5];
+        let is_padding = &rem[6];
+
+        let one: PackedSecureField = SecureField::one().into();
+        let accesses = [
+            (None, dst_addr, prev_value, dst_prev_ts, dst_next_ts),
+            (Some(is_copy), src_addr, value, src_prev_ts, src_next_ts),
+        ];
+        for (copy_flag, addr, prev_val, prev_ts, next_ts) in accesses {
+            let mut logup_col_gen = logup_gen.new_col();
+            for vec_idx in 0..(1 << (log_size - LOG_N_LANES)) {
+                let p0: PackedSecureField = {
+                    let tuple: Vec<PackedM31> = addr
+                        .iter()
+                        .chain(std::iter::once(prev_val))
+                        .chain(prev_ts)
+                        .map(|col| col.data[vec_idx])
+                        .collect();
+                    memory_lookup_elements.combine(&tuple)
+                };
+
+                let p1: PackedSecureField = {
+                    let tuple: Vec<PackedM31> = addr
+                        .iter()
+                        .chain(std::iter::once(value))
+                        .chain(next_ts)
+                        .map(|col| col.data[vec_idx])
+                        .collect();
+                    memory_lookup_elements.combine(&tuple)
+                };
+                // destination bytes are written on every real row, source bytes are only read by `memcpy`
+                let multiplicity = match copy_flag {
+                    Some(is_copy) => PackedSecureField::from(is_copy.data[vec_idx]),
+                    None => one - PackedSecureField::from(is_padding.data[vec_idx]),
+                };
+                let numerator = multiplicity * (p0 - p1);
+                logup_col_gen.write_frac(vec_idx, numerator, p0 * p1);
+            }
+
+            logup_col_gen.finalize_col();
+        }
+
+        // every real row consumes the cursor of its byte and all but the last one produce the next cursor
+        let modulus = PackedM31::broadcast(BaseField::from(1 << 16));
+        let one_m31 = PackedM31::broadcast(BaseField::one());
+        let mut logup_col_gen = logup_gen.new_col();
+        for vec_idx in 0..(1 << (log_size - LOG_N_LANES)) {
+            let is_copy = is_copy.data[vec_idx];
+            let remaining = remaining.data[vec_idx];
+            let dst_addr_carry = dst_addr_carry.data[vec_idx];
+            let src_addr_carry = src_addr_carry.data[vec_idx];
+
+            let cursor: PackedSecureField = {
+                let tuple: Vec<PackedM31> = dst_addr
+                    .iter()
+                    .chain(src_addr)
+                    .map(|col| col.data[vec_idx])
+                    .chain([remaining, is_copy])
+                    .collect();
+                call_lookup_elements.combine(&tuple)
+            };
+            let next_cursor: PackedSecureField = {
+                let tuple = [
+                    dst_addr[0].data[vec_idx] + one_m31 - dst_addr_carry * modulus,
+                    dst_addr[1].data[vec_idx] + dst_addr_carry,
+                    src_addr[0].data[vec_idx] + is_copy - src_addr_carry * modulus,
+                    src_addr[1].data[vec_idx] + src_addr_carry,
+                    remaining - one_m31,
+                    is_copy,
+                ];
+                call_lookup_elements.combine(&tuple)
+            };
+
+            let is_real = one - PackedSecureField::from(is_padding.data[vec_idx]);
+            let is_last = PackedSecureField::from(is_last.data[vec_idx]);
+            let numerator = (is_real - is_last) * cursor - is_real * next_cursor;
+            logup_col_gen.write_frac(vec_idx, numerator, cursor * next_cursor);
+        }
+        logup_col_gen.finalize_col();
+
+        logup_gen.finalize_last()
+    }
+}
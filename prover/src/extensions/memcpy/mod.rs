@@ -0,0 +1,100 @@
+//! Components proving the `memcpy` and `memset` custom instructions.
+//!
+//! A single call accesses a number of bytes only known at runtime, [`MemcpyMemoryCheck`] therefore spends a row on
+//! every byte rather than on every call.
+
+pub(crate) mod memory_check;
+
+pub(crate) use memory_check::MemcpyMemoryCheck;
+
+use super::ExtensionComponent;
+
+pub const fn memcpy_extensions() -> &'static [ExtensionComponent] {
+    // A constant rather than a promoted temporary: the enum has variants with destructors.
+    const EXTENSIONS: &[ExtensionComponent] =
+        &[ExtensionComponent::MemcpyMemoryCheck(MemcpyMemoryCheck {
+            _private: (),
+        })];
+    EXTENSIONS
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        config::DEFAULT_MIN_SECURITY_BITS,
+        machine::{BaseComponent, Machine},
+    };
+    use nexus_vm::{
+        riscv::{BasicBlock, BuiltinOpcode, Instruction, Opcode},
+        trace::k_trace_direct,
+    };
+
+    use super::memcpy_extensions;
+
+    #[test]
+    fn prove_execution_with_memcpy() {
+        let mut instructions = vec![
+            // Create usable addresses, x2 = 0x80008 and x3 = 0x80040
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::ADDI), 1, 0, 1),
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::SLLI), 1, 1, 19),
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::ADDI), 2, 1, 8),
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::ADDI), 3, 1, 64),
+            // x4 = 0x1ab
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::ADDI), 4, 0, 0x1ab),
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::SW), 2, 4, 4),
+        ];
+        // memset x2, x4, 13
+        instructions.push(Instruction::new_ir(
+            Opcode::new(0b1011010, Some(0b101), None, "memset"),
+            2,
+            4,
+            13,
+        ));
+        // memcpy x3, x2, 21
+        instructions.extend(vec![
+            Instruction::new_ir(
+                Opcode::new(0b1011010, Some(0b100), None, "memcpy"),
+                3,
+                2,
+                21,
+            );
+            5
+        ]);
+        // memcpy x2, x1, 11 with overlapping regions
+        instructions.push(Instruction::new_ir(
+            Opcode::new(0b1011010, Some(0b100), None, "memcpy"),
+            2,
+            1,
+            11,
+        ));
+        // reading copied bytes with regular loads
+        instructions.push(Instruction::new_ir(
+            Opcode::from(BuiltinOpcode::LW),
+            5,
+            3,
+            4,
+        ));
+
+        let basic_block = vec![BasicBlock::new(instructions)];
+        let (view, program_trace) =
+            k_trace_direct(&basic_block, 1).expect("error generating trace");
+
+        let proof = Machine::<BaseComponent>::prove_with_extensions(
+            memcpy_extensions(),
+            &program_trace,
+            &view,
+        )
+        .unwrap();
+        Machine::<BaseComponent>::verify_with_extensions(
+            memcpy_extensions(),
+            DEFAULT_MIN_SECURITY_BITS,
+            proof,
+            view.get_program_memory(),
+            &[],
+            view.get_initial_memory(),
+            view.get_exit_code(),
+            view.get_public_output(),
+        )
+        .unwrap();
+    }
+}
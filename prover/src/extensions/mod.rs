@@ -5,16 +5,21 @@
 //! each component can have a smaller log size or higher constraint degree bound. Each component is expected to emit
 //! a logup sum that matches with the one from the main trace, enforcing the total sum to equal to zero.
 //!
-//! There's no support for external our-of-crate extensions, mainly precompiles, yet. All components are considered
-//! to be built-in.
-//!
 //! To define a new built-in component, a struct implementing [`BuiltInExtension`] must be added to [`ExtensionComponent`]
-//! enum.
+//! enum. Out-of-crate extensions, mainly precompiles, implement [`ExternalExtension`] instead and are wrapped with
+//! [`ExtensionComponent::external`].
 //!
 //! Some components must always be present, for example [`final_reg::FinalReg`]. They should only be accessible within
 //! the crate to avoid misuse.
 
+use external::ExternalComponent;
+use nexus_common::constants::{
+    KECCAKF_FN3, KECCAKF_OPCODE, MEMCPY_FN3, MEMSET_FN3, MUL_ADD_MOD_FN3, POSEIDON2_FN3,
+    SHA256_COMPRESS_FN3,
+};
 use ram_init_final::RamInitFinal;
+pub(crate) use ram_init_final::{proven_output_entries, proven_output_sum};
+use serde::{Deserialize, Serialize};
 use stwo_prover::{
     constraint_framework::{
         FrameworkComponent, FrameworkEval, InfoEvaluator, TraceLocationAllocator,
@@ -22,6 +27,7 @@ use stwo_prover::{
     core::{
         air::{Component, ComponentProver},
         backend::simd::SimdBackend,
+        channel::Channel,
         fields::{m31::BaseField, qm31::SecureField},
         pcs::TreeVec,
         poly::{circle::CircleEvaluation, BitReversedOrder},
@@ -31,10 +37,12 @@ use stwo_prover::{
 
 use crate::{
     components::AllLookupElements,
+    debug::{DebugReport, InteractionTrace},
     trace::{program_trace::ProgramTraceRef, sidenote::SideNote},
 };
 
 pub(crate) mod bit_op;
+pub mod external;
 pub(crate) mod final_reg;
 
 mod multiplicity;
@@ -47,18 +55,31 @@ mod config;
 #[doc(hidden)]
 pub use config::ExtensionsConfig;
 
+pub(crate) mod bigint;
 pub(crate) mod keccak;
+pub(crate) mod memcpy;
+pub(crate) mod poseidon2;
+pub(crate) mod sha256;
 
 pub(crate) use trace::ComponentTrace;
 
+pub use external::{
+    ExternalExtension, ExternalInstructionLookupElements, ExternalLookupElements,
+    ExternalMemoryAccess, ExternalStep, MemoryLookupElements,
+};
+
 use bit_op::BitOpMultiplicity;
 use final_reg::FinalReg;
 use multiplicity::{Multiplicity128, Multiplicity16, Multiplicity256, Multiplicity32};
 use multiplicity8::Multiplicity8;
 
+use bigint::MulAddModMemoryCheck;
 use keccak::{
     bit_rotate::BitRotateTable, BitNotAndTable, KeccakRound, PermutationMemoryCheck, XorTable,
 };
+use memcpy::MemcpyMemoryCheck;
+use poseidon2::Poseidon2Permutation;
+use sha256::{Sha256MemoryCheck, Sha256Round};
 
 trait FrameworkEvalExt: FrameworkEval + Sync + 'static {
     fn new(log_size: u32, lookup_elements: &AllLookupElements) -> Self;
@@ -119,6 +140,22 @@ trait BuiltInExtension {
         ))
     }
 
+    fn debug_constraints(
+        &self,
+        name: &str,
+        component_trace: &ComponentTrace,
+        interaction_trace: &InteractionTrace,
+        lookup_elements: &AllLookupElements,
+        report: &mut DebugReport,
+    ) {
+        report.check_component(
+            name,
+            &Self::Eval::new(component_trace.log_size, lookup_elements),
+            component_trace,
+            interaction_trace,
+        );
+    }
+
     fn compute_log_size(&self, side_note: &SideNote) -> u32;
 
     fn trace_sizes(&self, log_size: u32) -> TreeVec<Vec<u32>> {
@@ -148,6 +185,11 @@ extension_dispatch! {
         BitRotateTable,
         KeccakRound,
         PermutationMemoryCheck,
+        Sha256MemoryCheck,
+        Sha256Round,
+        MulAddModMemoryCheck,
+        Poseidon2Permutation,
+        MemcpyMemoryCheck,
     }
 }
 
@@ -180,19 +222,129 @@ impl ExtensionComponent {
     pub const fn keccak_extensions() -> &'static [Self] {
         keccak::keccak_extensions()
     }
+
+    pub const fn sha256_extensions() -> &'static [Self] {
+        sha256::sha256_extensions()
+    }
+
+    pub const fn bigint_extensions() -> &'static [Self] {
+        bigint::bigint_extensions()
+    }
+
+    pub const fn poseidon2_extensions() -> &'static [Self] {
+        poseidon2::poseidon2_extensions()
+    }
+
+    pub const fn memcpy_extensions() -> &'static [Self] {
+        memcpy::memcpy_extensions()
+    }
+
+    pub(crate) fn draw_lookup_elements(
+        &self,
+        lookup_elements: &mut AllLookupElements,
+        channel: &mut impl Channel,
+    ) {
+        if let Self::External(inner) = self {
+            inner.draw_lookup_elements(lookup_elements, channel);
+        }
+    }
+}
+
+/// Serializable identifier of a group of extension components that is enabled together.
+///
+/// Unlike [`ExtensionComponent`], which describes a single component, this type is meant to be stored
+/// alongside a proof, so that the verifier reconstructs the exact same set of components.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Extension {
+    /// `keccakf` custom instruction.
+    Keccak,
+    /// `sha256_compress` custom instruction.
+    Sha256,
+    /// `mul_add_mod` custom instruction.
+    BigInt,
+    /// `poseidon2` custom instruction.
+    Poseidon2,
+    /// `memcpy` and `memset` custom instructions.
+    Memcpy,
+}
+
+impl Extension {
+    /// Returns the components required by this extension.
+    pub const fn components(self) -> &'static [ExtensionComponent] {
+        match self {
+            Self::Keccak => ExtensionComponent::keccak_extensions(),
+            Self::Sha256 => ExtensionComponent::sha256_extensions(),
+            Self::BigInt => ExtensionComponent::bigint_extensions(),
+            Self::Poseidon2 => ExtensionComponent::poseidon2_extensions(),
+            Self::Memcpy => ExtensionComponent::memcpy_extensions(),
+        }
+    }
+
+    /// Returns the extension proving the built-in custom instruction with the given opcode and fn3, if any.
+    ///
+    /// This is the single table of built-in custom instructions, shared by their chips in the main component and by
+    /// the cost estimate.
+    pub(crate) fn of_opcode(opcode: u8, fn3: u8) -> Option<Self> {
+        if opcode != KECCAKF_OPCODE {
+            return None;
+        }
+        match fn3 {
+            KECCAKF_FN3 => Some(Self::Keccak),
+            SHA256_COMPRESS_FN3 => Some(Self::Sha256),
+            MUL_ADD_MOD_FN3 => Some(Self::BigInt),
+            POSEIDON2_FN3 => Some(Self::Poseidon2),
+            MEMCPY_FN3 | MEMSET_FN3 => Some(Self::Memcpy),
+            _ => None,
+        }
+    }
+
+    /// Returns whether the encoded instruction requires this extension to be proven.
+    pub fn is_required_by(self, instruction: u32) -> bool {
+        let opcode = (instruction & 0x7f) as u8;
+        let fn3 = ((instruction >> 12) & 0b111) as u8;
+        Self::of_opcode(opcode, fn3) == Some(self)
+    }
+
+    /// Detects the extensions needed to prove a program from its encoded instructions.
+    pub fn detect(instructions: &[u32]) -> Vec<Self> {
+        [
+            Self::Keccak,
+            Self::Sha256,
+            Self::BigInt,
+            Self::Poseidon2,
+            Self::Memcpy,
+        ]
+        .into_iter()
+        .filter(|ext| instructions.iter().any(|&insn| ext.is_required_by(insn)))
+        .collect()
+    }
+
+    /// Flattens a list of extensions into the list of their components.
+    pub fn to_components(extensions: &[Self]) -> Vec<ExtensionComponent> {
+        let mut components: Vec<ExtensionComponent> = Vec::new();
+        for ext in extensions {
+            for component in ext.components() {
+                if !components.contains(component) {
+                    components.push(component.clone());
+                }
+            }
+        }
+        components
+    }
 }
 
 // A macro mimicking enum_dispatch, but with less flexibility and therefore without shared state managing.
 //
 // To avoid repetitive implementations of components, the main trait [`BuiltInExtension`] features associated
-// type with bound which makes it non object safe, or non dyn-compatible. External precompiles, once introduced,
-// will require type-erased version of this trait since the prover crate cannot know details of implementation.
-// Such precompiles can be implemented as a separate `Custom(Box<dyn ...>)` variant.
+// type with bound which makes it non object safe, or non dyn-compatible. External precompiles use a type-erased
+// version of this trait since the prover crate cannot know details of implementation, and are dispatched through
+// a separate `External` variant.
 macro_rules! extension_dispatch {
     ($vis:vis enum $_enum:ident { $( $name:ident ),* $(,)? }) => {
         #[derive(Debug, Clone, PartialEq, Eq, Hash)]
         $vis enum $_enum {
             $($name($name),)*
+            External(ExternalComponent),
         }
 
         $(
@@ -206,6 +358,14 @@ macro_rules! extension_dispatch {
         impl $_enum {
             #![allow(unused)]
 
+            /// Returns the name of the component, as used in reports.
+            pub(crate) fn name(&self) -> &str {
+                match self {
+                    $( $_enum::$name(_) => stringify!($name), )*
+                    $_enum::External(inner) => inner.name(),
+                }
+            }
+
             pub(crate) fn generate_preprocessed_trace(
                 &self,
                 log_size: u32,
@@ -213,6 +373,7 @@ macro_rules! extension_dispatch {
             ) -> ColumnVec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>> {
                 match self {
                     $( $_enum::$name(inner) => <$name as BuiltInExtension>::generate_preprocessed_trace(inner, log_size, program_trace_ref), )*
+                    $_enum::External(inner) => inner.generate_preprocessed_trace(log_size, program_trace_ref),
                 }
             }
 
@@ -224,6 +385,7 @@ macro_rules! extension_dispatch {
             ) -> ComponentTrace {
                 match self {
                     $( $_enum::$name(inner) => <$name as BuiltInExtension>::generate_component_trace(inner, log_size, program_trace_ref, side_note), )*
+                    $_enum::External(inner) => inner.generate_component_trace(log_size, program_trace_ref, &side_note.external_steps),
                 }
             }
 
@@ -238,6 +400,7 @@ macro_rules! extension_dispatch {
             ) {
                 match self {
                     $( $_enum::$name(inner) => <$name as BuiltInExtension>::generate_interaction_trace(inner, component_trace, side_note, lookup_elements), )*
+                    $_enum::External(inner) => inner.generate_interaction_trace(component_trace, lookup_elements),
                 }
             }
 
@@ -250,6 +413,7 @@ macro_rules! extension_dispatch {
             ) -> Box<dyn ComponentProver<SimdBackend>> {
                 match self {
                     $( $_enum::$name(inner) => <$name as BuiltInExtension>::to_component_prover(inner, tree_span_provider, lookup_elements, log_size, claimed_sum), )*
+                    $_enum::External(inner) => inner.to_component_prover(tree_span_provider, lookup_elements, log_size, claimed_sum),
                 }
             }
 
@@ -262,27 +426,103 @@ macro_rules! extension_dispatch {
             ) -> Box<dyn Component> {
                 match self {
                     $( $_enum::$name(inner) => <$name as BuiltInExtension>::to_component(inner, tree_span_provider, lookup_elements, log_size, claimed_sum), )*
+                    $_enum::External(inner) => inner.to_component(tree_span_provider, lookup_elements, log_size, claimed_sum),
+                }
+            }
+
+            pub(crate) fn debug_constraints(
+                &self,
+                component_trace: &ComponentTrace,
+                interaction_trace: &InteractionTrace,
+                lookup_elements: &AllLookupElements,
+                report: &mut DebugReport,
+            ) {
+                match self {
+                    $( $_enum::$name(inner) => <$name as BuiltInExtension>::debug_constraints(inner, stringify!($name), component_trace, interaction_trace, lookup_elements, report), )*
+                    $_enum::External(inner) => inner.debug_constraints(component_trace, interaction_trace, lookup_elements, report),
                 }
             }
 
             pub(crate) fn compute_log_size(&self, side_note: &SideNote) -> u32 {
                 match self {
                     $( $_enum::$name(inner) => <$name as BuiltInExtension>::compute_log_size(inner, side_note), )*
+                    $_enum::External(inner) => inner.compute_log_size(&side_note.external_steps),
                 }
             }
 
             pub(crate) fn trace_sizes(&self, log_size: u32) -> TreeVec<Vec<u32>> {
                 match self {
                     $( $_enum::$name(inner) => <$name as BuiltInExtension>::trace_sizes(inner, log_size), )*
+                    $_enum::External(inner) => inner.trace_sizes(log_size),
                 }
             }
 
             pub(crate) fn preprocessed_trace_sizes(&self, log_size: u32) -> Vec<u32> {
                 match self {
                     $( $_enum::$name(inner) => <$name as BuiltInExtension>::preprocessed_trace_sizes(log_size), )*
+                    $_enum::External(inner) => inner.preprocessed_trace_sizes(log_size),
                 }
             }
         }
     };
 }
 pub(self) use extension_dispatch;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_extensions() {
+        // addi x1, x0, 1
+        let addi = 0x00100093;
+        // keccakf x10
+        let keccakf = (10 << 15) | KECCAKF_OPCODE as u32;
+        // sha256_compress x10, x11
+        let sha256 =
+            (11 << 20) | (10 << 15) | ((SHA256_COMPRESS_FN3 as u32) << 12) | KECCAKF_OPCODE as u32;
+        // mul_add_mod x10, x11
+        let mul_add_mod =
+            (11 << 20) | (10 << 15) | ((MUL_ADD_MOD_FN3 as u32) << 12) | KECCAKF_OPCODE as u32;
+        // poseidon2 x10
+        let poseidon2 = (10 << 15) | ((POSEIDON2_FN3 as u32) << 12) | KECCAKF_OPCODE as u32;
+        // memcpy x10, x11, 4 and memset x10, x11, 4
+        let memcpy = (11 << 20)
+            | (10 << 15)
+            | ((MEMCPY_FN3 as u32) << 12)
+            | (4 << 7)
+            | KECCAKF_OPCODE as u32;
+        let memset = (11 << 20)
+            | (10 << 15)
+            | ((MEMSET_FN3 as u32) << 12)
+            | (4 << 7)
+            | KECCAKF_OPCODE as u32;
+        // same opcode with an unassigned fn3 is not a built-in call
+        let other = (0b111 << 12) | KECCAKF_OPCODE as u32;
+
+        assert!(Extension::detect(&[addi, other]).is_empty());
+        assert_eq!(Extension::detect(&[addi, keccakf]), vec![Extension::Keccak]);
+        assert_eq!(Extension::detect(&[sha256, addi]), vec![Extension::Sha256]);
+        assert_eq!(
+            Extension::detect(&[sha256, keccakf]),
+            vec![Extension::Keccak, Extension::Sha256]
+        );
+        assert_eq!(
+            Extension::detect(&[mul_add_mod, sha256]),
+            vec![Extension::Sha256, Extension::BigInt]
+        );
+        assert_eq!(
+            Extension::detect(&[poseidon2, addi]),
+            vec![Extension::Poseidon2]
+        );
+        assert_eq!(Extension::detect(&[memcpy]), vec![Extension::Memcpy]);
+        assert_eq!(
+            Extension::detect(&[memset, poseidon2]),
+            vec![Extension::Poseidon2, Extension::Memcpy]
+        );
+        assert_eq!(
+            Extension::to_components(&[Extension::Keccak, Extension::Keccak]),
+            ExtensionComponent::keccak_extensions()
+        );
+    }
+}
@@ -0,0 +1,75 @@
+//! Components proving the `poseidon2` custom instruction.
+//!
+//! Poseidon2 is defined over the same field the prover works over, [`Poseidon2Permutation`] applies all rounds of
+//! the permutation within a single row, alongside checking memory accesses of the call.
+
+pub(crate) mod permutation;
+
+pub(crate) use permutation::Poseidon2Permutation;
+
+use super::ExtensionComponent;
+
+pub const fn poseidon2_extensions() -> &'static [ExtensionComponent] {
+    // A constant rather than a promoted temporary: the enum has variants with destructors.
+    const EXTENSIONS: &[ExtensionComponent] = &[ExtensionComponent::Poseidon2Permutation(
+        Poseidon2Permutation { _private: () },
+    )];
+    EXTENSIONS
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        config::DEFAULT_MIN_SECURITY_BITS,
+        machine::{BaseComponent, Machine},
+    };
+    use nexus_vm::{
+        riscv::{BasicBlock, BuiltinOpcode, Instruction, Opcode},
+        trace::k_trace_direct,
+    };
+
+    use super::poseidon2_extensions;
+
+    #[test]
+    fn prove_execution_with_poseidon2() {
+        let mut instructions = vec![
+            // Create a usable address for the state, x2 = 0x81008
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::ADDI), 1, 0, 1),
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::SLLI), 1, 1, 19),
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::ADD), 2, 1, 2),
+            // Non-canonical input, x3 = 2^32 - 1
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::ADDI), 3, 0, 1),
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::SUB), 3, 0, 3),
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::SW), 2, 3, 4),
+        ];
+        let poseidon2_inst = Instruction::new_ir(
+            Opcode::new(0b1011010, Some(0b011), None, "poseidon2"),
+            2,
+            0,
+            0,
+        );
+        instructions.extend(vec![poseidon2_inst; 20]);
+
+        let basic_block = vec![BasicBlock::new(instructions)];
+        let (view, program_trace) =
+            k_trace_direct(&basic_block, 1).expect("error generating trace");
+
+        let proof = Machine::<BaseComponent>::prove_with_extensions(
+            poseidon2_extensions(),
+            &program_trace,
+            &view,
+        )
+        .unwrap();
+        Machine::<BaseComponent>::verify_with_extensions(
+            poseidon2_extensions(),
+            DEFAULT_MIN_SECURITY_BITS,
+            proof,
+            view.get_program_memory(),
+            &[],
+            view.get_initial_memory(),
+            view.get_exit_code(),
+            view.get_public_output(),
+        )
+        .unwrap();
+    }
+}
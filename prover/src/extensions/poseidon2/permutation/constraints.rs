@@ -0,0 +1,174 @@
+use nexus_common::constants::WORD_SIZE_HALVED;
+use nexus_vm::cpu::instructions::custom::poseidon2::{
+    EXTERNAL_ROUND_CONSTANTS, FULL_ROUNDS, INTERNAL_ROUND_CONSTANTS, POSEIDON2_WIDTH,
+};
+use num_traits::{One, Zero};
+use stwo_prover::{
+    constraint_framework::{EvalAtRow, RelationEntry},
+    core::fields::m31::BaseField,
+};
+
+use super::{apply_external_matrix, apply_internal_matrix, pow5};
+use crate::components::lookups::LoadStoreLookupElements;
+
+pub struct Poseidon2PermutationEval<'a, E> {
+    pub(crate) eval: E,
+    pub(crate) memory_lookup_elements: &'a LoadStoreLookupElements,
+}
+
+impl<E: EvalAtRow> Poseidon2PermutationEval<'_, E> {
+    const NUM_BYTES: usize = super::Poseidon2PermutationEval::NUM_BYTES;
+    const OUTPUT_BITS: usize = super::Poseidon2PermutationEval::OUTPUT_BITS;
+
+    pub fn eval(mut self) -> E {
+        let input_bytes = self.next_columns(Self::NUM_BYTES);
+        let mut state: [E::F; POSEIDON2_WIDTH] = std::array::from_fn(|i| {
+            input_bytes[i * 4..(i + 1) * 4]
+                .iter()
+                .rev()
+                .fold(E::F::zero(), |acc, byte| {
+                    acc * BaseField::from(1 << 8) + byte.clone()
+                })
+        });
+
+        apply_external_matrix(&mut state);
+        for constants in &EXTERNAL_ROUND_CONSTANTS[..FULL_ROUNDS / 2] {
+            self.full_round(&mut state, constants);
+        }
+        for &c in &INTERNAL_ROUND_CONSTANTS {
+            state[0] = self.s_box(state[0].clone(), c);
+            apply_internal_matrix(&mut state);
+        }
+        for constants in &EXTERNAL_ROUND_CONSTANTS[FULL_ROUNDS / 2..] {
+            self.full_round(&mut state, constants);
+        }
+
+        let output_bits = self.next_columns(POSEIDON2_WIDTH * Self::OUTPUT_BITS);
+        let inverses = self.next_columns(POSEIDON2_WIDTH);
+        let addrs = self.next_columns(Self::NUM_BYTES * WORD_SIZE_HALVED);
+        let prev_ts = self.next_columns(Self::NUM_BYTES * WORD_SIZE_HALVED);
+        let next_ts = self.next_columns(Self::NUM_BYTES * WORD_SIZE_HALVED);
+        let addr_carries = self.next_columns(Self::NUM_BYTES);
+        let ts_carries = self.next_columns(Self::NUM_BYTES);
+
+        let is_padding = self.eval.next_trace_mask();
+        self.eval
+            .add_constraint(is_padding.clone() * (E::F::one() - is_padding.clone()));
+        let is_real = E::F::one() - is_padding;
+
+        for bit in output_bits.iter().chain(&addr_carries).chain(&ts_carries) {
+            self.eval
+                .add_constraint(bit.clone() * (E::F::one() - bit.clone()));
+        }
+
+        // Outputs are canonical: bits of each element aren't all ones.
+        for ((out, bits), inv) in state
+            .iter()
+            .zip(output_bits.chunks_exact(Self::OUTPUT_BITS))
+            .zip(&inverses)
+        {
+            let (low_bits, high_bits) = bits.split_at(16);
+            let low = Self::from_bits(low_bits);
+            let high = Self::from_bits(high_bits);
+            self.eval.add_constraint(
+                low.clone() + high.clone() * BaseField::from(1 << 16) - out.clone(),
+            );
+
+            let distance = E::F::from(BaseField::from((1 << 16) - 1)) - low
+                + E::F::from(BaseField::from((1 << 15) - 1))
+                - high;
+            self.eval
+                .add_constraint(distance * inv.clone() - E::F::one());
+        }
+        let output_bytes: Vec<E::F> = output_bits
+            .chunks_exact(Self::OUTPUT_BITS)
+            .flat_map(|bits| bits.chunks(8).map(Self::from_bits).collect::<Vec<_>>())
+            .collect();
+
+        // addresses and timestamps of consecutive bytes
+        for (i, carry) in addr_carries.iter().enumerate().take(Self::NUM_BYTES - 1) {
+            let j = i * WORD_SIZE_HALVED;
+            let addr = &addrs[j..j + WORD_SIZE_HALVED];
+            let next_addr = &addrs[j + WORD_SIZE_HALVED..j + WORD_SIZE_HALVED * 2];
+            self.constrain_increment(&is_real, addr, next_addr, carry);
+        }
+        for (i, (prev_ts, next_ts)) in prev_ts
+            .chunks_exact(WORD_SIZE_HALVED)
+            .zip(next_ts.chunks_exact(WORD_SIZE_HALVED))
+            .enumerate()
+        {
+            self.constrain_increment(&is_real, prev_ts, next_ts, &ts_carries[i]);
+        }
+
+        for (i, (prev_val, next_val)) in input_bytes.iter().zip(&output_bytes).enumerate() {
+            let j = i * WORD_SIZE_HALVED;
+            // (addr, val, ts)
+            let sub_access = [
+                &addrs[j..j + WORD_SIZE_HALVED],
+                std::slice::from_ref(prev_val),
+                &prev_ts[j..j + WORD_SIZE_HALVED],
+            ]
+            .concat();
+            let add_access = [
+                &addrs[j..j + WORD_SIZE_HALVED],
+                std::slice::from_ref(next_val),
+                &next_ts[j..j + WORD_SIZE_HALVED],
+            ]
+            .concat();
+
+            self.eval.add_to_relation(RelationEntry::new(
+                self.memory_lookup_elements,
+                (-is_real.clone()).into(),
+                &sub_access,
+            ));
+            self.eval.add_to_relation(RelationEntry::new(
+                self.memory_lookup_elements,
+                is_real.clone().into(),
+                &add_access,
+            ));
+        }
+
+        self.eval.finalize_logup_in_pairs();
+
+        self.eval
+    }
+
+    /// Constrains the next column to hold `(x + c)^5` and returns it.
+    fn s_box(&mut self, x: E::F, c: u32) -> E::F {
+        let out = self.eval.next_trace_mask();
+        let x = x + E::F::from(BaseField::from(c));
+        self.eval.add_constraint(out.clone() - pow5(x));
+        out
+    }
+
+    fn full_round(&mut self, state: &mut [E::F; POSEIDON2_WIDTH], constants: &[u32]) {
+        for (x, &c) in state.iter_mut().zip(constants) {
+            *x = self.s_box(x.clone(), c);
+        }
+        apply_external_matrix(state);
+    }
+
+    fn next_columns(&mut self, size: usize) -> Vec<E::F> {
+        std::iter::repeat_with(|| self.eval.next_trace_mask())
+            .take(size)
+            .collect()
+    }
+
+    fn from_bits(bits: &[E::F]) -> E::F {
+        bits.iter().enumerate().fold(E::F::zero(), |acc, (i, bit)| {
+            acc + bit.clone() * BaseField::from(1 << i)
+        })
+    }
+
+    /// Constrains a 32-bit value split into halves to be incremented by one.
+    fn constrain_increment(&mut self, is_real: &E::F, prev: &[E::F], next: &[E::F], carry: &E::F) {
+        self.eval.add_constraint(
+            is_real.clone()
+                * (next[0].clone() + carry.clone() * E::F::from((1 << 16).into())
+                    - prev[0].clone()
+                    - E::F::one()),
+        );
+        self.eval
+            .add_constraint(is_real.clone() * (next[1].clone() - prev[1].clone() - carry.clone()));
+    }
+}
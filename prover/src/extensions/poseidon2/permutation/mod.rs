@@ -0,0 +1,181 @@
+use std::ops::{Add, Mul};
+
+use nexus_vm::cpu::instructions::custom::poseidon2::POSEIDON2_WIDTH;
+use stwo_prover::{
+    constraint_framework::{EvalAtRow, FrameworkEval},
+    core::{
+        backend::simd::{m31::LOG_N_LANES, SimdBackend},
+        fields::{m31::BaseField, qm31::SecureField},
+        poly::{circle::CircleEvaluation, BitReversedOrder},
+        ColumnVec,
+    },
+};
+
+use crate::{
+    components::{lookups::LoadStoreLookupElements, AllLookupElements, LOG_CONSTRAINT_DEGREE},
+    extensions::{BuiltInExtension, ComponentTrace, FrameworkEvalExt},
+    trace::{program_trace::ProgramTraceRef, sidenote::SideNote},
+};
+
+mod constraints;
+mod trace;
+
+/// Applies the Poseidon2 permutation to the state accessed by `poseidon2`, one call per row.
+///
+/// Every row reads sixteen words from memory, each one interpreted as a field element, stores the output of every
+/// S-box in a column and writes canonical output elements back to memory. Outputs are decomposed into 31 bits and
+/// checked to differ from 2^31 - 1.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Poseidon2Permutation {
+    pub(crate) _private: (),
+}
+
+pub(crate) struct Poseidon2PermutationEval {
+    log_size: u32,
+    memory_lookup_elements: LoadStoreLookupElements,
+}
+
+impl Poseidon2PermutationEval {
+    const NUM_BYTES: usize = POSEIDON2_WIDTH * 4;
+    const OUTPUT_BITS: usize = 31;
+}
+
+/// Multiplies four elements by the matrix `[[5, 7, 1, 3], [4, 6, 1, 1], [1, 3, 5, 7], [1, 1, 4, 6]]`.
+fn apply_m4<F: Clone + Add<Output = F>>(x: [F; 4]) -> [F; 4] {
+    let [x0, x1, x2, x3] = x;
+    let t0 = x0 + x1.clone();
+    let t02 = t0.clone() + t0.clone();
+    let t1 = x2 + x3.clone();
+    let t12 = t1.clone() + t1.clone();
+    let t2 = x1.clone() + x1 + t1;
+    let t3 = x3.clone() + x3 + t0;
+    let t4 = t12.clone() + t12 + t3.clone();
+    let t5 = t02.clone() + t02 + t2.clone();
+    let t6 = t3 + t5.clone();
+    let t7 = t2 + t4.clone();
+    [t6, t5, t7, t4]
+}
+
+/// Same as the external matrix of the emulator, generic over trace values and constraint expressions.
+pub(super) fn apply_external_matrix<F: Clone + Add<Output = F>>(state: &mut [F; POSEIDON2_WIDTH]) {
+    for chunk in state.chunks_exact_mut(4) {
+        let x = apply_m4([
+            chunk[0].clone(),
+            chunk[1].clone(),
+            chunk[2].clone(),
+            chunk[3].clone(),
+        ]);
+        chunk.clone_from_slice(&x);
+    }
+    for j in 0..4 {
+        let sum =
+            state[j].clone() + state[j + 4].clone() + state[j + 8].clone() + state[j + 12].clone();
+        for i in 0..4 {
+            state[4 * i + j] = state[4 * i + j].clone() + sum.clone();
+        }
+    }
+}
+
+/// Same as the internal matrix of the emulator, generic over trace values and constraint expressions.
+pub(super) fn apply_internal_matrix<F>(state: &mut [F; POSEIDON2_WIDTH])
+where
+    F: Clone + Add<Output = F> + Mul<BaseField, Output = F>,
+{
+    let sum = state[1..]
+        .iter()
+        .fold(state[0].clone(), |acc, x| acc + x.clone());
+    for (i, x) in state.iter_mut().enumerate() {
+        *x = x.clone() * BaseField::from(1 << (i + 1)) + sum.clone();
+    }
+}
+
+pub(super) fn pow5<F: Clone + Mul<Output = F>>(x: F) -> F {
+    let x2 = x.clone() * x.clone();
+    let x4 = x2.clone() * x2;
+    x4 * x
+}
+
+impl FrameworkEval for Poseidon2PermutationEval {
+    fn log_size(&self) -> u32 {
+        self.log_size
+    }
+
+    fn max_constraint_log_degree_bound(&self) -> u32 {
+        // S-boxes are constrained with degree 5.
+        self.log_size + LOG_CONSTRAINT_DEGREE
+    }
+
+    fn evaluate<E: EvalAtRow>(&self, eval: E) -> E {
+        constraints::Poseidon2PermutationEval {
+            eval,
+            memory_lookup_elements: &self.memory_lookup_elements,
+        }
+        .eval()
+    }
+}
+
+impl FrameworkEvalExt for Poseidon2PermutationEval {
+    fn new(log_size: u32, lookup_elements: &AllLookupElements) -> Self {
+        let memory_lookup_elements: &LoadStoreLookupElements = lookup_elements.as_ref();
+        Self {
+            log_size,
+            memory_lookup_elements: memory_lookup_elements.clone(),
+        }
+    }
+
+    fn dummy(log_size: u32) -> Self {
+        Self {
+            log_size,
+            memory_lookup_elements: LoadStoreLookupElements::dummy(),
+        }
+    }
+}
+
+impl BuiltInExtension for Poseidon2Permutation {
+    type Eval = Poseidon2PermutationEval;
+
+    fn generate_preprocessed_trace(
+        &self,
+        _log_size: u32,
+        _program_trace_ref: ProgramTraceRef,
+    ) -> ColumnVec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>> {
+        vec![]
+    }
+
+    fn generate_component_trace(
+        &self,
+        log_size: u32,
+        _program_trace_ref: ProgramTraceRef,
+        side_note: &mut SideNote,
+    ) -> ComponentTrace {
+        trace::generate_poseidon2_trace(log_size, side_note)
+    }
+
+    fn generate_interaction_trace(
+        &self,
+        component_trace: ComponentTrace,
+        side_note: &SideNote,
+        lookup_elements: &AllLookupElements,
+    ) -> (
+        ColumnVec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>>,
+        SecureField,
+    ) {
+        let memory_lookup_elements: &LoadStoreLookupElements = lookup_elements.as_ref();
+        trace::MemoryCheckLogUpGenerator {
+            component_trace: &component_trace,
+            inputs: &side_note.poseidon2.inputs,
+        }
+        .interaction_trace(memory_lookup_elements)
+    }
+
+    fn compute_log_size(&self, side_note: &SideNote) -> u32 {
+        let num_inputs = side_note.poseidon2.inputs.len();
+        let log_size = num_inputs.next_power_of_two().ilog2();
+
+        log_size.max(LOG_N_LANES)
+    }
+
+    fn preprocessed_trace_sizes(_log_size: u32) -> Vec<u32> {
+        vec![]
+    }
+}
@@ -0,0 +1,233 @@
+use nexus_common::constants::WORD_SIZE_HALVED;
+use nexus_vm::cpu::instructions::custom::poseidon2::{
+    poseidon2_permute, EXTERNAL_ROUND_CONSTANTS, FULL_ROUNDS, INTERNAL_ROUND_CONSTANTS,
+    M31_MODULUS, PARTIAL_ROUNDS, POSEIDON2_WIDTH,
+};
+use num_traits::{One, Zero};
+use stwo_prover::{
+    constraint_framework::{logup::LogupTraceGenerator, Relation},
+    core::{
+        backend::simd::{
+            column::BaseColumn,
+            m31::{PackedM31, LOG_N_LANES},
+            qm31::PackedSecureField,
+            SimdBackend,
+        },
+        fields::{m31::BaseField, qm31::SecureField, FieldExpOps},
+        poly::{circle::CircleEvaluation, BitReversedOrder},
+        ColumnVec,
+    },
+};
+
+use super::{apply_external_matrix, apply_internal_matrix, pow5, Poseidon2PermutationEval};
+use crate::{
+    components::lookups::LoadStoreLookupElements,
+    extensions::{keccak::round::trace::get_is_padding_base_column, ComponentTrace},
+    trace::sidenote::SideNote,
+};
+
+const MASK: u32 = (1 << 16) - 1;
+
+/// Returns the canonical output of the permutation applied to words stored in memory.
+fn permute(input: &[u32; POSEIDON2_WIDTH]) -> [u32; POSEIDON2_WIDTH] {
+    let mut output = input.map(|word| word % M31_MODULUS);
+    poseidon2_permute(&mut output);
+    output
+}
+
+/// Returns values of the columns applying the permutation, without memory addresses and timestamps.
+fn permutation_values(input: &[u32; POSEIDON2_WIDTH]) -> Vec<BaseField> {
+    let mut values: Vec<BaseField> = input
+        .iter()
+        .flat_map(|word| word.to_le_bytes())
+        .map(|byte| BaseField::from(u32::from(byte)))
+        .collect();
+
+    let mut state = input.map(|word| BaseField::from(word % M31_MODULUS));
+    let mut s_box = |x: BaseField, c: u32| {
+        let out = pow5(x + BaseField::from(c));
+        values.push(out);
+        out
+    };
+    apply_external_matrix(&mut state);
+    for constants in &EXTERNAL_ROUND_CONSTANTS[..FULL_ROUNDS / 2] {
+        for (x, &c) in state.iter_mut().zip(constants) {
+            *x = s_box(*x, c);
+        }
+        apply_external_matrix(&mut state);
+    }
+    for &c in &INTERNAL_ROUND_CONSTANTS {
+        state[0] = s_box(state[0], c);
+        apply_internal_matrix(&mut state);
+    }
+    for constants in &EXTERNAL_ROUND_CONSTANTS[FULL_ROUNDS / 2..] {
+        for (x, &c) in state.iter_mut().zip(constants) {
+            *x = s_box(*x, c);
+        }
+        apply_external_matrix(&mut state);
+    }
+
+    let output = state.map(|x| x.0);
+    assert_eq!(output, permute(input));
+    values.extend(output.iter().flat_map(|x| {
+        (0..Poseidon2PermutationEval::OUTPUT_BITS).map(move |i| BaseField::from((x >> i) & 1))
+    }));
+    values.extend(output.iter().map(|x| {
+        let distance = (MASK - (x & MASK)) + ((1 << 15) - 1 - (x >> 16));
+        BaseField::from(distance).inverse()
+    }));
+
+    values
+}
+
+pub fn generate_poseidon2_trace(log_size: u32, side_note: &SideNote) -> ComponentTrace {
+    let num_bytes = Poseidon2PermutationEval::NUM_BYTES;
+    let output_bits = Poseidon2PermutationEval::OUTPUT_BITS;
+    // [input_bytes, s_boxes, output_bits, inverses, addresses, prev_ts, next_ts, addr_carries, ts_carries]
+    let num_cols = num_bytes
+        + FULL_ROUNDS * POSEIDON2_WIDTH
+        + PARTIAL_ROUNDS
+        + POSEIDON2_WIDTH * output_bits
+        + POSEIDON2_WIDTH
+        + num_bytes * WORD_SIZE_HALVED * 3
+        + num_bytes * 2;
+    let mut original_trace = vec![vec![BaseField::zero(); 1 << log_size]; num_cols];
+
+    let poseidon2_side_note = &side_note.poseidon2;
+    for (row, input) in poseidon2_side_note.inputs.iter().enumerate() {
+        let addr = poseidon2_side_note.addresses[row];
+        let timestamps = &poseidon2_side_note.timestamps[row];
+
+        let mut values = permutation_values(input);
+
+        let addrs: Vec<u32> = (0..num_bytes).map(|i| addr + i as u32).collect();
+        assert_eq!(timestamps.len(), addrs.len());
+        let mut memory_values: Vec<u32> = Vec::new();
+        memory_values.extend(addrs.iter().flat_map(|addr| [addr & MASK, addr >> 16]));
+        memory_values.extend(timestamps.iter().flat_map(|ts| [ts & MASK, ts >> 16]));
+        memory_values.extend(timestamps.iter().flat_map(|ts| {
+            let ts = ts + 1;
+            [ts & MASK, ts >> 16]
+        }));
+        memory_values.extend(addrs.iter().map(|addr| u32::from(addr & MASK == MASK)));
+        memory_values.extend(timestamps.iter().map(|ts| u32::from(ts & MASK == MASK)));
+        values.extend(memory_values.into_iter().map(BaseField::from));
+
+        assert_eq!(values.len(), num_cols);
+        for (col, value) in original_trace.iter_mut().zip(values) {
+            col[row] = value;
+        }
+    }
+    // Permutation constraints aren't disabled on padding, these rows permute the zero state.
+    let real_rows = poseidon2_side_note.inputs.len();
+    let padding_values = permutation_values(&[0; POSEIDON2_WIDTH]);
+    for (col, value) in original_trace.iter_mut().zip(padding_values) {
+        col[real_rows..].fill(value);
+    }
+
+    let is_padding = get_is_padding_base_column(log_size, real_rows);
+    let mut original_trace: Vec<BaseColumn> = original_trace
+        .into_iter()
+        .map(BaseColumn::from_iter)
+        .collect();
+    original_trace.push(is_padding);
+
+    ComponentTrace {
+        log_size,
+        preprocessed_trace: vec![],
+        original_trace,
+    }
+}
+
+pub(super) struct MemoryCheckLogUpGenerator<'a> {
+    pub(super) component_trace: &'a ComponentTrace,
+    pub(super) inputs: &'a [[u32; POSEIDON2_WIDTH]],
+}
+
+impl MemoryCheckLogUpGenerator<'_> {
+    pub fn interaction_trace(
+        &self,
+        memory_lookup_elements: &LoadStoreLookupElements,
+    ) -> (
+        ColumnVec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>>,
+        SecureField,
+    ) {
+        let num_bytes = Poseidon2PermutationEval::NUM_BYTES;
+        let output_bits = Poseidon2PermutationEval::OUTPUT_BITS;
+        let log_size = self.component_trace.log_size;
+        let mut logup_gen = LogupTraceGenerator::new(log_size);
+
+        let original_trace = self.component_trace.original_trace.as_slice();
+
+        let (input_bytes, rem) = original_trace.split_at(num_bytes);
+        // skip s-boxes, output bits and inverses
+        let (_, rem) = rem.split_at(
+            FULL_ROUNDS * POSEIDON2_WIDTH
+                + PARTIAL_ROUNDS
+                + POSEIDON2_WIDTH * output_bits
+                + POSEIDON2_WIDTH,
+        );
+        let (addrs, rem) = rem.split_at(num_bytes * WORD_SIZE_HALVED);
+        let (prev_ts, rem) = rem.split_at(num_bytes * WORD_SIZE_HALVED);
+        let (next_ts, rem) = rem.split_at(num_bytes * WORD_SIZE_HALVED);
+
+        // skip carries
+        let (_, rem) = rem.split_at(num_bytes * 2);
+
+        assert_eq!(rem.len(), 1);
+        let is_padding = &rem[0];
+
+        // Output bytes are recomputed rather than collected from bits, padding rows permute the zero state.
+        let mut output_bytes = vec![vec![]; num_bytes];
+        for row in 0..1 << log_size {
+            let input = self.inputs.get(row).copied().unwrap_or_default();
+            for (col, byte) in output_bytes
+                .iter_mut()
+                .zip(permute(&input).iter().flat_map(|word| word.to_le_bytes()))
+            {
+                col.push(BaseField::from(u32::from(byte)));
+            }
+        }
+        let output_bytes: Vec<BaseColumn> = output_bytes
+            .into_iter()
+            .map(BaseColumn::from_iter)
+            .collect();
+
+        let one: PackedSecureField = SecureField::one().into();
+        for i in 0..num_bytes {
+            let j = i * WORD_SIZE_HALVED;
+            let mut logup_col_gen = logup_gen.new_col();
+            for vec_idx in 0..(1 << (log_size - LOG_N_LANES)) {
+                let addr = &addrs[j..j + WORD_SIZE_HALVED];
+
+                let p0: PackedSecureField = {
+                    let prev_ts = &prev_ts[j..j + WORD_SIZE_HALVED];
+                    let tuple: Vec<PackedM31> = addr
+                        .iter()
+                        .chain(std::iter::once(&input_bytes[i]))
+                        .chain(prev_ts)
+                        .map(|col| col.data[vec_idx])
+                        .collect();
+                    memory_lookup_elements.combine(&tuple)
+                };
+
+                let p1: PackedSecureField = {
+                    let next_ts = &next_ts[j..j + WORD_SIZE_HALVED];
+                    let tuple: Vec<PackedM31> = addr
+                        .iter()
+                        .chain(std::iter::once(&output_bytes[i]))
+                        .chain(next_ts)
+                        .map(|col| col.data[vec_idx])
+                        .collect();
+                    memory_lookup_elements.combine(&tuple)
+                };
+                let is_real = one - PackedSecureField::from(is_padding.data[vec_idx]);
+                let numerator = is_real * (p0 - p1);
+                logup_col_gen.write_frac(vec_idx, numerator, p0 * p1);
+            }
+
+            logup_col_gen.finalize_col();
+        }
+        logup_gen.finalize_last()
+    }
+}
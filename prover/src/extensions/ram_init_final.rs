@@ -1,11 +1,16 @@
+use std::collections::{BTreeMap, BTreeSet};
+
 use itertools::Itertools;
 use nexus_common::constants::WORD_SIZE_HALVED;
-use nexus_vm::WORD_SIZE;
+use nexus_vm::{
+    emulator::{MemoryInitializationEntry, PublicOutputEntry},
+    WORD_SIZE,
+};
 use num_traits::{One, Zero};
 use stwo_prover::{
     constraint_framework::{
         logup::LogupTraceGenerator, preprocessed_columns::PreProcessedColumnId, EvalAtRow,
-        FrameworkEval, Relation, RelationEntry,
+        FrameworkEval, Relation, RelationEntry, ORIGINAL_TRACE_IDX,
     },
     core::{
         backend::simd::{
@@ -13,7 +18,7 @@ use stwo_prover::{
             m31::{PackedBaseField, LOG_N_LANES},
             SimdBackend,
         },
-        fields::{m31::BaseField, qm31::SecureField},
+        fields::{m31::BaseField, qm31::SecureField, FieldExpOps},
         poly::{
             circle::{CanonicCoset, CircleEvaluation},
             BitReversedOrder,
@@ -24,11 +29,17 @@ use stwo_prover::{
 
 use crate::{
     chips::{
-        instructions::load_store::LoadStoreLookupElements,
+        instructions::load_store::{
+            AccessMode, LoadStoreLookupElements, PublicOutputLookupElements,
+        },
         range_check::range256::Range256LookupElements,
     },
-    components::AllLookupElements,
-    trace::{program_trace::ProgramTraceRef, sidenote::SideNote, utils::IntoBaseFields},
+    components::{AllLookupElements, LOG_CONSTRAINT_DEGREE},
+    trace::{
+        program_trace::{program_memory_bytes, ProgramTraceRef},
+        sidenote::SideNote,
+        utils::{finalize_columns, IntoBaseFields},
+    },
 };
 
 use super::{BuiltInExtension, ComponentTrace, FrameworkEvalExt};
@@ -40,7 +51,8 @@ pub struct RamInitFinal {
 }
 
 impl RamInitFinal {
-    const NUM_PREPROCESSED_TRACE_COLS: usize = WORD_SIZE + 4;
+    const NUM_PREPROCESSED_TRACE_COLS: usize = WORD_SIZE + 7;
+    const NUM_ORIGINAL_TRACE_COLS: usize = 3 * WORD_SIZE + 4;
     pub(super) const fn new() -> Self {
         Self { _private: () }
     }
@@ -49,6 +61,7 @@ impl RamInitFinal {
 pub(crate) struct RamInitFinalEval {
     log_size: u32,
     load_store_elements: LoadStoreLookupElements,
+    public_output_elements: PublicOutputLookupElements,
     range256_elements: Range256LookupElements,
 }
 
@@ -57,7 +70,7 @@ impl FrameworkEval for RamInitFinalEval {
         self.log_size
     }
     fn max_constraint_log_degree_bound(&self) -> u32 {
-        self.log_size + 1
+        self.log_size + LOG_CONSTRAINT_DEGREE
     }
     fn evaluate<E: stwo_prover::constraint_framework::EvalAtRow>(&self, mut eval: E) -> E {
         // Retrieve all preprocessed columns in the same order as generated
@@ -80,14 +93,35 @@ impl FrameworkEval for RamInitFinalEval {
         let preprocessed_output_value = eval.get_preprocessed_column(PreProcessedColumnId {
             id: "preprocessed_ram_init_final_output_value".to_owned(),
         });
+        let preprocessed_closed_flag = eval.get_preprocessed_column(PreProcessedColumnId {
+            id: "preprocessed_ram_init_final_closed_flag".to_owned(),
+        });
+        let preprocessed_access_mode = eval.get_preprocessed_column(PreProcessedColumnId {
+            id: "preprocessed_ram_init_final_access_mode".to_owned(),
+        });
+        let preprocessed_is_last = eval.get_preprocessed_column(PreProcessedColumnId {
+            id: "preprocessed_ram_init_final_is_last".to_owned(),
+        });
         // The byte-address of RAM initial & final states. Each row contains information about one byte of initial & final RAM states.
-        let ram_init_final_addr = (0..WORD_SIZE).map(|_| eval.next_trace_mask()).collect_vec();
+        let [ram_init_final_addr, ram_init_final_addr_next]: [Vec<E::F>; 2] = {
+            let addr = (0..WORD_SIZE)
+                .map(|_| eval.next_interaction_mask(ORIGINAL_TRACE_IDX, [0, 1]))
+                .collect_vec();
+            [0, 1].map(|offset| addr.iter().map(|mask| mask[offset].clone()).collect())
+        };
         // The flag indicating whether (RamInitFinalAddr, RamFinalValue, RamFinalCounter) represents a byte in the final RAM state.
-        let ram_init_final_flag = eval.next_trace_mask();
+        let [ram_init_final_flag, ram_init_final_flag_next] =
+            eval.next_interaction_mask(ORIGINAL_TRACE_IDX, [0, 1]);
         // The final value of the RAM at address RamInitFinalAddr
         let ram_final_value = eval.next_trace_mask();
         // The final access counter value of the RAM at address RamInitFinalAddr
         let ram_final_counter = (0..WORD_SIZE).map(|_| eval.next_trace_mask()).collect_vec();
+        // The difference between the address on the next row and the address on this row, minus one.
+        let ram_addr_diff = (0..WORD_SIZE).map(|_| eval.next_trace_mask()).collect_vec();
+        // The carry from the low half of the address to the high half when adding RamAddrDiff.
+        let ram_addr_diff_carry = eval.next_trace_mask();
+        // The flag indicating whether RamFinalValue is a byte of the exit code or the public output.
+        let ram_public_output_flag = eval.next_trace_mask();
 
         // For each limb of the address, enforce:
         // (initial_memory_flag + public_output_flag) * (ram_init_final_addr[i] - public_ram_addr[i]) = 0
@@ -99,17 +133,61 @@ impl FrameworkEval for RamInitFinalEval {
         }
         // Enforce: public_output_flag * (ram_final_value - public_output_value) = 0
         eval.add_constraint(
-            preprocessed_output_flag * (ram_final_value.clone() - preprocessed_output_value),
+            preprocessed_output_flag.clone()
+                * (ram_final_value.clone() - preprocessed_output_value),
         );
 
         // Enforce RemInitFinalFlag is boolean
         eval.add_constraint(
             ram_init_final_flag.clone() * (ram_init_final_flag.clone() - E::F::one()),
         );
+        // Public rows are always used, so that no other row can take their addresses.
+        // Enforce: (initial_memory_flag + public_output_flag) * (1 - ram_init_final_flag) = 0
+        eval.add_constraint(
+            (preprocessed_init_flag.clone() + preprocessed_output_flag.clone())
+                * (E::F::one() - ram_init_final_flag.clone()),
+        );
+        // When the final RAM state is closed, the rows used are exactly the public ones.
+        // Enforce: closed_flag * (ram_init_final_flag - public_output_flag) = 0
+        eval.add_constraint(
+            preprocessed_closed_flag.clone()
+                * (ram_init_final_flag.clone() - preprocessed_output_flag),
+        );
 
+        // The exit code and the public output are provided by the verifier through a lookup. They are held by used
+        // rows outside of the publicly known memory, which are write-only. Continuation segments don't prove any output.
+        // Enforce RamPublicOutputFlag is boolean
+        eval.add_constraint(
+            ram_public_output_flag.clone() * (ram_public_output_flag.clone() - E::F::one()),
+        );
+        // Enforce: ram_public_output_flag * (1 - ram_init_final_flag) = 0
+        eval.add_constraint(
+            ram_public_output_flag.clone() * (E::F::one() - ram_init_final_flag.clone()),
+        );
+        // Enforce: (initial_memory_flag + closed_flag) * ram_public_output_flag = 0
+        eval.add_constraint(
+            (preprocessed_init_flag.clone() + preprocessed_closed_flag)
+                * ram_public_output_flag.clone(),
+        );
+        // Rows other than the public ones have the read-write mode in the preprocessed trace.
+        let access_mode = preprocessed_access_mode
+            + ram_public_output_flag.clone()
+                * E::F::from(BaseField::from(AccessMode::WriteOnly as u32));
+
+        self.constrain_unique_addresses(
+            &mut eval,
+            &ram_init_final_addr,
+            &ram_init_final_addr_next,
+            ram_init_final_flag.clone(),
+            ram_init_final_flag_next,
+            &ram_addr_diff,
+            ram_addr_diff_carry,
+            preprocessed_is_last,
+        );
         self.constrain_add_initial_values(
             &mut eval,
             &ram_init_final_addr,
+            access_mode.clone(),
             preprocessed_init_flag,
             preprocessed_init_value,
             ram_init_final_flag.clone(),
@@ -117,15 +195,23 @@ impl FrameworkEval for RamInitFinalEval {
         self.constrain_subtract_final_values(
             &mut eval,
             &ram_init_final_addr,
+            access_mode,
             ram_final_value.clone(),
             &ram_final_counter,
             ram_init_final_flag,
         );
+        self.constrain_subtract_public_output(
+            &mut eval,
+            &ram_init_final_addr,
+            ram_final_value.clone(),
+            ram_public_output_flag,
+        );
         self.constrain_add_range256_occurrences(
             &mut eval,
             &ram_init_final_addr,
             ram_final_value,
             &ram_final_counter,
+            &ram_addr_diff,
         );
 
         eval.finalize_logup();
@@ -137,10 +223,12 @@ impl FrameworkEval for RamInitFinalEval {
 impl FrameworkEvalExt for RamInitFinalEval {
     fn new(log_size: u32, lookup_elements: &AllLookupElements) -> Self {
         let load_store_lookup_elements: &LoadStoreLookupElements = lookup_elements.as_ref();
+        let public_output_lookup_elements: &PublicOutputLookupElements = lookup_elements.as_ref();
         let range256_lookup_elements: &Range256LookupElements = lookup_elements.as_ref();
         Self {
             log_size,
             load_store_elements: load_store_lookup_elements.clone(),
+            public_output_elements: public_output_lookup_elements.clone(),
             range256_elements: range256_lookup_elements.clone(),
         }
     }
@@ -148,16 +236,69 @@ impl FrameworkEvalExt for RamInitFinalEval {
         Self {
             log_size,
             load_store_elements: LoadStoreLookupElements::dummy(),
+            public_output_elements: PublicOutputLookupElements::dummy(),
             range256_elements: Range256LookupElements::dummy(),
         }
     }
 }
 
 impl RamInitFinalEval {
+    /// Constrains the used rows to come first, sorted by strictly increasing addresses.
+    ///
+    /// Every address then has at most one row, so that neither its initial value nor its access mode can be chosen
+    /// twice. Rows of the publicly known memory have their address and access mode fixed by the preprocessed trace,
+    /// all other addresses are read-write.
+    #[allow(clippy::too_many_arguments)]
+    fn constrain_unique_addresses<E: EvalAtRow>(
+        &self,
+        eval: &mut E,
+        ram_init_final_addr: &[E::F],
+        ram_init_final_addr_next: &[E::F],
+        ram_init_final_flag: E::F,
+        ram_init_final_flag_next: E::F,
+        ram_addr_diff: &[E::F],
+        ram_addr_diff_carry: E::F,
+        preprocessed_is_last: E::F,
+    ) {
+        let not_last = E::F::one() - preprocessed_is_last;
+        // A used row can't follow an unused one.
+        // Enforce: (1 - is_last) * flag_next * (1 - flag) = 0
+        eval.add_constraint(
+            not_last.clone()
+                * ram_init_final_flag_next.clone()
+                * (E::F::one() - ram_init_final_flag),
+        );
+        // Enforce RamAddrDiffCarry is boolean
+        eval.add_constraint(
+            ram_addr_diff_carry.clone() * (ram_addr_diff_carry.clone() - E::F::one()),
+        );
+
+        // addr_next = addr + 1 + diff, where diff is a range-checked 32-bit value and the sum doesn't overflow.
+        let half =
+            |bytes: &[E::F]| bytes[0].clone() + bytes[1].clone() * E::F::from((1 << 8).into());
+        let is_next_used = not_last * ram_init_final_flag_next;
+        // Enforce: (1 - is_last) * flag_next * (addr_low + 1 + diff_low - carry * 2^16 - addr_next_low) = 0
+        eval.add_constraint(
+            is_next_used.clone()
+                * (half(&ram_init_final_addr[0..2]) + E::F::one() + half(&ram_addr_diff[0..2])
+                    - ram_addr_diff_carry.clone() * E::F::from((1 << 16).into())
+                    - half(&ram_init_final_addr_next[0..2])),
+        );
+        // Enforce: (1 - is_last) * flag_next * (addr_high + diff_high + carry - addr_next_high) = 0
+        eval.add_constraint(
+            is_next_used
+                * (half(&ram_init_final_addr[2..4])
+                    + half(&ram_addr_diff[2..4])
+                    + ram_addr_diff_carry
+                    - half(&ram_init_final_addr_next[2..4])),
+        );
+    }
+
     fn constrain_add_initial_values<E: EvalAtRow>(
         &self,
         eval: &mut E,
         ram_init_final_addr: &[E::F],
+        preprocessed_access_mode: E::F,
         preprocessed_init_flag: E::F,
         preprocessed_init_value: E::F,
         ram_init_final_flag: E::F,
@@ -167,7 +308,8 @@ impl RamInitFinalEval {
         let addr_low = ram_init_final_addr[0].clone()
             + ram_init_final_addr[1].clone() * E::F::from((1 << 8).into());
         let addr_high = ram_init_final_addr[2].clone()
-            + ram_init_final_addr[3].clone() * E::F::from((1 << 8).into());
+            + ram_init_final_addr[3].clone() * E::F::from((1 << 8).into())
+            + preprocessed_access_mode * E::F::from(AccessMode::ADDR_HIGH_SHIFT.into());
         tuple.push(addr_low);
         tuple.push(addr_high);
         // Add the product of preprocessed init flag and value.
@@ -188,6 +330,7 @@ impl RamInitFinalEval {
         &self,
         eval: &mut E,
         ram_init_final_addr: &[E::F],
+        preprocessed_access_mode: E::F,
         ram_final_value: E::F,
         ram_final_counter: &[E::F],
         ram_init_final_flag: E::F,
@@ -196,7 +339,8 @@ impl RamInitFinalEval {
         let addr_low = ram_init_final_addr[0].clone()
             + ram_init_final_addr[1].clone() * E::F::from((1 << 8).into());
         let addr_high = ram_init_final_addr[2].clone()
-            + ram_init_final_addr[3].clone() * E::F::from((1 << 8).into());
+            + ram_init_final_addr[3].clone() * E::F::from((1 << 8).into())
+            + preprocessed_access_mode * E::F::from(AccessMode::ADDR_HIGH_SHIFT.into());
         tuple.push(addr_low);
         tuple.push(addr_high);
 
@@ -217,12 +361,33 @@ impl RamInitFinalEval {
             &tuple,
         ));
     }
+    /// Consumes a byte of the exit code or the public output, the verifier adds the entries of the claimed values.
+    fn constrain_subtract_public_output<E: EvalAtRow>(
+        &self,
+        eval: &mut E,
+        ram_init_final_addr: &[E::F],
+        ram_final_value: E::F,
+        ram_public_output_flag: E::F,
+    ) {
+        let addr_low = ram_init_final_addr[0].clone()
+            + ram_init_final_addr[1].clone() * E::F::from((1 << 8).into());
+        let addr_high = ram_init_final_addr[2].clone()
+            + ram_init_final_addr[3].clone() * E::F::from((1 << 8).into());
+        let tuple = vec![addr_low, addr_high, ram_final_value];
+        assert_eq!(tuple.len(), WORD_SIZE_HALVED + 1);
+        eval.add_to_relation(RelationEntry::new(
+            &self.public_output_elements,
+            (-ram_public_output_flag).into(),
+            &tuple,
+        ));
+    }
     fn constrain_add_range256_occurrences<E: EvalAtRow>(
         &self,
         eval: &mut E,
         ram_init_final_addr: &[E::F],
         ram_final_value: E::F,
         ram_final_counter: &[E::F],
+        ram_addr_diff: &[E::F],
     ) {
         for ram_init_final_addr_byte in ram_init_final_addr.iter() {
             let checked_tuple = vec![ram_init_final_addr_byte.clone()];
@@ -246,6 +411,14 @@ impl RamInitFinalEval {
                 &checked_tuple,
             ));
         }
+        for ram_addr_diff_byte in ram_addr_diff.iter() {
+            let checked_tuple = vec![ram_addr_diff_byte.clone()];
+            eval.add_to_relation(RelationEntry::new(
+                &self.range256_elements,
+                SecureField::one().into(),
+                &checked_tuple,
+            ));
+        }
     }
 }
 
@@ -259,7 +432,7 @@ impl BuiltInExtension for RamInitFinal {
         side_note: &mut SideNote,
     ) -> ComponentTrace {
         let preprocessed_cols = Self::preprocessed_columns(log_size, program_trace_ref);
-        let original_cols = Self::original_columns(log_size, side_note);
+        let original_cols = Self::original_columns(log_size, program_trace_ref, side_note);
         // update multiplicity for init_final_addr
         for col in &original_cols[0..WORD_SIZE] {
             Self::update_range256_multiplicities(col, side_note);
@@ -267,8 +440,8 @@ impl BuiltInExtension for RamInitFinal {
         // update multiplicity for final_value
         let final_value_col = &original_cols[WORD_SIZE + 1];
         Self::update_range256_multiplicities(final_value_col, side_note);
-        // update multiplicity for final_counter
-        for col in &original_cols[WORD_SIZE + 2..WORD_SIZE + 2 + WORD_SIZE] {
+        // update multiplicity for final_counter and addr_diff
+        for col in &original_cols[WORD_SIZE + 2..3 * WORD_SIZE + 2] {
             Self::update_range256_multiplicities(col, side_note);
         }
 
@@ -306,6 +479,7 @@ impl BuiltInExtension for RamInitFinal {
         SecureField,
     ) {
         let load_store_elements: &LoadStoreLookupElements = lookup_elements.as_ref();
+        let public_output_elements: &PublicOutputLookupElements = lookup_elements.as_ref();
         let range256_elements: &Range256LookupElements = lookup_elements.as_ref();
         let preprocessed_cols = &component_trace.preprocessed_trace;
         let original_cols = &component_trace.original_trace;
@@ -323,11 +497,19 @@ impl BuiltInExtension for RamInitFinal {
 
         Self::subtract_final_values(
             log_size,
+            preprocessed_cols,
             original_cols,
             load_store_elements,
             &mut logup_trace_gen,
         );
 
+        Self::subtract_public_output(
+            log_size,
+            original_cols,
+            public_output_elements,
+            &mut logup_trace_gen,
+        );
+
         Self::add_range256_occurrences(
             log_size,
             original_cols,
@@ -345,118 +527,156 @@ impl BuiltInExtension for RamInitFinal {
 }
 
 impl RamInitFinal {
+    /// Returns publicly known rows of the RAM state as `(address, access mode, initial value, final value)`, sorted by address.
+    ///
+    /// Outside of continuation segments, these are the program and the initial memory rows, so that the preprocessed
+    /// trace doesn't depend on the output of the execution. The exit code and the public output are consumed from the
+    /// verifier instead, see [`proven_output_sum`]. For a segment, there is exactly one row per address of the program
+    /// and of the final memory image, so that they match the final RAM state row by row.
+    ///
+    /// The program is read-only, so that loads from it return instruction words. The exit code and the public output
+    /// are write-only, the initial memory is either read-only or read-write.
+    fn public_rows(
+        program_trace_ref: ProgramTraceRef,
+    ) -> Vec<(u32, AccessMode, Option<u8>, Option<u8>)> {
+        let init_mode = |entry: &MemoryInitializationEntry| {
+            if entry.read_only {
+                AccessMode::ReadOnly
+            } else {
+                AccessMode::ReadWrite
+            }
+        };
+        let program = program_memory_bytes(program_trace_ref.program_memory);
+        let mut rows: Vec<_> = match program_trace_ref.segment {
+            None => program
+                .chain(program_trace_ref.init_memory.iter().copied())
+                .map(|entry| (entry.address, init_mode(&entry), Some(entry.value), None))
+                .collect(),
+            Some(segment) => {
+                let init_memory: BTreeMap<u32, &MemoryInitializationEntry> = program_trace_ref
+                    .init_memory
+                    .iter()
+                    .map(|entry| (entry.address, entry))
+                    .collect();
+                // Output addresses stay write-only, the output itself is checked against the final memory image.
+                let public_output: BTreeSet<u32> = program_trace_ref
+                    .exit_code
+                    .iter()
+                    .chain(program_trace_ref.public_output)
+                    .map(|entry| entry.address)
+                    .collect();
+                // The program is never written, it's left out of the final memory image.
+                program
+                    .map(|entry| {
+                        (
+                            entry.address,
+                            AccessMode::ReadOnly,
+                            Some(entry.value),
+                            Some(entry.value),
+                        )
+                    })
+                    .chain(segment.final_memory.iter().map(|entry| {
+                        let init = init_memory.get(&entry.address);
+                        let mode = if public_output.contains(&entry.address) {
+                            AccessMode::WriteOnly
+                        } else {
+                            init.map_or(AccessMode::ReadWrite, |init| init_mode(init))
+                        };
+                        (
+                            entry.address,
+                            mode,
+                            init.map(|init| init.value),
+                            Some(entry.value),
+                        )
+                    }))
+                    .collect()
+            }
+        };
+        rows.sort_by_key(|(address, _, _, _)| *address);
+        rows
+    }
+
+    /// Returns the preprocessed columns, rows are ordered so that the next row of the AIR is the next public row.
     fn preprocessed_columns(log_size: u32, program_trace_ref: ProgramTraceRef) -> Vec<BaseColumn> {
-        let total_len = program_trace_ref.init_memory.len()
-            + program_trace_ref.exit_code.len()
-            + program_trace_ref.public_output.len();
-        let padding_length = (1usize << log_size)
-            .checked_sub(total_len)
+        let num_rows = 1usize << log_size;
+        let public_rows = Self::public_rows(program_trace_ref);
+        let padding_length = num_rows
+            .checked_sub(public_rows.len())
             .expect("log_size too small");
-        let mut preprocessed_cols = vec![];
+        let rows = public_rows.into_iter().chain(std::iter::repeat_n(
+            (0, AccessMode::ReadWrite, None, None),
+            padding_length,
+        ));
+        let mut preprocessed_cols: Vec<Vec<BaseField>> = vec![];
 
-        // Iterator for PublicRamAddr: take the address from each group.
-        let public_ram_addr_iter = program_trace_ref
-            .init_memory
-            .iter()
-            .map(|entry| entry.address)
-            .chain(
-                program_trace_ref
-                    .exit_code
-                    .iter()
-                    .map(|entry| entry.address),
-            )
-            .chain(
-                program_trace_ref
-                    .public_output
-                    .iter()
-                    .map(|entry| entry.address),
-            )
-            .chain(std::iter::repeat_n(0, padding_length));
-        let public_ram_addr_iter = public_ram_addr_iter
-            .map(|address| -> [BaseField; WORD_SIZE] { address.into_base_fields() });
-        assert_eq!(public_ram_addr_iter.clone().count(), 1 << log_size);
+        // PublicRamAddr: the address of each public row.
+        let public_ram_addr_iter = rows
+            .clone()
+            .map(|(address, _, _, _)| -> [BaseField; WORD_SIZE] { address.into_base_fields() });
         (0..WORD_SIZE).for_each(|i| {
-            let base_column =
-                BaseColumn::from_iter(public_ram_addr_iter.clone().map(|address| address[i]));
-            preprocessed_cols.push(base_column);
+            preprocessed_cols.push(
+                public_ram_addr_iter
+                    .clone()
+                    .map(|address| address[i])
+                    .collect(),
+            );
         });
 
-        // Iterator for PublicInitialMemoryFlag: true for init_memory rows, false for others.
-        let public_initial_memory_flag_iter = program_trace_ref
-            .init_memory
-            .iter()
-            .map(|_| true)
-            .chain(std::iter::repeat_n(
-                false,
-                program_trace_ref.exit_code.len(),
-            ))
-            .chain(std::iter::repeat_n(
-                false,
-                program_trace_ref.public_output.len(),
-            ))
-            .chain(std::iter::repeat_n(false, padding_length));
-        assert_eq!(
-            public_initial_memory_flag_iter.clone().count(),
-            1 << log_size
+        // PublicInitialMemoryFlag and PublicInitialMemoryValue: the initial value is used if the flag is true.
+        preprocessed_cols.push(
+            rows.clone()
+                .map(|(_, _, init, _)| init.is_some().into_base_fields()[0])
+                .collect(),
         );
-        let public_initial_memory_flag_iter =
-            public_initial_memory_flag_iter.map(|flag| flag.into_base_fields()[0]);
-        let public_initial_memory_flag_column =
-            BaseColumn::from_iter(public_initial_memory_flag_iter);
-        preprocessed_cols.push(public_initial_memory_flag_column);
-
-        // Iterator for PublicInitialMemoryValue: use the init_memory value, zero otherwise.
-        let public_initial_memory_value_iter = program_trace_ref
-            .init_memory
-            .iter()
-            .map(|entry| entry.value)
-            .chain(std::iter::repeat_n(0, program_trace_ref.exit_code.len()))
-            .chain(std::iter::repeat_n(
-                0,
-                program_trace_ref.public_output.len(),
-            ))
-            .chain(std::iter::repeat_n(0, padding_length));
-        assert_eq!(
-            public_initial_memory_value_iter.clone().count(),
-            1 << log_size
+        preprocessed_cols.push(
+            rows.clone()
+                .map(|(_, _, init, _)| init.unwrap_or_default().into_base_fields()[0])
+                .collect(),
         );
-        let public_initial_memory_value_iter =
-            public_initial_memory_value_iter.map(|value| value.into_base_fields());
-        let base_column =
-            BaseColumn::from_iter(public_initial_memory_value_iter.map(|value| value[0]));
-        preprocessed_cols.push(base_column);
-
-        // Iterator for PublicOutputFlag: false for init_memory rows, true for exit_code and public_output.
-        let public_output_flag_iter =
-            std::iter::repeat_n(false, program_trace_ref.init_memory.len())
-                .chain(program_trace_ref.exit_code.iter().map(|_| true))
-                .chain(program_trace_ref.public_output.iter().map(|_| true))
-                .chain(std::iter::repeat_n(false, padding_length));
-        assert_eq!(public_output_flag_iter.clone().count(), 1 << log_size);
-        let public_output_flag_iter =
-            public_output_flag_iter.map(|flag| flag.into_base_fields()[0]);
-        let public_output_flag_column = BaseColumn::from_iter(public_output_flag_iter);
-        preprocessed_cols.push(public_output_flag_column);
-
-        // Iterator for PublicOutputValue: zero for init_memory rows, use the provided value for the others.
-        let public_output_value_iter = std::iter::repeat_n(0, program_trace_ref.init_memory.len())
-            .chain(program_trace_ref.exit_code.iter().map(|entry| entry.value))
-            .chain(
-                program_trace_ref
-                    .public_output
-                    .iter()
-                    .map(|entry| entry.value),
-            )
-            .chain(std::iter::repeat_n(0, padding_length));
-        assert_eq!(public_output_value_iter.clone().count(), 1 << log_size);
-        let public_output_value_iter =
-            public_output_value_iter.map(|value| value.into_base_fields());
-        let base_column = BaseColumn::from_iter(public_output_value_iter.map(|value| value[0]));
-        preprocessed_cols.push(base_column);
+
+        // PublicOutputFlag and PublicOutputValue: the final value is enforced if the flag is true. Only set for
+        // continuation segments, the exit code and the public output of an execution aren't in the preprocessed trace.
+        preprocessed_cols.push(
+            rows.clone()
+                .map(|(_, _, _, output)| output.is_some().into_base_fields()[0])
+                .collect(),
+        );
+        preprocessed_cols.push(
+            rows.clone()
+                .map(|(_, _, _, output)| output.unwrap_or_default().into_base_fields()[0])
+                .collect(),
+        );
+
+        // PublicClosedFlag: true on every row for continuation segments, whose final RAM state is entirely public.
+        let closed_flag = program_trace_ref.segment.is_some().into_base_fields()[0];
+        preprocessed_cols.push(vec![closed_flag; num_rows]);
+
+        // PublicAccessMode: the access mode of each row, rows without public data are read-write.
+        preprocessed_cols.push(
+            rows.map(|(_, mode, _, _)| BaseField::from(mode as u32))
+                .collect(),
+        );
+
+        // IsLast: true on the last row, whose next row is the first one.
+        let mut is_last = vec![BaseField::zero(); num_rows];
+        is_last[num_rows - 1] = BaseField::one();
+        preprocessed_cols.push(is_last);
+
+        preprocessed_cols.iter().enumerate().for_each(|(i, col)| {
+            assert_eq!(col.len(), num_rows, "{}th column has wrong length", i);
+        });
         assert_eq!(preprocessed_cols.len(), Self::NUM_PREPROCESSED_TRACE_COLS);
-        preprocessed_cols
+        finalize_columns(preprocessed_cols)
     }
-    fn original_columns(log_size: u32, side_note: &SideNote) -> Vec<BaseColumn> {
+
+    /// Returns the original columns with one row per accessed address sorted by address, followed by unused rows.
+    ///
+    /// The rows are ordered in the same way as the preprocessed ones.
+    fn original_columns(
+        log_size: u32,
+        program_trace_ref: ProgramTraceRef,
+        side_note: &SideNote,
+    ) -> Vec<BaseColumn> {
         // First, create an iterator on rw_mem_check_last_access extended to the expected number of rows.
         let num_rows = 1usize << log_size;
         let num_entries = side_note.rw_mem_check.last_access.len();
@@ -469,30 +689,40 @@ impl RamInitFinal {
             .iter()
             .map(Some)
             .chain(std::iter::repeat_n(None, num_extension));
-        let mut ret = vec![];
+        let mut ret: Vec<Vec<BaseField>> = vec![];
         let ram_init_final_addrs = extended_iter
             .clone()
             .map(|entry| entry.map_or_else(|| 0u32, |(address, _last_access)| *address));
-        let ram_init_final_addrs = ram_init_final_addrs
+        let ram_init_final_addrs_bytes = ram_init_final_addrs
+            .clone()
             .map(|address| -> [BaseField; WORD_SIZE] { address.into_base_fields() });
         (0..WORD_SIZE).for_each(|i| {
-            let base_column =
-                BaseColumn::from_iter(ram_init_final_addrs.clone().map(|address| address[i]));
-            ret.push(base_column);
-        });
-        let ram_init_final_flag = extended_iter
-            .clone()
-            .map(|entry| entry.is_some().into_base_fields()[0]);
-        let ram_init_final_flag = BaseColumn::from_iter(ram_init_final_flag);
-        ret.push(ram_init_final_flag);
-        let ram_final_values = extended_iter.clone().map(|entry| {
-            entry.map_or_else(
-                BaseField::zero,
-                |(_address, (_last_counter, last_value))| BaseField::from(*last_value as u32),
-            )
+            ret.push(
+                ram_init_final_addrs_bytes
+                    .clone()
+                    .map(|address| address[i])
+                    .collect(),
+            );
         });
-        let ram_final_values = BaseColumn::from_iter(ram_final_values);
-        ret.push(ram_final_values);
+        ret.push(
+            extended_iter
+                .clone()
+                .map(|entry| entry.is_some().into_base_fields()[0])
+                .collect(),
+        );
+        ret.push(
+            extended_iter
+                .clone()
+                .map(|entry| {
+                    entry.map_or_else(
+                        BaseField::zero,
+                        |(_address, (_last_counter, last_value))| {
+                            BaseField::from(*last_value as u32)
+                        },
+                    )
+                })
+                .collect(),
+        );
         let ram_final_counters = extended_iter.map(|entry| {
             entry.map_or_else(
                 || [BaseField::zero(); WORD_SIZE],
@@ -500,15 +730,61 @@ impl RamInitFinal {
             )
         });
         (0..WORD_SIZE).for_each(|i| {
-            let base_column =
-                BaseColumn::from_iter(ram_final_counters.clone().map(|counter| counter[i]));
-            ret.push(base_column);
+            ret.push(
+                ram_final_counters
+                    .clone()
+                    .map(|counter| counter[i])
+                    .collect(),
+            );
+        });
+
+        // RamAddrDiff and RamAddrDiffCarry: between consecutive used rows, next_address = address + 1 + diff.
+        let addresses: Vec<u32> = side_note.rw_mem_check.last_access.keys().copied().collect();
+        let diffs = (0..num_rows).map(|row| match (addresses.get(row), addresses.get(row + 1)) {
+            (Some(&address), Some(&next_address)) => {
+                let diff = next_address
+                    .checked_sub(address + 1)
+                    .expect("addresses must be sorted and unique");
+                let carry = (address & 0xFFFF) + 1 + (diff & 0xFFFF) >= 1 << 16;
+                (diff, carry)
+            }
+            _ => (0, false),
+        });
+        let diff_bytes = diffs
+            .clone()
+            .map(|(diff, _carry)| -> [BaseField; WORD_SIZE] { diff.into_base_fields() });
+        (0..WORD_SIZE).for_each(|i| {
+            ret.push(diff_bytes.clone().map(|diff| diff[i]).collect());
         });
+        ret.push(
+            diffs
+                .map(|(_diff, carry)| carry.into_base_fields()[0])
+                .collect(),
+        );
+
+        // RamPublicOutputFlag: the row holds a byte of the exit code or the public output.
+        let proven_output: BTreeSet<u32> = program_trace_ref
+            .proven_output()
+            .map(|entry| entry.address)
+            .collect();
+        ret.push(
+            addresses
+                .iter()
+                .map(Some)
+                .chain(std::iter::repeat_n(None, num_extension))
+                .map(|address| {
+                    address
+                        .is_some_and(|address| proven_output.contains(address))
+                        .into_base_fields()[0]
+                })
+                .collect(),
+        );
+
         ret.iter().enumerate().for_each(|(i, col)| {
-            assert_eq!(col.length, num_rows, "{}th element has wrong length", i);
+            assert_eq!(col.len(), num_rows, "{}th element has wrong length", i);
         });
-        assert!(ret.len() == 2 * WORD_SIZE + 2);
-        ret
+        assert_eq!(ret.len(), Self::NUM_ORIGINAL_TRACE_COLS);
+        finalize_columns(ret)
     }
 
     /// Fills the interaction trace for adding the initial content of the RW memory.
@@ -518,7 +794,7 @@ impl RamInitFinal {
     /// - `InitialMemoryFlag` indicates whether a row should contain a byte of the publicly known initial RW memory, flag being zero means the initial value is zero.
     /// - `InitialMemoryValue` contains the initial value of the RW memory, used if `InitialMemoryFlag` is true.
     ///
-    /// The counter of the initial value is always zero.
+    /// The counter of the initial value is always zero, and the access mode of the address is public.
     fn add_initial_values(
         log_size: u32,
         preprocessed_cols: &[BaseColumn],
@@ -531,13 +807,16 @@ impl RamInitFinal {
         let initial_memory_value = &preprocessed_cols[WORD_SIZE + 1];
         let _preprocessed_output_flag = &preprocessed_cols[WORD_SIZE + 2];
         let _preprocessed_output_value = &preprocessed_cols[WORD_SIZE + 3];
+        let _preprocessed_closed_flag = &preprocessed_cols[WORD_SIZE + 4];
+        let access_mode = &preprocessed_cols[WORD_SIZE + 5];
         assert_eq!(preprocessed_cols.len(), Self::NUM_PREPROCESSED_TRACE_COLS);
 
         let ram_init_final_addr = &original_cols[0..WORD_SIZE];
         let ram_init_final_flag = &original_cols[WORD_SIZE];
         let _ram_final_value = &original_cols[WORD_SIZE + 1];
         let _ram_final_counter = &original_cols[WORD_SIZE + 2..WORD_SIZE + 2 + WORD_SIZE];
-        assert_eq!(original_cols.len(), WORD_SIZE + 2 + WORD_SIZE);
+        let ram_public_output_flag = &original_cols[3 * WORD_SIZE + 3];
+        assert_eq!(original_cols.len(), Self::NUM_ORIGINAL_TRACE_COLS);
 
         let mut logup_col_gen = logup_trace_gen.new_col();
         // Add (address, value, 0)
@@ -548,7 +827,9 @@ impl RamInitFinal {
                     * PackedBaseField::broadcast((1 << 8).into());
             let addr_high = ram_init_final_addr[2].data[vec_row]
                 + ram_init_final_addr[3].data[vec_row]
-                    * PackedBaseField::broadcast((1 << 8).into());
+                    * PackedBaseField::broadcast((1 << 8).into())
+                + Self::access_mode(access_mode, ram_public_output_flag, vec_row)
+                    * PackedBaseField::broadcast(AccessMode::ADDR_HIGH_SHIFT.into());
             tuple.push(addr_low);
             tuple.push(addr_high);
 
@@ -573,6 +854,7 @@ impl RamInitFinal {
     /// The public output related columns do not appear here because they are constrained to use `RamFinalValue`.
     fn subtract_final_values(
         log_size: u32,
+        preprocessed_cols: &[BaseColumn],
         original_cols: &[BaseColumn],
         lookup_element: &LoadStoreLookupElements,
         logup_trace_gen: &mut LogupTraceGenerator,
@@ -581,7 +863,10 @@ impl RamInitFinal {
         let ram_init_final_flag = &original_cols[WORD_SIZE];
         let ram_final_value = &original_cols[WORD_SIZE + 1];
         let ram_final_counter = &original_cols[WORD_SIZE + 2..WORD_SIZE + 2 + WORD_SIZE];
-        assert_eq!(original_cols.len(), WORD_SIZE + 2 + WORD_SIZE);
+        let ram_public_output_flag = &original_cols[3 * WORD_SIZE + 3];
+        assert_eq!(original_cols.len(), Self::NUM_ORIGINAL_TRACE_COLS);
+        let access_mode = &preprocessed_cols[WORD_SIZE + 5];
+        assert_eq!(preprocessed_cols.len(), Self::NUM_PREPROCESSED_TRACE_COLS);
 
         let mut logup_col_gen = logup_trace_gen.new_col();
         for vec_row in 0..(1 << (log_size - LOG_N_LANES)) {
@@ -592,7 +877,9 @@ impl RamInitFinal {
                     * PackedBaseField::broadcast((1 << 8).into());
             let addr_high = ram_init_final_addr[2].data[vec_row]
                 + ram_init_final_addr[3].data[vec_row]
-                    * PackedBaseField::broadcast((1 << 8).into());
+                    * PackedBaseField::broadcast((1 << 8).into())
+                + Self::access_mode(access_mode, ram_public_output_flag, vec_row)
+                    * PackedBaseField::broadcast(AccessMode::ADDR_HIGH_SHIFT.into());
             tuple.push(addr_low);
             tuple.push(addr_high);
 
@@ -612,6 +899,50 @@ impl RamInitFinal {
         }
         logup_col_gen.finalize_col();
     }
+
+    /// Fills the interaction trace for consuming the exit code and the public output.
+    ///
+    /// - `RamPublicOutputFlag` indicates whether `RamFinalValue` is a byte of the output at `RamInitFinalAddr`.
+    ///
+    /// The entries are added by the verifier from the claimed output, see [`proven_output_sum`].
+    fn subtract_public_output(
+        log_size: u32,
+        original_cols: &[BaseColumn],
+        lookup_element: &PublicOutputLookupElements,
+        logup_trace_gen: &mut LogupTraceGenerator,
+    ) {
+        let ram_init_final_addr = &original_cols[0..WORD_SIZE];
+        let ram_final_value = &original_cols[WORD_SIZE + 1];
+        let ram_public_output_flag = &original_cols[3 * WORD_SIZE + 3];
+        assert_eq!(original_cols.len(), Self::NUM_ORIGINAL_TRACE_COLS);
+
+        let mut logup_col_gen = logup_trace_gen.new_col();
+        for vec_row in 0..(1 << (log_size - LOG_N_LANES)) {
+            let addr_low = ram_init_final_addr[0].data[vec_row]
+                + ram_init_final_addr[1].data[vec_row]
+                    * PackedBaseField::broadcast((1 << 8).into());
+            let addr_high = ram_init_final_addr[2].data[vec_row]
+                + ram_init_final_addr[3].data[vec_row]
+                    * PackedBaseField::broadcast((1 << 8).into());
+            let tuple = [addr_low, addr_high, ram_final_value.data[vec_row]];
+            let denom = lookup_element.combine(&tuple);
+            let numerator = ram_public_output_flag.data[vec_row];
+            logup_col_gen.write_frac(vec_row, (-numerator).into(), denom);
+        }
+        logup_col_gen.finalize_col();
+    }
+
+    /// Returns the access mode of the rows, public rows use the preprocessed one and output rows are write-only.
+    fn access_mode(
+        preprocessed_access_mode: &BaseColumn,
+        ram_public_output_flag: &BaseColumn,
+        vec_row: usize,
+    ) -> PackedBaseField {
+        preprocessed_access_mode.data[vec_row]
+            + ram_public_output_flag.data[vec_row]
+                * PackedBaseField::broadcast(BaseField::from(AccessMode::WriteOnly as u32))
+    }
+
     fn add_range256_occurrences(
         log_size: u32,
         original_cols: &[BaseColumn],
@@ -622,7 +953,8 @@ impl RamInitFinal {
         let _ram_init_final_flag = &original_cols[WORD_SIZE];
         let ram_final_value = &original_cols[WORD_SIZE + 1];
         let ram_final_counter = &original_cols[WORD_SIZE + 2..WORD_SIZE + 2 + WORD_SIZE];
-        assert_eq!(original_cols.len(), WORD_SIZE + 2 + WORD_SIZE);
+        let ram_addr_diff = &original_cols[2 * WORD_SIZE + 2..3 * WORD_SIZE + 2];
+        assert_eq!(original_cols.len(), Self::NUM_ORIGINAL_TRACE_COLS);
 
         for ram_init_final_addr_byte in ram_init_final_addr.iter() {
             let mut logup_col_gen = logup_trace_gen.new_col();
@@ -641,10 +973,10 @@ impl RamInitFinal {
             logup_col_gen.write_frac(vec_row, SecureField::one().into(), denom);
         }
         logup_col_gen.finalize_col();
-        for ram_final_counter_byte in ram_final_counter.iter() {
+        for ram_counter_or_diff_byte in ram_final_counter.iter().chain(ram_addr_diff) {
             let mut logup_col_gen = logup_trace_gen.new_col();
             for vec_row in 0..(1 << (log_size - LOG_N_LANES)) {
-                let checked_tuple = vec![ram_final_counter_byte.data[vec_row]];
+                let checked_tuple = vec![ram_counter_or_diff_byte.data[vec_row]];
                 let denom = lookup_element.combine(&checked_tuple);
                 logup_col_gen.write_frac(vec_row, SecureField::one().into(), denom);
             }
@@ -666,3 +998,31 @@ impl RamInitFinal {
         }
     }
 }
+
+/// Returns the combined entries of the exit code and the public output consumed by the RAM init/final component.
+pub(crate) fn proven_output_entries<'a>(
+    lookup_elements: &AllLookupElements,
+    proven_output: impl Iterator<Item = &'a PublicOutputEntry>,
+) -> Vec<SecureField> {
+    let lookup_elements: &PublicOutputLookupElements = lookup_elements.as_ref();
+    proven_output
+        .map(|PublicOutputEntry { address, value }| {
+            let [addr_low, addr_high] = [address & 0xFFFF, address >> 16].map(BaseField::from);
+            lookup_elements.combine(&[addr_low, addr_high, BaseField::from(*value as u32)])
+        })
+        .collect()
+}
+
+/// Returns the logup sum of the exit code and the public output consumed by the RAM init/final component.
+///
+/// The verifier adds it to the claimed sums of the components, which then add up to zero only if the final RAM state
+/// holds the claimed output.
+pub(crate) fn proven_output_sum<'a>(
+    lookup_elements: &AllLookupElements,
+    proven_output: impl Iterator<Item = &'a PublicOutputEntry>,
+) -> SecureField {
+    proven_output_entries(lookup_elements, proven_output)
+        .into_iter()
+        .map(|denom| denom.inverse())
+        .sum()
+}
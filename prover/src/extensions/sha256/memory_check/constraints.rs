@@ -0,0 +1,183 @@
+use nexus_common::constants::WORD_SIZE_HALVED;
+use num_traits::{One, Zero};
+use stwo_prover::{
+    constraint_framework::{preprocessed_columns::PreProcessedColumnId, EvalAtRow, RelationEntry},
+    core::fields::m31::BaseField,
+};
+
+use crate::{
+    components::lookups::{LoadStoreLookupElements, Sha256StateLookupElements},
+    extensions::sha256::round::ROUNDS,
+};
+
+use super::trace::PREPROCESSED_COLUMN_ID;
+
+pub struct Sha256MemoryCheckEval<'a, E> {
+    pub(crate) eval: E,
+    pub(crate) state_lookup_elements: &'a Sha256StateLookupElements,
+    pub(crate) memory_lookup_elements: &'a LoadStoreLookupElements,
+}
+
+impl<E: EvalAtRow> Sha256MemoryCheckEval<'_, E> {
+    const BLOCK_BYTES: usize = super::Sha256MemoryCheckEval::BLOCK_BYTES;
+    const STATE_BYTES: usize = super::Sha256MemoryCheckEval::STATE_BYTES;
+    const NUM_BYTES: usize = super::Sha256MemoryCheckEval::NUM_BYTES;
+
+    pub fn eval(mut self) -> E {
+        let id = self.eval.get_preprocessed_column(PreProcessedColumnId {
+            id: PREPROCESSED_COLUMN_ID.to_string(),
+        });
+
+        let block_in = self.next_columns(Self::BLOCK_BYTES);
+        let state_in = self.next_columns(Self::STATE_BYTES);
+        let final_vars = self.next_columns(Self::STATE_BYTES / WORD_SIZE_HALVED);
+        let out_bits = self.next_columns(Self::STATE_BYTES * 8);
+        let out_carries = self.next_columns(Self::STATE_BYTES / WORD_SIZE_HALVED);
+        let addrs = self.next_columns(Self::NUM_BYTES * WORD_SIZE_HALVED);
+        let prev_ts = self.next_columns(Self::NUM_BYTES * WORD_SIZE_HALVED);
+        let next_ts = self.next_columns(Self::NUM_BYTES * WORD_SIZE_HALVED);
+        let addr_carries = self.next_columns(Self::NUM_BYTES);
+        let ts_carries = self.next_columns(Self::NUM_BYTES);
+
+        let is_padding = self.eval.next_trace_mask();
+        self.eval
+            .add_constraint(is_padding.clone() * (E::F::one() - is_padding.clone()));
+        let is_real = E::F::one() - is_padding;
+
+        for bit in out_bits
+            .iter()
+            .chain(&out_carries)
+            .chain(&addr_carries)
+            .chain(&ts_carries)
+        {
+            self.eval
+                .add_constraint(bit.clone() * (E::F::one() - bit.clone()));
+        }
+
+        // out = in + final_vars mod 2^32, computed on halves
+        let modulus = E::F::from(BaseField::from(1 << 16));
+        let in_halves = Self::halves_from_bytes(&state_in);
+        let out_bytes: Vec<E::F> = out_bits.chunks_exact(8).map(Self::from_bits).collect();
+        let out_halves = Self::halves_from_bytes(&out_bytes);
+        for (i, carry) in out_carries.iter().enumerate() {
+            let carry_in = if i % WORD_SIZE_HALVED == 0 {
+                E::F::zero()
+            } else {
+                out_carries[i - 1].clone()
+            };
+            self.eval.add_constraint(
+                out_halves[i].clone() + carry.clone() * modulus.clone()
+                    - in_halves[i].clone()
+                    - final_vars[i].clone()
+                    - carry_in,
+            );
+        }
+
+        // addresses and timestamps of consecutive bytes
+        for (start, len) in [
+            (0, Self::BLOCK_BYTES),
+            (Self::BLOCK_BYTES, Self::STATE_BYTES),
+        ] {
+            for i in start..start + len - 1 {
+                let j = i * WORD_SIZE_HALVED;
+                let addr = &addrs[j..j + WORD_SIZE_HALVED];
+                let next_addr = &addrs[j + WORD_SIZE_HALVED..j + WORD_SIZE_HALVED * 2];
+                self.constrain_increment(&is_real, addr, next_addr, &addr_carries[i]);
+            }
+        }
+        for (i, (prev_ts, next_ts)) in prev_ts
+            .chunks_exact(WORD_SIZE_HALVED)
+            .zip(next_ts.chunks_exact(WORD_SIZE_HALVED))
+            .enumerate()
+        {
+            self.constrain_increment(&is_real, prev_ts, next_ts, &ts_carries[i]);
+        }
+
+        let block_halves = Self::halves_from_bytes(&block_in);
+        let input_state: Vec<E::F> = [id.clone(), E::F::zero()]
+            .into_iter()
+            .chain(in_halves)
+            .chain(block_halves)
+            .collect();
+        let output_vars: Vec<E::F> = [id, E::F::from(BaseField::from(ROUNDS as u32))]
+            .into_iter()
+            .chain(final_vars)
+            .collect();
+        self.eval.add_to_relation(RelationEntry::new(
+            self.state_lookup_elements,
+            is_real.clone().into(),
+            &input_state,
+        ));
+        self.eval.add_to_relation(RelationEntry::new(
+            self.state_lookup_elements,
+            (-is_real.clone()).into(),
+            &output_vars,
+        ));
+
+        // the block is only read, the state is overwritten
+        let prev_vals = block_in.iter().chain(&state_in);
+        let next_vals = block_in.iter().chain(&out_bytes);
+        for (i, (prev_val, next_val)) in prev_vals.zip(next_vals).enumerate() {
+            let j = i * WORD_SIZE_HALVED;
+            // (addr, val, ts)
+            let sub_access = [
+                &addrs[j..j + WORD_SIZE_HALVED],
+                std::slice::from_ref(prev_val),
+                &prev_ts[j..j + WORD_SIZE_HALVED],
+            ]
+            .concat();
+            let add_access = [
+                &addrs[j..j + WORD_SIZE_HALVED],
+                std::slice::from_ref(next_val),
+                &next_ts[j..j + WORD_SIZE_HALVED],
+            ]
+            .concat();
+
+            self.eval.add_to_relation(RelationEntry::new(
+                self.memory_lookup_elements,
+                (-is_real.clone()).into(),
+                &sub_access,
+            ));
+            self.eval.add_to_relation(RelationEntry::new(
+                self.memory_lookup_elements,
+                is_real.clone().into(),
+                &add_access,
+            ));
+        }
+
+        self.eval.finalize_logup_in_pairs();
+
+        self.eval
+    }
+
+    fn next_columns(&mut self, size: usize) -> Vec<E::F> {
+        std::iter::repeat_with(|| self.eval.next_trace_mask())
+            .take(size)
+            .collect()
+    }
+
+    fn from_bits(bits: &[E::F]) -> E::F {
+        bits.iter().enumerate().fold(E::F::zero(), |acc, (i, bit)| {
+            acc + bit.clone() * BaseField::from(1 << i)
+        })
+    }
+
+    fn halves_from_bytes(bytes: &[E::F]) -> Vec<E::F> {
+        bytes
+            .chunks_exact(2)
+            .map(|pair| pair[0].clone() + pair[1].clone() * BaseField::from(1 << 8))
+            .collect()
+    }
+
+    /// Constrains a 32-bit value split into halves to be incremented by one.
+    fn constrain_increment(&mut self, is_real: &E::F, prev: &[E::F], next: &[E::F], carry: &E::F) {
+        self.eval.add_constraint(
+            is_real.clone()
+                * (next[0].clone() + carry.clone() * E::F::from((1 << 16).into())
+                    - prev[0].clone()
+                    - E::F::one()),
+        );
+        self.eval
+            .add_constraint(is_real.clone() * (next[1].clone() - prev[1].clone() - carry.clone()));
+    }
+}
@@ -0,0 +1,256 @@
+use nexus_common::constants::WORD_SIZE_HALVED;
+use nexus_vm::cpu::instructions::custom::sha256::sha256_compress;
+use num_traits::{One, Zero};
+use stwo_prover::{
+    constraint_framework::{logup::LogupTraceGenerator, Relation},
+    core::{
+        backend::simd::{
+            column::BaseColumn,
+            m31::{PackedM31, LOG_N_LANES},
+            qm31::PackedSecureField,
+            SimdBackend,
+        },
+        fields::{m31::BaseField, qm31::SecureField},
+        poly::{circle::CircleEvaluation, BitReversedOrder},
+        ColumnVec,
+    },
+};
+
+use super::Sha256MemoryCheckEval;
+use crate::{
+    components::lookups::{LoadStoreLookupElements, Sha256StateLookupElements},
+    extensions::{
+        keccak::round::trace::get_is_padding_base_column, sha256::round::ROUNDS, ComponentTrace,
+    },
+    trace::sidenote::SideNote,
+};
+
+pub(super) const PREPROCESSED_COLUMN_ID: &str = "sha256_memory_check_id";
+
+const MASK: u32 = (1 << 16) - 1;
+
+pub(super) fn preprocessed_id_column(log_size: u32) -> BaseColumn {
+    (0..1u32 << log_size).map(BaseField::from).collect()
+}
+
+/// Returns the input state and block, the output state, and working variables after the last round.
+fn compress(input: &([u32; 8], [u32; 16])) -> ([u32; 8], [u32; 16], [u32; 8], [u32; 8]) {
+    let (state, block) = *input;
+    let mut output = state;
+    sha256_compress(&mut output, &block);
+    let final_vars = std::array::from_fn(|i| output[i].wrapping_sub(state[i]));
+    (state, block, output, final_vars)
+}
+
+pub fn generate_sha256_mem_check_trace(log_size: u32, side_note: &SideNote) -> ComponentTrace {
+    let num_bytes = Sha256MemoryCheckEval::NUM_BYTES;
+    let state_bytes = Sha256MemoryCheckEval::STATE_BYTES;
+    // [block_in, state_in, final_vars, out_bits, out_carries, addresses, prev_ts, next_ts, addr_carries, ts_carries]
+    let num_cols = num_bytes
+        + state_bytes / WORD_SIZE_HALVED * 2
+        + state_bytes * 8
+        + num_bytes * WORD_SIZE_HALVED * 3
+        + num_bytes * 2;
+    let mut original_trace = vec![vec![BaseField::zero(); 1 << log_size]; num_cols];
+
+    let sha256_side_note = &side_note.sha256;
+    for (row, input) in sha256_side_note.inputs.iter().enumerate() {
+        let (state, block, output, final_vars) = compress(input);
+        let (state_addr, block_addr) = sha256_side_note.addresses[row];
+        let timestamps = &sha256_side_note.timestamps[row];
+
+        let mut values: Vec<u32> = Vec::with_capacity(num_cols);
+        let to_bytes = |words: &[u32]| -> Vec<u32> {
+            words
+                .iter()
+                .flat_map(|word| word.to_le_bytes())
+                .map(u32::from)
+                .collect()
+        };
+        values.extend(to_bytes(&block));
+        values.extend(to_bytes(&state));
+        values.extend(final_vars.iter().flat_map(|v| [v & MASK, v >> 16]));
+        values.extend(
+            output
+                .iter()
+                .flat_map(|word| (0..32).map(move |i| (word >> i) & 1)),
+        );
+        for (word, v) in state.iter().zip(&final_vars) {
+            let carry_low = ((word & MASK) + (v & MASK)) >> 16;
+            let carry_high = ((word >> 16) + (v >> 16) + carry_low) >> 16;
+            values.extend([carry_low, carry_high]);
+        }
+
+        let addrs: Vec<u32> = (0..block.len() * 4)
+            .map(|i| block_addr + i as u32)
+            .chain((0..state.len() * 4).map(|i| state_addr + i as u32))
+            .collect();
+        assert_eq!(timestamps.len(), addrs.len());
+        values.extend(addrs.iter().flat_map(|addr| [addr & MASK, addr >> 16]));
+        values.extend(timestamps.iter().flat_map(|ts| [ts & MASK, ts >> 16]));
+        values.extend(timestamps.iter().flat_map(|ts| {
+            let ts = ts + 1;
+            [ts & MASK, ts >> 16]
+        }));
+        values.extend(addrs.iter().map(|addr| u32::from(addr & MASK == MASK)));
+        values.extend(timestamps.iter().map(|ts| u32::from(ts & MASK == MASK)));
+
+        assert_eq!(values.len(), num_cols);
+        for (col, value) in original_trace.iter_mut().zip(values) {
+            col[row] = BaseField::from(value);
+        }
+    }
+    let real_rows = sha256_side_note.inputs.len();
+    let is_padding = get_is_padding_base_column(log_size, real_rows);
+    let mut original_trace: Vec<BaseColumn> = original_trace
+        .into_iter()
+        .map(BaseColumn::from_iter)
+        .collect();
+    original_trace.push(is_padding);
+    let preprocessed_trace = vec![preprocessed_id_column(log_size)];
+
+    ComponentTrace {
+        log_size,
+        preprocessed_trace,
+        original_trace,
+    }
+}
+
+pub(super) struct MemoryCheckLogUpGenerator<'a> {
+    pub(super) component_trace: &'a ComponentTrace,
+    pub(super) inputs: &'a [([u32; 8], [u32; 16])],
+}
+
+impl MemoryCheckLogUpGenerator<'_> {
+    pub fn interaction_trace(
+        &self,
+        state_lookup_elements: &Sha256StateLookupElements,
+        memory_lookup_elements: &LoadStoreLookupElements,
+    ) -> (
+        ColumnVec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>>,
+        SecureField,
+    ) {
+        let num_bytes = Sha256MemoryCheckEval::NUM_BYTES;
+        let state_bytes = Sha256MemoryCheckEval::STATE_BYTES;
+        let log_size = self.component_trace.log_size;
+        let mut logup_gen = LogupTraceGenerator::new(log_size);
+
+        let original_trace = self.component_trace.original_trace.as_slice();
+
+        let (block_and_state_in, trace) = original_trace.split_at(num_bytes);
+        // skip final vars, output bits and carries
+        let (_, trace) = trace.split_at(state_bytes / WORD_SIZE_HALVED * 2 + state_bytes * 8);
+        let (addrs, trace) = trace.split_at(num_bytes * WORD_SIZE_HALVED);
+        let (prev_ts, trace) = trace.split_at(num_bytes * WORD_SIZE_HALVED);
+        let (next_ts, rem) = trace.split_at(num_bytes * WORD_SIZE_HALVED);
+
+        // skip carries
+        let (_, rem) = rem.split_at(num_bytes * 2);
+
+        assert_eq!(rem.len(), 1);
+        let is_padding = &rem[0];
+
+        // Tuples with output values are recomputed rather than collected from bits.
+        let mut input_states = vec![vec![]; 2 + 24 * 2];
+        let mut output_vars = vec![vec![]; 2 + 8 * 2];
+        let mut out_bytes = vec![vec![]; state_bytes];
+        let halves = |words: &[u32]| -> Vec<u32> {
+            words
+                .iter()
+                .flat_map(|word| [word & MASK, word >> 16])
+                .collect()
+        };
+        for row in 0..1u32 << log_size {
+            // padding rows are zeroed, same as in the component trace
+            let (state, block, output, final_vars) = self
+                .inputs
+                .get(row as usize)
+                .map(compress)
+                .unwrap_or_default();
+            let input_state = [row, 0]
+                .into_iter()
+                .chain(halves(&state))
+                .chain(halves(&block));
+            for (col, value) in input_states.iter_mut().zip(input_state) {
+                col.push(BaseField::from(value));
+            }
+            let vars = [row, ROUNDS as u32].into_iter().chain(halves(&final_vars));
+            for (col, value) in output_vars.iter_mut().zip(vars) {
+                col.push(BaseField::from(value));
+            }
+            for (col, byte) in out_bytes
+                .iter_mut()
+                .zip(output.iter().flat_map(|word| word.to_le_bytes()))
+            {
+                col.push(BaseField::from(u32::from(byte)));
+            }
+        }
+        let into_columns = |cols: Vec<Vec<BaseField>>| -> Vec<BaseColumn> {
+            cols.into_iter().map(BaseColumn::from_iter).collect()
+        };
+        let input_states = into_columns(input_states);
+        let output_vars = into_columns(output_vars);
+        let out_bytes = into_columns(out_bytes);
+
+        let one: PackedSecureField = SecureField::one().into();
+        let mut logup_col_gen = logup_gen.new_col();
+        for vec_idx in 0..(1 << (log_size - LOG_N_LANES)) {
+            let p0: PackedSecureField = {
+                let tuple: Vec<PackedM31> =
+                    input_states.iter().map(|col| col.data[vec_idx]).collect();
+                state_lookup_elements.combine(&tuple)
+            };
+            let p1: PackedSecureField = {
+                let tuple: Vec<PackedM31> =
+                    output_vars.iter().map(|col| col.data[vec_idx]).collect();
+                state_lookup_elements.combine(&tuple)
+            };
+            let is_real = one - PackedSecureField::from(is_padding.data[vec_idx]);
+            let numerator = is_real * (p1 - p0);
+            logup_col_gen.write_frac(vec_idx, numerator, p0 * p1);
+        }
+        logup_col_gen.finalize_col();
+
+        // the block is only read, the state is overwritten
+        let prev_vals = block_and_state_in;
+        let next_vals: Vec<&BaseColumn> = block_and_state_in[..num_bytes - state_bytes]
+            .iter()
+            .chain(&out_bytes)
+            .collect();
+        for i in 0..num_bytes {
+            let j = i * WORD_SIZE_HALVED;
+            let mut logup_col_gen = logup_gen.new_col();
+            for vec_idx in 0..(1 << (log_size - LOG_N_LANES)) {
+                let addr = &addrs[j..j + WORD_SIZE_HALVED];
+
+                let p0: PackedSecureField = {
+                    let prev_ts = &prev_ts[j..j + WORD_SIZE_HALVED];
+                    let tuple: Vec<PackedM31> = addr
+                        .iter()
+                        .chain(std::iter::once(&prev_vals[i]))
+                        .chain(prev_ts)
+                        .map(|col| col.data[vec_idx])
+                        .collect();
+                    memory_lookup_elements.combine(&tuple)
+                };
+
+                let p1: PackedSecureField = {
+                    let next_ts = &next_ts[j..j + WORD_SIZE_HALVED];
+                    let tuple: Vec<PackedM31> = addr
+                        .iter()
+                        .chain(std::iter::once(next_vals[i]))
+                        .chain(next_ts)
+                        .map(|col| col.data[vec_idx])
+                        .collect();
+                    memory_lookup_elements.combine(&tuple)
+                };
+                let is_real = one - PackedSecureField::from(is_padding.data[vec_idx]);
+                let numerator = is_real * (p0 - p1);
+                logup_col_gen.write_frac(vec_idx, numerator, p0 * p1);
+            }
+
+            logup_col_gen.finalize_col();
+        }
+        logup_gen.finalize_last()
+    }
+}
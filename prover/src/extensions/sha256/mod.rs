@@ -0,0 +1,75 @@
+//! Components proving the `sha256_compress` custom instruction.
+//!
+//! [`Sha256MemoryCheck`] checks memory accesses of every call and passes the input to [`Sha256Round`], which applies
+//! 64 rounds of the compression function and returns working variables to the memory checking component.
+
+pub(crate) mod memory_check;
+pub(crate) mod round;
+
+pub(crate) use memory_check::Sha256MemoryCheck;
+pub(crate) use round::Sha256Round;
+
+use super::ExtensionComponent;
+
+pub const fn sha256_extensions() -> &'static [ExtensionComponent] {
+    // A constant rather than a promoted temporary: the enum has variants with destructors.
+    const EXTENSIONS: &[ExtensionComponent] = &[
+        ExtensionComponent::Sha256MemoryCheck(Sha256MemoryCheck { _private: () }),
+        ExtensionComponent::Sha256Round(Sha256Round { _private: () }),
+    ];
+    EXTENSIONS
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        config::DEFAULT_MIN_SECURITY_BITS,
+        machine::{BaseComponent, Machine},
+    };
+    use nexus_vm::{
+        riscv::{BasicBlock, BuiltinOpcode, Instruction, Opcode},
+        trace::k_trace_direct,
+    };
+
+    use super::sha256_extensions;
+
+    #[test]
+    fn prove_execution_with_sha256() {
+        let mut instructions = vec![
+            // Create usable addresses for the state and the block, x2 = 0x81008, x3 = x2 + 32
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::ADDI), 1, 0, 1),
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::SLLI), 1, 1, 19),
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::ADD), 2, 1, 2),
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::ADDI), 3, 2, 32),
+        ];
+        let sha256_inst = Instruction::new_ir(
+            Opcode::new(0b1011010, Some(0b001), None, "sha256_compress"),
+            2,
+            3,
+            0,
+        );
+        instructions.extend(vec![sha256_inst; 20]);
+
+        let basic_block = vec![BasicBlock::new(instructions)];
+        let (view, program_trace) =
+            k_trace_direct(&basic_block, 1).expect("error generating trace");
+
+        let proof = Machine::<BaseComponent>::prove_with_extensions(
+            sha256_extensions(),
+            &program_trace,
+            &view,
+        )
+        .unwrap();
+        Machine::<BaseComponent>::verify_with_extensions(
+            sha256_extensions(),
+            DEFAULT_MIN_SECURITY_BITS,
+            proof,
+            view.get_program_memory(),
+            &[],
+            view.get_initial_memory(),
+            view.get_exit_code(),
+            view.get_public_output(),
+        )
+        .unwrap();
+    }
+}
@@ -0,0 +1,281 @@
+use num_traits::{One, Zero};
+use stwo_prover::{
+    constraint_framework::{preprocessed_columns::PreProcessedColumnId, EvalAtRow, RelationEntry},
+    core::fields::m31::BaseField,
+};
+
+use crate::components::lookups::Sha256StateLookupElements;
+
+use super::trace::{BITS_WINDOW_INDICES, PREPROCESSED_COLUMN_IDS};
+
+pub struct Sha256RoundEval<'a, E> {
+    pub(crate) eval: E,
+    pub(crate) state_lookup_elements: &'a Sha256StateLookupElements,
+}
+
+impl<E: EvalAtRow> Sha256RoundEval<'_, E> {
+    pub fn eval(mut self) -> E {
+        let [id, round, k_low, k_high, is_last] = PREPROCESSED_COLUMN_IDS.map(|id| {
+            self.eval
+                .get_preprocessed_column(PreProcessedColumnId { id: id.to_string() })
+        });
+
+        let a = self.next_bits();
+        let b = self.next_bits();
+        let c = self.next_bits();
+        let d = self.next_halves();
+        let e = self.next_bits();
+        let f = self.next_bits();
+        let g = self.next_bits();
+        let h = self.next_halves();
+
+        let mut w1 = vec![];
+        let mut w14 = vec![];
+        let mut window = vec![];
+        for j in 0..16 {
+            if BITS_WINDOW_INDICES.contains(&j) {
+                let bits = self.next_bits();
+                window.push(Self::halves(&bits));
+                if j == 1 {
+                    w1 = bits;
+                } else {
+                    w14 = bits;
+                }
+            } else {
+                window.push(self.next_halves());
+            }
+        }
+
+        let new_a = self.next_bits();
+        let new_e = self.next_bits();
+        let new_w = self.next_halves();
+
+        let big_sigma0_xor = self.next_bits();
+        let big_sigma1_xor = self.next_bits();
+        let sigma0_xor = self.next_bits();
+        let sigma1_xor = self.next_bits();
+        let maj_ab = self.next_bits();
+
+        let new_a_carries = [self.next_carry(3), self.next_carry(3)];
+        let new_e_carries = [self.next_carry(3), self.next_carry(3)];
+        let new_w_carries = [self.next_carry(2), self.next_carry(2)];
+
+        let is_padding = self.eval.next_trace_mask();
+        self.eval
+            .add_constraint(is_padding.clone() * (E::F::one() - is_padding.clone()));
+
+        for bits in [&a, &b, &c, &e, &f, &g, &w1, &w14, &new_a, &new_e] {
+            for bit in bits {
+                self.eval
+                    .add_constraint(bit.clone() * (E::F::one() - bit.clone()));
+            }
+        }
+
+        // Σ0(a) = rotr(a, 2) ^ rotr(a, 13) ^ rotr(a, 22)
+        let big_sigma0 = self.xor3(
+            &big_sigma0_xor,
+            &Self::rotr(&a, 2),
+            &Self::rotr(&a, 13),
+            &Self::rotr(&a, 22),
+        );
+        // Σ1(e) = rotr(e, 6) ^ rotr(e, 11) ^ rotr(e, 25)
+        let big_sigma1 = self.xor3(
+            &big_sigma1_xor,
+            &Self::rotr(&e, 6),
+            &Self::rotr(&e, 11),
+            &Self::rotr(&e, 25),
+        );
+        // σ0(w[1]) = rotr(w[1], 7) ^ rotr(w[1], 18) ^ shr(w[1], 3)
+        let sigma0 = self.xor3(
+            &sigma0_xor,
+            &Self::rotr(&w1, 7),
+            &Self::rotr(&w1, 18),
+            &Self::shr(&w1, 3),
+        );
+        // σ1(w[14]) = rotr(w[14], 17) ^ rotr(w[14], 19) ^ shr(w[14], 10)
+        let sigma1 = self.xor3(
+            &sigma1_xor,
+            &Self::rotr(&w14, 17),
+            &Self::rotr(&w14, 19),
+            &Self::shr(&w14, 10),
+        );
+
+        // Ch(e, f, g) = e・f + (1 - e)・g
+        let ch: Vec<E::F> = (0..32)
+            .map(|i| e[i].clone() * f[i].clone() + (E::F::one() - e[i].clone()) * g[i].clone())
+            .collect();
+        // Maj(a, b, c) = a・b + c・(a ^ b), where a ^ b = a + b - 2・a・b
+        let maj: Vec<E::F> = (0..32)
+            .map(|i| {
+                self.eval
+                    .add_constraint(maj_ab[i].clone() - a[i].clone() * b[i].clone());
+                maj_ab[i].clone()
+                    + c[i].clone()
+                        * (a[i].clone() + b[i].clone() - maj_ab[i].clone() * BaseField::from(2u32))
+            })
+            .collect();
+
+        let k = [k_low, k_high];
+        let t1 = [
+            h.clone(),
+            Self::halves(&big_sigma1),
+            Self::halves(&ch),
+            k,
+            window[0].clone(),
+        ];
+        let t2 = [Self::halves(&big_sigma0), Self::halves(&maj)];
+
+        // new_e = d + T1
+        let new_e_terms: Vec<[E::F; 2]> = std::iter::once(d.clone())
+            .chain(t1.iter().cloned())
+            .collect();
+        self.constrain_add(Self::halves(&new_e), new_e_carries, &new_e_terms);
+        // new_a = T1 + T2
+        let new_a_terms: Vec<[E::F; 2]> = t1.iter().chain(&t2).cloned().collect();
+        self.constrain_add(Self::halves(&new_a), new_a_carries, &new_a_terms);
+        // new_w = σ1(w[14]) + w[9] + σ0(w[1]) + w[0]
+        let new_w_terms = [
+            Self::halves(&sigma1),
+            window[9].clone(),
+            Self::halves(&sigma0),
+            window[0].clone(),
+        ];
+        self.constrain_add(new_w.clone(), new_w_carries, &new_w_terms);
+
+        let next_round = round.clone() + E::F::one();
+        let vars = [
+            Self::halves(&a),
+            Self::halves(&b),
+            Self::halves(&c),
+            d,
+            Self::halves(&e),
+            Self::halves(&f),
+            Self::halves(&g),
+            h,
+        ];
+        let next_vars = [
+            Self::halves(&new_a),
+            vars[0].clone(),
+            vars[1].clone(),
+            vars[2].clone(),
+            Self::halves(&new_e),
+            vars[4].clone(),
+            vars[5].clone(),
+            vars[6].clone(),
+        ];
+
+        let input_state: Vec<E::F> = [id.clone(), round]
+            .into_iter()
+            .chain(vars.into_iter().flatten())
+            .chain(window.iter().cloned().flatten())
+            .collect();
+        let output_vars: Vec<E::F> = [id, next_round]
+            .into_iter()
+            .chain(next_vars.into_iter().flatten())
+            .collect();
+        let output_state: Vec<E::F> = output_vars
+            .iter()
+            .cloned()
+            .chain(window[1..].iter().cloned().flatten())
+            .chain(new_w)
+            .collect();
+
+        let is_real = E::F::one() - is_padding;
+        self.eval.add_to_relation(RelationEntry::new(
+            self.state_lookup_elements,
+            (-is_real.clone()).into(),
+            &input_state,
+        ));
+        self.eval.add_to_relation(RelationEntry::new(
+            self.state_lookup_elements,
+            (is_real.clone() * (E::F::one() - is_last.clone())).into(),
+            &output_state,
+        ));
+        // The last round only returns working variables, the message schedule isn't needed anymore.
+        self.eval.add_to_relation(RelationEntry::new(
+            self.state_lookup_elements,
+            (is_real * is_last).into(),
+            &output_vars,
+        ));
+
+        self.eval.finalize_logup();
+
+        self.eval
+    }
+
+    fn next_bits(&mut self) -> Vec<E::F> {
+        std::iter::repeat_with(|| self.eval.next_trace_mask())
+            .take(32)
+            .collect()
+    }
+
+    fn next_halves(&mut self) -> [E::F; 2] {
+        std::array::from_fn(|_| self.eval.next_trace_mask())
+    }
+
+    /// Reads bits of a carry, returns its value.
+    fn next_carry(&mut self, num_bits: usize) -> E::F {
+        let mut carry = E::F::zero();
+        for i in 0..num_bits {
+            let bit = self.eval.next_trace_mask();
+            self.eval
+                .add_constraint(bit.clone() * (E::F::one() - bit.clone()));
+            carry = carry + bit * BaseField::from(1 << i);
+        }
+        carry
+    }
+
+    fn halves(bits: &[E::F]) -> [E::F; 2] {
+        std::array::from_fn(|half| {
+            bits[half * 16..(half + 1) * 16]
+                .iter()
+                .enumerate()
+                .fold(E::F::zero(), |acc, (i, bit)| {
+                    acc + bit.clone() * BaseField::from(1 << i)
+                })
+        })
+    }
+
+    fn rotr(bits: &[E::F], n: usize) -> Vec<E::F> {
+        (0..32).map(|i| bits[(i + n) % 32].clone()).collect()
+    }
+
+    fn shr(bits: &[E::F], n: usize) -> Vec<E::F> {
+        (0..32)
+            .map(|i| bits.get(i + n).cloned().unwrap_or_else(E::F::zero))
+            .collect()
+    }
+
+    fn xor(x: &E::F, y: &E::F) -> E::F {
+        x.clone() + y.clone() - x.clone() * y.clone() * BaseField::from(2u32)
+    }
+
+    /// Constrains `xor_col` to x ^ y and returns bits of x ^ y ^ z.
+    fn xor3(&mut self, xor_col: &[E::F], x: &[E::F], y: &[E::F], z: &[E::F]) -> Vec<E::F> {
+        (0..32)
+            .map(|i| {
+                self.eval
+                    .add_constraint(xor_col[i].clone() - Self::xor(&x[i], &y[i]));
+                Self::xor(&xor_col[i], &z[i])
+            })
+            .collect()
+    }
+
+    /// Constrains `result` to the sum of `terms` modulo 2^32, carries are the ones of the low and the high half.
+    fn constrain_add(&mut self, result: [E::F; 2], carries: [E::F; 2], terms: &[[E::F; 2]]) {
+        let modulus = E::F::from(BaseField::from(1 << 16));
+        let [result_low, result_high] = result;
+        let [carry_low, carry_high] = carries;
+
+        let sum_low = terms
+            .iter()
+            .fold(E::F::zero(), |acc, term| acc + term[0].clone());
+        let sum_high = terms
+            .iter()
+            .fold(carry_low.clone(), |acc, term| acc + term[1].clone());
+        self.eval
+            .add_constraint(result_low + carry_low * modulus.clone() - sum_low);
+        self.eval
+            .add_constraint(result_high + carry_high * modulus - sum_high);
+    }
+}
@@ -0,0 +1,237 @@
+use stwo_prover::{
+    constraint_framework::{EvalAtRow, FrameworkEval},
+    core::{
+        backend::simd::{m31::LOG_N_LANES, SimdBackend},
+        fields::{m31::BaseField, qm31::SecureField},
+        poly::{
+            circle::{CanonicCoset, CircleEvaluation},
+            BitReversedOrder,
+        },
+        ColumnVec,
+    },
+};
+
+use nexus_vm::cpu::instructions::custom::sha256::ROUND_CONSTANTS;
+
+use crate::{
+    components::{lookups::Sha256StateLookupElements, AllLookupElements},
+    extensions::{BuiltInExtension, ComponentTrace, FrameworkEvalExt},
+    trace::{program_trace::ProgramTraceRef, sidenote::SideNote},
+};
+
+mod constraints;
+pub(crate) mod trace;
+
+/// Number of rounds of the compression function, each call takes this many rows.
+pub(crate) const ROUNDS: usize = 64;
+pub(crate) const LOG_ROUNDS: u32 = ROUNDS.ilog2();
+
+/// Proves rounds of the compression function, one round per row.
+///
+/// Rows of a call are consecutive, the compression id and the round index of a row are preprocessed. Every row takes
+/// the working variables and sixteen message words of the round from [`Sha256StateLookupElements`] and sends them to
+/// the next round, the last round sends the working variables back to [`Sha256MemoryCheck`](super::Sha256MemoryCheck).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Sha256Round {
+    pub(crate) _private: (),
+}
+
+pub(crate) struct Sha256RoundEval {
+    log_size: u32,
+    state_lookup_elements: Sha256StateLookupElements,
+}
+
+impl FrameworkEval for Sha256RoundEval {
+    fn log_size(&self) -> u32 {
+        self.log_size
+    }
+
+    fn max_constraint_log_degree_bound(&self) -> u32 {
+        self.log_size + 1
+    }
+
+    fn evaluate<E: EvalAtRow>(&self, eval: E) -> E {
+        constraints::Sha256RoundEval {
+            eval,
+            state_lookup_elements: &self.state_lookup_elements,
+        }
+        .eval()
+    }
+}
+
+impl FrameworkEvalExt for Sha256RoundEval {
+    fn new(log_size: u32, lookup_elements: &AllLookupElements) -> Self {
+        let state_lookup_elements: &Sha256StateLookupElements = lookup_elements.as_ref();
+        Self {
+            log_size,
+            state_lookup_elements: state_lookup_elements.clone(),
+        }
+    }
+
+    fn dummy(log_size: u32) -> Self {
+        Self {
+            log_size,
+            state_lookup_elements: Sha256StateLookupElements::dummy(),
+        }
+    }
+}
+
+impl BuiltInExtension for Sha256Round {
+    type Eval = Sha256RoundEval;
+
+    fn generate_preprocessed_trace(
+        &self,
+        log_size: u32,
+        _: ProgramTraceRef,
+    ) -> ColumnVec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>> {
+        let domain = CanonicCoset::new(log_size).circle_domain();
+        trace::preprocessed_columns(log_size)
+            .into_iter()
+            .map(|col| CircleEvaluation::new(domain, col))
+            .collect()
+    }
+
+    fn generate_component_trace(
+        &self,
+        log_size: u32,
+        _: ProgramTraceRef,
+        side_note: &mut SideNote,
+    ) -> ComponentTrace {
+        trace::generate_round_trace(log_size, &side_note.sha256.inputs)
+    }
+
+    fn generate_interaction_trace(
+        &self,
+        component_trace: ComponentTrace,
+        side_note: &SideNote,
+        lookup_elements: &AllLookupElements,
+    ) -> (
+        ColumnVec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>>,
+        SecureField,
+    ) {
+        let state_lookup_elements: &Sha256StateLookupElements = lookup_elements.as_ref();
+        trace::round_interaction_trace(
+            &component_trace,
+            &side_note.sha256.inputs,
+            state_lookup_elements,
+        )
+    }
+
+    fn compute_log_size(&self, side_note: &SideNote) -> u32 {
+        let num_inputs = side_note.sha256.inputs.len();
+        let log_size = num_inputs.next_power_of_two().ilog2();
+
+        // Keep the number of calls in line with the memory checking component.
+        log_size.max(LOG_N_LANES) + LOG_ROUNDS
+    }
+
+    fn preprocessed_trace_sizes(log_size: u32) -> Vec<u32> {
+        // compression id, round index, round constant halves, is_last
+        vec![log_size; 5]
+    }
+}
+
+/// Working variables and message words at the start of a round.
+///
+/// The message window holds the word used by the round followed by the next fifteen words of the schedule.
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct RoundState {
+    pub(crate) vars: [u32; 8],
+    pub(crate) window: [u32; 16],
+}
+
+impl RoundState {
+    pub(crate) fn sigma0(x: u32) -> u32 {
+        x.rotate_right(7) ^ x.rotate_right(18) ^ (x >> 3)
+    }
+
+    pub(crate) fn sigma1(x: u32) -> u32 {
+        x.rotate_right(17) ^ x.rotate_right(19) ^ (x >> 10)
+    }
+
+    pub(crate) fn big_sigma0(x: u32) -> u32 {
+        x.rotate_right(2) ^ x.rotate_right(13) ^ x.rotate_right(22)
+    }
+
+    pub(crate) fn big_sigma1(x: u32) -> u32 {
+        x.rotate_right(6) ^ x.rotate_right(11) ^ x.rotate_right(25)
+    }
+
+    /// Returns the next word of the message schedule.
+    pub(crate) fn next_word(&self) -> u32 {
+        let w = &self.window;
+        Self::sigma1(w[14])
+            .wrapping_add(w[9])
+            .wrapping_add(Self::sigma0(w[1]))
+            .wrapping_add(w[0])
+    }
+
+    /// Returns (T1, T2) of the round.
+    pub(crate) fn temps(&self, round: usize) -> (u32, u32) {
+        let [a, b, c, _d, e, f, g, h] = self.vars;
+        let ch = (e & f) ^ (!e & g);
+        let maj = (a & b) ^ (a & c) ^ (b & c);
+        let t1 = h
+            .wrapping_add(Self::big_sigma1(e))
+            .wrapping_add(ch)
+            .wrapping_add(ROUND_CONSTANTS[round])
+            .wrapping_add(self.window[0]);
+        let t2 = Self::big_sigma0(a).wrapping_add(maj);
+        (t1, t2)
+    }
+
+    /// Applies the round and shifts the message window.
+    pub(crate) fn next(&self, round: usize) -> Self {
+        let [a, b, c, d, e, f, g, _h] = self.vars;
+        let (t1, t2) = self.temps(round);
+
+        let mut window = [0u32; 16];
+        window[..15].copy_from_slice(&self.window[1..]);
+        window[15] = self.next_word();
+
+        Self {
+            vars: [t1.wrapping_add(t2), a, b, c, d.wrapping_add(t1), e, f, g],
+            window,
+        }
+    }
+
+    /// Returns states at the start of each round, followed by the state after the last round.
+    pub(crate) fn rounds(state: &[u32; 8], block: &[u32; 16]) -> Vec<Self> {
+        let mut states = Vec::with_capacity(ROUNDS + 1);
+        states.push(Self {
+            vars: *state,
+            window: *block,
+        });
+        for round in 0..ROUNDS {
+            let next = states[round].next(round);
+            states.push(next);
+        }
+        states
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nexus_vm::cpu::instructions::custom::sha256::sha256_compress;
+    use rand::{RngCore, SeedableRng};
+    use rand_chacha::ChaCha12Rng;
+
+    use super::*;
+
+    #[test]
+    fn test_sha256_rounds() {
+        let mut rng = ChaCha12Rng::from_seed(Default::default());
+        for _ in 0..10 {
+            let state: [u32; 8] = std::array::from_fn(|_| rng.next_u32());
+            let block: [u32; 16] = std::array::from_fn(|_| rng.next_u32());
+
+            let last = RoundState::rounds(&state, &block)[ROUNDS];
+            let result: [u32; 8] = std::array::from_fn(|i| state[i].wrapping_add(last.vars[i]));
+
+            let mut expected = state;
+            sha256_compress(&mut expected, &block);
+
+            assert_eq!(result, expected);
+        }
+    }
+}
@@ -0,0 +1,288 @@
+use num_traits::One;
+use stwo_prover::{
+    constraint_framework::{logup::LogupTraceGenerator, Relation},
+    core::{
+        backend::simd::{
+            column::BaseColumn,
+            m31::{PackedM31, LOG_N_LANES},
+            qm31::PackedSecureField,
+            SimdBackend,
+        },
+        fields::{m31::BaseField, qm31::SecureField},
+        poly::{circle::CircleEvaluation, BitReversedOrder},
+        ColumnVec,
+    },
+};
+
+use nexus_vm::cpu::instructions::custom::sha256::ROUND_CONSTANTS;
+
+use super::{RoundState, LOG_ROUNDS, ROUNDS};
+use crate::{
+    components::lookups::Sha256StateLookupElements,
+    extensions::{keccak::round::trace::get_is_padding_base_column, ComponentTrace},
+};
+
+pub(super) const PREPROCESSED_COLUMN_IDS: [&str; 5] = [
+    "sha256_round_compression_id",
+    "sha256_round_index",
+    "sha256_round_constant_low",
+    "sha256_round_constant_high",
+    "sha256_round_is_last",
+];
+
+/// Message words that are rotated by the schedule, these are stored as bits, other words are stored as halves.
+pub(super) const BITS_WINDOW_INDICES: [usize; 2] = [1, 14];
+
+const MASK: u32 = (1 << 16) - 1;
+
+pub(crate) fn preprocessed_columns(log_size: u32) -> Vec<BaseColumn> {
+    let rows = 0..1u32 << log_size;
+    let round = |row: u32| row & (ROUNDS as u32 - 1);
+    let round_constant = |row: u32| ROUND_CONSTANTS[round(row) as usize];
+    vec![
+        rows.clone()
+            .map(|row| BaseField::from(row >> LOG_ROUNDS))
+            .collect(),
+        rows.clone()
+            .map(|row| BaseField::from(round(row)))
+            .collect(),
+        rows.clone()
+            .map(|row| BaseField::from(round_constant(row) & MASK))
+            .collect(),
+        rows.clone()
+            .map(|row| BaseField::from(round_constant(row) >> 16))
+            .collect(),
+        rows.map(|row| BaseField::from(u32::from(round(row) == ROUNDS as u32 - 1)))
+            .collect(),
+    ]
+}
+
+pub(crate) fn generate_round_trace(
+    log_size: u32,
+    inputs: &[([u32; 8], [u32; 16])],
+) -> ComponentTrace {
+    let num_rows = 1 << log_size;
+    let mut original_trace: Vec<Vec<BaseField>> = vec![];
+
+    for (row, (state, round)) in padded_rounds(log_size, inputs).enumerate() {
+        let values = row_values(&state, round);
+        if original_trace.is_empty() {
+            original_trace = vec![Vec::with_capacity(num_rows); values.len()];
+        }
+        for (col, value) in original_trace.iter_mut().zip(values) {
+            col.push(BaseField::from(value));
+        }
+    }
+
+    let mut original_trace: Vec<BaseColumn> = original_trace
+        .into_iter()
+        .map(BaseColumn::from_iter)
+        .collect();
+    original_trace.push(get_is_padding_base_column(log_size, inputs.len() * ROUNDS));
+
+    ComponentTrace {
+        log_size,
+        preprocessed_trace: preprocessed_columns(log_size),
+        original_trace,
+    }
+}
+
+pub(crate) fn round_interaction_trace(
+    component_trace: &ComponentTrace,
+    inputs: &[([u32; 8], [u32; 16])],
+    lookup_elements: &Sha256StateLookupElements,
+) -> (
+    ColumnVec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>>,
+    SecureField,
+) {
+    let log_size = component_trace.log_size;
+    let num_rows = 1 << log_size;
+
+    // Input and output states of each round are recomputed rather than collected from the trace.
+    let tuple_size = 2 + 24 * 2;
+    let mut input_states = vec![Vec::with_capacity(num_rows); tuple_size];
+    let mut output_states = vec![Vec::with_capacity(num_rows); tuple_size];
+    for (row, (state, round)) in padded_rounds(log_size, inputs).enumerate() {
+        let id = row as u32 >> LOG_ROUNDS;
+        for (col, value) in input_states
+            .iter_mut()
+            .zip(state_tuple(id, round as u32, &state))
+        {
+            col.push(value);
+        }
+        for (col, value) in
+            output_states
+                .iter_mut()
+                .zip(state_tuple(id, round as u32 + 1, &state.next(round)))
+        {
+            col.push(value);
+        }
+    }
+    let input_states: Vec<BaseColumn> = input_states
+        .into_iter()
+        .map(BaseColumn::from_iter)
+        .collect();
+    let output_states: Vec<BaseColumn> = output_states
+        .into_iter()
+        .map(BaseColumn::from_iter)
+        .collect();
+
+    let is_padding = component_trace
+        .original_trace
+        .last()
+        .expect("trace must be non-empty");
+    let is_last = component_trace
+        .preprocessed_trace
+        .last()
+        .expect("preprocessed trace must be non-empty");
+
+    let mut logup_gen = LogupTraceGenerator::new(log_size);
+    let combine = |states: &[BaseColumn], vec_row: usize| -> PackedSecureField {
+        let tuple: Vec<PackedM31> = states.iter().map(|col| col.data[vec_row]).collect();
+        lookup_elements.combine(&tuple)
+    };
+    let one: PackedSecureField = SecureField::one().into();
+
+    // consume round input
+    let mut logup_col_gen = logup_gen.new_col();
+    for vec_row in 0..1 << (log_size - LOG_N_LANES) {
+        let is_padding = PackedSecureField::from(is_padding.data[vec_row]);
+        let denom = combine(&input_states, vec_row);
+        logup_col_gen.write_frac(vec_row, is_padding - one, denom);
+    }
+    logup_col_gen.finalize_col();
+
+    // send state to the next round
+    let mut logup_col_gen = logup_gen.new_col();
+    for vec_row in 0..1 << (log_size - LOG_N_LANES) {
+        let is_real = one - PackedSecureField::from(is_padding.data[vec_row]);
+        let is_last = PackedSecureField::from(is_last.data[vec_row]);
+        let denom = combine(&output_states, vec_row);
+        logup_col_gen.write_frac(vec_row, is_real * (one - is_last), denom);
+    }
+    logup_col_gen.finalize_col();
+
+    // return working variables after the last round
+    let mut logup_col_gen = logup_gen.new_col();
+    for vec_row in 0..1 << (log_size - LOG_N_LANES) {
+        let is_real = one - PackedSecureField::from(is_padding.data[vec_row]);
+        let is_last = PackedSecureField::from(is_last.data[vec_row]);
+        let denom = combine(&output_states[..2 + 8 * 2], vec_row);
+        logup_col_gen.write_frac(vec_row, is_real * is_last, denom);
+    }
+    logup_col_gen.finalize_col();
+
+    logup_gen.finalize_last()
+}
+
+/// Returns the state at the start of each row along with the round index, padding rows hold rounds of a zero state.
+fn padded_rounds(
+    log_size: u32,
+    inputs: &[([u32; 8], [u32; 16])],
+) -> impl Iterator<Item = (RoundState, usize)> + '_ {
+    let num_calls = 1 << (log_size - LOG_ROUNDS);
+    assert!(inputs.len() <= num_calls);
+
+    inputs
+        .iter()
+        .copied()
+        .chain(std::iter::repeat(([0u32; 8], [0u32; 16])))
+        .take(num_calls)
+        .flat_map(|(state, block)| {
+            RoundState::rounds(&state, &block)
+                .into_iter()
+                .take(ROUNDS)
+                .enumerate()
+                .map(|(round, state)| (state, round))
+        })
+}
+
+/// Compression id, round index, working variables and message window split into 16-bit halves.
+fn state_tuple(id: u32, round: u32, state: &RoundState) -> Vec<BaseField> {
+    [id, round]
+        .into_iter()
+        .chain(
+            state
+                .vars
+                .iter()
+                .chain(&state.window)
+                .flat_map(|word| [word & MASK, word >> 16]),
+        )
+        .map(BaseField::from)
+        .collect()
+}
+
+/// Returns values of original trace columns for a row, excluding the padding flag.
+fn row_values(state: &RoundState, round: usize) -> Vec<u32> {
+    fn bits(row: &mut Vec<u32>, word: u32) {
+        row.extend((0..32).map(|i| (word >> i) & 1));
+    }
+    fn halves(row: &mut Vec<u32>, word: u32) {
+        row.extend([word & MASK, word >> 16]);
+    }
+    fn carry_bits(row: &mut Vec<u32>, terms: &[u32], num_bits: u32) {
+        let sum_low: u32 = terms.iter().map(|term| term & MASK).sum();
+        let carry_low = sum_low >> 16;
+        let sum_high: u32 = terms.iter().map(|term| term >> 16).sum::<u32>() + carry_low;
+        let carry_high = sum_high >> 16;
+        for carry in [carry_low, carry_high] {
+            assert!(carry < 1 << num_bits);
+            row.extend((0..num_bits).map(|i| (carry >> i) & 1));
+        }
+    }
+
+    let mut row = vec![];
+    let [a, b, c, d, e, f, g, h] = state.vars;
+    let w = &state.window;
+
+    bits(&mut row, a);
+    bits(&mut row, b);
+    bits(&mut row, c);
+    halves(&mut row, d);
+    bits(&mut row, e);
+    bits(&mut row, f);
+    bits(&mut row, g);
+    halves(&mut row, h);
+    for (j, &word) in w.iter().enumerate() {
+        if BITS_WINDOW_INDICES.contains(&j) {
+            bits(&mut row, word);
+        } else {
+            halves(&mut row, word);
+        }
+    }
+
+    let next = state.next(round);
+    bits(&mut row, next.vars[0]);
+    bits(&mut row, next.vars[4]);
+    halves(&mut row, next.window[15]);
+
+    bits(&mut row, a.rotate_right(2) ^ a.rotate_right(13));
+    bits(&mut row, e.rotate_right(6) ^ e.rotate_right(11));
+    bits(&mut row, w[1].rotate_right(7) ^ w[1].rotate_right(18));
+    bits(&mut row, w[14].rotate_right(17) ^ w[14].rotate_right(19));
+    bits(&mut row, a & b);
+
+    let ch = (e & f) ^ (!e & g);
+    let maj = (a & b) ^ (a & c) ^ (b & c);
+    let t1 = [
+        h,
+        RoundState::big_sigma1(e),
+        ch,
+        ROUND_CONSTANTS[round],
+        w[0],
+    ];
+    let t2 = [RoundState::big_sigma0(a), maj];
+    let new_a_terms: Vec<u32> = t1.iter().chain(&t2).copied().collect();
+    let new_e_terms: Vec<u32> = std::iter::once(d).chain(t1).collect();
+    let new_w_terms = [
+        RoundState::sigma1(w[14]),
+        w[9],
+        RoundState::sigma0(w[1]),
+        w[0],
+    ];
+    carry_bits(&mut row, &new_a_terms, 3);
+    carry_bits(&mut row, &new_e_terms, 3);
+    carry_bits(&mut row, &new_w_terms, 2);
+
+    row
+}
@@ -12,6 +12,7 @@ use stwo_prover::{
 };
 
 /// Intermediate representation of the component trace.
+#[derive(Clone)]
 pub struct ComponentTrace {
     pub log_size: u32,
     pub preprocessed_trace: Vec<BaseColumn>,
@@ -6,10 +6,14 @@ pub mod extensions;
 pub mod trace;
 
 pub mod column;
+pub mod config;
 pub mod traits;
 pub mod virtual_column;
 
+pub mod debug;
+pub mod estimate;
 pub mod machine;
+pub mod segment;
 
 #[cfg(test)]
 mod test_utils;
@@ -17,19 +21,82 @@ mod test_utils;
 use nexus_vm::emulator::InternalView;
 pub(crate) use nexus_vm::WORD_SIZE;
 
-pub use machine::Proof;
+pub use config::{ProverConfig, DEFAULT_MIN_SECURITY_BITS};
+pub use estimate::{CostEstimate, CostModel, InstructionFamily};
+pub use extensions::Extension;
+pub use machine::{Proof, VerifyingKey};
+pub use segment::{BoundaryState, SegmentProof};
 
-pub use stwo_prover::core::prover::{ProvingError, VerificationError};
+pub use stwo_prover::core::{
+    channel::MerkleChannel,
+    prover::{ProvingError, VerificationError},
+    vcs::{blake2_merkle::Blake2sMerkleChannel, poseidon252_merkle::Poseidon252MerkleChannel},
+};
+
+use stwo_prover::core::backend::{simd::SimdBackend, BackendForChannel};
 
 pub fn prove(
-    trace: &impl nexus_vm::trace::Trace,
+    trace: &impl nexus_vm::trace::TraceSource,
     view: &nexus_vm::emulator::View,
 ) -> Result<Proof, ProvingError> {
     machine::Machine::<machine::BaseComponent>::prove(trace, view)
 }
 
-pub fn verify(proof: Proof, view: &nexus_vm::emulator::View) -> Result<(), VerificationError> {
-    machine::Machine::<machine::BaseComponent>::verify(
+pub fn prove_with_extensions(
+    extensions: &[Extension],
+    trace: &impl nexus_vm::trace::TraceSource,
+    view: &nexus_vm::emulator::View,
+) -> Result<Proof, ProvingError> {
+    machine::Machine::<machine::BaseComponent>::prove_with_extensions(
+        &Extension::to_components(extensions),
+        trace,
+        view,
+    )
+}
+
+/// Proves the execution with the given security parameters, committing to the trace with the Merkle hasher of `MC`.
+///
+/// Panics if `config` provides fewer than [`DEFAULT_MIN_SECURITY_BITS`] bits of security.
+pub fn prove_with_config<MC: MerkleChannel>(
+    extensions: &[Extension],
+    config: ProverConfig,
+    trace: &impl nexus_vm::trace::TraceSource,
+    view: &nexus_vm::emulator::View,
+) -> Result<Proof<MC>, ProvingError>
+where
+    SimdBackend: BackendForChannel<MC>,
+{
+    machine::Machine::<machine::BaseComponent, MC>::prove_with_config(
+        &Extension::to_components(extensions),
+        config,
+        trace,
+        view,
+    )
+}
+
+/// Estimates the cost of proving the execution with the given configuration, without proving it.
+pub fn estimate(
+    extensions: &[Extension],
+    config: ProverConfig,
+    trace: &impl nexus_vm::trace::TraceSource,
+    view: &nexus_vm::emulator::View,
+) -> CostEstimate {
+    machine::Machine::<machine::BaseComponent>::estimate(
+        &Extension::to_components(extensions),
+        config,
+        trace,
+        view,
+    )
+}
+
+pub fn verify<MC: MerkleChannel>(
+    proof: Proof<MC>,
+    view: &nexus_vm::emulator::View,
+) -> Result<(), VerificationError>
+where
+    SimdBackend: BackendForChannel<MC>,
+{
+    machine::Machine::<machine::BaseComponent, MC>::verify(
         proof,
         view.get_program_memory(),
         view.view_associated_data().as_deref().unwrap_or_default(),
@@ -38,3 +105,102 @@ pub fn verify(proof: Proof, view: &nexus_vm::emulator::View) -> Result<(), Verif
         view.get_public_output(),
     )
 }
+
+/// Verifies the proof, rejecting configurations with fewer than `min_security_bits` bits of security.
+pub fn verify_with_extensions<MC: MerkleChannel>(
+    extensions: &[Extension],
+    min_security_bits: u32,
+    proof: Proof<MC>,
+    view: &nexus_vm::emulator::View,
+) -> Result<(), VerificationError>
+where
+    SimdBackend: BackendForChannel<MC>,
+{
+    machine::Machine::<machine::BaseComponent, MC>::verify_with_extensions(
+        &Extension::to_components(extensions),
+        min_security_bits,
+        proof,
+        view.get_program_memory(),
+        view.view_associated_data().as_deref().unwrap_or_default(),
+        view.get_initial_memory(),
+        view.get_exit_code(),
+        view.get_public_output(),
+    )
+}
+
+/// Precomputes the verifying key of proofs of the program in `view`, see [`VerifyingKey`].
+///
+/// Only the program and the initial memory are read from `view`.
+pub fn setup<MC: MerkleChannel>(
+    extensions: &[Extension],
+    config: ProverConfig,
+    min_security_bits: u32,
+    view: &nexus_vm::emulator::View,
+) -> Result<VerifyingKey<MC>, VerificationError>
+where
+    SimdBackend: BackendForChannel<MC>,
+{
+    machine::Machine::<machine::BaseComponent, MC>::setup(
+        extensions,
+        config,
+        min_security_bits,
+        view.get_program_memory(),
+        view.get_initial_memory(),
+    )
+}
+
+/// Precomputes the commitment to the preprocessed trace of proofs with the given log sizes, see [`VerifyingKey`].
+pub fn prepare<MC: MerkleChannel>(
+    key: &mut VerifyingKey<MC>,
+    log_size: &[u32],
+) -> Result<(), VerificationError>
+where
+    SimdBackend: BackendForChannel<MC>,
+{
+    machine::Machine::<machine::BaseComponent, MC>::prepare(key, log_size)
+}
+
+/// Verifies the proof against the key, the associated data, the exit code and the public output are read from `view`.
+///
+/// Proofs whose log sizes weren't added to the key with [`prepare`] are rejected.
+pub fn verify_with_key<MC: MerkleChannel>(
+    key: &VerifyingKey<MC>,
+    proof: Proof<MC>,
+    view: &nexus_vm::emulator::View,
+) -> Result<(), VerificationError>
+where
+    SimdBackend: BackendForChannel<MC>,
+{
+    machine::Machine::<machine::BaseComponent, MC>::verify_with_key(
+        key,
+        proof,
+        view.view_associated_data().as_deref().unwrap_or_default(),
+        view.get_exit_code(),
+        view.get_public_output(),
+    )
+}
+
+pub fn prove_segments(
+    trace: &impl nexus_vm::trace::TraceSource,
+    view: &nexus_vm::emulator::View,
+    segment_size: usize,
+) -> Result<Vec<SegmentProof>, ProvingError> {
+    machine::Machine::<machine::BaseComponent>::prove_segments(trace, view, segment_size)
+}
+
+pub fn verify_segments<MC: MerkleChannel>(
+    proofs: Vec<SegmentProof<MC>>,
+    view: &nexus_vm::emulator::View,
+) -> Result<(), VerificationError>
+where
+    SimdBackend: BackendForChannel<MC>,
+{
+    machine::Machine::<machine::BaseComponent, MC>::verify_segments(
+        proofs,
+        view.get_program_memory(),
+        view.view_associated_data().as_deref().unwrap_or_default(),
+        view.get_initial_memory(),
+        view.get_exit_code(),
+        view.get_public_output(),
+    )
+}
@@ -1,28 +1,37 @@
-use std::marker::PhantomData;
+use std::{borrow::Cow, collections::BTreeMap, marker::PhantomData, ops::Range, time::Duration};
 
+use itertools::Itertools;
 use num_traits::Zero;
+use rayon::iter::{
+    IndexedParallelIterator, IntoParallelIterator, IntoParallelRefIterator, ParallelIterator,
+};
 use stwo_prover::{
     constraint_framework::TraceLocationAllocator,
     core::{
         air::{Component, ComponentProver},
-        backend::simd::SimdBackend,
-        channel::{Blake2sChannel, Channel},
-        fields::qm31::SecureField,
+        backend::{simd::SimdBackend, BackendForChannel},
+        channel::{Channel, MerkleChannel},
+        fields::{m31::BaseField, qm31::SecureField},
         pcs::{CommitmentSchemeProver, CommitmentSchemeVerifier, PcsConfig, TreeVec},
         poly::circle::{CanonicCoset, PolyOps},
         prover::{prove, verify, ProvingError, StarkProof, VerificationError},
-        vcs::blake2_merkle::{Blake2sMerkleChannel, Blake2sMerkleHasher},
+        vcs::{blake2_merkle::Blake2sMerkleChannel, ops::MerkleHasher},
     },
 };
 
 use super::trace::eval::{INTERACTION_TRACE_IDX, ORIGINAL_TRACE_IDX, PREPROCESSED_TRACE_IDX};
 use super::trace::{
-    program::iter_program_steps, program_trace::ProgramTracesBuilder, sidenote::SideNote,
+    program::{iter_program_steps, ProgramStep},
+    program_trace::{program_memory_range, ProgramTracesBuilder, SegmentBoundaryRef},
+    regs::RegisterMemCheckSideNote,
+    sidenote::SideNote,
+    utils::FromBaseFields,
     PreprocessedTraces, TracesBuilder,
 };
+use nexus_common::riscv::register::NUM_REGISTERS;
 use nexus_vm::{
     emulator::{InternalView, MemoryInitializationEntry, ProgramInfo, PublicOutputEntry, View},
-    trace::Trace,
+    trace::TraceSource,
 };
 
 use super::components::{MachineComponent, MachineEval, LOG_CONSTRAINT_DEGREE};
@@ -30,18 +39,26 @@ use super::traits::MachineChip;
 use crate::{
     chips::{
         AddChip, AuipcChip, BeqChip, BgeChip, BgeuChip, BitOpChip, BltChip, BltuChip, BneChip,
-        CpuChip, CustomInstructionChip, DecodingCheckChip, JalChip, JalrChip, LoadStoreChip,
-        LuiChip, ProgramMemCheckChip, RangeCheckChip, RegisterMemCheckChip, SllChip, SltChip,
-        SltuChip, SraChip, SrlChip, SubChip, SyscallChip, TimestampChip,
+        CpuChip, CustomInstructionChip, DecodingCheckChip, DivRemChip, JalChip, JalrChip,
+        LoadStoreChip, LuiChip, MulChip, MulhChip, ProgramMemCheckChip, RangeCheckChip,
+        RegisterMemCheckChip, SllChip, SltChip, SltuChip, SraChip, SrlChip, SubChip, SyscallChip,
+        TimestampChip,
     },
-    column::{PreprocessedColumn, ProgramColumn},
+    column::{Column, PreprocessedColumn, ProgramColumn},
     components::{self, AllLookupElements},
-    extensions::{ComponentTrace, ExtensionComponent, ExtensionsConfig},
+    config::{ProverConfig, DEFAULT_MIN_SECURITY_BITS},
+    debug::{self, ConstraintViolation, DebugReport, InteractionTrace},
+    estimate::{self, CostEstimate, CostModel, InstructionFamily},
+    extensions::{
+        proven_output_entries, proven_output_sum, ComponentTrace, Extension, ExtensionComponent,
+        ExtensionsConfig,
+    },
+    segment::{self, BoundaryState, SegmentBlocks, SegmentProof},
     trace::program_trace::ProgramTraceRef,
     traits::generate_interaction_trace,
 };
 use serde::{Deserialize, Serialize};
-/// Base component tuple for constraining virtual machine execution based on RV32I ISA.
+/// Base component tuple for constraining virtual machine execution based on RV32IM ISA.
 pub type BaseComponent = (
     CpuChip,
     DecodingCheckChip,
@@ -63,6 +80,9 @@ pub type BaseComponent = (
     SllChip,
     SrlChip,
     SraChip,
+    MulChip,
+    MulhChip,
+    DivRemChip,
     LoadStoreChip,
     SyscallChip,
     CustomInstructionChip,
@@ -72,6 +92,10 @@ pub type BaseComponent = (
     // Range checks must be positioned at the end. They use values filled by instruction chips.
     RangeCheckChip,
 );
+
+/// Minimum number of rows in a chunk of the main trace filled by a single thread.
+const MIN_CHUNK_SIZE: usize = 1 << 12;
+
 /// Base extensions used in conjunction with [`BaseComponent`]. These components are always enabled and are not accessible
 /// to downstream crates. ram_init_final() modifies multiplicities for multiplicity256(), so the ordering between these is important.
 const BASE_EXTENSIONS: &[ExtensionComponent] = &[
@@ -85,79 +109,661 @@ const BASE_EXTENSIONS: &[ExtensionComponent] = &[
     ExtensionComponent::multiplicity256(),
 ];
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct Proof {
-    pub stark_proof: StarkProof<Blake2sMerkleHasher>,
+/// Proof of the execution, committed with the Merkle hasher of `MC`.
+#[derive(Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "StarkProof<MC::H>: Serialize",
+    deserialize = "StarkProof<MC::H>: Deserialize<'de>"
+))]
+pub struct Proof<MC: MerkleChannel = Blake2sMerkleChannel> {
+    pub stark_proof: StarkProof<MC::H>,
     pub claimed_sum: Vec<SecureField>, // one per component
     pub log_size: Vec<u32>,            // one per component
+    pub config: ProverConfig,
+}
+
+// Merkle channels are marker types which don't implement common traits, hence no derives.
+impl<MC: MerkleChannel> Clone for Proof<MC>
+where
+    StarkProof<MC::H>: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            stark_proof: self.stark_proof.clone(),
+            claimed_sum: self.claimed_sum.clone(),
+            log_size: self.log_size.clone(),
+            config: self.config,
+        }
+    }
 }
 
-impl Proof {
+impl<MC: MerkleChannel> std::fmt::Debug for Proof<MC>
+where
+    StarkProof<MC::H>: std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Proof")
+            .field("stark_proof", &self.stark_proof)
+            .field("claimed_sum", &self.claimed_sum)
+            .field("log_size", &self.log_size)
+            .field("config", &self.config)
+            .finish()
+    }
+}
+
+impl<MC: MerkleChannel> Proof<MC> {
     /// Similarly to [`StarkProof::size_estimate`] returns the proof size estimate in bytes.
     pub fn size_estimate(&self) -> usize {
         let Self {
             stark_proof,
             claimed_sum,
             log_size,
+            config,
         } = self;
         stark_proof.size_estimate()
             + claimed_sum.len() * std::mem::size_of::<SecureField>()
             + log_size.len() * std::mem::size_of::<u32>()
+            + std::mem::size_of_val(config)
+    }
+}
+
+/// Precomputed data the verifier needs to check proofs of a program, see [`Machine::setup`].
+///
+/// The verifier recomputes the commitment to the preprocessed trace from public inputs, which requires building and
+/// committing to the whole preprocessed trace. It only depends on the program, the initial memory and the extensions,
+/// while the exit code and the public output are checked through a lookup. The commitment also depends on the log
+/// sizes of the components, which vary between executions, so the key stores one commitment per prepared shape, see
+/// [`Machine::prepare`]. Proofs of a shape that wasn't prepared are rejected, verifying with a key never generates a
+/// trace.
+///
+/// Only built-in extensions can be stored in the key. Segment proofs aren't supported, since the preprocessed trace
+/// of every segment depends on its boundary.
+#[derive(Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "<MC::H as MerkleHasher>::Hash: Serialize",
+    deserialize = "<MC::H as MerkleHasher>::Hash: Deserialize<'de>"
+))]
+pub struct VerifyingKey<MC: MerkleChannel = Blake2sMerkleChannel> {
+    pub extensions: Vec<Extension>,
+    pub config: ProverConfig,
+    pub program_info: ProgramInfo,
+    pub init_memory: Vec<MemoryInitializationEntry>,
+    /// Expected commitments to the preprocessed trace, along with the log sizes of the components they are for.
+    pub preprocessed_commitments: Vec<(Vec<u32>, <MC::H as MerkleHasher>::Hash)>,
+}
+
+impl<MC: MerkleChannel> VerifyingKey<MC> {
+    /// Returns the public inputs the preprocessed trace is generated from.
+    fn program_trace_ref(&self) -> ProgramTraceRef<'_> {
+        ProgramTraceRef {
+            program_memory: &self.program_info,
+            init_memory: &self.init_memory,
+            exit_code: &[],
+            public_output: &[],
+            segment: None,
+        }
+    }
+
+    /// Returns the expected commitment to the preprocessed trace of proofs with the given log sizes, if prepared.
+    fn preprocessed_commitment(&self, log_size: &[u32]) -> Option<<MC::H as MerkleHasher>::Hash> {
+        self.preprocessed_commitments
+            .iter()
+            .find(|(prepared_log_size, _)| prepared_log_size == log_size)
+            .map(|(_, commitment)| *commitment)
+    }
+}
+
+impl<MC: MerkleChannel> Clone for VerifyingKey<MC> {
+    fn clone(&self) -> Self {
+        Self {
+            extensions: self.extensions.clone(),
+            config: self.config,
+            program_info: self.program_info.clone(),
+            init_memory: self.init_memory.clone(),
+            preprocessed_commitments: self.preprocessed_commitments.clone(),
+        }
+    }
+}
+
+impl<MC: MerkleChannel> std::fmt::Debug for VerifyingKey<MC> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("VerifyingKey")
+            .field("extensions", &self.extensions)
+            .field("config", &self.config)
+            .field("program_info", &self.program_info)
+            .field("init_memory", &self.init_memory)
+            .field("preprocessed_commitments", &self.preprocessed_commitments)
+            .finish()
     }
 }
 
 /// Main (empty) struct implementing proving functionality of zkVM.
 ///
-/// The generic parameter determines which chips are enabled. The default is [`BaseComponent`] for RV32I ISA.
+/// The generic parameter determines which chips are enabled. The default is [`BaseComponent`] for RV32IM ISA.
 /// This functionality mainly exists for testing and removing a component **does not** remove columns it uses in the AIR.
 ///
 /// Note that the order of chips affects correctness, e.g. if columns used by a component require additional lookups,
 /// then it should be positioned in the front.
-pub struct Machine<C = BaseComponent> {
-    _phantom_data: PhantomData<C>,
+///
+/// The second generic parameter determines the Merkle hasher and the Fiat-Shamir channel of the proof, the default is
+/// [`Blake2sMerkleChannel`].
+pub struct Machine<C = BaseComponent, MC = Blake2sMerkleChannel> {
+    _phantom_data: PhantomData<(C, MC)>,
 }
 
-impl<C: MachineChip + Sync> Machine<C> {
-    pub fn prove(trace: &impl Trace, view: &View) -> Result<Proof, ProvingError> {
+impl<C: MachineChip + Sync, MC: MerkleChannel> Machine<C, MC>
+where
+    SimdBackend: BackendForChannel<MC>,
+{
+    pub fn prove(trace: &impl TraceSource, view: &View) -> Result<Proof<MC>, ProvingError> {
         Self::prove_with_extensions(&[], trace, view)
     }
 
     pub fn prove_with_extensions(
         extensions: &[ExtensionComponent],
-        trace: &impl Trace,
+        trace: &impl TraceSource,
         view: &View,
-    ) -> Result<Proof, ProvingError> {
-        let num_steps = trace.get_num_steps();
-        let program_len = view.get_program_memory().program.len();
-        let log_size =
-            Self::max_log_size(&[num_steps, program_len]).max(PreprocessedTraces::MIN_LOG_SIZE);
+    ) -> Result<Proof<MC>, ProvingError> {
+        Self::prove_with_config(extensions, ProverConfig::default(), trace, view)
+    }
+
+    /// Proves the execution with the given security parameters.
+    ///
+    /// Panics if `config` provides fewer than [`DEFAULT_MIN_SECURITY_BITS`] bits of security.
+    pub fn prove_with_config(
+        extensions: &[ExtensionComponent],
+        config: ProverConfig,
+        trace: &impl TraceSource,
+        view: &View,
+    ) -> Result<Proof<MC>, ProvingError> {
+        Self::check_config(config);
+        let program_trace_ref = ProgramTraceRef {
+            program_memory: view.get_program_memory(),
+            init_memory: view.get_initial_memory(),
+            exit_code: view.get_exit_code(),
+            public_output: view.get_public_output(),
+            segment: None,
+        };
+        let extensions_config = ExtensionsConfig::from(extensions);
+
+        // Fill columns of the original trace.
+        let mut prover_side_note = SideNote::from_program_trace_ref(program_trace_ref);
+        let prover_traces = Self::fill_main_trace(
+            trace,
+            program_trace_ref.program_memory,
+            &mut prover_side_note,
+            &extensions_config,
+        );
+
+        Self::prove_main_trace(
+            extensions,
+            config,
+            prover_traces,
+            prover_side_note,
+            program_trace_ref,
+            None,
+            view.view_associated_data().as_deref().unwrap_or_default(),
+        )
+    }
+
+    /// Proves the execution in segments of `segment_size` blocks, see [`crate::segment`].
+    pub fn prove_segments(
+        trace: &impl TraceSource,
+        view: &View,
+        segment_size: usize,
+    ) -> Result<Vec<SegmentProof<MC>>, ProvingError> {
+        Self::prove_segments_with_extensions(
+            &[],
+            ProverConfig::default(),
+            trace,
+            view,
+            segment_size,
+        )
+    }
+
+    /// Proves the execution in segments of `segment_size` blocks, see [`crate::segment`].
+    ///
+    /// The blocks are streamed from the trace, only the blocks of the segment being proven are held in memory.
+    pub fn prove_segments_with_extensions(
+        extensions: &[ExtensionComponent],
+        config: ProverConfig,
+        trace: &impl TraceSource,
+        view: &View,
+        segment_size: usize,
+    ) -> Result<Vec<SegmentProof<MC>>, ProvingError> {
+        assert!(segment_size > 0, "segment size must be positive");
+        Self::check_config(config);
+        let program_memory = view.get_program_memory();
+        let ad = view.view_associated_data().unwrap_or_default();
+        let extensions_config = ExtensionsConfig::from(extensions);
+        let program_range = program_memory_range(program_memory);
+
+        // The execution starts with zeroed registers and the publicly known initial memory.
+        let mut pc = program_memory.initial_pc;
+        let mut regs = [0u32; NUM_REGISTERS];
+        let mut memory = segment::sorted_memory(view.get_initial_memory());
+
+        let mut proofs = Vec::new();
+        for blocks in &trace.stream_blocks().chunks(segment_size) {
+            let segment_trace = SegmentBlocks {
+                blocks: blocks.map(Cow::into_owned).collect(),
+            };
+            debug_assert_eq!(
+                segment_trace.blocks.first().map(|block| block.steps[0].pc),
+                Some(pc),
+                "segment doesn't start where the previous one ended"
+            );
+            let initial_ref = ProgramTraceRef {
+                program_memory,
+                init_memory: &memory,
+                // The public output is checked against the final memory of the last segment, here it only determines
+                // write-only addresses.
+                exit_code: view.get_exit_code(),
+                public_output: view.get_public_output(),
+                segment: None,
+            };
+
+            let mut prover_side_note = SideNote::from_program_trace_ref(initial_ref);
+            prover_side_note.register_mem_check =
+                RegisterMemCheckSideNote::with_initial_values(regs);
+            let prover_traces = Self::fill_main_trace(
+                &segment_trace,
+                program_memory,
+                &mut prover_side_note,
+                &extensions_config,
+            );
+
+            // Read the final state after all chips have filled the trace.
+            let last_row_idx = segment_trace.num_steps() - 1;
+            let final_pc =
+                u32::from_base_fields(prover_traces.column(last_row_idx, Column::PcNext));
+            let final_regs = prover_side_note.register_mem_check.last_access_value;
+            // The program is a part of the RAM, but it's public and never changes.
+            let final_memory: Vec<MemoryInitializationEntry> = prover_side_note
+                .rw_mem_check
+                .last_access
+                .iter()
+                .filter(|(address, _)| !program_range.contains(address))
+                .map(|(&address, &(_counter, value))| MemoryInitializationEntry {
+                    address,
+                    value,
+                    read_only: prover_side_note.rw_mem_check.read_only.contains(&address),
+                })
+                .collect();
+
+            let initial_state = BoundaryState::new(pc, regs, &memory);
+            let final_state = BoundaryState::new(final_pc, final_regs, &final_memory);
+            let program_trace_ref = ProgramTraceRef {
+                segment: Some(SegmentBoundaryRef {
+                    initial_pc: pc,
+                    final_pc,
+                    initial_regs: &regs,
+                    final_regs: &final_regs,
+                    final_memory: &final_memory,
+                }),
+                ..initial_ref
+            };
+            let proof = Self::prove_main_trace(
+                extensions,
+                config,
+                prover_traces,
+                prover_side_note,
+                program_trace_ref,
+                Some((&initial_state, &final_state)),
+                &ad,
+            )?;
+
+            proofs.push(SegmentProof {
+                proof,
+                initial_state,
+                final_state,
+                memory_delta: segment::memory_delta(&memory, &final_memory),
+            });
+            pc = final_pc;
+            regs = final_regs;
+            memory = final_memory;
+        }
+        Ok(proofs)
+    }
 
+    /// Evaluates constraints of all components on every row of the trace, including logup constraints on the
+    /// interaction trace, and matches entries of logup relations.
+    ///
+    /// Returns the violations located by component, chip, row, executed instruction and columns read, none if the
+    /// trace is valid.
+    /// No proof is produced, see [`crate::debug`]. Meant for developing chips and extensions, it's slow on large traces.
+    pub fn debug_constraints(
+        trace: &impl TraceSource,
+        view: &View,
+        extensions: &[ExtensionComponent],
+    ) -> Vec<ConstraintViolation> {
+        let program_trace_ref = ProgramTraceRef {
+            program_memory: view.get_program_memory(),
+            init_memory: view.get_initial_memory(),
+            exit_code: view.get_exit_code(),
+            public_output: view.get_public_output(),
+            segment: None,
+        };
         let extensions_config = ExtensionsConfig::from(extensions);
         let extensions_iter = BASE_EXTENSIONS.iter().chain(extensions);
 
-        // Fill columns of the preprocessed trace.
+        let mut side_note = SideNote::from_program_trace_ref(program_trace_ref);
+        let prover_traces = Self::fill_main_trace(
+            trace,
+            program_trace_ref.program_memory,
+            &mut side_note,
+            &extensions_config,
+        );
+        let log_size = prover_traces.log_size();
+        let extension_log_sizes: Vec<u32> = extensions_iter
+            .clone()
+            .map(|ext| ext.compute_log_size(&side_note))
+            .collect();
+        let extension_traces: Vec<ComponentTrace> = extensions_iter
+            .clone()
+            .zip(&extension_log_sizes)
+            .map(|(ext, log_size)| {
+                ext.generate_component_trace(*log_size, program_trace_ref, &mut side_note)
+            })
+            .collect();
+
+        // Entries are matched by their combined values, any elements drawn from the channel will do.
+        let channel = &mut MC::C::default();
+        let mut lookup_elements = AllLookupElements::default();
+        C::draw_lookup_elements(&mut lookup_elements, channel, &extensions_config);
+        for ext in extensions {
+            ext.draw_lookup_elements(&mut lookup_elements, channel);
+        }
+
         let preprocessed_trace = PreprocessedTraces::new(log_size);
+        let program_trace = ProgramTracesBuilder::new(log_size, program_trace_ref).finalize();
+        let finalized_trace = prover_traces.finalize();
+        let preprocessed: Vec<Vec<BaseField>> = preprocessed_trace
+            .clone()
+            .into_circle_evaluation()
+            .into_iter()
+            .chain(program_trace.clone().into_circle_evaluation())
+            .map(|col| debug::coset_order(&col.values))
+            .collect();
+        let original: Vec<Vec<BaseField>> = finalized_trace
+            .clone()
+            .into_circle_evaluation()
+            .into_iter()
+            .map(|col| debug::coset_order(&col.values))
+            .collect();
+        let (interaction_trace, claimed_sum) = generate_interaction_trace::<C>(
+            &finalized_trace,
+            &preprocessed_trace,
+            &program_trace,
+            &lookup_elements,
+        );
+        let steps = iter_program_steps(trace, 1 << log_size).collect();
 
-        // Fill columns of the original trace.
-        let mut prover_traces = TracesBuilder::new(log_size);
+        let mut report = DebugReport::default();
+        report.check_main::<C>(
+            &preprocessed,
+            &original,
+            &InteractionTrace::new(interaction_trace, claimed_sum),
+            steps,
+            &lookup_elements,
+            &extensions_config,
+        );
+        for (ext, component_trace) in extensions_iter.zip(extension_traces) {
+            let (interaction_trace, claimed_sum) = ext.generate_interaction_trace(
+                component_trace.clone(),
+                &side_note,
+                &lookup_elements,
+            );
+            ext.debug_constraints(
+                &component_trace,
+                &InteractionTrace::new(interaction_trace, claimed_sum),
+                &lookup_elements,
+                &mut report,
+            );
+        }
+        report.add_verifier_entries(
+            debug::PROVEN_OUTPUT,
+            proven_output_entries(&lookup_elements, program_trace_ref.proven_output()),
+        );
+        report.finish()
+    }
+
+    /// Estimates the cost of proving the execution with the given extensions and configuration, without generating
+    /// any trace, see [`crate::estimate`].
+    pub fn estimate(
+        extensions: &[ExtensionComponent],
+        config: ProverConfig,
+        trace: &impl TraceSource,
+        view: &View,
+    ) -> CostEstimate {
         let program_trace_ref = ProgramTraceRef {
             program_memory: view.get_program_memory(),
             init_memory: view.get_initial_memory(),
             exit_code: view.get_exit_code(),
             public_output: view.get_public_output(),
+            segment: None,
         };
-        let program_traces = ProgramTracesBuilder::new(log_size, program_trace_ref);
-        let mut prover_side_note = SideNote::new(&program_traces, view);
-        let program_steps = iter_program_steps(trace, prover_traces.num_rows());
-        for (row_idx, program_step) in program_steps.enumerate() {
-            C::fill_main_trace(
+        let extensions_iter = BASE_EXTENSIONS.iter().chain(extensions);
+
+        let num_steps = trace.num_steps();
+        let log_size = Self::max_log_size(&[num_steps, view.get_program_memory().program.len()])
+            .max(PreprocessedTraces::MIN_LOG_SIZE);
+        let num_rows = 1usize << log_size;
+
+        let mut side_note = SideNote::from_program_trace_ref(program_trace_ref);
+        let mut rows = BTreeMap::new();
+        let program_steps = iter_program_steps(trace, num_rows)
+            .take(num_steps)
+            .flatten();
+        for program_step in program_steps {
+            estimate::record_step(program_step, &mut side_note, &mut rows);
+        }
+        if num_rows > num_steps {
+            rows.insert(InstructionFamily::Padding, num_rows - num_steps);
+        }
+
+        let extension_log_sizes: Vec<(String, u32)> = extensions_iter
+            .map(|ext| (ext.name().to_owned(), ext.compute_log_size(&side_note)))
+            .collect();
+        let all_log_sizes: Vec<u32> = std::iter::once(log_size)
+            .chain(extension_log_sizes.iter().map(|(_, log_size)| *log_size))
+            .collect();
+
+        let column_log_sizes = Self::column_log_sizes(extensions, &all_log_sizes);
+        let columns = column_log_sizes.iter().flatten();
+        let cells: u64 = columns.clone().map(|&log_size| 1u64 << log_size).sum();
+
+        CostEstimate {
+            num_steps,
+            log_size,
+            extension_log_sizes,
+            rows,
+            tracked_ram_size: view.view_tracked_ram_size(),
+            num_columns: columns.count(),
+            cells,
+            extended_cells: cells << config.log_blowup_factor,
+            proving_time: Duration::ZERO,
+            memory_bytes: 0,
+        }
+        .with_cost_model(&CostModel::default())
+    }
+
+    /// Fills the main trace, recording memory accesses in the side note.
+    ///
+    /// Rows are filled in parallel chunks, the result is the same as of filling them one after another. Steps of a
+    /// streaming trace are executed once and held in memory for one batch of chunks at a time, one chunk per thread.
+    fn fill_main_trace(
+        trace: &impl TraceSource,
+        program_memory: &ProgramInfo,
+        side_note: &mut SideNote,
+        extensions_config: &ExtensionsConfig,
+    ) -> TracesBuilder {
+        let num_steps = trace.num_steps();
+        let program_len = program_memory.program.len();
+        let log_size =
+            Self::max_log_size(&[num_steps, program_len]).max(PreprocessedTraces::MIN_LOG_SIZE);
+
+        let num_rows = 1usize << log_size;
+        let chunk_size = num_rows
+            .div_ceil(rayon::current_num_threads())
+            .max(MIN_CHUNK_SIZE);
+        Self::fill_main_trace_in_chunks(trace, log_size, side_note, extensions_config, chunk_size)
+    }
+
+    /// Fills all rows but the last one in chunks of `chunk_size` rows, going over the steps of `trace` once.
+    ///
+    /// Steps are collected for all rows at once, or for one batch of chunks per thread if `trace` is streaming.
+    /// The last row is filled on its own, since chips finalize the trace there. A `chunk_size` covering all rows fills
+    /// them one after another.
+    pub fn fill_main_trace_in_chunks(
+        trace: &impl TraceSource,
+        log_size: u32,
+        side_note: &mut SideNote,
+        extensions_config: &ExtensionsConfig,
+        chunk_size: usize,
+    ) -> TracesBuilder {
+        let num_rows = 1usize << log_size;
+        let mut prover_traces = TracesBuilder::new(log_size);
+        let last_row = num_rows - 1;
+        let mut program_steps = iter_program_steps(trace, num_rows);
+        if chunk_size >= last_row {
+            for (row_idx, program_step) in program_steps.enumerate() {
+                C::fill_main_trace(
+                    &mut prover_traces,
+                    row_idx,
+                    &program_step,
+                    side_note,
+                    extensions_config,
+                );
+            }
+            return prover_traces;
+        }
+
+        let batch_size = if trace.is_streaming() {
+            chunk_size.saturating_mul(rayon::current_num_threads())
+        } else {
+            last_row
+        };
+        for start in (0..last_row).step_by(batch_size) {
+            let rows = start..(start + batch_size).min(last_row);
+            // Padding follows all steps, the remaining rows of the batch are padding too.
+            let batch: Vec<Option<ProgramStep>> = program_steps
+                .by_ref()
+                .take(rows.len())
+                .take_while(Option::is_some)
+                .collect();
+            Self::fill_rows_in_chunks(
                 &mut prover_traces,
-                row_idx,
-                &program_step,
-                &mut prover_side_note,
-                &extensions_config,
+                rows,
+                &batch,
+                side_note,
+                extensions_config,
+                chunk_size,
             );
         }
+        C::fill_main_trace(
+            &mut prover_traces,
+            last_row,
+            &program_steps.next().flatten(),
+            side_note,
+            extensions_config,
+        );
+        prover_traces
+    }
+
+    /// Fills `rows`, whose steps are `program_steps` followed by padding, in chunks of `chunk_size` rows, in two
+    /// parallel passes.
+    ///
+    /// The first pass records accesses made by each chunk, which are replayed to find out the state of memory
+    /// checking each chunk starts with. The second pass fills chunks from these states and merges side notes in order.
+    ///
+    /// Every row is filled twice, so filling in chunks only pays off with more than two threads, see the `trace_gen`
+    /// benchmark. Replaying is sequential but only goes over the addresses each chunk touched.
+    fn fill_rows_in_chunks(
+        prover_traces: &mut TracesBuilder,
+        rows: Range<usize>,
+        program_steps: &[Option<ProgramStep>],
+        side_note: &mut SideNote,
+        extensions_config: &ExtensionsConfig,
+        chunk_size: usize,
+    ) {
+        let log_size = prover_traces.log_size();
+        let first_row = rows.start;
+        let padding = None;
+        let fill_rows =
+            |traces: &mut TracesBuilder, rows: Range<usize>, side_note: &mut SideNote| {
+                for row_idx in rows {
+                    C::fill_main_trace(
+                        traces,
+                        row_idx,
+                        program_steps.get(row_idx - first_row).unwrap_or(&padding),
+                        side_note,
+                        extensions_config,
+                    );
+                }
+            };
+        let chunks: Vec<Range<usize>> = rows
+            .clone()
+            .step_by(chunk_size)
+            .map(|start| start..(start + chunk_size).min(rows.end))
+            .collect();
+
+        let accesses: Vec<SideNote> = chunks
+            .par_iter()
+            .map(|rows| {
+                let mut window = TracesBuilder::new_window(log_size, rows.clone());
+                let mut fork = side_note.fork();
+                fork.rw_mem_check.speculative = true;
+                fill_rows(&mut window, rows.clone(), &mut fork);
+                fork
+            })
+            .collect();
+        // Replaying is done on `side_note` itself, so that no chunk copies the whole state. Merging filled forks in
+        // order below restates the same accesses, ending in the same state.
+        let forks: Vec<SideNote> = accesses
+            .iter()
+            .map(|accesses| {
+                let fork = side_note.fork_for_accesses(accesses);
+                side_note.replay_accesses(accesses);
+                fork
+            })
+            .collect();
+        drop(accesses);
+
+        let windows: Vec<(TracesBuilder, SideNote)> = chunks
+            .into_par_iter()
+            .zip(forks)
+            .map(|(rows, mut fork)| {
+                let mut window = TracesBuilder::new_window(log_size, rows.clone());
+                fill_rows(&mut window, rows, &mut fork);
+                (window, fork)
+            })
+            .collect();
+        for (window, fork) in windows {
+            prover_traces.copy_window(&window);
+            side_note.merge(fork);
+        }
+    }
+
+    /// Proves the filled main trace against the public inputs in `program_trace_ref`.
+    ///
+    /// `boundary` are the initial and the final state of a continuation segment, bound to the transcript.
+    fn prove_main_trace(
+        extensions: &[ExtensionComponent],
+        config: ProverConfig,
+        prover_traces: TracesBuilder,
+        mut prover_side_note: SideNote,
+        program_trace_ref: ProgramTraceRef,
+        boundary: Option<(&BoundaryState, &BoundaryState)>,
+        ad: &[u8],
+    ) -> Result<Proof<MC>, ProvingError> {
+        let log_size = prover_traces.log_size();
+        let extensions_config = ExtensionsConfig::from(extensions);
+        let extensions_iter = BASE_EXTENSIONS.iter().chain(extensions);
+
+        // Fill columns of the preprocessed trace.
+        let preprocessed_trace = PreprocessedTraces::new(log_size);
+        let program_traces = ProgramTracesBuilder::new(log_size, program_trace_ref);
 
         let finalized_trace = prover_traces.finalize();
         let finalized_program_trace = program_traces.finalize();
@@ -170,26 +776,29 @@ impl<C: MachineChip + Sync> Machine<C> {
             )
             .collect();
 
-        let config = PcsConfig::default();
+        let pcs_config = PcsConfig::from(config);
         // Precompute twiddles.
         let twiddles = SimdBackend::precompute_twiddles(
             CanonicCoset::new(
                 log_size.max(all_log_sizes.iter().copied().max().unwrap_or(0))
                     + LOG_CONSTRAINT_DEGREE
-                    + config.fri_config.log_blowup_factor,
+                    + pcs_config.fri_config.log_blowup_factor,
             )
             .circle_domain()
             .half_coset,
         );
 
         // Setup protocol.
-        let prover_channel = &mut Blake2sChannel::default();
-        for byte in view.view_associated_data().unwrap_or_default() {
+        let prover_channel = &mut MC::C::default();
+        for &byte in ad {
             prover_channel.mix_u64(byte.into());
         }
+        Self::mix_config(config, prover_channel);
+        Self::mix_boundary(boundary, prover_channel);
+        Self::mix_proven_output(program_trace_ref, prover_channel);
 
         let mut commitment_scheme =
-            CommitmentSchemeProver::<SimdBackend, Blake2sMerkleChannel>::new(config, &twiddles);
+            CommitmentSchemeProver::<SimdBackend, MC>::new(pcs_config, &twiddles);
         all_log_sizes.iter().for_each(|log_size| {
             prover_channel.mix_u64(*log_size as u64);
         });
@@ -227,6 +836,9 @@ impl<C: MachineChip + Sync> Machine<C> {
 
         let mut lookup_elements = AllLookupElements::default();
         C::draw_lookup_elements(&mut lookup_elements, prover_channel, &extensions_config);
+        for ext in extensions {
+            ext.draw_lookup_elements(&mut lookup_elements, prover_channel);
+        }
 
         let (interaction_trace, claimed_sum) = generate_interaction_trace::<C>(
             &finalized_trace,
@@ -271,21 +883,18 @@ impl<C: MachineChip + Sync> Machine<C> {
         let mut components_ref: Vec<&dyn ComponentProver<SimdBackend>> =
             ext_components.iter().map(|c| &**c).collect();
         components_ref.insert(0, &main_component);
-        let proof = prove::<SimdBackend, Blake2sMerkleChannel>(
-            &components_ref,
-            prover_channel,
-            commitment_scheme,
-        )?;
+        let proof = prove::<SimdBackend, MC>(&components_ref, prover_channel, commitment_scheme)?;
 
         Ok(Proof {
             stark_proof: proof,
             claimed_sum: all_claimed_sum,
             log_size: all_log_sizes,
+            config,
         })
     }
 
     pub fn verify(
-        proof: Proof,
+        proof: Proof<MC>,
         program_info: &ProgramInfo,
         ad: &[u8],
         init_memory: &[MemoryInitializationEntry],
@@ -294,6 +903,7 @@ impl<C: MachineChip + Sync> Machine<C> {
     ) -> Result<(), VerificationError> {
         Self::verify_with_extensions(
             &[],
+            DEFAULT_MIN_SECURITY_BITS,
             proof,
             program_info,
             ad,
@@ -303,142 +913,403 @@ impl<C: MachineChip + Sync> Machine<C> {
         )
     }
 
+    /// Verifies the proof, rejecting configurations with fewer than `min_security_bits` bits of security.
     pub fn verify_with_extensions(
         extensions: &[ExtensionComponent],
-        proof: Proof,
+        min_security_bits: u32,
+        proof: Proof<MC>,
         program_info: &ProgramInfo,
         ad: &[u8],
         init_memory: &[MemoryInitializationEntry],
         exit_code: &[PublicOutputEntry],
         output_memory: &[PublicOutputEntry],
     ) -> Result<(), VerificationError> {
-        let Proof {
-            stark_proof: proof,
-            claimed_sum,
-            log_size: all_log_sizes,
-        } = proof;
+        let program_trace_ref = ProgramTraceRef {
+            program_memory: program_info,
+            init_memory,
+            exit_code,
+            public_output: output_memory,
+            segment: None,
+        };
+        Self::verify_program_trace_ref(
+            extensions,
+            min_security_bits,
+            proof,
+            program_trace_ref,
+            None,
+            ad,
+        )
+    }
 
-        if claimed_sum.len() != extensions.len() + BASE_EXTENSIONS.len() + 1 {
-            return Err(VerificationError::InvalidStructure(
-                "claimed sum len mismatch".to_string(),
-            ));
-        }
-        if all_log_sizes.len() != extensions.len() + BASE_EXTENSIONS.len() + 1 {
-            return Err(VerificationError::InvalidStructure(
-                "log size len mismatch".to_string(),
-            ));
-        }
-        if claimed_sum.iter().sum::<SecureField>() != SecureField::zero() {
+    /// Verifies segment proofs of the execution and checks that they chain together, see [`crate::segment`].
+    pub fn verify_segments(
+        proofs: Vec<SegmentProof<MC>>,
+        program_info: &ProgramInfo,
+        ad: &[u8],
+        init_memory: &[MemoryInitializationEntry],
+        exit_code: &[PublicOutputEntry],
+        output_memory: &[PublicOutputEntry],
+    ) -> Result<(), VerificationError> {
+        Self::verify_segments_with_extensions(
+            &[],
+            DEFAULT_MIN_SECURITY_BITS,
+            proofs,
+            program_info,
+            ad,
+            init_memory,
+            exit_code,
+            output_memory,
+        )
+    }
+
+    pub fn verify_segments_with_extensions(
+        extensions: &[ExtensionComponent],
+        min_security_bits: u32,
+        proofs: Vec<SegmentProof<MC>>,
+        program_info: &ProgramInfo,
+        ad: &[u8],
+        init_memory: &[MemoryInitializationEntry],
+        exit_code: &[PublicOutputEntry],
+        output_memory: &[PublicOutputEntry],
+    ) -> Result<(), VerificationError> {
+        if proofs.is_empty() {
             return Err(VerificationError::InvalidStructure(
-                "claimed logup sum is not zero".to_string(),
+                "no segment proofs".to_string(),
             ));
         }
 
-        let extensions_config = ExtensionsConfig::from(extensions);
-        let extensions_iter = BASE_EXTENSIONS.iter().chain(extensions);
+        // The first segment must start with zeroed registers and the publicly known initial memory.
+        let program_range = program_memory_range(program_info);
+        let mut memory = segment::sorted_memory(init_memory);
+        let mut expected_state =
+            BoundaryState::new(program_info.initial_pc, [0; NUM_REGISTERS], &memory);
 
-        let config = PcsConfig::default();
-        let verifier_channel = &mut Blake2sChannel::default();
-        for &byte in ad {
-            verifier_channel.mix_u64(byte.into());
-        }
-        all_log_sizes.iter().for_each(|log_size| {
-            verifier_channel.mix_u64(*log_size as u64);
-        });
+        for (idx, segment_proof) in proofs.into_iter().enumerate() {
+            let SegmentProof {
+                proof,
+                initial_state,
+                final_state,
+                memory_delta,
+            } = segment_proof;
 
-        let commitment_scheme = &mut CommitmentSchemeVerifier::<Blake2sMerkleChannel>::new(config);
+            if initial_state != expected_state {
+                return Err(VerificationError::InvalidStructure(format!(
+                    "segment {idx} doesn't start in the final state of the previous segment"
+                )));
+            }
+            let final_memory = segment::apply_memory_delta(&memory, &memory_delta, &program_range)
+                .map_err(|err| {
+                    VerificationError::InvalidStructure(format!("segment {idx}: {err}"))
+                })?;
+            if segment::memory_digest(&final_memory) != final_state.memory_digest {
+                return Err(VerificationError::InvalidStructure(format!(
+                    "segment {idx}: final memory doesn't match its digest"
+                )));
+            }
 
-        // simulate the prover and compute expected commitment to preprocessed trace
-        {
-            let config = PcsConfig::default();
-            let verifier_channel = &mut verifier_channel.clone();
-            let twiddles = SimdBackend::precompute_twiddles(
-                CanonicCoset::new(
-                    all_log_sizes
-                        .iter()
-                        .copied()
-                        .max()
-                        .expect("log sizes is empty")
-                        + LOG_CONSTRAINT_DEGREE
-                        + config.fri_config.log_blowup_factor,
-                )
-                .circle_domain()
-                .half_coset,
-            );
-            let commitment_scheme =
-                &mut CommitmentSchemeProver::<SimdBackend, Blake2sMerkleChannel>::new(
-                    config, &twiddles,
-                );
-            let preprocessed_trace = PreprocessedTraces::new(all_log_sizes[0]);
             let program_trace_ref = ProgramTraceRef {
                 program_memory: program_info,
-                init_memory,
+                init_memory: &memory,
                 exit_code,
                 public_output: output_memory,
+                segment: Some(SegmentBoundaryRef {
+                    initial_pc: initial_state.pc,
+                    final_pc: final_state.pc,
+                    initial_regs: &initial_state.regs,
+                    final_regs: &final_state.regs,
+                    final_memory: &final_memory,
+                }),
             };
-            let program_trace =
-                ProgramTracesBuilder::new(all_log_sizes[0], program_trace_ref).finalize();
-
-            let mut tree_builder = commitment_scheme.tree_builder();
-            let _preprocessed_trace_location = tree_builder.extend_evals(
-                preprocessed_trace
-                    .into_circle_evaluation()
-                    .into_iter()
-                    .chain(program_trace.into_circle_evaluation()),
-            );
-            // Handle extensions for the preprocessed trace
-            for (ext, log_size) in extensions_iter
-                .clone()
-                .zip(all_log_sizes.get(1..).unwrap_or_default())
-            {
-                tree_builder
-                    .extend_evals(ext.generate_preprocessed_trace(*log_size, program_trace_ref));
-            }
-            tree_builder.commit(verifier_channel);
+            Self::verify_program_trace_ref(
+                extensions,
+                min_security_bits,
+                proof,
+                program_trace_ref,
+                Some((&initial_state, &final_state)),
+                ad,
+            )?;
 
-            let preprocessed_expected = commitment_scheme.roots()[PREPROCESSED_TRACE_IDX];
-            let preprocessed = proof.commitments[PREPROCESSED_TRACE_IDX];
-            if preprocessed_expected != preprocessed {
-                return Err(VerificationError::InvalidStructure(format!("invalid commitment to preprocessed trace: \
-                                                                        expected {preprocessed_expected}, got {preprocessed}")));
-            }
+            memory = final_memory;
+            expected_state = final_state;
         }
 
-        // Retrieve the expected column sizes in each commitment interaction, from the AIR.
-
-        // Info evaluation can be avoided if the prover sends lookup elements along with the proof, this requires
-        // implementing  [`serde::Serialize`] for all relations and [`AllLookupElements`]. Note that the verifier
-        // should still independently draw elements and match it against received ones.
-        let mut sizes = vec![
-            components::machine_component_info::<C>(extensions_config.clone())
-                .mask_offsets
-                .as_cols_ref()
-                .map_cols(|_| all_log_sizes[0]),
-        ];
-        for (ext, log_size) in extensions_iter
-            .clone()
-            .zip(all_log_sizes.get(1..).unwrap_or_default())
+        // The exit code and the public output are read from the memory at the end of the last segment.
+        if let Some(PublicOutputEntry { address, value }) = exit_code
+            .iter()
+            .chain(output_memory)
+            .find(|entry| segment::read_memory(&memory, entry.address) != entry.value)
         {
-            sizes.push(ext.trace_sizes(*log_size));
+            return Err(VerificationError::InvalidStructure(format!(
+                "public output mismatch at address={address:#x} value={value}"
+            )));
         }
-        let mut log_sizes = TreeVec::concat_cols(sizes.into_iter());
-        // use the fact that preprocessed columns are only allowed to have [0] mask
-        log_sizes[PREPROCESSED_TRACE_IDX] =
-            vec![all_log_sizes[0]; PreprocessedColumn::COLUMNS_NUM + ProgramColumn::COLUMNS_NUM];
-        for (ext, log_size) in extensions_iter
-            .clone()
-            .zip(all_log_sizes.get(1..).unwrap_or_default())
-        {
+        Ok(())
+    }
+
+    /// Precomputes the verifying key of proofs of the program with the given extensions, see [`VerifyingKey`].
+    ///
+    /// The setup fails if `config` provides fewer than `min_security_bits` bits of security. No commitment is
+    /// precomputed yet, the key verifies no proof until the shapes to accept are added with [`Self::prepare`].
+    pub fn setup(
+        extensions: &[Extension],
+        config: ProverConfig,
+        min_security_bits: u32,
+        program_info: &ProgramInfo,
+        init_memory: &[MemoryInitializationEntry],
+    ) -> Result<VerifyingKey<MC>, VerificationError> {
+        config
+            .validate(min_security_bits)
+            .map_err(VerificationError::InvalidStructure)?;
+        Ok(VerifyingKey {
+            extensions: extensions.to_vec(),
+            config,
+            program_info: program_info.clone(),
+            init_memory: init_memory.to_vec(),
+            preprocessed_commitments: Vec::new(),
+        })
+    }
+
+    /// Precomputes the commitment to the preprocessed trace of proofs with the given log sizes of the components, as
+    /// in [`Proof::log_size`], so that verifying them doesn't commit to any trace.
+    pub fn prepare(key: &mut VerifyingKey<MC>, log_size: &[u32]) -> Result<(), VerificationError> {
+        if key.preprocessed_commitment(log_size).is_some() {
+            return Ok(());
+        }
+        let preprocessed_commitment = Self::preprocessed_commitment(
+            &Extension::to_components(&key.extensions),
+            key.config,
+            log_size,
+            key.program_trace_ref(),
+        )?;
+        key.preprocessed_commitments
+            .push((log_size.to_vec(), preprocessed_commitment));
+        Ok(())
+    }
+
+    /// Verifies the proof against public inputs in `program_trace_ref`.
+    ///
+    /// `boundary` are the initial and the final state of a continuation segment, see [`Self::prove_main_trace`].
+    fn verify_program_trace_ref(
+        extensions: &[ExtensionComponent],
+        min_security_bits: u32,
+        proof: Proof<MC>,
+        program_trace_ref: ProgramTraceRef,
+        boundary: Option<(&BoundaryState, &BoundaryState)>,
+        ad: &[u8],
+    ) -> Result<(), VerificationError> {
+        proof
+            .config
+            .validate(min_security_bits)
+            .map_err(VerificationError::InvalidStructure)?;
+        let preprocessed_commitment = Self::preprocessed_commitment(
+            extensions,
+            proof.config,
+            &proof.log_size,
+            program_trace_ref,
+        )?;
+        Self::verify_with_commitment(
+            extensions,
+            preprocessed_commitment,
+            proof,
+            program_trace_ref,
+            boundary,
+            ad,
+        )
+    }
+
+    /// Computes the commitment to the preprocessed trace, simulating the prover.
+    ///
+    /// `all_log_sizes` are the log sizes of the main component followed by the ones of extensions.
+    fn preprocessed_commitment(
+        extensions: &[ExtensionComponent],
+        config: ProverConfig,
+        all_log_sizes: &[u32],
+        program_trace_ref: ProgramTraceRef,
+    ) -> Result<<MC::H as MerkleHasher>::Hash, VerificationError> {
+        if all_log_sizes.len() != extensions.len() + BASE_EXTENSIONS.len() + 1 {
+            return Err(VerificationError::InvalidStructure(
+                "log size len mismatch".to_string(),
+            ));
+        }
+
+        let extensions_iter = BASE_EXTENSIONS.iter().chain(extensions);
+        let pcs_config = PcsConfig::from(config);
+
+        let twiddles = SimdBackend::precompute_twiddles(
+            CanonicCoset::new(
+                all_log_sizes
+                    .iter()
+                    .copied()
+                    .max()
+                    .expect("log sizes is empty")
+                    + LOG_CONSTRAINT_DEGREE
+                    + pcs_config.fri_config.log_blowup_factor,
+            )
+            .circle_domain()
+            .half_coset,
+        );
+        let commitment_scheme =
+            &mut CommitmentSchemeProver::<SimdBackend, MC>::new(pcs_config, &twiddles);
+        let preprocessed_trace = PreprocessedTraces::new(all_log_sizes[0]);
+        let program_trace =
+            ProgramTracesBuilder::new(all_log_sizes[0], program_trace_ref).finalize();
+
+        let mut tree_builder = commitment_scheme.tree_builder();
+        let _preprocessed_trace_location = tree_builder.extend_evals(
+            preprocessed_trace
+                .into_circle_evaluation()
+                .into_iter()
+                .chain(program_trace.into_circle_evaluation()),
+        );
+        // Handle extensions for the preprocessed trace
+        for (ext, log_size) in extensions_iter.zip(&all_log_sizes[1..]) {
+            tree_builder
+                .extend_evals(ext.generate_preprocessed_trace(*log_size, program_trace_ref));
+        }
+        // The root doesn't depend on the state of the channel.
+        tree_builder.commit(&mut MC::C::default());
+
+        Ok(commitment_scheme.roots()[PREPROCESSED_TRACE_IDX])
+    }
+
+    /// Retrieves the expected column sizes in each commitment interaction, from the AIR.
+    ///
+    /// `all_log_sizes` are the log sizes of the main component followed by the ones of extensions.
+    fn column_log_sizes(
+        extensions: &[ExtensionComponent],
+        all_log_sizes: &[u32],
+    ) -> TreeVec<Vec<u32>> {
+        let extensions_config = ExtensionsConfig::from(extensions);
+        let extensions_iter = BASE_EXTENSIONS.iter().chain(extensions);
+
+        // Info evaluation can be avoided if the prover sends lookup elements along with the proof, this requires
+        // implementing  [`serde::Serialize`] for all relations and [`AllLookupElements`]. Note that the verifier
+        // should still independently draw elements and match it against received ones.
+        let mut sizes = vec![components::machine_component_info::<C>(extensions_config)
+            .mask_offsets
+            .as_cols_ref()
+            .map_cols(|_| all_log_sizes[0])];
+        for (ext, log_size) in extensions_iter.clone().zip(&all_log_sizes[1..]) {
+            sizes.push(ext.trace_sizes(*log_size));
+        }
+        let mut log_sizes = TreeVec::concat_cols(sizes.into_iter());
+        // use the fact that preprocessed columns are only allowed to have [0] mask
+        log_sizes[PREPROCESSED_TRACE_IDX] =
+            vec![all_log_sizes[0]; PreprocessedColumn::COLUMNS_NUM + ProgramColumn::COLUMNS_NUM];
+        for (ext, log_size) in extensions_iter.zip(&all_log_sizes[1..]) {
             // extending log_sizes[PREPROCESSED_TRACE_IDX] with the dimension of the preprocessed columns
             log_sizes[PREPROCESSED_TRACE_IDX].extend(ext.preprocessed_trace_sizes(*log_size));
         }
+        log_sizes
+    }
 
+    /// Verifies the proof against a key precomputed with [`Self::setup`], the exit code and the public output.
+    ///
+    /// Fails if the shape of the proof wasn't prepared with [`Self::prepare`], the commitment to the preprocessed
+    /// trace is never recomputed here.
+    pub fn verify_with_key(
+        key: &VerifyingKey<MC>,
+        proof: Proof<MC>,
+        ad: &[u8],
+        exit_code: &[PublicOutputEntry],
+        output_memory: &[PublicOutputEntry],
+    ) -> Result<(), VerificationError> {
+        if proof.config != key.config {
+            return Err(VerificationError::InvalidStructure(
+                "prover config doesn't match the verifying key".to_string(),
+            ));
+        }
+        let extensions = Extension::to_components(&key.extensions);
+        let program_trace_ref = ProgramTraceRef {
+            exit_code,
+            public_output: output_memory,
+            ..key.program_trace_ref()
+        };
+        let Some(preprocessed_commitment) = key.preprocessed_commitment(&proof.log_size) else {
+            return Err(VerificationError::InvalidStructure(format!(
+                "log sizes {:?} weren't prepared in the verifying key",
+                proof.log_size
+            )));
+        };
+        Self::verify_with_commitment(
+            &extensions,
+            preprocessed_commitment,
+            proof,
+            program_trace_ref,
+            None,
+            ad,
+        )
+    }
+
+    /// Verifies the proof given the expected commitment to the preprocessed trace, the output proven by the trace is
+    /// taken from `program_trace_ref`.
+    fn verify_with_commitment(
+        extensions: &[ExtensionComponent],
+        preprocessed_expected: <MC::H as MerkleHasher>::Hash,
+        proof: Proof<MC>,
+        program_trace_ref: ProgramTraceRef,
+        boundary: Option<(&BoundaryState, &BoundaryState)>,
+        ad: &[u8],
+    ) -> Result<(), VerificationError> {
+        let Proof {
+            stark_proof: proof,
+            claimed_sum,
+            log_size: all_log_sizes,
+            config,
+        } = proof;
+
+        if all_log_sizes.len() != extensions.len() + BASE_EXTENSIONS.len() + 1 {
+            return Err(VerificationError::InvalidStructure(
+                "log size len mismatch".to_string(),
+            ));
+        }
+        if claimed_sum.len() != extensions.len() + BASE_EXTENSIONS.len() + 1 {
+            return Err(VerificationError::InvalidStructure(
+                "claimed sum len mismatch".to_string(),
+            ));
+        }
+
+        let preprocessed = proof.commitments[PREPROCESSED_TRACE_IDX];
+        if preprocessed_expected != preprocessed {
+            return Err(VerificationError::InvalidStructure(format!("invalid commitment to preprocessed trace: \
+                                                                    expected {preprocessed_expected}, got {preprocessed}")));
+        }
+
+        let extensions_config = ExtensionsConfig::from(extensions);
+        let extensions_iter = BASE_EXTENSIONS.iter().chain(extensions);
+
+        let pcs_config = PcsConfig::from(config);
+        let verifier_channel = &mut MC::C::default();
+        for &byte in ad {
+            verifier_channel.mix_u64(byte.into());
+        }
+        Self::mix_config(config, verifier_channel);
+        Self::mix_boundary(boundary, verifier_channel);
+        Self::mix_proven_output(program_trace_ref, verifier_channel);
+        all_log_sizes.iter().for_each(|log_size| {
+            verifier_channel.mix_u64(*log_size as u64);
+        });
+
+        let commitment_scheme = &mut CommitmentSchemeVerifier::<MC>::new(pcs_config);
+        let log_sizes = Self::column_log_sizes(extensions, &all_log_sizes);
         for idx in [PREPROCESSED_TRACE_IDX, ORIGINAL_TRACE_IDX] {
             commitment_scheme.commit(proof.commitments[idx], &log_sizes[idx], verifier_channel);
         }
 
         let mut lookup_elements = AllLookupElements::default();
         C::draw_lookup_elements(&mut lookup_elements, verifier_channel, &extensions_config);
+        for ext in extensions {
+            ext.draw_lookup_elements(&mut lookup_elements, verifier_channel);
+        }
+
+        // The exit code and the public output consumed by the trace are added on the verifier side.
+        let proven_output_sum =
+            proven_output_sum(&lookup_elements, program_trace_ref.proven_output());
+        if claimed_sum.iter().sum::<SecureField>() + proven_output_sum != SecureField::zero() {
+            return Err(VerificationError::InvalidStructure(
+                "claimed logup sum doesn't match the public output".to_string(),
+            ));
+        }
 
         let tree_span_provider = &mut TraceLocationAllocator::default();
         let main_component = MachineComponent::new(
@@ -448,8 +1319,8 @@ impl<C: MachineChip + Sync> Machine<C> {
         );
 
         let ext_components: Vec<Box<dyn Component>> = extensions_iter
-            .zip(claimed_sum.get(1..).unwrap_or_default())
-            .zip(all_log_sizes.get(1..).unwrap_or_default())
+            .zip(&claimed_sum[1..])
+            .zip(&all_log_sizes[1..])
             .map(|((ext, claimed_sum), log_size)| {
                 ext.to_component(
                     tree_span_provider,
@@ -471,6 +1342,53 @@ impl<C: MachineChip + Sync> Machine<C> {
         verify(&components_ref, verifier_channel, commitment_scheme, proof)
     }
 
+    /// Panics if the configuration is unsupported or below [`DEFAULT_MIN_SECURITY_BITS`], before any proving work is
+    /// spent on a proof the default verifier would reject.
+    fn check_config(config: ProverConfig) {
+        if let Err(err) = config.validate(DEFAULT_MIN_SECURITY_BITS) {
+            panic!("invalid prover config: {err}");
+        }
+    }
+
+    /// Binds the boundary of a continuation segment to the transcript.
+    fn mix_boundary(boundary: Option<(&BoundaryState, &BoundaryState)>, channel: &mut MC::C) {
+        let Some((initial_state, final_state)) = boundary else {
+            return;
+        };
+        for state in [initial_state, final_state] {
+            channel.mix_u64(state.pc.into());
+            for reg in state.regs {
+                channel.mix_u64(reg.into());
+            }
+            for chunk in state.memory_digest.chunks_exact(8) {
+                channel.mix_u64(u64::from_le_bytes(
+                    chunk.try_into().expect("chunk has 8 bytes"),
+                ));
+            }
+        }
+    }
+
+    /// Binds the exit code and the public output proven by the trace to the transcript.
+    fn mix_proven_output(program_trace_ref: ProgramTraceRef, channel: &mut MC::C) {
+        let proven_output = program_trace_ref.proven_output();
+        channel.mix_u64(proven_output.clone().count() as u64);
+        for PublicOutputEntry { address, value } in proven_output {
+            channel.mix_u64((u64::from(*address) << 8) | u64::from(*value));
+        }
+    }
+
+    /// Binds the prover configuration to the transcript.
+    fn mix_config(config: ProverConfig, channel: &mut MC::C) {
+        let ProverConfig {
+            log_blowup_factor,
+            n_queries,
+            pow_bits,
+        } = config;
+        channel.mix_u64(log_blowup_factor.into());
+        channel.mix_u64(n_queries as u64);
+        channel.mix_u64(pow_bits.into());
+    }
+
     /// Computes minimum allowed log_size from a slice of lengths.
     fn max_log_size(sizes: &[usize]) -> u32 {
         sizes
@@ -484,10 +1402,15 @@ impl<C: MachineChip + Sync> Machine<C> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use nexus_common::constants::{
+        ELF_TEXT_START, KECCAKF_FN3, KECCAKF_OPCODE, MEMCPY_FN3, MEMSET_FN3, MUL_ADD_MOD_FN3,
+        POSEIDON2_FN3, SHA256_COMPRESS_FN3,
+    };
     use nexus_vm::{
         riscv::{BasicBlock, BuiltinOpcode, Instruction, Opcode},
-        trace::k_trace_direct,
+        trace::{k_trace_direct, Block, UniformTrace},
     };
+    use stwo_prover::core::vcs::poseidon252_merkle::Poseidon252MerkleChannel;
 
     #[test]
     fn prove_verify() {
@@ -513,4 +1436,624 @@ mod tests {
         )
         .unwrap();
     }
+
+    #[test]
+    fn prove_verify_with_config() {
+        let basic_block = vec![BasicBlock::new(vec![
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::ADDI), 1, 0, 1),
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::ADD), 2, 1, 1),
+        ])];
+        let (view, program_trace) =
+            k_trace_direct(&basic_block, 1).expect("error generating trace");
+        let verify = |proof: Proof, min_security_bits: u32| {
+            Machine::<BaseComponent>::verify_with_extensions(
+                &[],
+                min_security_bits,
+                proof,
+                view.get_program_memory(),
+                &[],
+                view.get_initial_memory(),
+                view.get_exit_code(),
+                view.get_public_output(),
+            )
+        };
+
+        let config = ProverConfig {
+            log_blowup_factor: 2,
+            n_queries: 45,
+            pow_bits: 10,
+        };
+        let proof = Machine::<BaseComponent>::prove_with_config(&[], config, &program_trace, &view)
+            .unwrap();
+        assert_eq!(proof.config, config);
+        verify(proof.clone(), DEFAULT_MIN_SECURITY_BITS).unwrap();
+
+        // The verifier can raise the security floor.
+        assert!(verify(proof.clone(), config.security_bits() + 1).is_err());
+
+        // The configuration is bound to the transcript.
+        let mut tampered = proof.clone();
+        tampered.config.pow_bits += 1;
+        assert!(verify(tampered, DEFAULT_MIN_SECURITY_BITS).is_err());
+
+        // Proofs below the security floor are rejected.
+        let mut insecure = proof;
+        insecure.config = ProverConfig {
+            log_blowup_factor: 1,
+            n_queries: 1,
+            pow_bits: 0,
+        };
+        assert!(verify(insecure, DEFAULT_MIN_SECURITY_BITS).is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid prover config")]
+    fn prove_with_insecure_config() {
+        let basic_block = vec![BasicBlock::new(vec![Instruction::new_ir(
+            Opcode::from(BuiltinOpcode::ADDI),
+            1,
+            0,
+            1,
+        )])];
+        let (view, program_trace) =
+            k_trace_direct(&basic_block, 1).expect("error generating trace");
+        let config = ProverConfig {
+            log_blowup_factor: 1,
+            n_queries: 3,
+            pow_bits: 5,
+        };
+        let _ = Machine::<BaseComponent>::prove_with_config(&[], config, &program_trace, &view);
+    }
+
+    #[test]
+    fn estimate_matches_proof() {
+        let basic_block = vec![BasicBlock::new(vec![
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::ADDI), 1, 0, 7),
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::ADDI), 2, 0, 3),
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::MUL), 3, 1, 2),
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::DIVU), 4, 3, 2),
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::SLLI), 5, 4, 2),
+        ])];
+        let (view, program_trace) =
+            k_trace_direct(&basic_block, 1).expect("error generating trace");
+        let config = ProverConfig::default();
+
+        let estimate = Machine::<BaseComponent>::estimate(&[], config, &program_trace, &view);
+        assert_eq!(estimate.num_steps, 5);
+        assert_eq!(estimate.rows[&InstructionFamily::Alu], 2);
+        assert_eq!(estimate.rows[&InstructionFamily::MulDiv], 2);
+        assert_eq!(estimate.rows[&InstructionFamily::Shift], 1);
+        assert_eq!(
+            estimate.rows.values().sum::<usize>(),
+            1 << estimate.log_size
+        );
+
+        let proof = Machine::<BaseComponent>::prove_with_config(&[], config, &program_trace, &view)
+            .unwrap();
+        let all_log_sizes: Vec<u32> = std::iter::once(estimate.log_size)
+            .chain(
+                estimate
+                    .extension_log_sizes
+                    .iter()
+                    .map(|(_, log_size)| *log_size),
+            )
+            .collect();
+        assert_eq!(all_log_sizes, proof.log_size);
+
+        let column_log_sizes = Machine::<BaseComponent>::column_log_sizes(&[], &proof.log_size);
+        assert_eq!(
+            estimate.num_columns,
+            column_log_sizes.iter().map(Vec::len).sum::<usize>()
+        );
+        assert_eq!(
+            estimate.extended_cells,
+            estimate.cells << config.log_blowup_factor
+        );
+        assert!(estimate.proving_time > Duration::ZERO);
+    }
+
+    #[test]
+    fn estimate_matches_proof_with_precompiles() {
+        let mut instructions = vec![
+            // x1 = 0x80000, the state of keccakf at x2, of sha256_compress at x3 with the block at x4, the result of
+            // mul_add_mod at x5 with the operands at x6 and the state of poseidon2 at x7
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::ADDI), 1, 0, 1),
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::SLLI), 1, 1, 19),
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::ADDI), 2, 1, 8),
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::ADDI), 3, 1, 256),
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::ADDI), 4, 1, 512),
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::ADDI), 5, 1, 768),
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::ADDI), 6, 1, 1024),
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::ADDI), 7, 1, 1280),
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::MUL), 8, 7, 6),
+        ];
+        let custom = |fn3, name, op_a, op_b, op_c| {
+            Instruction::new_ir(
+                Opcode::new(KECCAKF_OPCODE, Some(fn3), None, name),
+                op_a,
+                op_b,
+                op_c,
+            )
+        };
+        instructions.extend(vec![custom(KECCAKF_FN3, "keccakf", 2, 0, 0); 3]);
+        instructions.extend(vec![
+            custom(SHA256_COMPRESS_FN3, "sha256_compress", 3, 4, 0);
+            2
+        ]);
+        instructions.extend(vec![custom(MUL_ADD_MOD_FN3, "mul_add_mod", 5, 6, 0); 2]);
+        instructions.extend(vec![custom(POSEIDON2_FN3, "poseidon2", 7, 0, 0); 17]);
+        instructions.extend([
+            custom(MEMCPY_FN3, "memcpy", 3, 2, 40),
+            custom(MEMSET_FN3, "memset", 7, 8, 27),
+        ]);
+        let basic_block = vec![BasicBlock::new(instructions)];
+        let (view, program_trace) =
+            k_trace_direct(&basic_block, 1).expect("error generating trace");
+        let config = ProverConfig::default();
+        let extensions = Extension::to_components(&[
+            Extension::Keccak,
+            Extension::Sha256,
+            Extension::BigInt,
+            Extension::Poseidon2,
+            Extension::Memcpy,
+        ]);
+
+        let estimate =
+            Machine::<BaseComponent>::estimate(&extensions, config, &program_trace, &view);
+        assert_eq!(estimate.rows[&InstructionFamily::Custom], 26);
+        assert_eq!(estimate.rows[&InstructionFamily::MulDiv], 1);
+
+        let proof =
+            Machine::<BaseComponent>::prove_with_config(&extensions, config, &program_trace, &view)
+                .unwrap();
+        let all_log_sizes: Vec<u32> = std::iter::once(estimate.log_size)
+            .chain(
+                estimate
+                    .extension_log_sizes
+                    .iter()
+                    .map(|(_, log_size)| *log_size),
+            )
+            .collect();
+        assert_eq!(all_log_sizes, proof.log_size);
+
+        let column_log_sizes =
+            Machine::<BaseComponent>::column_log_sizes(&extensions, &proof.log_size);
+        assert_eq!(
+            estimate.num_columns,
+            column_log_sizes.iter().map(Vec::len).sum::<usize>()
+        );
+    }
+
+    #[test]
+    fn fill_main_trace_in_chunks() {
+        let mut instructions = vec![
+            // x1 = 0x80000, the state of keccakf at x2, and a copy of the bytes at x1 at x8
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::ADDI), 1, 0, 1),
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::SLLI), 1, 1, 19),
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::ADDI), 2, 1, 512),
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::ADDI), 8, 1, 256),
+        ];
+        let custom = |fn3, name, op_a, op_b, op_c| {
+            Instruction::new_ir(
+                Opcode::new(KECCAKF_OPCODE, Some(fn3), None, name),
+                op_a,
+                op_b,
+                op_c,
+            )
+        };
+        for i in 0..8 {
+            instructions.extend([
+                Instruction::new_ir(Opcode::from(BuiltinOpcode::ADDI), 3, 3, i + 1),
+                Instruction::new_ir(Opcode::from(BuiltinOpcode::SW), 1, 3, 4 * (i % 3)),
+                Instruction::new_ir(Opcode::from(BuiltinOpcode::LW), 4, 1, 4 * (i % 2)),
+                Instruction::new_ir(Opcode::from(BuiltinOpcode::SB), 1, 4, i),
+                Instruction::new_ir(Opcode::from(BuiltinOpcode::LBU), 5, 1, i / 2),
+                Instruction::new_ir(Opcode::from(BuiltinOpcode::XOR), 6, 5, 4),
+                Instruction::new_ir(Opcode::from(BuiltinOpcode::AND), 7, 6, 3),
+                // Precompiles move timestamps forward, the state of keccakf is only accessed by them.
+                custom(KECCAKF_FN3, "keccakf", 2, 0, 0),
+                custom(MEMCPY_FN3, "memcpy", 8, 1, 12),
+            ]);
+            if i % 2 == 1 {
+                // The state is read back by loads of the following rows.
+                instructions.push(custom(POSEIDON2_FN3, "poseidon2", 1, 0, 0));
+            }
+        }
+        let basic_block = vec![BasicBlock::new(instructions)];
+        let (view, program_trace) =
+            k_trace_direct(&basic_block, 1).expect("error generating trace");
+        let program_trace_ref = ProgramTraceRef {
+            program_memory: view.get_program_memory(),
+            init_memory: view.get_initial_memory(),
+            exit_code: view.get_exit_code(),
+            public_output: view.get_public_output(),
+            segment: None,
+        };
+        // Steps of the same trace, filled in batches as if they were streamed.
+        struct Streamed<'a>(&'a UniformTrace);
+
+        impl TraceSource for Streamed<'_> {
+            fn num_steps(&self) -> usize {
+                self.0.num_steps()
+            }
+
+            fn stream_blocks(&self) -> impl Iterator<Item = Cow<'_, Block>> + '_ {
+                self.0.stream_blocks()
+            }
+
+            fn is_streaming(&self) -> bool {
+                true
+            }
+        }
+
+        let log_size = PreprocessedTraces::MIN_LOG_SIZE;
+        let extensions =
+            Extension::to_components(&[Extension::Keccak, Extension::Poseidon2, Extension::Memcpy]);
+        let fill = |streaming: bool, chunk_size: usize| {
+            let mut side_note = SideNote::from_program_trace_ref(program_trace_ref);
+            let config = ExtensionsConfig::from(extensions.as_slice());
+            let traces = if streaming {
+                Machine::<BaseComponent>::fill_main_trace_in_chunks(
+                    &Streamed(&program_trace),
+                    log_size,
+                    &mut side_note,
+                    &config,
+                    chunk_size,
+                )
+            } else {
+                Machine::<BaseComponent>::fill_main_trace_in_chunks(
+                    &program_trace,
+                    log_size,
+                    &mut side_note,
+                    &config,
+                    chunk_size,
+                )
+            };
+            (traces, side_note)
+        };
+
+        let (expected_traces, expected) = fill(false, 1 << log_size);
+        for (streaming, chunk_size) in itertools::iproduct!([false, true], [1, 3, 7, 64]) {
+            let (traces, side_note) = fill(streaming, chunk_size);
+            assert_eq!(traces.cols, expected_traces.cols);
+            assert_eq!(
+                side_note.program_mem_check.last_access_counter,
+                expected.program_mem_check.last_access_counter
+            );
+            assert_eq!(side_note.register_mem_check, expected.register_mem_check);
+            assert_eq!(
+                side_note.rw_mem_check.last_access,
+                expected.rw_mem_check.last_access
+            );
+            assert_eq!(
+                side_note.bit_op.multiplicity_xor,
+                expected.bit_op.multiplicity_xor
+            );
+            assert_eq!(
+                side_note.bit_op.multiplicity_and,
+                expected.bit_op.multiplicity_and
+            );
+            assert_eq!(side_note.range8.multiplicity, expected.range8.multiplicity);
+            assert_eq!(
+                side_note.range256.multiplicity,
+                expected.range256.multiplicity
+            );
+            assert_eq!(side_note.keccak.timestamps, expected.keccak.timestamps);
+            assert_eq!(
+                side_note.poseidon2.timestamps,
+                expected.poseidon2.timestamps
+            );
+            assert_eq!(side_note.poseidon2.inputs, expected.poseidon2.inputs);
+            assert_eq!(side_note.memcpy.timestamps, expected.memcpy.timestamps);
+            assert_eq!(side_note.memcpy.values, expected.memcpy.values);
+        }
+    }
+
+    #[test]
+    fn debug_constraints() {
+        use crate::trace::eval::{trace_eval, TraceEval};
+        use stwo_prover::constraint_framework::EvalAtRow;
+
+        // Forbids additions.
+        struct NoAddChip;
+
+        impl MachineChip for NoAddChip {
+            fn fill_main_trace(
+                _traces: &mut TracesBuilder,
+                _row_idx: usize,
+                _vm_step: &Option<ProgramStep>,
+                _side_note: &mut SideNote,
+                _config: &ExtensionsConfig,
+            ) {
+            }
+
+            fn add_constraints<E: EvalAtRow>(
+                eval: &mut E,
+                trace_eval: &TraceEval<E>,
+                _lookup_elements: &AllLookupElements,
+                _config: &ExtensionsConfig,
+            ) {
+                let [is_add] = trace_eval!(trace_eval, Column::IsAdd);
+                eval.add_constraint(is_add);
+            }
+        }
+
+        let basic_block = vec![BasicBlock::new(vec![
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::ADDI), 1, 0, 1),
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::ADD), 2, 1, 1),
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::SUB), 3, 2, 1),
+        ])];
+        let (view, program_trace) =
+            k_trace_direct(&basic_block, 1).expect("error generating trace");
+
+        assert!(Machine::<BaseComponent>::debug_constraints(&program_trace, &view, &[]).is_empty());
+
+        let violations =
+            Machine::<(BaseComponent, NoAddChip)>::debug_constraints(&program_trace, &view, &[]);
+        assert_eq!(
+            violations,
+            vec![ConstraintViolation {
+                component: debug::MAIN_COMPONENT.to_owned(),
+                chip: "NoAddChip".to_owned(),
+                row: 1,
+                pc: Some(ELF_TEXT_START + 4),
+                instruction: Some(basic_block[0].0[1].to_string()),
+                kind: debug::ViolationKind::Constraint(0),
+                columns: vec!["IsAdd".to_owned()],
+            }]
+        );
+    }
+
+    #[test]
+    fn prove_verify_with_key() {
+        let basic_block = vec![BasicBlock::new(vec![
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::ADDI), 1, 0, 1),
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::ADD), 2, 1, 1),
+        ])];
+        let (view, program_trace) =
+            k_trace_direct(&basic_block, 1).expect("error generating trace");
+        let verify = |key: &VerifyingKey, proof: Proof| {
+            Machine::<BaseComponent>::verify_with_key(
+                key,
+                proof,
+                &[],
+                view.get_exit_code(),
+                view.get_public_output(),
+            )
+        };
+
+        let proof = Machine::<BaseComponent>::prove(&program_trace, &view).unwrap();
+        // The key doesn't depend on the execution, commitments are added per shape.
+        let mut key = Machine::<BaseComponent>::setup(
+            &[],
+            proof.config,
+            DEFAULT_MIN_SECURITY_BITS,
+            view.get_program_memory(),
+            view.get_initial_memory(),
+        )
+        .unwrap();
+        assert!(verify(&key, proof.clone()).is_err());
+        Machine::<BaseComponent>::prepare(&mut key, &proof.log_size).unwrap();
+        assert_eq!(key.preprocessed_commitments.len(), 1);
+        verify(&key, proof.clone()).unwrap();
+
+        // Verifying only reads the prepared commitment, no trace is generated from the program of the key.
+        let mut stripped_key = key.clone();
+        stripped_key.program_info.program.clear();
+        stripped_key.init_memory.clear();
+        verify(&stripped_key, proof.clone()).unwrap();
+
+        // The prepared commitment is still checked against the proof.
+        let mut other_log_size = proof.log_size.clone();
+        other_log_size[0] += 1;
+        let mut other_key = key.clone();
+        other_key.preprocessed_commitments.clear();
+        Machine::<BaseComponent>::prepare(&mut other_key, &other_log_size).unwrap();
+        let mut tampered_key = key.clone();
+        tampered_key.preprocessed_commitments[0].1 = other_key.preprocessed_commitments[0].1;
+        assert!(verify(&tampered_key, proof.clone()).is_err());
+
+        // The public output isn't a part of the key, but it's still checked against the trace.
+        let mut output_memory = view.get_public_output().to_vec();
+        output_memory.push(PublicOutputEntry {
+            address: 0x1000,
+            value: 1,
+        });
+        assert!(Machine::<BaseComponent>::verify_with_key(
+            &key,
+            proof,
+            &[],
+            view.get_exit_code(),
+            &output_memory,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn prove_verify_poseidon252() {
+        let basic_block = vec![BasicBlock::new(vec![
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::ADDI), 1, 0, 1),
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::ADD), 2, 1, 1),
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::ADD), 3, 2, 1),
+        ])];
+        let (view, program_trace) =
+            k_trace_direct(&basic_block, 1).expect("error generating trace");
+
+        type PoseidonMachine = Machine<BaseComponent, Poseidon252MerkleChannel>;
+        let proof = PoseidonMachine::prove(&program_trace, &view).unwrap();
+        PoseidonMachine::verify(
+            proof,
+            view.get_program_memory(),
+            &[],
+            view.get_initial_memory(),
+            view.get_exit_code(),
+            view.get_public_output(),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn verify_store_to_read_only_memory() {
+        let basic_block = vec![BasicBlock::new(vec![
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::ADDI), 1, 0, 1),
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::SLLI), 1, 1, 19),
+            // *x1 = 0
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::SB), 1, 0, 0),
+        ])];
+        let (view, program_trace) =
+            k_trace_direct(&basic_block, 1).expect("error generating trace");
+
+        // The stored byte is a part of the publicly known initial memory.
+        let init_memory = vec![MemoryInitializationEntry {
+            address: 1 << 19,
+            value: 1,
+            read_only: false,
+        }];
+        let view = View::new(
+            &None,
+            &vec![],
+            view.get_program_memory(),
+            &init_memory,
+            0,
+            &vec![],
+            &vec![],
+            &vec![],
+        );
+        let proof = Machine::<BaseComponent>::prove(&program_trace, &view).unwrap();
+        let verify = |proof: Proof, init_memory: &[MemoryInitializationEntry]| {
+            Machine::<BaseComponent>::verify(
+                proof,
+                view.get_program_memory(),
+                &[],
+                init_memory,
+                view.get_exit_code(),
+                view.get_public_output(),
+            )
+        };
+        verify(proof.clone(), &init_memory).unwrap();
+
+        // The same execution is invalid if the byte is read-only.
+        let read_only_memory: Vec<_> = init_memory
+            .into_iter()
+            .map(MemoryInitializationEntry::into_read_only)
+            .collect();
+        assert!(verify(proof, &read_only_memory).is_err());
+    }
+
+    #[test]
+    fn prove_verify_load_from_program() {
+        let basic_block = vec![BasicBlock::new(vec![
+            // x1 = address of the first instruction
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::ADDI), 1, 0, ELF_TEXT_START),
+            // x2 = the first instruction word
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::LW), 2, 1, 0),
+            // x3 = the second byte of the second instruction word
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::LBU), 3, 1, 5),
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::ADD), 4, 2, 3),
+        ])];
+        let (view, program_trace) =
+            k_trace_direct(&basic_block, 1).expect("error generating trace");
+        let program = &view.get_program_memory().program;
+
+        let proof = Machine::<BaseComponent>::prove(&program_trace, &view).unwrap();
+        Machine::<BaseComponent>::verify(
+            proof,
+            view.get_program_memory(),
+            &[],
+            view.get_initial_memory(),
+            view.get_exit_code(),
+            view.get_public_output(),
+        )
+        .unwrap();
+
+        let proofs = Machine::<BaseComponent>::prove_segments(&program_trace, &view, 2).unwrap();
+        let final_regs = proofs.last().unwrap().final_state.regs;
+        assert_eq!(final_regs[2], program[0].instruction_word);
+        assert_eq!(
+            final_regs[3],
+            program[1].instruction_word.to_le_bytes()[1] as u32
+        );
+        Machine::<BaseComponent>::verify_segments(
+            proofs,
+            view.get_program_memory(),
+            &[],
+            view.get_initial_memory(),
+            view.get_exit_code(),
+            view.get_public_output(),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn prove_verify_segments() {
+        let basic_block = vec![BasicBlock::new(vec![
+            // x1 = 0x80000
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::ADDI), 1, 0, 1),
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::SLLI), 1, 1, 19),
+            // *x1 = 128
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::ADDI), 3, 0, 128),
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::SW), 1, 3, 0),
+            // the following segment reads memory and registers written by the first one
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::ADDI), 3, 3, 1),
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::LW), 4, 1, 0),
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::SB), 1, 3, 5),
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::LBU), 5, 1, 5),
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::ADD), 6, 4, 5),
+        ])];
+        let (view, program_trace) =
+            k_trace_direct(&basic_block, 1).expect("error generating trace");
+        let verify = |proofs: Vec<SegmentProof>| {
+            Machine::<BaseComponent>::verify_segments(
+                proofs,
+                view.get_program_memory(),
+                &[],
+                view.get_initial_memory(),
+                view.get_exit_code(),
+                view.get_public_output(),
+            )
+        };
+
+        let proofs = Machine::<BaseComponent>::prove_segments(&program_trace, &view, 4).unwrap();
+        assert_eq!(proofs.len(), 3);
+        assert_eq!(proofs[2].final_state.regs[6], 128 + 129);
+        verify(proofs.clone()).unwrap();
+
+        // Segments must be chained in order and none of them can be skipped.
+        let mut reordered = proofs.clone();
+        reordered.swap(1, 2);
+        assert!(verify(reordered).is_err());
+        let mut truncated = proofs.clone();
+        truncated.remove(1);
+        assert!(verify(truncated).is_err());
+
+        // Only the bytes changed by a segment are carried in its proof.
+        assert!(proofs[0]
+            .memory_delta
+            .iter()
+            .any(|entry| entry.address == 1 << 19 && entry.value == 128));
+        assert!(proofs[2].memory_delta.is_empty());
+
+        // Consistently tampered boundary must be rejected by the segment proof.
+        let mut tampered = proofs.clone();
+        let program_range = program_memory_range(view.get_program_memory());
+        let init_memory = segment::sorted_memory(view.get_initial_memory());
+        let entry = tampered[0]
+            .memory_delta
+            .iter_mut()
+            .find(|entry| entry.address == 1 << 19)
+            .expect("stored byte is missing");
+        entry.value += 1;
+        let final_memory =
+            segment::apply_memory_delta(&init_memory, &tampered[0].memory_delta, &program_range)
+                .unwrap();
+        let digest = segment::memory_digest(&final_memory);
+        tampered[0].final_state.memory_digest = digest;
+        tampered[1].initial_state.memory_digest = digest;
+        assert!(verify(tampered).is_err());
+
+        let mut tampered = proofs;
+        tampered[0].final_state.regs[3] += 1;
+        tampered[1].initial_state.regs[3] += 1;
+        assert!(verify(tampered).is_err());
+    }
 }
@@ -0,0 +1,267 @@
+//! Continuation (segmented) proving of long executions.
+//!
+//! The blocks of the trace are streamed and grouped into segments of a fixed number of blocks, and each segment is
+//! proven separately, so that the prover memory is bounded by the segment size rather than by the length of the
+//! execution.
+//!
+//! Every segment publicly commits to its boundary: the program counter, the registers and the complete RW memory image
+//! at its start and at its end. The boundary is a part of the preprocessed trace, whose commitment is recomputed by the
+//! verifier, and the AIR enforces that the execution starts in the initial state and ends in the final one. Both
+//! boundary states are also mixed into the Fiat-Shamir channel, so that the proof can't be replayed with another
+//! boundary. Segments are chained together by requiring each of them to start in the final state of the previous one.
+//!
+//! A segment proof only carries the bytes of the memory image changed or first accessed by the segment. The verifier
+//! applies them to the image at the start of the segment, see [`apply_memory_delta`].
+
+use std::{borrow::Cow, ops::Range};
+
+use nexus_common::riscv::register::NUM_REGISTERS;
+use nexus_vm::{
+    emulator::MemoryInitializationEntry,
+    trace::{Block, TraceSource},
+};
+use serde::{Deserialize, Serialize};
+use stwo_prover::core::{channel::MerkleChannel, vcs::blake2_merkle::Blake2sMerkleChannel};
+use tiny_keccak::{Hasher, Keccak};
+
+use crate::machine::Proof;
+
+/// Machine state at a segment boundary.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BoundaryState {
+    /// Program counter of the next instruction to be executed.
+    pub pc: u32,
+    /// Values of the registers.
+    pub regs: [u32; NUM_REGISTERS],
+    /// Digest of the RW memory image, see [`memory_digest`].
+    pub memory_digest: [u8; 32],
+}
+
+impl BoundaryState {
+    /// Creates the state from the sorted memory image.
+    pub fn new(pc: u32, regs: [u32; NUM_REGISTERS], memory: &[MemoryInitializationEntry]) -> Self {
+        Self {
+            pc,
+            regs,
+            memory_digest: memory_digest(memory),
+        }
+    }
+}
+
+/// Proof of a single segment along with its public boundary.
+#[derive(Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "Proof<MC>: Serialize",
+    deserialize = "Proof<MC>: Deserialize<'de>"
+))]
+pub struct SegmentProof<MC: MerkleChannel = Blake2sMerkleChannel> {
+    pub proof: Proof<MC>,
+    pub initial_state: BoundaryState,
+    pub final_state: BoundaryState,
+    /// Bytes of the RW memory image at the end of the segment which are not in the initial image with the same value,
+    /// sorted by address.
+    pub memory_delta: Vec<MemoryInitializationEntry>,
+}
+
+impl<MC: MerkleChannel> Clone for SegmentProof<MC>
+where
+    Proof<MC>: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            proof: self.proof.clone(),
+            initial_state: self.initial_state.clone(),
+            final_state: self.final_state.clone(),
+            memory_delta: self.memory_delta.clone(),
+        }
+    }
+}
+
+impl<MC: MerkleChannel> std::fmt::Debug for SegmentProof<MC>
+where
+    Proof<MC>: std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SegmentProof")
+            .field("proof", &self.proof)
+            .field("initial_state", &self.initial_state)
+            .field("final_state", &self.final_state)
+            .field("memory_delta", &self.memory_delta)
+            .finish()
+    }
+}
+
+impl<MC: MerkleChannel> SegmentProof<MC> {
+    /// Similarly to [`Proof::size_estimate`] returns the proof size estimate in bytes.
+    pub fn size_estimate(&self) -> usize {
+        self.proof.size_estimate()
+            + 2 * std::mem::size_of::<BoundaryState>()
+            + self.memory_delta.len() * std::mem::size_of::<MemoryInitializationEntry>()
+    }
+}
+
+/// Computes Keccak-256 digest of the memory image, which is assumed to be sorted by address.
+pub fn memory_digest(memory: &[MemoryInitializationEntry]) -> [u8; 32] {
+    let mut hasher = Keccak::v256();
+    for MemoryInitializationEntry {
+        address,
+        value,
+        read_only,
+    } in memory
+    {
+        hasher.update(&address.to_le_bytes());
+        hasher.update(&[*value, *read_only as u8]);
+    }
+    let mut output = [0u8; 32];
+    hasher.finalize(&mut output);
+    output
+}
+
+/// Returns a copy of the memory image sorted by address.
+pub(crate) fn sorted_memory(
+    memory: &[MemoryInitializationEntry],
+) -> Vec<MemoryInitializationEntry> {
+    let mut memory = memory.to_vec();
+    memory.sort_by_key(|entry| entry.address);
+    memory
+}
+
+/// Reads a byte from the sorted memory image, bytes which were never accessed are zero.
+pub(crate) fn read_memory(memory: &[MemoryInitializationEntry], address: u32) -> u8 {
+    memory
+        .binary_search_by_key(&address, |entry| entry.address)
+        .map_or(0, |idx| memory[idx].value)
+}
+
+/// Returns the bytes of the sorted final memory image which aren't in the sorted initial one with the same value.
+pub(crate) fn memory_delta(
+    init_memory: &[MemoryInitializationEntry],
+    final_memory: &[MemoryInitializationEntry],
+) -> Vec<MemoryInitializationEntry> {
+    final_memory
+        .iter()
+        .filter(|entry| {
+            !init_memory
+                .binary_search_by_key(&entry.address, |entry| entry.address)
+                .is_ok_and(|idx| init_memory[idx] == **entry)
+        })
+        .copied()
+        .collect()
+}
+
+/// Applies the memory delta of a segment to the sorted memory image at its start, returning the final image.
+///
+/// The delta must be sorted by unique addresses. Read-only bytes can't be changed, and no other byte can become
+/// read-only. The program is a public part of the RAM which never changes, it must not appear in the delta.
+pub fn apply_memory_delta(
+    init_memory: &[MemoryInitializationEntry],
+    delta: &[MemoryInitializationEntry],
+    program_range: &Range<u32>,
+) -> Result<Vec<MemoryInitializationEntry>, String> {
+    if delta
+        .windows(2)
+        .any(|pair| pair[0].address >= pair[1].address)
+    {
+        return Err("memory delta must be sorted by unique addresses".to_string());
+    }
+    if let Some(entry) = delta
+        .iter()
+        .find(|entry| program_range.contains(&entry.address))
+    {
+        return Err(format!(
+            "memory delta overlaps with the program at address={:#x}",
+            entry.address
+        ));
+    }
+    if let Some(entry) = delta.iter().find(|entry| {
+        let init = init_memory
+            .binary_search_by_key(&entry.address, |entry| entry.address)
+            .ok()
+            .map(|idx| init_memory[idx]);
+        entry.read_only || init.is_some_and(|init| init.read_only)
+    }) {
+        return Err(format!(
+            "read-only memory is changed at address={:#x}",
+            entry.address
+        ));
+    }
+
+    // Merge the two sorted lists, entries of the delta take precedence.
+    let mut memory = Vec::with_capacity(init_memory.len() + delta.len());
+    let mut init_iter = init_memory.iter().peekable();
+    for entry in delta {
+        while let Some(init) = init_iter.next_if(|init| init.address < entry.address) {
+            memory.push(*init);
+        }
+        init_iter.next_if(|init| init.address == entry.address);
+        memory.push(*entry);
+    }
+    memory.extend(init_iter);
+    Ok(memory)
+}
+
+/// Blocks of one segment, collected from the stream of blocks of the whole trace.
+pub(crate) struct SegmentBlocks {
+    pub blocks: Vec<Block>,
+}
+
+impl TraceSource for SegmentBlocks {
+    fn num_steps(&self) -> usize {
+        self.blocks.iter().map(|block| block.steps.len()).sum()
+    }
+
+    fn stream_blocks(&self) -> impl Iterator<Item = Cow<'_, Block>> + '_ {
+        self.blocks.iter().map(Cow::Borrowed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(address: u32, value: u8) -> MemoryInitializationEntry {
+        MemoryInitializationEntry {
+            address,
+            value,
+            read_only: false,
+        }
+    }
+
+    #[test]
+    fn test_apply_memory_delta() {
+        let init_memory = [entry(4, 1), entry(8, 2)];
+        let program_range = 16..24;
+        let apply = |init_memory: &[MemoryInitializationEntry],
+                     delta: &[MemoryInitializationEntry]| {
+            apply_memory_delta(init_memory, delta, &program_range)
+        };
+
+        let final_memory = [entry(4, 3), entry(6, 0), entry(8, 2)];
+        let delta = memory_delta(&init_memory, &final_memory);
+        assert_eq!(delta, [entry(4, 3), entry(6, 0)]);
+        assert_eq!(apply(&init_memory, &delta).unwrap(), final_memory);
+        assert_eq!(apply(&init_memory, &[]).unwrap(), init_memory);
+        assert_eq!(
+            apply(&init_memory, &[entry(2, 5), entry(12, 6)]).unwrap(),
+            [entry(2, 5), entry(4, 1), entry(8, 2), entry(12, 6)]
+        );
+
+        // unsorted
+        assert!(apply(&init_memory, &[entry(8, 3), entry(4, 3)]).is_err());
+        // duplicate address
+        assert!(apply(&init_memory, &[entry(4, 3), entry(4, 3)]).is_err());
+        // becomes read-only
+        assert!(apply(&init_memory, &[entry(4, 1).into_read_only()]).is_err());
+        assert!(apply(&init_memory, &[entry(6, 1).into_read_only()]).is_err());
+        // overlaps with the program
+        assert!(apply(&init_memory, &[entry(20, 0)]).is_err());
+
+        assert_eq!(read_memory(&init_memory, 8), 2);
+        assert_eq!(read_memory(&init_memory, 12), 0);
+
+        // read-only bytes never change
+        let init_memory = [entry(4, 1).into_read_only()];
+        assert!(memory_delta(&init_memory, &init_memory).is_empty());
+        assert!(apply(&init_memory, &[entry(4, 2)]).is_err());
+    }
+}
@@ -35,7 +35,11 @@ impl PreprocessedBuilder {
             Self::MIN_LOG_SIZE,
         );
         let cols = vec![vec![BaseField::zero(); 1 << log_size]; PreprocessedColumn::COLUMNS_NUM];
-        let mut ret = Self(TracesBuilder { cols, log_size });
+        let mut ret = Self(TracesBuilder {
+            cols,
+            log_size,
+            row_offset: 0,
+        });
         ret.fill_is_first();
         ret.fill_is_last();
         ret.fill_timestamps();
@@ -2,7 +2,7 @@ use nexus_common::cpu::Registers;
 use nexus_vm::{
     cpu::RegisterFile,
     riscv::{BuiltinOpcode, InstructionType, Register},
-    trace::{Step, Trace},
+    trace::{Block, Step, TraceSource},
     SyscallCode, WORD_SIZE,
 };
 
@@ -80,9 +80,9 @@ impl ProgramStep {
         if let Some(syscall_value) = self.get_syscall_code() {
             let syscall_number = SyscallCode::from(syscall_value);
             match syscall_number {
-                SyscallCode::ReadFromPrivateInput | SyscallCode::OverwriteHeapPointer => {
-                    Register::X10
-                }
+                SyscallCode::ReadFromPrivateInput
+                | SyscallCode::ReadFromAuxiliaryInput
+                | SyscallCode::OverwriteHeapPointer => Register::X10,
                 SyscallCode::OverwriteStackPointer => Register::X2,
                 _ => Register::X0,
             }
@@ -134,18 +134,19 @@ impl ProgramStep {
 /// Iterates over the program steps in `trace``, padded to `num_rows` with `None`
 ///
 /// Panics if `trace` contains more than `num_rows` steps.
-pub fn iter_program_steps<TR: Trace>(
+pub fn iter_program_steps<TR: TraceSource>(
     trace: &TR,
     num_rows: usize,
 ) -> impl Iterator<Item = Option<ProgramStep>> + '_ {
-    assert!(trace.get_num_steps() <= num_rows, "Too many ProgramSteps");
+    assert!(trace.num_steps() <= num_rows, "Too many ProgramSteps");
     trace
-        .get_blocks_iter()
+        .stream_blocks()
         .map(|block| {
-            assert_eq!(block.steps.len(), 1, "Only k = 1 traces are supported.");
+            let Block { regs, mut steps } = block.into_owned();
+            assert_eq!(steps.len(), 1, "Only k = 1 traces are supported.");
             Some(ProgramStep {
-                step: block.steps[0].clone(),
-                regs: block.regs,
+                step: steps.pop().expect("block has a step"),
+                regs,
             })
         })
         .chain(std::iter::repeat(None))
@@ -1,3 +1,5 @@
+use std::ops::Range;
+
 use num_traits::Zero;
 use stwo_prover::core::{
     backend::simd::{column::BaseColumn, m31::LOG_N_LANES, SimdBackend},
@@ -15,18 +17,17 @@ use super::{
 };
 use crate::column::ProgramColumn;
 
-use nexus_vm::{
-    emulator::{MemoryInitializationEntry, ProgramInfo, ProgramMemoryEntry, PublicOutputEntry},
-    WORD_SIZE,
+use nexus_common::riscv::register::NUM_REGISTERS;
+use nexus_vm::emulator::{
+    MemoryInitializationEntry, ProgramInfo, ProgramMemoryEntry, PublicOutputEntry,
 };
 
 /// Wrapper around [`TracesBuilder`] that contains the program layout for figuring out the row_idx out of pc.
 pub struct ProgramTracesBuilder {
     traces_builder: TracesBuilder,
-    /// Program counter written on the first row. The current assumption is that the program is in contiguous memory starting from [`Self::pc_offset`].
+    /// Program counter written on each row, in increasing order. The current assumption is that the program is in contiguous memory.
     /// This value is used by the program memory checking when it computes the row index corresponding to a pc value.
-    pub(crate) pc_offset: u32,
-    pub(crate) num_instructions: usize,
+    pub(crate) pcs: Vec<u32>,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -39,6 +40,78 @@ pub struct ProgramTraceRef<'a> {
     pub exit_code: &'a [PublicOutputEntry],
     /// Slice of public output entries.
     pub public_output: &'a [PublicOutputEntry],
+    /// Public boundary of a continuation segment, `None` if the trace covers the whole execution.
+    ///
+    /// For a segment, `init_memory` is the complete RW memory image at the start of the segment, and
+    /// both `exit_code` and `public_output` are expected to be empty.
+    pub segment: Option<SegmentBoundaryRef<'a>>,
+}
+
+/// Publicly known boundary of a continuation segment, see [`crate::segment`].
+#[derive(Debug, Clone, Copy)]
+pub struct SegmentBoundaryRef<'a> {
+    /// Program counter of the first instruction executed in the segment.
+    pub initial_pc: u32,
+    /// Program counter following the last instruction executed in the segment.
+    pub final_pc: u32,
+    /// Register values at the start of the segment.
+    pub initial_regs: &'a [u32; NUM_REGISTERS],
+    /// Register values at the end of the segment.
+    pub final_regs: &'a [u32; NUM_REGISTERS],
+    /// Complete RW memory image at the end of the segment, sorted by address.
+    pub final_memory: &'a [MemoryInitializationEntry],
+}
+
+impl<'a> ProgramTraceRef<'a> {
+    /// Returns the program counter of the first executed instruction.
+    pub fn initial_pc(&self) -> u32 {
+        self.segment
+            .map_or(self.program_memory.initial_pc, |segment| segment.initial_pc)
+    }
+
+    /// Returns the bytes of the exit code and the public output proven to be in the final RAM state.
+    ///
+    /// They aren't a part of the preprocessed trace, the verifier adds them to the logup sum instead. Continuation
+    /// segments don't prove any output, their final RAM state is public.
+    pub fn proven_output(&self) -> impl Iterator<Item = &'a PublicOutputEntry> + Clone {
+        let (exit_code, public_output): (&'a [PublicOutputEntry], &'a [PublicOutputEntry]) =
+            match self.segment {
+                Some(_) => (&[], &[]),
+                None => (self.exit_code, self.public_output),
+            };
+        exit_code.iter().chain(public_output)
+    }
+}
+
+/// Returns the bytes of the program as read-only RAM, so that loads from the program memory return instruction words.
+pub(crate) fn program_memory_bytes(
+    program_memory: &ProgramInfo,
+) -> impl Iterator<Item = MemoryInitializationEntry> + '_ {
+    program_memory.program.iter().flat_map(|entry| {
+        let pc = entry.pc;
+        entry
+            .instruction_word
+            .to_le_bytes()
+            .into_iter()
+            .take(entry.size() as usize)
+            .enumerate()
+            .map(move |(i, value)| MemoryInitializationEntry {
+                address: pc + i as u32,
+                value,
+                read_only: true,
+            })
+    })
+}
+
+/// Returns the range of addresses occupied by the program, which is assumed to be contiguous.
+pub(crate) fn program_memory_range(program_memory: &ProgramInfo) -> Range<u32> {
+    match (
+        program_memory.program.first(),
+        program_memory.program.last(),
+    ) {
+        (Some(first), Some(last)) => first.pc..last.pc + last.size(),
+        _ => 0..0,
+    }
 }
 
 #[cfg(test)]
@@ -49,6 +122,7 @@ impl<'a> ProgramTraceRef<'a> {
             init_memory: &[],
             exit_code: &[],
             public_output: &[],
+            segment: None,
         }
     }
 }
@@ -62,49 +136,49 @@ impl ProgramTracesBuilder {
         );
 
         let cols = vec![vec![BaseField::zero(); 1 << log_size]; ProgramColumn::COLUMNS_NUM];
-        let builder = TracesBuilder { cols, log_size };
+        let builder = TracesBuilder {
+            cols,
+            log_size,
+            row_offset: 0,
+        };
         let mut ret = Self {
             traces_builder: builder,
-            pc_offset: 0u32,
-            num_instructions: 0usize,
+            pcs: Vec::with_capacity(params.program_memory.program.len()),
         };
 
-        ret.fill_program_columns(
-            0,
-            params.program_memory.initial_pc,
-            ProgramColumn::PrgInitialPc,
-        );
-        for (
-            row_idx,
-            ProgramMemoryEntry {
-                pc,
-                instruction_word,
-            },
-        ) in params.program_memory.program.iter().enumerate()
-        {
-            if row_idx == 0 {
-                ret.pc_offset = *pc;
+        ret.fill_program_columns(0, params.initial_pc(), ProgramColumn::PrgInitialPc);
+        if let Some(segment) = params.segment {
+            for row_idx in 0..1 << log_size {
+                ret.fill_program_columns(row_idx, segment.final_pc, ProgramColumn::PrgFinalPc);
+                ret.fill_program_columns(row_idx, true, ProgramColumn::PrgSegmentFlag);
             }
-            ret.num_instructions += 1;
-            assert_eq!(
-                row_idx * WORD_SIZE + ret.pc_offset as usize,
-                *pc as usize,
+        }
+        let mut next_pc = None;
+        for (row_idx, entry) in params.program_memory.program.iter().enumerate() {
+            let ProgramMemoryEntry { pc, compressed, .. } = *entry;
+            assert!(
+                next_pc.is_none_or(|next_pc| next_pc == pc),
                 "The program is assumed to be in contiguous memory."
             );
-            let (pc_low, pc_high) = (*pc & 0xFFFF, *pc >> 16);
+            next_pc = Some(pc + entry.size());
+            ret.pcs.push(pc);
+            let (pc_low, pc_high) = (pc & 0xFFFF, pc >> 16);
             ret.fill_program_columns(
                 row_idx,
                 [pc_low, pc_high].map(BaseField::from),
                 ProgramColumn::PrgMemoryPc,
             );
+            // Compressed instructions are checked against the expanded word the CPU executes.
+            let instruction_word = entry.expanded_word();
             let (instruction_low, instruction_high) =
-                (*instruction_word & 0xFFFF, *instruction_word >> 16);
+                (instruction_word & 0xFFFF, instruction_word >> 16);
             ret.fill_program_columns(
                 row_idx,
                 [instruction_low, instruction_high].map(BaseField::from),
                 ProgramColumn::PrgMemoryWord,
             );
             ret.fill_program_columns(row_idx, true, ProgramColumn::PrgMemoryFlag);
+            ret.fill_program_columns(row_idx, compressed, ProgramColumn::PrgMemoryCompressed);
         }
         ret
     }
@@ -26,6 +26,13 @@ impl RegisterMemCheckSideNote {
             last_access_value: [0; NUM_REGISTERS],
         }
     }
+    /// Creates a side note for registers holding `values` at timestamp zero, as at the start of a continuation segment.
+    pub(crate) fn with_initial_values(values: [u32; NUM_REGISTERS]) -> Self {
+        Self {
+            last_access_timestamp: [0; NUM_REGISTERS],
+            last_access_value: values,
+        }
+    }
     pub(crate) fn access(&mut self, reg: u32, cur_timestamp: u32, cur_value: u32) -> AccessResult {
         assert!((reg as usize) < NUM_REGISTERS);
         let ret = AccessResult {
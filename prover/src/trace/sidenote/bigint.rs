@@ -0,0 +1,21 @@
+#[derive(Default)]
+pub struct BigIntSideNote {
+    /// Operands `a`, `b`, `c` and `m` of each `mul_add_mod` call.
+    pub(crate) inputs: Vec<[[u32; 8]; 4]>,
+    /// Bytes of the result memory before each call.
+    pub(crate) prev_results: Vec<[u8; 32]>,
+    /// Addresses of the result and of the operands.
+    pub(crate) addresses: Vec<(u32, u32)>,
+    /// Previous timestamps of the operand bytes followed by the ones of the result bytes.
+    pub(crate) timestamps: Vec<Vec<u32>>,
+}
+
+impl BigIntSideNote {
+    /// Appends the calls recorded by a fork filled for the following rows.
+    pub(crate) fn merge(&mut self, other: Self) {
+        self.inputs.extend(other.inputs);
+        self.prev_results.extend(other.prev_results);
+        self.addresses.extend(other.addresses);
+        self.timestamps.extend(other.timestamps);
+    }
+}
@@ -30,3 +30,14 @@ pub struct RoundLookups {
     pub(crate) xor_rc_lookup: (usize, usize),
     pub(crate) output_state_lookup: Vec<usize>,
 }
+
+impl KeccakSideNote {
+    /// Appends the calls recorded by a fork filled for the following rows.
+    ///
+    /// Accumulators and round lookups are only filled after the main trace, they are left untouched.
+    pub(crate) fn merge(&mut self, other: Self) {
+        self.inputs.extend(other.inputs);
+        self.timestamps.extend(other.timestamps);
+        self.addresses.extend(other.addresses);
+    }
+}
@@ -0,0 +1,24 @@
+#[derive(Default)]
+pub struct MemcpySideNote {
+    /// Bytes written by `memcpy` and `memset` calls, one entry per byte.
+    pub(crate) values: Vec<u8>,
+    /// Bytes of the destination memory before each call.
+    pub(crate) prev_values: Vec<u8>,
+    /// Destination addresses, along with source addresses of bytes copied by `memcpy`.
+    pub(crate) addresses: Vec<(u32, Option<u32>)>,
+    /// Previous timestamps of the destination bytes, along with the ones of bytes read by `memcpy`.
+    pub(crate) timestamps: Vec<(u32, Option<u32>)>,
+    /// Number of bytes left to write by the call, including the current one.
+    pub(crate) remaining: Vec<u32>,
+}
+
+impl MemcpySideNote {
+    /// Appends the calls recorded by a fork filled for the following rows.
+    pub(crate) fn merge(&mut self, other: Self) {
+        self.values.extend(other.values);
+        self.prev_values.extend(other.prev_values);
+        self.addresses.extend(other.addresses);
+        self.timestamps.extend(other.timestamps);
+        self.remaining.extend(other.remaining);
+    }
+}
@@ -1,23 +1,34 @@
 // This file defines the side note structures for main trace filling
 
-use std::collections::BTreeMap;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
+};
 
-use nexus_vm::{
-    emulator::{InternalView, MemoryInitializationEntry, PublicOutputEntry, View},
-    WORD_SIZE,
+use nexus_common::riscv::register::NUM_REGISTERS;
+use nexus_vm::emulator::{
+    InternalView, MemoryInitializationEntry, ProgramInfo, PublicOutputEntry, View,
 };
 
-use super::{program_trace::ProgramTracesBuilder, regs::RegisterMemCheckSideNote};
+use crate::{chips::instructions::load_store::AccessMode, extensions::ExternalStep};
+
+use super::{
+    program_trace::{program_memory_bytes, ProgramTraceRef, ProgramTracesBuilder},
+    regs::RegisterMemCheckSideNote,
+};
 
+pub(crate) mod bigint;
 pub(crate) mod keccak;
+pub(crate) mod memcpy;
+pub(crate) mod poseidon2;
+pub(crate) mod sha256;
 
 pub struct ProgramMemCheckSideNote {
     /// For each Pc, the number of accesses to that Pc so far (None if never)
     pub(crate) last_access_counter: BTreeMap<u32, u32>,
-    /// Program counter written on the first row. The current assumption is that the program is in contiguous memory starting from [`Self::pc_offset`].
+    /// Program counter written on each row of the program trace, in increasing order.
     /// This value is used by the program memory checking when it computes the row index corresponding to a pc value.
-    pc_offset: u32,
-    num_instructions: usize,
+    pcs: Arc<[u32]>,
 }
 
 /// Side note for committing to the final RW memory content and for computing the final read digest
@@ -25,24 +36,42 @@ pub struct ProgramMemCheckSideNote {
 pub struct ReadWriteMemCheckSideNote {
     /// u32 is the access counter, u8 is the value of the byte
     pub(crate) last_access: BTreeMap<u32, (u32, u8)>,
-    /// Public output with the exit code.
-    pub(crate) public_output: BTreeMap<u32, u8>,
+    /// Public output with the exit code, shared by forks.
+    pub(crate) public_output: Arc<BTreeMap<u32, u8>>,
+    /// Addresses of the read-only initial memory, shared by forks.
+    pub(crate) read_only: Arc<BTreeSet<u32>>,
+    /// Whether the previous values are unknown, as when filling rows without the accesses made before them.
+    pub(crate) speculative: bool,
+    /// Addresses first accessed by a precompile while filling speculatively, along with whether the value was
+    /// written since, see [`Self::precompile_access`].
+    pub(crate) relative: BTreeMap<u32, bool>,
 }
 
 impl ReadWriteMemCheckSideNote {
     /// Create a new side note for read write memory checking
     ///
     /// The side note will be used for keeping track of the latest value and access counter for each address, to be put under memory checking.
+    /// * `program_memory` - the program, which is a part of the read-only initial memory
     /// * `public_output` - addresses and values of public output
     pub fn new(
+        program_memory: &ProgramInfo,
         init_memory: &[MemoryInitializationEntry],
         public_output: &[PublicOutputEntry],
         exit_code: &[PublicOutputEntry],
     ) -> Self {
         let mut ret: Self = Default::default();
-        for MemoryInitializationEntry { address, value } in init_memory {
-            let old = ret.last_access.insert(*address, (0, *value));
+        let mut read_only = BTreeSet::new();
+        for MemoryInitializationEntry {
+            address,
+            value,
+            read_only: is_read_only,
+        } in program_memory_bytes(program_memory).chain(init_memory.iter().copied())
+        {
+            let old = ret.last_access.insert(address, (0, value));
             assert!(old.is_none(), "Duplicate memory initialization entry");
+            if is_read_only {
+                read_only.insert(address);
+            }
         }
         let mut public_output: BTreeMap<u32, u8> = public_output
             .iter()
@@ -53,27 +82,54 @@ impl ReadWriteMemCheckSideNote {
                 panic!("exit code overlaps with public output at address={address} value={val}")
             }
         }
-        ret.public_output = public_output;
+        ret.public_output = Arc::new(public_output);
+        ret.read_only = Arc::new(read_only);
         ret
     }
+
+    /// Records an access at `timestamp` leaving `value` at `address`, as made by loads and stores, and returns the
+    /// previous one.
+    pub(crate) fn access(&mut self, address: u32, timestamp: u32, value: u8) -> Option<(u32, u8)> {
+        self.relative.remove(&address);
+        self.last_access.insert(address, (timestamp, value))
+    }
+
+    /// Returns the last access to `address`, for a precompile to update.
+    ///
+    /// Precompiles move the timestamp of each byte they access forward by one instead of setting it to the clock, so
+    /// the state they leave depends on the one they start from. While filling speculatively, addresses first accessed
+    /// this way are recorded, so that replaying them adds up timestamps, see [`SideNote::replay_accesses`]. `written`
+    /// tells whether the precompile overwrites the byte.
+    pub(crate) fn precompile_access(&mut self, address: u32, written: bool) -> &mut (u32, u8) {
+        if self.speculative {
+            if !self.last_access.contains_key(&address) {
+                self.relative.insert(address, written);
+            } else if let Some(relative_written) = self.relative.get_mut(&address) {
+                *relative_written |= written;
+            }
+        }
+        self.last_access.entry(address).or_default()
+    }
+
+    /// Returns the access mode of the byte at `address`.
+    ///
+    /// The public output and the exit code are write-only, bytes which are neither in the read-only initial memory
+    /// nor in the public output are read-write.
+    pub(crate) fn access_mode(&self, address: u32) -> AccessMode {
+        if self.public_output.contains_key(&address) {
+            AccessMode::WriteOnly
+        } else if self.read_only.contains(&address) {
+            AccessMode::ReadOnly
+        } else {
+            AccessMode::ReadWrite
+        }
+    }
 }
 
 impl ProgramMemCheckSideNote {
     /// Finds the row_idx from pc
     pub(crate) fn find_row_idx(&self, pc: u32) -> Option<usize> {
-        if pc < self.pc_offset {
-            return None;
-        }
-        let pc = pc - self.pc_offset;
-        let pc = pc as usize;
-        if pc % WORD_SIZE != 0 {
-            return None;
-        }
-        let row_idx = pc / WORD_SIZE;
-        if row_idx >= self.num_instructions {
-            return None;
-        }
-        Some(row_idx)
+        self.pcs.binary_search(&pc).ok()
     }
 }
 
@@ -91,6 +147,14 @@ impl<const LEN: usize> Default for RangeCheckSideNote<LEN> {
     }
 }
 
+impl<const LEN: usize> RangeCheckSideNote<LEN> {
+    fn merge(&mut self, other: Self) {
+        for (multiplicity, other) in self.multiplicity.iter_mut().zip(other.multiplicity) {
+            *multiplicity += other;
+        }
+    }
+}
+
 /// Side note for bitwise operations. Each multiplicity counter stores (b * 16 + c) as a key.
 #[derive(Default)]
 pub struct BitOpSideNote {
@@ -99,6 +163,20 @@ pub struct BitOpSideNote {
     pub(crate) multiplicity_xor: BTreeMap<u8, u32>,
 }
 
+impl BitOpSideNote {
+    fn merge(&mut self, other: Self) {
+        for (multiplicity, other) in [
+            (&mut self.multiplicity_and, other.multiplicity_and),
+            (&mut self.multiplicity_or, other.multiplicity_or),
+            (&mut self.multiplicity_xor, other.multiplicity_xor),
+        ] {
+            for (looked_up_row, count) in other {
+                *multiplicity.entry(looked_up_row).or_default() += count;
+            }
+        }
+    }
+}
+
 pub struct SideNote {
     pub program_mem_check: ProgramMemCheckSideNote,
     pub(crate) register_mem_check: RegisterMemCheckSideNote,
@@ -110,6 +188,12 @@ pub struct SideNote {
     pub(crate) range128: RangeCheckSideNote<{ 1 << 7 }>,
     pub(crate) range256: RangeCheckSideNote<{ 1 << 8 }>,
     pub(crate) keccak: keccak::KeccakSideNote,
+    pub(crate) sha256: sha256::Sha256SideNote,
+    pub(crate) bigint: bigint::BigIntSideNote,
+    pub(crate) poseidon2: poseidon2::Poseidon2SideNote,
+    pub(crate) memcpy: memcpy::MemcpySideNote,
+    /// Custom instructions proven by external extensions, in the order of execution.
+    pub(crate) external_steps: Vec<ExternalStep>,
 }
 
 impl SideNote {
@@ -117,11 +201,11 @@ impl SideNote {
         Self {
             program_mem_check: ProgramMemCheckSideNote {
                 last_access_counter: BTreeMap::new(),
-                pc_offset: program_traces.pc_offset,
-                num_instructions: program_traces.num_instructions,
+                pcs: program_traces.pcs.as_slice().into(),
             },
             register_mem_check: RegisterMemCheckSideNote::default(),
             rw_mem_check: ReadWriteMemCheckSideNote::new(
+                view.get_program_memory(),
                 view.get_initial_memory(),
                 view.get_public_output(),
                 view.get_exit_code(),
@@ -133,8 +217,175 @@ impl SideNote {
             range128: RangeCheckSideNote::<{ 1 << 7 }>::default(),
             range256: RangeCheckSideNote::<{ 1 << 8 }>::default(),
             keccak: keccak::KeccakSideNote::default(),
+            sha256: sha256::Sha256SideNote::default(),
+            bigint: bigint::BigIntSideNote::default(),
+            poseidon2: poseidon2::Poseidon2SideNote::default(),
+            memcpy: memcpy::MemcpySideNote::default(),
+            external_steps: Vec::new(),
+        }
+    }
+
+    /// Creates a side note directly from public inputs of the program trace, without building it first.
+    pub(crate) fn from_program_trace_ref(program_trace_ref: ProgramTraceRef) -> Self {
+        let program = &program_trace_ref.program_memory.program;
+        Self {
+            program_mem_check: ProgramMemCheckSideNote {
+                last_access_counter: BTreeMap::new(),
+                pcs: program.iter().map(|entry| entry.pc).collect(),
+            },
+            register_mem_check: RegisterMemCheckSideNote::default(),
+            rw_mem_check: ReadWriteMemCheckSideNote::new(
+                program_trace_ref.program_memory,
+                program_trace_ref.init_memory,
+                program_trace_ref.public_output,
+                program_trace_ref.exit_code,
+            ),
+            bit_op: BitOpSideNote::default(),
+            range8: RangeCheckSideNote::<{ 1 << 3 }>::default(),
+            range16: RangeCheckSideNote::<{ 1 << 4 }>::default(),
+            range32: RangeCheckSideNote::<{ 1 << 5 }>::default(),
+            range128: RangeCheckSideNote::<{ 1 << 7 }>::default(),
+            range256: RangeCheckSideNote::<{ 1 << 8 }>::default(),
+            keccak: keccak::KeccakSideNote::default(),
+            sha256: sha256::Sha256SideNote::default(),
+            bigint: bigint::BigIntSideNote::default(),
+            poseidon2: poseidon2::Poseidon2SideNote::default(),
+            memcpy: memcpy::MemcpySideNote::default(),
+            external_steps: Vec::new(),
+        }
+    }
+}
+
+/// Splitting the trace into chunks of rows filled in parallel.
+///
+/// Memory checking carries the state of registers, the RW memory and the program memory from one row to the next one,
+/// while multiplicities only add up and calls of precompiles are appended in order. A chunk filled with a
+/// [`SideNote::fork`] records its accesses, which are replayed to find out the state the next chunk starts with.
+impl SideNote {
+    /// Returns a side note for the same public inputs, with no accesses recorded, zero multiplicities and no calls of
+    /// precompiles.
+    pub(crate) fn fork(&self) -> Self {
+        Self {
+            program_mem_check: ProgramMemCheckSideNote {
+                last_access_counter: BTreeMap::new(),
+                pcs: Arc::clone(&self.program_mem_check.pcs),
+            },
+            register_mem_check: RegisterMemCheckSideNote::default(),
+            rw_mem_check: ReadWriteMemCheckSideNote {
+                last_access: BTreeMap::new(),
+                public_output: Arc::clone(&self.rw_mem_check.public_output),
+                read_only: Arc::clone(&self.rw_mem_check.read_only),
+                speculative: false,
+                relative: BTreeMap::new(),
+            },
+            bit_op: BitOpSideNote::default(),
+            range8: RangeCheckSideNote::<{ 1 << 3 }>::default(),
+            range16: RangeCheckSideNote::<{ 1 << 4 }>::default(),
+            range32: RangeCheckSideNote::<{ 1 << 5 }>::default(),
+            range128: RangeCheckSideNote::<{ 1 << 7 }>::default(),
+            range256: RangeCheckSideNote::<{ 1 << 8 }>::default(),
+            keccak: keccak::KeccakSideNote::default(),
+            sha256: sha256::Sha256SideNote::default(),
+            bigint: bigint::BigIntSideNote::default(),
+            poseidon2: poseidon2::Poseidon2SideNote::default(),
+            memcpy: memcpy::MemcpySideNote::default(),
+            external_steps: Vec::new(),
         }
     }
+
+    /// Returns a fork starting from the state of memory checking of `self`, restricted to what `accesses` touched.
+    ///
+    /// `accesses` is a fork filled speculatively for the same rows, so the restricted state is all the rows read, and
+    /// copying it costs as much as the accesses of the chunk rather than the whole memory.
+    pub(crate) fn fork_for_accesses(&self, accesses: &Self) -> Self {
+        let mut fork = self.fork();
+        fork.program_mem_check.last_access_counter = accesses
+            .program_mem_check
+            .last_access_counter
+            .keys()
+            .filter_map(|pc| {
+                let counter = self.program_mem_check.last_access_counter.get(pc)?;
+                Some((*pc, *counter))
+            })
+            .collect();
+        fork.register_mem_check = self.register_mem_check;
+        fork.rw_mem_check.last_access = accesses
+            .rw_mem_check
+            .last_access
+            .keys()
+            .filter_map(|address| {
+                let last_access = self.rw_mem_check.last_access.get(address)?;
+                Some((*address, *last_access))
+            })
+            .collect();
+        fork
+    }
+
+    /// Replays accesses recorded by a fork filled for the rows following the ones of `self`.
+    pub(crate) fn replay_accesses(&mut self, fork: &Self) {
+        for (&pc, &counter) in &fork.program_mem_check.last_access_counter {
+            *self
+                .program_mem_check
+                .last_access_counter
+                .entry(pc)
+                .or_default() += counter;
+        }
+        let registers = &fork.register_mem_check;
+        for reg_idx in 0..NUM_REGISTERS {
+            // Accesses always have a positive timestamp.
+            if registers.last_access_timestamp[reg_idx] != 0 {
+                self.register_mem_check.last_access_timestamp[reg_idx] =
+                    registers.last_access_timestamp[reg_idx];
+                self.register_mem_check.last_access_value[reg_idx] =
+                    registers.last_access_value[reg_idx];
+            }
+        }
+        for (&address, &(timestamp, value)) in &fork.rw_mem_check.last_access {
+            let last_access = match fork.rw_mem_check.relative.get(&address) {
+                // The fork counted accesses of precompiles from a zero timestamp.
+                Some(&written) => {
+                    let (prev_timestamp, prev_value) = self
+                        .rw_mem_check
+                        .last_access
+                        .get(&address)
+                        .copied()
+                        .unwrap_or_default();
+                    (
+                        prev_timestamp + timestamp,
+                        if written { value } else { prev_value },
+                    )
+                }
+                None => (timestamp, value),
+            };
+            self.rw_mem_check.last_access.insert(address, last_access);
+        }
+    }
+
+    /// Merges a fork filled for the rows following the ones of `self`, starting from its state of memory checking.
+    ///
+    /// The state of memory checking is updated with the entries of the fork, while multiplicities and steps proven by
+    /// other components add up.
+    pub(crate) fn merge(&mut self, fork: Self) {
+        self.program_mem_check
+            .last_access_counter
+            .extend(fork.program_mem_check.last_access_counter);
+        self.register_mem_check = fork.register_mem_check;
+        self.rw_mem_check
+            .last_access
+            .extend(fork.rw_mem_check.last_access);
+        self.bit_op.merge(fork.bit_op);
+        self.range8.merge(fork.range8);
+        self.range16.merge(fork.range16);
+        self.range32.merge(fork.range32);
+        self.range128.merge(fork.range128);
+        self.range256.merge(fork.range256);
+        self.keccak.merge(fork.keccak);
+        self.sha256.merge(fork.sha256);
+        self.bigint.merge(fork.bigint);
+        self.poseidon2.merge(fork.poseidon2);
+        self.memcpy.merge(fork.memcpy);
+        self.external_steps.extend(fork.external_steps);
+    }
 }
 
 pub(crate) trait RangeCheckSideNoteGetter<const LEN: usize> {
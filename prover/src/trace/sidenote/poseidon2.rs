@@ -0,0 +1,18 @@
+#[derive(Default)]
+pub struct Poseidon2SideNote {
+    /// Words of the state before each `poseidon2` call, as stored in memory.
+    pub(crate) inputs: Vec<[u32; 16]>,
+    /// Addresses of the state.
+    pub(crate) addresses: Vec<u32>,
+    /// Previous timestamps of the state bytes.
+    pub(crate) timestamps: Vec<Vec<u32>>,
+}
+
+impl Poseidon2SideNote {
+    /// Appends the calls recorded by a fork filled for the following rows.
+    pub(crate) fn merge(&mut self, other: Self) {
+        self.inputs.extend(other.inputs);
+        self.addresses.extend(other.addresses);
+        self.timestamps.extend(other.timestamps);
+    }
+}
@@ -0,0 +1,18 @@
+#[derive(Default)]
+pub struct Sha256SideNote {
+    /// State and message words of each `sha256_compress` call.
+    pub(crate) inputs: Vec<([u32; 8], [u32; 16])>,
+    /// Addresses of the state and of the message block.
+    pub(crate) addresses: Vec<(u32, u32)>,
+    /// Previous timestamps of the message bytes followed by the ones of the state bytes.
+    pub(crate) timestamps: Vec<Vec<u32>>,
+}
+
+impl Sha256SideNote {
+    /// Appends the calls recorded by a fork filled for the following rows.
+    pub(crate) fn merge(&mut self, other: Self) {
+        self.inputs.extend(other.inputs);
+        self.addresses.extend(other.addresses);
+        self.timestamps.extend(other.timestamps);
+    }
+}
@@ -1,3 +1,5 @@
+use std::ops::Range;
+
 use itertools::Itertools;
 use nexus_vm::WORD_SIZE;
 use num_traits::Zero;
@@ -18,10 +20,15 @@ use crate::column::Column;
 /// mutable access to columns.
 ///
 /// Values are stored in original (coset) order.
+///
+/// A builder can also hold a window of consecutive rows of the trace, which are accessed by their index in the
+/// whole trace, see [`TracesBuilder::new_window`].
 #[derive(Debug, Clone)]
 pub struct TracesBuilder {
     pub cols: Vec<Vec<BaseField>>,
     pub log_size: u32,
+    /// Index of the first row held by the builder.
+    pub(crate) row_offset: usize,
 }
 
 impl TracesBuilder {
@@ -31,11 +38,33 @@ impl TracesBuilder {
         Self {
             cols: vec![vec![BaseField::zero(); 1 << log_size]; Column::COLUMNS_NUM],
             log_size,
+            row_offset: 0,
+        }
+    }
+
+    /// Returns zeroed columns holding `rows` of the trace with `2.pow(log_size)` rows.
+    pub(crate) fn new_window(log_size: u32, rows: Range<usize>) -> Self {
+        assert!(log_size >= LOG_N_LANES);
+        assert!(rows.end <= 1 << log_size, "rows are out of the trace");
+        Self {
+            cols: vec![vec![BaseField::zero(); rows.len()]; Column::COLUMNS_NUM],
+            log_size,
+            row_offset: rows.start,
+        }
+    }
+
+    /// Copies rows held by `window` into the trace.
+    pub(crate) fn copy_window(&mut self, window: &Self) {
+        assert_eq!(self.log_size, window.log_size, "log size mismatch");
+        let start = window.row_offset - self.row_offset;
+        for (col, window_col) in self.cols.iter_mut().zip(&window.cols) {
+            col[start..start + window_col.len()].copy_from_slice(window_col);
         }
     }
 
     /// Returns inner representation of columns.
     pub fn into_inner(self) -> Vec<Vec<BaseField>> {
+        assert_eq!(self.row_offset, 0, "window doesn't hold the whole trace");
         self.cols
     }
 
@@ -56,6 +85,7 @@ impl TracesBuilder {
 
         let offset = col.offset();
         let mut iter = self.cols[offset..].iter();
+        let row = row - self.row_offset;
         std::array::from_fn(|_idx| iter.next().expect("invalid offset; must be unreachable")[row])
     }
 
@@ -66,6 +96,7 @@ impl TracesBuilder {
 
         let offset = col.offset();
         let mut iter = self.cols[offset..].iter_mut();
+        let row = row - self.row_offset;
         std::array::from_fn(|_idx| {
             &mut iter.next().expect("invalid offset; must be unreachable")[row]
         })
@@ -95,6 +126,7 @@ impl TracesBuilder {
     pub fn fill_columns_base_field(&mut self, row: usize, value: &[BaseField], col: Column) {
         let n = value.len();
         assert_eq!(col.size(), n, "column size mismatch");
+        let row = row - self.row_offset;
         for (i, b) in value.iter().enumerate() {
             self.cols[col.offset() + i][row] = *b;
         }
@@ -129,6 +161,7 @@ impl TracesBuilder {
 
     /// Finalize trace and convert raw columns to [`BaseColumn`].
     pub fn finalize(self) -> FinalizedTraces {
+        assert_eq!(self.row_offset, 0, "window doesn't hold the whole trace");
         let cols = finalize_columns(self.cols);
 
         FinalizedTraces {
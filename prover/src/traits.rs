@@ -14,6 +14,7 @@ use stwo_prover::{
 
 use crate::{
     components::AllLookupElements,
+    debug::DebugEvaluator,
     extensions::ExtensionsConfig,
     trace::{
         eval::TraceEval, preprocessed::PreprocessedTraces, program_trace::ProgramTraces,
@@ -47,6 +48,20 @@ pub trait MachineChip {
         config: &ExtensionsConfig,
     );
 
+    /// Called on each row during constraint debugging, see [`crate::debug`].
+    ///
+    /// Tuples forward the call to each of their chips, so that violations are attributed to the chip adding them.
+    #[doc(hidden)]
+    fn debug_constraints<'a>(
+        eval: &mut DebugEvaluator<'a>,
+        trace_eval: &TraceEval<DebugEvaluator<'a>>,
+        lookup_elements: &AllLookupElements,
+        config: &ExtensionsConfig,
+    ) {
+        eval.enter_chip(std::any::type_name::<Self>());
+        Self::add_constraints(eval, trace_eval, lookup_elements, config);
+    }
+
     /// Called just once for generating the interaction trace.
     ///
     /// The signature of this method is intentionally similar to `gen_interaction_trace()` in stwo examples.
@@ -79,7 +94,7 @@ pub trait MachineChip {
     }
 }
 
-#[impl_for_tuples(1, 27)]
+#[impl_for_tuples(1, 32)]
 impl MachineChip for Tuple {
     fn fill_main_trace(
         traces: &mut TracesBuilder,
@@ -100,6 +115,15 @@ impl MachineChip for Tuple {
         for_tuples!( #( Tuple::add_constraints(eval, trace_eval, lookup_elements, config); )* );
     }
 
+    fn debug_constraints<'a>(
+        eval: &mut DebugEvaluator<'a>,
+        trace_eval: &TraceEval<DebugEvaluator<'a>>,
+        lookup_elements: &AllLookupElements,
+        config: &ExtensionsConfig,
+    ) {
+        for_tuples!( #( Tuple::debug_constraints(eval, trace_eval, lookup_elements, config); )* );
+    }
+
     fn fill_interaction_trace(
         logup_trace_gen: &mut LogupTraceGenerator,
         original_traces: &FinalizedTraces,
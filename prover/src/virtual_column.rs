@@ -9,8 +9,10 @@ use stwo_prover::{
 use crate::{
     column::Column::{
         self, ImmC, IsAdd, IsAnd, IsAuipc, IsBeq, IsBge, IsBgeu, IsBlt, IsBltu, IsBne,
-        IsCustomKeccak, IsEbreak, IsEcall, IsJal, IsJalr, IsLb, IsLbu, IsLh, IsLhu, IsLui, IsLw,
-        IsOr, IsSb, IsSh, IsSll, IsSlt, IsSltu, IsSra, IsSrl, IsSub, IsSw, IsXor,
+        IsCompressed, IsCustomBigInt, IsCustomExternal, IsCustomKeccak, IsCustomMemcpy,
+        IsCustomPoseidon2, IsCustomSha256, IsDiv, IsDivu, IsEbreak, IsEcall, IsJal, IsJalr, IsLb,
+        IsLbu, IsLh, IsLhu, IsLui, IsLw, IsMul, IsMulh, IsMulhsu, IsMulhu, IsOr, IsRem, IsRemu,
+        IsSb, IsSh, IsSll, IsSlt, IsSltu, IsSra, IsSrl, IsSub, IsSw, IsXor,
     },
     trace::{eval::trace_eval, eval::TraceEval, FinalizedTraces, TracesBuilder},
 };
@@ -65,8 +67,9 @@ impl<S: VirtualColumnForSum> VirtualColumn<1> for S {
 pub(crate) struct IsTypeR;
 
 impl IsTypeR {
-    const TYPE_R_OPS: [Column; 10] = [
-        IsAdd, IsSub, IsSlt, IsSltu, IsXor, IsOr, IsAnd, IsSll, IsSrl, IsSra,
+    const TYPE_R_OPS: [Column; 18] = [
+        IsAdd, IsSub, IsSlt, IsSltu, IsXor, IsOr, IsAnd, IsSll, IsSrl, IsSra, IsMul, IsMulh,
+        IsMulhsu, IsMulhu, IsDiv, IsDivu, IsRem, IsRemu,
     ];
 }
 
@@ -106,6 +109,55 @@ impl VirtualColumn<1> for IsTypeR {
     }
 }
 
+/// One on rows for custom instructions proven by external extensions, which are encoded as type R. Zero otherwise.
+pub(crate) struct IsCustomTypeR;
+
+impl VirtualColumnForSum for IsCustomTypeR {
+    fn columns() -> &'static [Column] {
+        &[IsCustomExternal]
+    }
+}
+
+/// One on rows for built-in custom instructions that read both of their registers, which are encoded as type S.
+/// Zero otherwise.
+pub(crate) struct IsCustomTypeS;
+
+impl VirtualColumnForSum for IsCustomTypeS {
+    fn columns() -> &'static [Column] {
+        &[
+            IsCustomSha256,
+            IsCustomBigInt,
+            IsCustomPoseidon2,
+            IsCustomMemcpy,
+        ]
+    }
+}
+
+/// A virtual column that regulates the second register access, which reads rs2 of type R instructions.
+pub(crate) struct Reg2Accessed;
+
+// reg2_accessed = is_type_r + is_custom_type_r
+impl VirtualColumn<1> for Reg2Accessed {
+    fn read_from_traces_builder(traces: &TracesBuilder, row_idx: usize) -> [BaseField; 1] {
+        let [is_type_r] = IsTypeR::read_from_traces_builder(traces, row_idx);
+        let [is_custom_type_r] = IsCustomTypeR::read_from_traces_builder(traces, row_idx);
+        [is_type_r + is_custom_type_r]
+    }
+    fn read_from_finalized_traces(
+        traces: &FinalizedTraces,
+        vec_idx: usize,
+    ) -> [PackedBaseField; 1] {
+        let is_type_r = IsTypeR::read_from_finalized_traces(traces, vec_idx)[0];
+        let is_custom_type_r = IsCustomTypeR::read_from_finalized_traces(traces, vec_idx)[0];
+        [is_type_r + is_custom_type_r]
+    }
+    fn eval<E: EvalAtRow>(trace_eval: &TraceEval<E>) -> [E::F; 1] {
+        let [is_type_r] = IsTypeR::eval(trace_eval);
+        let [is_custom_type_r] = IsCustomTypeR::eval(trace_eval);
+        [is_type_r + is_custom_type_r]
+    }
+}
+
 pub(crate) struct IsTypeU;
 
 impl VirtualColumnForSum for IsTypeU {
@@ -119,7 +171,8 @@ pub(crate) struct IsAlu;
 impl VirtualColumnForSum for IsAlu {
     fn columns() -> &'static [Column] {
         &[
-            IsAdd, IsSub, IsSlt, IsSltu, IsXor, IsOr, IsAnd, IsSll, IsSrl, IsSra,
+            IsAdd, IsSub, IsSlt, IsSltu, IsXor, IsOr, IsAnd, IsSll, IsSrl, IsSra, IsMul, IsMulh,
+            IsMulhsu, IsMulhu, IsDiv, IsDivu, IsRem, IsRemu,
         ]
     }
 }
@@ -306,6 +359,11 @@ impl VirtualColumn<1> for IsPcIncremented {
         let [is_type_u] = IsTypeU::read_from_traces_builder(traces, row_idx);
         let [is_type_sys] = IsTypeSys::read_from_traces_builder(traces, row_idx);
         let [is_custom_keccak] = traces.column(row_idx, IsCustomKeccak);
+        let [is_custom_sha256] = traces.column(row_idx, IsCustomSha256);
+        let [is_custom_bigint] = traces.column(row_idx, IsCustomBigInt);
+        let [is_custom_poseidon2] = traces.column(row_idx, IsCustomPoseidon2);
+        let [is_custom_memcpy] = traces.column(row_idx, IsCustomMemcpy);
+        let [is_custom_external] = traces.column(row_idx, IsCustomExternal);
 
         let [is_sys_halt] = traces.column(row_idx, Column::IsSysHalt);
         let ret = is_alu
@@ -313,7 +371,12 @@ impl VirtualColumn<1> for IsPcIncremented {
             + is_type_s
             + is_type_sys * (BaseField::one() - is_sys_halt)
             + is_type_u
-            + is_custom_keccak;
+            + is_custom_keccak
+            + is_custom_sha256
+            + is_custom_bigint
+            + is_custom_poseidon2
+            + is_custom_memcpy
+            + is_custom_external;
         [ret]
     }
     fn read_from_finalized_traces(
@@ -328,12 +391,24 @@ impl VirtualColumn<1> for IsPcIncremented {
 
         let is_sys_halt = traces.get_base_column::<1>(Column::IsSysHalt)[0].data[vec_idx];
         let is_custom_keccak = traces.get_base_column::<1>(Column::IsCustomKeccak)[0].data[vec_idx];
+        let is_custom_sha256 = traces.get_base_column::<1>(Column::IsCustomSha256)[0].data[vec_idx];
+        let is_custom_bigint = traces.get_base_column::<1>(Column::IsCustomBigInt)[0].data[vec_idx];
+        let is_custom_poseidon2 =
+            traces.get_base_column::<1>(Column::IsCustomPoseidon2)[0].data[vec_idx];
+        let is_custom_memcpy = traces.get_base_column::<1>(Column::IsCustomMemcpy)[0].data[vec_idx];
+        let is_custom_external =
+            traces.get_base_column::<1>(Column::IsCustomExternal)[0].data[vec_idx];
         let ret = is_alu
             + is_load
             + is_type_s
             + is_type_sys * (PackedBaseField::one() - is_sys_halt)
             + is_type_u
-            + is_custom_keccak;
+            + is_custom_keccak
+            + is_custom_sha256
+            + is_custom_bigint
+            + is_custom_poseidon2
+            + is_custom_memcpy
+            + is_custom_external;
         [ret]
     }
     fn eval<E: EvalAtRow>(trace_eval: &TraceEval<E>) -> [E::F; 1] {
@@ -345,22 +420,56 @@ impl VirtualColumn<1> for IsPcIncremented {
 
         let [is_sys_halt] = trace_eval!(trace_eval, Column::IsSysHalt);
         let [is_custom_keccak] = trace_eval!(trace_eval, Column::IsCustomKeccak);
+        let [is_custom_sha256] = trace_eval!(trace_eval, Column::IsCustomSha256);
+        let [is_custom_bigint] = trace_eval!(trace_eval, Column::IsCustomBigInt);
+        let [is_custom_poseidon2] = trace_eval!(trace_eval, Column::IsCustomPoseidon2);
+        let [is_custom_memcpy] = trace_eval!(trace_eval, Column::IsCustomMemcpy);
+        let [is_custom_external] = trace_eval!(trace_eval, Column::IsCustomExternal);
         let ret = is_alu
             + is_load
             + is_type_s
             + is_type_sys * (E::F::one() - is_sys_halt)
             + is_type_u
-            + is_custom_keccak;
+            + is_custom_keccak
+            + is_custom_sha256
+            + is_custom_bigint
+            + is_custom_poseidon2
+            + is_custom_memcpy
+            + is_custom_external;
         [ret]
     }
 }
 
+/// The size of the instruction in bytes, by which the program counter is incremented: two for compressed
+/// instructions and four otherwise.
+pub(crate) struct InstrSize;
+
+impl VirtualColumn<1> for InstrSize {
+    fn read_from_traces_builder(traces: &TracesBuilder, row_idx: usize) -> [BaseField; 1] {
+        let [is_compressed] = traces.column(row_idx, IsCompressed);
+        [BaseField::from(4) - is_compressed - is_compressed]
+    }
+    fn read_from_finalized_traces(
+        traces: &FinalizedTraces,
+        vec_idx: usize,
+    ) -> [PackedBaseField; 1] {
+        let is_compressed = traces.get_base_column::<1>(IsCompressed)[0].data[vec_idx];
+        [PackedBaseField::from(BaseField::from(4)) - is_compressed - is_compressed]
+    }
+    fn eval<E: EvalAtRow>(trace_eval: &TraceEval<E>) -> [E::F; 1] {
+        let [is_compressed] = trace_eval!(trace_eval, IsCompressed);
+        [E::F::from(BaseField::from(4)) - is_compressed.clone() - is_compressed]
+    }
+}
+
 /// op-b-flag indicates whether or not the instruction's second operand (op-b) is a register index.
 ///
 /// The definition of op-b-flag follows:
 /// (is-sb + is-sh + is-sw + is-lb + is-lh + is-lw + is-lbu + is-lhu + is-jalr + is-add + is-sub + is-slt + is-sltu
-/// + is-xor + is-or + is-and + is-sll + is-srl + is-sra+ is-beq + is-bne + is-blt + is-bge + is-bltu
-/// + is-bgeu + is-ecall + is-ebreak − op-b-flag) = 0
+/// + is-xor + is-or + is-and + is-sll + is-srl + is-sra + is-mul + is-mulh + is-mulhsu + is-mulhu + is-div
+/// + is-divu + is-rem + is-remu + is-beq + is-bne + is-blt + is-bge + is-bltu + is-bgeu + is-ecall + is-ebreak
+/// + is-custom-external + is-custom-sha256 + is-custom-bigint + is-custom-poseidon2 + is-custom-memcpy
+/// − op-b-flag) = 0
 ///
 /// op-b-flag controls whether Reg1Address is used.
 pub(crate) struct OpBFlag;
@@ -368,9 +477,46 @@ pub(crate) struct OpBFlag;
 impl VirtualColumnForSum for OpBFlag {
     fn columns() -> &'static [Column] {
         &[
-            IsSb, IsSh, IsSw, IsLb, IsLh, IsLw, IsLbu, IsLhu, IsJalr, IsAdd, IsSub, IsSlt, IsSltu,
-            IsXor, IsOr, IsAnd, IsSll, IsSrl, IsSra, IsBeq, IsBne, IsBlt, IsBge, IsBltu, IsBgeu,
-            IsEcall, IsEbreak,
+            IsSb,
+            IsSh,
+            IsSw,
+            IsLb,
+            IsLh,
+            IsLw,
+            IsLbu,
+            IsLhu,
+            IsJalr,
+            IsAdd,
+            IsSub,
+            IsSlt,
+            IsSltu,
+            IsXor,
+            IsOr,
+            IsAnd,
+            IsSll,
+            IsSrl,
+            IsSra,
+            IsMul,
+            IsMulh,
+            IsMulhsu,
+            IsMulhu,
+            IsDiv,
+            IsDivu,
+            IsRem,
+            IsRemu,
+            IsBeq,
+            IsBne,
+            IsBlt,
+            IsBge,
+            IsBltu,
+            IsBgeu,
+            IsEcall,
+            IsEbreak,
+            IsCustomExternal,
+            IsCustomSha256,
+            IsCustomBigInt,
+            IsCustomPoseidon2,
+            IsCustomMemcpy,
         ]
     }
 }
@@ -379,13 +525,14 @@ impl VirtualColumnForSum for OpBFlag {
 ///
 /// The third register access is done using ValueAEffective and Reg3Address.
 /// The third access is mainly used for writing into rd (destination register).
-/// The third register access is also used for reading from rs1 (first register argument) of type S and type B instructions.
+/// The third register access is also used for reading from rs1 (first register argument) of type S and type B instructions,
+/// including built-in custom instructions encoded as type S.
 pub(crate) struct Reg3Accessed;
 
 // reg3_accessed =
-// (is_type_s + is_type_b) +   // When reading from rs1
-// (is_type_r + is_type_i + is_type_u + is_type_j)  + // For instructions with rd
-// (is_type_sys)·(is_sys_priv_input + is_sys_heap_reset + is_sys_stack_reset) // For some syscalls
+// (is_type_s + is_type_b + is_custom_type_s) +   // When reading from rs1
+// (is_type_r + is_type_i + is_type_u + is_type_j + is_custom_type_r)  + // For instructions with rd
+// (is_type_sys)·(is_sys_priv_input + is_sys_aux_input + is_sys_heap_reset + is_sys_stack_reset) // For some syscalls
 impl VirtualColumn<1> for Reg3Accessed {
     fn read_from_traces_builder(traces: &TracesBuilder, row_idx: usize) -> [BaseField; 1] {
         let [is_type_s] = IsTypeS::read_from_traces_builder(traces, row_idx);
@@ -394,8 +541,11 @@ impl VirtualColumn<1> for Reg3Accessed {
         let [is_type_i] = IsTypeI::read_from_traces_builder(traces, row_idx);
         let [is_type_u] = IsTypeU::read_from_traces_builder(traces, row_idx);
         let [is_type_j] = IsTypeJ::read_from_traces_builder(traces, row_idx);
+        let [is_custom_type_r] = IsCustomTypeR::read_from_traces_builder(traces, row_idx);
+        let [is_custom_type_s] = IsCustomTypeS::read_from_traces_builder(traces, row_idx);
         let [is_type_sys] = IsTypeSys::read_from_traces_builder(traces, row_idx);
         let [is_sys_priv_input] = traces.column(row_idx, Column::IsSysPrivInput);
+        let [is_sys_aux_input] = traces.column(row_idx, Column::IsSysAuxInput);
         let [is_sys_heap_reset] = traces.column(row_idx, Column::IsSysHeapReset);
         let [is_sys_stack_reset] = traces.column(row_idx, Column::IsSysStackReset);
 
@@ -405,7 +555,10 @@ impl VirtualColumn<1> for Reg3Accessed {
             + is_type_i
             + is_type_u
             + is_type_j
-            + is_type_sys * (is_sys_priv_input + is_sys_heap_reset + is_sys_stack_reset);
+            + is_custom_type_r
+            + is_custom_type_s
+            + is_type_sys
+                * (is_sys_priv_input + is_sys_aux_input + is_sys_heap_reset + is_sys_stack_reset);
         [ret]
     }
     fn read_from_finalized_traces(
@@ -418,9 +571,12 @@ impl VirtualColumn<1> for Reg3Accessed {
         let is_type_i = IsTypeI::read_from_finalized_traces(traces, vec_idx)[0];
         let is_type_u = IsTypeU::read_from_finalized_traces(traces, vec_idx)[0];
         let is_type_j = IsTypeJ::read_from_finalized_traces(traces, vec_idx)[0];
+        let is_custom_type_r = IsCustomTypeR::read_from_finalized_traces(traces, vec_idx)[0];
+        let is_custom_type_s = IsCustomTypeS::read_from_finalized_traces(traces, vec_idx)[0];
         let is_type_sys = IsTypeSys::read_from_finalized_traces(traces, vec_idx)[0];
         let is_sys_priv_input =
             traces.get_base_column::<1>(Column::IsSysPrivInput)[0].data[vec_idx];
+        let is_sys_aux_input = traces.get_base_column::<1>(Column::IsSysAuxInput)[0].data[vec_idx];
         let is_sys_heap_reset =
             traces.get_base_column::<1>(Column::IsSysHeapReset)[0].data[vec_idx];
         let is_sys_stack_reset =
@@ -431,7 +587,10 @@ impl VirtualColumn<1> for Reg3Accessed {
             + is_type_i
             + is_type_u
             + is_type_j
-            + is_type_sys * (is_sys_priv_input + is_sys_heap_reset + is_sys_stack_reset);
+            + is_custom_type_r
+            + is_custom_type_s
+            + is_type_sys
+                * (is_sys_priv_input + is_sys_aux_input + is_sys_heap_reset + is_sys_stack_reset);
         [ret]
     }
     fn eval<E: EvalAtRow>(trace_eval: &TraceEval<E>) -> [E::F; 1] {
@@ -441,8 +600,11 @@ impl VirtualColumn<1> for Reg3Accessed {
         let [is_type_i] = IsTypeI::eval(trace_eval);
         let [is_type_u] = IsTypeU::eval(trace_eval);
         let [is_type_j] = IsTypeJ::eval(trace_eval);
+        let [is_custom_type_r] = IsCustomTypeR::eval(trace_eval);
+        let [is_custom_type_s] = IsCustomTypeS::eval(trace_eval);
         let [is_type_sys] = IsTypeSys::eval(trace_eval);
         let [is_sys_priv_input] = trace_eval!(trace_eval, Column::IsSysPrivInput);
+        let [is_sys_aux_input] = trace_eval!(trace_eval, Column::IsSysAuxInput);
         let [is_sys_heap_reset] = trace_eval!(trace_eval, Column::IsSysHeapReset);
         let [is_sys_stack_reset] = trace_eval!(trace_eval, Column::IsSysStackReset);
         let ret = is_type_s
@@ -451,7 +613,10 @@ impl VirtualColumn<1> for Reg3Accessed {
             + is_type_i
             + is_type_u
             + is_type_j
-            + is_type_sys * (is_sys_priv_input + is_sys_heap_reset + is_sys_stack_reset);
+            + is_custom_type_r
+            + is_custom_type_s
+            + is_type_sys
+                * (is_sys_priv_input + is_sys_aux_input + is_sys_heap_reset + is_sys_stack_reset);
         [ret]
     }
 }
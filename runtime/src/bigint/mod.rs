@@ -0,0 +1,59 @@
+//! 256-bit modular arithmetic accelerated by the `mul_add_mod` custom instruction.
+//!
+//! Integers are represented by eight little-endian 32-bit words. Inputs don't have to be reduced.
+//!
+//! # Example
+//!
+//! ```
+//! use nexus_rt::bigint::{add_mod, mul_mod, U256};
+//!
+//! let m: U256 = [7, 0, 0, 0, 0, 0, 0, 0];
+//! let a: U256 = [5, 0, 0, 0, 0, 0, 0, 0];
+//! let b: U256 = [4, 0, 0, 0, 0, 0, 0, 0];
+//! assert_eq!(mul_mod(&a, &b, &m), [6, 0, 0, 0, 0, 0, 0, 0]);
+//! assert_eq!(add_mod(&a, &b, &m), [2, 0, 0, 0, 0, 0, 0, 0]);
+//! ```
+
+#[cfg(all(target_arch = "riscv32", not(target_feature = "c")))]
+mod riscv32;
+#[cfg(all(target_arch = "riscv32", not(target_feature = "c")))]
+pub use riscv32::mul_add_mod;
+
+#[cfg(not(all(target_arch = "riscv32", not(target_feature = "c"))))]
+mod soft;
+#[cfg(not(all(target_arch = "riscv32", not(target_feature = "c"))))]
+pub use soft::mul_add_mod;
+
+/// 256-bit integer as little-endian 32-bit words.
+pub type U256 = [u32; 8];
+
+const ZERO: U256 = [0; 8];
+const ONE: U256 = [1, 0, 0, 0, 0, 0, 0, 0];
+
+/// Computes `(a * b) mod m`, a zero modulus stands for 2^256.
+pub fn mul_mod(a: &U256, b: &U256, m: &U256) -> U256 {
+    mul_add_mod(a, b, &ZERO, m)
+}
+
+/// Computes `(a + b) mod m`, a zero modulus stands for 2^256.
+pub fn add_mod(a: &U256, b: &U256, m: &U256) -> U256 {
+    mul_add_mod(a, &ONE, b, m)
+}
+
+/// Converts 32 big-endian bytes into an integer.
+pub fn from_be_bytes(bytes: &[u8; 32]) -> U256 {
+    let mut words = ZERO;
+    for (word, chunk) in words.iter_mut().zip(bytes.rchunks_exact(4)) {
+        *word = u32::from_be_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+    }
+    words
+}
+
+/// Converts an integer into 32 big-endian bytes.
+pub fn to_be_bytes(words: &U256) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    for (chunk, word) in bytes.rchunks_exact_mut(4).zip(words) {
+        chunk.copy_from_slice(&word.to_be_bytes());
+    }
+    bytes
+}
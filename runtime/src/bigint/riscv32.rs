@@ -0,0 +1,26 @@
+use super::U256;
+
+/// Writes `(a * b + c) mod m` to `result` with operands stored one after another at `operands`. The macro can only
+/// be invoked through the public interface.
+macro_rules! mul_add_mod_call {
+    ($result:expr, $operands:expr) => {
+        unsafe {
+            core::arch::asm!(
+                ".insn s 0b1011010, 0b010, {1}, 0({0})",
+                in(reg) $result,
+                in(reg) $operands,
+            )
+        }
+    };
+}
+
+/// Computes `(a * b + c) mod m`, a zero modulus stands for 2^256.
+pub fn mul_add_mod(a: &U256, b: &U256, c: &U256, m: &U256) -> U256 {
+    let operands = [*a, *b, *c, *m];
+    let mut result = [0u32; 8];
+
+    let result_ptr = &mut result as *mut _;
+    let operands_ptr = &operands as *const _;
+    mul_add_mod_call!(result_ptr, operands_ptr);
+    result
+}
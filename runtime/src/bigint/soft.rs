@@ -0,0 +1,48 @@
+use super::U256;
+
+/// Computes `(a * b + c) mod m`, a zero modulus stands for 2^256.
+pub fn mul_add_mod(a: &U256, b: &U256, c: &U256, m: &U256) -> U256 {
+    // a * b + c < 2^512
+    let mut t = [0u32; 16];
+    t[..8].copy_from_slice(c);
+    for (i, &a) in a.iter().enumerate() {
+        let mut carry = 0u64;
+        for (j, &b) in b.iter().enumerate() {
+            let v = u64::from(a) * u64::from(b) + u64::from(t[i + j]) + carry;
+            t[i + j] = v as u32;
+            carry = v >> 32;
+        }
+        for word in &mut t[i + 8..] {
+            let v = u64::from(*word) + carry;
+            *word = v as u32;
+            carry = v >> 32;
+        }
+    }
+
+    let mut r = [0u32; 9];
+    if m.iter().all(|&word| word == 0) {
+        r[..8].copy_from_slice(&t[..8]);
+    } else {
+        for bit in (0..512).rev() {
+            let mut carry = (t[bit / 32] >> (bit % 32)) & 1;
+            for word in &mut r {
+                let shifted_out = *word >> 31;
+                *word = (*word << 1) | carry;
+                carry = shifted_out;
+            }
+
+            if r[8] != 0 || r[..8].iter().rev().cmp(m.iter().rev()).is_ge() {
+                let mut borrow = 0i64;
+                for (i, word) in r.iter_mut().enumerate() {
+                    let v = i64::from(*word) - i64::from(m.get(i).copied().unwrap_or(0)) + borrow;
+                    *word = v as u32;
+                    borrow = v >> 32;
+                }
+            }
+        }
+    }
+
+    let mut result = [0u32; 8];
+    result.copy_from_slice(&r[..8]);
+    result
+}
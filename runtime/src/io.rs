@@ -6,7 +6,7 @@ mod riscv32 {
     extern crate alloc;
     use crate::{
         ecall, read_input, write_output, NexusRTError, SYS_CYCLE_COUNT, SYS_EXIT, SYS_LOG,
-        SYS_READ_PRIVATE_INPUT, WORD_SIZE,
+        SYS_READ_AUX_INPUT, SYS_READ_PRIVATE_INPUT, WORD_SIZE,
     };
     use serde::{de::DeserializeOwned, Serialize};
 
@@ -54,6 +54,20 @@ mod riscv32 {
         } // u32::MAX is used a sentinel value that there is nothing (left) on the input tape
     }
 
+    /// Read bytes off the auxiliary input tape into `buf`
+    ///
+    /// returns the number of bytes read, which is less than the length of `buf` only once the tape is exhausted
+    pub fn read_aux_input(buf: &mut [u8]) -> usize {
+        for (i, byte) in buf.iter_mut().enumerate() {
+            let out = ecall!(SYS_READ_AUX_INPUT);
+            if out == u32::MAX {
+                return i;
+            }
+            *byte = out.to_le_bytes()[0];
+        }
+        buf.len()
+    }
+
     /// Read an object from the public input segment.
     pub fn read_public_input<T: DeserializeOwned>() -> Result<T, NexusRTError> {
         // The first word stores the length of the input (in bytes).
@@ -167,6 +181,10 @@ mod native {
         unimplemented!()
     }
 
+    pub fn read_aux_input<UNUSABLE: RequiresRV32Target>(_buf: &mut [u8]) -> usize {
+        unimplemented!()
+    }
+
     pub fn write_public_output<UNUSABLE: RequiresRV32Target, T: Serialize + ?Sized>(_val: &T) {
         unimplemented!()
     }
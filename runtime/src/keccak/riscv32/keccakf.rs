@@ -1,6 +1,7 @@
 use super::{Buffer, Permutation};
 
 /// Updates the keccak state stored at `addr`. The macro can only be invoked through the public interface.
+#[cfg(not(target_feature = "c"))]
 macro_rules! keccakf_call {
     ($addr:expr) => {
         unsafe {
@@ -12,11 +13,79 @@ macro_rules! keccakf_call {
     };
 }
 
+#[cfg(not(target_feature = "c"))]
 pub fn keccakf(state: &mut [u64; 25]) {
     let state_ptr = state as *mut _;
     keccakf_call!(state_ptr);
 }
 
+/// Software `keccak-f[1600]`, used in compressed code where the `keccakf` custom instruction can't be encoded.
+#[cfg(target_feature = "c")]
+pub fn keccakf(state: &mut [u64; 25]) {
+    const RC: [u64; 24] = [
+        0x0000000000000001,
+        0x0000000000008082,
+        0x800000000000808a,
+        0x8000000080008000,
+        0x000000000000808b,
+        0x0000000080000001,
+        0x8000000080008081,
+        0x8000000000008009,
+        0x000000000000008a,
+        0x0000000000000088,
+        0x0000000080008009,
+        0x000000008000000a,
+        0x000000008000808b,
+        0x800000000000008b,
+        0x8000000000008089,
+        0x8000000000008003,
+        0x8000000000008002,
+        0x8000000000000080,
+        0x000000000000800a,
+        0x800000008000000a,
+        0x8000000080008081,
+        0x8000000000008080,
+        0x0000000080000001,
+        0x8000000080008008,
+    ];
+    const RHO: [u32; 24] = [
+        1, 3, 6, 10, 15, 21, 28, 36, 45, 55, 2, 14, 27, 41, 56, 8, 25, 43, 62, 18, 39, 61, 20, 44,
+    ];
+    const PI: [usize; 24] = [
+        10, 7, 11, 17, 18, 3, 5, 16, 8, 21, 24, 4, 15, 23, 19, 13, 12, 2, 20, 14, 22, 9, 6, 1,
+    ];
+
+    for rc in RC {
+        // Theta
+        let c: [u64; 5] = core::array::from_fn(|x| {
+            state[x] ^ state[x + 5] ^ state[x + 10] ^ state[x + 15] ^ state[x + 20]
+        });
+        for (i, lane) in state.iter_mut().enumerate() {
+            let x = i % 5;
+            *lane ^= c[(x + 4) % 5] ^ c[(x + 1) % 5].rotate_left(1);
+        }
+
+        // Rho and pi
+        let mut last = state[1];
+        for (&pi, &rho) in PI.iter().zip(&RHO) {
+            let lane = state[pi];
+            state[pi] = last.rotate_left(rho);
+            last = lane;
+        }
+
+        // Chi
+        for row in state.chunks_exact_mut(5) {
+            let lanes: [u64; 5] = core::array::from_fn(|x| row[x]);
+            for (x, lane) in row.iter_mut().enumerate() {
+                *lane = lanes[x] ^ (!lanes[(x + 1) % 5] & lanes[(x + 2) % 5]);
+            }
+        }
+
+        // Iota
+        state[0] ^= rc;
+    }
+}
+
 pub struct KeccakF;
 
 impl Permutation for KeccakF {
@@ -20,7 +20,11 @@ mod error;
 pub use error::*;
 pub use postcard;
 
+pub mod bigint;
 pub mod keccak;
+pub mod mem;
+pub mod poseidon2;
+pub mod sha256;
 
 // Ecall codes. Allow dead code here because these are only used in the RISC-V runtime, not when
 // compiling for the host.
@@ -38,6 +42,8 @@ pub(crate) const SYS_OVERWRITE_SP: u32 = 0x402;
 #[cfg(target_arch = "riscv32")]
 pub(crate) const SYS_ALLOC_ALIGNED: u32 = 0x403;
 #[cfg(target_arch = "riscv32")]
+pub(crate) const SYS_READ_AUX_INPUT: u32 = 0x404;
+#[cfg(target_arch = "riscv32")]
 pub(crate) const SYS_PERFORM_HEAP_ALLOCATION: u32 = 0x405;
 // Error codes.
 #[cfg(target_arch = "riscv32")]
@@ -0,0 +1,68 @@
+//! Bulk memory operations accelerated by the `memcpy` and `memset` custom instructions.
+//!
+//! Each instruction copies or fills up to 1024 bytes in a single step, longer regions are split into several calls.
+//!
+//! With the `memcpy-precompile` feature, the runtime also exports the `memcpy` and `memset` symbols, so that
+//! `core::ptr::copy_nonoverlapping`, `core::ptr::write_bytes` and everything built on top of them go through these
+//! instructions. Programs compiled this way must be proven with the corresponding prover extension. Copies of small
+//! constant sizes are usually lowered to plain loads and stores by the compiler and aren't affected.
+//!
+//! # Example
+//!
+//! ```
+//! use nexus_rt::mem::{copy, fill};
+//!
+//! let mut buf = [0u8; 8];
+//! copy(&mut buf[..4], &[1, 2, 3, 4]);
+//! fill(&mut buf[4..], 5);
+//! assert_eq!(buf, [1, 2, 3, 4, 5, 5, 5, 5]);
+//! ```
+
+#[cfg(all(target_arch = "riscv32", not(target_feature = "c")))]
+mod riscv32;
+#[cfg(all(target_arch = "riscv32", not(target_feature = "c")))]
+pub use riscv32::{memcpy, memset};
+
+#[cfg(not(all(target_arch = "riscv32", not(target_feature = "c"))))]
+mod soft;
+#[cfg(not(all(target_arch = "riscv32", not(target_feature = "c"))))]
+pub use soft::{memcpy, memset};
+
+/// Copies `src` into `dst`.
+///
+/// # Panics
+///
+/// Panics if the slices have different lengths.
+pub fn copy(dst: &mut [u8], src: &[u8]) {
+    assert_eq!(dst.len(), src.len(), "slices must have the same length");
+    // SAFETY: both regions are valid for `src.len()` bytes and can't overlap.
+    unsafe { memcpy(dst.as_mut_ptr(), src.as_ptr(), src.len()) }
+}
+
+/// Sets every byte of `dst` to `value`.
+pub fn fill(dst: &mut [u8], value: u8) {
+    // SAFETY: the region is valid for `dst.len()` bytes.
+    unsafe { memset(dst.as_mut_ptr(), value, dst.len()) }
+}
+
+/// Symbols the compiler emits calls to, taking precedence over the weak ones from `compiler_builtins`.
+///
+/// Not exported in compressed code, where the software fallbacks would call back into them.
+#[cfg(all(
+    target_arch = "riscv32",
+    not(target_feature = "c"),
+    feature = "memcpy-precompile"
+))]
+mod symbols {
+    #[no_mangle]
+    unsafe extern "C" fn memcpy(dst: *mut u8, src: *const u8, n: usize) -> *mut u8 {
+        super::memcpy(dst, src, n);
+        dst
+    }
+
+    #[no_mangle]
+    unsafe extern "C" fn memset(dst: *mut u8, c: i32, n: usize) -> *mut u8 {
+        super::memset(dst, c as u8, n);
+        dst
+    }
+}
@@ -0,0 +1,81 @@
+/// Number of bytes handled by a single instruction of the longest form.
+const MAX_CHUNK: usize = 1024;
+
+/// Copies `$len` bytes from `$src` to `$dst`, the length is encoded in the immediate. The macro can only be invoked
+/// through the public interface.
+macro_rules! memcpy_call {
+    ($dst:expr, $src:expr, $len:expr) => {
+        unsafe {
+            core::arch::asm!(
+                ".insn s 0b1011010, 0b100, {1}, {2}({0})",
+                in(reg) $dst,
+                in(reg) $src,
+                const $len,
+            )
+        }
+    };
+}
+
+/// Fills `$len` bytes at `$dst` with the low byte of `$value`, the length is encoded in the immediate. The macro can
+/// only be invoked through the public interface.
+macro_rules! memset_call {
+    ($dst:expr, $value:expr, $len:expr) => {
+        unsafe {
+            core::arch::asm!(
+                ".insn s 0b1011010, 0b101, {1}, {2}({0})",
+                in(reg) $dst,
+                in(reg) $value,
+                const $len,
+            )
+        }
+    };
+}
+
+/// Splits `$n` into chunks of constant sizes, one instruction per chunk and at most one per size below
+/// [`MAX_CHUNK`].
+macro_rules! for_each_chunk {
+    ($n:expr, |$offset:ident, $len:ident| $call:expr) => {{
+        let rem = $n % MAX_CHUNK;
+        for $offset in (0..$n - rem).step_by(MAX_CHUNK) {
+            const $len: usize = MAX_CHUNK;
+            $call;
+        }
+        for_each_chunk!(@rem $n, rem, |$offset, $len| $call; 512, 256, 128, 64, 32, 16, 8, 4, 2, 1);
+    }};
+    (@rem $n:expr, $rem:ident, |$offset:ident, $len:ident| $call:expr; $($size:literal),*) => {
+        $(
+            if $rem & $size != 0 {
+                const $len: usize = $size;
+                // larger chunks of the remainder come first
+                let $offset = $n - $rem + ($rem & !(2 * $size - 1));
+                $call;
+            }
+        )*
+    };
+}
+
+/// Copies `n` bytes from `src` to `dst`.
+///
+/// # Safety
+///
+/// `src` must be valid for reads and `dst` must be valid for writes of `n` bytes, the regions must not overlap.
+pub unsafe fn memcpy(dst: *mut u8, src: *const u8, n: usize) {
+    for_each_chunk!(n, |offset, LEN| memcpy_call!(
+        dst.wrapping_add(offset),
+        src.wrapping_add(offset),
+        LEN
+    ));
+}
+
+/// Sets `n` bytes starting at `dst` to `value`.
+///
+/// # Safety
+///
+/// `dst` must be valid for writes of `n` bytes.
+pub unsafe fn memset(dst: *mut u8, value: u8, n: usize) {
+    for_each_chunk!(n, |offset, LEN| memset_call!(
+        dst.wrapping_add(offset),
+        value as u32,
+        LEN
+    ));
+}
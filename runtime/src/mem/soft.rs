@@ -0,0 +1,17 @@
+/// Copies `n` bytes from `src` to `dst`.
+///
+/// # Safety
+///
+/// `src` must be valid for reads and `dst` must be valid for writes of `n` bytes, the regions must not overlap.
+pub unsafe fn memcpy(dst: *mut u8, src: *const u8, n: usize) {
+    core::ptr::copy_nonoverlapping(src, dst, n);
+}
+
+/// Sets `n` bytes starting at `dst` to `value`.
+///
+/// # Safety
+///
+/// `dst` must be valid for writes of `n` bytes.
+pub unsafe fn memset(dst: *mut u8, value: u8, n: usize) {
+    core::ptr::write_bytes(dst, value, n);
+}
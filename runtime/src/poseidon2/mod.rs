@@ -0,0 +1,67 @@
+//! Poseidon2 hash over the Mersenne-31 field, with the permutation accelerated by the `poseidon2` custom instruction.
+//!
+//! Elements are `u32` words below 2^31 - 1, the field the prover works over, so hashing them in a guest is far
+//! cheaper to prove than hashing bytes with Keccak. The state holds sixteen elements, eight of which are absorbed at a
+//! time.
+//!
+//! # Example
+//!
+//! ```
+//! use nexus_rt::poseidon2::{compress, hash};
+//!
+//! let left = hash(&[1, 2, 3]);
+//! let right = hash(&[4, 5, 6]);
+//! let root: [u32; 8] = compress(&left, &right);
+//! ```
+
+#[cfg(all(target_arch = "riscv32", not(target_feature = "c")))]
+mod riscv32;
+#[cfg(all(target_arch = "riscv32", not(target_feature = "c")))]
+pub use riscv32::permute;
+
+#[cfg(not(all(target_arch = "riscv32", not(target_feature = "c"))))]
+mod soft;
+#[cfg(not(all(target_arch = "riscv32", not(target_feature = "c"))))]
+pub use soft::permute;
+
+/// Modulus of the Mersenne-31 field.
+pub const MODULUS: u32 = (1 << 31) - 1;
+
+/// Number of elements in the permuted state.
+pub const WIDTH: usize = 16;
+
+/// Number of elements absorbed per permutation and returned as a digest.
+pub const RATE: usize = WIDTH / 2;
+
+fn add(a: u32, b: u32) -> u32 {
+    ((u64::from(a) + u64::from(b)) % u64::from(MODULUS)) as u32
+}
+
+/// Compresses two digests into one, as used for inner nodes of Merkle trees.
+///
+/// Computes the permutation of `left || right` truncated to eight elements, plus `left`.
+pub fn compress(left: &[u32; RATE], right: &[u32; RATE]) -> [u32; RATE] {
+    let mut state = [0u32; WIDTH];
+    state[..RATE].copy_from_slice(left);
+    state[RATE..].copy_from_slice(right);
+    permute(&mut state);
+
+    core::array::from_fn(|i| add(state[i], left[i] % MODULUS))
+}
+
+/// Hashes a sequence of field elements with a sponge, the length of the input is absorbed into the capacity.
+pub fn hash(input: &[u32]) -> [u32; RATE] {
+    let mut state = [0u32; WIDTH];
+    state[RATE] = (input.len() as u32) % MODULUS;
+
+    let mut chunks = input.chunks(RATE);
+    let first = chunks.next().unwrap_or_default();
+    for chunk in core::iter::once(first).chain(chunks) {
+        for (x, &y) in state.iter_mut().zip(chunk) {
+            *x = add(*x, y % MODULUS);
+        }
+        permute(&mut state);
+    }
+
+    state[..RATE].try_into().expect("invalid slice length")
+}
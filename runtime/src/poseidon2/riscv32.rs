@@ -0,0 +1,19 @@
+use super::WIDTH;
+
+/// Permutes the state stored at `addr`. The macro can only be invoked through the public interface.
+macro_rules! poseidon2_call {
+    ($addr:expr) => {
+        unsafe {
+            core::arch::asm!(
+                ".insn s 0b1011010, 0b011, x0, 0({0})",
+                in(reg) $addr,
+            )
+        }
+    };
+}
+
+/// Applies the Poseidon2 permutation to `state`, elements that aren't canonical are reduced first.
+pub fn permute(state: &mut [u32; WIDTH]) {
+    let state_ptr = state as *mut _;
+    poseidon2_call!(state_ptr);
+}
@@ -0,0 +1,112 @@
+use super::{MODULUS, WIDTH};
+
+const FULL_ROUNDS: usize = 8;
+const PARTIAL_ROUNDS: usize = 14;
+
+const EXTERNAL_ROUND_CONSTANTS: [[u32; WIDTH]; FULL_ROUNDS] = round_constants().0;
+const INTERNAL_ROUND_CONSTANTS: [u32; PARTIAL_ROUNDS] = round_constants().1;
+
+/// Derives round constants from the splitmix64 sequence seeded with "Poseidon", one element per output.
+const fn round_constants() -> ([[u32; WIDTH]; FULL_ROUNDS], [u32; PARTIAL_ROUNDS]) {
+    const fn next(state: u64) -> (u64, u32) {
+        let state = state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^= z >> 31;
+        (state, (z >> 33) as u32 % MODULUS)
+    }
+
+    let mut state = u64::from_be_bytes(*b"Poseidon");
+    let mut external = [[0u32; WIDTH]; FULL_ROUNDS];
+    let mut internal = [0u32; PARTIAL_ROUNDS];
+
+    let mut round = 0;
+    while round < FULL_ROUNDS {
+        let mut i = 0;
+        while i < WIDTH {
+            (state, external[round][i]) = next(state);
+            i += 1;
+        }
+        round += 1;
+    }
+    let mut round = 0;
+    while round < PARTIAL_ROUNDS {
+        (state, internal[round]) = next(state);
+        round += 1;
+    }
+    (external, internal)
+}
+
+fn add(a: u32, b: u32) -> u32 {
+    ((u64::from(a) + u64::from(b)) % u64::from(MODULUS)) as u32
+}
+
+fn mul(a: u32, b: u32) -> u32 {
+    ((u64::from(a) * u64::from(b)) % u64::from(MODULUS)) as u32
+}
+
+fn pow5(x: u32) -> u32 {
+    let x2 = mul(x, x);
+    mul(mul(x2, x2), x)
+}
+
+/// Multiplies four elements by the circulant-like matrix `[[5, 7, 1, 3], [4, 6, 1, 1], [1, 3, 5, 7], [1, 1, 4, 6]]`.
+fn apply_m4(x: [u32; 4]) -> [u32; 4] {
+    let t0 = add(x[0], x[1]);
+    let t02 = add(t0, t0);
+    let t1 = add(x[2], x[3]);
+    let t12 = add(t1, t1);
+    let t2 = add(add(x[1], x[1]), t1);
+    let t3 = add(add(x[3], x[3]), t0);
+    let t4 = add(add(t12, t12), t3);
+    let t5 = add(add(t02, t02), t2);
+    let t6 = add(t3, t5);
+    let t7 = add(t2, t4);
+    [t6, t5, t7, t4]
+}
+
+fn apply_external_matrix(state: &mut [u32; WIDTH]) {
+    for chunk in state.chunks_exact_mut(4) {
+        let x = apply_m4([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        chunk.copy_from_slice(&x);
+    }
+    for j in 0..4 {
+        let sum = (0..4).fold(0, |acc, i| add(acc, state[4 * i + j]));
+        for i in 0..4 {
+            state[4 * i + j] = add(state[4 * i + j], sum);
+        }
+    }
+}
+
+fn apply_internal_matrix(state: &mut [u32; WIDTH]) {
+    let sum = state.iter().fold(0, |acc, &x| add(acc, x));
+    for (i, x) in state.iter_mut().enumerate() {
+        *x = add(mul(*x, 1 << (i + 1)), sum);
+    }
+}
+
+/// Applies the Poseidon2 permutation to `state`, elements that aren't canonical are reduced first.
+pub fn permute(state: &mut [u32; WIDTH]) {
+    for x in state.iter_mut() {
+        *x %= MODULUS;
+    }
+
+    apply_external_matrix(state);
+    for constants in &EXTERNAL_ROUND_CONSTANTS[..FULL_ROUNDS / 2] {
+        for (x, &c) in state.iter_mut().zip(constants) {
+            *x = pow5(add(*x, c));
+        }
+        apply_external_matrix(state);
+    }
+    for &c in &INTERNAL_ROUND_CONSTANTS {
+        state[0] = pow5(add(state[0], c));
+        apply_internal_matrix(state);
+    }
+    for constants in &EXTERNAL_ROUND_CONSTANTS[FULL_ROUNDS / 2..] {
+        for (x, &c) in state.iter_mut().zip(constants) {
+            *x = pow5(add(*x, c));
+        }
+        apply_external_matrix(state);
+    }
+}
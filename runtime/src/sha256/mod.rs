@@ -0,0 +1,109 @@
+//! SHA-256 hash function, with the compression function accelerated by the `sha256_compress` custom instruction.
+//!
+//! # Example
+//!
+//! ```
+//! use nexus_rt::sha256::Sha256;
+//!
+//! let mut hasher = Sha256::new();
+//! hasher.update(b"hello ");
+//! hasher.update(b"world");
+//! let digest: [u8; 32] = hasher.finalize();
+//! ```
+
+#[cfg(all(target_arch = "riscv32", not(target_feature = "c")))]
+mod riscv32;
+#[cfg(all(target_arch = "riscv32", not(target_feature = "c")))]
+pub use riscv32::sha256_compress;
+
+#[cfg(not(all(target_arch = "riscv32", not(target_feature = "c"))))]
+mod soft;
+#[cfg(not(all(target_arch = "riscv32", not(target_feature = "c"))))]
+pub use soft::sha256_compress;
+
+const BLOCK_SIZE: usize = 64;
+
+const INITIAL_STATE: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+/// Incremental SHA-256 hasher.
+#[derive(Clone)]
+pub struct Sha256 {
+    state: [u32; 8],
+    buffer: [u8; BLOCK_SIZE],
+    offset: usize,
+    len: u64,
+}
+
+impl Default for Sha256 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Sha256 {
+    /// Creates a new hasher.
+    pub fn new() -> Self {
+        Self {
+            state: INITIAL_STATE,
+            buffer: [0u8; BLOCK_SIZE],
+            offset: 0,
+            len: 0,
+        }
+    }
+
+    /// Absorbs additional input. Can be called multiple times.
+    pub fn update(&mut self, input: &[u8]) {
+        self.len += input.len() as u64;
+
+        let mut input = input;
+        while !input.is_empty() {
+            let len = input.len().min(BLOCK_SIZE - self.offset);
+            self.buffer[self.offset..][..len].copy_from_slice(&input[..len]);
+            self.offset += len;
+            input = &input[len..];
+
+            if self.offset == BLOCK_SIZE {
+                self.compress();
+            }
+        }
+    }
+
+    /// Pads the message and returns the digest.
+    pub fn finalize(mut self) -> [u8; 32] {
+        let bit_len = self.len.wrapping_mul(8);
+
+        self.buffer[self.offset] = 0x80;
+        self.offset += 1;
+        if self.offset > BLOCK_SIZE - 8 {
+            self.buffer[self.offset..].fill(0);
+            self.compress();
+        }
+        self.buffer[self.offset..BLOCK_SIZE - 8].fill(0);
+        self.buffer[BLOCK_SIZE - 8..].copy_from_slice(&bit_len.to_be_bytes());
+        self.compress();
+
+        let mut digest = [0u8; 32];
+        for (bytes, word) in digest.chunks_exact_mut(4).zip(self.state) {
+            bytes.copy_from_slice(&word.to_be_bytes());
+        }
+        digest
+    }
+
+    fn compress(&mut self) {
+        let mut block = [0u32; 16];
+        for (word, bytes) in block.iter_mut().zip(self.buffer.chunks_exact(4)) {
+            *word = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        }
+        sha256_compress(&mut self.state, &block);
+        self.offset = 0;
+    }
+}
+
+/// Computes the SHA-256 digest of `input`.
+pub fn sha256(input: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(input);
+    hasher.finalize()
+}
@@ -0,0 +1,20 @@
+/// Compresses the block stored at `block` into the state stored at `state`. The macro can only be invoked through the
+/// public interface.
+macro_rules! sha256_compress_call {
+    ($state:expr, $block:expr) => {
+        unsafe {
+            core::arch::asm!(
+                ".insn s 0b1011010, 0b001, {1}, 0({0})",
+                in(reg) $state,
+                in(reg) $block,
+            )
+        }
+    };
+}
+
+/// Applies the SHA-256 compression function to `state` with a block of sixteen big-endian message words.
+pub fn sha256_compress(state: &mut [u32; 8], block: &[u32; 16]) {
+    let state_ptr = state as *mut _;
+    let block_ptr = block as *const _;
+    sha256_compress_call!(state_ptr, block_ptr);
+}
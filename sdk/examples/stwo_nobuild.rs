@@ -4,7 +4,7 @@ const EXAMPLE_NAME: &str = "example";
 
 const TARGET_PATH: &str = concat!(
     env!("CARGO_MANIFEST_DIR"),
-    "/../target/riscv32i-unknown-none-elf/release"
+    "/../target/riscv32im-unknown-none-elf/release"
 );
 
 fn main() {
@@ -13,7 +13,7 @@ fn main() {
         panic!(
             "{}{} was not found, make sure to compile the program \
              with `cd examples && cargo build --release --bin {}`",
-            "target/riscv32i-unknown-none-elf/release/", EXAMPLE_NAME, EXAMPLE_NAME,
+            "target/riscv32im-unknown-none-elf/release/", EXAMPLE_NAME, EXAMPLE_NAME,
         );
     }
 
@@ -10,6 +10,10 @@ pub enum ConfigurationError {
     /// The prover or verifier was invoked without yet having been configured.
     #[error("operation invoked without required configuration having been done")]
     NotYetConfigured,
+
+    /// The prover configuration is unsupported or too weak for the verifier to accept.
+    #[error("invalid prover configuration: {0}")]
+    InvalidProverConfig(String),
 }
 
 /// Errors that occur during dynamic compilation of guest programs.
@@ -6,6 +6,7 @@ use std::marker::PhantomData;
 use thiserror::Error;
 
 use crate::error::{BuildError, ConfigurationError, IOError, PathError};
+use nexus_core::nvm::internals::InternalView;
 
 /// Errors that occur while proving using Stwo.
 #[derive(Debug, Error)]
@@ -53,14 +54,154 @@ pub struct Stwo<C: Compute = Local> {
     pub elf: nexus_core::nvm::ElfFile,
     /// The associated data to prove with.
     pub ad: Vec<u8>,
+    /// The security parameters to prove with.
+    pub config: nexus_core::stwo::ProverConfig,
+    /// The hash function to commit to the execution trace with.
+    pub hasher: MerkleHasher,
     _compute: PhantomData<C>,
 }
 
+/// Hash function used by Stwo for the Merkle commitments and the Fiat-Shamir channel.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MerkleHasher {
+    /// Blake2s, the fastest to prove and to verify natively.
+    #[default]
+    Blake2s,
+    /// Poseidon over the Stark252 field, cheaper to verify in an algebraic (recursive or on-chain) verifier.
+    Poseidon252,
+}
+
+/// The Stwo proof, committed with one of the supported [`MerkleHasher`]s.
+#[derive(Serialize, Deserialize)]
+enum StwoProof {
+    Blake2s(nexus_core::stwo::Proof<nexus_core::stwo::Blake2sMerkleChannel>),
+    Poseidon252(nexus_core::stwo::Proof<nexus_core::stwo::Poseidon252MerkleChannel>),
+}
+
 /// The Stwo proof, alongside machine configuration information needed for verification.
 #[derive(Serialize, Deserialize)]
 pub struct Proof {
-    proof: nexus_core::stwo::Proof,
+    proof: StwoProof,
     memory_layout: nexus_core::nvm::internals::LinearMemoryLayout,
+    extensions: Vec<nexus_core::stwo::Extension>,
+}
+
+impl Proof {
+    /// The hash function the proof is committed with.
+    pub fn hasher(&self) -> MerkleHasher {
+        match self.proof {
+            StwoProof::Blake2s(_) => MerkleHasher::Blake2s,
+            StwoProof::Poseidon252(_) => MerkleHasher::Poseidon252,
+        }
+    }
+
+    /// Verify the proof, rejecting proofs with fewer than `min_security_bits` bits of security.
+    pub fn verify_with_security(
+        &self,
+        view: &nexus_core::nvm::View,
+        min_security_bits: u32,
+    ) -> Result<(), Error> {
+        // The extensions are derived from the expected program, never trusted from the proof.
+        let extensions = required_extensions(view);
+        if extensions != self.extensions {
+            return Err(
+                nexus_core::stwo::VerificationError::InvalidStructure(format!(
+                    "proof extensions {:?} don't match the extensions {:?} required by the program",
+                    self.extensions, extensions
+                ))
+                .into(),
+            );
+        }
+
+        match &self.proof {
+            StwoProof::Blake2s(proof) => nexus_core::stwo::verify_with_extensions(
+                &extensions,
+                min_security_bits,
+                proof.clone(),
+                view,
+            )?,
+            StwoProof::Poseidon252(proof) => nexus_core::stwo::verify_with_extensions(
+                &extensions,
+                min_security_bits,
+                proof.clone(),
+                view,
+            )?,
+        }
+        Ok(())
+    }
+}
+
+impl<C: Compute> Stwo<C> {
+    /// Set the security parameters to prove with, trading the proof size for the prover time.
+    ///
+    /// The parameters are carried in the proof, and the verifier rejects proofs below its security floor, by default
+    /// [`DEFAULT_MIN_SECURITY_BITS`](nexus_core::stwo::DEFAULT_MIN_SECURITY_BITS) bits.
+    pub fn set_prover_config(&mut self, config: nexus_core::stwo::ProverConfig) {
+        self.config = config;
+    }
+
+    /// Set the hash function to commit to the execution trace with, see [`MerkleHasher`].
+    pub fn set_merkle_hasher(&mut self, hasher: MerkleHasher) {
+        self.hasher = hasher;
+    }
+}
+
+/// Encodes an input with `postcard` and COBS, padded to a whole number of words.
+fn encode_input<T: Serialize>(input: &T) -> Result<Vec<u8>, IOError> {
+    let mut encoded = postcard::to_stdvec(input)?;
+    if !encoded.is_empty() {
+        encoded = postcard::to_stdvec_cobs(input)?;
+        let padded_len = (encoded.len() + 3) & !3;
+
+        assert!(padded_len >= encoded.len());
+        encoded.resize(padded_len, 0x00); // cobs ignores 0x00 padding
+    }
+    Ok(encoded)
+}
+
+/// Returns the prover extensions required by the custom instructions of the program in `view`.
+///
+/// Compressed instructions are skipped, some of them share the low bits of the built-in custom opcode.
+fn required_extensions(view: &nexus_core::nvm::View) -> Vec<nexus_core::stwo::Extension> {
+    let instructions: Vec<u32> = view
+        .get_program_memory()
+        .program
+        .iter()
+        .filter(|entry| !entry.compressed)
+        .map(|entry| entry.instruction_word)
+        .collect();
+    nexus_core::stwo::Extension::detect(&instructions)
+}
+
+impl Stwo<Local> {
+    /// Run the zkVM on private input of type `S` and public input of type `T` and estimate the cost of proving the
+    /// execution, without proving it.
+    ///
+    /// The estimate uses the extensions and the security parameters [`Prover::prove_with_input`] would use.
+    pub fn estimate_with_input<S: Serialize + Sized, T: Serialize + DeserializeOwned + Sized>(
+        &self,
+        private_input: &S,
+        public_input: &T,
+    ) -> Result<nexus_core::stwo::CostEstimate, Error> {
+        let private_encoded = encode_input(private_input)?;
+        let public_encoded = encode_input(public_input)?;
+
+        let (view, trace) = nexus_core::nvm::k_trace(
+            self.elf.clone(),
+            self.ad.as_slice(),
+            public_encoded.as_slice(),
+            private_encoded.as_slice(),
+            1,
+        )?;
+
+        let extensions = required_extensions(&view);
+        Ok(nexus_core::stwo::estimate(
+            &extensions,
+            self.config,
+            &trace,
+            &view,
+        ))
+    }
 }
 
 impl<C: Compute> ByGuestCompilation for Stwo<C>
@@ -86,6 +227,8 @@ impl Prover for Stwo<Local> {
         Ok(Self {
             elf: elf.clone(),
             ad: Vec::new(),
+            config: nexus_core::stwo::ProverConfig::default(),
+            hasher: MerkleHasher::default(),
             _compute: PhantomData,
         })
     }
@@ -102,33 +245,25 @@ impl Prover for Stwo<Local> {
         private_input: &S,
         public_input: &T,
     ) -> Result<Self::View, <Self as Prover>::Error> {
-        let mut private_encoded = postcard::to_stdvec(&private_input).map_err(IOError::from)?;
-        if !private_encoded.is_empty() {
-            let private = private_input.to_owned();
-
-            private_encoded = postcard::to_stdvec_cobs(&private).map_err(IOError::from)?;
-            let private_padded_len = (private_encoded.len() + 3) & !3;
-
-            assert!(private_padded_len >= private_encoded.len());
-            private_encoded.resize(private_padded_len, 0x00); // cobs ignores 0x00 padding
-        }
-
-        let mut public_encoded = postcard::to_stdvec(&public_input).map_err(IOError::from)?;
-        if !public_encoded.is_empty() {
-            let public = public_input.to_owned();
-
-            public_encoded = postcard::to_stdvec_cobs(&public).map_err(IOError::from)?;
-            let public_padded_len = (public_encoded.len() + 3) & !3;
+        self.run_with_aux_input(private_input, public_input, &[])
+    }
 
-            assert!(public_padded_len >= public_encoded.len());
-            public_encoded.resize(public_padded_len, 0x00); // cobs ignores 0x00 padding
-        }
+    /// Run the zkVM on private input of type `S`, public input of type `T` and raw auxiliary input, and return a view of the execution output.
+    fn run_with_aux_input<S: Serialize + Sized, T: Serialize + DeserializeOwned + Sized>(
+        &self,
+        private_input: &S,
+        public_input: &T,
+        aux_input: &[u8],
+    ) -> Result<Self::View, <Self as Prover>::Error> {
+        let private_encoded = encode_input(private_input)?;
+        let public_encoded = encode_input(public_input)?;
 
-        let (view, _) = nexus_core::nvm::k_trace(
+        let (view, _) = nexus_core::nvm::k_trace_with_aux_input(
             self.elf.clone(),
             self.ad.as_slice(),
             public_encoded.as_slice(),
             private_encoded.as_slice(),
+            aux_input,
             1,
         )?; // todo: run without tracing?
 
@@ -141,42 +276,53 @@ impl Prover for Stwo<Local> {
         private_input: &S,
         public_input: &T,
     ) -> Result<(Self::View, Self::Proof), <Self as Prover>::Error> {
-        let mut private_encoded = postcard::to_stdvec(&private_input).map_err(IOError::from)?;
-        if !private_encoded.is_empty() {
-            let private = private_input.to_owned();
-
-            private_encoded = postcard::to_stdvec_cobs(&private).map_err(IOError::from)?;
-            let private_padded_len = (private_encoded.len() + 3) & !3;
-
-            assert!(private_padded_len >= private_encoded.len());
-            private_encoded.resize(private_padded_len, 0x00); // cobs ignores 0x00 padding
-        }
-
-        let mut public_encoded = postcard::to_stdvec(&public_input).map_err(IOError::from)?;
-        if !public_encoded.is_empty() {
-            let public = public_input.to_owned();
+        self.prove_with_aux_input(private_input, public_input, &[])
+    }
 
-            public_encoded = postcard::to_stdvec_cobs(&public).map_err(IOError::from)?;
-            let public_padded_len = (public_encoded.len() + 3) & !3;
+    /// Run the zkVM on private input of type `S`, public input of type `T` and raw auxiliary input, and return a verifiable proof, along with a view of the execution output.
+    fn prove_with_aux_input<S: Serialize + Sized, T: Serialize + DeserializeOwned + Sized>(
+        self,
+        private_input: &S,
+        public_input: &T,
+        aux_input: &[u8],
+    ) -> Result<(Self::View, Self::Proof), <Self as Prover>::Error> {
+        // reject a configuration the verifier would reject before spending any time on tracing and proving
+        self.config
+            .validate(nexus_core::stwo::DEFAULT_MIN_SECURITY_BITS)
+            .map_err(ConfigurationError::InvalidProverConfig)?;
 
-            assert!(public_padded_len >= public_encoded.len());
-            public_encoded.resize(public_padded_len, 0x00); // cobs ignores 0x00 padding
-        }
+        let private_encoded = encode_input(private_input)?;
+        let public_encoded = encode_input(public_input)?;
 
-        let (view, trace) = nexus_core::nvm::k_trace(
+        let (view, trace) = nexus_core::nvm::k_trace_with_aux_input(
             self.elf.clone(),
             self.ad.as_slice(),
             public_encoded.as_slice(),
             private_encoded.as_slice(),
+            aux_input,
             1,
         )?;
-        let proof = nexus_core::stwo::prove(&trace, &view)?;
+
+        // enable the prover extensions required by custom instructions in the guest program
+        let extensions = required_extensions(&view);
+        let proof = match self.hasher {
+            MerkleHasher::Blake2s => StwoProof::Blake2s(nexus_core::stwo::prove_with_config(
+                &extensions,
+                self.config,
+                &trace,
+                &view,
+            )?),
+            MerkleHasher::Poseidon252 => StwoProof::Poseidon252(
+                nexus_core::stwo::prove_with_config(&extensions, self.config, &trace, &view)?,
+            ),
+        };
 
         Ok((
             view,
             Proof {
                 proof,
                 memory_layout: trace.memory_layout,
+                extensions,
             },
         ))
     }
@@ -191,11 +337,13 @@ impl Verifiable for Proof {
     }
 
     fn verify(&self, view: &Self::View) -> Result<(), <Self as Verifiable>::Error> {
-        nexus_core::stwo::verify(self.proof.clone(), view)?;
-        Ok(())
+        self.verify_with_security(view, nexus_core::stwo::DEFAULT_MIN_SECURITY_BITS)
     }
 
     fn size_estimate(&self) -> usize {
-        self.proof.size_estimate()
+        match &self.proof {
+            StwoProof::Blake2s(proof) => proof.size_estimate(),
+            StwoProof::Poseidon252(proof) => proof.size_estimate(),
+        }
     }
 }
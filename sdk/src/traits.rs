@@ -41,11 +41,11 @@ impl CheckedView for nexus_core::nvm::View {
         let emulator = LinearEmulator::default();
 
         // Replace custom instructions `rin` and `wou` with `lw` and `sw`.
-        let instructions = expected_elf
-            .instructions
-            .iter()
-            .map(|instr| convert_instruction(&emulator.executor.instruction_executor, instr))
-            .collect();
+        let instructions = convert_instructions(
+            &emulator.executor.instruction_executor,
+            &expected_elf.instructions,
+            expected_elf.compressed,
+        );
 
         let converted_elf = nexus_core::nvm::ElfFile {
             instructions,
@@ -64,7 +64,11 @@ impl CheckedView for nexus_core::nvm::View {
         )
         .iter()
         .chain(map_into_io_entries::<MemoryInitializationEntry>(&expected_elf.rom_image).iter())
-        .chain(map_into_io_entries::<MemoryInitializationEntry>(&expected_elf.ram_image).iter())
+        .copied()
+        .map(MemoryInitializationEntry::into_read_only)
+        .chain(map_into_io_entries::<MemoryInitializationEntry>(
+            &expected_elf.ram_image,
+        ))
         .chain(
             slice_into_io_entries::<MemoryInitializationEntry>(
                 memory_layout.public_input_start(),
@@ -74,9 +78,9 @@ impl CheckedView for nexus_core::nvm::View {
                 ]
                 .concat(),
             )
-            .iter(),
+            .into_iter()
+            .map(MemoryInitializationEntry::into_read_only),
         )
-        .copied()
         .collect();
 
         let exit_code = slice_into_io_entries::<PublicOutputEntry>(
@@ -257,6 +261,22 @@ pub trait Prover: Sized {
         public_input: &T,
     ) -> Result<Self::View, <Self as Prover>::Error>;
 
+    /// Run the zkVM on private input of type `S`, public input of type `T` and raw auxiliary input, and return a view of the execution output.
+    ///
+    /// The auxiliary input holds untrusted hints, which the guest program reads in streaming fashion with `nexus_rt::read_aux_input`.
+    /// Provers that don't support it only accept an empty auxiliary input.
+    fn run_with_aux_input<S: Serialize + Sized, T: Serialize + DeserializeOwned + Sized>(
+        &self,
+        private_input: &S,
+        public_input: &T,
+        aux_input: &[u8],
+    ) -> Result<Self::View, <Self as Prover>::Error> {
+        if !aux_input.is_empty() {
+            return Err(nexus_core::nvm::VMError::AuxiliaryInputUnsupported.into());
+        }
+        Self::run_with_input::<S, T>(self, private_input, public_input)
+    }
+
     /// Run the zkVM and return a verifiable proof, along with a view of the execution output.
     fn prove(self) -> Result<(Self::View, Self::Proof), <Self as Prover>::Error>
     where
@@ -271,6 +291,21 @@ pub trait Prover: Sized {
         private_input: &S,
         public_input: &T,
     ) -> Result<(Self::View, Self::Proof), <Self as Prover>::Error>;
+
+    /// Run the zkVM on private input of type `S`, public input of type `T` and raw auxiliary input, and return a verifiable proof, along with a view of the execution output.
+    ///
+    /// Provers that don't support auxiliary input only accept an empty one.
+    fn prove_with_aux_input<S: Serialize + Sized, T: Serialize + DeserializeOwned + Sized>(
+        self,
+        private_input: &S,
+        public_input: &T,
+        aux_input: &[u8],
+    ) -> Result<(Self::View, Self::Proof), <Self as Prover>::Error> {
+        if !aux_input.is_empty() {
+            return Err(nexus_core::nvm::VMError::AuxiliaryInputUnsupported.into());
+        }
+        Self::prove_with_input::<S, T>(self, private_input, public_input)
+    }
 }
 
 /// An object that can be configured with necessary parameters for proving and verification.
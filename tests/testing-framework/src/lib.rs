@@ -1,17 +1,19 @@
 #[cfg(test)]
 mod test {
+    use nexus_common::constants::{KECCAKF_OPCODE, MEMCPY_FN3, MEMSET_FN3};
     use nexus_common::memory::alignment::Alignable;
     use nexus_common_testing::emulator::{
-        compile_multi, emulate, parse_output, EmulatorType, IOArgs, Input, Output,
+        compile_multi, compile_multi_with_features, emulate, parse_output, EmulatorType, IOArgs,
+        Input, Output,
     };
     use nexus_common_testing::program_trace;
     use nexus_vm::elf::ElfFile;
     use nexus_vm::emulator::InternalView;
-    use nexus_vm::trace::{k_trace, k_trace_direct};
+    use nexus_vm::trace::{k_trace, k_trace_direct, k_trace_streaming, Trace};
     use nexus_vm_prover::{
         extensions::ExtensionComponent,
         machine::{BaseComponent, Machine},
-        prove, verify,
+        prove, verify, DEFAULT_MIN_SECURITY_BITS,
     };
     use postcard::to_allocvec_cobs;
     use serial_test::serial;
@@ -104,6 +106,27 @@ mod test {
         }
     }
 
+    /// Helper function to prove and verify an example that uses custom instructions of the given extensions.
+    fn prove_with_extensions(name: &str, extensions: &[ExtensionComponent]) {
+        let elfs = compile_multi(name, &["-C opt-level=3"], &HOME_PATH);
+        let (view, execution_trace) =
+            k_trace(elfs[0].clone(), &[], &[], &[], K).expect("error generating trace");
+        let proof =
+            Machine::<BaseComponent>::prove_with_extensions(extensions, &execution_trace, &view)
+                .unwrap();
+        Machine::<BaseComponent>::verify_with_extensions(
+            extensions,
+            DEFAULT_MIN_SECURITY_BITS,
+            proof,
+            view.get_program_memory(),
+            view.view_associated_data().as_deref().unwrap_or_default(),
+            view.get_initial_memory(),
+            view.get_exit_code(),
+            view.get_public_output(),
+        )
+        .unwrap();
+    }
+
     #[test]
     #[serial]
     fn test_emulate_io() {
@@ -187,6 +210,24 @@ mod test {
         verify(proof, &view).unwrap();
     }
 
+    #[test]
+    #[serial]
+    fn test_prove_fact_streaming() {
+        let elfs = compile_multi("examples/src/bin/fact", &["-C opt-level=3"], &HOME_PATH);
+        let (view, execution_trace) =
+            k_trace(elfs[0].clone(), &[], &[], &[], K).expect("error generating trace");
+        let (streaming_view, streaming_trace) =
+            k_trace_streaming(elfs[0].clone(), &[], &[], &[], K).expect("error generating trace");
+
+        let proof = prove(&execution_trace, &view).unwrap();
+        let streaming_proof = prove(&streaming_trace, &streaming_view).unwrap();
+        assert_eq!(
+            postcard::to_allocvec(&streaming_proof).unwrap(),
+            postcard::to_allocvec(&proof).unwrap()
+        );
+        verify(streaming_proof, &view).unwrap();
+    }
+
     #[test]
     #[serial]
     fn test_emulate_fib() {
@@ -378,21 +419,82 @@ mod test {
     #[test]
     #[serial]
     fn test_prove_keccak_precompile() {
-        let elfs = compile_multi(
+        prove_with_extensions(
             "examples/src/bin/keccak_precompile",
+            ExtensionComponent::keccak_extensions(),
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_prove_sha256_precompile() {
+        prove_with_extensions(
+            "examples/src/bin/sha256_precompile",
+            ExtensionComponent::sha256_extensions(),
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_prove_bigint_precompile() {
+        prove_with_extensions(
+            "examples/src/bin/bigint_precompile",
+            ExtensionComponent::bigint_extensions(),
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_prove_poseidon2_precompile() {
+        prove_with_extensions(
+            "examples/src/bin/poseidon2_precompile",
+            ExtensionComponent::poseidon2_extensions(),
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_prove_memcpy_precompile() {
+        prove_with_extensions(
+            "examples/src/bin/memcpy_precompile",
+            ExtensionComponent::memcpy_extensions(),
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_prove_memcpy_builtins() {
+        let elfs = compile_multi_with_features(
+            "examples/src/bin/memcpy_builtins",
             &["-C opt-level=3"],
+            &["memcpy-precompile"],
             &HOME_PATH,
         );
         let (view, execution_trace) =
             k_trace(elfs[0].clone(), &[], &[], &[], K).expect("error generating trace");
-        let proof = Machine::<BaseComponent>::prove_with_extensions(
-            ExtensionComponent::keccak_extensions(),
-            &execution_trace,
-            &view,
-        )
-        .unwrap();
+        assert!(view.get_exit_code().iter().all(|entry| entry.value == 0));
+
+        // The compiler-generated calls must have reached the custom instructions.
+        let count = |fn3: u8| {
+            execution_trace
+                .get_blocks_iter()
+                .flat_map(|block| block.steps.iter())
+                .filter(|step| {
+                    let opcode = &step.instruction.opcode;
+                    opcode.raw() == KECCAKF_OPCODE && opcode.fn3().value() == fn3
+                })
+                .count()
+        };
+        assert!(count(MEMCPY_FN3) > 0, "no memcpy instruction in the trace");
+        assert!(count(MEMSET_FN3) > 0, "no memset instruction in the trace");
+
+        let extensions = ExtensionComponent::memcpy_extensions();
+        let proof =
+            Machine::<BaseComponent>::prove_with_extensions(extensions, &execution_trace, &view)
+                .unwrap();
         Machine::<BaseComponent>::verify_with_extensions(
-            ExtensionComponent::keccak_extensions(),
+            extensions,
+            DEFAULT_MIN_SECURITY_BITS,
             proof,
             view.get_program_memory(),
             view.view_associated_data().as_deref().unwrap_or_default(),
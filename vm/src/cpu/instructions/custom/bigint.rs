@@ -0,0 +1,274 @@
+use crate::{
+    cpu::state::{InstructionExecutor, InstructionState},
+    memory::{LoadOp, LoadOps, MemAccessSize, MemoryProcessor, StoreOps},
+    riscv::Instruction,
+};
+use nexus_common::{
+    constants::WORD_SIZE,
+    cpu::{Processor, Registers},
+};
+
+/// Number of 32-bit words of a 256-bit integer.
+pub const BIGINT_WORDS: usize = 8;
+
+/// Number of operands read by `mul_add_mod`: the factors, the addend and the modulus.
+pub const MUL_ADD_MOD_OPERANDS: usize = 4;
+
+/// Returns the quotient and the remainder of `a * b + c` divided by `m`.
+///
+/// Integers are little-endian words, a zero modulus stands for 2^256. The quotient can take up to 512 bits when
+/// inputs aren't reduced.
+pub fn mul_add_div_rem(
+    a: &[u32; BIGINT_WORDS],
+    b: &[u32; BIGINT_WORDS],
+    c: &[u32; BIGINT_WORDS],
+    m: &[u32; BIGINT_WORDS],
+) -> ([u32; 2 * BIGINT_WORDS], [u32; BIGINT_WORDS]) {
+    // a * b + c < 2^512
+    let mut t = [0u32; 2 * BIGINT_WORDS];
+    t[..BIGINT_WORDS].copy_from_slice(c);
+    for (i, &a) in a.iter().enumerate() {
+        let mut carry = 0u64;
+        for (j, &b) in b.iter().enumerate() {
+            let v = u64::from(a) * u64::from(b) + u64::from(t[i + j]) + carry;
+            t[i + j] = v as u32;
+            carry = v >> 32;
+        }
+        for word in &mut t[i + BIGINT_WORDS..] {
+            let v = u64::from(*word) + carry;
+            *word = v as u32;
+            carry = v >> 32;
+        }
+    }
+
+    if m.iter().all(|&word| word == 0) {
+        let mut q = [0u32; 2 * BIGINT_WORDS];
+        q[..BIGINT_WORDS].copy_from_slice(&t[BIGINT_WORDS..]);
+        let r = t[..BIGINT_WORDS].try_into().expect("invalid slice length");
+        return (q, r);
+    }
+
+    // Binary long division, the remainder takes an extra word to hold the shifted out bit.
+    let mut q = [0u32; 2 * BIGINT_WORDS];
+    let mut r = [0u32; BIGINT_WORDS + 1];
+    for bit in (0..2 * BIGINT_WORDS * 32).rev() {
+        let mut carry = (t[bit / 32] >> (bit % 32)) & 1;
+        for word in &mut r {
+            let shifted_out = *word >> 31;
+            *word = (*word << 1) | carry;
+            carry = shifted_out;
+        }
+
+        let ge = r[BIGINT_WORDS] != 0 || {
+            let low = &r[..BIGINT_WORDS];
+            low.iter().rev().cmp(m.iter().rev()).is_ge()
+        };
+        if ge {
+            let mut borrow = 0i64;
+            for (i, word) in r.iter_mut().enumerate() {
+                let v = i64::from(*word) - i64::from(m.get(i).copied().unwrap_or(0)) + borrow;
+                *word = v as u32;
+                borrow = v >> 32;
+            }
+            q[bit / 32] |= 1 << (bit % 32);
+        }
+    }
+
+    let r = r[..BIGINT_WORDS].try_into().expect("invalid slice length");
+    (q, r)
+}
+
+/// Computes `(a * b + c) mod m` of 256-bit integers, a zero modulus stands for 2^256.
+pub fn mul_add_mod(
+    a: &[u32; BIGINT_WORDS],
+    b: &[u32; BIGINT_WORDS],
+    c: &[u32; BIGINT_WORDS],
+    m: &[u32; BIGINT_WORDS],
+) -> [u32; BIGINT_WORDS] {
+    mul_add_div_rem(a, b, c, m).1
+}
+
+/// Computes `(a * b + c) mod m` of 256-bit little-endian integers.
+///
+/// The first register holds the address the result is written to, the second register holds the address of the
+/// operands `a`, `b`, `c` and `m` stored one after another. The result may overwrite the operands.
+pub struct MulAddModInstruction {
+    result_addr: u32,
+    operands_addr: u32,
+    operands: [[u32; BIGINT_WORDS]; MUL_ADD_MOD_OPERANDS],
+    result: [u32; BIGINT_WORDS],
+}
+
+impl InstructionState for MulAddModInstruction {
+    fn memory_read(
+        &mut self,
+        memory: &impl MemoryProcessor,
+    ) -> Result<LoadOps, nexus_common::error::MemoryError> {
+        let mut loads = LoadOps::default();
+        for (i, word) in self.operands.iter_mut().flatten().enumerate() {
+            let op = memory.read(
+                self.operands_addr + (i * WORD_SIZE) as u32,
+                MemAccessSize::Word,
+            )?;
+            loads.insert(op);
+
+            let LoadOp::Op(.., v) = op;
+            *word = v;
+        }
+
+        Ok(loads)
+    }
+
+    fn memory_write(
+        &self,
+        memory: &mut impl MemoryProcessor,
+    ) -> Result<StoreOps, nexus_common::error::MemoryError> {
+        let mut stores = StoreOps::default();
+        for (i, &word) in self.result.iter().enumerate() {
+            let op = memory.write(
+                self.result_addr + (i * WORD_SIZE) as u32,
+                MemAccessSize::Word,
+                word,
+            )?;
+            stores.insert(op);
+        }
+
+        Ok(stores)
+    }
+
+    fn execute(&mut self) {
+        let [a, b, c, m] = &self.operands;
+        self.result = mul_add_mod(a, b, c, m);
+    }
+
+    fn write_back(&self, _cpu: &mut impl Processor) -> Option<u32> {
+        None
+    }
+}
+
+impl InstructionExecutor for MulAddModInstruction {
+    type InstructionState = Self;
+
+    fn decode(ins: &Instruction, registers: &impl Registers) -> Self {
+        Self {
+            result_addr: registers[ins.op_a],
+            operands_addr: registers[ins.op_b],
+            operands: [[0u32; BIGINT_WORDS]; MUL_ADD_MOD_OPERANDS],
+            result: [0u32; BIGINT_WORDS],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nexus_common::{
+        constants::{KECCAKF_OPCODE, MUL_ADD_MOD_FN3},
+        memory::RW,
+        riscv::{register::Register, Opcode},
+    };
+
+    use crate::{cpu::Cpu, memory::VariableMemory};
+
+    use super::*;
+
+    // secp256k1 base field modulus
+    const P: [u32; BIGINT_WORDS] = [
+        0xfffffc2f, 0xfffffffe, 0xffffffff, 0xffffffff, 0xffffffff, 0xffffffff, 0xffffffff,
+        0xffffffff,
+    ];
+
+    fn from_u128(value: u128) -> [u32; BIGINT_WORDS] {
+        let mut words = [0u32; BIGINT_WORDS];
+        for (i, word) in words.iter_mut().take(4).enumerate() {
+            *word = (value >> (32 * i)) as u32;
+        }
+        words
+    }
+
+    #[test]
+    fn test_mul_add_div_rem() {
+        let zero = [0u32; BIGINT_WORDS];
+        let one = from_u128(1);
+
+        // small values
+        let (q, r) = mul_add_div_rem(
+            &from_u128(1 << 70),
+            &from_u128(3),
+            &from_u128(5),
+            &from_u128(7),
+        );
+        let expected = (3u128 << 70) + 5;
+        assert_eq!(r, from_u128(expected % 7));
+        assert_eq!(q[..BIGINT_WORDS], from_u128(expected / 7));
+
+        // (p - 1)^2 = 1 mod p
+        let mut p_minus_one = P;
+        p_minus_one[0] -= 1;
+        assert_eq!(mul_add_mod(&p_minus_one, &p_minus_one, &zero, &P), one);
+
+        // (p - 1) + 1 = 0 mod p
+        assert_eq!(mul_add_mod(&p_minus_one, &one, &one, &P), zero);
+
+        // zero modulus wraps around 2^256
+        let max = [u32::MAX; BIGINT_WORDS];
+        let (q, r) = mul_add_div_rem(&max, &max, &max, &zero);
+        // (2^256 - 1)^2 + 2^256 - 1 = 2^512 - 2^256
+        assert_eq!(r, zero);
+        assert_eq!(q[..BIGINT_WORDS], max);
+        assert_eq!(q[BIGINT_WORDS..], zero);
+
+        // unreduced inputs with a small modulus take a wide quotient
+        let (q, r) = mul_add_div_rem(&max, &max, &zero, &from_u128(3));
+        assert_eq!(r, zero);
+        assert_ne!(q[BIGINT_WORDS..], zero);
+    }
+
+    #[test]
+    fn test_mul_add_mod_instruction() {
+        let mut cpu = Cpu::default();
+        let mut memory = VariableMemory::<RW>::default();
+
+        let result_addr = 0x1000;
+        let operands_addr = 0x2000;
+        cpu.registers.write(Register::X1, result_addr);
+        cpu.registers.write(Register::X2, operands_addr);
+
+        let operands = [from_u128(u128::MAX), from_u128(2), from_u128(1), P];
+        for (i, &word) in operands.iter().flatten().enumerate() {
+            memory
+                .write(
+                    operands_addr + (i * WORD_SIZE) as u32,
+                    MemAccessSize::Word,
+                    word,
+                )
+                .expect("write failed");
+        }
+
+        let bare_instruction = Instruction::new_ir(
+            Opcode::new(KECCAKF_OPCODE, Some(MUL_ADD_MOD_FN3), None, "mul_add_mod"),
+            1,
+            2,
+            0,
+        );
+        let mut instruction = MulAddModInstruction::decode(&bare_instruction, &cpu.registers);
+
+        instruction.memory_read(&memory).expect("read failed");
+        instruction.execute();
+        instruction.memory_write(&mut memory).expect("write failed");
+
+        let result: Vec<u32> = memory
+            .segment_bytes(
+                result_addr,
+                Some(result_addr + (BIGINT_WORDS * WORD_SIZE) as u32),
+            )
+            .expect("segment read failed")
+            .chunks(WORD_SIZE)
+            .map(|word| u32::from_le_bytes(word.try_into().expect("invalid word size")))
+            .collect();
+
+        // 2 * (2^128 - 1) + 1 = 2^129 - 1 is below the modulus
+        let mut expected = from_u128(u128::MAX);
+        expected[4] = 1;
+        assert_eq!(result, expected);
+    }
+}
@@ -0,0 +1,228 @@
+use crate::{
+    cpu::state::{InstructionExecutor, InstructionState},
+    memory::{LoadOp, LoadOps, MemAccessSize, MemoryProcessor, StoreOps},
+    riscv::Instruction,
+};
+use nexus_common::cpu::{Processor, Registers};
+
+/// Copies bytes between memory regions in a single step.
+///
+/// The first register holds the destination address, the second register holds the source address and the immediate
+/// holds the number of bytes. All source bytes are read before any of them is written, so overlapping regions are
+/// copied as if through an intermediate buffer.
+pub struct MemcpyInstruction {
+    dst: u32,
+    src: u32,
+    bytes: Vec<u8>,
+}
+
+impl InstructionState for MemcpyInstruction {
+    fn memory_read(
+        &mut self,
+        memory: &impl MemoryProcessor,
+    ) -> Result<LoadOps, nexus_common::error::MemoryError> {
+        let mut loads = LoadOps::default();
+        for (i, byte) in self.bytes.iter_mut().enumerate() {
+            let op = memory.read(self.src + i as u32, MemAccessSize::Byte)?;
+            loads.insert(op);
+
+            let LoadOp::Op(.., v) = op;
+            *byte = v as u8;
+        }
+
+        Ok(loads)
+    }
+
+    fn memory_write(
+        &self,
+        memory: &mut impl MemoryProcessor,
+    ) -> Result<StoreOps, nexus_common::error::MemoryError> {
+        let mut stores = StoreOps::default();
+        for (i, &byte) in self.bytes.iter().enumerate() {
+            let op = memory.write(self.dst + i as u32, MemAccessSize::Byte, byte.into())?;
+            stores.insert(op);
+        }
+
+        Ok(stores)
+    }
+
+    fn execute(&mut self) {}
+
+    fn write_back(&self, _cpu: &mut impl Processor) -> Option<u32> {
+        None
+    }
+}
+
+impl InstructionExecutor for MemcpyInstruction {
+    type InstructionState = Self;
+
+    fn decode(ins: &Instruction, registers: &impl Registers) -> Self {
+        Self {
+            dst: registers[ins.op_a],
+            src: registers[ins.op_b],
+            bytes: vec![0u8; ins.op_c as usize],
+        }
+    }
+}
+
+/// Fills a memory region with a byte in a single step.
+///
+/// The first register holds the destination address, the low byte of the second register holds the value and the
+/// immediate holds the number of bytes.
+pub struct MemsetInstruction {
+    dst: u32,
+    value: u8,
+    len: u32,
+}
+
+impl InstructionState for MemsetInstruction {
+    fn memory_read(
+        &mut self,
+        _memory: &impl MemoryProcessor,
+    ) -> Result<LoadOps, nexus_common::error::MemoryError> {
+        Ok(LoadOps::default())
+    }
+
+    fn memory_write(
+        &self,
+        memory: &mut impl MemoryProcessor,
+    ) -> Result<StoreOps, nexus_common::error::MemoryError> {
+        let mut stores = StoreOps::default();
+        for i in 0..self.len {
+            let op = memory.write(self.dst + i, MemAccessSize::Byte, self.value.into())?;
+            stores.insert(op);
+        }
+
+        Ok(stores)
+    }
+
+    fn execute(&mut self) {}
+
+    fn write_back(&self, _cpu: &mut impl Processor) -> Option<u32> {
+        None
+    }
+}
+
+impl InstructionExecutor for MemsetInstruction {
+    type InstructionState = Self;
+
+    fn decode(ins: &Instruction, registers: &impl Registers) -> Self {
+        Self {
+            dst: registers[ins.op_a],
+            value: registers[ins.op_b] as u8,
+            len: ins.op_c,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nexus_common::{
+        constants::{KECCAKF_OPCODE, MEMCPY_FN3, MEMSET_FN3},
+        memory::RW,
+        riscv::{register::Register, Opcode},
+    };
+
+    use crate::{cpu::Cpu, memory::VariableMemory};
+
+    use super::*;
+
+    fn read_bytes(memory: &VariableMemory<RW>, addr: u32, len: u32) -> Vec<u8> {
+        (addr..addr + len)
+            .map(|addr| {
+                let LoadOp::Op(.., v) =
+                    memory.read(addr, MemAccessSize::Byte).expect("read failed");
+                v as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_memcpy_instruction() {
+        let mut cpu = Cpu::default();
+        let mut memory = VariableMemory::<RW>::default();
+
+        let (src, dst) = (0x1000, 0x2003);
+        cpu.registers.write(Register::X1, dst);
+        cpu.registers.write(Register::X2, src);
+        for i in 0..13 {
+            memory
+                .write(src + i, MemAccessSize::Byte, i + 1)
+                .expect("write failed");
+        }
+
+        let bare_instruction = Instruction::new_ir(
+            Opcode::new(KECCAKF_OPCODE, Some(MEMCPY_FN3), None, "memcpy"),
+            1,
+            2,
+            13,
+        );
+        let mut instruction = MemcpyInstruction::decode(&bare_instruction, &cpu.registers);
+
+        let loads = instruction.memory_read(&memory).expect("read failed");
+        instruction.execute();
+        let stores = instruction.memory_write(&mut memory).expect("write failed");
+        assert_eq!(loads.len(), 13);
+        assert_eq!(stores.len(), 13);
+
+        let expected: Vec<u8> = (1..=13).collect();
+        assert_eq!(read_bytes(&memory, dst, 13), expected);
+    }
+
+    #[test]
+    fn test_memcpy_overlapping() {
+        let mut cpu = Cpu::default();
+        let mut memory = VariableMemory::<RW>::default();
+
+        let src = 0x1000;
+        cpu.registers.write(Register::X1, src + 2);
+        cpu.registers.write(Register::X2, src);
+        for i in 0..6 {
+            memory
+                .write(src + i, MemAccessSize::Byte, i + 1)
+                .expect("write failed");
+        }
+
+        let bare_instruction = Instruction::new_ir(
+            Opcode::new(KECCAKF_OPCODE, Some(MEMCPY_FN3), None, "memcpy"),
+            1,
+            2,
+            4,
+        );
+        let mut instruction = MemcpyInstruction::decode(&bare_instruction, &cpu.registers);
+
+        instruction.memory_read(&memory).expect("read failed");
+        instruction.execute();
+        instruction.memory_write(&mut memory).expect("write failed");
+
+        assert_eq!(read_bytes(&memory, src, 6), vec![1, 2, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_memset_instruction() {
+        let mut cpu = Cpu::default();
+        let mut memory = VariableMemory::<RW>::default();
+
+        let dst = 0x1001;
+        cpu.registers.write(Register::X1, dst);
+        cpu.registers.write(Register::X2, 0x1ab);
+
+        let bare_instruction = Instruction::new_ir(
+            Opcode::new(KECCAKF_OPCODE, Some(MEMSET_FN3), None, "memset"),
+            1,
+            2,
+            7,
+        );
+        let mut instruction = MemsetInstruction::decode(&bare_instruction, &cpu.registers);
+
+        instruction.memory_read(&memory).expect("read failed");
+        instruction.execute();
+        let stores = instruction.memory_write(&mut memory).expect("write failed");
+        assert_eq!(stores.len(), 7);
+
+        assert_eq!(
+            read_bytes(&memory, dst - 1, 9),
+            [0, 0xab, 0xab, 0xab, 0xab, 0xab, 0xab, 0xab, 0]
+        );
+    }
+}
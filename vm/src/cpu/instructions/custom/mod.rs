@@ -1 +1,5 @@
+pub mod bigint;
 pub mod keccakf;
+pub mod memcpy;
+pub mod poseidon2;
+pub mod sha256;
@@ -0,0 +1,267 @@
+use crate::{
+    cpu::state::{InstructionExecutor, InstructionState},
+    memory::{LoadOp, LoadOps, MemAccessSize, MemoryProcessor, StoreOps},
+    riscv::Instruction,
+};
+use nexus_common::{
+    constants::WORD_SIZE,
+    cpu::{Processor, Registers},
+};
+
+/// Modulus of the Mersenne-31 field.
+pub const M31_MODULUS: u32 = (1 << 31) - 1;
+
+/// Number of field elements in the permuted state.
+pub const POSEIDON2_WIDTH: usize = 16;
+/// Number of full rounds, half of them are applied before partial rounds.
+pub const FULL_ROUNDS: usize = 8;
+/// Number of partial rounds, applying the S-box to the first element only.
+pub const PARTIAL_ROUNDS: usize = 14;
+
+/// Round constants of full rounds.
+pub const EXTERNAL_ROUND_CONSTANTS: [[u32; POSEIDON2_WIDTH]; FULL_ROUNDS] = round_constants().0;
+/// Round constants of partial rounds.
+pub const INTERNAL_ROUND_CONSTANTS: [u32; PARTIAL_ROUNDS] = round_constants().1;
+
+/// Derives round constants from the splitmix64 sequence seeded with "Poseidon", one element per output.
+const fn round_constants() -> ([[u32; POSEIDON2_WIDTH]; FULL_ROUNDS], [u32; PARTIAL_ROUNDS]) {
+    const fn next(state: u64) -> (u64, u32) {
+        let state = state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^= z >> 31;
+        (state, (z >> 33) as u32 % M31_MODULUS)
+    }
+
+    let mut state = u64::from_be_bytes(*b"Poseidon");
+    let mut external = [[0u32; POSEIDON2_WIDTH]; FULL_ROUNDS];
+    let mut internal = [0u32; PARTIAL_ROUNDS];
+
+    let mut round = 0;
+    while round < FULL_ROUNDS {
+        let mut i = 0;
+        while i < POSEIDON2_WIDTH {
+            (state, external[round][i]) = next(state);
+            i += 1;
+        }
+        round += 1;
+    }
+    let mut round = 0;
+    while round < PARTIAL_ROUNDS {
+        (state, internal[round]) = next(state);
+        round += 1;
+    }
+    (external, internal)
+}
+
+fn add(a: u32, b: u32) -> u32 {
+    ((u64::from(a) + u64::from(b)) % u64::from(M31_MODULUS)) as u32
+}
+
+fn mul(a: u32, b: u32) -> u32 {
+    ((u64::from(a) * u64::from(b)) % u64::from(M31_MODULUS)) as u32
+}
+
+fn pow5(x: u32) -> u32 {
+    let x2 = mul(x, x);
+    mul(mul(x2, x2), x)
+}
+
+/// Multiplies four elements by the circulant-like matrix `[[5, 7, 1, 3], [4, 6, 1, 1], [1, 3, 5, 7], [1, 1, 4, 6]]`.
+fn apply_m4(x: [u32; 4]) -> [u32; 4] {
+    let t0 = add(x[0], x[1]);
+    let t02 = add(t0, t0);
+    let t1 = add(x[2], x[3]);
+    let t12 = add(t1, t1);
+    let t2 = add(add(x[1], x[1]), t1);
+    let t3 = add(add(x[3], x[3]), t0);
+    let t4 = add(add(t12, t12), t3);
+    let t5 = add(add(t02, t02), t2);
+    let t6 = add(t3, t5);
+    let t7 = add(t2, t4);
+    [t6, t5, t7, t4]
+}
+
+fn apply_external_matrix(state: &mut [u32; POSEIDON2_WIDTH]) {
+    for chunk in state.chunks_exact_mut(4) {
+        let x = apply_m4([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        chunk.copy_from_slice(&x);
+    }
+    for j in 0..4 {
+        let sum = (0..4).fold(0, |acc, i| add(acc, state[4 * i + j]));
+        for i in 0..4 {
+            state[4 * i + j] = add(state[4 * i + j], sum);
+        }
+    }
+}
+
+fn apply_internal_matrix(state: &mut [u32; POSEIDON2_WIDTH]) {
+    let sum = state.iter().fold(0, |acc, &x| add(acc, x));
+    for (i, x) in state.iter_mut().enumerate() {
+        *x = add(mul(*x, 1 << (i + 1)), sum);
+    }
+}
+
+/// Applies the Poseidon2 permutation to sixteen canonical elements of the Mersenne-31 field.
+///
+/// The S-box is `x^5`, the internal matrix is `1 + diag(2, 4, ..., 2^16)`.
+pub fn poseidon2_permute(state: &mut [u32; POSEIDON2_WIDTH]) {
+    assert!(state.iter().all(|&x| x < M31_MODULUS));
+
+    apply_external_matrix(state);
+    for constants in &EXTERNAL_ROUND_CONSTANTS[..FULL_ROUNDS / 2] {
+        for (x, &c) in state.iter_mut().zip(constants) {
+            *x = pow5(add(*x, c));
+        }
+        apply_external_matrix(state);
+    }
+    for &c in &INTERNAL_ROUND_CONSTANTS {
+        state[0] = pow5(add(state[0], c));
+        apply_internal_matrix(state);
+    }
+    for constants in &EXTERNAL_ROUND_CONSTANTS[FULL_ROUNDS / 2..] {
+        for (x, &c) in state.iter_mut().zip(constants) {
+            *x = pow5(add(*x, c));
+        }
+        apply_external_matrix(state);
+    }
+}
+
+/// Permutes sixteen field elements stored as words at the address held by the first register.
+///
+/// Words that aren't canonical are reduced modulo 2^31 - 1, the output is always canonical.
+pub struct Poseidon2Instruction {
+    rs1: u32,
+    state: [u32; POSEIDON2_WIDTH],
+}
+
+impl InstructionState for Poseidon2Instruction {
+    fn memory_read(
+        &mut self,
+        memory: &impl MemoryProcessor,
+    ) -> Result<LoadOps, nexus_common::error::MemoryError> {
+        let mut loads = LoadOps::default();
+        for (i, x) in self.state.iter_mut().enumerate() {
+            let op = memory.read(self.rs1 + (i * WORD_SIZE) as u32, MemAccessSize::Word)?;
+            loads.insert(op);
+
+            let LoadOp::Op(.., v) = op;
+            *x = v % M31_MODULUS;
+        }
+
+        Ok(loads)
+    }
+
+    fn memory_write(
+        &self,
+        memory: &mut impl MemoryProcessor,
+    ) -> Result<StoreOps, nexus_common::error::MemoryError> {
+        let mut stores = StoreOps::default();
+        for (i, &x) in self.state.iter().enumerate() {
+            let op = memory.write(self.rs1 + (i * WORD_SIZE) as u32, MemAccessSize::Word, x)?;
+            stores.insert(op);
+        }
+
+        Ok(stores)
+    }
+
+    fn execute(&mut self) {
+        poseidon2_permute(&mut self.state);
+    }
+
+    fn write_back(&self, _cpu: &mut impl Processor) -> Option<u32> {
+        None
+    }
+}
+
+impl InstructionExecutor for Poseidon2Instruction {
+    type InstructionState = Self;
+
+    fn decode(ins: &Instruction, registers: &impl Registers) -> Self {
+        Self {
+            rs1: registers[ins.op_a],
+            state: [0u32; POSEIDON2_WIDTH],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nexus_common::{
+        constants::{KECCAKF_OPCODE, POSEIDON2_FN3},
+        memory::RW,
+        riscv::{register::Register, Opcode},
+    };
+
+    use crate::{cpu::Cpu, memory::VariableMemory};
+
+    use super::*;
+
+    #[test]
+    fn test_m4_matrix() {
+        const M4: [[u32; 4]; 4] = [[5, 7, 1, 3], [4, 6, 1, 1], [1, 3, 5, 7], [1, 1, 4, 6]];
+        let x = [3, 1 << 20, M31_MODULUS - 1, 12345];
+        let expected: Vec<u32> = M4
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .zip(x)
+                    .fold(0, |acc, (&m, x)| add(acc, mul(m, x)))
+            })
+            .collect();
+        assert_eq!(apply_m4(x).to_vec(), expected);
+    }
+
+    #[test]
+    fn test_poseidon2_permute() {
+        let mut zero = [0u32; POSEIDON2_WIDTH];
+        poseidon2_permute(&mut zero);
+        assert!(zero.iter().all(|&x| x < M31_MODULUS));
+
+        let mut one = [0u32; POSEIDON2_WIDTH];
+        one[15] = 1;
+        poseidon2_permute(&mut one);
+        assert_ne!(zero, one);
+    }
+
+    #[test]
+    fn test_poseidon2_instruction() {
+        let mut cpu = Cpu::default();
+        let mut memory = VariableMemory::<RW>::default();
+
+        let addr = 0x1000;
+        cpu.registers.write(Register::X1, addr);
+        // non-canonical words are reduced
+        let input: [u32; POSEIDON2_WIDTH] =
+            std::array::from_fn(|i| M31_MODULUS * (i as u32 % 2) + i as u32);
+        for (i, &word) in input.iter().enumerate() {
+            memory
+                .write(addr + (i * WORD_SIZE) as u32, MemAccessSize::Word, word)
+                .expect("write failed");
+        }
+
+        let bare_instruction = Instruction::new_ir(
+            Opcode::new(KECCAKF_OPCODE, Some(POSEIDON2_FN3), None, "poseidon2"),
+            1,
+            0,
+            0,
+        );
+        let mut instruction = Poseidon2Instruction::decode(&bare_instruction, &cpu.registers);
+
+        instruction.memory_read(&memory).expect("read failed");
+        instruction.execute();
+        instruction.memory_write(&mut memory).expect("write failed");
+
+        let output: Vec<u32> = memory
+            .segment_bytes(addr, Some(addr + (POSEIDON2_WIDTH * WORD_SIZE) as u32))
+            .expect("segment read failed")
+            .chunks(WORD_SIZE)
+            .map(|word| u32::from_le_bytes(word.try_into().expect("invalid word size")))
+            .collect();
+
+        let mut expected: [u32; POSEIDON2_WIDTH] = std::array::from_fn(|i| i as u32);
+        poseidon2_permute(&mut expected);
+        assert_eq!(output, expected);
+    }
+}
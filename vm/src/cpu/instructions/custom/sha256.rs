@@ -0,0 +1,210 @@
+use crate::{
+    cpu::state::{InstructionExecutor, InstructionState},
+    memory::{LoadOp, LoadOps, MemAccessSize, MemoryProcessor, StoreOps},
+    riscv::Instruction,
+};
+use nexus_common::{
+    constants::WORD_SIZE,
+    cpu::{Processor, Registers},
+};
+
+/// Round constants of SHA-256.
+pub const ROUND_CONSTANTS: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+/// Applies the SHA-256 compression function to `state` with a block of sixteen big-endian message words.
+pub fn sha256_compress(state: &mut [u32; 8], block: &[u32; 16]) {
+    let mut w = [0u32; 64];
+    w[..16].copy_from_slice(block);
+    for t in 16..64 {
+        let s0 = w[t - 15].rotate_right(7) ^ w[t - 15].rotate_right(18) ^ (w[t - 15] >> 3);
+        let s1 = w[t - 2].rotate_right(17) ^ w[t - 2].rotate_right(19) ^ (w[t - 2] >> 10);
+        w[t] = s1
+            .wrapping_add(w[t - 7])
+            .wrapping_add(s0)
+            .wrapping_add(w[t - 16]);
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+    for t in 0..64 {
+        let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+        let ch = (e & f) ^ (!e & g);
+        let t1 = h
+            .wrapping_add(s1)
+            .wrapping_add(ch)
+            .wrapping_add(ROUND_CONSTANTS[t])
+            .wrapping_add(w[t]);
+        let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+        let maj = (a & b) ^ (a & c) ^ (b & c);
+        let t2 = s0.wrapping_add(maj);
+
+        h = g;
+        g = f;
+        f = e;
+        e = d.wrapping_add(t1);
+        d = c;
+        c = b;
+        b = a;
+        a = t1.wrapping_add(t2);
+    }
+
+    for (word, var) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+        *word = word.wrapping_add(var);
+    }
+}
+
+/// Compresses a block into the SHA-256 state.
+///
+/// The first register holds the address of eight state words, which are overwritten with the result. The second
+/// register holds the address of sixteen message words.
+pub struct Sha256CompressInstruction {
+    state_addr: u32,
+    block_addr: u32,
+    state: [u32; 8],
+    block: [u32; 16],
+}
+
+impl InstructionState for Sha256CompressInstruction {
+    fn memory_read(
+        &mut self,
+        memory: &impl MemoryProcessor,
+    ) -> Result<LoadOps, nexus_common::error::MemoryError> {
+        let mut loads = LoadOps::default();
+        for (address, words) in [
+            (self.state_addr, self.state.as_mut_slice()),
+            (self.block_addr, self.block.as_mut_slice()),
+        ] {
+            for (i, word) in words.iter_mut().enumerate() {
+                let op = memory.read(address + (i * WORD_SIZE) as u32, MemAccessSize::Word)?;
+                loads.insert(op);
+
+                let LoadOp::Op(.., v) = op;
+                *word = v;
+            }
+        }
+
+        Ok(loads)
+    }
+
+    fn memory_write(
+        &self,
+        memory: &mut impl MemoryProcessor,
+    ) -> Result<StoreOps, nexus_common::error::MemoryError> {
+        let mut stores = StoreOps::default();
+        for (i, &word) in self.state.iter().enumerate() {
+            let op = memory.write(
+                self.state_addr + (i * WORD_SIZE) as u32,
+                MemAccessSize::Word,
+                word,
+            )?;
+            stores.insert(op);
+        }
+
+        Ok(stores)
+    }
+
+    fn execute(&mut self) {
+        sha256_compress(&mut self.state, &self.block);
+    }
+
+    fn write_back(&self, _cpu: &mut impl Processor) -> Option<u32> {
+        None
+    }
+}
+
+impl InstructionExecutor for Sha256CompressInstruction {
+    type InstructionState = Self;
+
+    fn decode(ins: &Instruction, registers: &impl Registers) -> Self {
+        Self {
+            state_addr: registers[ins.op_a],
+            block_addr: registers[ins.op_b],
+            state: [0u32; 8],
+            block: [0u32; 16],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nexus_common::{
+        constants::{KECCAKF_OPCODE, SHA256_COMPRESS_FN3},
+        memory::RW,
+        riscv::{register::Register, Opcode},
+    };
+
+    use crate::{cpu::Cpu, memory::VariableMemory};
+
+    use super::*;
+
+    const INITIAL_STATE: [u32; 8] = [
+        0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab,
+        0x5be0cd19,
+    ];
+
+    #[test]
+    fn test_sha256_compress() {
+        let mut cpu = Cpu::default();
+        let mut memory = VariableMemory::<RW>::default();
+
+        let state_addr = 0x1000;
+        let block_addr = 0x2000;
+        cpu.registers.write(Register::X1, state_addr);
+        cpu.registers.write(Register::X2, block_addr);
+
+        // Padded single block of the message "abc".
+        let mut block = [0u32; 16];
+        block[0] = 0x61626380;
+        block[15] = 0x18;
+        for (addr, words) in [
+            (state_addr, INITIAL_STATE.as_slice()),
+            (block_addr, block.as_slice()),
+        ] {
+            for (i, &word) in words.iter().enumerate() {
+                memory
+                    .write(addr + (i * WORD_SIZE) as u32, MemAccessSize::Word, word)
+                    .expect("write failed");
+            }
+        }
+
+        let bare_instruction = Instruction::new_ir(
+            Opcode::new(
+                KECCAKF_OPCODE,
+                Some(SHA256_COMPRESS_FN3),
+                None,
+                "sha256_compress",
+            ),
+            1,
+            2,
+            0,
+        );
+        let mut instruction = Sha256CompressInstruction::decode(&bare_instruction, &cpu.registers);
+
+        instruction.memory_read(&memory).expect("read failed");
+        instruction.execute();
+        instruction.memory_write(&mut memory).expect("write failed");
+
+        let state: Vec<u32> = memory
+            .segment_bytes(state_addr, Some(state_addr + 8 * WORD_SIZE as u32))
+            .expect("segment read failed")
+            .chunks(WORD_SIZE)
+            .map(|word| u32::from_le_bytes(word.try_into().expect("invalid word size")))
+            .collect();
+
+        assert_eq!(
+            state,
+            [
+                0xba7816bf, 0x8f01cfea, 0x414140de, 0x5dae2223, 0xb00361a3, 0x96177a9c, 0xb410ff61,
+                0xf20015ad,
+            ]
+        );
+    }
+}
@@ -12,6 +12,7 @@ pub struct BeqInstruction {
     rs1: u32,
     rs2: u32,
     imm: u32,
+    size: u32,
 }
 
 impl InstructionState for BeqInstruction {
@@ -30,7 +31,7 @@ impl InstructionState for BeqInstruction {
         if self.rs1 == self.rs2 {
             cpu.pc_mut().branch(self.imm);
         } else {
-            cpu.pc_mut().step();
+            cpu.pc_mut().step_by(self.size);
         }
 
         Some(cpu.pc().value)
@@ -45,6 +46,7 @@ impl InstructionExecutor for BeqInstruction {
             rs1: registers[ins.op_a],
             rs2: registers[ins.op_b],
             imm: ins.op_c,
+            size: ins.size(),
         }
     }
 }
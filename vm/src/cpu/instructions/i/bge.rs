@@ -12,6 +12,7 @@ pub struct BgeInstruction {
     rs1: u32,
     rs2: u32,
     imm: u32,
+    size: u32,
 }
 
 impl InstructionState for BgeInstruction {
@@ -29,7 +30,7 @@ impl InstructionState for BgeInstruction {
         if (self.rs1 as i32) >= (self.rs2 as i32) {
             cpu.pc_mut().branch(self.imm);
         } else {
-            cpu.pc_mut().step();
+            cpu.pc_mut().step_by(self.size);
         }
 
         Some(cpu.pc().value)
@@ -44,6 +45,7 @@ impl InstructionExecutor for BgeInstruction {
             rs1: registers[ins.op_a],
             rs2: registers[ins.op_b],
             imm: ins.op_c,
+            size: ins.size(),
         }
     }
 }
@@ -52,6 +54,7 @@ pub struct BgeuInstruction {
     rs1: u32,
     rs2: u32,
     imm: u32,
+    size: u32,
 }
 
 impl InstructionState for BgeuInstruction {
@@ -69,7 +72,7 @@ impl InstructionState for BgeuInstruction {
         if self.rs1 >= self.rs2 {
             cpu.pc_mut().branch(self.imm);
         } else {
-            cpu.pc_mut().step();
+            cpu.pc_mut().step_by(self.size);
         }
 
         Some(cpu.pc().value)
@@ -84,6 +87,7 @@ impl InstructionExecutor for BgeuInstruction {
             rs1: registers[ins.op_a],
             rs2: registers[ins.op_b],
             imm: ins.op_c,
+            size: ins.size(),
         }
     }
 }
@@ -12,6 +12,7 @@ pub struct BneInstruction {
     rs1: u32,
     rs2: u32,
     imm: u32,
+    size: u32,
 }
 
 impl InstructionState for BneInstruction {
@@ -29,7 +30,7 @@ impl InstructionState for BneInstruction {
         if self.rs1 != self.rs2 {
             cpu.pc_mut().branch(self.imm);
         } else {
-            cpu.pc_mut().step();
+            cpu.pc_mut().step_by(self.size);
         }
 
         Some(cpu.pc().value)
@@ -44,6 +45,7 @@ impl InstructionExecutor for BneInstruction {
             rs1: registers[ins.op_a],
             rs2: registers[ins.op_b],
             imm: ins.op_c,
+            size: ins.size(),
         }
     }
 }
@@ -11,6 +11,7 @@ use nexus_common::{
 pub struct JalInstruction {
     rd: Register,
     imm: u32,
+    size: u32,
 }
 
 impl InstructionState for JalInstruction {
@@ -25,7 +26,7 @@ impl InstructionState for JalInstruction {
     fn execute(&mut self) {}
 
     fn write_back(&self, cpu: &mut impl Processor) -> Option<u32> {
-        let next_addr = cpu.pc().value + 4;
+        let next_addr = cpu.pc().value + self.size;
         cpu.registers_mut().write(self.rd, next_addr);
         cpu.pc_mut().jal(self.imm);
 
@@ -40,6 +41,7 @@ impl InstructionExecutor for JalInstruction {
         Self {
             rd: ins.op_a,
             imm: ins.op_c,
+            size: ins.size(),
         }
     }
 }
@@ -48,6 +50,7 @@ pub struct JalrInstruction {
     rd: Register,
     rs1: u32,
     imm: u32,
+    size: u32,
 }
 
 impl InstructionState for JalrInstruction {
@@ -64,9 +67,9 @@ impl InstructionState for JalrInstruction {
     fn write_back(&self, cpu: &mut impl Processor) -> Option<u32> {
         let tmp = cpu.pc().value;
         cpu.pc_mut().jalr(self.rs1, self.imm);
-        cpu.registers_mut().write(self.rd, tmp + 4);
+        cpu.registers_mut().write(self.rd, tmp + self.size);
 
-        Some(tmp + 4)
+        Some(tmp + self.size)
     }
 }
 
@@ -78,6 +81,7 @@ impl InstructionExecutor for JalrInstruction {
             rd: ins.op_a,
             rs1: register[ins.op_b],
             imm: ins.op_c,
+            size: ins.size(),
         }
     }
 }
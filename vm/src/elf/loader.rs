@@ -19,6 +19,8 @@
 //!   - Program base address
 //!   - Read-only memory image (ROM)
 //!   - Read-write memory image (RAM)
+//!   - Function symbols
+//!   - Whether the code uses compressed (RV32C) instructions
 //!
 //! - `ElfFile::from_bytes`: Allows creation of `ElfFile` from raw bytes
 //! - `ElfFile::from_path`: Allows creation of `ElfFile` from a file path
@@ -45,16 +47,18 @@
 
 use crate::{elf::parser, error::VMError, memory::MemorySegmentImage};
 
-use elf::{endian::LittleEndian, ElfBytes};
+use elf::{abi, endian::LittleEndian, ElfBytes};
 use std::fs::File;
 use std::path::Path;
 
-use super::{error::ParserError, parser::ParsedElfData};
+use super::{error::ParserError, parser::ParsedElfData, symbols::SymbolTable};
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct ElfFile {
-    /// The instructions of the program encoded as 32-bits.
+    /// The instructions of the program packed into 32-bit words.
+    ///
+    /// Compressed programs mix 16-bit and 32-bit instructions, so that instructions are only aligned to half words.
     pub instructions: Vec<u32>,
 
     /// The entrypoint of the program.
@@ -71,6 +75,15 @@ pub struct ElfFile {
 
     /// Nexus-specific metadata embedded in the ELF file.
     pub nexus_metadata: Vec<u32>,
+
+    /// Function symbols, empty if the ELF file is stripped.
+    pub symbols: SymbolTable,
+
+    /// Whether the program was built with the C extension, as flagged by `EF_RISCV_RVC`.
+    ///
+    /// Compressed instructions are only decoded in such programs, since their encodings overlap the custom
+    /// instructions of precompiles.
+    pub compressed: bool,
 }
 
 impl ElfFile {
@@ -89,6 +102,8 @@ impl ElfFile {
             rom_image,
             ram_image,
             nexus_metadata,
+            symbols: SymbolTable::default(),
+            compressed: false,
         }
     }
 
@@ -116,6 +131,8 @@ impl ElfFile {
             rom_image: parsed_elf_data.readonly_memory,
             ram_image: parsed_elf_data.writable_memory,
             nexus_metadata: parsed_elf_data.nexus_metadata,
+            symbols: SymbolTable::parse(&elf)?,
+            compressed: elf.ehdr.e_flags & abi::EF_RISCV_RVC != 0,
         })
     }
 
@@ -0,0 +1,138 @@
+//! Function symbols of an ELF file, used to annotate program counters with the function they belong to.
+//!
+//! Names are kept as they appear in the symbol table, Rust symbols can be demangled with tools such as
+//! `rustfilt`. Stripped executables yield an empty table.
+
+use std::collections::BTreeMap;
+
+use elf::{abi, endian::LittleEndian, ElfBytes};
+use serde::{Deserialize, Serialize};
+
+use super::error::ParserError;
+use crate::error::Result;
+
+/// A function symbol.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Symbol {
+    /// The name of the function.
+    pub name: String,
+    /// The address of the first instruction of the function.
+    pub address: u32,
+    /// The size of the function in bytes, zero if unknown.
+    pub size: u32,
+}
+
+/// Function symbols indexed by address.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SymbolTable {
+    symbols: BTreeMap<u32, Symbol>,
+}
+
+impl SymbolTable {
+    /// Collects function symbols, along with global labels in executable sections such as `_start`.
+    pub(crate) fn parse(elf: &ElfBytes<LittleEndian>) -> Result<Self> {
+        let mut table = Self::default();
+
+        let Some((symbol_table, string_table)) =
+            elf.symbol_table().map_err(ParserError::ELFError)?
+        else {
+            return Ok(table);
+        };
+        let section_headers = elf.section_headers();
+
+        for symbol in symbol_table {
+            let is_function = symbol.st_symtype() == abi::STT_FUNC;
+            let is_label = symbol.st_symtype() == abi::STT_NOTYPE
+                && symbol.st_bind() == abi::STB_GLOBAL
+                && section_headers
+                    .and_then(|headers| headers.get(symbol.st_shndx as usize).ok())
+                    .is_some_and(|header| header.sh_flags & abi::SHF_EXECINSTR as u64 != 0);
+            if !is_function && !is_label {
+                continue;
+            }
+
+            let name = string_table
+                .get(symbol.st_name as usize)
+                .map_err(ParserError::ELFError)?;
+            if name.is_empty() {
+                continue;
+            }
+
+            table.insert(Symbol {
+                name: name.to_string(),
+                address: symbol.st_value as u32,
+                size: symbol.st_size as u32,
+            });
+        }
+
+        Ok(table)
+    }
+
+    /// Adds a symbol, keeping the larger one if another symbol starts at the same address.
+    pub fn insert(&mut self, symbol: Symbol) {
+        match self.symbols.get(&symbol.address) {
+            Some(existing) if existing.size >= symbol.size => {}
+            _ => {
+                self.symbols.insert(symbol.address, symbol);
+            }
+        }
+    }
+
+    /// Returns the function containing `pc`.
+    ///
+    /// Symbols of unknown size are assumed to extend up to the next symbol.
+    pub fn lookup(&self, pc: u32) -> Option<&Symbol> {
+        let (_, symbol) = self.symbols.range(..=pc).next_back()?;
+        (symbol.size == 0 || pc - symbol.address < symbol.size).then_some(symbol)
+    }
+
+    pub fn len(&self) -> usize {
+        self.symbols.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Symbol> + '_ {
+        self.symbols.values()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::elf::ElfFile;
+
+    fn symbol(name: &str, address: u32, size: u32) -> Symbol {
+        Symbol {
+            name: name.to_string(),
+            address,
+            size,
+        }
+    }
+
+    #[test]
+    fn test_lookup() {
+        let mut table = SymbolTable::default();
+        table.insert(symbol("_start", 0x88, 0));
+        table.insert(symbol("main", 0x100, 0x20));
+        table.insert(symbol("alias", 0x100, 0));
+
+        assert_eq!(table.len(), 2);
+        assert_eq!(table.lookup(0x80), None);
+        assert_eq!(table.lookup(0x88).unwrap().name, "_start");
+        assert_eq!(table.lookup(0xfc).unwrap().name, "_start");
+        assert_eq!(table.lookup(0x11c).unwrap().name, "main");
+        assert_eq!(table.lookup(0x120), None);
+    }
+
+    #[test]
+    fn test_parse_symbols() {
+        let elf = ElfFile::from_path("test/fib_10.elf").unwrap();
+
+        assert_eq!(elf.symbols.lookup(elf.entry).unwrap().name, "_start");
+        let main = elf.symbols.iter().find(|s| s.name == "main").unwrap();
+        assert_eq!(elf.symbols.lookup(main.address + 4), Some(main));
+    }
+}
@@ -132,7 +132,8 @@
 //! basic block caching, custom instruction support, debug logging, and associated data handling.
 
 use super::{
-    layout::LinearMemoryLayout, memory_stats::*, registry::InstructionExecutorRegistry, *,
+    layout::LinearMemoryLayout, memory_stats::*, registry::InstructionExecutorRegistry,
+    snapshot::HarvardState, *,
 };
 use crate::{
     cpu::{instructions::InstructionResult, Cpu},
@@ -140,9 +141,12 @@ use crate::{
     error::{Result, VMError},
     memory::{
         FixedMemory, LoadOp, MemoryProcessor, MemoryRecords, MemorySegmentImage, Modes, StoreOp,
-        UnifiedMemory, VariableMemory, NA, RO, RW, WO,
+        UnifiedMemory, UnifiedMemorySnapshot, VariableMemory, NA, RO, RW, WO,
+    },
+    riscv::{
+        decode_until_end_of_a_block, decode_until_end_of_a_mixed_block, BasicBlock, Instruction,
+        Opcode, Register,
     },
-    riscv::{decode_until_end_of_a_block, BasicBlock, Instruction, Opcode, Register},
     system::SyscallInstruction,
 };
 
@@ -170,6 +174,9 @@ pub struct Executor {
     // The private input tape as a FIFO queue.
     pub private_input_tape: VecDeque<u8>,
 
+    // The auxiliary input tape of untrusted hints as a FIFO queue.
+    pub auxiliary_input_tape: VecDeque<u8>,
+
     // The global clock counter
     pub global_clock: usize,
 
@@ -185,6 +192,9 @@ pub struct Executor {
     // The entrypoint of the program
     entrypoint: u32,
 
+    // Whether the program may contain compressed instructions
+    compressed: bool,
+
     // The cycles tracker: (name, (cycle_count, occurrence))
     pub cycle_tracker: HashMap<String, (usize, usize)>,
 
@@ -196,6 +206,44 @@ pub struct Executor {
 }
 
 impl Executor {
+    /// Returns the cached basic block with an instruction starting at `pc`, if any.
+    fn cached_block(&self, pc: u32) -> Option<BasicBlockEntry> {
+        let start = self.basic_block_ref_cache.get(&pc)?;
+        let entry = self.basic_block_cache.get(start)?;
+        entry.instruction_index(pc).map(|_| entry.clone())
+    }
+
+    /// Returns the address of the word the basic block at `pc` is decoded from.
+    ///
+    /// Compressed instructions are only aligned to half words, so a block may start in the upper half of a word.
+    fn block_address(&self, pc: u32) -> u32 {
+        if self.compressed {
+            pc & !(WORD_SIZE as u32 - 1)
+        } else {
+            pc
+        }
+    }
+
+    /// Decodes and caches the basic block starting at `pc`, from the words starting at [`Self::block_address`].
+    fn decode_block(&mut self, pc: u32, words: &[u32]) -> Result<BasicBlockEntry> {
+        let block = if self.compressed {
+            decode_until_end_of_a_mixed_block(words, pc % WORD_SIZE as u32 != 0)
+        } else {
+            decode_until_end_of_a_block(words)
+        };
+        if block.is_empty() {
+            return Err(VMError::VMOutOfInstructions);
+        }
+
+        let entry = BasicBlockEntry::new(pc, block);
+        let _ = self.basic_block_cache.insert(pc, entry.clone());
+
+        self.basic_block_ref_cache
+            .insert(entry.start..entry.end, pc);
+
+        Ok(entry)
+    }
+
     /// Adds a new opcode and its corresponding execution function to the emulator.
     fn add_opcode<IE: InstructionExecutor>(&mut self, op: &Opcode) -> Result<()> {
         self.instruction_executor.add_opcode::<IE>(op)
@@ -206,6 +254,11 @@ impl Executor {
         self.private_input_tape = VecDeque::<u8>::from(private_input.to_vec());
     }
 
+    /// Set or overwrite auxiliary input into the auxiliary input tape
+    fn set_auxiliary_input(&mut self, auxiliary_input: &[u8]) {
+        self.auxiliary_input_tape = VecDeque::<u8>::from(auxiliary_input.to_vec());
+    }
+
     /// Set whether to capture logs or print out.
     pub(crate) fn capture_logs(&mut self, capture: bool) {
         if capture && self.logs.is_none() {
@@ -216,6 +269,87 @@ impl Executor {
             self.logs = None;
         }
     }
+
+    /// Captures the execution state, along with the state of the memories of the emulator.
+    fn snapshot(
+        &self,
+        kind: EmulatorKind,
+        memory: UnifiedMemorySnapshot,
+        harvard: Option<HarvardState>,
+    ) -> Snapshot {
+        let mut access_timestamps: Vec<(u32, u64)> = self
+            .access_timestamps
+            .iter()
+            .map(|(&address, &timestamp)| (address, timestamp as u64))
+            .collect();
+        access_timestamps.sort_unstable();
+
+        let mut cycle_tracker: Vec<(String, u64, u64)> = self
+            .cycle_tracker
+            .iter()
+            .map(|(name, &(cycles, occurrences))| (name.clone(), cycles as u64, occurrences as u64))
+            .collect();
+        cycle_tracker.sort_unstable();
+
+        Snapshot {
+            kind,
+            base_address: self.base_address,
+            entrypoint: self.entrypoint,
+            registers: self.cpu.registers,
+            pc: self.cpu.pc.value,
+            cycles: self.cpu.cycles,
+            global_clock: self.global_clock as u64,
+            private_input_tape: self.private_input_tape.iter().copied().collect(),
+            auxiliary_input_tape: self.auxiliary_input_tape.iter().copied().collect(),
+            access_timestamps,
+            cycle_tracker,
+            logs: self.logs.clone(),
+            memory,
+            harvard,
+        }
+    }
+
+    /// Checks that a snapshot was taken from the same kind of emulator, running the same program.
+    fn check_snapshot(&self, snapshot: &Snapshot, kind: EmulatorKind) -> Result<()> {
+        if snapshot.kind != kind {
+            return Err(VMError::InvalidSnapshot(
+                "taken from another kind of emulator",
+            ));
+        }
+        if snapshot.base_address != self.base_address || snapshot.entrypoint != self.entrypoint {
+            return Err(VMError::InvalidSnapshot("taken from another program"));
+        }
+
+        Ok(())
+    }
+
+    /// Restores the execution state, the memories of the emulator are restored separately.
+    fn restore(&mut self, snapshot: &Snapshot) -> Result<()> {
+        self.cpu.registers = snapshot.registers;
+        self.cpu.pc.value = snapshot.pc;
+        self.cpu.cycles = snapshot.cycles;
+        self.global_clock = snapshot.global_clock.try_into()?;
+        self.private_input_tape = snapshot.private_input_tape.iter().copied().collect();
+        self.auxiliary_input_tape = snapshot.auxiliary_input_tape.iter().copied().collect();
+        self.access_timestamps = snapshot
+            .access_timestamps
+            .iter()
+            .map(|&(address, timestamp)| Ok((address, timestamp.try_into()?)))
+            .collect::<Result<_>>()?;
+        self.cycle_tracker = snapshot
+            .cycle_tracker
+            .iter()
+            .map(|(name, cycles, occurrences)| {
+                Ok((
+                    name.clone(),
+                    ((*cycles).try_into()?, (*occurrences).try_into()?),
+                ))
+            })
+            .collect::<Result<_>>()?;
+        self.logs = snapshot.logs.clone();
+
+        Ok(())
+    }
 }
 
 pub trait Emulator {
@@ -296,11 +430,10 @@ pub trait Emulator {
         let mut results: Vec<InstructionResult> = Vec::new();
         let mut transcript: MemoryTranscript = Vec::new();
 
-        let at = (self.get_executor().cpu.pc.value as usize - basic_block_entry.start as usize)
-            / WORD_SIZE;
+        let pc = self.get_executor().cpu.pc.value;
 
         // Execute the instructions in the basic block
-        for instruction in basic_block_entry.block.0[at..].iter() {
+        for instruction in basic_block_entry.instructions_from(pc) {
             let (res, mem) = self.execute_instruction(instruction, force_provable_transcript)?;
             results.push(res);
             transcript.push(mem);
@@ -337,6 +470,11 @@ pub trait Emulator {
         self.get_executor_mut().set_private_input(private_input)
     }
 
+    /// Set or overwrite auxiliary input into the auxiliary input tape
+    fn set_auxiliary_input(&mut self, auxiliary_input: &[u8]) {
+        self.get_executor_mut().set_auxiliary_input(auxiliary_input)
+    }
+
     /// Update and return previous timestamps, but it currently works word-wise, so not used.
     #[allow(dead_code)]
     fn manage_timestamps(&mut self, size: &MemAccessSize, address: &u32) -> usize {
@@ -470,6 +608,19 @@ impl HarvardEmulator {
             .add_fixed_ro(FixedMemory::<RO>::from_word_slice(0x80, 8, &[0, 0]))
             .unwrap();
 
+        let instruction_memory = FixedMemory::<RO>::from_word_vec(
+            elf.base,
+            elf.instructions.len() * WORD_SIZE,
+            elf.instructions.clone(),
+        );
+
+        // The program is readable as data, e.g. for jump tables and constants embedded in the code.
+        if !elf.instructions.is_empty() {
+            data_memory
+                .add_fixed_ro(instruction_memory.clone())
+                .unwrap();
+        }
+
         // Add the public input length to the beginning of the public input.
         let len_bytes = (public_input.len()) as u32;
         let public_input_with_len = [&len_bytes.to_le_bytes()[..], public_input].concat();
@@ -479,14 +630,11 @@ impl HarvardEmulator {
                 private_input_tape: VecDeque::<u8>::from(private_input.to_vec()),
                 base_address: elf.base,
                 entrypoint: elf.entry,
+                compressed: elf.compressed,
                 global_clock: 1, // global_clock = 0 captures initalization for memory records
                 ..Default::default()
             },
-            instruction_memory: FixedMemory::<RO>::from_word_vec(
-                elf.base,
-                elf.instructions.len() * WORD_SIZE,
-                elf.instructions.clone(),
-            ),
+            instruction_memory,
             input_memory: FixedMemory::<RO>::from_byte_slice(0, &public_input_with_len),
             output_memory: VariableMemory::<WO>::default(),
             initial_rom_image: elf.rom_image.clone(),
@@ -508,6 +656,18 @@ impl HarvardEmulator {
             encoded_basic_blocks.extend(block.encode());
         }
 
+        let instruction_memory = FixedMemory::<RO>::from_word_vec(
+            ELF_TEXT_START,
+            encoded_basic_blocks.len() * WORD_SIZE,
+            encoded_basic_blocks,
+        );
+        let mut data_memory = UnifiedMemory::from(VariableMemory::<RW>::default());
+        if instruction_memory.max_len > 0 {
+            data_memory
+                .add_fixed_ro(instruction_memory.clone())
+                .unwrap();
+        }
+
         let mut emulator = Self {
             executor: Executor {
                 base_address: ELF_TEXT_START,
@@ -515,17 +675,46 @@ impl HarvardEmulator {
                 global_clock: 1, // global_clock = 0 captures initalization for memory records
                 ..Default::default()
             },
-            instruction_memory: FixedMemory::<RO>::from_word_vec(
-                ELF_TEXT_START,
-                encoded_basic_blocks.len() * WORD_SIZE,
-                encoded_basic_blocks,
-            ),
-            data_memory: UnifiedMemory::from(VariableMemory::<RW>::default()),
+            instruction_memory,
+            data_memory,
             ..Default::default()
         };
         emulator.executor.cpu.pc.value = emulator.executor.entrypoint;
         emulator
     }
+
+    /// Captures the current state of the emulator.
+    pub fn snapshot(&self) -> Snapshot {
+        let (max_heap_access, min_stack_access) = self.memory_stats.accesses();
+        let harvard = HarvardState {
+            output_memory: self.output_memory.word_runs(),
+            max_heap_access,
+            min_stack_access,
+        };
+
+        self.executor.snapshot(
+            EmulatorKind::Harvard,
+            self.data_memory.snapshot(),
+            Some(harvard),
+        )
+    }
+
+    /// Resumes from a snapshot taken from an emulator built from the same ELF file and inputs.
+    pub fn restore(&mut self, snapshot: &Snapshot) -> Result<()> {
+        self.executor
+            .check_snapshot(snapshot, EmulatorKind::Harvard)?;
+        let Some(harvard) = &snapshot.harvard else {
+            return Err(VMError::InvalidSnapshot("missing Harvard emulator state"));
+        };
+
+        let output_memory = VariableMemory::from_word_runs(&harvard.output_memory)?;
+        self.data_memory.restore(&snapshot.memory)?;
+        self.output_memory = output_memory;
+        self.memory_stats
+            .set_accesses(harvard.max_heap_access, harvard.min_stack_access);
+
+        self.executor.restore(snapshot)
+    }
 }
 
 impl Emulator for HarvardEmulator {
@@ -601,7 +790,7 @@ impl Emulator for HarvardEmulator {
             .update_stack_access(self.executor.cpu.registers.read(Register::X2));
 
         if !bare_instruction.is_branch_or_jump_instruction() {
-            self.executor.cpu.pc.step();
+            self.executor.cpu.pc.step_by(bare_instruction.size());
         }
 
         // The global clock will update according to the currency of ZK (constraint?)
@@ -623,23 +812,14 @@ impl Emulator for HarvardEmulator {
     /// # Returns
     /// if success, return a `BasicBlockEntry` starting at the current PC.
     fn fetch_block(&mut self, pc: u32) -> Result<BasicBlockEntry> {
-        if let Some(start) = self.executor.basic_block_ref_cache.get(&pc) {
-            return Ok(self.executor.basic_block_cache.get(start).unwrap().clone());
-        }
-
-        let block = decode_until_end_of_a_block(self.instruction_memory.segment_words(pc, None));
-        if block.is_empty() {
-            return Err(VMError::VMOutOfInstructions);
+        if let Some(entry) = self.executor.cached_block(pc) {
+            return Ok(entry);
         }
 
-        let entry = BasicBlockEntry::new(pc, block);
-        let _ = self.executor.basic_block_cache.insert(pc, entry.clone());
-
-        self.executor
-            .basic_block_ref_cache
-            .insert(entry.start..entry.end, pc);
-
-        Ok(entry)
+        let words = self
+            .instruction_memory
+            .segment_words(self.executor.block_address(pc), None);
+        self.executor.decode_block(pc, words)
     }
 
     fn get_executor(&self) -> &Executor {
@@ -684,6 +864,7 @@ impl Emulator for HarvardEmulator {
             .map(|(i, byte)| MemoryInitializationEntry {
                 address: self.input_memory.base_address + i as u32,
                 value: *byte,
+                read_only: true,
             })
             .collect();
         let initial_rom_iter = self
@@ -694,6 +875,7 @@ impl Emulator for HarvardEmulator {
             .map(|(i, &byte)| MemoryInitializationEntry {
                 address: self.initial_rom_image.base() + i as u32,
                 value: byte,
+                read_only: true,
             });
         let initial_ram_iter = self
             .initial_ram_image
@@ -703,6 +885,7 @@ impl Emulator for HarvardEmulator {
             .map(|(i, &byte)| MemoryInitializationEntry {
                 address: self.initial_ram_image.base() + i as u32,
                 value: byte,
+                read_only: false,
             });
 
         let debug_logs: Vec<Vec<u8>> = if self.get_executor().logs.is_some() {
@@ -729,16 +912,12 @@ impl Emulator for HarvardEmulator {
             debug_logs,
             program_memory: ProgramInfo {
                 initial_pc: self.executor.entrypoint,
-                program: self
-                    .instruction_memory
-                    .segment_words(self.executor.base_address, None)
-                    .iter()
-                    .enumerate()
-                    .map(|(pc_offset, instruction)| ProgramMemoryEntry {
-                        pc: self.executor.base_address + (pc_offset * WORD_SIZE) as u32,
-                        instruction_word: *instruction,
-                    })
-                    .collect(),
+                program: program_memory_entries(
+                    self.executor.base_address,
+                    self.instruction_memory
+                        .segment_words(self.executor.base_address, None),
+                    self.executor.compressed,
+                ),
             },
             initial_memory,
             tracked_ram_size,
@@ -797,13 +976,11 @@ impl LinearEmulator {
         let output_memory_byte_len = emulator_harvard.output_memory.bytes_spanned();
 
         // Replace custom instructions `rin` and `wou` with `lw` and `sw`.
-        let instructions = compiled_elf
-            .instructions
-            .iter()
-            .map(|instr| {
-                super::convert_instruction(&emulator_harvard.executor.instruction_executor, instr)
-            })
-            .collect();
+        let instructions = super::convert_instructions(
+            &emulator_harvard.executor.instruction_executor,
+            &compiled_elf.instructions,
+            compiled_elf.compressed,
+        );
 
         let elf = ElfFile {
             instructions,
@@ -978,6 +1155,7 @@ impl LinearEmulator {
                 private_input_tape: VecDeque::<u8>::from(private_input.to_vec()),
                 base_address: code_start,
                 entrypoint: code_start + (elf.entry - elf.base),
+                compressed: elf.compressed,
                 global_clock: 1, // global_clock = 0 captures initalization for memory records
                 ..Default::default()
             },
@@ -994,6 +1172,21 @@ impl LinearEmulator {
         emulator.executor.cpu.pc.value = emulator.executor.entrypoint;
         emulator
     }
+
+    /// Captures the current state of the emulator.
+    pub fn snapshot(&self) -> Snapshot {
+        self.executor
+            .snapshot(EmulatorKind::Linear, self.memory.snapshot(), None)
+    }
+
+    /// Resumes from a snapshot taken from an emulator built from the same ELF file, memory layout and inputs.
+    pub fn restore(&mut self, snapshot: &Snapshot) -> Result<()> {
+        self.executor
+            .check_snapshot(snapshot, EmulatorKind::Linear)?;
+        self.memory.restore(&snapshot.memory)?;
+
+        self.executor.restore(snapshot)
+    }
 }
 
 impl Emulator for LinearEmulator {
@@ -1058,7 +1251,7 @@ impl Emulator for LinearEmulator {
         });
 
         if !bare_instruction.is_branch_or_jump_instruction() {
-            self.executor.cpu.pc.step();
+            self.executor.cpu.pc.step_by(bare_instruction.size());
         }
 
         // The global clock will update according to the currency of ZK (constraint?)
@@ -1080,27 +1273,16 @@ impl Emulator for LinearEmulator {
     /// # Returns
     /// if success, return a `BasicBlockEntry` starting at the current PC.
     fn fetch_block(&mut self, pc: u32) -> Result<BasicBlockEntry> {
-        if let Some(start) = self.executor.basic_block_ref_cache.get(&pc) {
-            return Ok(self.executor.basic_block_cache.get(start).unwrap().clone());
+        if let Some(entry) = self.executor.cached_block(pc) {
+            return Ok(entry);
         }
 
-        let block = decode_until_end_of_a_block(self.memory.segment_words(
+        let words = self.memory.segment_words(
             self.instruction_index,
-            pc,
+            self.executor.block_address(pc),
             None,
-        )?);
-        if block.is_empty() {
-            return Err(VMError::VMOutOfInstructions);
-        }
-
-        let entry = BasicBlockEntry::new(pc, block);
-        let _ = self.executor.basic_block_cache.insert(pc, entry.clone());
-
-        self.executor
-            .basic_block_ref_cache
-            .insert(entry.start..entry.end, pc);
-
-        Ok(entry)
+        )?;
+        self.executor.decode_block(pc, words)
     }
 
     fn get_executor(&self) -> &Executor {
@@ -1165,6 +1347,7 @@ impl Emulator for LinearEmulator {
                     .map(move |(j, byte)| MemoryInitializationEntry {
                         address: base_address + j as u32,
                         value: byte,
+                        read_only: true,
                     })
             });
 
@@ -1182,6 +1365,7 @@ impl Emulator for LinearEmulator {
                     .map(move |(j, byte)| MemoryInitializationEntry {
                         address: base_address + j as u32,
                         value: byte,
+                        read_only: true,
                     })
             });
         // TODO: avoid creating a BtreeMap and produce an iterator directly
@@ -1197,6 +1381,7 @@ impl Emulator for LinearEmulator {
             .map(|(addr, byte)| MemoryInitializationEntry {
                 address: *addr,
                 value: *byte,
+                read_only: true,
             });
 
         let ram_initialization = &self.initial_static_ram_image;
@@ -1208,6 +1393,7 @@ impl Emulator for LinearEmulator {
                 .map(|(offset, byte)| MemoryInitializationEntry {
                     address: offset as u32 + self.initial_static_ram_image.base(),
                     value: *byte,
+                    read_only: false,
                 });
 
         let debug_logs: Vec<Vec<u8>> = if self.get_executor().logs.is_some() {
@@ -1244,21 +1430,17 @@ impl Emulator for LinearEmulator {
                 // todo: this likely isn't robust, we need to rely on elf.entry,
                 //       but it seems to be working with the current runtime
                 initial_pc: self.memory_layout.program_start(),
-                program: self
-                    .memory
-                    .segment_words(
-                        self.instruction_index,
-                        self.memory_layout.program_start(),
-                        None,
-                    )
-                    .expect("Cannot find program memory in LinearEmulator")
-                    .iter()
-                    .enumerate()
-                    .map(|(pc_offset, instruction)| ProgramMemoryEntry {
-                        pc: self.memory_layout.program_start() + (pc_offset * WORD_SIZE) as u32,
-                        instruction_word: *instruction,
-                    })
-                    .collect(),
+                program: program_memory_entries(
+                    self.memory_layout.program_start(),
+                    self.memory
+                        .segment_words(
+                            self.instruction_index,
+                            self.memory_layout.program_start(),
+                            None,
+                        )
+                        .expect("Cannot find program memory in LinearEmulator"),
+                    self.executor.compressed,
+                ),
             },
             initial_memory,
             tracked_ram_size,
@@ -1273,7 +1455,8 @@ impl Emulator for LinearEmulator {
 mod tests {
     use super::*;
     use crate::elf::ElfFile;
-    use crate::riscv::{BuiltinOpcode, Instruction, Opcode};
+    use crate::riscv::{BuiltinOpcode, Instruction, Opcode, Register};
+    use crate::SyscallCode;
     use serial_test::serial;
 
     fn setup_basic_block_ir() -> Vec<BasicBlock> {
@@ -1406,6 +1589,114 @@ mod tests {
         assert_eq!(emulator.executor.private_input_tape, private_input_vec);
     }
 
+    #[test]
+    fn test_auxiliary_input() {
+        // a0 = aux byte; a1 = aux byte; a2 = aux byte, past the end of the tape
+        let basic_blocks = vec![BasicBlock::new(vec![
+            Instruction::new_ir(
+                Opcode::from(BuiltinOpcode::ADDI),
+                17,
+                0,
+                SyscallCode::ReadFromAuxiliaryInput as u32,
+            ),
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::ECALL), 0, 0, 0),
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::ADDI), 11, 10, 0),
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::ECALL), 0, 0, 0),
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::ADDI), 12, 10, 0),
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::ECALL), 0, 0, 0),
+        ])];
+
+        let mut emulator = HarvardEmulator::from_basic_blocks(&basic_blocks);
+        emulator.set_private_input(&[7]);
+        emulator.set_auxiliary_input(&[1, 2]);
+        assert_eq!(emulator.execute(false), Err(VMError::VMOutOfInstructions));
+
+        let registers = emulator.executor.cpu.registers;
+        assert_eq!(registers[Register::X11], 1);
+        assert_eq!(registers[Register::X12], 2);
+        assert_eq!(registers[Register::X10], u32::MAX);
+        // The private input tape is left untouched.
+        assert_eq!(emulator.executor.private_input_tape, [7]);
+    }
+
+    #[test]
+    fn test_compressed_instructions() {
+        // c.li a0, 1; addi a1, a0, 2; c.add a0, a1; c.jal f; li a7, 513; ecall;
+        // f: c.addi a0, 3; c.bnez a0, skip; c.li a0, 0; skip: c.jr ra; c.nop
+        let instructions = vec![
+            0x05934505, 0x952e0025, 0x08932029, 0x00732010, 0x050d0000, 0x4501e111, 0x00018082,
+        ];
+        let mut elf = ElfFile::new(
+            instructions,
+            ELF_TEXT_START,
+            ELF_TEXT_START,
+            MemorySegmentImage::default(),
+            MemorySegmentImage::default(),
+            Vec::new(),
+        );
+        elf.compressed = true;
+
+        let mut harvard = HarvardEmulator::from_elf(&elf, &[], &[]);
+        assert_eq!(harvard.execute(false), Err(VMError::VMExited(7)));
+        // `c.jal` links to the instruction right after it.
+        assert_eq!(
+            harvard.executor.cpu.registers[Register::X1],
+            ELF_TEXT_START + 0xa
+        );
+
+        let mut linear =
+            LinearEmulator::from_elf(LinearMemoryLayout::default(), &[], &elf, &[], &[]);
+        let mut pcs = Vec::new();
+        let exit = loop {
+            let pc = linear.executor.cpu.pc.value;
+            pcs.push(pc - linear.memory_layout.program_start());
+            let entry = linear.fetch_block(pc).unwrap();
+            if let Err(e) = linear.execute_instruction(&entry.instructions_from(pc)[0], false) {
+                break e;
+            }
+        };
+        assert_eq!(exit, VMError::VMExited(7));
+        assert_eq!(pcs, [0x0, 0x2, 0x6, 0x8, 0x12, 0x14, 0x18, 0xa, 0xe]);
+
+        // Program memory holds one entry per instruction, of the size of its encoding.
+        let view = linear.finalize();
+        let program = &view.get_program_memory().program;
+        let sizes: Vec<u32> = program.iter().map(ProgramMemoryEntry::size).collect();
+        assert_eq!(sizes, [2, 4, 2, 2, 4, 4, 2, 2, 2, 2, 2]);
+        assert_eq!(program[1].expanded_word(), 0x00250593);
+        assert_eq!(program[3].expanded_word(), 0x00a000ef);
+    }
+
+    #[test]
+    fn test_compressed_instructions_with_custom_opcode_bits() {
+        // c.li s6, 7; c.mv a0, s6; li a7, 513; ecall
+        // The low bits of `c.mv a0, s6` are those of the built-in custom opcode.
+        let instructions = vec![0x855a4b1d, 0x20100893, 0x00000073];
+        let mut elf = ElfFile::new(
+            instructions,
+            ELF_TEXT_START,
+            ELF_TEXT_START,
+            MemorySegmentImage::default(),
+            MemorySegmentImage::default(),
+            Vec::new(),
+        );
+        elf.compressed = true;
+
+        let mut harvard = HarvardEmulator::from_elf(&elf, &[], &[]);
+        assert_eq!(harvard.execute(false), Err(VMError::VMExited(7)));
+
+        let mut linear =
+            LinearEmulator::from_elf(LinearMemoryLayout::default(), &[], &elf, &[], &[]);
+        assert_eq!(linear.execute(false), Err(VMError::VMExited(7)));
+
+        let view = linear.finalize();
+        let program = &view.get_program_memory().program;
+        let sizes: Vec<u32> = program.iter().map(ProgramMemoryEntry::size).collect();
+        assert_eq!(sizes, [2, 2, 4, 4]);
+        assert_eq!(program[1].instruction_word, 0x855a);
+        assert_eq!(program[1].expanded_word(), 0x01600533);
+    }
+
     #[test]
     fn test_unimplemented_instruction() {
         let op = Opcode::new(0, None, None, "unsupported");
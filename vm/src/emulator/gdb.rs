@@ -0,0 +1,538 @@
+//! # GDB Remote Serial Protocol Stub
+//!
+//! This module lets a debugger attach to a running emulator over TCP, so that guest programs can be
+//! inspected with `riscv32-unknown-elf-gdb` (or `gdb-multiarch`) and the symbols of their ELF file.
+//!
+//! The stub is opt-in: nothing listens unless [`GdbServer::listen`] is called on an emulator that has
+//! not started executing yet.
+//!
+//! ## Supported Packets
+//!
+//! - `?`, `g`, `p`: stop reason, all registers (`x0`-`x31` followed by `pc`) and a single register.
+//! - `m`: memory reads through the unified memory of the emulator.
+//! - `Z0`/`z0`: software breakpoints, kept by the stub instead of being patched into the program.
+//! - `s`, `c`: single-step and continue, interrupted by `Ctrl-C` from the debugger.
+//! - `k`, `D`: kill and detach, both of which close the connection.
+//!
+//! Any other packet receives an empty reply, which tells the debugger it is unsupported.
+//!
+//! ## Usage
+//!
+//! ```no_run
+//! use nexus_vm::elf::ElfFile;
+//! use nexus_vm::emulator::{GdbServer, HarvardEmulator};
+//!
+//! let elf_file = ElfFile::from_path("test/fib_10.elf").unwrap();
+//! let mut emulator = HarvardEmulator::from_elf(&elf_file, &[], &[]);
+//!
+//! // Then run `target remote localhost:1234` from gdb.
+//! GdbServer::new(&mut emulator).listen("127.0.0.1:1234").unwrap();
+//! ```
+
+use std::{
+    collections::BTreeSet,
+    io::{self, BufReader, Read, Write},
+    net::{TcpListener, TcpStream, ToSocketAddrs},
+};
+
+use nexus_common::cpu::Registers;
+
+use super::{Emulator, HarvardEmulator, LinearEmulator};
+use crate::{
+    error::{Result, VMError},
+    memory::{LoadOp, MemAccessSize, MemoryProcessor, UnifiedMemory},
+    riscv::Register,
+};
+
+/// Number of general purpose registers reported to the debugger, the program counter follows them.
+const NUM_REGISTERS: usize = 32;
+
+/// Number of instructions executed between checks for an interrupt request while continuing.
+const INTERRUPT_POLL_INTERVAL: usize = 1 << 16;
+
+/// Maximum size of a packet the stub accepts, advertised in reply to `qSupported`.
+const PACKET_SIZE: usize = 0x4000;
+
+/// The byte sent out of band by the debugger to interrupt a running target.
+const INTERRUPT: u8 = 0x03;
+
+/// Signal numbers used in stop replies.
+const SIGINT: u8 = 2;
+const SIGILL: u8 = 4;
+const SIGTRAP: u8 = 5;
+
+/// An emulator that can be attached to a debugger.
+pub trait GdbTarget: Emulator {
+    /// Return the memory that is read on behalf of the debugger.
+    fn memory(&self) -> &UnifiedMemory;
+}
+
+impl GdbTarget for HarvardEmulator {
+    fn memory(&self) -> &UnifiedMemory {
+        &self.data_memory
+    }
+}
+
+impl GdbTarget for LinearEmulator {
+    fn memory(&self) -> &UnifiedMemory {
+        &self.memory
+    }
+}
+
+/// Why the target stopped, reported to the debugger as a stop reply.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StopReason {
+    Signal(u8),
+    Exited(u32),
+}
+
+impl StopReason {
+    fn reply(self) -> String {
+        match self {
+            StopReason::Signal(signal) => format!("S{signal:02x}"),
+            StopReason::Exited(code) => format!("W{:02x}", code as u8),
+        }
+    }
+}
+
+/// A GDB remote serial protocol server driving an emulator.
+pub struct GdbServer<'a, E: GdbTarget> {
+    emulator: &'a mut E,
+    breakpoints: BTreeSet<u32>,
+    last_stop: StopReason,
+}
+
+impl<'a, E: GdbTarget> GdbServer<'a, E> {
+    pub fn new(emulator: &'a mut E) -> Self {
+        Self {
+            emulator,
+            breakpoints: BTreeSet::new(),
+            last_stop: StopReason::Signal(SIGTRAP),
+        }
+    }
+
+    /// Wait for a single debugger connection on `addr` and serve it until it is closed.
+    pub fn listen(&mut self, addr: impl ToSocketAddrs) -> io::Result<()> {
+        let listener = TcpListener::bind(addr)?;
+        tracing::info!("Waiting for GDB connection on {}", listener.local_addr()?);
+
+        let (stream, peer) = listener.accept()?;
+        tracing::info!("GDB connected from {peer}");
+        self.serve(stream)
+    }
+
+    /// Serve a debugger connection until it is closed, or the debugger kills or detaches the target.
+    pub fn serve(&mut self, stream: TcpStream) -> io::Result<()> {
+        stream.set_nodelay(true)?;
+        let mut connection = Connection::new(stream)?;
+
+        while let Some(packet) = connection.read_packet()? {
+            if packet == "k" {
+                return Ok(());
+            }
+            if packet.starts_with('D') {
+                return connection.write_packet("OK");
+            }
+
+            let reply = self.handle_packet(&packet, || connection.interrupted());
+            connection.write_packet(&reply)?;
+        }
+
+        Ok(())
+    }
+
+    /// Handle a packet and return the reply, `interrupted` is polled while the target runs.
+    fn handle_packet(&mut self, packet: &str, interrupted: impl FnMut() -> bool) -> String {
+        let (command, args) = packet.split_at(packet.chars().next().map_or(0, char::len_utf8));
+        match command {
+            "?" => self.last_stop.reply(),
+            "g" => (0..=NUM_REGISTERS).map(|i| self.read_register(i)).collect(),
+            "p" => usize::from_str_radix(args, 16)
+                .ok()
+                .filter(|&i| i <= NUM_REGISTERS)
+                .map_or_else(|| "E01".to_string(), |i| self.read_register(i)),
+            "m" => parse_addr_len(args).map_or_else(
+                || "E01".to_string(),
+                |(addr, len)| self.read_memory(addr, len),
+            ),
+            "Z" | "z" => self.update_breakpoint(command == "Z", args),
+            "s" | "c" => match parse_resume_addr(args) {
+                Some(addr) => {
+                    if let Some(addr) = addr {
+                        self.emulator.get_executor_mut().cpu.pc.value = addr;
+                    }
+                    self.last_stop = if command == "s" {
+                        self.step()
+                    } else {
+                        self.resume(interrupted)
+                    };
+                    self.last_stop.reply()
+                }
+                None => "E01".to_string(),
+            },
+            "H" => "OK".to_string(),
+            "q" if args.starts_with("Supported") => format!("PacketSize={PACKET_SIZE:x}"),
+            "q" if args == "Attached" => "1".to_string(),
+            "q" if args == "fThreadInfo" => "m1".to_string(),
+            "q" if args == "sThreadInfo" => "l".to_string(),
+            "q" if args == "C" => "QC1".to_string(),
+            _ => String::new(),
+        }
+    }
+
+    /// Encode a register as little-endian hex, index `NUM_REGISTERS` is the program counter.
+    fn read_register(&self, index: usize) -> String {
+        let cpu = &self.emulator.get_executor().cpu;
+        let value = if index == NUM_REGISTERS {
+            cpu.pc.value
+        } else {
+            cpu.registers.read(Register::from(index as u8))
+        };
+
+        encode_hex(&value.to_le_bytes())
+    }
+
+    /// Encode memory as hex, stopping at the first byte that can't be read.
+    fn read_memory(&self, addr: u32, len: usize) -> String {
+        let memory = self.emulator.memory();
+        let bytes: Vec<u8> = (0..len as u32)
+            .map_while(|i| {
+                let LoadOp::Op(.., value) = memory
+                    .read(addr.checked_add(i)?, MemAccessSize::Byte)
+                    .ok()?;
+                Some(value as u8)
+            })
+            .collect();
+
+        if bytes.is_empty() && len > 0 {
+            "E14".to_string()
+        } else {
+            encode_hex(&bytes)
+        }
+    }
+
+    /// Insert or remove a software breakpoint, other kinds of breakpoints are unsupported.
+    fn update_breakpoint(&mut self, insert: bool, args: &str) -> String {
+        let Some(args) = args.strip_prefix("0,") else {
+            return String::new();
+        };
+        let Some(addr) = args
+            .split(',')
+            .next()
+            .and_then(|addr| u32::from_str_radix(addr, 16).ok())
+        else {
+            return "E01".to_string();
+        };
+
+        if insert {
+            self.breakpoints.insert(addr);
+        } else {
+            self.breakpoints.remove(&addr);
+        }
+        "OK".to_string()
+    }
+
+    /// Execute the instruction at the current program counter.
+    fn execute_one(&mut self) -> Result<()> {
+        let pc = self.emulator.get_executor().cpu.pc.value;
+        let entry = self.emulator.fetch_block(pc)?;
+        self.emulator
+            .execute_instruction(&entry.instructions_from(pc)[0], false)
+            .map(|_| ())
+    }
+
+    fn step(&mut self) -> StopReason {
+        match self.execute_one() {
+            Ok(()) => StopReason::Signal(SIGTRAP),
+            Err(e) => Self::stop_on_error(e),
+        }
+    }
+
+    /// Run until a breakpoint is reached, the program stops, or the debugger interrupts it.
+    fn resume(&mut self, mut interrupted: impl FnMut() -> bool) -> StopReason {
+        let mut count = 0usize;
+        loop {
+            if let Err(e) = self.execute_one() {
+                return Self::stop_on_error(e);
+            }
+
+            let pc = self.emulator.get_executor().cpu.pc.value;
+            if self.breakpoints.contains(&pc) {
+                return StopReason::Signal(SIGTRAP);
+            }
+
+            count += 1;
+            if count % INTERRUPT_POLL_INTERVAL == 0 && interrupted() {
+                return StopReason::Signal(SIGINT);
+            }
+        }
+    }
+
+    fn stop_on_error(e: VMError) -> StopReason {
+        match e {
+            VMError::VMExited(code) => StopReason::Exited(code),
+            e => {
+                tracing::warn!("Guest stopped with an error: {e}");
+                StopReason::Signal(SIGILL)
+            }
+        }
+    }
+}
+
+/// A connection framing packets as `$<data>#<checksum>`, acknowledging each of them.
+struct Connection {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl Connection {
+    fn new(stream: TcpStream) -> io::Result<Self> {
+        Ok(Self {
+            writer: stream.try_clone()?,
+            reader: BufReader::new(stream),
+        })
+    }
+
+    fn read_byte(&mut self) -> io::Result<Option<u8>> {
+        let mut byte = [0u8];
+        match self.reader.read(&mut byte)? {
+            0 => Ok(None),
+            _ => Ok(Some(byte[0])),
+        }
+    }
+
+    /// Read the next packet, or `None` once the debugger disconnects.
+    fn read_packet(&mut self) -> io::Result<Option<String>> {
+        loop {
+            // Skip acknowledgements and interrupts received while the target was already stopped.
+            match self.read_byte()? {
+                None => return Ok(None),
+                Some(b'$') => {}
+                Some(_) => continue,
+            }
+
+            let mut data = Vec::new();
+            loop {
+                match self.read_byte()? {
+                    None => return Ok(None),
+                    Some(b'#') => break,
+                    Some(byte) => data.push(byte),
+                }
+            }
+            let mut checksum = [0u8; 2];
+            self.reader.read_exact(&mut checksum)?;
+
+            let valid = std::str::from_utf8(&checksum)
+                .ok()
+                .and_then(|checksum| u8::from_str_radix(checksum, 16).ok())
+                == Some(compute_checksum(&data));
+            if !valid {
+                self.writer.write_all(b"-")?;
+                continue;
+            }
+
+            self.writer.write_all(b"+")?;
+            return Ok(Some(String::from_utf8_lossy(&data).into_owned()));
+        }
+    }
+
+    fn write_packet(&mut self, data: &str) -> io::Result<()> {
+        self.writer.write_all(frame_packet(data).as_bytes())?;
+        self.writer.flush()
+    }
+
+    /// Check without blocking whether the debugger has requested an interrupt.
+    fn interrupted(&mut self) -> bool {
+        let next = match self.reader.buffer().first() {
+            Some(&byte) => Some(byte),
+            None => self.peek_byte(),
+        };
+
+        next == Some(INTERRUPT) && matches!(self.read_byte(), Ok(Some(INTERRUPT)))
+    }
+
+    /// Peek at the next byte on the socket if one has already arrived.
+    fn peek_byte(&self) -> Option<u8> {
+        self.writer.set_nonblocking(true).ok()?;
+        let mut byte = [0u8];
+        let peeked = self.writer.peek(&mut byte);
+        self.writer.set_nonblocking(false).ok()?;
+
+        matches!(peeked, Ok(1)).then_some(byte[0])
+    }
+}
+
+fn compute_checksum(data: &[u8]) -> u8 {
+    data.iter().fold(0u8, |acc, &byte| acc.wrapping_add(byte))
+}
+
+fn frame_packet(data: &str) -> String {
+    format!("${data}#{:02x}", compute_checksum(data.as_bytes()))
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Parse the `addr,length` arguments of a memory read.
+fn parse_addr_len(args: &str) -> Option<(u32, usize)> {
+    let (addr, len) = args.split_once(',')?;
+    let len = usize::from_str_radix(len, 16).ok()?;
+    Some((
+        u32::from_str_radix(addr, 16).ok()?,
+        len.min(PACKET_SIZE / 2),
+    ))
+}
+
+/// Parse the optional address a step or continue resumes from.
+fn parse_resume_addr(args: &str) -> Option<Option<u32>> {
+    if args.is_empty() {
+        Some(None)
+    } else {
+        u32::from_str_radix(args, 16).ok().map(Some)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::elf::ElfFile;
+    use crate::riscv::{BasicBlock, BuiltinOpcode, Instruction, Opcode};
+    use nexus_common::constants::{ELF_TEXT_START, WORD_SIZE};
+    use serial_test::serial;
+
+    fn setup_emulator() -> HarvardEmulator {
+        let basic_block = BasicBlock::new(vec![
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::ADDI), 1, 0, 1),
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::ADDI), 2, 1, 2),
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::ADDI), 3, 2, 3),
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::ADDI), 4, 3, 4),
+        ]);
+        HarvardEmulator::from_basic_blocks(&vec![basic_block])
+    }
+
+    fn register_reply(emulator: &mut HarvardEmulator, index: usize) -> String {
+        GdbServer::new(emulator).handle_packet(&format!("p{index:x}"), || false)
+    }
+
+    #[test]
+    fn test_frame_packet() {
+        assert_eq!(frame_packet("OK"), "$OK#9a");
+        assert_eq!(frame_packet(""), "$#00");
+        assert_eq!(encode_hex(&[0x00, 0xab, 0x10]), "00ab10");
+    }
+
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(parse_addr_len("1000,4"), Some((0x1000, 4)));
+        assert_eq!(parse_addr_len("1000"), None);
+        assert_eq!(parse_resume_addr(""), Some(None));
+        assert_eq!(parse_resume_addr("88"), Some(Some(0x88)));
+        assert_eq!(parse_resume_addr("x"), None);
+    }
+
+    #[test]
+    fn test_read_registers_and_memory() {
+        let mut emulator = setup_emulator();
+        emulator
+            .executor
+            .cpu
+            .registers
+            .write(Register::X5, 0x12345678);
+
+        let mut server = GdbServer::new(&mut emulator);
+        let registers = server.handle_packet("g", || false);
+        assert_eq!(registers.len(), (NUM_REGISTERS + 1) * 8);
+        assert_eq!(&registers[5 * 8..6 * 8], "78563412");
+        assert_eq!(
+            &registers[NUM_REGISTERS * 8..],
+            encode_hex(&ELF_TEXT_START.to_le_bytes())
+        );
+        assert_eq!(server.handle_packet("p21", || false), "E01");
+
+        // The program is readable as data.
+        let encoded = BasicBlock::new(vec![Instruction::new_ir(
+            Opcode::from(BuiltinOpcode::ADDI),
+            1,
+            0,
+            1,
+        )])
+        .encode();
+        assert_eq!(
+            server.handle_packet(&format!("m{ELF_TEXT_START:x},4"), || false),
+            encode_hex(&encoded[0].to_le_bytes())
+        );
+    }
+
+    #[test]
+    fn test_step_and_breakpoints() {
+        let mut emulator = setup_emulator();
+        let mut server = GdbServer::new(&mut emulator);
+
+        assert_eq!(server.handle_packet("s", || false), "S05");
+        let breakpoint = ELF_TEXT_START + 3 * WORD_SIZE as u32;
+        assert_eq!(
+            server.handle_packet(&format!("Z0,{breakpoint:x},4"), || false),
+            "OK"
+        );
+        assert_eq!(server.handle_packet("c", || false), "S05");
+        assert_eq!(server.handle_packet("?", || false), "S05");
+
+        assert_eq!(register_reply(&mut emulator, 3), "06000000");
+        assert_eq!(register_reply(&mut emulator, 4), "00000000");
+        assert_eq!(
+            register_reply(&mut emulator, NUM_REGISTERS),
+            encode_hex(&breakpoint.to_le_bytes())
+        );
+
+        // Running past the end of the program is reported as an error.
+        let mut server = GdbServer::new(&mut emulator);
+        assert_eq!(
+            server.handle_packet(&format!("z0,{breakpoint:x},4"), || false),
+            "OK"
+        );
+        assert_eq!(server.handle_packet("c", || false), "S04");
+        assert_eq!(server.handle_packet("Z1,0,4", || false), "");
+    }
+
+    #[test]
+    #[serial]
+    fn test_continue_until_exit() {
+        let elf_file = ElfFile::from_path("test/fib_10.elf").expect("Unable to load ELF file");
+        let mut emulator = HarvardEmulator::from_elf(&elf_file, &[], &[]);
+
+        let mut server = GdbServer::new(&mut emulator);
+        assert_eq!(server.handle_packet("c", || false), "W00");
+    }
+
+    #[test]
+    fn test_serve_connection() {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("failed to bind");
+        let addr = listener.local_addr().unwrap();
+
+        let client = std::thread::spawn(move || {
+            let mut stream = TcpStream::connect(addr).expect("failed to connect");
+            let mut reply = |packet: &str| {
+                stream.write_all(frame_packet(packet).as_bytes()).unwrap();
+
+                // An acknowledgement, then the framed reply.
+                let mut received = Vec::new();
+                let mut byte = [0u8];
+                while received.len() < 3 || received[received.len() - 3] != b'#' {
+                    stream.read_exact(&mut byte).unwrap();
+                    received.push(byte[0]);
+                }
+                String::from_utf8(received).unwrap()
+            };
+
+            assert_eq!(reply("s"), format!("+{}", frame_packet("S05")));
+            assert_eq!(reply("p1"), format!("+{}", frame_packet("01000000")));
+            reply("D");
+        });
+
+        let mut emulator = setup_emulator();
+        let (stream, _) = listener.accept().unwrap();
+        GdbServer::new(&mut emulator).serve(stream).unwrap();
+        client.join().unwrap();
+    }
+}
@@ -71,6 +71,17 @@ impl MemoryStats {
         }
     }
 
+    /// Returns the highest heap address and the lowest stack address accessed so far.
+    pub(crate) fn accesses(&self) -> (u32, u32) {
+        (self.max_heap_access, self.min_stack_access)
+    }
+
+    /// Overwrites the highest heap address and the lowest stack address accessed so far.
+    pub(crate) fn set_accesses(&mut self, max_heap_access: u32, min_stack_access: u32) {
+        self.max_heap_access = max_heap_access;
+        self.min_stack_access = min_stack_access;
+    }
+
     /// Create an optimized linear memory layout based on the memory stats.
     ///
     /// Note: `input_size` is the size of the public input, and `output_size` is the size of the
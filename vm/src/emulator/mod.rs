@@ -9,6 +9,8 @@
 //! - `HarvardEmulator`: An implementation of the emulator using Harvard architecture.
 //! - `LinearEmulator`: An implementation of the emulator using Linear architecture.
 //! - `LinearMemoryLayout`: Defines the memory layout for the linear emulator.
+//! - `GdbServer`: An opt-in GDB remote serial protocol stub for debugging guest programs.
+//! - `Snapshot`: The state of an emulator in the middle of a run, to be saved and restored later.
 //!
 //! ## Memory Management
 //!
@@ -53,12 +55,16 @@
 //! with a single memory space, with added read and write protection), and offering detailed
 //! visibility into the emulator's state and execution results.
 mod executor;
+mod gdb;
 mod layout;
 pub(crate) mod memory_stats;
 mod registry;
+mod snapshot;
 
 pub use executor::{Emulator, Executor, HarvardEmulator, LinearEmulator};
+pub use gdb::{GdbServer, GdbTarget};
 pub use layout::LinearMemoryLayout;
+pub use snapshot::{EmulatorKind, Snapshot};
 
 mod utils;
 pub use utils::*;
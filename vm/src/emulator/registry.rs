@@ -53,7 +53,14 @@
 //! This registry is crucial for the emulator's operation, providing a flexible and
 //! efficient way to map opcodes to their execution functions, including support for
 //! custom and special instructions.
-use nexus_common::{constants::KECCAKF_OPCODE, cpu::InstructionExecutor, error::MemoryError};
+use nexus_common::{
+    constants::{
+        KECCAKF_FN3, KECCAKF_OPCODE, MEMCPY_FN3, MEMSET_FN3, MUL_ADD_MOD_FN3, POSEIDON2_FN3,
+        SHA256_COMPRESS_FN3,
+    },
+    cpu::InstructionExecutor,
+    error::MemoryError,
+};
 
 use crate::memory::MemoryProcessor;
 use crate::{
@@ -80,6 +87,11 @@ pub struct InstructionExecutorRegistry {
     read_input: Opcode,
     write_output: Opcode,
     keccakf: Opcode,
+    sha256_compress: Opcode,
+    mul_add_mod: Opcode,
+    poseidon2: Opcode,
+    memcpy: Opcode,
+    memset: Opcode,
 }
 
 impl Default for InstructionExecutorRegistry {
@@ -230,7 +242,17 @@ impl Default for InstructionExecutorRegistry {
             precompiles: HashMap::<Opcode, InstructionExecutorFn<UnifiedMemory>>::new(),
             read_input: Opcode::new(0b0101011, Some(0b000), None, "rin"),
             write_output: Opcode::new(0b1011011, Some(0b000), None, "wou"),
-            keccakf: Opcode::new(KECCAKF_OPCODE, Some(0b000), None, "keccakf"),
+            keccakf: Opcode::new(KECCAKF_OPCODE, Some(KECCAKF_FN3), None, "keccakf"),
+            sha256_compress: Opcode::new(
+                KECCAKF_OPCODE,
+                Some(SHA256_COMPRESS_FN3),
+                None,
+                "sha256_compress",
+            ),
+            mul_add_mod: Opcode::new(KECCAKF_OPCODE, Some(MUL_ADD_MOD_FN3), None, "mul_add_mod"),
+            poseidon2: Opcode::new(KECCAKF_OPCODE, Some(POSEIDON2_FN3), None, "poseidon2"),
+            memcpy: Opcode::new(KECCAKF_OPCODE, Some(MEMCPY_FN3), None, "memcpy"),
+            memset: Opcode::new(KECCAKF_OPCODE, Some(MEMSET_FN3), None, "memset"),
         }
     }
 }
@@ -295,6 +317,26 @@ impl InstructionExecutorRegistry {
                 instructions::custom::keccakf::KeccakFInstruction::evaluator
                     as InstructionExecutorFn<M>
             }
+            op if self.is_sha256_compress(op) => {
+                instructions::custom::sha256::Sha256CompressInstruction::evaluator
+                    as InstructionExecutorFn<M>
+            }
+            op if self.is_mul_add_mod(op) => {
+                instructions::custom::bigint::MulAddModInstruction::evaluator
+                    as InstructionExecutorFn<M>
+            }
+            op if self.is_poseidon2(op) => {
+                instructions::custom::poseidon2::Poseidon2Instruction::evaluator
+                    as InstructionExecutorFn<M>
+            }
+            op if self.is_memcpy(op) => {
+                instructions::custom::memcpy::MemcpyInstruction::evaluator
+                    as InstructionExecutorFn<M>
+            }
+            op if self.is_memset(op) => {
+                instructions::custom::memcpy::MemsetInstruction::evaluator
+                    as InstructionExecutorFn<M>
+            }
             _ => return None,
         })
     }
@@ -313,4 +355,29 @@ impl InstructionExecutorRegistry {
     pub fn is_keccakf(&self, op: &Opcode) -> bool {
         op.raw() == self.keccakf.raw() && op.fn3() == self.keccakf.fn3()
     }
+
+    #[inline(always)]
+    pub fn is_sha256_compress(&self, op: &Opcode) -> bool {
+        op.raw() == self.sha256_compress.raw() && op.fn3() == self.sha256_compress.fn3()
+    }
+
+    #[inline(always)]
+    pub fn is_mul_add_mod(&self, op: &Opcode) -> bool {
+        op.raw() == self.mul_add_mod.raw() && op.fn3() == self.mul_add_mod.fn3()
+    }
+
+    #[inline(always)]
+    pub fn is_poseidon2(&self, op: &Opcode) -> bool {
+        op.raw() == self.poseidon2.raw() && op.fn3() == self.poseidon2.fn3()
+    }
+
+    #[inline(always)]
+    pub fn is_memcpy(&self, op: &Opcode) -> bool {
+        op.raw() == self.memcpy.raw() && op.fn3() == self.memcpy.fn3()
+    }
+
+    #[inline(always)]
+    pub fn is_memset(&self, op: &Opcode) -> bool {
+        op.raw() == self.memset.raw() && op.fn3() == self.memset.fn3()
+    }
 }
@@ -0,0 +1,307 @@
+//! Snapshots of the state of an emulator in the middle of a run.
+//!
+//! A [`Snapshot`] holds everything that changes while a program executes: the CPU registers and program counter,
+//! the global clock, the remaining private and auxiliary inputs, the last access timestamp of every address,
+//! profiling counters, captured logs and the writable memory regions. Read-only data such as the program itself is
+//! not included, so a snapshot can only be restored into an emulator of the same kind, built from the same ELF file
+//! and inputs.
+//!
+//! Snapshots are stored as an 8-byte magic string, a little-endian `u32` format version and the `postcard` encoding
+//! of the snapshot. Snapshots of an earlier version are still read, fields added since are left empty.
+//!
+//! ```rust
+//! use nexus_vm::elf::ElfFile;
+//! use nexus_vm::emulator::{Emulator, HarvardEmulator, Snapshot};
+//! use nexus_vm::error::VMError;
+//!
+//! let elf_file = ElfFile::from_path("test/fib_10.elf").unwrap();
+//! let emulator = HarvardEmulator::from_elf(&elf_file, &[], &[]);
+//!
+//! let mut bytes = Vec::new();
+//! emulator.snapshot().write_to(&mut bytes).unwrap();
+//!
+//! let mut restored = HarvardEmulator::from_elf(&elf_file, &[], &[]);
+//! restored.restore(&Snapshot::read_from(bytes.as_slice()).unwrap()).unwrap();
+//! assert_eq!(restored.execute(false), Err(VMError::VMExited(0)));
+//! ```
+
+use std::io::{self, Read, Write};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{cpu::RegisterFile, memory::UnifiedMemorySnapshot};
+
+/// Magic string at the start of every snapshot.
+const MAGIC: [u8; 8] = *b"NXSNAP\0\0";
+
+/// Version of the snapshot format, to be increased on any change to [`Snapshot`].
+const VERSION: u32 = 2;
+
+/// Version of the format before the auxiliary input tape was added.
+const VERSION_1: u32 = 1;
+
+/// The emulator a snapshot was taken from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EmulatorKind {
+    Harvard,
+    Linear,
+}
+
+/// State only found in the Harvard emulator.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct HarvardState {
+    /// Runs of words written to the output memory.
+    pub(crate) output_memory: Vec<(u32, Vec<u32>)>,
+    pub(crate) max_heap_access: u32,
+    pub(crate) min_stack_access: u32,
+}
+
+/// The mutable state of an emulator.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub(crate) kind: EmulatorKind,
+    /// Base address of the program, used to check the snapshot matches the emulator it is restored into.
+    pub(crate) base_address: u32,
+    /// Entrypoint of the program, used to check the snapshot matches the emulator it is restored into.
+    pub(crate) entrypoint: u32,
+    pub(crate) registers: RegisterFile,
+    pub(crate) pc: u32,
+    pub(crate) cycles: u64,
+    pub(crate) global_clock: u64,
+    pub(crate) private_input_tape: Vec<u8>,
+    pub(crate) auxiliary_input_tape: Vec<u8>,
+    /// Last access timestamps, sorted by address.
+    pub(crate) access_timestamps: Vec<(u32, u64)>,
+    /// Cycle tracker entries as (name, cycle count, occurrences), sorted by name.
+    pub(crate) cycle_tracker: Vec<(String, u64, u64)>,
+    pub(crate) logs: Option<Vec<Vec<u8>>>,
+    pub(crate) memory: UnifiedMemorySnapshot,
+    pub(crate) harvard: Option<HarvardState>,
+}
+
+/// Snapshot of version 1, without the auxiliary input tape.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct SnapshotV1 {
+    kind: EmulatorKind,
+    base_address: u32,
+    entrypoint: u32,
+    registers: RegisterFile,
+    pc: u32,
+    cycles: u64,
+    global_clock: u64,
+    private_input_tape: Vec<u8>,
+    access_timestamps: Vec<(u32, u64)>,
+    cycle_tracker: Vec<(String, u64, u64)>,
+    logs: Option<Vec<Vec<u8>>>,
+    memory: UnifiedMemorySnapshot,
+    harvard: Option<HarvardState>,
+}
+
+impl From<SnapshotV1> for Snapshot {
+    fn from(snapshot: SnapshotV1) -> Self {
+        Self {
+            kind: snapshot.kind,
+            base_address: snapshot.base_address,
+            entrypoint: snapshot.entrypoint,
+            registers: snapshot.registers,
+            pc: snapshot.pc,
+            cycles: snapshot.cycles,
+            global_clock: snapshot.global_clock,
+            private_input_tape: snapshot.private_input_tape,
+            auxiliary_input_tape: Vec::new(),
+            access_timestamps: snapshot.access_timestamps,
+            cycle_tracker: snapshot.cycle_tracker,
+            logs: snapshot.logs,
+            memory: snapshot.memory,
+            harvard: snapshot.harvard,
+        }
+    }
+}
+
+impl Snapshot {
+    pub fn kind(&self) -> EmulatorKind {
+        self.kind
+    }
+
+    pub fn pc(&self) -> u32 {
+        self.pc
+    }
+
+    pub fn global_clock(&self) -> u64 {
+        self.global_clock
+    }
+
+    /// Writes the snapshot in the versioned binary format.
+    pub fn write_to(&self, mut writer: impl Write) -> io::Result<()> {
+        let bytes = postcard::to_stdvec(self).map_err(io::Error::other)?;
+        writer.write_all(&MAGIC)?;
+        writer.write_all(&VERSION.to_le_bytes())?;
+        writer.write_all(&bytes)?;
+        writer.flush()
+    }
+
+    /// Reads a snapshot written by [`Snapshot::write_to`].
+    pub fn read_from(mut reader: impl Read) -> io::Result<Self> {
+        let mut magic = [0u8; MAGIC.len()];
+        reader.read_exact(&mut magic)?;
+        if magic != MAGIC {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not an emulator snapshot",
+            ));
+        }
+
+        let mut version = [0u8; 4];
+        reader.read_exact(&mut version)?;
+        let version = u32::from_le_bytes(version);
+        if version != VERSION && version != VERSION_1 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unsupported snapshot version {version}, expected {VERSION}"),
+            ));
+        }
+
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        let snapshot = if version == VERSION_1 {
+            postcard::from_bytes::<SnapshotV1>(&bytes).map(Snapshot::from)
+        } else {
+            postcard::from_bytes(&bytes)
+        };
+        snapshot.map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        elf::ElfFile,
+        emulator::{Emulator, HarvardEmulator, LinearEmulator, LinearMemoryLayout, View},
+        error::{Result, VMError},
+    };
+    use serial_test::serial;
+
+    /// Executes `steps` instructions, one at a time.
+    fn run_steps(emulator: &mut impl Emulator, steps: usize) -> Result<()> {
+        for _ in 0..steps {
+            let pc = emulator.get_executor().cpu.pc.value;
+            let entry = emulator.fetch_block(pc)?;
+            emulator.execute_instruction(&entry.instructions_from(pc)[0], false)?;
+        }
+        Ok(())
+    }
+
+    fn assert_same_view(a: &View, b: &View) {
+        // Views have no equality, but print every field.
+        assert_eq!(format!("{a:?}"), format!("{b:?}"));
+    }
+
+    fn round_trip(snapshot: &Snapshot) -> Snapshot {
+        let mut bytes = Vec::new();
+        snapshot.write_to(&mut bytes).unwrap();
+        Snapshot::read_from(bytes.as_slice()).unwrap()
+    }
+
+    #[test]
+    #[serial]
+    fn test_harvard_snapshot_resume() {
+        let elf = ElfFile::from_path("test/fib_10.elf").unwrap();
+
+        let mut emulator = HarvardEmulator::from_elf(&elf, &[], &[]);
+        run_steps(&mut emulator, 500).unwrap();
+        let snapshot = round_trip(&emulator.snapshot());
+        assert_eq!(snapshot.kind(), EmulatorKind::Harvard);
+        assert_eq!(snapshot.pc(), emulator.executor.cpu.pc.value);
+
+        let mut restored = HarvardEmulator::from_elf(&elf, &[], &[]);
+        restored.restore(&snapshot).unwrap();
+        assert_eq!(restored.snapshot(), snapshot);
+
+        assert_eq!(emulator.execute(false), Err(VMError::VMExited(0)));
+        assert_eq!(restored.execute(false), Err(VMError::VMExited(0)));
+        assert_eq!(
+            restored.executor.global_clock,
+            emulator.executor.global_clock
+        );
+        assert_same_view(&restored.finalize(), &emulator.finalize());
+    }
+
+    #[test]
+    #[serial]
+    fn test_linear_snapshot_resume() {
+        let elf = ElfFile::from_path("test/fib_10.elf").unwrap();
+        let new_emulator =
+            || LinearEmulator::from_elf(LinearMemoryLayout::default(), &[], &elf, &[], &[]);
+
+        let mut emulator = new_emulator();
+        run_steps(&mut emulator, 500).unwrap();
+        let snapshot = round_trip(&emulator.snapshot());
+        assert_eq!(snapshot.kind(), EmulatorKind::Linear);
+
+        let mut restored = new_emulator();
+        restored.restore(&snapshot).unwrap();
+
+        assert_eq!(emulator.execute(false), Err(VMError::VMExited(0)));
+        assert_eq!(restored.execute(false), Err(VMError::VMExited(0)));
+        assert_eq!(
+            restored.executor.global_clock,
+            emulator.executor.global_clock
+        );
+        assert_eq!(
+            restored.executor.access_timestamps,
+            emulator.executor.access_timestamps
+        );
+        assert_same_view(&restored.finalize(), &emulator.finalize());
+
+        // Snapshots can't be restored into the other kind of emulator.
+        let mut harvard = HarvardEmulator::from_elf(&elf, &[], &[]);
+        assert!(matches!(
+            harvard.restore(&snapshot),
+            Err(VMError::InvalidSnapshot(_))
+        ));
+    }
+
+    #[test]
+    #[serial]
+    fn test_read_v1_snapshot() {
+        let elf = ElfFile::from_path("test/fib_10.elf").unwrap();
+        let mut emulator = HarvardEmulator::from_elf(&elf, &[], &[1, 2]);
+        run_steps(&mut emulator, 100).unwrap();
+        let snapshot = emulator.snapshot();
+
+        let v1 = SnapshotV1 {
+            kind: snapshot.kind,
+            base_address: snapshot.base_address,
+            entrypoint: snapshot.entrypoint,
+            registers: snapshot.registers,
+            pc: snapshot.pc,
+            cycles: snapshot.cycles,
+            global_clock: snapshot.global_clock,
+            private_input_tape: snapshot.private_input_tape.clone(),
+            access_timestamps: snapshot.access_timestamps.clone(),
+            cycle_tracker: snapshot.cycle_tracker.clone(),
+            logs: snapshot.logs.clone(),
+            memory: snapshot.memory.clone(),
+            harvard: snapshot.harvard.clone(),
+        };
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend(VERSION_1.to_le_bytes());
+        bytes.extend(postcard::to_stdvec(&v1).unwrap());
+
+        let read = Snapshot::read_from(bytes.as_slice()).unwrap();
+        assert!(read.auxiliary_input_tape.is_empty());
+        assert_eq!(read, snapshot);
+    }
+
+    #[test]
+    fn test_read_invalid_snapshot() {
+        let err = Snapshot::read_from(&b"NOTASNAPSHOT"[..]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend((VERSION + 1).to_le_bytes());
+        let err = Snapshot::read_from(bytes.as_slice()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
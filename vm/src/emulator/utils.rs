@@ -1,6 +1,6 @@
 use crate::elf::ElfFile;
 use crate::memory::MemorySegmentImage;
-use crate::riscv::{decode_instruction, BasicBlock};
+use crate::riscv::{decode_instruction, expand_compressed, is_compressed, BasicBlock, Instruction};
 
 pub use super::executor::Emulator;
 pub use super::layout::LinearMemoryLayout;
@@ -9,6 +9,7 @@ use super::registry;
 use nexus_common::constants::WORD_SIZE;
 use nexus_common::memory::MemoryRecords;
 use nexus_common::riscv::{opcode::BuiltinOpcode, Opcode};
+use serde::{Deserialize, Serialize};
 
 pub type MemoryTranscript = Vec<MemoryRecords>;
 
@@ -23,16 +24,21 @@ pub trait IOEntry {
 }
 
 macro_rules! io {
-    ( $id:ident ) => {
+    ( $id:ident $(, $field:ident: $default:expr)* ) => {
         impl IOEntry for $id {
             fn new(address: u32, value: u8) -> Self {
-                Self { address, value }
+                Self {
+                    address,
+                    value,
+                    $($field: $default,)*
+                }
             }
 
             fn new_from_offset(base: u32, offset: u32, value: u8) -> Self {
                 Self {
                     address: base + offset,
                     value,
+                    $($field: $default,)*
                 }
             }
 
@@ -62,6 +68,96 @@ pub fn convert_instruction(registry: &registry::InstructionExecutorRegistry, ins
     }
 }
 
+/// Convert `rin` and `wou` instructions of a program, see [`convert_instruction`].
+///
+/// In code with compressed instructions, 32-bit instructions are only aligned to halfwords.
+pub fn convert_instructions(
+    registry: &registry::InstructionExecutorRegistry,
+    instructions: &[u32],
+    compressed: bool,
+) -> Vec<u32> {
+    if !compressed {
+        return instructions
+            .iter()
+            .map(|instr| convert_instruction(registry, instr))
+            .collect();
+    }
+
+    let mut halfwords = into_halfwords(instructions);
+    let mut i = 0;
+    while i + 1 < halfwords.len() {
+        if is_compressed(halfwords[i]) {
+            i += 1;
+            continue;
+        }
+
+        let instr = halfwords[i] as u32 | (halfwords[i + 1] as u32) << 16;
+        let converted = convert_instruction(registry, &instr);
+        halfwords[i] = converted as u16;
+        halfwords[i + 1] = (converted >> 16) as u16;
+        i += 2;
+    }
+
+    halfwords
+        .chunks(2)
+        .map(|pair| pair[0] as u32 | (pair[1] as u32) << 16)
+        .collect()
+}
+
+/// Lists the instructions of a program held in `words` from `base`.
+///
+/// Code with compressed instructions is walked halfword by halfword from `base`, which assumes no data is
+/// interleaved with the instructions.
+pub(crate) fn program_memory_entries(
+    base: u32,
+    words: &[u32],
+    compressed: bool,
+) -> Vec<ProgramMemoryEntry> {
+    if !compressed {
+        return words
+            .iter()
+            .enumerate()
+            .map(|(pc_offset, instruction)| ProgramMemoryEntry {
+                pc: base + (pc_offset * WORD_SIZE) as u32,
+                instruction_word: *instruction,
+                compressed: false,
+            })
+            .collect();
+    }
+
+    let halfwords = into_halfwords(words);
+    let mut entries = Vec::new();
+    let mut i = 0;
+    while i < halfwords.len() {
+        let pc = base + (i * WORD_SIZE / 2) as u32;
+        // A trailing halfword is kept as a compressed instruction, even if it starts a 32-bit one.
+        let entry = if is_compressed(halfwords[i]) || i + 1 == halfwords.len() {
+            ProgramMemoryEntry {
+                pc,
+                instruction_word: halfwords[i] as u32,
+                compressed: true,
+            }
+        } else {
+            ProgramMemoryEntry {
+                pc,
+                instruction_word: halfwords[i] as u32 | (halfwords[i + 1] as u32) << 16,
+                compressed: false,
+            }
+        };
+        i += entry.size() as usize / 2;
+        entries.push(entry);
+    }
+
+    entries
+}
+
+fn into_halfwords(words: &[u32]) -> Vec<u16> {
+    words
+        .iter()
+        .flat_map(|&word| [word as u16, (word >> 16) as u16])
+        .collect()
+}
+
 pub fn io_entries_into_vec<T: IOEntry>(base: u32, entries: &[T]) -> Vec<u8> {
     let mut vec: Vec<u8> = Vec::new();
     vec.resize(entries.len(), u8::default());
@@ -96,23 +192,27 @@ pub fn slice_into_io_entries<T: IOEntry>(base: u32, values: &[u8]) -> Vec<T> {
 pub fn elf_into_program_info(elf: &ElfFile, layout: &LinearMemoryLayout) -> ProgramInfo {
     ProgramInfo {
         initial_pc: layout.program_start(),
-        program: elf
-            .instructions
-            .iter()
-            .enumerate()
-            .map(|(pc_offset, instruction)| ProgramMemoryEntry {
-                pc: layout.program_start() + (pc_offset * WORD_SIZE) as u32,
-                instruction_word: *instruction,
-            })
-            .collect(),
+        program: program_memory_entries(layout.program_start(), &elf.instructions, elf.compressed),
     }
 }
 
 // One entry per byte because RO memory can be accessed bytewise
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
 pub struct MemoryInitializationEntry {
     pub address: u32,
     pub value: u8,
+    /// Whether the byte belongs to a read-only segment, e.g. the public input or the static ROM.
+    pub read_only: bool,
+}
+
+impl MemoryInitializationEntry {
+    /// Marks the entry as belonging to a read-only segment.
+    pub fn into_read_only(self) -> Self {
+        Self {
+            read_only: true,
+            ..self
+        }
+    }
 }
 
 // One entry per byte because WO memory can be accessed bytewise
@@ -122,17 +222,42 @@ pub struct PublicOutputEntry {
     pub value: u8,
 }
 
-io!(MemoryInitializationEntry);
+io!(MemoryInitializationEntry, read_only: false);
 io!(PublicOutputEntry);
 
 // One entry per instruction because program memory is always accessed instruction-wise
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ProgramMemoryEntry {
     pub pc: u32,
+    /// The encoding of the instruction, held in the lower half for compressed instructions.
     pub instruction_word: u32,
+    /// Whether the instruction is a 16-bit RV32C instruction.
+    pub compressed: bool,
 }
 
-#[derive(Debug, Clone)]
+impl ProgramMemoryEntry {
+    /// Returns the size of the instruction in bytes.
+    pub fn size(&self) -> u32 {
+        if self.compressed {
+            2
+        } else {
+            WORD_SIZE as u32
+        }
+    }
+
+    /// Returns the 32-bit instruction executed at `pc`, which is the expansion of compressed instructions.
+    ///
+    /// Compressed encodings without an expansion never execute, they are returned as is.
+    pub fn expanded_word(&self) -> u32 {
+        if self.compressed {
+            expand_compressed(self.instruction_word as u16).unwrap_or(self.instruction_word)
+        } else {
+            self.instruction_word
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ProgramInfo {
     // The program counter where the execution starts
     pub initial_pc: u32,
@@ -159,10 +284,43 @@ impl BasicBlockEntry {
     pub fn new(start: u32, block: BasicBlock) -> Self {
         BasicBlockEntry {
             start,
-            end: start + (block.len() * WORD_SIZE) as u32,
+            end: start + block.0.iter().map(Instruction::size).sum::<u32>(),
             block,
         }
     }
+
+    /// Returns the index of the instruction starting at `pc`, if any.
+    pub fn instruction_index(&self, pc: u32) -> Option<usize> {
+        if pc < self.start || pc >= self.end {
+            return None;
+        }
+        // Without compressed instructions, all instructions are one word long.
+        if (self.end - self.start) as usize == self.block.len() * WORD_SIZE {
+            let offset = (pc - self.start) as usize;
+            return (offset % WORD_SIZE == 0).then_some(offset / WORD_SIZE);
+        }
+
+        let mut address = self.start;
+        for (index, instruction) in self.block.0.iter().enumerate() {
+            if address == pc {
+                return Some(index);
+            }
+            address += instruction.size();
+        }
+        None
+    }
+
+    /// Returns the instructions of the block from the one at `pc`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no instruction of the block starts at `pc`.
+    pub fn instructions_from(&self, pc: u32) -> &[Instruction] {
+        let index = self
+            .instruction_index(pc)
+            .expect("pc is not at an instruction of the basic block");
+        &self.block.0[index..]
+    }
 }
 
 pub trait InternalView {
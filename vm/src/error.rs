@@ -73,6 +73,14 @@ pub enum VMError {
     // Merging non-contiguous memory segments
     #[error("Non-contiguous memory")]
     NonContiguousMemory,
+
+    // Snapshot taken from a different emulator or program
+    #[error("Invalid snapshot: {0}")]
+    InvalidSnapshot(&'static str),
+
+    // Auxiliary input given to a prover that can't pass it to the guest program
+    #[error("Auxiliary input is not supported")]
+    AuxiliaryInputUnsupported,
 }
 
 impl PartialEq for VMError {
@@ -13,5 +13,5 @@ pub use nexus_common::memory::traits::{
 pub use fixed::FixedMemory;
 pub use memory_image::MemorySegmentImage;
 pub use paged_memory::PagedMemory;
-pub use unified::{Modes, UnifiedMemory};
+pub use unified::{Modes, UnifiedMemory, UnifiedMemorySnapshot};
 pub use variable::VariableMemory;
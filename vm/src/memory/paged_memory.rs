@@ -197,6 +197,21 @@ impl PagedMemory {
         })
     }
 
+    /// Returns the contiguous runs of set words, along with their start addresses.
+    pub fn word_runs(&self) -> Vec<(u32, Vec<u32>)> {
+        self.ranges
+            .iter()
+            .map(|range| {
+                let words = (range.start..range.end)
+                    .step_by(WORD_SIZE)
+                    // Safety: this address is in a range, so it is present.
+                    .map(|addr| self.get_word(addr).unwrap().unwrap())
+                    .collect();
+                (range.start, words)
+            })
+            .collect()
+    }
+
     pub fn occupied_bytes(&self) -> u32 {
         self.ranges
             .iter()
@@ -68,17 +68,19 @@
 //! The use of `RangeMap` for memory layout allows for efficient lookup of the correct memory
 //! region for a given address. However, the performance may vary depending on the number and
 //! size of fixed memory regions.
-use nexus_common::error::MemoryError;
+use nexus_common::{constants::WORD_SIZE, error::MemoryError, memory::alignment::Alignable};
 use num_derive::FromPrimitive;
 use num_traits::FromPrimitive;
 use rangemap::RangeMap;
+use serde::{Deserialize, Serialize};
 use std::{
     collections::BTreeMap,
     fmt::{Debug, Display, Formatter, Result as FmtResult},
 };
 
 use super::{
-    FixedMemory, LoadOp, MemAccessSize, MemoryProcessor, StoreOp, VariableMemory, NA, RO, RW, WO,
+    FixedMemory, LoadOp, MemAccessSize, MemoryProcessor, Mode, StoreOp, VariableMemory, NA, RO, RW,
+    WO,
 };
 
 #[derive(Debug, Clone, Eq, PartialEq, FromPrimitive)]
@@ -223,7 +225,83 @@ macro_rules! add_fixed {
     };
 }
 
+/// Contents of the regions of a [`UnifiedMemory`] that can change during execution.
+///
+/// Read-only and inaccessible regions are left out, they are restored from the program itself.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnifiedMemorySnapshot {
+    /// Base address and words of every fixed read-write region, in the order they were added.
+    pub fixed_rw: Vec<(u32, Vec<u32>)>,
+    /// Base address and words of every fixed write-only region, in the order they were added.
+    pub fixed_wo: Vec<(u32, Vec<u32>)>,
+    /// Contiguous runs of words of the variable read-write region, if there is one.
+    pub variable: Option<Vec<(u32, Vec<u32>)>>,
+}
+
 impl UnifiedMemory {
+    /// Captures the contents of the writable regions.
+    pub fn snapshot(&self) -> UnifiedMemorySnapshot {
+        fn fixed_words<M: Mode>(store: &[FixedMemory<M>]) -> Vec<(u32, Vec<u32>)> {
+            store
+                .iter()
+                .map(|mem| {
+                    let words = mem.segment_words(mem.base_address, None).to_vec();
+                    (mem.base_address, words)
+                })
+                .collect()
+        }
+
+        UnifiedMemorySnapshot {
+            fixed_rw: fixed_words(&self.frw_store),
+            fixed_wo: fixed_words(&self.fwo_store),
+            variable: self.vrw.as_ref().map(VariableMemory::word_runs),
+        }
+    }
+
+    /// Overwrites the writable regions with a snapshot taken from a memory with the same regions.
+    ///
+    /// The memory is left untouched if the regions don't match.
+    pub fn restore(&mut self, snapshot: &UnifiedMemorySnapshot) -> Result<(), MemoryError> {
+        fn check_fixed<M: Mode>(
+            store: &[FixedMemory<M>],
+            snapshot: &[(u32, Vec<u32>)],
+        ) -> Result<(), MemoryError> {
+            let matches = store.len() == snapshot.len()
+                && store
+                    .iter()
+                    .zip(snapshot)
+                    .all(|(mem, (base_address, words))| {
+                        mem.base_address == *base_address
+                            && words.len() * WORD_SIZE <= mem.max_len.word_align()
+                    });
+            if matches {
+                Ok(())
+            } else {
+                Err(MemoryError::UndefinedMemoryRegion)
+            }
+        }
+
+        fn restore_fixed<M: Mode>(store: &mut [FixedMemory<M>], snapshot: &[(u32, Vec<u32>)]) {
+            for (mem, (base_address, words)) in store.iter_mut().zip(snapshot) {
+                *mem = FixedMemory::from_word_vec(*base_address, mem.max_len, words.clone());
+            }
+        }
+
+        check_fixed(&self.frw_store, &snapshot.fixed_rw)?;
+        check_fixed(&self.fwo_store, &snapshot.fixed_wo)?;
+        let vrw = match (&self.vrw, &snapshot.variable) {
+            (Some(_), Some(runs)) => Some(VariableMemory::from_word_runs(runs)?),
+            (None, None) => None,
+            _ => return Err(MemoryError::UndefinedMemoryRegion),
+        };
+
+        restore_fixed(&mut self.frw_store, &snapshot.fixed_rw);
+        restore_fixed(&mut self.fwo_store, &snapshot.fixed_wo);
+        self.vrw = vrw;
+
+        Ok(())
+    }
+
     pub fn add_variable(&mut self, vrw: VariableMemory<RW>) -> Result<(), MemoryError> {
         if self.vrw.is_some() {
             return Err(MemoryError::MemoryOverlap);
@@ -202,6 +202,24 @@ impl<M: Mode> VariableMemory<M> {
         self.store.addressed_iter()
     }
 
+    /// Returns the contiguous runs of set words, along with their start addresses.
+    pub fn word_runs(&self) -> Vec<(u32, Vec<u32>)> {
+        self.store.word_runs()
+    }
+
+    /// Creates a memory holding exactly the given runs of words, the inverse of [`VariableMemory::word_runs`].
+    pub fn from_word_runs(runs: &[(u32, Vec<u32>)]) -> Result<Self, MemoryError> {
+        let mut store = PagedMemory::new();
+        for (address, words) in runs {
+            store.set_words(*address, words)?;
+        }
+
+        Ok(Self {
+            store,
+            _phantom_data: PhantomData,
+        })
+    }
+
     pub fn get_word(&self, address: u32) -> Result<Option<u32>, MemoryError> {
         self.store.get_word(address)
     }
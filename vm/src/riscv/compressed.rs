@@ -0,0 +1,332 @@
+//! # Expansion of RV32C Compressed Instructions
+//!
+//! Every 16-bit instruction of the C extension is a shorter encoding of a 32-bit RV32I instruction. Instead of
+//! decoding compressed instructions separately, they are expanded into their 32-bit equivalents, which are then
+//! decoded and executed as usual. Only the size of the instruction, which determines the next program counter and
+//! the return address of jumps, tells them apart.
+//!
+//! Instructions of the C extension working on floating-point registers, as well as reserved and illegal
+//! encodings, have no expansion.
+
+/// Returns true if the halfword is the start of a 16-bit instruction, i.e. its two lowest bits are not `0b11`.
+#[inline(always)]
+pub fn is_compressed(halfword: u16) -> bool {
+    halfword & 0b11 != 0b11
+}
+
+const OPCODE_LOAD: u32 = 0b0000011;
+const OPCODE_OP_IMM: u32 = 0b0010011;
+const OPCODE_STORE: u32 = 0b0100011;
+const OPCODE_OP: u32 = 0b0110011;
+const OPCODE_LUI: u32 = 0b0110111;
+const OPCODE_BRANCH: u32 = 0b1100011;
+const OPCODE_JALR: u32 = 0b1100111;
+const OPCODE_JAL: u32 = 0b1101111;
+
+const EBREAK: u32 = 0x00100073;
+
+const X0: u32 = 0;
+const RA: u32 = 1;
+const SP: u32 = 2;
+
+#[inline(always)]
+fn bits(halfword: u32, high: u32, low: u32) -> u32 {
+    (halfword >> low) & ((1 << (high - low + 1)) - 1)
+}
+
+/// Sign-extends the `width` lowest bits of `value`.
+#[inline(always)]
+fn sign_extend(value: u32, width: u32) -> u32 {
+    let shift = 32 - width;
+    (((value << shift) as i32) >> shift) as u32
+}
+
+fn r_type(fn7: u32, rs2: u32, rs1: u32, fn3: u32, rd: u32, opcode: u32) -> u32 {
+    (fn7 << 25) | (rs2 << 20) | (rs1 << 15) | (fn3 << 12) | (rd << 7) | opcode
+}
+
+fn i_type(imm: u32, rs1: u32, fn3: u32, rd: u32, opcode: u32) -> u32 {
+    ((imm & 0xFFF) << 20) | (rs1 << 15) | (fn3 << 12) | (rd << 7) | opcode
+}
+
+fn s_type(imm: u32, rs2: u32, rs1: u32, fn3: u32, opcode: u32) -> u32 {
+    (bits(imm, 11, 5) << 25)
+        | (rs2 << 20)
+        | (rs1 << 15)
+        | (fn3 << 12)
+        | (bits(imm, 4, 0) << 7)
+        | opcode
+}
+
+fn b_type(imm: u32, rs2: u32, rs1: u32, fn3: u32) -> u32 {
+    (bits(imm, 12, 12) << 31)
+        | (bits(imm, 10, 5) << 25)
+        | (rs2 << 20)
+        | (rs1 << 15)
+        | (fn3 << 12)
+        | (bits(imm, 4, 1) << 8)
+        | (bits(imm, 11, 11) << 7)
+        | OPCODE_BRANCH
+}
+
+fn j_type(imm: u32, rd: u32) -> u32 {
+    (bits(imm, 20, 20) << 31)
+        | (bits(imm, 10, 1) << 21)
+        | (bits(imm, 11, 11) << 20)
+        | (bits(imm, 19, 12) << 12)
+        | (rd << 7)
+        | OPCODE_JAL
+}
+
+/// Register in bits [4:2] of the CIW, CL, CS, CA and CB formats, which only address `x8` to `x15`.
+#[inline(always)]
+fn rd_prime(c: u32) -> u32 {
+    8 + bits(c, 4, 2)
+}
+
+/// Register in bits [9:7] of the CL, CS, CA and CB formats, which only address `x8` to `x15`.
+#[inline(always)]
+fn rs1_prime(c: u32) -> u32 {
+    8 + bits(c, 9, 7)
+}
+
+/// Sign-extended 6-bit immediate of `c.addi`, `c.li` and `c.andi`.
+#[inline(always)]
+fn ci_imm(c: u32) -> u32 {
+    sign_extend((bits(c, 12, 12) << 5) | bits(c, 6, 2), 6)
+}
+
+/// Shift amount of `c.slli`, `c.srli` and `c.srai`, `None` for the amounts reserved on RV32.
+#[inline(always)]
+fn shamt(c: u32) -> Option<u32> {
+    (bits(c, 12, 12) == 0).then(|| bits(c, 6, 2))
+}
+
+/// Sign-extended offset of `c.j` and `c.jal`.
+fn cj_offset(c: u32) -> u32 {
+    let offset = (bits(c, 12, 12) << 11)
+        | (bits(c, 11, 11) << 4)
+        | (bits(c, 10, 9) << 8)
+        | (bits(c, 8, 8) << 10)
+        | (bits(c, 7, 7) << 6)
+        | (bits(c, 6, 6) << 7)
+        | (bits(c, 5, 3) << 1)
+        | (bits(c, 2, 2) << 5);
+    sign_extend(offset, 12)
+}
+
+/// Sign-extended offset of `c.beqz` and `c.bnez`.
+fn cb_offset(c: u32) -> u32 {
+    let offset = (bits(c, 12, 12) << 8)
+        | (bits(c, 11, 10) << 3)
+        | (bits(c, 6, 5) << 6)
+        | (bits(c, 4, 3) << 1)
+        | (bits(c, 2, 2) << 5);
+    sign_extend(offset, 9)
+}
+
+/// Expands a 16-bit RV32C instruction into the equivalent 32-bit instruction.
+///
+/// Returns `None` for halfwords that are not compressed instructions, for floating-point instructions and for
+/// reserved or illegal encodings, including the all-zero halfword.
+pub fn expand_compressed(halfword: u16) -> Option<u32> {
+    let c = halfword as u32;
+    let fn3 = bits(c, 15, 13);
+    let rd = bits(c, 11, 7);
+    let rs2 = bits(c, 6, 2);
+
+    let expanded = match (bits(c, 1, 0), fn3) {
+        // c.addi4spn
+        (0b00, 0b000) => {
+            let imm = (bits(c, 12, 11) << 4)
+                | (bits(c, 10, 7) << 6)
+                | (bits(c, 6, 6) << 2)
+                | (bits(c, 5, 5) << 3);
+            if imm == 0 {
+                return None;
+            }
+            i_type(imm, SP, 0b000, rd_prime(c), OPCODE_OP_IMM)
+        }
+        // c.lw
+        (0b00, 0b010) => {
+            let imm = (bits(c, 12, 10) << 3) | (bits(c, 6, 6) << 2) | (bits(c, 5, 5) << 6);
+            i_type(imm, rs1_prime(c), 0b010, rd_prime(c), OPCODE_LOAD)
+        }
+        // c.sw
+        (0b00, 0b110) => {
+            let imm = (bits(c, 12, 10) << 3) | (bits(c, 6, 6) << 2) | (bits(c, 5, 5) << 6);
+            s_type(imm, rd_prime(c), rs1_prime(c), 0b010, OPCODE_STORE)
+        }
+        // c.addi, c.nop
+        (0b01, 0b000) => i_type(ci_imm(c), rd, 0b000, rd, OPCODE_OP_IMM),
+        // c.jal
+        (0b01, 0b001) => j_type(cj_offset(c), RA),
+        // c.li
+        (0b01, 0b010) => i_type(ci_imm(c), X0, 0b000, rd, OPCODE_OP_IMM),
+        // c.addi16sp
+        (0b01, 0b011) if rd == SP => {
+            let imm = (bits(c, 12, 12) << 9)
+                | (bits(c, 6, 6) << 4)
+                | (bits(c, 5, 5) << 6)
+                | (bits(c, 4, 3) << 7)
+                | (bits(c, 2, 2) << 5);
+            if imm == 0 {
+                return None;
+            }
+            i_type(sign_extend(imm, 10), SP, 0b000, SP, OPCODE_OP_IMM)
+        }
+        // c.lui
+        (0b01, 0b011) => {
+            let imm = (bits(c, 12, 12) << 17) | (bits(c, 6, 2) << 12);
+            if imm == 0 {
+                return None;
+            }
+            (sign_extend(imm, 18) & 0xFFFFF000) | (rd << 7) | OPCODE_LUI
+        }
+        (0b01, 0b100) => {
+            let rd = rs1_prime(c);
+            match bits(c, 11, 10) {
+                // c.srli
+                0b00 => i_type(shamt(c)?, rd, 0b101, rd, OPCODE_OP_IMM),
+                // c.srai
+                0b01 => i_type((0b0100000 << 5) | shamt(c)?, rd, 0b101, rd, OPCODE_OP_IMM),
+                // c.andi
+                0b10 => i_type(ci_imm(c), rd, 0b111, rd, OPCODE_OP_IMM),
+                // c.subw and c.addw only exist on RV64
+                _ if bits(c, 12, 12) == 1 => return None,
+                _ => {
+                    let rs2 = rd_prime(c);
+                    let (fn7, fn3) = match bits(c, 6, 5) {
+                        // c.sub
+                        0b00 => (0b0100000, 0b000),
+                        // c.xor
+                        0b01 => (0b0000000, 0b100),
+                        // c.or
+                        0b10 => (0b0000000, 0b110),
+                        // c.and
+                        _ => (0b0000000, 0b111),
+                    };
+                    r_type(fn7, rs2, rd, fn3, rd, OPCODE_OP)
+                }
+            }
+        }
+        // c.j
+        (0b01, 0b101) => j_type(cj_offset(c), X0),
+        // c.beqz
+        (0b01, 0b110) => b_type(cb_offset(c), X0, rs1_prime(c), 0b000),
+        // c.bnez
+        (0b01, 0b111) => b_type(cb_offset(c), X0, rs1_prime(c), 0b001),
+        // c.slli
+        (0b10, 0b000) => i_type(shamt(c)?, rd, 0b001, rd, OPCODE_OP_IMM),
+        // c.lwsp
+        (0b10, 0b010) => {
+            if rd == X0 {
+                return None;
+            }
+            let imm = (bits(c, 12, 12) << 5) | (bits(c, 6, 4) << 2) | (bits(c, 3, 2) << 6);
+            i_type(imm, SP, 0b010, rd, OPCODE_LOAD)
+        }
+        (0b10, 0b100) => match (bits(c, 12, 12), rd, rs2) {
+            // c.jr
+            (0, X0, X0) => return None,
+            (0, _, X0) => i_type(0, rd, 0b000, X0, OPCODE_JALR),
+            // c.mv
+            (0, _, _) => r_type(0, rs2, X0, 0b000, rd, OPCODE_OP),
+            // c.ebreak
+            (_, X0, X0) => EBREAK,
+            // c.jalr
+            (_, _, X0) => i_type(0, rd, 0b000, RA, OPCODE_JALR),
+            // c.add
+            _ => r_type(0, rs2, rd, 0b000, rd, OPCODE_OP),
+        },
+        // c.swsp
+        (0b10, 0b110) => {
+            let imm = (bits(c, 12, 9) << 2) | (bits(c, 8, 7) << 6);
+            s_type(imm, rs2, SP, 0b010, OPCODE_STORE)
+        }
+        // Floating-point loads and stores, reserved encodings, and 32-bit instructions
+        _ => return None,
+    };
+
+    Some(expanded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::riscv::{decode_compressed_instruction, decode_instruction, Instruction};
+
+    #[test]
+    fn test_expand_compressed() {
+        // Encodings from `llvm-mc -triple=riscv32 -mattr=+c`, with and without the C extension.
+        let test_cases = [
+            (0x1fe0, 0x3fc10413), // c.addi4spn s0, sp, 1020
+            (0x5efc, 0x07c6a783), // c.lw a5, 124(a3)
+            (0xc0a8, 0x04a4a023), // c.sw a0, 64(s1)
+            (0x0001, 0x00000013), // c.nop
+            (0x1501, 0xfe050513), // c.addi a0, -32
+            (0x3001, 0x801ff0ef), // c.jal -2048
+            (0x437d, 0x01f00313), // c.li t1, 31
+            (0x7101, 0xe0010113), // c.addi16sp sp, -512
+            (0x7701, 0xfffe0737), // c.lui a4, 0xfffe0
+            (0x697d, 0x0001f937), // c.lui s2, 31
+            (0x81fd, 0x01f5d593), // c.srli a1, 31
+            (0x8485, 0x4014d493), // c.srai s1, 1
+            (0x9a7d, 0xfff67613), // c.andi a2, -1
+            (0x8c1d, 0x40f40433), // c.sub s0, a5
+            (0x8d2d, 0x00b54533), // c.xor a0, a1
+            (0x8e55, 0x00d66633), // c.or a2, a3
+            (0x8f7d, 0x00f77733), // c.and a4, a5
+            (0xaffd, 0x7fe0006f), // c.j 2046
+            (0xd101, 0xf00500e3), // c.beqz a0, -256
+            (0xecfd, 0x0e049f63), // c.bnez s1, 254
+            (0x0e46, 0x011e1e13), // c.slli t3, 17
+            (0x50fe, 0x0fc12083), // c.lwsp ra, 252(sp)
+            (0x8082, 0x00008067), // c.jr ra
+            (0x856e, 0x01b00533), // c.mv a0, s11
+            (0x9002, 0x00100073), // c.ebreak
+            (0x9282, 0x000280e7), // c.jalr t0
+            (0x9146, 0x01110133), // c.add sp, a7
+            (0xc122, 0x08812023), // c.swsp s0, 128(sp)
+        ];
+
+        for (compressed, expanded) in test_cases {
+            assert!(is_compressed(compressed));
+            assert_eq!(
+                expand_compressed(compressed),
+                Some(expanded),
+                "wrong expansion of {compressed:#06x}"
+            );
+
+            let instruction = decode_compressed_instruction(compressed);
+            assert_eq!(instruction.size(), 2);
+            assert_eq!(
+                Instruction {
+                    compressed: false,
+                    ..instruction
+                },
+                decode_instruction(expanded)
+            );
+        }
+    }
+
+    #[test]
+    fn test_expand_invalid_compressed() {
+        let test_cases = [
+            0x0000, // illegal instruction
+            0x2000, // c.fld
+            0x6101, // c.addi16sp with a zero immediate
+            0x6701, // c.lui with a zero immediate
+            0x9c2d, // c.subw
+            0x1586, // c.slli with a 6-bit shift amount
+            0x4002, // c.lwsp into x0
+            0x8002, // c.jr x0
+            0x0013, // the first half of a 32-bit instruction
+        ];
+
+        for halfword in test_cases {
+            assert_eq!(expand_compressed(halfword), None, "{halfword:#06x}");
+        }
+        assert!(!is_compressed(0x0013));
+    }
+}
@@ -23,6 +23,8 @@
 //! - `decode_instruction`: Decodes a single RISC-V instruction from its raw 32-bit representation.
 //! - `decode_instructions`: Decodes a series of RISC-V instructions and organizes them into basic blocks.
 //! - `decode_until_end_of_a_block`: Decodes instructions until the end of a single basic block is reached.
+//! - `decode_compressed_instruction`: Decodes a single 16-bit RV32C instruction by expanding it.
+//! - `decode_until_end_of_a_mixed_block`: Decodes a basic block of code mixing 16-bit and 32-bit instructions.
 //!
 //! ## Usage Example
 //!
@@ -57,7 +59,10 @@
 //! This module is particularly useful for tasks such as control flow analysis, optimization,
 //! and instruction-level parallelism detection in RISC-V programs.
 
-use crate::riscv::instructions::{BasicBlock, BasicBlockProgram, Instruction, InstructionDecoder};
+use crate::riscv::{
+    compressed::{expand_compressed, is_compressed},
+    instructions::{BasicBlock, BasicBlockProgram, Instruction, InstructionDecoder},
+};
 use nexus_common::{
     constants::KECCAKF_OPCODE,
     riscv::{instruction::InstructionType, register::Register, Opcode},
@@ -174,6 +179,18 @@ pub fn decode_instruction(u32_instruction: u32) -> Instruction {
     })
 }
 
+/// Decodes a 16-bit RV32C instruction into the instruction it expands to.
+///
+/// Halfwords without an expansion are decoded as unimplemented instructions, which are still 2 bytes long.
+pub fn decode_compressed_instruction(u16_instruction: u16) -> Instruction {
+    let mut instruction = match expand_compressed(u16_instruction) {
+        Some(u32_instruction) => decode_instruction(u32_instruction),
+        None => Instruction::unimpl(),
+    };
+    instruction.compressed = true;
+    instruction
+}
+
 /// Decodes RISC-V instructions from an ELF file into basic blocks
 ///
 /// # Arguments
@@ -231,6 +248,46 @@ pub fn decode_until_end_of_a_block(u32_instructions: &[u32]) -> BasicBlock {
     block
 }
 
+/// Decodes code mixing 16-bit and 32-bit instructions until the end of a single basic block is reached.
+///
+/// # Arguments
+///
+/// * `u32_words` - Memory words, starting with the word containing the first instruction
+/// * `upper_half` - Whether the first instruction starts in the upper half of the first word
+///
+/// A 32-bit instruction cut off at the end of `u32_words` is not decoded.
+///
+/// Halfwords are told apart by their two lowest bits only, so the built-in custom instructions, whose opcode
+/// [`KECCAKF_OPCODE`] ends in `0b10`, are read as compressed instructions here and can't be used in mixed code.
+pub fn decode_until_end_of_a_mixed_block(u32_words: &[u32], upper_half: bool) -> BasicBlock {
+    let mut block = BasicBlock::default();
+    let mut halfwords = u32_words
+        .iter()
+        .flat_map(|&word| [word as u16, (word >> 16) as u16])
+        .skip(upper_half as usize);
+
+    while let Some(low) = halfwords.next() {
+        let decoded_instruction = if is_compressed(low) {
+            decode_compressed_instruction(low)
+        } else {
+            let Some(high) = halfwords.next() else {
+                break;
+            };
+            decode_instruction(low as u32 | (high as u32) << 16)
+        };
+
+        let pc_changed = decoded_instruction.is_branch_or_jump_instruction();
+
+        block.0.push(decoded_instruction);
+
+        if pc_changed {
+            break;
+        }
+    }
+
+    block
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -307,4 +364,47 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_decode_mixed_block() {
+        // c.li a0, 1; addi a1, a0, 2; c.add a0, a1; c.bnez s1, 254; c.nop
+        let halfwords: [u16; 6] = [0x4505, 0x0593, 0x0025, 0x952e, 0xecfd, 0x0001];
+        let words: Vec<u32> = halfwords
+            .chunks(2)
+            .map(|pair| pair[0] as u32 | (pair[1] as u32) << 16)
+            .collect();
+
+        let block = decode_until_end_of_a_mixed_block(&words, false);
+        let sizes: Vec<u32> = block.0.iter().map(Instruction::size).collect();
+        assert_eq!(sizes, [2, 4, 2, 2]);
+        assert_eq!(block[0].to_string(), "li a0, 1");
+        assert_eq!(block[1].to_string(), "addi a1, a0, 2");
+        assert_eq!(block[2].to_string(), "add a0, a0, a1");
+        assert!(block[3].is_branch_or_jump_instruction());
+
+        // Starting in the upper half of a word, as after a jump to `c.add`.
+        let block = decode_until_end_of_a_mixed_block(&words[1..], true);
+        assert_eq!(block.len(), 2);
+        assert_eq!(block[0].to_string(), "add a0, a0, a1");
+    }
+
+    #[test]
+    fn test_decode_mixed_block_with_custom_opcode_bits() {
+        // keccakf with the state at a0, as emitted by the runtime in code without compressed instructions.
+        let keccakf = 0x0005005a;
+        let instruction = decode_instruction(keccakf);
+        assert_eq!(instruction.opcode.raw(), KECCAKF_OPCODE);
+        assert_eq!(instruction.size(), 4);
+
+        // In mixed code, the same low bits start a compressed instruction: c.li s6, 7; c.mv a0, s6; c.jr ra
+        let words = [0x855a4b1d, 0x00008082];
+        let block = decode_until_end_of_a_mixed_block(&words, false);
+        let sizes: Vec<u32> = block.0.iter().map(Instruction::size).collect();
+        assert_eq!(sizes, [2, 2, 2]);
+        assert_eq!(block[1].to_string(), "add a0, zero, s6");
+
+        // A custom instruction in mixed code is split into two halfwords.
+        let block = decode_until_end_of_a_mixed_block(&[keccakf], false);
+        assert_eq!(block[0].size(), 2);
+    }
 }
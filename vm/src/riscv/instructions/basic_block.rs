@@ -27,8 +27,10 @@ impl BasicBlock {
 
     pub fn print_with_offset(&self, offset: usize) {
         println!("┌─────────────────────────────────────────────────");
-        for (j, instruction) in self.0.iter().enumerate() {
-            println!("│ {:3x}: {}", j * 4 + offset, instruction);
+        let mut address = offset;
+        for instruction in self.0.iter() {
+            println!("│ {:3x}: {}", address, instruction);
+            address += instruction.size() as usize;
         }
         println!("└─────────────────────────────────────────────────");
     }
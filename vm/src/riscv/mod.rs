@@ -1,7 +1,12 @@
+pub(crate) mod compressed;
 pub(crate) mod decoder;
 pub(crate) mod instructions;
 
-pub use decoder::{decode_instruction, decode_instructions, decode_until_end_of_a_block};
+pub use compressed::{expand_compressed, is_compressed};
+pub use decoder::{
+    decode_compressed_instruction, decode_instruction, decode_instructions,
+    decode_until_end_of_a_block, decode_until_end_of_a_mixed_block,
+};
 pub use instructions::{
     BasicBlock, BasicBlockProgram, BuiltinOpcode, Instruction, InstructionType, Opcode,
 };
@@ -17,6 +17,7 @@
 //!    - Exit: Terminate the program with a specified error code.
 //!    - CycleCount: Profile function execution time.
 //!    - ReadFromPrivateInput: Read data from a private input tape.
+//!    - ReadFromAuxiliaryInput: Read data from an auxiliary input tape, for untrusted hints.
 //!    - OverwriteStackPointer: Modify the stack pointer based on memory layout.
 //!    - OverwriteHeapPointer: Modify the heap pointer based on memory layout.
 //! 3. Handling memory interactions for syscalls.
@@ -60,7 +61,7 @@ impl SyscallCode {
             0x401 => SyscallCode::CycleCount,
             0x402 => SyscallCode::OverwriteStackPointer,
             0x403 => SyscallCode::OverwriteHeapPointer,
-            //0x404 => SyscallCode::ReadFromAuxiliaryInput,
+            0x404 => SyscallCode::ReadFromAuxiliaryInput,
             0x405 => SyscallCode::MemoryAdvise,
             _ => return Err(VMError::UnimplementedSyscall(value, pc)),
         };
@@ -244,15 +245,11 @@ impl SyscallInstruction {
         Ok(())
     }
 
-    fn execute_read_from_private_input(
-        &mut self,
-        private_input_tape: &mut VecDeque<u8>,
-    ) -> Result<()> {
+    /// Pops a byte off an input tape, `u32::MAX` signals the tape is exhausted.
+    fn execute_read_from_input_tape(&mut self, input_tape: &mut VecDeque<u8>) -> Result<()> {
         self.result = Some((
             Register::X10,
-            private_input_tape
-                .pop_front()
-                .map_or(u32::MAX, |v| v as u32),
+            input_tape.pop_front().map_or(u32::MAX, |v| v as u32),
         ));
         Ok(())
     }
@@ -356,7 +353,7 @@ impl SyscallInstruction {
             }
 
             SyscallCode::ReadFromPrivateInput => {
-                self.execute_read_from_private_input(&mut executor.private_input_tape)
+                self.execute_read_from_input_tape(&mut executor.private_input_tape)
             }
 
             SyscallCode::OverwriteStackPointer => {
@@ -365,7 +362,9 @@ impl SyscallInstruction {
 
             SyscallCode::OverwriteHeapPointer => self.execute_overwrite_heap_pointer(memory_layout),
 
-            SyscallCode::ReadFromAuxiliaryInput => unreachable!(), // unreachable since parsing of the code will fail
+            SyscallCode::ReadFromAuxiliaryInput => {
+                self.execute_read_from_input_tape(&mut executor.auxiliary_input_tape)
+            }
 
             SyscallCode::MemoryAdvise => {
                 // No-op on second pass.
@@ -608,7 +607,7 @@ mod tests {
         // Test reading values
         for expected_value in 1..=3 {
             syscall_instruction
-                .execute_read_from_private_input(&mut private_input_tape)
+                .execute_read_from_input_tape(&mut private_input_tape)
                 .expect("Failed to execute read from private input");
             assert!(syscall_instruction
                 .result
@@ -617,7 +616,7 @@ mod tests {
 
         // Test reading when private input is empty
         syscall_instruction
-            .execute_read_from_private_input(&mut private_input_tape)
+            .execute_read_from_input_tape(&mut private_input_tape)
             .expect("Failed to execute read from private input");
         assert!(syscall_instruction
             .result
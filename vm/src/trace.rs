@@ -1,3 +1,5 @@
+use std::borrow::Cow;
+
 use serde::{Deserialize, Serialize};
 
 use crate::{
@@ -7,9 +9,11 @@ use crate::{
     error::{Result, VMError},
     memory::MemoryRecords,
     riscv::{BasicBlock, Instruction},
-    WORD_SIZE,
 };
 
+pub mod export;
+pub mod profile;
+
 /// A program step.
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct Step {
@@ -55,6 +59,31 @@ pub trait Trace {
     }
 }
 
+/// A source of the blocks of a trace, which are either held in memory or produced on demand.
+pub trait TraceSource {
+    /// Returns the total number of steps.
+    fn num_steps(&self) -> usize;
+
+    /// Returns an iterator over all blocks in order.
+    fn stream_blocks(&self) -> impl Iterator<Item = Cow<'_, Block>> + '_;
+
+    /// Whether every call to [`TraceSource::stream_blocks`] produces blocks anew, in which case callers should
+    /// go over them once and avoid collecting them.
+    fn is_streaming(&self) -> bool {
+        false
+    }
+}
+
+impl<T: Trace> TraceSource for T {
+    fn num_steps(&self) -> usize {
+        self.get_num_steps()
+    }
+
+    fn stream_blocks(&self) -> impl Iterator<Item = Cow<'_, Block>> + '_ {
+        self.get_blocks_iter().map(Cow::Borrowed)
+    }
+}
+
 /// Represents a program trace over uniform blocks.
 #[derive(Default, Clone, Serialize, Deserialize)]
 pub struct UniformTrace {
@@ -239,11 +268,9 @@ fn k_step(
                 return (Some(block), Err(e));
             }
             Ok(basic_block_entry) => {
-                let at = (vm.get_executor().cpu.pc.value as usize
-                    - basic_block_entry.start as usize)
-                    / WORD_SIZE;
+                let pc = vm.get_executor().cpu.pc.value;
 
-                for instruction in basic_block_entry.block.0[at..].iter() {
+                for instruction in basic_block_entry.instructions_from(pc) {
                     if block.steps.len() == k {
                         return (Some(block), Ok(()));
                     }
@@ -292,15 +319,29 @@ pub fn k_trace(
     public_input: &[u8],
     private_input: &[u8],
     k: usize,
+) -> Result<(View, UniformTrace)> {
+    k_trace_with_aux_input(elf, ad, public_input, private_input, &[], k)
+}
+
+/// Similar to `k_trace`, but also fills the auxiliary input tape the program reads untrusted hints from.
+pub fn k_trace_with_aux_input(
+    elf: ElfFile,
+    ad: &[u8],
+    public_input: &[u8],
+    private_input: &[u8],
+    aux_input: &[u8],
+    k: usize,
 ) -> Result<(View, UniformTrace)> {
     assert!(k > 0);
     let mut harvard = HarvardEmulator::from_elf(&elf, public_input, private_input);
+    harvard.set_auxiliary_input(aux_input);
     harvard.get_executor_mut().capture_logs(true);
 
     match harvard.execute(false) {
         Err(VMError::VMExited(_)) => {
             // todo: consistency check i/o between harvard and linear?
             let mut linear = LinearEmulator::from_harvard(&harvard, elf, ad, private_input)?;
+            linear.set_auxiliary_input(aux_input);
 
             let mut trace = UniformTrace {
                 memory_layout: linear.memory_layout,
@@ -336,6 +377,156 @@ pub fn k_trace(
     }
 }
 
+/// Blocks of `k` steps, generated by evaluating `vm` until the program exits.
+struct KSteps<E: Emulator> {
+    vm: E,
+    k: usize,
+    force_second_pass: bool,
+    exited: bool,
+}
+
+impl<E: Emulator> KSteps<E> {
+    fn new(vm: E, k: usize, force_second_pass: bool) -> Self {
+        Self {
+            vm,
+            k,
+            force_second_pass,
+            exited: false,
+        }
+    }
+}
+
+impl<E: Emulator> Iterator for KSteps<E> {
+    type Item = Result<Block>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.exited {
+            return None;
+        }
+        match k_step(&mut self.vm, self.k, self.force_second_pass) {
+            (Some(block), Ok(())) => Some(Ok(block)),
+            (Some(block), Err(VMError::VMExited(_))) => {
+                self.exited = true;
+                (!block.steps.is_empty()).then_some(Ok(block))
+            }
+            (_, Err(e)) => {
+                self.exited = true;
+                Some(Err(e))
+            }
+            (None, Ok(())) => unreachable!(),
+        }
+    }
+}
+
+/// Represents a program trace over uniform blocks, which are generated on demand by re-executing the program.
+///
+/// Unlike [`UniformTrace`], blocks aren't held in memory: each pass over them runs a new [`LinearEmulator`].
+pub struct StreamingTrace {
+    /// Memory layout.
+    pub memory_layout: LinearMemoryLayout,
+    /// Steps per block.
+    pub k: usize,
+    num_steps: usize,
+    harvard: HarvardEmulator,
+    elf: ElfFile,
+    ad: Vec<u8>,
+    private_input: Vec<u8>,
+    aux_input: Vec<u8>,
+}
+
+impl StreamingTrace {
+    fn linear_emulator(&self) -> Result<LinearEmulator> {
+        let mut linear = LinearEmulator::from_harvard(
+            &self.harvard,
+            self.elf.clone(),
+            &self.ad,
+            &self.private_input,
+        )?;
+        linear.set_auxiliary_input(&self.aux_input);
+        Ok(linear)
+    }
+
+    /// Returns an iterator over blocks, executing the program as they are consumed.
+    pub fn blocks(&self) -> impl Iterator<Item = Block> + '_ {
+        let linear = self
+            .linear_emulator()
+            .expect("the emulator was already created from the same inputs");
+        KSteps::new(linear, self.k, false)
+            .map(|block| block.expect("the program was already executed to completion"))
+    }
+}
+
+impl TraceSource for StreamingTrace {
+    fn num_steps(&self) -> usize {
+        self.num_steps
+    }
+
+    fn stream_blocks(&self) -> impl Iterator<Item = Cow<'_, Block>> + '_ {
+        self.blocks().map(Cow::Owned)
+    }
+
+    fn is_streaming(&self) -> bool {
+        true
+    }
+}
+
+/// Similar to `k_trace`, but returns a [`StreamingTrace`] that doesn't keep steps in memory.
+///
+/// The program is executed once to compute the view and the number of steps, and once more
+/// for every pass over the blocks of the returned trace.
+pub fn k_trace_streaming(
+    elf: ElfFile,
+    ad: &[u8],
+    public_input: &[u8],
+    private_input: &[u8],
+    k: usize,
+) -> Result<(View, StreamingTrace)> {
+    k_trace_streaming_with_aux_input(elf, ad, public_input, private_input, &[], k)
+}
+
+/// Similar to `k_trace_streaming`, but also fills the auxiliary input tape the program reads untrusted hints from.
+///
+/// The tape is kept in the returned trace and refilled for every pass over its blocks.
+pub fn k_trace_streaming_with_aux_input(
+    elf: ElfFile,
+    ad: &[u8],
+    public_input: &[u8],
+    private_input: &[u8],
+    aux_input: &[u8],
+    k: usize,
+) -> Result<(View, StreamingTrace)> {
+    assert!(k > 0);
+    let mut harvard = HarvardEmulator::from_elf(&elf, public_input, private_input);
+    harvard.set_auxiliary_input(aux_input);
+    harvard.get_executor_mut().capture_logs(true);
+
+    match harvard.execute(false) {
+        Err(VMError::VMExited(_)) => {
+            let mut trace = StreamingTrace {
+                memory_layout: LinearMemoryLayout::default(),
+                k,
+                num_steps: 0,
+                harvard,
+                elf,
+                ad: ad.to_vec(),
+                private_input: private_input.to_vec(),
+                aux_input: aux_input.to_vec(),
+            };
+            let mut blocks = KSteps::new(trace.linear_emulator()?, k, false);
+            trace.memory_layout = blocks.vm.memory_layout;
+            for block in blocks.by_ref() {
+                trace.num_steps += block?.steps.len();
+            }
+
+            let mut view = blocks.vm.finalize();
+            view.add_logs(&trace.harvard);
+            Ok((view, trace))
+        }
+        Err(e) => Err(e),
+        Ok(_) => unreachable!(),
+    }
+}
+
 /// Similar to `k_trace`, but uses HarvardEmulator and supports Intermediate Representation (IR) as input instead of an ELF file.
 pub fn k_trace_direct(basic_blocks: &Vec<BasicBlock>, k: usize) -> Result<(View, UniformTrace)> {
     let mut harvard = HarvardEmulator::from_basic_blocks(basic_blocks);
@@ -378,10 +569,9 @@ fn bb_step(vm: &mut impl Emulator) -> (Option<Block>, Result<()>) {
     match vm.fetch_block(vm.get_executor().cpu.pc.value) {
         Err(e) => return (None, Err(e)),
         Ok(basic_block_entry) => {
-            let at = (vm.get_executor().cpu.pc.value as usize - basic_block_entry.start as usize)
-                / WORD_SIZE;
+            let pc = vm.get_executor().cpu.pc.value;
 
-            for instruction in basic_block_entry.block.0[at..].iter() {
+            for instruction in basic_block_entry.instructions_from(pc) {
                 let pc = vm.get_executor().cpu.pc.value;
                 let timestamp = vm.get_executor().global_clock as u32;
 
@@ -490,6 +680,7 @@ pub fn bb_trace_direct(basic_blocks: &Vec<BasicBlock>) -> Result<(View, BBTrace)
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::memory::MemorySegmentImage;
     use crate::riscv::{BuiltinOpcode, Opcode, Register};
     use nexus_common::constants::ELF_TEXT_START;
     use serial_test::serial;
@@ -597,6 +788,94 @@ mod tests {
         assert!(step.memory_records.is_empty());
     }
 
+    #[test]
+    #[serial]
+    fn test_k1_streaming_trace_nexus_rt_binary() {
+        let elf_file = ElfFile::from_path("test/fib_10.elf").expect("Unable to load ELF file");
+        let (view, trace) = k_trace(elf_file.clone(), &[], &[], &[], 1).unwrap();
+        let (streaming_view, streaming_trace) =
+            k_trace_streaming(elf_file, &[], &[], &[], 1).unwrap();
+
+        let output = |view: &View| {
+            view.get_exit_code()
+                .iter()
+                .chain(view.get_public_output())
+                .map(|entry| (entry.address, entry.value))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(output(&streaming_view), output(&view));
+        assert_eq!(
+            streaming_view.get_initial_memory(),
+            view.get_initial_memory()
+        );
+        assert_eq!(streaming_trace.num_steps(), trace.get_num_steps());
+
+        // Blocks are generated anew on every pass.
+        for _ in 0..2 {
+            let mut num_blocks = 0;
+            for (block, expected) in streaming_trace.blocks().zip(&trace.blocks) {
+                assert_eq!(block.regs, expected.regs);
+                let (step, expected) = (&block.steps[0], &expected.steps[0]);
+                assert_eq!(step.timestamp, expected.timestamp);
+                assert_eq!(step.pc, expected.pc);
+                assert_eq!(step.next_pc, expected.next_pc);
+                assert_eq!(step.raw_instruction, expected.raw_instruction);
+                assert_eq!(step.result, expected.result);
+                assert_eq!(step.memory_records, expected.memory_records);
+                num_blocks += 1;
+            }
+            assert_eq!(num_blocks, trace.blocks.len());
+        }
+    }
+
+    #[test]
+    fn test_k1_streaming_trace_with_aux_input() {
+        // li a7, 0x404; ecall; mv s0, a0; ecall; add a0, a0, s0;
+        // lw t0, 0x84(x0); wou a0, 0(t0); li a7, 0x201; ecall
+        let instructions = vec![
+            0x40400893, 0x00000073, 0x00050413, 0x00000073, 0x00850533, 0x08402283, 0x00a2805b,
+            0x20100893, 0x00000073,
+        ];
+        let elf_file = ElfFile::new(
+            instructions,
+            ELF_TEXT_START,
+            ELF_TEXT_START,
+            MemorySegmentImage::default(),
+            MemorySegmentImage::default(),
+            Vec::new(),
+        );
+        let aux_input = [3, 4];
+
+        let (view, trace) =
+            k_trace_with_aux_input(elf_file.clone(), &[], &[], &[], &aux_input, 1).unwrap();
+        let (streaming_view, streaming_trace) =
+            k_trace_streaming_with_aux_input(elf_file, &[], &[], &[], &aux_input, 1).unwrap();
+        let exit_code = |view: &View| {
+            view.get_exit_code()
+                .iter()
+                .map(|entry| (entry.address, entry.value))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(exit_code(&streaming_view), exit_code(&view));
+        assert_eq!(streaming_trace.num_steps(), trace.get_num_steps());
+
+        // The tape is refilled for every pass over the blocks.
+        for _ in 0..2 {
+            let steps: Vec<Step> = streaming_trace
+                .blocks()
+                .flat_map(|block| block.steps)
+                .collect();
+            let expected: Vec<&Step> = trace.blocks.iter().flat_map(|block| &block.steps).collect();
+            assert_eq!(steps.len(), expected.len());
+            for (step, expected) in steps.iter().zip(expected) {
+                assert_eq!(step.pc, expected.pc);
+                assert_eq!(step.result, expected.result);
+                assert_eq!(step.memory_records, expected.memory_records);
+            }
+            assert_eq!(steps[4].result, Some(7));
+        }
+    }
+
     #[test]
     #[serial]
     fn test_bb_trace_nexus_rt_binary() {
@@ -0,0 +1,287 @@
+//! Export of execution traces for offline analysis.
+//!
+//! Every step of a trace is written as a [`StepRecord`], annotated with the function it belongs to, the register it
+//! writes and its memory accesses sorted by address. Records are written either as JSON Lines, one object per line,
+//! or in a compact binary format where each record is encoded with `postcard` and prefixed by its length as a
+//! little-endian `u32`.
+//!
+//! ```rust
+//! use nexus_vm::elf::ElfFile;
+//! use nexus_vm::trace::{
+//!     export::{TraceExporter, TraceFormat},
+//!     k_trace,
+//! };
+//!
+//! let elf = ElfFile::from_path("test/fib_10.elf").unwrap();
+//! let symbols = elf.symbols.clone();
+//! let (_view, trace) = k_trace(elf, &[], &[], &[], 1).unwrap();
+//!
+//! let mut exporter = TraceExporter::new(Vec::new(), TraceFormat::JsonLines, &symbols);
+//! exporter.export(&trace).unwrap();
+//! ```
+
+use std::io::{self, Read, Write};
+
+use nexus_common::cpu::Registers;
+use serde::{Deserialize, Serialize};
+
+use super::{Block, Step, TraceSource};
+use crate::{
+    cpu::RegisterFile,
+    elf::SymbolTable,
+    memory::MemoryRecord,
+    riscv::{InstructionType, Register},
+    system::SyscallCode,
+};
+
+/// The encoding of exported records.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraceFormat {
+    /// One JSON object per line.
+    JsonLines,
+    /// Length-prefixed `postcard` encoding.
+    Binary,
+}
+
+/// A register written by a step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RegisterWrite {
+    /// Index of the register.
+    pub register: u8,
+    /// Value written.
+    pub value: u32,
+}
+
+/// A memory access of a step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemoryAccess {
+    pub address: u32,
+    /// Size of the access in bytes.
+    pub size: u8,
+    /// Value loaded or stored.
+    pub value: u32,
+    /// Value overwritten by a store.
+    pub prev_value: Option<u32>,
+}
+
+/// An exported program step.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StepRecord {
+    pub timestamp: u32,
+    pub pc: u32,
+    pub next_pc: u32,
+    pub raw_instruction: u32,
+    /// Disassembled instruction.
+    pub instruction: String,
+    /// Function containing `pc`, if the symbol table has one.
+    pub symbol: Option<String>,
+    pub register_write: Option<RegisterWrite>,
+    pub loads: Vec<MemoryAccess>,
+    pub stores: Vec<MemoryAccess>,
+}
+
+impl StepRecord {
+    /// Annotates `step`, where `regs` holds register values prior to it.
+    pub fn new(step: &Step, regs: &RegisterFile, symbols: &SymbolTable) -> Self {
+        let mut loads = Vec::new();
+        let mut stores = Vec::new();
+        for record in &step.memory_records {
+            let access = MemoryAccess {
+                address: record.get_address(),
+                size: record.get_size() as u8,
+                value: record.get_value(),
+                prev_value: record.get_prev_value(),
+            };
+            match record {
+                MemoryRecord::LoadRecord(..) => loads.push(access),
+                MemoryRecord::StoreRecord(..) => stores.push(access),
+            }
+        }
+        loads.sort_by_key(|access| access.address);
+        stores.sort_by_key(|access| access.address);
+
+        Self {
+            timestamp: step.timestamp,
+            pc: step.pc,
+            next_pc: step.next_pc,
+            raw_instruction: step.raw_instruction,
+            instruction: step.instruction.to_string(),
+            symbol: symbols.lookup(step.pc).map(|symbol| symbol.name.clone()),
+            register_write: register_write(step, regs).map(|(register, value)| RegisterWrite {
+                register: register as u8,
+                value,
+            }),
+            loads,
+            stores,
+        }
+    }
+}
+
+/// Returns the register written by `step`, which is always the destination register except for syscalls.
+fn register_write(step: &Step, regs: &RegisterFile) -> Option<(Register, u32)> {
+    let value = step.result?;
+    let register = match step.instruction.ins_type {
+        // Branches report the next program counter.
+        InstructionType::BType => return None,
+        _ if step.instruction.is_system_instruction() => match regs[Register::X17] {
+            // The exit code isn't written back.
+            code if code == SyscallCode::Exit as u32 => return None,
+            code if code == SyscallCode::OverwriteStackPointer as u32 => Register::X2,
+            _ => Register::X10,
+        },
+        _ => step.instruction.op_a,
+    };
+
+    (register != Register::X0).then_some((register, value))
+}
+
+/// Writes records of traces to `writer`.
+pub struct TraceExporter<'a, W: Write> {
+    writer: W,
+    format: TraceFormat,
+    symbols: &'a SymbolTable,
+}
+
+impl<'a, W: Write> TraceExporter<'a, W> {
+    pub fn new(writer: W, format: TraceFormat, symbols: &'a SymbolTable) -> Self {
+        Self {
+            writer,
+            format,
+            symbols,
+        }
+    }
+
+    /// Writes all steps of a trace, in order.
+    pub fn export(&mut self, trace: &impl TraceSource) -> io::Result<()> {
+        for block in trace.stream_blocks() {
+            self.export_block(&block)?;
+        }
+        self.writer.flush()
+    }
+
+    /// Writes the steps of a block, registers are tracked from the starting register file of the block.
+    pub fn export_block(&mut self, block: &Block) -> io::Result<()> {
+        let mut regs = block.regs;
+        for step in &block.steps {
+            let record = StepRecord::new(step, &regs, self.symbols);
+            if let Some(write) = &record.register_write {
+                regs.write(Register::from(write.register), write.value);
+            }
+            self.write_record(&record)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn write_record(&mut self, record: &StepRecord) -> io::Result<()> {
+        match self.format {
+            TraceFormat::JsonLines => {
+                serde_json::to_writer(&mut self.writer, record)?;
+                self.writer.write_all(b"\n")
+            }
+            TraceFormat::Binary => {
+                let bytes = postcard::to_stdvec(record).map_err(io::Error::other)?;
+                self.writer.write_all(&(bytes.len() as u32).to_le_bytes())?;
+                self.writer.write_all(&bytes)
+            }
+        }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// Reads records written in the binary format.
+pub fn read_binary_records(mut reader: impl Read) -> impl Iterator<Item = io::Result<StepRecord>> {
+    std::iter::from_fn(move || {
+        let mut len = [0u8; 4];
+        match reader.read_exact(&mut len) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return None,
+            Err(e) => return Some(Err(e)),
+        }
+
+        let mut bytes = vec![0u8; u32::from_le_bytes(len) as usize];
+        Some(
+            reader
+                .read_exact(&mut bytes)
+                .and_then(|()| postcard::from_bytes(&bytes).map_err(io::Error::other)),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        elf::ElfFile,
+        riscv::{BasicBlock, BuiltinOpcode, Instruction, Opcode},
+        trace::{k_trace, k_trace_direct, Trace},
+    };
+    use serial_test::serial;
+
+    #[test]
+    fn test_export_register_writes() {
+        let basic_blocks = vec![BasicBlock::new(vec![
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::ADDI), 1, 0, 5),
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::ADDI), 0, 1, 1),
+            Instruction::new_ir(Opcode::from(BuiltinOpcode::ADD), 2, 1, 1),
+        ])];
+        let (_view, trace) = k_trace_direct(&basic_blocks, 1).unwrap();
+
+        let symbols = SymbolTable::default();
+        let mut exporter = TraceExporter::new(Vec::new(), TraceFormat::JsonLines, &symbols);
+        exporter.export(&trace).unwrap();
+
+        let output = String::from_utf8(exporter.into_inner()).unwrap();
+        let records: Vec<StepRecord> = output
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+        assert_eq!(records.len(), 3);
+        assert_eq!(
+            records[0].register_write,
+            Some(RegisterWrite {
+                register: 1,
+                value: 5
+            })
+        );
+        assert_eq!(records[1].register_write, None);
+        assert_eq!(
+            records[2].register_write,
+            Some(RegisterWrite {
+                register: 2,
+                value: 10
+            })
+        );
+        assert!(records.iter().all(|record| record.symbol.is_none()));
+    }
+
+    #[test]
+    #[serial]
+    fn test_export_binary_round_trip() {
+        let elf = ElfFile::from_path("test/fib_10.elf").unwrap();
+        let symbols = elf.symbols.clone();
+        let entry = elf.entry;
+        let (_view, trace) = k_trace(elf, &[], &[], &[], 1).unwrap();
+
+        let mut exporter = TraceExporter::new(Vec::new(), TraceFormat::Binary, &symbols);
+        exporter.export(&trace).unwrap();
+        let output = exporter.into_inner();
+
+        let records: Vec<StepRecord> = read_binary_records(output.as_slice())
+            .collect::<io::Result<_>>()
+            .unwrap();
+        assert_eq!(records.len(), trace.get_num_steps());
+
+        assert_eq!(records[0].pc, entry);
+        assert_eq!(records[0].symbol.as_deref(), Some("_start"));
+        assert!(records
+            .iter()
+            .any(|record| record.symbol.as_deref() == Some("main")));
+        assert!(records.iter().any(|record| !record.loads.is_empty()));
+        assert!(records.iter().any(|record| !record.stores.is_empty()));
+    }
+}
@@ -0,0 +1,369 @@
+//! Function-level step profiler for guest programs.
+//!
+//! Unlike the cycle tracker filled by `#[nexus_rt::profile]` markers, the profiler needs no changes to the guest:
+//! it replays the steps of a trace and rebuilds the call stack from the RISC-V calling convention, where `jal` and
+//! `jalr` writing a link register (`ra` or `t0`) are calls and `jalr` jumping to a link register is a return.
+//! Every step is attributed to the stack of functions it executes in, using the ELF symbol table.
+//!
+//! Profiles are written as folded stacks, as consumed by `inferno` or `flamegraph.pl`, or as an uncompressed pprof
+//! protobuf. Function names are kept mangled, they can be demangled with tools such as `rustfilt`.
+//!
+//! ```rust
+//! use nexus_vm::elf::ElfFile;
+//! use nexus_vm::trace::{k_trace, profile::Profile};
+//!
+//! let elf = ElfFile::from_path("test/fib_10.elf").unwrap();
+//! let symbols = elf.symbols.clone();
+//! let (_view, trace) = k_trace(elf, &[], &[], &[], 1).unwrap();
+//!
+//! let profile = Profile::from_trace(&trace, &symbols);
+//! let mut folded = Vec::new();
+//! profile.write_folded(&mut folded).unwrap();
+//! ```
+
+use std::{
+    collections::HashMap,
+    io::{self, Write},
+};
+
+use super::{Step, TraceSource};
+use crate::{
+    elf::SymbolTable,
+    riscv::{BuiltinOpcode, Register},
+};
+
+/// Name of the frame for code without a symbol.
+const UNKNOWN_FUNCTION: &str = "[unknown]";
+
+/// Number of steps spent in every observed call stack.
+#[derive(Debug, Default, Clone)]
+pub struct Profile {
+    /// Interned function names.
+    functions: Vec<String>,
+    /// Steps per call stack, given as indices into `functions` from the outermost frame.
+    stacks: HashMap<Vec<usize>, u64>,
+}
+
+impl Profile {
+    /// Attributes every step of `trace` to the call stack it executes in.
+    pub fn from_trace(trace: &impl TraceSource, symbols: &SymbolTable) -> Self {
+        let mut profiler = Profiler::new(symbols);
+        for block in trace.stream_blocks() {
+            for step in &block.steps {
+                profiler.record(step);
+            }
+        }
+
+        profiler.profile
+    }
+
+    pub fn total_steps(&self) -> u64 {
+        self.stacks.values().sum()
+    }
+
+    /// Returns call stacks as function names from the outermost frame, along with their step counts, sorted.
+    pub fn stacks(&self) -> Vec<(Vec<&str>, u64)> {
+        let mut stacks: Vec<_> = self
+            .stacks
+            .iter()
+            .map(|(stack, &count)| {
+                let names = stack.iter().map(|&i| self.functions[i].as_str()).collect();
+                (names, count)
+            })
+            .collect();
+        stacks.sort();
+        stacks
+    }
+
+    /// Writes one `outer;inner count` line per call stack.
+    pub fn write_folded(&self, mut writer: impl Write) -> io::Result<()> {
+        for (stack, count) in self.stacks() {
+            writeln!(writer, "{} {count}", stack.join(";"))?;
+        }
+        writer.flush()
+    }
+
+    /// Writes an uncompressed pprof `Profile` message, with a location and a function per symbol.
+    pub fn write_pprof(&self, mut writer: impl Write) -> io::Result<()> {
+        // Indices into the string table, function names follow the fixed strings.
+        const STEPS: u64 = 1;
+        const COUNT: u64 = 2;
+        const FIRST_NAME: u64 = 3;
+
+        let mut value_type = Vec::new();
+        pprof::put_uint(&mut value_type, 1, STEPS);
+        pprof::put_uint(&mut value_type, 2, COUNT);
+
+        let mut profile = Vec::new();
+        pprof::put_bytes(&mut profile, 1, &value_type);
+
+        let mut stacks: Vec<_> = self.stacks.iter().collect();
+        stacks.sort();
+        for (stack, &count) in stacks {
+            // Locations are listed from the innermost frame, ids start at one.
+            let location_ids: Vec<u64> = stack.iter().rev().map(|&i| i as u64 + 1).collect();
+
+            let mut sample = Vec::new();
+            pprof::put_packed(&mut sample, 1, &location_ids);
+            pprof::put_packed(&mut sample, 2, &[count]);
+            pprof::put_bytes(&mut profile, 2, &sample);
+        }
+
+        for i in 0..self.functions.len() as u64 {
+            let mut line = Vec::new();
+            pprof::put_uint(&mut line, 1, i + 1);
+
+            let mut location = Vec::new();
+            pprof::put_uint(&mut location, 1, i + 1);
+            pprof::put_bytes(&mut location, 4, &line);
+            pprof::put_bytes(&mut profile, 4, &location);
+        }
+
+        for i in 0..self.functions.len() as u64 {
+            let mut function = Vec::new();
+            pprof::put_uint(&mut function, 1, i + 1);
+            pprof::put_uint(&mut function, 2, FIRST_NAME + i);
+            pprof::put_uint(&mut function, 3, FIRST_NAME + i);
+            pprof::put_bytes(&mut profile, 5, &function);
+        }
+
+        for string in ["", "steps", "count"]
+            .into_iter()
+            .chain(self.functions.iter().map(String::as_str))
+        {
+            pprof::put_bytes(&mut profile, 6, string.as_bytes());
+        }
+
+        pprof::put_bytes(&mut profile, 11, &value_type);
+        pprof::put_uint(&mut profile, 12, 1);
+
+        writer.write_all(&profile)?;
+        writer.flush()
+    }
+}
+
+/// Rebuilds the call stack while steps are replayed.
+struct Profiler<'a> {
+    symbols: &'a SymbolTable,
+    profile: Profile,
+    function_ids: HashMap<String, usize>,
+    stack: Vec<usize>,
+}
+
+impl<'a> Profiler<'a> {
+    fn new(symbols: &'a SymbolTable) -> Self {
+        Self {
+            symbols,
+            profile: Profile::default(),
+            function_ids: HashMap::new(),
+            stack: Vec::new(),
+        }
+    }
+
+    fn function_at(&mut self, pc: u32) -> usize {
+        let name = self
+            .symbols
+            .lookup(pc)
+            .map_or(UNKNOWN_FUNCTION, |symbol| symbol.name.as_str());
+        if let Some(&id) = self.function_ids.get(name) {
+            return id;
+        }
+
+        let id = self.profile.functions.len();
+        self.profile.functions.push(name.to_string());
+        self.function_ids.insert(name.to_string(), id);
+        id
+    }
+
+    fn record(&mut self, step: &Step) {
+        if self.stack.is_empty() {
+            let function = self.function_at(step.pc);
+            self.stack.push(function);
+        }
+
+        match self.profile.stacks.get_mut(self.stack.as_slice()) {
+            Some(count) => *count += 1,
+            None => {
+                self.profile.stacks.insert(self.stack.clone(), 1);
+            }
+        }
+
+        let opcode = step.instruction.opcode.builtin();
+        if !matches!(opcode, Some(BuiltinOpcode::JAL | BuiltinOpcode::JALR)) {
+            return;
+        }
+
+        let rd = step.instruction.op_a;
+        let rs1 = step.instruction.op_b;
+        let rd_is_link = is_link(rd);
+        let rs1_is_link = opcode == Some(BuiltinOpcode::JALR) && is_link(rs1);
+        let target = self.function_at(step.next_pc);
+
+        match (rd_is_link, rs1_is_link) {
+            // A return followed by a call, e.g. through `t0`.
+            (true, true) if rd != rs1 => {
+                self.stack.pop();
+                self.stack.push(target);
+            }
+            (true, _) => self.stack.push(target),
+            (false, true) => {
+                if self.stack.len() > 1 {
+                    self.stack.pop();
+                }
+                // Resynchronize after returning from a function that was entered through a tail call.
+                *self.stack.last_mut().unwrap() = target;
+            }
+            // A plain jump to the start of another function is a tail call.
+            (false, false) => {
+                let is_entry = self
+                    .symbols
+                    .lookup(step.next_pc)
+                    .is_some_and(|symbol| symbol.address == step.next_pc);
+                if is_entry {
+                    *self.stack.last_mut().unwrap() = target;
+                }
+            }
+        }
+    }
+}
+
+fn is_link(register: Register) -> bool {
+    matches!(register, Register::X1 | Register::X5)
+}
+
+/// Minimal protobuf encoding for pprof messages.
+mod pprof {
+    const VARINT: u64 = 0;
+    const LENGTH_DELIMITED: u64 = 2;
+
+    fn put_varint(buf: &mut Vec<u8>, mut value: u64) {
+        while value >= 0x80 {
+            buf.push(value as u8 | 0x80);
+            value >>= 7;
+        }
+        buf.push(value as u8);
+    }
+
+    pub(super) fn put_uint(buf: &mut Vec<u8>, field: u64, value: u64) {
+        put_varint(buf, (field << 3) | VARINT);
+        put_varint(buf, value);
+    }
+
+    pub(super) fn put_bytes(buf: &mut Vec<u8>, field: u64, bytes: &[u8]) {
+        put_varint(buf, (field << 3) | LENGTH_DELIMITED);
+        put_varint(buf, bytes.len() as u64);
+        buf.extend_from_slice(bytes);
+    }
+
+    pub(super) fn put_packed(buf: &mut Vec<u8>, field: u64, values: &[u64]) {
+        let mut packed = Vec::new();
+        for &value in values {
+            put_varint(&mut packed, value);
+        }
+        put_bytes(buf, field, &packed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        elf::{ElfFile, Symbol},
+        riscv::{Instruction, Opcode},
+        trace::{k_trace, Block, Trace, UniformTrace},
+    };
+    use serial_test::serial;
+
+    fn step(pc: u32, next_pc: u32, instruction: Instruction) -> Step {
+        Step {
+            pc,
+            next_pc,
+            instruction,
+            ..Default::default()
+        }
+    }
+
+    fn nop(pc: u32) -> Step {
+        step(pc, pc + 4, Instruction::nop())
+    }
+
+    fn symbols() -> SymbolTable {
+        let mut symbols = SymbolTable::default();
+        for (name, address) in [("main", 0x100), ("f", 0x200), ("g", 0x300)] {
+            symbols.insert(Symbol {
+                name: name.to_string(),
+                address,
+                size: 0x100,
+            });
+        }
+        symbols
+    }
+
+    #[test]
+    fn test_call_stacks() {
+        let jal = |rd| Instruction::new_ir(Opcode::from(BuiltinOpcode::JAL), rd, 0, 0);
+        let jalr = Opcode::from(BuiltinOpcode::JALR);
+        let steps = vec![
+            nop(0x100),
+            // main calls f
+            step(0x104, 0x200, jal(1)),
+            nop(0x200),
+            // f tail-calls g
+            step(0x204, 0x300, jal(0)),
+            nop(0x300),
+            nop(0x304),
+            // g returns to main
+            step(0x308, 0x108, Instruction::new_ir(jalr, 0, 1, 0)),
+            nop(0x108),
+        ];
+        let trace = UniformTrace {
+            k: steps.len(),
+            blocks: vec![Block {
+                steps,
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let profile = Profile::from_trace(&trace, &symbols());
+        assert_eq!(profile.total_steps(), 8);
+        assert_eq!(
+            profile.stacks(),
+            vec![
+                (vec!["main"], 3),
+                (vec!["main", "f"], 2),
+                (vec!["main", "g"], 3),
+            ]
+        );
+
+        let mut folded = Vec::new();
+        profile.write_folded(&mut folded).unwrap();
+        assert_eq!(
+            String::from_utf8(folded).unwrap(),
+            "main 3\nmain;f 2\nmain;g 3\n"
+        );
+
+        let mut pprof = Vec::new();
+        profile.write_pprof(&mut pprof).unwrap();
+        // The sample type comes first: field 1, of length 4, with type and unit indices.
+        assert_eq!(pprof[..6], [0x0a, 0x04, 0x08, 0x01, 0x10, 0x02]);
+    }
+
+    #[test]
+    #[serial]
+    fn test_profile_elf() {
+        let elf = ElfFile::from_path("test/fib_10.elf").unwrap();
+        let symbols = elf.symbols.clone();
+        let (_view, trace) = k_trace(elf, &[], &[], &[], 1).unwrap();
+
+        let profile = Profile::from_trace(&trace, &symbols);
+        assert_eq!(profile.total_steps() as usize, trace.get_num_steps());
+
+        // `_start` calls `_start_rust`, which calls `main`.
+        let stacks = profile.stacks();
+        assert!(stacks.iter().all(|(stack, _)| stack[0] == "_start"));
+        assert!(stacks
+            .iter()
+            .any(|(stack, _)| stack[..] == ["_start", "_start_rust", "main"]));
+    }
+}